@@ -160,7 +160,7 @@ async fn create_response_sample() -> Result<
         network.http_client.sync_account_state(account).await?;
         let objects: Vec<SuiObjectInfo> = network
             .http_client
-            .get_objects_owned_by_address(account)
+            .get_objects_owned_by_address(account, None)
             .await?;
         owned_objects.insert(account, objects);
     }
@@ -2,11 +2,17 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::ValidatorInfo;
-use anyhow::Context;
+use anyhow::{anyhow, bail, Context};
 use base64ct::Encoding;
 use move_binary_format::CompiledModule;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use std::{fs, path::Path};
+use std::{
+    fs,
+    io::Read,
+    path::Path,
+    sync::mpsc::{self, RecvTimeoutError},
+    time::Duration,
+};
 use sui_types::{
     base_types::TxContext,
     committee::{Committee, EpochId},
@@ -65,6 +71,51 @@ impl Genesis {
         Ok(bcs::from_bytes(&bytes)?)
     }
 
+    /// Like [`Genesis::load`], but fails rather than reading more than `max_size` bytes, or
+    /// waiting longer than `timeout` for the file to be read and parsed.
+    pub fn load_with_limits<P: AsRef<Path>>(
+        path: P,
+        max_size: u64,
+        timeout: Duration,
+    ) -> Result<Self, anyhow::Error> {
+        let path = path.as_ref().to_owned();
+        trace!("Reading Genesis from {}", path.display());
+        let (sender, receiver) = mpsc::channel();
+        let loader_path = path.clone();
+        // The loader can't be interrupted, so it is left to finish on its own after a timeout.
+        std::thread::Builder::new()
+            .name("genesis-loader".to_string())
+            .spawn(move || {
+                let _ = sender.send(Self::load_bounded(&loader_path, max_size));
+            })?;
+        match receiver.recv_timeout(timeout) {
+            Ok(genesis) => genesis,
+            Err(RecvTimeoutError::Timeout) => Err(anyhow!(
+                "Loading Genesis from {} took longer than {timeout:?}",
+                path.display()
+            )),
+            Err(RecvTimeoutError::Disconnected) => {
+                Err(anyhow!("Loading Genesis from {} failed", path.display()))
+            }
+        }
+    }
+
+    fn load_bounded(path: &Path, max_size: u64) -> Result<Self, anyhow::Error> {
+        let file = fs::File::open(path)
+            .with_context(|| format!("Unable to load Genesis from {}", path.display()))?;
+        let mut bytes = Vec::new();
+        file.take(max_size.saturating_add(1))
+            .read_to_end(&mut bytes)
+            .with_context(|| format!("Unable to load Genesis from {}", path.display()))?;
+        if bytes.len() as u64 > max_size {
+            bail!(
+                "Genesis in {} is larger than the {max_size} bytes allowed",
+                path.display()
+            );
+        }
+        bcs::from_bytes(&bytes).with_context(|| format!("Invalid Genesis in {}", path.display()))
+    }
+
     pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), anyhow::Error> {
         let path = path.as_ref();
         trace!("Writing Genesis to {}", path.display());
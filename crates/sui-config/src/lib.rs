@@ -17,7 +17,15 @@ pub mod node;
 mod swarm;
 pub mod utils;
 
-pub use node::{ConsensusConfig, NodeConfig, ValidatorInfo};
+pub use node::{
+    CatchUpBurstConfig, CertificatePushConfig, ColumnFamilyGroupConfig, ConsensusConfig,
+    EventIndex, EventStoreConfig, FollowMode, GrpcConnectionLimitsConfig, KafkaDelivery,
+    KafkaEventConfig, KafkaEventKey, MetricsAuthConfig, NetworkTimeoutConfig, NodeConfig,
+    NodeSyncConfig, ObjectHistoryConfig, OtlpMetricsConfig, PeerConnectMode, PeerSelectionConfig,
+    PostProcessingConfig, ReexecutionVerifierConfig, RpcAuthConfig, RpcBackpressureConfig,
+    RpcListenAddress, RpcResponseCacheConfig, StateSnapshotConfig, StoreColumnFamiliesConfig,
+    SubsystemRestartConfig, SyncPeers, TcpKeepaliveConfig, TrustedCheckpoint, ValidatorInfo,
+};
 pub use swarm::NetworkConfig;
 
 const SUI_DIR: &str = ".sui";
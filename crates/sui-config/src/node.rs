@@ -3,19 +3,23 @@
 
 use crate::genesis;
 use crate::Config;
-use anyhow::Result;
+use anyhow::{bail, Result};
 use debug_ignore::DebugIgnore;
 use multiaddr::Multiaddr;
 use narwhal_config::Parameters as ConsensusParameters;
 use narwhal_config::SharedCommittee as ConsensusCommittee;
 use narwhal_crypto::ed25519::Ed25519PublicKey;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 use sui_types::base_types::SuiAddress;
 use sui_types::committee::StakeUnit;
 use sui_types::crypto::{KeyPair, PublicKeyBytes};
+use sui_types::messages_checkpoint::{CheckpointDigest, CheckpointSequenceNumber};
+use tracing::warn;
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
@@ -25,22 +29,292 @@ pub struct NodeConfig {
     pub db_path: PathBuf,
     #[serde(default = "default_grpc_address")]
     pub network_address: Multiaddr,
+    /// Caps on the inbound connections the gRPC server on `network-address` keeps open.
+    /// Unlimited when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grpc_connection_limits: Option<GrpcConnectionLimitsConfig>,
     #[serde(default = "default_metrics_address")]
     pub metrics_address: SocketAddr,
+    /// Credentials required to scrape `/metrics` on `metrics-address`. The admin routes and
+    /// `/ready` on it aren't covered. Open when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metrics_auth: Option<MetricsAuthConfig>,
+    /// Also push the metrics to an OpenTelemetry collector. They are still served on
+    /// `metrics-address` for scraping.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub otlp_metrics: Option<OtlpMetricsConfig>,
+    /// Serve the admin routes on `metrics-address` to non-loopback peers as well.
+    #[serde(default)]
+    pub admin_allow_remote: bool,
+    /// A TCP socket address, or the path of a Unix domain socket for clients on the same host.
     #[serde(default = "default_json_rpc_address")]
-    pub json_rpc_address: SocketAddr,
+    pub json_rpc_address: RpcListenAddress,
+    /// Permissions of the socket file created when `json-rpc-address` is a Unix socket path.
+    #[serde(default = "default_json_rpc_socket_mode")]
+    pub json_rpc_socket_mode: u32,
+    /// TCP keepalive for the connections accepted on a TCP `json-rpc-address`. The OS settings
+    /// apply when unset, which on Linux only probes a connection after two idle hours.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub json_rpc_tcp_keepalive: Option<TcpKeepaliveConfig>,
+    /// Number of JSON-RPC servers serving a TCP `json-rpc-address`, to use more cores than a
+    /// single server does. They all listen on it with SO_REUSEPORT, so that the kernel spreads
+    /// the connections between them, and share the node state, the response cache and the
+    /// metrics. A Unix socket is served by one server only. Defaults to 1.
+    #[serde(default = "default_json_rpc_server_instances")]
+    pub json_rpc_server_instances: usize,
     #[serde(default = "default_websocket_address")]
     pub websocket_address: Option<SocketAddr>,
+    /// Interval in milliseconds at which the websocket server pings its clients, keeping
+    /// subscriptions alive behind proxies that drop idle connections. Disabled when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub websocket_ping_interval_ms: Option<u64>,
+    /// Websocket connections the client sent nothing on for this many milliseconds, not even a
+    /// pong, are closed and counted by `rpc_ws_dropped_on_timeout`. Should be longer than
+    /// `websocket-ping-interval-ms`, so that clients answering the pings stay connected.
+    /// Disabled when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub websocket_idle_timeout_ms: Option<u64>,
+    /// JSON-RPC calls taking longer than this many milliseconds are logged at WARN, with their
+    /// parameters truncated. Disabled when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rpc_slow_query_threshold_ms: Option<u64>,
+    /// JSON-RPC calls taking longer than this many milliseconds are answered with a timeout
+    /// error naming the method, and counted by `rpc_timeouts_by_route`. Unlimited when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rpc_default_timeout_ms: Option<u64>,
+    /// `rpc-default-timeout-ms` by method, e.g. `sui_getObjectTypeStats: 30000`, for the slow
+    /// scans that need longer than point lookups, or the other way around.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub rpc_method_timeouts_ms: BTreeMap<String, u64>,
+    /// Tell JSON-RPC clients how loaded the node is in the headers of every HTTP response, so
+    /// that they back off before it is overwhelmed. Disabled when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rpc_backpressure: Option<RpcBackpressureConfig>,
+    /// Only serve JSON-RPC requests over TCP that carry one of the API keys or a JWT this
+    /// accepts, and don't start the websocket server, which can't authenticate them. Open to
+    /// all when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rpc_auth: Option<RpcAuthConfig>,
+    /// Keep the JSON-RPC read API responses that can't change, like those for immutable
+    /// objects, past object versions and executed transactions, in memory to answer repeated
+    /// requests. Disabled when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rpc_response_cache: Option<RpcResponseCacheConfig>,
+    /// Most items a JSON-RPC list endpoint returns per call. Larger requested counts and
+    /// ranges are clamped to it, and the length of the response is the effective page size.
+    /// Endpoints without a count or range, like the owned objects of an address, are not
+    /// paginated and still return everything.
+    #[serde(default = "default_max_page_size")]
+    pub max_page_size: usize,
+    /// Number of items a JSON-RPC list endpoint returns when the client doesn't pass a count.
+    /// Defaults to `max-page-size`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_page_size: Option<usize>,
+    /// `default-page-size` by method, e.g. `sui_getEventsBySender: 100`, for the endpoints
+    /// whose clients want smaller or larger pages than the others. Still capped at
+    /// `max-page-size`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub method_page_sizes: BTreeMap<String, usize>,
+    /// Largest object in bytes the JSON-RPC read API returns as JSON. Larger ones are refused
+    /// with an error pointing to the BCS API, so that a single huge Move object can't make the
+    /// server build an enormous response. Defaults to 4 MiB.
+    #[serde(default = "default_rpc_max_object_size")]
+    pub rpc_max_object_size: usize,
+    /// Most pairs of transaction and effects digests `verifyEffectsBatch` checks in a call.
+    /// Larger batches are refused. Defaults to 1000.
+    #[serde(default = "default_rpc_max_verification_batch")]
+    pub rpc_max_verification_batch: usize,
+    /// Answer JSON-RPC reads as of this checkpoint, from the past versions of objects, while
+    /// the node keeps syncing past it. Transactions of later checkpoints read as not found,
+    /// the objects of an owner and balances are those as of the checkpoint, with no coin
+    /// locked, and the total and the recent transactions are refused. Needs node sync to
+    /// verify against checkpoints, from before the pinned one, and can't be set with
+    /// `object-history-config`, which prunes the past versions read. Reads are of the latest
+    /// state when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pin_reads_at_checkpoint: Option<CheckpointSequenceNumber>,
+    /// Only start the JSON-RPC and websocket servers once node sync is within
+    /// `rpc-sync-distance` transactions of every validator the node follows, so that clients
+    /// aren't served stale reads right after boot. Ignored by nodes that don't sync from
+    /// validators.
+    #[serde(default)]
+    pub rpc_require_synced: bool,
+    /// Defaults to 1000.
+    #[serde(default = "default_rpc_sync_distance")]
+    pub rpc_sync_distance: u64,
+
+    /// Stream the effects of every executed transaction in order over gRPC on this address,
+    /// see `sui_network::api::EffectsFirehose`. Only served by full nodes. Disabled when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effects_firehose_address: Option<Multiaddr>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub consensus_config: Option<ConsensusConfig>,
 
+    /// Don't check at startup that a validator's key pair signs for the public key genesis
+    /// records for it at `network-address`. The check fails startup on a mismatch, rather
+    /// than the validator failing once it signs something live.
+    #[serde(default)]
+    pub skip_signing_self_test: bool,
+
+    /// Largest BCS-serialized transaction data a validator accepts, in bytes. Transactions and
+    /// certificates over it are rejected before their signatures are checked. Can only be
+    /// stricter than the `max-tx-size-bytes` of the protocol, larger values are capped to it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_transaction_size_bytes: Option<usize>,
+
+    /// A directory of the key pairs a validator signs with from each epoch on, in base64 files
+    /// named after the epoch, like `12.key`. A file is read when its epoch starts, and its
+    /// public key must be the one registered for this validator in the committee of that
+    /// epoch. The current key is kept for epochs without a file, and the key of the latest
+    /// epoch is used again after a restart.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checkpoint_keys_dir: Option<PathBuf>,
+
+    /// Number of recently read objects kept in memory in front of the store. Disabled when 0.
+    #[serde(default)]
+    pub object_cache_entries: usize,
+
+    /// Write the objects of the transactions executed within this many microseconds of each
+    /// other to the store together, in one atomic write. Saves write overhead under bursts of
+    /// transactions, at the cost of up to this much latency. Disabled when 0, and otherwise at
+    /// least 1000, since the window is timed in whole milliseconds.
+    #[serde(default)]
+    pub group_commit_window_us: u64,
+
+    /// Give the column families of the store written by execution and those holding the
+    /// history of executed transactions their own block caches and memtables. All column
+    /// families share the RocksDB defaults when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub store_column_families: Option<StoreColumnFamiliesConfig>,
+
+    /// Periodically snapshot the store and the checkpoints of a validator, to restore it from
+    /// after losing its disk. Ignored by full nodes, which have no checkpoints. Disabled when
+    /// unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_snapshot_config: Option<StateSnapshotConfig>,
+
+    /// Periodically delete past versions of objects beyond a number kept per object type. All
+    /// versions are kept when unset. Ignored with a secondary store, which the primary prunes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub object_history_config: Option<ObjectHistoryConfig>,
+
+    /// Periodically execute again a sample of the transactions committed since and compare
+    /// their effects with those stored, quarantining those that differ. Disabled when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reexecution_verifier: Option<ReexecutionVerifierConfig>,
+
+    /// Fraction of transactions, from 0 to 1, whose execution is traced phase by phase: loading
+    /// inputs, running the VM, writing effects and indexing. Each phase runs in a
+    /// `sampled_execution` span carrying the transaction digest and logs its duration. None
+    /// are traced by default.
+    #[serde(default)]
+    pub execution_trace_sample_rate: f64,
+
+    /// Read the input objects of a certificate up to this many at a time before checking and
+    /// executing it, which then reads them from the caches of the store. Speeds up
+    /// transactions with many input objects. The `load_inputs_latency` metric measures
+    /// reading the inputs with or without prefetching. Disabled when 0.
+    #[serde(default)]
+    pub input_prefetch_concurrency: usize,
+
     #[serde(default)]
     pub enable_event_processing: bool,
 
+    #[serde(default)]
+    pub event_store_config: EventStoreConfig,
+
+    #[serde(default)]
+    pub post_processing_config: PostProcessingConfig,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub certificate_push_config: Option<CertificatePushConfig>,
+
+    /// Publishes every event emitted by executed transactions to a Kafka topic. Independent of
+    /// `enable-event-processing`, so it can be used with or without the SQL event store.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kafka_event_config: Option<KafkaEventConfig>,
+
+    /// How the gossip and post processing subsystems are restarted when they crash.
+    #[serde(default)]
+    pub subsystem_restart_config: SubsystemRestartConfig,
+
     #[serde(default)]
     pub enable_gossip: bool,
 
+    /// A [`SyncPeers`] file restricting the genesis validators this node follows. All of them
+    /// are followed when unset. The file is read again when `/admin/sync-peers/reload` is
+    /// requested, so peers can be changed without a restart.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sync_peers_file: Option<PathBuf>,
+
+    /// How the validators to follow are picked, by gossip on validators and by node sync on
+    /// full nodes.
+    #[serde(default)]
+    pub peer_selection: PeerSelectionConfig,
+
+    /// Whether the connections to the validators followed are opened at startup or by the
+    /// first request sent to each. Defaults to `lazy`.
+    #[serde(default)]
+    pub peer_connect_mode: PeerConnectMode,
+
+    /// Caps the bytes per second downloaded from the validators followed, by gossip or node
+    /// sync, together. Following slows down rather than disconnects when it is reached, and
+    /// `gossip_bytes_received` counts what is downloaded. Unlimited when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gossip_max_bytes_per_sec: Option<u64>,
+
+    /// Number of other validators a validator gossips with at once, at most those of the
+    /// committee. Defaults to 4. Ignored by full nodes, which follow all validators.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gossip_degree: Option<usize>,
+
+    /// Timeouts of the connections to the validators followed.
+    #[serde(default)]
+    pub network_timeout_config: NetworkTimeoutConfig,
+
+    #[serde(default)]
+    pub node_sync_config: NodeSyncConfig,
+
+    /// A recent checkpoint the operator trusts, from which node sync verifying against
+    /// checkpoints starts. Checkpoints below it are only checked to be the ones it follows,
+    /// rather than certified by the committee of their epoch, and node sync refuses to
+    /// proceed if the validators' checkpoint at that sequence number has another digest.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trusted_checkpoint: Option<TrustedCheckpoint>,
+
+    /// How this node obtains its state. Defaults to executing transactions itself.
+    #[serde(default)]
+    pub follow_mode: FollowMode,
+
+    /// Number of transactions the node must have executed within `startup-sync-deadline-secs`
+    /// of starting. Otherwise `SuiNode::wait` fails, so that orchestration can replace a stuck
+    /// node. The node waits forever when either is unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub startup_sync_target: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub startup_sync_deadline_secs: Option<u64>,
+
+    /// Largest genesis file in bytes the node loads from `genesis-file-location`. Defaults to
+    /// 512 MiB.
+    #[serde(default = "default_max_genesis_size")]
+    pub max_genesis_size: u64,
+    /// Seconds reading and parsing the genesis file may take before startup fails. Defaults
+    /// to 60.
+    #[serde(default = "default_genesis_load_timeout_secs")]
+    pub genesis_load_timeout_secs: u64,
+
+    /// Seconds the node retries opening its store for while another process holds the lock of
+    /// the database, e.g. the previous process of a restart that is still shutting down.
+    /// Startup fails at once when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub db_lock_timeout_secs: Option<u64>,
+
+    /// Seconds the gRPC and JSON-RPC servers are given on shutdown to finish the requests they
+    /// are serving, after they stopped accepting connections. Defaults to 10.
+    #[serde(default = "default_shutdown_drain_timeout_secs")]
+    pub shutdown_drain_timeout_secs: u64,
+
     pub genesis: Genesis,
 }
 
@@ -58,9 +332,17 @@ fn default_metrics_address() -> SocketAddr {
     SocketAddr::new(IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0)), 9184)
 }
 
-pub fn default_json_rpc_address() -> SocketAddr {
+pub fn default_json_rpc_address() -> RpcListenAddress {
     use std::net::{IpAddr, Ipv4Addr};
-    SocketAddr::new(IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0)), 9000)
+    SocketAddr::new(IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0)), 9000).into()
+}
+
+pub fn default_json_rpc_socket_mode() -> u32 {
+    0o660
+}
+
+pub fn default_json_rpc_server_instances() -> usize {
+    1
 }
 
 pub fn default_websocket_address() -> Option<SocketAddr> {
@@ -68,8 +350,64 @@ pub fn default_websocket_address() -> Option<SocketAddr> {
     Some(SocketAddr::new(IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0)), 9001))
 }
 
+pub fn default_max_page_size() -> usize {
+    1000
+}
+
+pub fn default_rpc_max_object_size() -> usize {
+    4 << 20
+}
+
+pub fn default_rpc_max_verification_batch() -> usize {
+    1000
+}
+
+pub fn default_rpc_sync_distance() -> u64 {
+    1000
+}
+
+pub fn default_max_genesis_size() -> u64 {
+    512 << 20
+}
+
+pub fn default_genesis_load_timeout_secs() -> u64 {
+    60
+}
+
+pub fn default_gossip_degree() -> usize {
+    4
+}
+
+pub fn default_shutdown_drain_timeout_secs() -> u64 {
+    10
+}
+
 impl Config for NodeConfig {}
 
+/// Where a JSON-RPC server listens. Written as `127.0.0.1:9000` for TCP, anything that
+/// doesn't parse as a socket address is taken as a Unix socket path.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum RpcListenAddress {
+    Tcp(SocketAddr),
+    Unix(PathBuf),
+}
+
+impl From<SocketAddr> for RpcListenAddress {
+    fn from(address: SocketAddr) -> Self {
+        Self::Tcp(address)
+    }
+}
+
+impl std::fmt::Display for RpcListenAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Tcp(address) => address.fmt(f),
+            Self::Unix(path) => path.display().fmt(f),
+        }
+    }
+}
+
 impl NodeConfig {
     pub fn key_pair(&self) -> &KeyPair {
         &self.key_pair
@@ -95,8 +433,29 @@ impl NodeConfig {
         self.consensus_config.as_ref()
     }
 
+    /// The `gossip-degree` of a validator in a committee of `committee_size`, lowered to the
+    /// number of other validators in it. Fails if it is 0.
+    pub fn effective_gossip_degree(&self, committee_size: usize) -> Result<usize> {
+        let degree = self.gossip_degree.unwrap_or_else(default_gossip_degree);
+        if degree == 0 {
+            bail!("gossip-degree must be at least 1");
+        }
+        let peers = committee_size.saturating_sub(1);
+        if degree > peers {
+            warn!(
+                degree,
+                "Gossiping with the {peers} other validators of the committee only"
+            );
+            return Ok(peers);
+        }
+        Ok(degree)
+    }
+
     pub fn genesis(&self) -> Result<&genesis::Genesis> {
-        self.genesis.genesis()
+        self.genesis.genesis(
+            self.max_genesis_size,
+            Duration::from_secs(self.genesis_load_timeout_secs),
+        )
     }
 }
 
@@ -129,6 +488,756 @@ impl ConsensusConfig {
     }
 }
 
+/// Authorization required by the metrics server, matching what Prometheus can send with a
+/// scrape job.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MetricsAuthConfig {
+    Basic { username: String, password: String },
+    Bearer { token: String },
+}
+
+/// Pushes the metrics to an OpenTelemetry collector over OTLP/HTTP.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct OtlpMetricsConfig {
+    /// The OTLP/HTTP metrics endpoint of the collector, usually
+    /// `http://<collector>:4318/v1/metrics`.
+    pub endpoint: String,
+    /// Defaults to 15.
+    #[serde(default = "default_otlp_push_interval_secs")]
+    pub push_interval_secs: u64,
+}
+
+fn default_otlp_push_interval_secs() -> u64 {
+    15
+}
+
+/// Default maximum number of connections used to serve event queries
+pub const DEFAULT_EVENT_STORE_READ_POOL_SIZE: u32 = 4;
+/// Default maximum number of connections used to ingest events.  SQLite only allows one
+/// writer at a time, so more than one write connection mostly adds lock contention.
+pub const DEFAULT_EVENT_STORE_WRITE_POOL_SIZE: u32 = 1;
+
+/// Configuration of the SQLite event store, used when `enable-event-processing` is set.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct EventStoreConfig {
+    /// Maximum number of connections serving event queries. Defaults to 4.
+    #[serde(default = "default_event_store_read_pool_size")]
+    pub read_pool_size: u32,
+
+    /// Maximum number of connections ingesting events. SQLite only allows a single writer at a
+    /// time, so this defaults to 1.
+    #[serde(default = "default_event_store_write_pool_size")]
+    pub write_pool_size: u32,
+
+    /// Number of events written to the store together. Events of a partial batch are written
+    /// out once no new transaction arrived for a moment. Defaults to 1, writing the events of
+    /// every transaction right away.
+    #[serde(default = "default_event_store_insert_batch_size")]
+    pub insert_batch_size: usize,
+
+    /// Secondary indexes built, each speeding up the event queries filtering on it at the cost
+    /// of slower ingestion and more space. Indexes removed from the list are dropped at the
+    /// next start. Defaults to event-type and package, time ranges and transactions are always
+    /// indexed.
+    #[serde(default = "default_event_store_indexes")]
+    pub indexes: Vec<EventIndex>,
+}
+
+/// A secondary index of the event store.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum EventIndex {
+    EventType,
+    Sender,
+    /// Package and module.
+    Package,
+    ObjectId,
+    /// The type of Move events, by which they are queried by type, module or package.
+    MoveEventType,
+}
+
+fn default_event_store_read_pool_size() -> u32 {
+    DEFAULT_EVENT_STORE_READ_POOL_SIZE
+}
+
+fn default_event_store_write_pool_size() -> u32 {
+    DEFAULT_EVENT_STORE_WRITE_POOL_SIZE
+}
+
+fn default_event_store_insert_batch_size() -> usize {
+    1
+}
+
+fn default_event_store_indexes() -> Vec<EventIndex> {
+    vec![EventIndex::EventType, EventIndex::Package]
+}
+
+impl Default for EventStoreConfig {
+    fn default() -> Self {
+        Self {
+            read_pool_size: default_event_store_read_pool_size(),
+            write_pool_size: default_event_store_write_pool_size(),
+            insert_batch_size: default_event_store_insert_batch_size(),
+            indexes: default_event_store_indexes(),
+        }
+    }
+}
+
+/// Retry behaviour of transaction post processing (indexing and event emission).
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct PostProcessingConfig {
+    /// Attempts per transaction, including the first one, before it is moved to the dead
+    /// letter store. Defaults to 4.
+    #[serde(default = "default_post_processing_max_attempts")]
+    pub max_attempts: u32,
+
+    /// Delay before the first retry, doubled after every further failure. Defaults to 100ms.
+    #[serde(default = "default_post_processing_initial_backoff_ms")]
+    pub initial_backoff_ms: u64,
+
+    /// Upper bound of the retry delay. Defaults to 2s.
+    #[serde(default = "default_post_processing_max_backoff_ms")]
+    pub max_backoff_ms: u64,
+
+    /// How often the object and address counts kept with the indexes are recomputed from
+    /// scratch, which scans all live objects. The counts are updated as transactions are
+    /// indexed in between, so they are approximate after a crash until the next
+    /// reconciliation. Defaults to 6 hours.
+    #[serde(default = "default_object_count_reconciliation_interval_secs")]
+    pub object_count_reconciliation_interval_secs: u64,
+}
+
+fn default_post_processing_max_attempts() -> u32 {
+    4
+}
+
+fn default_post_processing_initial_backoff_ms() -> u64 {
+    100
+}
+
+fn default_post_processing_max_backoff_ms() -> u64 {
+    2_000
+}
+
+fn default_object_count_reconciliation_interval_secs() -> u64 {
+    6 * 60 * 60
+}
+
+impl Default for PostProcessingConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: default_post_processing_max_attempts(),
+            initial_backoff_ms: default_post_processing_initial_backoff_ms(),
+            max_backoff_ms: default_post_processing_max_backoff_ms(),
+            object_count_reconciliation_interval_secs:
+                default_object_count_reconciliation_interval_secs(),
+        }
+    }
+}
+
+/// How a crashed background subsystem is restarted. A subsystem that crashed more than
+/// `max-restarts` times over the life of the node fails the node instead.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SubsystemRestartConfig {
+    /// Restarts per subsystem before the node fails. 0 fails the node on the first crash.
+    /// Defaults to 5.
+    #[serde(default = "default_subsystem_max_restarts")]
+    pub max_restarts: u32,
+
+    /// Delay before the first restart, doubled after every further crash. Defaults to 1s.
+    #[serde(default = "default_subsystem_initial_backoff_ms")]
+    pub initial_backoff_ms: u64,
+
+    /// Upper bound of the restart delay. Defaults to 1 minute.
+    #[serde(default = "default_subsystem_max_backoff_ms")]
+    pub max_backoff_ms: u64,
+}
+
+fn default_subsystem_max_restarts() -> u32 {
+    5
+}
+
+fn default_subsystem_initial_backoff_ms() -> u64 {
+    1_000
+}
+
+fn default_subsystem_max_backoff_ms() -> u64 {
+    60_000
+}
+
+impl Default for SubsystemRestartConfig {
+    fn default() -> Self {
+        Self {
+            max_restarts: default_subsystem_max_restarts(),
+            initial_backoff_ms: default_subsystem_initial_backoff_ms(),
+            max_backoff_ms: default_subsystem_max_backoff_ms(),
+        }
+    }
+}
+
+/// Each snapshot is a directory of `dir` named after the last checkpoint it holds, with RocksDB
+/// checkpoints of the store and of the checkpoint store and a `manifest.json`. It is written
+/// under a `.tmp` name first, so that directories named after a checkpoint are complete.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct StateSnapshotConfig {
+    /// Should be on the same filesystem as `db-path`, since the store files are hard-linked
+    /// into snapshots rather than copied.
+    pub dir: PathBuf,
+    /// Checkpoints made between two snapshots.
+    pub interval_checkpoints: u64,
+    /// Snapshots kept, the oldest are deleted. Defaults to 3.
+    #[serde(default = "default_state_snapshot_max_snapshots")]
+    pub max_snapshots: usize,
+}
+
+fn default_state_snapshot_max_snapshots() -> usize {
+    3
+}
+
+/// The past versions of each object kept besides its latest one, by type. Types are matched
+/// by patterns like `0x2::coin::Coin<0x2::sui::SUI>` for a type, `0x2::devnet_nft` for a module
+/// or `0x2` for a package, and the most specific pattern matching an object applies.
+/// Validators also keep the versions the transactions not yet in a certified checkpoint read,
+/// which reverting them at the end of an epoch restores.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ObjectHistoryConfig {
+    /// Past versions kept of objects no pattern matches, and of packages. All are kept when
+    /// unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_depth: Option<u64>,
+    /// Past versions kept by type pattern, all of them for a null depth.
+    #[serde(default)]
+    pub type_depths: BTreeMap<String, Option<u64>>,
+    /// How often old versions are deleted. Defaults to 1 hour.
+    #[serde(default = "default_object_history_prune_interval_secs")]
+    pub prune_interval_secs: u64,
+    /// Objects whose versions each pruning pass goes through, from where the previous pass
+    /// stopped, starting over from the first object after the last one. Defaults to 100000.
+    #[serde(default = "default_object_history_objects_per_pass")]
+    pub objects_per_pass: usize,
+}
+
+fn default_object_history_prune_interval_secs() -> u64 {
+    60 * 60
+}
+
+fn default_object_history_objects_per_pass() -> usize {
+    100_000
+}
+
+/// Transactions are sampled among those committed since the last check, which diverging
+/// effects are logged as errors for, counted by the `authority_reexecution_divergences` metric
+/// and kept with both sets of effects in `quarantine_db`. Where sampling left off is kept there
+/// as well, so a restart carries on from it, and the first check only samples the transactions
+/// committed since the verifier was enabled.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ReexecutionVerifierConfig {
+    /// Fraction of transactions, from 0 to 1, executed again. Defaults to 0.001.
+    #[serde(default = "default_reexecution_sample_rate")]
+    pub sample_rate: f64,
+    /// How often transactions committed since are sampled. Defaults to 1 minute.
+    #[serde(default = "default_reexecution_interval_secs")]
+    pub interval_secs: u64,
+}
+
+fn default_reexecution_sample_rate() -> f64 {
+    0.001
+}
+
+fn default_reexecution_interval_secs() -> u64 {
+    60
+}
+
+/// Connections beyond a cap are closed as soon as they are accepted.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct GrpcConnectionLimitsConfig {
+    pub max_inbound_connections: usize,
+    /// Connections open from a single IP address. Only the total cap applies when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_inbound_connections_per_ip: Option<usize>,
+}
+
+/// TCP keepalive probing, so that the OS closes connections whose peer went away without
+/// closing them, e.g. behind a proxy that dropped them.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct TcpKeepaliveConfig {
+    /// Seconds a connection is idle before the first probe is sent.
+    pub idle_secs: u64,
+    /// Seconds between unanswered probes. The OS default when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interval_secs: Option<u64>,
+    /// Unanswered probes after which the connection is closed. The OS default when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub count: Option<u32>,
+}
+
+/// When JSON-RPC clients are asked to back off. The node is elevated or overloaded once either
+/// measure reaches the threshold of that level.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RpcBackpressureConfig {
+    /// Certificates waiting to be executed. Default to 1000 and 10000.
+    #[serde(default = "default_backpressure_execution_queue_elevated")]
+    pub execution_queue_elevated: usize,
+    #[serde(default = "default_backpressure_execution_queue_overloaded")]
+    pub execution_queue_overloaded: usize,
+    /// Executed transactions not post-processed yet, so missing from the indexes and the
+    /// event store. Default to 1000 and 10000.
+    #[serde(default = "default_backpressure_indexing_lag_elevated")]
+    pub indexing_lag_elevated: u64,
+    #[serde(default = "default_backpressure_indexing_lag_overloaded")]
+    pub indexing_lag_overloaded: u64,
+    /// Backoff between requests suggested to clients at each level. Default to 100ms and 1s.
+    #[serde(default = "default_backpressure_elevated_backoff_ms")]
+    pub elevated_backoff_ms: u64,
+    #[serde(default = "default_backpressure_overloaded_backoff_ms")]
+    pub overloaded_backoff_ms: u64,
+}
+
+fn default_backpressure_execution_queue_elevated() -> usize {
+    1_000
+}
+
+fn default_backpressure_execution_queue_overloaded() -> usize {
+    10_000
+}
+
+fn default_backpressure_indexing_lag_elevated() -> u64 {
+    1_000
+}
+
+fn default_backpressure_indexing_lag_overloaded() -> u64 {
+    10_000
+}
+
+fn default_backpressure_elevated_backoff_ms() -> u64 {
+    100
+}
+
+fn default_backpressure_overloaded_backoff_ms() -> u64 {
+    1_000
+}
+
+/// Credentials the JSON-RPC server accepts. API keys are sent in the `x-api-key` header or as
+/// bearer tokens, and JWTs as bearer tokens.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RpcAuthConfig {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub api_keys: Vec<String>,
+    /// Where the JWKS whose keys sign the accepted JWTs is published. Only RS256 JWTs are
+    /// accepted, and none when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jwks_url: Option<String>,
+    /// How often the JWKS is fetched again, to pick up rotated keys. Defaults to 300.
+    #[serde(default = "default_jwks_refresh_interval_secs")]
+    pub jwks_refresh_interval_secs: u64,
+    /// The `iss` and `aud` claims JWTs must carry, when set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jwt_issuer: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jwt_audience: Option<String>,
+}
+
+fn default_jwks_refresh_interval_secs() -> u64 {
+    300
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RpcResponseCacheConfig {
+    /// Number of responses kept, the least recently used go first. Defaults to 10000.
+    #[serde(default = "default_rpc_response_cache_entries")]
+    pub entries: usize,
+    /// Seconds after which a response is read again. Responses are kept until evicted when
+    /// unset, they can't change.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttl_secs: Option<u64>,
+}
+
+fn default_rpc_response_cache_entries() -> usize {
+    10_000
+}
+
+/// How a full node reacts when the validators it follows announce different effects for the
+/// same transaction. It is synced with the effects a quorum agrees on, unless two effects have
+/// the validity threshold of stake behind each: then honest validators are on both sides, and
+/// the transaction is never executed since picking either could corrupt the local state.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct NodeSyncConfig {
+    /// Stop using the peers that announced other effects than the quorum a transaction was
+    /// synced with. Once quarantined, a peer stays so until the node restarts.
+    #[serde(default)]
+    pub quarantine_equivocating_peers: bool,
+    /// Only execute a downloaded transaction once a certified checkpoint commits to its
+    /// effects, rather than trusting the peers that announced them. Transactions are then
+    /// executed no earlier than they are checkpointed.
+    #[serde(default)]
+    pub verify_against_checkpoints: bool,
+    /// When verifying against checkpoints, the number of other peers that must also serve a
+    /// checkpoint whose certificate doesn't verify, after the one a peer served didn't, for
+    /// node sync to halt and the node to report itself not ready. It is fetched from all the
+    /// peers node sync can follow until one serves a valid one. Defaults to 3.
+    #[serde(default = "default_node_sync_checkpoint_peer_retries")]
+    pub checkpoint_peer_retries: usize,
+    /// When verifying against checkpoints, the number of checkpoints downloaded at once while
+    /// catching up. They are still verified and committed in order, and no more than this many
+    /// are held in memory. Defaults to 1, one at a time.
+    #[serde(default = "default_node_sync_checkpoint_download_concurrency")]
+    pub checkpoint_download_concurrency: usize,
+    /// Sync faster while the node is far behind the validators it follows, e.g. after an
+    /// extended downtime. Disabled when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub catch_up_burst: Option<CatchUpBurstConfig>,
+}
+
+fn default_node_sync_checkpoint_peer_retries() -> usize {
+    3
+}
+
+fn default_node_sync_checkpoint_download_concurrency() -> usize {
+    1
+}
+
+impl Default for NodeSyncConfig {
+    fn default() -> Self {
+        Self {
+            quarantine_equivocating_peers: false,
+            verify_against_checkpoints: false,
+            checkpoint_peer_retries: default_node_sync_checkpoint_peer_retries(),
+            checkpoint_download_concurrency: default_node_sync_checkpoint_download_concurrency(),
+            catch_up_burst: None,
+        }
+    }
+}
+
+/// Each of them defaults to 5 seconds. Raising them keeps gossip and node sync from dropping
+/// peers that are slow to respond, e.g. far away ones.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct NetworkTimeoutConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connect_timeout_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_timeout_ms: Option<u64>,
+    /// How often HTTP/2 pings are sent on idle connections.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keepalive_interval_ms: Option<u64>,
+}
+
+const DEFAULT_NETWORK_TIMEOUT: Duration = Duration::from_secs(5);
+
+impl NetworkTimeoutConfig {
+    pub fn connect_timeout(&self) -> Duration {
+        Self::or_default(self.connect_timeout_ms)
+    }
+
+    pub fn request_timeout(&self) -> Duration {
+        Self::or_default(self.request_timeout_ms)
+    }
+
+    pub fn keepalive_interval(&self) -> Duration {
+        Self::or_default(self.keepalive_interval_ms)
+    }
+
+    fn or_default(ms: Option<u64>) -> Duration {
+        ms.map(Duration::from_millis)
+            .unwrap_or(DEFAULT_NETWORK_TIMEOUT)
+    }
+}
+
+/// Node sync settings used while the node is more than `lag-threshold` transactions behind a
+/// validator it follows. Steady-state settings are restored once it is back within the
+/// threshold.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct CatchUpBurstConfig {
+    /// Defaults to 10000.
+    #[serde(default = "default_catch_up_burst_lag_threshold")]
+    pub lag_threshold: u64,
+    /// Number of transactions downloaded concurrently, 20 in the steady state. Defaults to 100.
+    #[serde(default = "default_catch_up_burst_concurrency")]
+    pub concurrency: usize,
+    /// Number of transaction digests requested from a validator at once, 100000 in the steady
+    /// state. Defaults to 1000000.
+    #[serde(default = "default_catch_up_burst_batch_size")]
+    pub batch_size: u64,
+}
+
+fn default_catch_up_burst_lag_threshold() -> u64 {
+    10_000
+}
+
+fn default_catch_up_burst_concurrency() -> usize {
+    100
+}
+
+fn default_catch_up_burst_batch_size() -> u64 {
+    1_000_000
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct TrustedCheckpoint {
+    pub sequence: CheckpointSequenceNumber,
+    /// The base64 digest of the checkpoint summary.
+    #[serde(with = "checkpoint_digest")]
+    pub digest: CheckpointDigest,
+}
+
+mod checkpoint_digest {
+    use base64ct::Encoding;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+    use sui_types::messages_checkpoint::CheckpointDigest;
+
+    pub fn serialize<S: Serializer>(
+        digest: &CheckpointDigest,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&base64ct::Base64::encode_string(digest))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<CheckpointDigest, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        let digest = base64ct::Base64::decode_vec(&encoded).map_err(D::Error::custom)?;
+        digest
+            .try_into()
+            .map_err(|_| D::Error::custom("a checkpoint digest is 32 bytes long"))
+    }
+}
+
+/// Tuning of the groups of column families of the `store` database.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct StoreColumnFamiliesConfig {
+    /// Objects, the owner index, shared object locks and pending certificates: written and
+    /// read by the execution of every transaction. Defaults to a 512 MiB block cache and four
+    /// 128 MiB memtables.
+    #[serde(default = "default_execution_column_families")]
+    pub execution: ColumnFamilyGroupConfig,
+    /// Certificates, effects, parent sync and batches: appended to by execution, but mostly
+    /// read to serve history and sync other nodes. Defaults to a 256 MiB block cache and two
+    /// 64 MiB memtables.
+    #[serde(default = "default_history_column_families")]
+    pub history: ColumnFamilyGroupConfig,
+    /// Number of flushes and compactions run concurrently. They are shared by all column
+    /// families of the database. The RocksDB default when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_background_jobs: Option<i32>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ColumnFamilyGroupConfig {
+    /// Size of the block cache shared by the column families of the group.
+    pub block_cache_mb: usize,
+    /// Size of a memtable of a column family of the group.
+    pub write_buffer_mb: usize,
+    /// Number of memtables of a column family, including those being flushed.
+    pub max_write_buffers: i32,
+}
+
+fn default_execution_column_families() -> ColumnFamilyGroupConfig {
+    ColumnFamilyGroupConfig {
+        block_cache_mb: 512,
+        write_buffer_mb: 128,
+        max_write_buffers: 4,
+    }
+}
+
+fn default_history_column_families() -> ColumnFamilyGroupConfig {
+    ColumnFamilyGroupConfig {
+        block_cache_mb: 256,
+        write_buffer_mb: 64,
+        max_write_buffers: 2,
+    }
+}
+
+/// A peer is picked at random whenever a follower slot is free.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct PeerSelectionConfig {
+    /// Pick validators with a probability proportional to their stake, rather than uniformly.
+    /// Defaults to true.
+    #[serde(default = "default_peer_selection_weight_by_stake")]
+    pub weight_by_stake: bool,
+    /// Share of the picks made uniformly even when weighting by stake, so that validators with
+    /// little stake are still followed now and then. Defaults to 0.1.
+    #[serde(default = "default_peer_selection_exploration_rate")]
+    pub exploration_rate: f64,
+}
+
+fn default_peer_selection_weight_by_stake() -> bool {
+    true
+}
+
+fn default_peer_selection_exploration_rate() -> f64 {
+    0.1
+}
+
+impl Default for PeerSelectionConfig {
+    fn default() -> Self {
+        Self {
+            weight_by_stake: default_peer_selection_weight_by_stake(),
+            exploration_rate: default_peer_selection_exploration_rate(),
+        }
+    }
+}
+
+/// The validators a node follows when `sync-peers-file` is set.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SyncPeers {
+    /// Public keys of the validators to follow. Keys that aren't in the genesis validator set
+    /// are ignored.
+    pub peers: Vec<PublicKeyBytes>,
+}
+
+impl Config for SyncPeers {}
+
+/// Pushes every certificate executed by the node to an external HTTP endpoint.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct CertificatePushConfig {
+    /// URL that batches of certificate summaries are POSTed to as JSON.
+    pub endpoint: String,
+
+    /// Maximum number of summaries held in memory while waiting for delivery. Delivery is
+    /// resumed from the node's own store, so a full buffer never drops certificates. Defaults
+    /// to 10000.
+    #[serde(default = "default_certificate_push_buffer_size")]
+    pub buffer_size: usize,
+
+    /// Maximum number of summaries sent in one request. Defaults to 100.
+    #[serde(default = "default_certificate_push_max_batch_size")]
+    pub max_batch_size: usize,
+}
+
+fn default_certificate_push_buffer_size() -> usize {
+    10_000
+}
+
+fn default_certificate_push_max_batch_size() -> usize {
+    100
+}
+
+/// Publishes every event emitted by the node's executed transactions to a Kafka topic.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct KafkaEventConfig {
+    /// Comma separated `host:port` list of the brokers to bootstrap from.
+    pub brokers: String,
+
+    pub topic: String,
+
+    /// What messages are keyed by, and so which events share a partition. Defaults to the
+    /// transaction digest.
+    #[serde(default)]
+    pub key: KafkaEventKey,
+
+    /// Defaults to at-least-once.
+    #[serde(default)]
+    pub delivery: KafkaDelivery,
+
+    /// Maximum number of events held in memory while waiting for the brokers. Events are read
+    /// again from the node's own store, so a full buffer never drops events. Defaults to 10000.
+    #[serde(default = "default_kafka_event_buffer_size")]
+    pub buffer_size: usize,
+}
+
+fn default_kafka_event_buffer_size() -> usize {
+    10_000
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum KafkaEventKey {
+    /// The object the event is about. Events without one, like Move events, fall back to the
+    /// transaction digest.
+    ObjectId,
+    TransactionDigest,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum KafkaDelivery {
+    /// Events are retried until the brokers acknowledge them, and may be published again after
+    /// a restart.
+    AtLeastOnce,
+    /// Events are published once, and dropped if the brokers don't acknowledge them.
+    AtMostOnce,
+}
+
+impl Default for KafkaEventKey {
+    fn default() -> Self {
+        KafkaEventKey::TransactionDigest
+    }
+}
+
+impl Default for KafkaDelivery {
+    fn default() -> Self {
+        KafkaDelivery::AtLeastOnce
+    }
+}
+
+/// When the connections to the validators a node follows are opened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PeerConnectMode {
+    /// On the first request to each validator, which then also waits for the connection.
+    Lazy,
+    /// At startup, and startup waits for them. Validators that can't be reached are connected
+    /// lazily instead, so the node starts anyway.
+    Eager,
+}
+
+impl Default for PeerConnectMode {
+    fn default() -> Self {
+        PeerConnectMode::Lazy
+    }
+}
+
+/// Where a full node takes its state from.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FollowMode {
+    /// Execute transactions locally, synced from validators or produced by consensus.
+    Standard,
+    /// Serve reads from the authority store of another node process on the same machine,
+    /// opened as a RocksDB secondary instance. The node never executes or accepts transactions.
+    #[serde(rename_all = "kebab-case")]
+    SecondaryDb {
+        /// `db-path` of the primary node.
+        primary_path: PathBuf,
+        /// How often to catch up with the writes of the primary. Defaults to 1000ms.
+        #[serde(default = "default_secondary_catch_up_interval_ms")]
+        catch_up_interval_ms: u64,
+    },
+}
+
+impl Default for FollowMode {
+    fn default() -> Self {
+        FollowMode::Standard
+    }
+}
+
+fn default_secondary_catch_up_interval_ms() -> u64 {
+    1000
+}
+
 /// Publicly known information about a validator
 /// TODO read most of this from on-chain
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
@@ -183,14 +1292,14 @@ impl Genesis {
         }
     }
 
-    fn genesis(&self) -> Result<&genesis::Genesis> {
+    fn genesis(&self, max_size: u64, load_timeout: Duration) -> Result<&genesis::Genesis> {
         match &self.location {
             GenesisLocation::InPlace { genesis } => Ok(genesis),
             GenesisLocation::File {
                 genesis_file_location,
-            } => self
-                .genesis
-                .get_or_try_init(|| genesis::Genesis::load(&genesis_file_location)),
+            } => self.genesis.get_or_try_init(|| {
+                genesis::Genesis::load_with_limits(genesis_file_location, max_size, load_timeout)
+            }),
         }
     }
 }
@@ -209,8 +1318,12 @@ enum GenesisLocation {
 
 #[cfg(test)]
 mod tests {
-    use super::Genesis;
-    use crate::{genesis, NodeConfig};
+    use super::{
+        default_genesis_load_timeout_secs, default_max_genesis_size, Genesis, TrustedCheckpoint,
+    };
+    use crate::{builder::ConfigBuilder, genesis, NodeConfig};
+    use std::num::NonZeroUsize;
+    use std::time::Duration;
 
     #[test]
     fn serialize_genesis_config_from_file() {
@@ -244,14 +1357,94 @@ mod tests {
         let genesis = genesis::Genesis::get_default_genesis();
         genesis.save(file.path()).unwrap();
 
-        let loaded_genesis = genesis_config.genesis().unwrap();
+        let loaded_genesis = genesis_config
+            .genesis(
+                default_max_genesis_size(),
+                Duration::from_secs(default_genesis_load_timeout_secs()),
+            )
+            .unwrap();
         assert_eq!(&genesis, loaded_genesis);
     }
 
+    #[test]
+    fn load_genesis_config_over_size_limit() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let genesis_config = Genesis::new_from_file(file.path());
+
+        let genesis = genesis::Genesis::get_default_genesis();
+        genesis.save(file.path()).unwrap();
+        let size = file.as_file().metadata().unwrap().len();
+
+        let error = genesis_config
+            .genesis(size - 1, Duration::from_secs(60))
+            .unwrap_err();
+        assert!(error.to_string().contains("larger than"));
+        // Fits exactly.
+        let loaded_genesis = genesis_config
+            .genesis(size, Duration::from_secs(60))
+            .unwrap();
+        assert_eq!(&genesis, loaded_genesis);
+    }
+
+    #[test]
+    fn serialize_trusted_checkpoint() {
+        let checkpoint = TrustedCheckpoint {
+            sequence: 12,
+            digest: [7; 32],
+        };
+        let s = serde_yaml::to_string(&checkpoint).unwrap();
+        assert_eq!(
+            "---\nsequence: 12\ndigest: BwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwc=\n",
+            s
+        );
+        let loaded: TrustedCheckpoint = serde_yaml::from_str(&s).unwrap();
+        assert_eq!(checkpoint, loaded);
+
+        let truncated = "---\nsequence: 12\ndigest: BwcH\n";
+        assert!(serde_yaml::from_str::<TrustedCheckpoint>(truncated).is_err());
+    }
+
     #[test]
     fn fullnode_template() {
         const TEMPLATE: &str = include_str!("../data/fullnode-template.yaml");
 
         let _template: NodeConfig = serde_yaml::from_str(TEMPLATE).unwrap();
     }
+
+    #[test]
+    fn gossip_degree_lowered_to_committee() {
+        let dir = tempfile::tempdir().unwrap();
+        let network = ConfigBuilder::new(&dir)
+            .committee_size(NonZeroUsize::new(2).unwrap())
+            .build();
+        let mut config = network.validator_configs()[0].clone();
+        let committee_size = config.genesis().unwrap().committee().unwrap().num_members();
+        assert_eq!(committee_size, 2);
+
+        // The default of 4 is more than the one other validator.
+        assert_eq!(config.effective_gossip_degree(committee_size).unwrap(), 1);
+        config.gossip_degree = Some(3);
+        assert_eq!(config.effective_gossip_degree(committee_size).unwrap(), 1);
+        assert_eq!(config.effective_gossip_degree(10).unwrap(), 3);
+        config.gossip_degree = Some(0);
+        assert!(config.effective_gossip_degree(committee_size).is_err());
+    }
+
+    #[test]
+    fn network_timeouts_default_to_5_secs() {
+        const TEMPLATE: &str = include_str!("../data/fullnode-template.yaml");
+
+        let yaml = format!("{TEMPLATE}\nnetwork-timeout-config:\n  request-timeout-ms: 30000\n");
+        let config: NodeConfig = serde_yaml::from_str(&yaml).unwrap();
+        let timeouts = &config.network_timeout_config;
+        assert_eq!(timeouts.request_timeout(), Duration::from_secs(30));
+        assert_eq!(timeouts.connect_timeout(), Duration::from_secs(5));
+        assert_eq!(timeouts.keepalive_interval(), Duration::from_secs(5));
+
+        let config: NodeConfig = serde_yaml::from_str(TEMPLATE).unwrap();
+        assert_eq!(
+            config.network_timeout_config.request_timeout(),
+            Duration::from_secs(5)
+        );
+    }
 }
@@ -4,6 +4,7 @@
 use crate::{builder, genesis, utils, Config, NodeConfig, ValidatorInfo, FULL_NODE_DB_PATH};
 use rand::rngs::OsRng;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::num::NonZeroUsize;
 use std::path::Path;
 use std::sync::Arc;
@@ -66,12 +67,67 @@ impl NetworkConfig {
             key_pair,
             db_path: db_path.join(FULL_NODE_DB_PATH),
             network_address: utils::new_network_address(),
+            grpc_connection_limits: None,
             metrics_address: utils::available_local_socket_address(),
-            json_rpc_address: utils::available_local_socket_address(),
+            metrics_auth: None,
+            otlp_metrics: None,
+            admin_allow_remote: false,
+            json_rpc_address: utils::available_local_socket_address().into(),
+            json_rpc_socket_mode: crate::node::default_json_rpc_socket_mode(),
+            json_rpc_tcp_keepalive: None,
+            json_rpc_server_instances: crate::node::default_json_rpc_server_instances(),
             websocket_address: Some(utils::available_local_socket_address()),
+            websocket_ping_interval_ms: None,
+            websocket_idle_timeout_ms: None,
+            rpc_slow_query_threshold_ms: None,
+            rpc_default_timeout_ms: None,
+            rpc_method_timeouts_ms: BTreeMap::new(),
+            rpc_backpressure: None,
+            rpc_auth: None,
+            rpc_response_cache: None,
+            max_page_size: crate::node::default_max_page_size(),
+            default_page_size: None,
+            method_page_sizes: BTreeMap::new(),
+            rpc_max_object_size: crate::node::default_rpc_max_object_size(),
+            rpc_max_verification_batch: crate::node::default_rpc_max_verification_batch(),
+            pin_reads_at_checkpoint: None,
+            rpc_require_synced: false,
+            rpc_sync_distance: crate::node::default_rpc_sync_distance(),
+            effects_firehose_address: None,
             consensus_config: None,
+            checkpoint_keys_dir: None,
+            object_cache_entries: 0,
+            group_commit_window_us: 0,
+            store_column_families: None,
+            state_snapshot_config: None,
+            object_history_config: None,
+            reexecution_verifier: None,
+            execution_trace_sample_rate: 0.0,
+            input_prefetch_concurrency: 0,
             enable_event_processing: true,
+            event_store_config: Default::default(),
+            post_processing_config: Default::default(),
+            certificate_push_config: None,
+            kafka_event_config: None,
+            subsystem_restart_config: Default::default(),
+            skip_signing_self_test: false,
+            max_transaction_size_bytes: None,
+            follow_mode: Default::default(),
+            startup_sync_target: None,
+            startup_sync_deadline_secs: None,
+            max_genesis_size: crate::node::default_max_genesis_size(),
+            genesis_load_timeout_secs: crate::node::default_genesis_load_timeout_secs(),
+            db_lock_timeout_secs: None,
+            shutdown_drain_timeout_secs: crate::node::default_shutdown_drain_timeout_secs(),
             enable_gossip: true,
+            sync_peers_file: None,
+            peer_selection: Default::default(),
+            peer_connect_mode: Default::default(),
+            gossip_max_bytes_per_sec: None,
+            gossip_degree: None,
+            network_timeout_config: Default::default(),
+            node_sync_config: Default::default(),
+            trusted_checkpoint: None,
             genesis: validator_config.genesis.clone(),
         }
     }
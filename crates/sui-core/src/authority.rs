@@ -4,17 +4,21 @@
 
 use crate::checkpoints::FragmentInternalError;
 use crate::{
+    authority_active::gossip::sync_progress::SyncProgress,
     authority_batch::{BroadcastReceiver, BroadcastSender},
-    checkpoints::CheckpointStore,
+    checkpoints::{CheckpointSigners, CheckpointStore},
+    consensus_adapter::ConsensusStatus,
     epoch::EpochInfoLocals,
     event_handler::EventHandler,
     execution_engine,
-    query_helpers::QueryHelpers,
+    metrics::{self, MetricsNamespace},
+    query_helpers::{self, QueryHelpers},
     transaction_input_checker,
 };
 use arc_swap::ArcSwap;
 use async_trait::async_trait;
 use chrono::prelude::*;
+use futures::StreamExt;
 use move_binary_format::CompiledModule;
 use move_bytecode_utils::module_cache::SyncModuleCache;
 use move_core_types::{
@@ -27,22 +31,29 @@ use move_vm_runtime::{move_vm::MoveVM, native_functions::NativeFunctionTable};
 use narwhal_executor::ExecutionStateError;
 use narwhal_executor::{ExecutionIndices, ExecutionState};
 use parking_lot::Mutex;
-use prometheus::{
-    register_histogram_with_registry, register_int_counter_with_registry, Histogram, IntCounter,
-};
+use prometheus::{Gauge, Histogram, IntCounter, IntCounterVec, IntGauge, IntGaugeVec};
 use std::ops::Deref;
 use std::{
-    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
     pin::Pin,
     sync::{
         atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
         Arc,
     },
+    time::{Duration, Instant},
 };
 use sui_adapter::adapter;
 use sui_config::genesis::Genesis;
+use sui_json_rpc_api::rpc_types::{
+    SuiCoinBalance, SuiCoinPage, SuiEffectsVerification, SuiObjectTypeFilter,
+    SuiTransactionBlockOptions, SuiTransactionBlockResponse,
+};
 use sui_storage::{
+    dead_letter_store::DeadLetterStore,
     event_store::{EventStore, EventStoreType, StoredEvent},
+    indexes::ObjectCounts,
+    lock_service::LockedObject,
+    post_processing_store::PostProcessingStore,
     write_ahead_log::{DBTxGuard, TxGuard, WriteAheadLog},
     IndexStore,
 };
@@ -50,18 +61,22 @@ use sui_storage::{
 use sui_types::{
     base_types::*,
     batch::{TxSequenceNumber, UpdateItem},
-    committee::Committee,
-    crypto::AuthoritySignature,
+    coin::TreasuryCap,
+    committee::{Committee, EpochId},
+    crypto::{AuthoritySignature, KeyPair},
     error::{SuiError, SuiResult},
+    event::EventType,
+    event_filter::MoveEventTypeFilter,
     fp_bail, fp_ensure,
     gas::SuiGasStatus,
+    gas_coin::{GasCoin, GAS},
     messages::*,
     object::{Data, Object, ObjectFormatOptions, ObjectRead},
     storage::{BackingPackageStore, DeleteKind, Storage},
     MOVE_STDLIB_ADDRESS, SUI_FRAMEWORK_ADDRESS, SUI_SYSTEM_STATE_OBJECT_ID,
 };
-use tokio::sync::broadcast::error::RecvError;
-use tracing::{debug, error, instrument, warn};
+use tokio::sync::{broadcast::error::RecvError, watch};
+use tracing::{debug, error, info, instrument, warn};
 use typed_store::Map;
 
 #[cfg(test)]
@@ -83,12 +98,30 @@ mod gas_tests;
 mod temporary_store;
 pub use temporary_store::AuthorityTemporaryStore;
 
+mod object_cache;
+pub use object_cache::ObjectCache;
+
+mod group_commit;
+pub use group_commit::GroupCommitter;
+
+mod object_history;
+pub use object_history::ObjectHistoryPolicy;
+
+mod execution_trace;
+pub use execution_trace::ExecutionTraceSampler;
+
+mod execution_load;
+pub use execution_load::ExecutionLoad;
+use execution_load::ExecutionLoadTracker;
+
 mod authority_store;
 pub use authority_store::{
-    AuthorityStore, GatewayStore, ResolverWrapper, SuiDataStore, UpdateType,
+    is_db_locked_error, AuthorityStore, GatewayStore, ResolverWrapper, StoreColumnFamilyGroups,
+    SuiDataStore, UpdateType,
 };
 use sui_types::messages_checkpoint::{
-    CheckpointRequest, CheckpointRequestType, CheckpointResponse,
+    CheckpointContents, CheckpointRequest, CheckpointRequestType, CheckpointResponse,
+    CheckpointSequenceNumber,
 };
 use sui_types::object::Owner;
 use sui_types::sui_system_state::SuiSystemState;
@@ -103,6 +136,83 @@ pub const MAX_ITEMS_LIMIT: u64 = 100_000;
 const BROADCAST_CAPACITY: usize = 10_000;
 
 const MAX_TX_RECOVERY_RETRY: u32 = 3;
+
+/// How post processing retries a transaction it fails to index or emit events for, before
+/// moving it to the dead letter store.
+#[derive(Clone, Debug)]
+pub struct PostProcessingRetryPolicy {
+    /// Number of attempts per transaction, including the first one.
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled after every further failure.
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for PostProcessingRetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(2),
+        }
+    }
+}
+
+/// Pauses and resumes post processing at runtime. Execution carries on while it is paused, and
+/// the transactions executed meanwhile are processed once it resumes. The pause is persisted,
+/// so post processing stays paused across restarts until it is resumed.
+#[derive(Clone)]
+pub struct PostProcessingControl {
+    store: Arc<PostProcessingStore>,
+    paused: Arc<watch::Sender<bool>>,
+}
+
+impl PostProcessingControl {
+    pub fn new(store: Arc<PostProcessingStore>) -> SuiResult<Self> {
+        let (paused, _) = watch::channel(store.is_paused()?);
+        Ok(Self {
+            store,
+            paused: Arc::new(paused),
+        })
+    }
+
+    pub fn is_paused(&self) -> bool {
+        *self.paused.borrow()
+    }
+
+    pub fn set_paused(&self, paused: bool) -> SuiResult {
+        self.store.set_paused(paused)?;
+        self.paused.send_replace(paused);
+        Ok(())
+    }
+}
+
+/// The oldest data each store still retains. Reads of anything older fail, or find nothing,
+/// because it was pruned rather than because it never existed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RetentionWatermarks {
+    /// Sequence number of the oldest transaction whose certificate, effects and written object
+    /// versions are stored.
+    pub objects: TxSequenceNumber,
+    /// The first sequence number batches can be streamed from.
+    pub batches: TxSequenceNumber,
+    /// The oldest stored checkpoint, None without checkpoints or before the first one.
+    pub checkpoints: Option<CheckpointSequenceNumber>,
+    /// Timestamp of the oldest stored event, None without an event store or before the first
+    /// event.
+    pub events: Option<u64>,
+}
+
+/// How long post processing waits for another transaction before writing out a partial batch
+/// of events.
+const EVENT_FLUSH_IDLE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How often the post processing lag metric is updated.
+const POST_PROCESSING_LAG_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Transactions read from the store at a time while post processing catches up after a pause.
+const POST_PROCESSING_CATCH_UP_CHUNK: u64 = 1_000;
+
 type CertTxGuard<'a> = DBTxGuard<'a, CertifiedTransaction>;
 
 /// Prometheus metrics which can be displayed in Grafana, queried and alerted on
@@ -117,11 +227,47 @@ pub struct AuthorityMetrics {
     num_input_objs: Histogram,
     num_shared_objects: Histogram,
     batch_size: Histogram,
+    load_inputs_latency: Histogram,
+    prefetched_input_objects: IntCounter,
 
     pub gossip_queued_count: IntCounter,
     pub gossip_sync_count: IntCounter,
     pub gossip_task_success_count: IntCounter,
     pub gossip_task_error_count: IntCounter,
+    pub gossip_active_peers: IntGauge,
+    pub gossip_active_stake: IntGauge,
+    pub gossip_peer_selections: IntCounterVec,
+    pub gossip_bytes_received: IntCounter,
+
+    pub node_sync_effects_conflicts: IntCounter,
+    pub node_sync_quarantined_peers: IntGauge,
+    pub node_sync_checkpoint_verification_failures: IntCounter,
+    pub node_sync_burst_active: IntGauge,
+    pub node_sync_checkpoint_downloads_in_flight: IntGauge,
+    pub node_sync_checkpoints_downloaded: IntCounter,
+    pub node_sync_checkpoint_transactions_downloaded: IntCounter,
+
+    pub post_processing_retries: IntCounter,
+    pub post_processing_dead_letters: IntGauge,
+    pub post_processing_paused: IntGauge,
+    pub post_processing_lag: IntGauge,
+
+    pub live_objects: IntGauge,
+    pub owner_addresses: IntGauge,
+
+    pub execution_queue_depth: IntGauge,
+    pub execution_throughput: Gauge,
+    pub execution_throughput_average: Gauge,
+
+    pub oldest_retained: IntGaugeVec,
+
+    pub epoch: IntGauge,
+    pub committee_size: IntGauge,
+    pub epoch_changes: IntCounter,
+    pub epoch_change_checkpoint: IntGauge,
+
+    pub reexecuted_transactions: IntCounter,
+    pub reexecution_divergences: IntCounter,
 }
 
 // Override default Prom buckets for positive numbers in 0-50k range
@@ -131,95 +277,262 @@ const POSITIVE_INT_BUCKETS: &[f64] = &[
 
 impl AuthorityMetrics {
     pub fn new(registry: &prometheus::Registry) -> AuthorityMetrics {
+        let authority = MetricsNamespace::new(registry, metrics::AUTHORITY);
+        let gossip = MetricsNamespace::new(registry, metrics::GOSSIP);
+        let node_sync = MetricsNamespace::new(registry, metrics::NODE_SYNC);
         Self {
-            tx_orders: register_int_counter_with_registry!(
-                "total_transaction_orders",
-                "Total number of transaction orders",
-                registry,
-            )
-            .unwrap(),
-            total_certs: register_int_counter_with_registry!(
-                "total_transaction_certificates",
-                "Total number of transaction certificates handled",
-                registry,
-            )
-            .unwrap(),
+            tx_orders: authority
+                .int_counter(
+                    "total_transaction_orders",
+                    "Total number of transaction orders",
+                )
+                .unwrap(),
+            total_certs: authority
+                .int_counter(
+                    "total_transaction_certificates",
+                    "Total number of transaction certificates handled",
+                )
+                .unwrap(),
             // total_effects == total transactions finished
-            total_effects: register_int_counter_with_registry!(
-                "total_transaction_effects",
-                "Total number of transaction effects produced",
-                registry,
-            )
-            .unwrap(),
-            total_events: register_int_counter_with_registry!(
-                "total_events",
-                "Total number of events produced",
-                registry,
-            )
-            .unwrap(),
-            signature_errors: register_int_counter_with_registry!(
-                "total_signature_errors",
-                "Number of transaction signature errors",
-                registry,
-            )
-            .unwrap(),
-            shared_obj_tx: register_int_counter_with_registry!(
-                "num_shared_obj_tx",
-                "Number of transactions involving shared objects",
-                registry,
-            )
-            .unwrap(),
-            tx_already_processed: register_int_counter_with_registry!(
-                "num_tx_already_processed",
-                "Number of transaction orders already processed previously",
-                registry,
-            )
-            .unwrap(),
-            num_input_objs: register_histogram_with_registry!(
-                "num_input_objects",
-                "Distribution of number of input TX objects per TX",
-                POSITIVE_INT_BUCKETS.to_vec(),
-                registry,
-            )
-            .unwrap(),
-            num_shared_objects: register_histogram_with_registry!(
-                "num_shared_objects",
-                "Number of shared input objects per TX",
-                POSITIVE_INT_BUCKETS.to_vec(),
-                registry,
-            )
-            .unwrap(),
-            batch_size: register_histogram_with_registry!(
-                "batch_size",
-                "Distribution of size of transaction batch",
-                POSITIVE_INT_BUCKETS.to_vec(),
-                registry,
-            )
-            .unwrap(),
-            gossip_queued_count: register_int_counter_with_registry!(
-                "gossip_queued_count",
-                "Number of digests queued from gossip peers",
-                registry,
-            )
-            .unwrap(),
-            gossip_sync_count: register_int_counter_with_registry!(
-                "gossip_sync_count",
-                "Number of certificates downloaded from gossip peers",
-                registry,
-            )
-            .unwrap(),
-            gossip_task_success_count: register_int_counter_with_registry!(
-                "gossip_task_success_count",
-                "Number of gossip tasks that completed successfully",
-                registry,
-            )
-            .unwrap(),
-            gossip_task_error_count: register_int_counter_with_registry!(
-                "gossip_task_error_count",
-                "Number of gossip tasks that completed with errors",
-                registry,
-            )
-            .unwrap(),
+            total_effects: authority
+                .int_counter(
+                    "total_transaction_effects",
+                    "Total number of transaction effects produced",
+                )
+                .unwrap(),
+            total_events: authority
+                .int_counter("total_events", "Total number of events produced")
+                .unwrap(),
+            signature_errors: authority
+                .int_counter(
+                    "total_signature_errors",
+                    "Number of transaction signature errors",
+                )
+                .unwrap(),
+            shared_obj_tx: authority
+                .int_counter(
+                    "num_shared_obj_tx",
+                    "Number of transactions involving shared objects",
+                )
+                .unwrap(),
+            tx_already_processed: authority
+                .int_counter(
+                    "num_tx_already_processed",
+                    "Number of transaction orders already processed previously",
+                )
+                .unwrap(),
+            num_input_objs: authority
+                .histogram_with_buckets(
+                    "num_input_objects",
+                    "Distribution of number of input TX objects per TX",
+                    POSITIVE_INT_BUCKETS.to_vec(),
+                )
+                .unwrap(),
+            num_shared_objects: authority
+                .histogram_with_buckets(
+                    "num_shared_objects",
+                    "Number of shared input objects per TX",
+                    POSITIVE_INT_BUCKETS.to_vec(),
+                )
+                .unwrap(),
+            batch_size: authority
+                .histogram_with_buckets(
+                    "batch_size",
+                    "Distribution of size of transaction batch",
+                    POSITIVE_INT_BUCKETS.to_vec(),
+                )
+                .unwrap(),
+            load_inputs_latency: authority
+                .histogram(
+                    "load_inputs_latency",
+                    "Seconds to read and check the inputs of a certificate, prefetching included",
+                )
+                .unwrap(),
+            prefetched_input_objects: authority
+                .int_counter(
+                    "prefetched_input_objects",
+                    "Number of input objects read ahead of checking their certificate",
+                )
+                .unwrap(),
+            gossip_queued_count: gossip
+                .int_counter("queued_count", "Number of digests queued from gossip peers")
+                .unwrap(),
+            gossip_sync_count: gossip
+                .int_counter(
+                    "sync_count",
+                    "Number of certificates downloaded from gossip peers",
+                )
+                .unwrap(),
+            gossip_task_success_count: gossip
+                .int_counter(
+                    "task_success_count",
+                    "Number of gossip tasks that completed successfully",
+                )
+                .unwrap(),
+            gossip_task_error_count: gossip
+                .int_counter(
+                    "task_error_count",
+                    "Number of gossip tasks that completed with errors",
+                )
+                .unwrap(),
+            gossip_active_peers: gossip
+                .int_gauge("active_peers", "Number of peers currently followed")
+                .unwrap(),
+            gossip_active_stake: gossip
+                .int_gauge(
+                    "active_stake",
+                    "Total stake of the peers currently followed",
+                )
+                .unwrap(),
+            gossip_peer_selections: gossip
+                .int_counter_vec(
+                    "peer_selections",
+                    "Number of peers selected to follow, by how they were sampled",
+                    &["sampling"],
+                )
+                .unwrap(),
+            gossip_bytes_received: gossip
+                .int_counter(
+                    "bytes_received",
+                    "Bytes of responses received from followed peers, its rate is the throughput",
+                )
+                .unwrap(),
+            node_sync_effects_conflicts: node_sync
+                .int_counter(
+                    "effects_conflicts",
+                    "Number of transactions peers announced conflicting effects for",
+                )
+                .unwrap(),
+            node_sync_quarantined_peers: node_sync
+                .int_gauge(
+                    "quarantined_peers",
+                    "Number of peers ignored for announcing conflicting effects",
+                )
+                .unwrap(),
+            node_sync_checkpoint_verification_failures: node_sync
+                .int_counter(
+                    "checkpoint_verification_failures",
+                    "Number of synced transactions that failed checkpoint verification",
+                )
+                .unwrap(),
+            node_sync_burst_active: node_sync
+                .int_gauge(
+                    "burst_active",
+                    "1 while node sync is catching up in burst mode, 0 otherwise",
+                )
+                .unwrap(),
+            node_sync_checkpoint_downloads_in_flight: node_sync
+                .int_gauge(
+                    "checkpoint_downloads_in_flight",
+                    "Number of checkpoints being downloaded to verify synced transactions against",
+                )
+                .unwrap(),
+            node_sync_checkpoints_downloaded: node_sync
+                .int_counter(
+                    "checkpoints_downloaded",
+                    "Number of verified checkpoints downloaded, its rate is the throughput",
+                )
+                .unwrap(),
+            node_sync_checkpoint_transactions_downloaded: node_sync
+                .int_counter(
+                    "checkpoint_transactions_downloaded",
+                    "Number of transactions listed by the verified checkpoints downloaded",
+                )
+                .unwrap(),
+            post_processing_retries: authority
+                .int_counter(
+                    "post_processing_retries",
+                    "Number of times post processing of a transaction was retried",
+                )
+                .unwrap(),
+            post_processing_dead_letters: authority
+                .int_gauge(
+                    "post_processing_dead_letters",
+                    "Number of transactions that post processing gave up on",
+                )
+                .unwrap(),
+            post_processing_paused: authority
+                .int_gauge(
+                    "post_processing_paused",
+                    "1 while post processing is paused by an operator, 0 otherwise",
+                )
+                .unwrap(),
+            post_processing_lag: authority
+                .int_gauge(
+                    "post_processing_lag",
+                    "Number of executed transactions that post processing didn't process yet",
+                )
+                .unwrap(),
+            live_objects: authority
+                .int_gauge(
+                    "live_objects",
+                    "Approximate number of live objects, counted by the indexes",
+                )
+                .unwrap(),
+            owner_addresses: authority
+                .int_gauge(
+                    "owner_addresses",
+                    "Approximate number of addresses owning objects, counted by the indexes",
+                )
+                .unwrap(),
+            execution_queue_depth: authority
+                .int_gauge(
+                    "execution_queue_depth",
+                    "Number of certificates waiting to be executed, as last sampled",
+                )
+                .unwrap(),
+            execution_throughput: authority
+                .gauge(
+                    "execution_throughput",
+                    "Transactions executed per second between the last two samples",
+                )
+                .unwrap(),
+            execution_throughput_average: authority
+                .gauge(
+                    "execution_throughput_average",
+                    "Moving average over about a minute of the transactions executed per second",
+                )
+                .unwrap(),
+            oldest_retained: authority
+                .int_gauge_vec(
+                    "oldest_retained",
+                    "Oldest sequence number, or timestamp for events, each store retains",
+                    &["store"],
+                )
+                .unwrap(),
+            epoch: authority
+                .int_gauge("epoch", "The epoch the authority is in")
+                .unwrap(),
+            committee_size: authority
+                .int_gauge(
+                    "committee_size",
+                    "Number of validators in the committee of the current epoch",
+                )
+                .unwrap(),
+            epoch_changes: authority
+                .int_counter(
+                    "epoch_changes",
+                    "Number of epoch boundaries crossed since the node started",
+                )
+                .unwrap(),
+            epoch_change_checkpoint: authority
+                .int_gauge(
+                    "epoch_change_checkpoint",
+                    "Last checkpoint of the previous epoch, 0 until an epoch change is seen",
+                )
+                .unwrap(),
+            reexecuted_transactions: authority
+                .int_counter(
+                    "reexecuted_transactions",
+                    "Number of committed transactions executed again to check their effects",
+                )
+                .unwrap(),
+            reexecution_divergences: authority
+                .int_counter(
+                    "reexecution_divergences",
+                    "Number of transactions whose effects differed when executed again",
+                )
+                .unwrap(),
         }
     }
 }
@@ -235,12 +548,15 @@ pub type StableSyncAuthoritySigner =
 
 const DEFAULT_QUERY_LIMIT: usize = 1000;
 
-pub struct AuthorityState {
-    // Fixed size, static, identity of the authority
-    /// The name of this authority.
+/// The name of an authority and the key it signs with.
+pub struct AuthorityIdentity {
     pub name: AuthorityName,
-    /// The signature key of the authority.
     pub secret: StableSyncAuthoritySigner,
+}
+
+pub struct AuthorityState {
+    /// Identity of the authority, which changes when its key is rotated at reconfiguration.
+    identity: ArcSwap<AuthorityIdentity>,
 
     /// Committee of this Sui instance.
     pub committee: ArcSwap<Committee>,
@@ -280,6 +596,41 @@ pub struct AuthorityState {
 
     // Cache the latest checkpoint number to avoid expensive locking to access checkpoint store
     latest_checkpoint_num: AtomicU64,
+
+    /// Timestamp given to the transaction post processed last, so that timestamps never go
+    /// back when the local clock does.
+    last_tx_timestamp_ms: AtomicU64,
+
+    /// Set when committing a certificate failed in the store. From then on the authority
+    /// refuses all new work, since the store may be in an inconsistent state; the certificate
+    /// remains in the recovery log and is retried on restart.
+    storage_failure: Mutex<Option<String>>,
+
+    /// How far the followers of other authorities got, see `sync_eta`.
+    pub(crate) sync_progress: SyncProgress,
+
+    /// The sequence number following the last transaction post-processed, or `u64::MAX` while
+    /// post-processing isn't running. See `post_processing_lag`.
+    post_processed_next: AtomicU64,
+
+    /// Outcome of the latest certificates submitted to consensus, see `consensus_status`.
+    consensus_status: Mutex<ConsensusStatus>,
+
+    /// Samples the transactions whose execution phases are traced.
+    execution_trace: ExecutionTraceSampler,
+
+    /// See `sample_execution_load`.
+    execution_load: Mutex<ExecutionLoadTracker>,
+
+    /// Input objects read at once by `prefetch_inputs`, which is disabled when 0.
+    input_prefetch_concurrency: usize,
+
+    /// Set once the node shuts down, see `begin_shutdown`.
+    shutdown: watch::Sender<bool>,
+
+    /// Number of upcoming `index_tx` calls that fail, to test post processing retries.
+    #[cfg(test)]
+    index_failures: AtomicU64,
 }
 
 /// The authority state encapsulates all state, drives execution, and ensures safety.
@@ -289,11 +640,66 @@ pub struct AuthorityState {
 ///
 /// Repeating valid commands should produce no changes and return no error.
 impl AuthorityState {
+    /// Trace the execution phases of `rate` of the transactions, see [`ExecutionTraceSampler`].
+    pub fn with_execution_trace_sample_rate(mut self, rate: f64) -> Self {
+        self.execution_trace = ExecutionTraceSampler::new(rate);
+        self
+    }
+
+    /// Read up to `concurrency` input objects of a certificate at a time before executing it,
+    /// see `prefetch_inputs`.
+    pub fn with_input_prefetch_concurrency(mut self, concurrency: usize) -> Self {
+        self.input_prefetch_concurrency = concurrency;
+        self
+    }
+
     /// Get a broadcast receiver for updates
     pub fn subscribe_batch(&self) -> BroadcastReceiver {
         self.batch_channels.subscribe()
     }
 
+    /// Stop the batch service, post processing, gossip, node sync and the processes following
+    /// the executed transactions, which return once they wrote out what they were working on.
+    /// Transactions are still executed.
+    pub fn begin_shutdown(&self) {
+        self.shutdown.send_replace(true);
+    }
+
+    pub fn is_shutting_down(&self) -> bool {
+        *self.shutdown.borrow()
+    }
+
+    /// Resolves once `begin_shutdown` was called.
+    pub async fn shutdown_requested(&self) {
+        let mut shutdown = self.subscribe_shutdown();
+        while !*shutdown.borrow() {
+            // The sender lives as long as self.
+            let _ = shutdown.changed().await;
+        }
+    }
+
+    /// Set to true by `begin_shutdown`, for the tasks that don't hold on to the authority.
+    pub fn subscribe_shutdown(&self) -> watch::Receiver<bool> {
+        self.shutdown.subscribe()
+    }
+
+    /// Write the memtables of the stores to disk, so that opening them again needn't replay
+    /// their write-ahead logs. Meant for shutdown, once nothing writes to them anymore.
+    pub fn flush_stores(&self) -> SuiResult {
+        // A secondary mirrors a primary, and isn't written to.
+        if self.database.is_secondary() {
+            return Ok(());
+        }
+        self.database.flush()?;
+        if let Some(checkpoints) = &self.checkpoints {
+            checkpoints.lock().flush()?;
+        }
+        if let Some(indexes) = &self.indexes {
+            indexes.flush()?;
+        }
+        Ok(())
+    }
+
     async fn handle_transaction_impl(
         &self,
         transaction: Transaction,
@@ -326,11 +732,12 @@ impl AuthorityState {
 
         let owned_objects = input_objects.filter_owned_objects();
 
+        let identity = self.identity();
         let signed_transaction = SignedTransaction::new(
             self.committee.load().epoch,
             transaction,
-            self.name,
-            &*self.secret,
+            identity.name,
+            &*identity.secret,
         );
 
         // Check and write locks, to signed transaction, into the database
@@ -349,6 +756,8 @@ impl AuthorityState {
         &self,
         transaction: Transaction,
     ) -> Result<TransactionInfoResponse, SuiError> {
+        self.ensure_writable()?;
+        self.check_healthy()?;
         self.metrics.tx_orders.inc();
         // Check the sender's signature.
         transaction.verify().map_err(|e| {
@@ -427,11 +836,224 @@ impl AuthorityState {
         self.process_certificate(tx_guard, certificate).await
     }
 
+    /// Reject anything that would write to a read-only secondary store.
+    fn ensure_writable(&self) -> SuiResult {
+        fp_ensure!(
+            !self.database.is_secondary(),
+            SuiError::UnsupportedFeatureError {
+                error: "this node serves a read-only secondary store".to_string(),
+            }
+        );
+        Ok(())
+    }
+
+    /// Estimated time until this authority processed everything the authorities it follows
+    /// announced, based on the recent sync rate. `None` when there isn't enough data yet.
+    pub fn sync_eta(&self) -> Option<Duration> {
+        self.sync_progress.eta()
+    }
+
+    /// Most transactions an authority this authority follows announced beyond those processed
+    /// from it. `None` until something was processed.
+    pub fn sync_lag(&self) -> Option<TxSequenceNumber> {
+        self.sync_progress.lag()
+    }
+
+    /// Delete the past versions `policy` doesn't keep of the objects the next pruning pass goes
+    /// through. Skipped while post-processing lags, since indexing a transaction reads the
+    /// object versions it touched. The versions read by the transactions that aren't in a
+    /// certified checkpoint yet are kept too, and all those after them, since reverting such a
+    /// transaction at the end of an epoch restores them. Returns how many versions were
+    /// deleted, or None when skipped. Stops early once shutting down.
+    pub fn prune_object_versions(&self, policy: &ObjectHistoryPolicy) -> SuiResult<Option<usize>> {
+        if matches!(self.post_processing_lag()?, Some(lag) if lag > 0) {
+            return Ok(None);
+        }
+        self.database
+            .prune_object_versions(
+                |object| policy.depth(object),
+                || self.uncheckpointed_input_versions(),
+                policy.objects_per_pass(),
+                || self.is_shutting_down(),
+            )
+            .map(Some)
+    }
+
+    /// The oldest version of each object that the executed transactions not yet in a certified
+    /// checkpoint read, or none without a checkpoint store.
+    fn uncheckpointed_input_versions(&self) -> SuiResult<HashMap<ObjectID, SequenceNumber>> {
+        let mut versions = HashMap::new();
+        let (extra_transactions, next_expected) = match &self.checkpoints {
+            Some(checkpoints) => {
+                let mut checkpoints = checkpoints.lock();
+                let extra_transactions: Vec<_> = checkpoints
+                    .extra_transactions
+                    .iter()
+                    .map(|(digests, _)| digests)
+                    .collect();
+                (
+                    extra_transactions,
+                    checkpoints.next_transaction_sequence_expected(),
+                )
+            }
+            None => return Ok(versions),
+        };
+        // Those executed since the last batch the checkpoint store was given aren't in it yet.
+        let not_batched = self
+            .database
+            .executed_sequence
+            .iter()
+            .skip_to(&next_expected)?
+            .map(|(_, digests)| digests);
+        for digests in extra_transactions.into_iter().chain(not_batched) {
+            let effects = self.database.get_effects(&digests.transaction)?;
+            let inputs = effects
+                .mutated
+                .iter()
+                .map(|(object_ref, _)| object_ref)
+                .chain(effects.deleted.iter())
+                .chain(effects.wrapped.iter());
+            for (id, version, _) in inputs {
+                let input = version.decrement()?;
+                versions
+                    .entry(*id)
+                    .and_modify(|kept: &mut SequenceNumber| *kept = (*kept).min(input))
+                    .or_insert(input);
+            }
+        }
+        Ok(versions)
+    }
+
+    /// The latest checkpoint each validator of the committee reported to the follower of this
+    /// authority, and how long ago. `None` for the validators it doesn't follow, or that never
+    /// answered.
+    pub fn validator_checkpoints(
+        &self,
+    ) -> Vec<(AuthorityName, Option<(CheckpointSequenceNumber, Duration)>)> {
+        self.committee
+            .load()
+            .names()
+            .map(|name| (*name, self.sync_progress.peer_checkpoint(name)))
+            .collect()
+    }
+
+    /// The latest checkpoint this authority has synced: the latest in its checkpoint store for
+    /// a validator, and for a full node the latest whose transactions, and those of the
+    /// checkpoints before, node sync verified and executed. `None` when not known, including
+    /// on full nodes that don't verify against checkpoints.
+    pub fn synced_checkpoint(&self) -> Option<CheckpointSequenceNumber> {
+        match &self.checkpoints {
+            Some(checkpoints) => checkpoints.lock().next_checkpoint().checked_sub(1),
+            None => self.sync_progress.synced_checkpoint(),
+        }
+    }
+
+    /// Fails with a retryable error until this authority has synced `checkpoint`, for reads
+    /// that must not see a state older than it.
+    pub fn ensure_checkpoint_synced(&self, checkpoint: CheckpointSequenceNumber) -> SuiResult {
+        let synced = self.synced_checkpoint();
+        fp_ensure!(
+            synced.map_or(false, |synced| synced >= checkpoint),
+            SuiError::CheckpointNotYetAvailable { checkpoint, synced }
+        );
+        Ok(())
+    }
+
+    /// Number of executed transactions that post-processing didn't index and write the events
+    /// of yet. `None` when post-processing isn't running.
+    pub fn post_processing_lag(&self) -> SuiResult<Option<TxSequenceNumber>> {
+        let processed = self.post_processed_next.load(Ordering::Relaxed);
+        if processed == u64::MAX {
+            return Ok(None);
+        }
+        Ok(Some(
+            self.database
+                .next_sequence_number()?
+                .saturating_sub(processed),
+        ))
+    }
+
+    /// Fails once a storage write failure stopped this authority. Used for readiness checks.
+    pub fn check_healthy(&self) -> SuiResult {
+        match &*self.storage_failure.lock() {
+            Some(error) => Err(SuiError::AuthorityUnhealthy {
+                error: error.clone(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Fails once node sync stopped for good, e.g. because no peer served a checkpoint that
+    /// verifies. Used for readiness checks.
+    pub fn check_syncing(&self) -> SuiResult {
+        match self.sync_progress.halted() {
+            Some(error) => Err(SuiError::NodeSyncHalted {
+                error: error.to_string(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Number of certificates waiting to be executed.
+    pub fn execution_queue_depth(&self) -> SuiResult<usize> {
+        Ok(self.database.get_pending_certificates()?.len())
+    }
+
+    /// Read the execution queue, and the throughput since the previous call, to export them as
+    /// the execution_queue_depth and execution_throughput gauges. Meant to be called
+    /// periodically, since reading the queue walks the pending certificates.
+    pub fn sample_execution_load(&self) -> SuiResult<ExecutionLoad> {
+        let queue_depth = self.execution_queue_depth()?;
+        let load = self.execution_load.lock().sample(
+            queue_depth,
+            self.metrics.total_effects.get(),
+            Instant::now(),
+        );
+        self.metrics
+            .execution_queue_depth
+            .set(load.queue_depth as i64);
+        self.metrics.execution_throughput.set(load.throughput);
+        self.metrics
+            .execution_throughput_average
+            .set(load.average_throughput);
+        Ok(load)
+    }
+
+    /// The execution load as of the latest `sample_execution_load`.
+    pub fn execution_load(&self) -> ExecutionLoad {
+        self.execution_load.lock().load()
+    }
+
+    /// How the latest certificates submitted to consensus by this authority fared. Never
+    /// updated on authorities that don't run consensus.
+    pub fn consensus_status(&self) -> ConsensusStatus {
+        self.consensus_status.lock().clone()
+    }
+
+    pub(crate) fn record_consensus_submission(&self, result: &SuiResult) {
+        let mut status = self.consensus_status.lock();
+        match result {
+            Ok(()) => status.last_sequenced = Some(Instant::now()),
+            Err(error) => status.last_failure = Some((Instant::now(), error.clone())),
+        }
+    }
+
+    /// Stop accepting work after `error` was returned while writing to the store.
+    pub(crate) fn record_storage_failure(&self, error: &SuiError) {
+        let mut storage_failure = self.storage_failure.lock();
+        if storage_failure.is_none() {
+            error!("Storage write failed, refusing all new transactions and certificates: {error}");
+            *storage_failure = Some(error.to_string());
+        }
+    }
+
     async fn acquire_tx_guard<'a>(
         &'a self,
         digest: &TransactionDigest,
         cert: &CertifiedTransaction,
     ) -> SuiResult<CertTxGuard<'a>> {
+        self.ensure_writable()?;
+        self.check_healthy()?;
         match self.database.wal.begin_tx(digest, cert).await? {
             Some(g) => Ok(g),
             None => {
@@ -549,7 +1171,7 @@ impl AuthorityState {
             .await
         {
             Err(e) => {
-                debug!(name = ?self.name, digest = ?transaction_digest, "Error preparing transaction: {}", e);
+                debug!(name = ?self.name(), digest = ?transaction_digest, "Error preparing transaction: {}", e);
                 tx_guard.release();
                 return Err(e);
             }
@@ -558,7 +1180,12 @@ impl AuthorityState {
 
         // If commit_certificate returns an error, tx_guard will be dropped and the certificate
         // will be persisted in the log for later recovery.
-        self.commit_certificate(temporary_store, &certificate, &signed_effects)
+        self.execution_trace
+            .trace(
+                "write_effects",
+                &transaction_digest,
+                self.commit_certificate(temporary_store, &certificate, &signed_effects),
+            )
             .await?;
 
         // commit_certificate finished, the tx is fully committed to the store.
@@ -571,6 +1198,34 @@ impl AuthorityState {
         })
     }
 
+    /// Read the input objects of `certificate` concurrently, `input_prefetch_concurrency` at a
+    /// time, so that checking them reads them from the caches of the store rather than one by
+    /// one from disk. Read errors are left to the check to report.
+    async fn prefetch_inputs(&self, certificate: &CertifiedTransaction) {
+        if self.input_prefetch_concurrency == 0 {
+            return;
+        }
+        let ids: Vec<_> = match certificate.data.input_objects() {
+            Ok(inputs) => inputs.iter().map(|kind| kind.object_id()).collect(),
+            Err(_) => return,
+        };
+        // A single object gains nothing from reading it twice.
+        if ids.len() < 2 {
+            return;
+        }
+        self.metrics
+            .prefetched_input_objects
+            .inc_by(ids.len() as u64);
+        futures::stream::iter(ids)
+            .map(|id| {
+                let database = self.database.clone();
+                tokio::task::spawn_blocking(move || database.get_object(&id))
+            })
+            .buffer_unordered(self.input_prefetch_concurrency)
+            .for_each(|_| async {})
+            .await;
+    }
+
     /// prepare_certificate validates the transaction input, and executes the certificate,
     /// returning effects, output objects, events, etc.
     ///
@@ -589,12 +1244,22 @@ impl AuthorityState {
         AuthorityTemporaryStore<AuthorityStore>,
         SignedTransactionEffects,
     )> {
-        let (gas_status, input_objects) = transaction_input_checker::check_transaction_input(
-            &self.database,
-            certificate,
-            &self.metrics.shared_obj_tx,
-        )
-        .await?;
+        let started = Instant::now();
+        let (gas_status, input_objects) = self
+            .execution_trace
+            .trace("load_inputs", &transaction_digest, async {
+                self.prefetch_inputs(certificate).await;
+                transaction_input_checker::check_transaction_input(
+                    &self.database,
+                    certificate,
+                    &self.metrics.shared_obj_tx,
+                )
+                .await
+            })
+            .await?;
+        self.metrics
+            .load_inputs_latency
+            .observe(started.elapsed().as_secs_f64());
 
         // At this point we need to check if any shared objects need locks,
         // and whether they have them.
@@ -626,17 +1291,21 @@ impl AuthorityState {
         let transaction_dependencies = input_objects.transaction_dependencies();
         let mut temporary_store =
             AuthorityTemporaryStore::new(self.database.clone(), input_objects, transaction_digest);
-        let (effects, _execution_error) = execution_engine::execute_transaction_to_effects(
-            shared_object_refs,
-            &mut temporary_store,
-            certificate.data.clone(),
-            transaction_digest,
-            transaction_dependencies,
-            &self.move_vm,
-            &self._native_functions,
-            gas_status,
-            self.committee.load().epoch,
-        );
+        let (effects, _execution_error) =
+            self.execution_trace
+                .trace_blocking("execute", &transaction_digest, || {
+                    execution_engine::execute_transaction_to_effects(
+                        shared_object_refs,
+                        &mut temporary_store,
+                        certificate.data.clone(),
+                        transaction_digest,
+                        transaction_dependencies,
+                        &self.move_vm,
+                        &self._native_functions,
+                        gas_status,
+                        self.committee.load().epoch,
+                    )
+                });
 
         self.metrics.total_effects.inc();
         self.metrics
@@ -644,12 +1313,67 @@ impl AuthorityState {
             .inc_by(effects.events.len() as u64);
 
         // TODO: Distribute gas charge and rebate, which can be retrieved from effects.
-        let signed_effects =
-            effects.to_sign_effects(self.committee.load().epoch, &self.name, &*self.secret);
+        let identity = self.identity();
+        let signed_effects = effects.to_sign_effects(
+            self.committee.load().epoch,
+            &identity.name,
+            &*identity.secret,
+        );
 
         Ok((temporary_store, signed_effects))
     }
 
+    /// Execute the committed transaction `digest` again from the object versions it read,
+    /// without writing anything, and return the effects stored for it and those it has now,
+    /// which differ only if execution is nondeterministic or the store is corrupted. None if
+    /// it wasn't executed, or a version it read was pruned since. Executed in the current
+    /// epoch, which transactions of past epochs may have read.
+    pub async fn reexecute_transaction(
+        &self,
+        digest: &TransactionDigest,
+    ) -> SuiResult<Option<(TransactionEffects, TransactionEffects)>> {
+        let certificate = match self.database.read_certificate(digest)? {
+            Some(certificate) => certificate,
+            None => return Ok(None),
+        };
+        let effects = match self.database.get_effects(digest) {
+            Ok(effects) => effects,
+            Err(SuiError::TransactionNotFound { .. }) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let (gas_status, input_objects) = match transaction_input_checker::load_executed_inputs(
+            &self.database,
+            &certificate,
+            &effects,
+        )
+        .await?
+        {
+            Some(inputs) => inputs,
+            None => return Ok(None),
+        };
+
+        let shared_object_refs = input_objects.filter_shared_objects();
+        let transaction_dependencies = input_objects.transaction_dependencies();
+        let mut temporary_store =
+            AuthorityTemporaryStore::new(self.database.clone(), input_objects, *digest);
+        let (reexecuted, _execution_error) = execution_engine::execute_transaction_to_effects(
+            shared_object_refs,
+            &mut temporary_store,
+            certificate.data,
+            *digest,
+            transaction_dependencies,
+            &self.move_vm,
+            &self._native_functions,
+            gas_status,
+            self.committee.load().epoch,
+        );
+        self.metrics.reexecuted_transactions.inc();
+        if reexecuted != effects {
+            self.metrics.reexecution_divergences.inc();
+        }
+        Ok(Some((effects, reexecuted)))
+    }
+
     fn index_tx(
         &self,
         indexes: &IndexStore,
@@ -659,6 +1383,36 @@ impl AuthorityState {
         effects: &SignedTransactionEffects,
         timestamp_ms: u64,
     ) -> SuiResult {
+        #[cfg(test)]
+        if self
+            .index_failures
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1))
+            .is_ok()
+        {
+            return Err(SuiError::GenericStorageError(
+                "Injected indexing failure".to_string(),
+            ));
+        }
+
+        let mut treasury_caps = Vec::new();
+        let mut published_packages = Vec::new();
+        for ((object_id, version, _), _) in &effects.effects.created {
+            let object = match self.database.get_object_by_key(object_id, *version)? {
+                Some(object) => object,
+                None => continue,
+            };
+            // Packages can't be upgraded, so every package created is a fresh publish.
+            if object.is_package() {
+                published_packages.push(*object_id);
+            }
+            let coin_type = object
+                .data
+                .type_()
+                .and_then(|type_| TreasuryCap::coin_type(type_).cloned());
+            if let Some(coin_type) = coin_type {
+                treasury_caps.push((coin_type, *object_id));
+            }
+        }
         indexes.index_tx(
             cert.sender_address(),
             cert.data.input_objects()?.iter().map(|o| o.object_id()),
@@ -667,13 +1421,120 @@ impl AuthorityState {
                 .move_calls()?
                 .iter()
                 .map(|mc| (mc.package.0, mc.module.clone(), mc.function.clone())),
+            treasury_caps.into_iter(),
+            published_packages.into_iter(),
             seq,
             digest,
             timestamp_ms,
-        )
+        )?;
+
+        let effects = &effects.effects;
+        let object_delta = (effects.created.len() + effects.unwrapped.len()) as i64
+            - (effects.deleted.len() + effects.wrapped.len()) as i64;
+        // Types are written the way the owner index writes them, for reconciliation to agree.
+        let type_of = |id: &ObjectID, version: SequenceNumber| -> SuiResult<Option<String>> {
+            Ok(self.database.get_object_by_key(id, version)?.map(|object| {
+                object
+                    .data
+                    .type_()
+                    .map(|tag| tag.to_string())
+                    .unwrap_or_else(|| "Package".to_string())
+            }))
+        };
+        let mut type_deltas = Vec::new();
+        for ((id, version, _), _) in effects.created.iter().chain(effects.unwrapped.iter()) {
+            if let Some(type_) = type_of(id, *version)? {
+                type_deltas.push((type_, 1));
+            }
+        }
+        // Objects deleted or wrapped are inputs, except those unwrapped by the same transaction,
+        // which weren't counted.
+        let input_versions: BTreeMap<_, _> = cert
+            .data
+            .input_objects()?
+            .into_iter()
+            .filter_map(|kind| match kind {
+                InputObjectKind::ImmOrOwnedMoveObject((id, version, _)) => Some((id, version)),
+                _ => None,
+            })
+            .collect();
+        for (id, _, _) in effects.deleted.iter().chain(effects.wrapped.iter()) {
+            if let Some(version) = input_versions.get(id) {
+                if let Some(type_) = type_of(id, *version)? {
+                    type_deltas.push((type_, -1));
+                }
+            }
+        }
+        // Transactions only take objects owned by their sender, so only the sender can lose
+        // objects, and only the owners of the objects written can gain them.
+        let addresses: BTreeSet<_> = std::iter::once(cert.sender_address())
+            .chain(
+                effects
+                    .mutated_and_created()
+                    .chain(effects.unwrapped.iter())
+                    .filter_map(|(_, owner)| match owner {
+                        Owner::AddressOwner(address) => Some(*address),
+                        _ => None,
+                    }),
+            )
+            .collect();
+        let owners = addresses
+            .into_iter()
+            .map(|address| {
+                let owns_objects = self.database.owns_objects(Owner::AddressOwner(address))?;
+                Ok((address, owns_objects))
+            })
+            .collect::<SuiResult<Vec<_>>>()?;
+        let counts = indexes.update_object_counts(
+            seq,
+            object_delta,
+            type_deltas.into_iter(),
+            owners.into_iter(),
+        )?;
+        self.report_object_counts(&counts);
+        Ok(())
+    }
+
+    /// Recompute the object counts of the indexes, including those by type, from the owner
+    /// index, which scans all of it.
+    /// Returns `None` when this authority doesn't keep indexes, or started shutting down during
+    /// the scan, which then writes nothing.
+    pub fn reconcile_object_counts(&self) -> SuiResult<Option<ObjectCounts>> {
+        let indexes = match &self.indexes {
+            Some(indexes) => indexes,
+            None => return Ok(None),
+        };
+        // Transactions executed during the scan may or may not be counted twice.
+        let next_sequence = self.database.next_sequence_number()?;
+        let objects = self.database.live_objects().map(|object| {
+            if self.is_shutting_down() {
+                return Err(SuiError::GenericStorageError(
+                    "Interrupted by shutdown".to_string(),
+                ));
+            }
+            Ok(object)
+        });
+        let counts = match indexes.reconcile_object_counts(objects, next_sequence) {
+            Err(_) if self.is_shutting_down() => return Ok(None),
+            result => result?,
+        };
+        self.report_object_counts(&counts);
+        Ok(Some(counts))
     }
 
-    async fn process_one_tx(&self, seq: TxSequenceNumber, digest: &TransactionDigest) -> SuiResult {
+    fn report_object_counts(&self, counts: &ObjectCounts) {
+        self.metrics.live_objects.set(counts.live_objects as i64);
+        self.metrics
+            .owner_addresses
+            .set(counts.owner_addresses as i64);
+    }
+
+    async fn process_one_tx(
+        &self,
+        seq: TxSequenceNumber,
+        digest: &TransactionDigest,
+        event_insert_batch_size: usize,
+    ) -> SuiResult {
         // Load cert and effects.
         let info = self.make_transaction_info(digest).await?;
         let (cert, effects) = match info {
@@ -689,35 +1550,195 @@ impl AuthorityState {
             }
         };
 
-        let timestamp_ms = Self::unixtime_now_ms();
+        let timestamp_ms = self.next_tx_timestamp_ms();
 
         // Index tx
         if let Some(indexes) = &self.indexes {
-            if let Err(e) =
+            self.execution_trace.trace_blocking("index", digest, || {
                 self.index_tx(indexes.as_ref(), seq, digest, &cert, &effects, timestamp_ms)
-            {
-                warn!(?digest, "Couldn't index tx: {}", e);
-            }
+            })?;
         }
 
         // Emit events
         if let Some(event_handler) = &self.event_handler {
             let checkpoint_num = self.latest_checkpoint_num.load(Ordering::Relaxed);
             event_handler
-                .process_events(&effects.effects, timestamp_ms, seq, checkpoint_num)
+                .process_events(
+                    &effects.effects,
+                    timestamp_ms,
+                    seq,
+                    checkpoint_num,
+                    event_insert_batch_size,
+                )
                 .await?;
         }
 
-        Ok(())
-    }
+        Ok(())
+    }
+
+    /// Runs `process_one_tx` under `retry_policy`. Transactions that still fail after the last
+    /// attempt are recorded in `dead_letters`.
+    async fn process_one_tx_with_retry(
+        &self,
+        seq: TxSequenceNumber,
+        digest: &TransactionDigest,
+        retry_policy: &PostProcessingRetryPolicy,
+        event_insert_batch_size: usize,
+        dead_letters: &DeadLetterStore,
+    ) {
+        let mut backoff = retry_policy.initial_backoff;
+        let mut attempt = 1;
+        loop {
+            match self
+                .process_one_tx(seq, digest, event_insert_batch_size)
+                .await
+            {
+                Ok(()) => return,
+                Err(e) if attempt < retry_policy.max_attempts => {
+                    warn!(
+                        ?digest,
+                        attempt, "Couldn't process tx, retrying in {backoff:?}: {e}"
+                    );
+                    self.metrics.post_processing_retries.inc();
+                    tokio::time::sleep(backoff).await;
+                    backoff = std::cmp::min(backoff * 2, retry_policy.max_backoff);
+                    attempt += 1;
+                }
+                Err(e) => {
+                    error!(
+                        ?digest,
+                        seq, "Couldn't process tx after {attempt} attempts: {e}"
+                    );
+                    match dead_letters.insert(seq, digest, e.to_string()) {
+                        Ok(()) => self.metrics.post_processing_dead_letters.inc(),
+                        Err(e) => error!(?digest, seq, "Couldn't record dead letter: {e}"),
+                    }
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Makes one more attempt at every transaction in `dead_letters`, removing those that now
+    /// succeed. Returns the number of transactions reprocessed. Events are written right away,
+    /// together with any that were waiting for a batch to fill up.
+    pub async fn reprocess_dead_letters(&self, dead_letters: &DeadLetterStore) -> SuiResult<usize> {
+        let mut reprocessed = 0;
+        for (seq, (digest, _)) in dead_letters.iter().collect::<Vec<_>>() {
+            match self.process_one_tx(seq, &digest, 1).await {
+                Ok(()) => {
+                    dead_letters.remove(seq)?;
+                    self.metrics.post_processing_dead_letters.dec();
+                    reprocessed += 1;
+                }
+                Err(e) => warn!(?digest, seq, "Couldn't reprocess dead letter: {e}"),
+            }
+        }
+        Ok(reprocessed)
+    }
+
+    /// Events are written to the event store `event_insert_batch_size` at a time, a partial
+    /// batch is written once no transaction arrived for `EVENT_FLUSH_IDLE_INTERVAL`.
+    ///
+    /// While `control` is paused no transaction is processed. On resuming, the transactions
+    /// executed meanwhile are read back from the store before following new ones again.
+    // TODO: This should persist the last successfully-processed sequence to disk, and upon
+    // starting up, look for any sequences in the store since then and process them.
+    pub async fn run_tx_post_processing_process(
+        &self,
+        retry_policy: PostProcessingRetryPolicy,
+        event_insert_batch_size: usize,
+        dead_letters: Arc<DeadLetterStore>,
+        control: PostProcessingControl,
+    ) -> SuiResult {
+        let mut subscriber = self.subscribe_batch();
+        let mut paused = control.paused.subscribe();
+        self.metrics
+            .post_processing_dead_letters
+            .set(dead_letters.count() as i64);
+        if let Some(counts) = self
+            .indexes
+            .as_ref()
+            .map(|indexes| indexes.object_counts())
+            .transpose()?
+            .flatten()
+        {
+            self.report_object_counts(&counts);
+        }
+        // Transactions executed before the subscription aren't processed, unless a pause left
+        // them behind.
+        let mut next = match control.store.resume_from()? {
+            Some(seq) => seq,
+            None => self.database.next_sequence_number()?,
+        };
+        self.post_processed_next.store(next, Ordering::Relaxed);
+        let mut lag_reported = Instant::now();
+
+        loop {
+            if control.is_paused() {
+                self.flush_events().await;
+                control.store.set_resume_from(Some(next))?;
+                self.metrics.post_processing_paused.set(1);
+                info!(seq = next, "Post processing paused");
+                while control.is_paused() {
+                    if self.is_shutting_down() {
+                        return Ok(());
+                    }
+                    self.report_post_processing_lag();
+                    let _ =
+                        tokio::time::timeout(POST_PROCESSING_LAG_INTERVAL, paused.changed()).await;
+                }
+                self.metrics.post_processing_paused.set(0);
+                info!(seq = next, "Post processing resumed");
+            }
 
-    // TODO: This should persist the last successfully-processed sequence to disk, and upon
-    // starting up, look for any sequences in the store since then and process them.
-    pub async fn run_tx_post_processing_process(&self) -> SuiResult {
-        let mut subscriber = self.subscribe_batch();
+            if control.store.resume_from()?.is_some() {
+                // Subscribing before reading the tip makes sure the transactions executed while
+                // catching up are announced. Catching up again until nothing is left keeps the
+                // subscription from lagging behind.
+                loop {
+                    subscriber = self.subscribe_batch();
+                    let tip = self.database.next_sequence_number()?;
+                    if next >= tip || control.is_paused() {
+                        break;
+                    }
+                    next = self
+                        .catch_up_post_processing(
+                            next,
+                            tip,
+                            &retry_policy,
+                            event_insert_batch_size,
+                            &dead_letters,
+                            &control,
+                        )
+                        .await?;
+                }
+                if control.is_paused() {
+                    continue;
+                }
+                control.store.set_resume_from(None)?;
+                info!(seq = next, "Post processing caught up");
+            }
 
-        loop {
-            match subscriber.recv().await {
+            let result = tokio::select! {
+                result = tokio::time::timeout(EVENT_FLUSH_IDLE_INTERVAL, subscriber.recv()) => {
+                    result
+                }
+                _ = paused.changed() => continue,
+                _ = self.shutdown_requested() => break,
+            };
+            if lag_reported.elapsed() >= POST_PROCESSING_LAG_INTERVAL {
+                self.report_post_processing_lag();
+                lag_reported = Instant::now();
+            }
+            let result = match result {
+                Ok(result) => result,
+                Err(_) => {
+                    self.flush_events().await;
+                    continue;
+                }
+            };
+            match result {
                 Ok(item) => {
                     if let UpdateItem::Transaction((
                         seq,
@@ -727,9 +1748,21 @@ impl AuthorityState {
                         },
                     )) = item
                     {
-                        if let Err(e) = self.process_one_tx(seq, &digest).await {
-                            warn!(?digest, "Couldn't process tx: {}", e);
+                        // Already processed while catching up.
+                        if seq < next {
+                            continue;
                         }
+                        self.process_one_tx_with_retry(
+                            seq,
+                            &digest,
+                            &retry_policy,
+                            event_insert_batch_size,
+                            &dead_letters,
+                        )
+                        .await;
+                        next = seq + 1;
+                        self.post_processed_next
+                            .fetch_max(seq + 1, Ordering::Relaxed);
                     }
                 }
 
@@ -750,10 +1783,79 @@ impl AuthorityState {
                 }
             }
         }
+        self.flush_events().await;
 
         Ok(())
     }
 
+    /// Process the transactions from `start` to `end` read from the store, until `control` is
+    /// paused. Returns the sequence number following the last one processed.
+    async fn catch_up_post_processing(
+        &self,
+        start: TxSequenceNumber,
+        end: TxSequenceNumber,
+        retry_policy: &PostProcessingRetryPolicy,
+        event_insert_batch_size: usize,
+        dead_letters: &DeadLetterStore,
+        control: &PostProcessingControl,
+    ) -> SuiResult<TxSequenceNumber> {
+        let mut next = start;
+        while next < end {
+            let chunk_end = std::cmp::min(next + POST_PROCESSING_CATCH_UP_CHUNK, end);
+            for (seq, digest) in self.database.transactions_in_seq_range(next, chunk_end)? {
+                if control.is_paused() {
+                    return Ok(next);
+                }
+                self.process_one_tx_with_retry(
+                    seq,
+                    &digest,
+                    retry_policy,
+                    event_insert_batch_size,
+                    dead_letters,
+                )
+                .await;
+                next = seq + 1;
+                self.post_processed_next.store(next, Ordering::Relaxed);
+                control.store.set_resume_from(Some(next))?;
+            }
+            next = chunk_end;
+            self.post_processed_next.store(next, Ordering::Relaxed);
+            control.store.set_resume_from(Some(next))?;
+            self.report_post_processing_lag();
+        }
+        Ok(next)
+    }
+
+    fn report_post_processing_lag(&self) {
+        match self.post_processing_lag() {
+            Ok(lag) => self
+                .metrics
+                .post_processing_lag
+                .set(lag.unwrap_or(0) as i64),
+            Err(e) => warn!("Couldn't measure the post processing lag: {e}"),
+        }
+    }
+
+    /// Write out the events waiting for their batch to fill up. On failure they stay queued for
+    /// the next attempt.
+    async fn flush_events(&self) {
+        if let Some(event_handler) = &self.event_handler {
+            if let Err(e) = event_handler.flush_events().await {
+                warn!("Couldn't write events to the event store: {e}");
+            }
+        }
+    }
+
+    /// The timestamp of the transaction post processed next. Checkpoints carry no timestamp
+    /// to derive it from, so it is read from the local clock, but never goes back so that
+    /// timestamps follow the execution order on this node. Nodes whose clocks differ give the
+    /// same transaction different timestamps.
+    fn next_tx_timestamp_ms(&self) -> u64 {
+        let now = Self::unixtime_now_ms();
+        let last = self.last_tx_timestamp_ms.fetch_max(now, Ordering::Relaxed);
+        now.max(last)
+    }
+
     pub fn unixtime_now_ms() -> u64 {
         let ts_ms = Utc::now().timestamp_millis();
         u64::try_from(ts_ms).expect("Travelling in time machine")
@@ -916,7 +2018,16 @@ impl AuthorityState {
         // If we do not have a start, pick next sequence number that has
         // not yet been put into a batch.
         let start = match request.start {
-            Some(start) => start,
+            Some(start) => {
+                let retained_from = self.database.batch_history_start()?;
+                if start < retained_from {
+                    return Err(SuiError::BatchHistoryUnavailable {
+                        requested: start,
+                        retained_from,
+                    });
+                }
+                start
+            }
             None => {
                 self.last_batch()?
                     .expect("Authority is always initialized with a batch")
@@ -987,6 +2098,22 @@ impl AuthorityState {
         }
     }
 
+    /// Returns the signers of checkpoint `seq`. Fails with `UnsupportedFeatureError` on nodes
+    /// that do not keep a checkpoint store.
+    pub fn get_checkpoint_signers(
+        &self,
+        seq: CheckpointSequenceNumber,
+    ) -> SuiResult<Option<CheckpointSigners>> {
+        let checkpoint_store = self
+            .checkpoints
+            .as_ref()
+            .ok_or(SuiError::UnsupportedFeatureError {
+                error: "Checkpoint not supported".to_owned(),
+            })?
+            .lock();
+        checkpoint_store.get_checkpoint_signers(seq, |epoch| self.committee_for_epoch(epoch))
+    }
+
     // TODO: This function takes both committee and genesis as parameter.
     // Technically genesis already contains committee information. Could consider merging them.
     pub async fn new(
@@ -1008,11 +2135,16 @@ impl AuthorityState {
                 .expect("We defined natives to not fail here"),
         );
 
-        // Only initialize an empty database.
-        if store
+        let database_is_empty = store
             .database_is_empty()
-            .expect("Database read should not fail.")
-        {
+            .expect("Database read should not fail.");
+        assert!(
+            !(database_is_empty && store.is_secondary()),
+            "The primary store must be initialized before a secondary can follow it"
+        );
+
+        // Only initialize an empty database.
+        if database_is_empty {
             let mut genesis_ctx = genesis.genesis_ctx().to_owned();
             for genesis_modules in genesis.modules() {
                 store_package_and_init_modules_for_genesis(
@@ -1043,11 +2175,11 @@ impl AuthorityState {
             .get_last_epoch_info()
             .expect("Fail to load the current epoch info");
 
-        let event_handler = event_store.map(|es| Arc::new(EventHandler::new(store.clone(), es)));
+        let event_handler = event_store
+            .map(|es| Arc::new(EventHandler::new(store.clone(), es, prometheus_registry)));
 
         let mut state = AuthorityState {
-            name,
-            secret,
+            identity: ArcSwap::from(Arc::new(AuthorityIdentity { name, secret })),
             committee: ArcSwap::from(Arc::new(current_epoch_info.committee)),
             halted: AtomicBool::new(current_epoch_info.validator_halted),
             _native_functions: native_functions,
@@ -1067,7 +2199,31 @@ impl AuthorityState {
             consensus_guardrail: AtomicUsize::new(0),
             metrics: AuthorityMetrics::new(prometheus_registry),
             latest_checkpoint_num: AtomicU64::new(0),
+            last_tx_timestamp_ms: AtomicU64::new(0),
+            storage_failure: Mutex::new(None),
+            sync_progress: SyncProgress::default(),
+            post_processed_next: AtomicU64::new(u64::MAX),
+            consensus_status: Mutex::new(ConsensusStatus::default()),
+            execution_trace: ExecutionTraceSampler::default(),
+            execution_load: Mutex::new(ExecutionLoadTracker::default()),
+            input_prefetch_concurrency: 0,
+            shutdown: watch::channel(false).0,
+            #[cfg(test)]
+            index_failures: AtomicU64::new(0),
         };
+        let committee = state.committee.load();
+        state.metrics.epoch.set(committee.epoch as i64);
+        state
+            .metrics
+            .committee_size
+            .set(committee.num_members() as i64);
+        drop(committee);
+
+        // A secondary store mirrors a primary that does its own recovery, and cannot be
+        // written to anyway.
+        if state.database.is_secondary() {
+            return state;
+        }
 
         // Process tx recovery log first, so that the batch and checkpoint recovery (below)
         // don't observe partially-committed txes.
@@ -1191,10 +2347,50 @@ impl AuthorityState {
         self.database.clone()
     }
 
+    /// The name of this authority.
+    pub fn name(&self) -> AuthorityName {
+        self.identity.load().name
+    }
+
+    /// The key this authority signs with.
+    pub fn secret(&self) -> StableSyncAuthoritySigner {
+        self.identity.load().secret.clone()
+    }
+
+    /// The name and key of this authority, loaded together so that a signature always goes
+    /// with the name that verifies it.
+    pub fn identity(&self) -> Arc<AuthorityIdentity> {
+        self.identity.load_full()
+    }
+
+    /// Sign as the public key of `key_pair` from now on, including checkpoints. Called at the
+    /// start of an epoch whose committee knows this authority by that key.
+    pub(crate) fn rotate_key(&self, key_pair: KeyPair) {
+        let name = *key_pair.public_key_bytes();
+        let secret: StableSyncAuthoritySigner = Arc::pin(key_pair);
+        if let Some(checkpoints) = &self.checkpoints {
+            checkpoints.lock().rotate_signing_key(name, secret.clone());
+        }
+        self.identity
+            .store(Arc::new(AuthorityIdentity { name, secret }));
+    }
+
     pub fn clone_committee(&self) -> Committee {
         self.committee.load().clone().deref().clone()
     }
 
+    /// The committee of `epoch`, or None if this authority didn't reach it yet.
+    pub fn committee_for_epoch(&self, epoch: EpochId) -> SuiResult<Option<Arc<Committee>>> {
+        let committee = self.committee.load_full();
+        if committee.epoch == epoch {
+            return Ok(Some(committee));
+        }
+        Ok(self
+            .database
+            .get_epoch_info(epoch)?
+            .map(|info| Arc::new(info.committee)))
+    }
+
     async fn get_object(&self, object_id: &ObjectID) -> Result<Option<Object>, SuiError> {
         self.database.get_object(object_id)
     }
@@ -1221,6 +2417,44 @@ impl AuthorityState {
         Ok(result)
     }
 
+    /// Total supply of the coins of type `coin_type`, and the TreasuryCap it was read from.
+    /// The supply of SUI is kept by the system state object rather than a cap.
+    ///
+    /// Other caps are found through the indexes, which only know the caps created by the
+    /// transactions indexed since the index of caps was added. Coins that never had a cap, e.g.
+    /// whose supply was turned into a `Supply` kept by another object, are not found either, nor
+    /// are coins whose cap is wrapped in another object, since then it can't be read. The supply
+    /// is read from the latest version of the cap every time: mints change it at any point of
+    /// an epoch, so it can't be cached.
+    pub async fn get_total_supply(
+        &self,
+        coin_type: &StructTag,
+    ) -> SuiResult<(u64, Option<ObjectID>)> {
+        if *coin_type == GAS::type_() {
+            let system_state = self.get_sui_system_state_object().await?;
+            return Ok((system_state.treasury_cap.value, None));
+        }
+
+        let cap_id = self
+            .get_indexes()?
+            .get_treasury_cap(coin_type)?
+            .ok_or_else(|| SuiError::GenericAuthorityError {
+                error: format!("No TreasuryCap is known for {coin_type}"),
+            })?;
+        let cap_object = self
+            .database
+            .get_object(&cap_id)?
+            .ok_or(SuiError::ObjectNotFound { object_id: cap_id })?;
+        let cap: TreasuryCap = cap_object
+            .data
+            .try_as_move()
+            .and_then(|object| bcs::from_bytes(object.contents()).ok())
+            .ok_or_else(|| SuiError::GenericAuthorityError {
+                error: format!("{cap_id} is not a TreasuryCap"),
+            })?;
+        Ok((cap.total_supply.value, Some(cap_id)))
+    }
+
     pub async fn get_object_read(&self, object_id: &ObjectID) -> Result<ObjectRead, SuiError> {
         match self.database.get_latest_parent_entry(*object_id)? {
             None => Ok(ObjectRead::NotExists(*object_id)),
@@ -1246,10 +2480,221 @@ impl AuthorityState {
         }
     }
 
+    /// Read `object_id` as it was at `version`. A version the object never had reads as
+    /// NotExists, and one whose contents are no longer stored is an ObjectVersionUnavailable
+    /// error.
+    pub async fn get_past_object_read(
+        &self,
+        object_id: &ObjectID,
+        version: SequenceNumber,
+    ) -> Result<ObjectRead, SuiError> {
+        match self.database.get_past_object(object_id, version)? {
+            None => Ok(ObjectRead::NotExists(*object_id)),
+            Some((obj_ref, None)) => Ok(ObjectRead::Deleted(obj_ref)),
+            Some((obj_ref, Some(object))) => {
+                let layout =
+                    object.get_layout(ObjectFormatOptions::default(), &self.module_cache)?;
+                Ok(ObjectRead::Exists(obj_ref, object, layout))
+            }
+        }
+    }
+
+    /// The checkpoint transaction `digest` is included in, or None if it wasn't checkpointed
+    /// yet. Genesis counts as part of checkpoint 0. Validators know it from their checkpoint
+    /// store, and full nodes with indexes from the checkpoints node sync verified, so not for
+    /// those before a trusted checkpoint.
+    pub fn get_transaction_checkpoint(
+        &self,
+        digest: &TransactionDigest,
+    ) -> SuiResult<Option<CheckpointSequenceNumber>> {
+        if *digest == TransactionDigest::genesis() {
+            return Ok(Some(0));
+        }
+        let checkpoints = match &self.checkpoints {
+            Some(checkpoints) => checkpoints,
+            None => return self.get_indexes()?.get_transaction_checkpoint(digest),
+        };
+        let effects = match self.database.get_effects(digest) {
+            Ok(effects) => effects,
+            Err(SuiError::TransactionNotFound { .. }) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let digests = ExecutionDigests::new(*digest, effects.digest());
+        Ok(checkpoints
+            .lock()
+            .transactions_to_checkpoint
+            .get(&digests)?
+            .map(|(checkpoint, _)| checkpoint))
+    }
+
+    /// Check each claimed pair of transaction and effects digests against the effects of the
+    /// certificate this node executed, with the checkpoint of those matching, when known.
+    pub fn verify_effects(
+        &self,
+        claimed: &[ExecutionDigests],
+    ) -> SuiResult<Vec<SuiEffectsVerification>> {
+        claimed
+            .iter()
+            .map(|digests| {
+                let valid = match self.database.get_effects(&digests.transaction) {
+                    Ok(effects) => effects.digest() == digests.effects,
+                    Err(SuiError::TransactionNotFound { .. }) => false,
+                    Err(e) => return Err(e),
+                };
+                let checkpoint = if valid {
+                    match self.get_transaction_checkpoint(&digests.transaction) {
+                        Ok(checkpoint) => checkpoint,
+                        // Neither a checkpoint store nor indexes to tell.
+                        Err(SuiError::UnsupportedFeatureError { .. }) => None,
+                        Err(e) => return Err(e),
+                    }
+                } else {
+                    None
+                };
+                Ok(SuiEffectsVerification {
+                    transaction_digest: digests.transaction,
+                    valid,
+                    checkpoint,
+                })
+            })
+            .collect()
+    }
+
+    /// Record that the transactions of `contents` are in checkpoint `seq`, once node sync
+    /// verified it. Not recorded without indexes.
+    pub(crate) fn index_checkpoint_contents(
+        &self,
+        seq: CheckpointSequenceNumber,
+        contents: &CheckpointContents,
+    ) -> SuiResult {
+        match &self.indexes {
+            Some(indexes) => indexes.index_checkpoint(
+                seq,
+                contents
+                    .transactions
+                    .iter()
+                    .map(|digests| digests.transaction),
+            ),
+            None => Ok(()),
+        }
+    }
+
+    /// The latest reference of `object_id` written by a transaction of `checkpoint` or before,
+    /// with the digest of that transaction. None if none of them wrote the object.
+    pub fn get_parent_entry_at_checkpoint(
+        &self,
+        object_id: ObjectID,
+        checkpoint: CheckpointSequenceNumber,
+    ) -> SuiResult<Option<(ObjectRef, TransactionDigest)>> {
+        // Walk the versions back from the latest, those of later checkpoints are the most
+        // recent ones.
+        let mut version = SequenceNumber::MAX;
+        while let Some((object_ref, digest)) = self
+            .database
+            .get_parent_entry_at_or_before(object_id, version)?
+        {
+            if matches!(self.get_transaction_checkpoint(&digest)?, Some(seq) if seq <= checkpoint) {
+                return Ok(Some((object_ref, digest)));
+            }
+            version = match object_ref.1.decrement() {
+                Ok(version) => version,
+                Err(_) => break,
+            };
+        }
+        Ok(None)
+    }
+
+    /// The objects `owner` had once the transactions of `checkpoint` and those before were
+    /// executed, with their reference then. Those are found among the objects it owns now and
+    /// those the transactions from or to it wrote, so this needs indexes. Fails with
+    /// ObjectVersionUnavailable if one of those versions was pruned.
+    pub fn get_owner_objects_at_checkpoint(
+        &self,
+        owner: Owner,
+        checkpoint: CheckpointSequenceNumber,
+    ) -> SuiResult<Vec<(ObjectRef, Object)>> {
+        let indexes = self.get_indexes()?;
+        let mut candidates: BTreeSet<ObjectID> = self
+            .database
+            .get_owner_objects(owner)?
+            .into_iter()
+            .map(|info| info.object_id)
+            .collect();
+        if let Ok(address) = owner.get_owner_address() {
+            let transactions = indexes
+                .get_transactions_from_addr(address)?
+                .into_iter()
+                .chain(indexes.get_transactions_to_addr(address)?);
+            for (_, digest) in transactions {
+                let effects = self.database.get_effects(&digest)?;
+                candidates.extend(
+                    effects
+                        .mutated_and_created()
+                        .chain(effects.unwrapped.iter())
+                        .map(|(object_ref, _)| object_ref)
+                        .chain(effects.deleted.iter())
+                        .chain(effects.wrapped.iter())
+                        .map(|object_ref| object_ref.0),
+                );
+            }
+        }
+
+        let mut objects = Vec::new();
+        for object_id in candidates {
+            let object_ref = match self.get_parent_entry_at_checkpoint(object_id, checkpoint)? {
+                Some((object_ref, _)) if object_ref.2.is_alive() => object_ref,
+                _ => continue,
+            };
+            let object = self
+                .database
+                .get_object_by_key(&object_id, object_ref.1)?
+                .ok_or(SuiError::ObjectVersionUnavailable {
+                    object_id,
+                    version: object_ref.1,
+                })?;
+            if object.owner == owner {
+                objects.push((object_ref, object));
+            }
+        }
+        Ok(objects)
+    }
+
+    /// Read `object_id` as it was once the transactions of `checkpoint` and those before were
+    /// executed, whatever was executed since. Fails with ObjectVersionUnavailable if that
+    /// version was pruned.
+    pub async fn get_object_read_at_checkpoint(
+        &self,
+        object_id: &ObjectID,
+        checkpoint: CheckpointSequenceNumber,
+    ) -> Result<ObjectRead, SuiError> {
+        match self.get_parent_entry_at_checkpoint(*object_id, checkpoint)? {
+            None => Ok(ObjectRead::NotExists(*object_id)),
+            Some((obj_ref, _)) => self.get_past_object_read(object_id, obj_ref.1).await,
+        }
+    }
+
+    /// Returns the owned objects locked to a transaction that wasn't executed yet, only those
+    /// owned by `owner` when set.
+    pub async fn get_locked_objects(
+        &self,
+        owner: Option<SuiAddress>,
+    ) -> SuiResult<Vec<LockedObject>> {
+        self.database.get_locked_objects(owner).await
+    }
+
     pub fn get_owner_objects(&self, owner: Owner) -> SuiResult<Vec<ObjectInfo>> {
         self.database.get_owner_objects(owner)
     }
 
+    /// Returns the objects of `owner` whose type matches `type_filter`.
+    pub fn get_owner_objects_by_type(
+        &self,
+        owner: Owner,
+        type_filter: &SuiObjectTypeFilter,
+    ) -> SuiResult<Vec<ObjectInfo>> {
+        self.database.get_owner_objects_by_type(owner, type_filter)
+    }
+
     pub fn get_total_transaction_number(&self) -> Result<u64, anyhow::Error> {
         QueryHelpers::get_total_transaction_number(&self.database)
     }
@@ -1276,7 +2721,146 @@ impl AuthorityState {
         QueryHelpers::get_transaction(&self.database, digest)
     }
 
-    fn get_indexes(&self) -> SuiResult<Arc<IndexStore>> {
+    /// Transaction `digest` with the parts of what it did asked for in `options`, see
+    /// [`QueryHelpers::get_transaction_block`].
+    pub async fn get_transaction_block(
+        &self,
+        digest: TransactionDigest,
+        options: SuiTransactionBlockOptions,
+    ) -> Result<SuiTransactionBlockResponse, anyhow::Error> {
+        let mut response = QueryHelpers::get_transaction_block(
+            &self.database,
+            &self.module_cache,
+            digest,
+            options,
+        )?;
+        if self.indexes.is_some() {
+            response.timestamp_ms = self.get_timestamp_ms(&digest).await?;
+        }
+        Ok(response)
+    }
+
+    pub async fn get_transaction_dependencies(
+        &self,
+        digest: TransactionDigest,
+        depth: u64,
+    ) -> Result<Vec<(TransactionDigest, Vec<TransactionDigest>)>, anyhow::Error> {
+        QueryHelpers::get_transaction_dependencies(&self.database, digest, depth)
+    }
+
+    pub async fn select_gas_coins(
+        &self,
+        address: SuiAddress,
+        budget: u64,
+    ) -> Result<Vec<ObjectRef>, anyhow::Error> {
+        QueryHelpers::select_gas_coins(&self.database, address, budget).await
+    }
+
+    pub async fn get_balances(
+        &self,
+        address: SuiAddress,
+        coin_type: Option<StructTag>,
+    ) -> Result<Vec<SuiCoinBalance>, anyhow::Error> {
+        QueryHelpers::get_balances(&self.database, address, coin_type.as_ref()).await
+    }
+
+    pub async fn get_coins(
+        &self,
+        address: SuiAddress,
+        coin_type: StructTag,
+        largest_first: bool,
+        exclude_locked: bool,
+        cursor: Option<ObjectID>,
+        limit: usize,
+    ) -> Result<SuiCoinPage, anyhow::Error> {
+        QueryHelpers::get_coins(
+            &self.database,
+            address,
+            &coin_type,
+            largest_first,
+            exclude_locked,
+            cursor,
+            limit,
+        )
+        .await
+    }
+
+    /// The objects `owner` had as of `checkpoint` whose type matches `type_filter`, see
+    /// [`Self::get_owner_objects_at_checkpoint`].
+    pub fn get_owner_objects_by_type_at_checkpoint(
+        &self,
+        owner: Owner,
+        type_filter: &SuiObjectTypeFilter,
+        checkpoint: CheckpointSequenceNumber,
+    ) -> SuiResult<Vec<(ObjectRef, Object)>> {
+        let mut objects = self.get_owner_objects_at_checkpoint(owner, checkpoint)?;
+        objects.retain(|(object_ref, object)| {
+            type_filter.matches(&ObjectInfo::new(object_ref, object).type_)
+        });
+        Ok(objects)
+    }
+
+    /// Like [`Self::select_gas_coins`], with the coins `address` had as of `checkpoint`. No
+    /// coin counts as locked then.
+    pub fn select_gas_coins_at_checkpoint(
+        &self,
+        address: SuiAddress,
+        budget: u64,
+        checkpoint: CheckpointSequenceNumber,
+    ) -> Result<Vec<ObjectRef>, anyhow::Error> {
+        let coin_type = SuiObjectTypeFilter::Exact(GasCoin::type_().to_string());
+        let coins = self.get_owner_objects_by_type_at_checkpoint(
+            Owner::AddressOwner(address),
+            &coin_type,
+            checkpoint,
+        )?;
+        query_helpers::gas_coins_covering(address, coins, &HashSet::new(), budget)
+    }
+
+    /// Like [`Self::get_balances`], with the coins `address` had as of `checkpoint`. No coin
+    /// counts as locked then.
+    pub fn get_balances_at_checkpoint(
+        &self,
+        address: SuiAddress,
+        coin_type: Option<StructTag>,
+        checkpoint: CheckpointSequenceNumber,
+    ) -> Result<Vec<SuiCoinBalance>, anyhow::Error> {
+        let coins = self.get_owner_objects_by_type_at_checkpoint(
+            Owner::AddressOwner(address),
+            &query_helpers::coins_type_filter(coin_type.as_ref()),
+            checkpoint,
+        )?;
+        query_helpers::coin_balances(coins, &HashSet::new(), coin_type.as_ref())
+    }
+
+    /// Like [`Self::get_coins`], with the coins `address` had as of `checkpoint`. No coin
+    /// counts as locked then.
+    pub fn get_coins_at_checkpoint(
+        &self,
+        address: SuiAddress,
+        coin_type: StructTag,
+        largest_first: bool,
+        cursor: Option<ObjectID>,
+        limit: usize,
+        checkpoint: CheckpointSequenceNumber,
+    ) -> Result<SuiCoinPage, anyhow::Error> {
+        let coins = self.get_owner_objects_by_type_at_checkpoint(
+            Owner::AddressOwner(address),
+            &query_helpers::coins_type_filter(Some(&coin_type)),
+            checkpoint,
+        )?;
+        query_helpers::coin_page(
+            address,
+            coins,
+            &HashSet::new(),
+            largest_first,
+            false,
+            cursor,
+            limit,
+        )
+    }
+
+    pub(crate) fn get_indexes(&self) -> SuiResult<Arc<IndexStore>> {
         match &self.indexes {
             Some(i) => Ok(i.clone()),
             None => Err(SuiError::UnsupportedFeatureError {
@@ -1303,6 +2887,17 @@ impl AuthorityState {
         Ok(self.get_indexes()?.get_timestamp_ms(digest)?)
     }
 
+    /// Up to `limit` object types with their number of live objects, most common first,
+    /// skipping the `offset` most common ones. Counted as transactions are indexed, and
+    /// reconciled with the owner index periodically.
+    pub fn get_object_type_stats(
+        &self,
+        offset: usize,
+        limit: usize,
+    ) -> SuiResult<Vec<(String, u64)>> {
+        self.get_indexes()?.object_type_counts(offset, limit)
+    }
+
     pub async fn get_transactions_by_input_object(
         &self,
         object: ObjectID,
@@ -1328,6 +2923,12 @@ impl AuthorityState {
         Ok(self.get_indexes()?.get_transactions_from_addr(address)?)
     }
 
+    /// Number of transactions sent by `address` that post-processing indexed, so it may lag
+    /// execution briefly.
+    pub async fn get_transaction_count(&self, address: SuiAddress) -> Result<u64, anyhow::Error> {
+        Ok(self.get_indexes()?.count_transactions_from_addr(address)?)
+    }
+
     pub async fn get_transactions_to_addr(
         &self,
         address: SuiAddress,
@@ -1335,6 +2936,35 @@ impl AuthorityState {
         Ok(self.get_indexes()?.get_transactions_to_addr(address)?)
     }
 
+    /// Up to `limit` packages published by transactions from `cursor` on, or else from
+    /// checkpoint `since_checkpoint` on, with the sequence number and digest of the
+    /// transaction and its sender. Transactions are in the order this node executed them, so
+    /// the first may be preceded by transactions of later checkpoints. Only nodes that keep a
+    /// checkpoint store can start at a checkpoint other than 0.
+    pub fn get_published_packages(
+        &self,
+        since_checkpoint: CheckpointSequenceNumber,
+        cursor: Option<TxSequenceNumber>,
+        limit: usize,
+    ) -> SuiResult<Vec<(TxSequenceNumber, ObjectID, SuiAddress, TransactionDigest)>> {
+        let since = match (cursor, since_checkpoint) {
+            (Some(cursor), _) => cursor,
+            (None, 0) => 0,
+            (None, checkpoint) => self
+                .checkpoints
+                .as_ref()
+                .ok_or(SuiError::UnsupportedFeatureError {
+                    error: "Checkpoint not supported".to_owned(),
+                })?
+                .lock()
+                .first_executed_transaction(checkpoint)?
+                .ok_or_else(|| SuiError::GenericAuthorityError {
+                    error: format!("No transaction of checkpoint {checkpoint} was executed"),
+                })?,
+        };
+        self.get_indexes()?.get_published_packages(since, limit)
+    }
+
     /// Returns a full handle to the event store, including inserts... so be careful!
     fn get_event_store(&self) -> Option<Arc<EventStoreType>> {
         self.event_handler
@@ -1363,6 +2993,105 @@ impl AuthorityState {
             .await
     }
 
+    /// Returns the most recent events of a type within a range of time, latest first
+    pub async fn get_events_by_type(
+        &self,
+        start_time: u64,
+        end_time: u64,
+        event_type: EventType,
+        limit: usize,
+    ) -> Result<Vec<StoredEvent>, SuiError> {
+        let es = self.get_event_store().ok_or(SuiError::NoEventStore)?;
+        es.events_by_type(start_time, end_time, event_type, limit)
+            .await
+    }
+
+    /// Returns the most recent events of a module within a range of time, latest first
+    pub async fn get_events_by_module(
+        &self,
+        start_time: u64,
+        end_time: u64,
+        module: ModuleId,
+        limit: usize,
+    ) -> Result<Vec<StoredEvent>, SuiError> {
+        let es = self.get_event_store().ok_or(SuiError::NoEventStore)?;
+        es.events_by_module_id(start_time, end_time, module, limit)
+            .await
+    }
+
+    /// Returns the most recent Move events whose type matches `filter` within a range of time,
+    /// latest first
+    pub async fn get_events_by_move_event_type(
+        &self,
+        start_time: u64,
+        end_time: u64,
+        filter: MoveEventTypeFilter,
+        limit: usize,
+    ) -> Result<Vec<StoredEvent>, SuiError> {
+        let es = self.get_event_store().ok_or(SuiError::NoEventStore)?;
+        es.events_by_move_event_type(start_time, end_time, filter, limit)
+            .await
+    }
+
+    /// Returns the most recent events of a sender within a range of time, latest first
+    pub async fn get_events_by_sender(
+        &self,
+        start_time: u64,
+        end_time: u64,
+        sender: SuiAddress,
+        limit: usize,
+    ) -> Result<Vec<StoredEvent>, SuiError> {
+        let es = self.get_event_store().ok_or(SuiError::NoEventStore)?;
+        es.events_by_sender(start_time, end_time, sender, limit)
+            .await
+    }
+
+    /// Returns the most recent events about an object within a range of time, latest first
+    pub async fn get_events_by_object(
+        &self,
+        start_time: u64,
+        end_time: u64,
+        object: ObjectID,
+        limit: usize,
+    ) -> Result<Vec<StoredEvent>, SuiError> {
+        let es = self.get_event_store().ok_or(SuiError::NoEventStore)?;
+        es.events_by_object(start_time, end_time, object, limit)
+            .await
+    }
+
+    /// The oldest data each store retains, which are also reported as the oldest_retained
+    /// gauges. Stores that are empty or not kept aren't reported.
+    pub async fn retention_watermarks(&self) -> SuiResult<RetentionWatermarks> {
+        let events = match self.get_event_store() {
+            Some(es) => es.oldest_timestamp().await?,
+            None => None,
+        };
+        let watermarks = RetentionWatermarks {
+            objects: self.database.oldest_retained_sequence()?,
+            batches: self.database.batch_history_start()?,
+            checkpoints: self
+                .checkpoints
+                .as_ref()
+                .and_then(|checkpoints| checkpoints.lock().oldest_stored_checkpoint()),
+            events,
+        };
+        let gauges = [
+            ("objects", Some(watermarks.objects)),
+            ("batches", Some(watermarks.batches)),
+            ("checkpoints", watermarks.checkpoints),
+            ("events", watermarks.events),
+        ];
+        for (store, watermark) in gauges {
+            if let Some(watermark) = watermark {
+                self.metrics
+                    .oldest_retained
+                    .with_label_values(&[store])
+                    .set(watermark as i64);
+            }
+        }
+        Ok(watermarks)
+    }
+
     pub async fn insert_genesis_object(&self, object: Object) {
         self.database
             .insert_genesis_object(object)
@@ -1440,6 +3169,14 @@ impl AuthorityState {
 
         debug!(digest = ?certificate.digest(), "commit_certificate finished");
 
+        // The write batch may have been only partially applied, so nothing else is executed
+        // on top of this store until the node is restarted and recovers from the log.
+        if let Err(e) = &res {
+            if e.is_storage_error() {
+                self.record_storage_failure(e);
+            }
+        }
+
         res
 
         // implicitly we drop the ticket here and that notifies the batch manager
@@ -1505,6 +3242,40 @@ impl AuthorityState {
     ) -> Result<Option<(ObjectRef, TransactionDigest)>, SuiError> {
         self.database.get_latest_parent_entry(object_id)
     }
+
+    /// The certificate and effects of the transaction that produced the current version of
+    /// `object_id`, or that deleted or wrapped it.
+    pub async fn get_latest_modifying_transaction(
+        &self,
+        object_id: ObjectID,
+    ) -> Result<(CertifiedTransaction, TransactionEffects), anyhow::Error> {
+        let (_, digest) = self
+            .get_latest_parent_entry(object_id)
+            .await?
+            .ok_or(SuiError::ObjectNotFound { object_id })?;
+        self.get_transaction(digest).await
+    }
+
+    /// The current version of the shared object `object_id`, and whether consensus sequenced
+    /// transactions on it that aren't executed yet.
+    pub fn get_shared_object_version(
+        &self,
+        object_id: ObjectID,
+    ) -> SuiResult<(SequenceNumber, bool)> {
+        let object = self
+            .database
+            .get_object(&object_id)?
+            .ok_or(SuiError::ObjectNotFound { object_id })?;
+        if !object.is_shared() {
+            return Err(SuiError::NotSharedObject { object_id });
+        }
+        // The schedule holds the version the next sequenced transaction will be assigned.
+        let contended = matches!(
+            self.database.get_schedule(&object_id)?,
+            Some(next) if next > object.version()
+        );
+        Ok((object.version(), contended))
+    }
 }
 
 #[async_trait]
@@ -1518,6 +3289,7 @@ impl ExecutionState for AuthorityState {
         consensus_index: ExecutionIndices,
         transaction: Self::Transaction,
     ) -> Result<Vec<u8>, Self::Error> {
+        self.check_healthy()?;
         match transaction {
             ConsensusTransaction::UserTransaction(certificate) => {
                 // Ensure the input is a shared object certificate. Remember that Byzantine authorities
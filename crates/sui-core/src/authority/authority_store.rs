@@ -6,15 +6,18 @@ use crate::epoch::EpochInfoLocals;
 use crate::gateway_state::GatewayTxSeqNumber;
 use crate::transaction_input_checker::InputObjects;
 use narwhal_executor::ExecutionIndices;
-use rocksdb::Options;
-use serde::{Deserialize, Serialize};
+use rocksdb::{ColumnFamilyDescriptor, DBWithThreadMode, MultiThreaded, Options};
+use serde::de::{self, SeqAccess, Visitor};
+use serde::ser::SerializeTuple;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_with::serde_as;
+use std::fmt;
 use std::path::Path;
 use sui_storage::{
-    default_db_options,
+    create_db_checkpoint, default_db_options, flush_db,
     mutex_table::{LockGuard, MutexTable},
     write_ahead_log::DBWriteAheadLog,
-    LockService,
+    ColumnFamilyGroup, LockService,
 };
 use tokio::sync::Notify;
 
@@ -26,9 +29,42 @@ use sui_types::crypto::{AuthoritySignInfo, EmptySignInfo};
 use sui_types::object::{Owner, OBJECT_START_VERSION};
 use tokio_retry::strategy::{jitter, ExponentialBackoff};
 use tracing::{debug, error, info, trace};
-use typed_store::rocks::{DBBatch, DBMap};
+use typed_store::rocks::{DBBatch, DBMap, TypedStoreError};
 use typed_store::{reopen, traits::Map};
 
+/// Column families of executed transactions, which execution appends to but mostly reads to
+/// serve the history of transactions and to sync other nodes.
+const HISTORY_COLUMN_FAMILIES: &[&str] = &[
+    "certificates",
+    "effects",
+    "parent_sync",
+    "executed_sequence",
+    "batches",
+];
+
+/// Separates the column families of the store written and read by the execution of every
+/// transaction (objects, owner index, shared object locks, pending certificates) from the history
+/// of executed transactions, so that heavy reads of the history don't evict the working set of
+/// execution from the block cache. The locks of owned objects are in the separate database of
+/// the `LockService`.
+pub struct StoreColumnFamilyGroups {
+    pub execution: ColumnFamilyGroup,
+    pub history: ColumnFamilyGroup,
+    /// Flushes and compactions run concurrently, shared by all column families of the store.
+    /// The RocksDB default when unset.
+    pub max_background_jobs: Option<i32>,
+}
+
+/// Whether opening a database failed because another process holds its lock, such as the
+/// previous process of a node that is still shutting down.
+pub fn is_db_locked_error(error: &SuiError) -> bool {
+    matches!(
+        error,
+        SuiError::StorageError(TypedStoreError::RocksDBError(message))
+            if message.contains("/LOCK: ")
+    )
+}
+
 pub type AuthorityStore = SuiDataStore<AuthoritySignInfo>;
 pub type GatewayStore = SuiDataStore<EmptySignInfo>;
 
@@ -36,10 +72,83 @@ pub type InternalSequenceNumber = u64;
 
 const NUM_SHARDS: usize = 4096;
 
+/// Object versions deleted per write when pruning.
+const PRUNE_BATCH_SIZE: usize = 10_000;
+
+/// The key where the object the next pruning pass starts from is stored in the database.
+const OBJECT_PRUNING_CURSOR_ADDR: u64 = 0;
+
 /// The key where the latest consensus index is stored in the database.
 // TODO: Make a single table (e.g., called `variables`) storing all our lonely variables in one place.
 const LAST_CONSENSUS_INDEX_ADDR: u64 = 0;
 
+/// Owner type index entries written per batch when building the index of an existing store.
+const OWNER_TYPE_INDEX_BATCH_SIZE: usize = 10_000;
+
+/// The key of the owner type index: the owner, the type of the object and its ID.
+type OwnerTypeKey = (Owner, TypeKey, ObjectID);
+
+/// The type of an object in an owner type index key. Unlike a `String`, which is prefixed by its
+/// length, it is encoded as its bytes followed by a 0 byte, so that the keys of the types that
+/// start with the same prefix are next to each other.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct TypeKey(String);
+
+impl Serialize for TypeKey {
+    fn serialize<Ser: Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        let mut tuple = serializer.serialize_tuple(self.0.len() + 1)?;
+        for byte in self.0.as_bytes().iter().chain(iter::once(&0)) {
+            tuple.serialize_element(byte)?;
+        }
+        tuple.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for TypeKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct TypeKeyVisitor;
+
+        impl<'de> Visitor<'de> for TypeKeyVisitor {
+            type Value = TypeKey;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a type terminated by a 0 byte")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<TypeKey, A::Error> {
+                let mut bytes = Vec::new();
+                loop {
+                    match seq.next_element::<u8>()? {
+                        Some(0) => break,
+                        Some(byte) => bytes.push(byte),
+                        None => return Err(de::Error::custom("unterminated type")),
+                    }
+                }
+                String::from_utf8(bytes)
+                    .map(TypeKey)
+                    .map_err(de::Error::custom)
+            }
+        }
+
+        // The length isn't encoded, the visitor reads up to the terminator.
+        deserializer.deserialize_tuple(usize::MAX, TypeKeyVisitor)
+    }
+}
+
+fn owner_type_key(info: &ObjectInfo) -> OwnerTypeKey {
+    (info.owner, TypeKey(info.type_.clone()), info.object_id)
+}
+
+/// The owner type index key of `object`, with the type `ObjectInfo::new` gives it.
+fn object_owner_type_key(object: &Object) -> OwnerTypeKey {
+    let type_ = object
+        .data
+        .type_()
+        .map(|tag| tag.to_string())
+        .unwrap_or_else(|| "Package".to_string());
+    (object.owner, TypeKey(type_), object.id())
+}
+
 /// ALL_OBJ_VER determines whether we want to store all past
 /// versions of every object in the store. Authority doesn't store
 /// them, but other entities such as replicas will.
@@ -71,6 +180,11 @@ pub struct SuiDataStore<S> {
     /// by a specific user, and their object reference.
     owner_index: DBMap<(Owner, ObjectID), ObjectInfo>,
 
+    /// The same entries as `owner_index`, keyed by owner, type and object ID instead, so that
+    /// the objects of an owner with a given type or type prefix are listed without scanning all
+    /// the objects of the owner.
+    owner_type_index: DBMap<OwnerTypeKey, ObjectInfo>,
+
     /// This is map between the transaction digest and transactions found in the `transaction_lock`.
     transactions: DBMap<TransactionDigest, TransactionEnvelope<S>>,
 
@@ -128,41 +242,145 @@ pub struct SuiDataStore<S> {
 
     /// Map from each epoch ID to the epoch information.
     epochs: DBMap<EpochId, EpochInfoLocals>,
+
+    /// A single entry table storing the object the next pass of `prune_object_versions`
+    /// starts from.
+    object_pruning_cursor: DBMap<u64, ObjectID>,
+
+    /// Set when the store is a RocksDB secondary instance tailing another process' store.
+    /// Secondary instances reject all writes.
+    secondary_db: Option<Arc<DBWithThreadMode<MultiThreaded>>>,
+
+    /// Set with `with_object_cache`, serves `get_object` for recently read objects.
+    object_cache: Option<ObjectCache>,
+
+    /// Set with `with_group_commit`, writes the objects of concurrent transactions together.
+    group_committer: Option<GroupCommitter>,
 }
 
 impl<S: Eq + Serialize + for<'de> Deserialize<'de>> SuiDataStore<S> {
     /// Open an authority store by directory path
     pub fn open<P: AsRef<Path>>(path: P, db_options: Option<Options>) -> Self {
-        let (options, point_lookup) = default_db_options(db_options, None);
+        Self::open_with_column_family_groups(path, db_options, None)
+    }
+
+    /// Open an authority store whose column families are split into `groups`. The column
+    /// families are the same either way, so a store can be reopened with or without groups.
+    pub fn open_with_column_family_groups<P: AsRef<Path>>(
+        path: P,
+        db_options: Option<Options>,
+        groups: Option<&StoreColumnFamilyGroups>,
+    ) -> Self {
+        Self::try_open_with_column_family_groups(path, db_options, groups).expect("Cannot open DB.")
+    }
+
+    /// Like [`Self::open_with_column_family_groups`], but returns the error of opening the
+    /// database, e.g. while another process holds its lock, see [`is_db_locked_error`].
+    pub fn try_open_with_column_family_groups<P: AsRef<Path>>(
+        path: P,
+        db_options: Option<Options>,
+        groups: Option<&StoreColumnFamilyGroups>,
+    ) -> SuiResult<Self> {
+        let (mut options, point_lookup) = default_db_options(db_options, None);
+        if let Some(jobs) = groups.and_then(|groups| groups.max_background_jobs) {
+            options.set_max_background_jobs(jobs);
+        }
 
         let db = {
             let path = &path;
             let db_options = Some(options.clone());
-            let opt_cfs: &[(&str, &rocksdb::Options)] = &[
-                ("objects", &point_lookup),
-                ("transactions", &point_lookup),
-                ("owner_index", &options),
-                ("certificates", &point_lookup),
-                ("pending_execution", &options),
-                ("parent_sync", &options),
-                ("effects", &point_lookup),
-                ("sequenced", &options),
-                ("schedule", &options),
-                ("executed_sequence", &options),
-                ("batches", &options),
-                ("last_consensus_index", &options),
-                ("epochs", &options),
-            ];
-            typed_store::rocks::open_cf_opts(path, db_options, opt_cfs)
-        }
-        .expect("Cannot open DB.");
+            let cfs = Self::column_families(&options, &point_lookup, groups);
+            let opt_cfs: Vec<_> = cfs.iter().map(|(name, options)| (*name, options)).collect();
+            typed_store::rocks::open_cf_opts(path, db_options, &opt_cfs)
+        }?;
+
+        let store = Self::open_with_db(db, path.as_ref(), None);
+        store.fill_owner_type_index()?;
+        Ok(store)
+    }
+
+    /// Open a read-only view of the store of another process at `primary_path` as a RocksDB
+    /// secondary instance. `secondary_path` holds the secondary's own logs as well as the lock
+    /// and recovery databases, which stay empty since the store never executes anything.
+    /// Call `try_catch_up_with_primary` to observe new writes of the primary.
+    pub fn open_secondary<P: AsRef<Path>>(
+        primary_path: P,
+        secondary_path: P,
+        db_options: Option<Options>,
+    ) -> Self {
+        let (mut options, point_lookup) = default_db_options(db_options, None);
+        // Secondary instances need to keep all the files of the primary open.
+        options.set_max_open_files(-1);
+
+        let cfs = Self::column_families(&options, &point_lookup, None)
+            .into_iter()
+            .map(|(name, cf_options)| ColumnFamilyDescriptor::new(name, cf_options));
+        let db = Arc::new(
+            DBWithThreadMode::<MultiThreaded>::open_cf_descriptors_as_secondary(
+                &options,
+                primary_path.as_ref(),
+                secondary_path.as_ref(),
+                cfs,
+            )
+            .expect("Cannot open secondary DB."),
+        );
+
+        Self::open_with_db(db.clone(), secondary_path.as_ref(), Some(db))
+    }
+
+    fn column_families(
+        options: &Options,
+        point_lookup: &Options,
+        groups: Option<&StoreColumnFamilyGroups>,
+    ) -> Vec<(&'static str, Options)> {
+        [
+            ("objects", true),
+            ("transactions", true),
+            ("owner_index", false),
+            ("owner_type_index", false),
+            ("certificates", true),
+            ("pending_execution", false),
+            ("parent_sync", false),
+            ("effects", true),
+            ("sequenced", false),
+            ("schedule", false),
+            ("executed_sequence", false),
+            ("batches", false),
+            ("last_consensus_index", false),
+            ("epochs", false),
+            ("object_pruning_cursor", false),
+        ]
+        .into_iter()
+        .map(|(name, is_point_lookup)| {
+            let cf_options = match groups {
+                Some(groups) => {
+                    let group = if HISTORY_COLUMN_FAMILIES.contains(&name) {
+                        &groups.history
+                    } else {
+                        &groups.execution
+                    };
+                    group.column_family_options(options, is_point_lookup)
+                }
+                None if is_point_lookup => point_lookup.clone(),
+                None => options.clone(),
+            };
+            (name, cf_options)
+        })
+        .collect()
+    }
 
+    fn open_with_db(
+        db: Arc<DBWithThreadMode<MultiThreaded>>,
+        path: &Path,
+        secondary_db: Option<Arc<DBWithThreadMode<MultiThreaded>>>,
+    ) -> Self {
         let executed_sequence =
             DBMap::reopen(&db, Some("executed_sequence")).expect("Cannot open CF.");
 
         let (
             objects,
             owner_index,
+            owner_type_index,
             transactions,
             certificates,
             pending_execution,
@@ -173,10 +391,12 @@ impl<S: Eq + Serialize + for<'de> Deserialize<'de>> SuiDataStore<S> {
             batches,
             last_consensus_index,
             epochs,
+            object_pruning_cursor,
         ) = reopen! (
             &db,
             "objects";<ObjectKey, Object>,
             "owner_index";<(Owner, ObjectID), ObjectInfo>,
+            "owner_type_index";<OwnerTypeKey, ObjectInfo>,
             "transactions";<TransactionDigest, TransactionEnvelope<S>>,
             "certificates";<TransactionDigest, CertifiedTransaction>,
             "pending_execution";<InternalSequenceNumber, TransactionDigest>,
@@ -186,16 +406,17 @@ impl<S: Eq + Serialize + for<'de> Deserialize<'de>> SuiDataStore<S> {
             "schedule";<ObjectID, SequenceNumber>,
             "batches";<TxSequenceNumber, SignedBatch>,
             "last_consensus_index";<u64, ExecutionIndices>,
-            "epochs";<EpochId, EpochInfoLocals>
+            "epochs";<EpochId, EpochInfoLocals>,
+            "object_pruning_cursor";<u64, ObjectID>
         );
 
         // For now, create one LockService for each SuiDataStore, and we use a specific
         // subdir of the data store directory
-        let lockdb_path = path.as_ref().join("lockdb");
+        let lockdb_path = path.join("lockdb");
         let lock_service =
             LockService::new(lockdb_path, None).expect("Could not initialize lockdb");
 
-        let wal_path = path.as_ref().join("recovery_log");
+        let wal_path = path.join("recovery_log");
         let wal = Arc::new(DBWriteAheadLog::new(wal_path));
 
         // Get the last sequence item
@@ -213,6 +434,7 @@ impl<S: Eq + Serialize + for<'de> Deserialize<'de>> SuiDataStore<S> {
             lock_service,
             mutex_table: MutexTable::new(NUM_SHARDS),
             owner_index,
+            owner_type_index,
             transactions,
             certificates,
             pending_execution,
@@ -226,9 +448,78 @@ impl<S: Eq + Serialize + for<'de> Deserialize<'de>> SuiDataStore<S> {
             batches,
             last_consensus_index,
             epochs,
+            object_pruning_cursor,
+            secondary_db,
+            object_cache: None,
+            group_committer: None,
         }
     }
 
+    /// Fill the owner type index from the owner index, for stores written before the owner type
+    /// index existed. It is filled in the order of the owner index, so it is complete once it
+    /// has the last entry of the owner index, which transactions update in both.
+    fn fill_owner_type_index(&self) -> SuiResult {
+        let last = match self.owner_index.iter().skip_to_last().next() {
+            Some((_, info)) => info,
+            None => return Ok(()),
+        };
+        if self.owner_type_index.contains_key(&owner_type_key(&last))? {
+            return Ok(());
+        }
+        info!("Building the owner type index");
+        let mut entries = self
+            .owner_index
+            .iter()
+            .map(|(_, info)| (owner_type_key(&info), info))
+            .peekable();
+        while entries.peek().is_some() {
+            self.owner_type_index
+                .batch()
+                .insert_batch(
+                    &self.owner_type_index,
+                    entries.by_ref().take(OWNER_TYPE_INDEX_BATCH_SIZE),
+                )?
+                .write()?;
+        }
+        Ok(())
+    }
+
+    /// Cache the latest version of recently read objects in memory. Secondary stores don't
+    /// observe the primary's writes as they happen, so they are never cached.
+    pub fn with_object_cache(mut self, cache: ObjectCache) -> Self {
+        if !self.is_secondary() {
+            self.object_cache = Some(cache);
+        }
+        self
+    }
+
+    /// Write the objects and certificates of the transactions executed within the window of
+    /// `committer` in a single write.
+    pub fn with_group_commit(mut self, committer: GroupCommitter) -> Self {
+        self.group_committer = Some(committer);
+        self
+    }
+
+    fn invalidate_cached_objects<'a>(&self, object_ids: impl IntoIterator<Item = &'a ObjectID>) {
+        if let Some(cache) = &self.object_cache {
+            cache.invalidate(object_ids);
+        }
+    }
+
+    /// True if this store is a read-only secondary opened with `open_secondary`.
+    pub fn is_secondary(&self) -> bool {
+        self.secondary_db.is_some()
+    }
+
+    /// Replay the writes the primary made since the last call. A no-op on primary stores.
+    pub fn try_catch_up_with_primary(&self) -> SuiResult {
+        if let Some(db) = &self.secondary_db {
+            db.try_catch_up_with_primary()
+                .map_err(|e| SuiError::GenericStorageError(e.to_string()))?;
+        }
+        Ok(())
+    }
+
     // TODO: Async retry method, using tokio-retry crate.
 
     /// Await a new pending certificate to be added
@@ -273,6 +564,18 @@ impl<S: Eq + Serialize + for<'de> Deserialize<'de>> SuiDataStore<S> {
             .is_none())
     }
 
+    /// Write a consistent copy of the store to `path`, see [`create_db_checkpoint`]. The owned
+    /// object locks and the recovery log are separate databases and aren't part of it.
+    pub fn create_db_checkpoint(&self, path: &Path) -> SuiResult {
+        create_db_checkpoint(&self.objects.rocksdb, path)
+    }
+
+    /// Write the memtables to disk, see [`flush_db`]. The owned object locks and the recovery
+    /// log are separate databases, which RocksDB writes out when they are closed.
+    pub fn flush(&self) -> SuiResult {
+        flush_db(&self.objects.rocksdb)
+    }
+
     pub fn next_sequence_number(&self) -> Result<TxSequenceNumber, SuiError> {
         Ok(self
             .executed_sequence
@@ -373,6 +676,46 @@ impl<S: Eq + Serialize + for<'de> Deserialize<'de>> SuiDataStore<S> {
             .collect())
     }
 
+    /// Whether `owner` owns any object.
+    pub fn owns_objects(&self, owner: Owner) -> Result<bool, SuiError> {
+        Ok(self
+            .owner_index
+            .iter()
+            .skip_to(&(owner, ObjectID::ZERO))?
+            .next()
+            .map_or(false, |((object_owner, _), _)| object_owner == owner))
+    }
+
+    /// The owner and type of every live object, ordered by owner. Scans the whole owner index.
+    pub fn live_objects(&self) -> impl Iterator<Item = (Owner, String)> + '_ {
+        self.owner_index
+            .iter()
+            .map(|((owner, _), info)| (owner, info.type_))
+    }
+
+    /// Like `get_owner_objects`, but only returns the objects whose type matches `type_filter`,
+    /// ordered by type and then by object ID. They are read from the owner type index starting
+    /// at the type or type prefix, so the other objects of the owner aren't read.
+    pub fn get_owner_objects_by_type(
+        &self,
+        owner: Owner,
+        type_filter: &SuiObjectTypeFilter,
+    ) -> Result<Vec<ObjectInfo>, SuiError> {
+        debug!(?owner, ?type_filter, "get_owner_objects_by_type");
+        let start = match type_filter {
+            SuiObjectTypeFilter::Exact(type_) | SuiObjectTypeFilter::Prefix(type_) => type_,
+        };
+        Ok(self
+            .owner_type_index
+            .iter()
+            .skip_to(&(owner, TypeKey(start.clone()), ObjectID::ZERO))?
+            .take_while(|((object_owner, TypeKey(type_), _), _)| {
+                object_owner == &owner && type_filter.matches(type_)
+            })
+            .map(|(_, object_info)| object_info)
+            .collect())
+    }
+
     pub fn get_object_by_key(
         &self,
         object_id: &ObjectID,
@@ -381,8 +724,127 @@ impl<S: Eq + Serialize + for<'de> Deserialize<'de>> SuiDataStore<S> {
         Ok(self.objects.get(&ObjectKey(*object_id, version))?)
     }
 
+    /// Delete the past versions of the next `max_objects` objects from the pruning cursor on,
+    /// beyond the `depth` given for the latest stored version of each, which is kept with the
+    /// `depth` versions before it. Objects given no depth keep all their versions. The cursor is
+    /// saved with every write, so that the next pass resumes after the objects this one went
+    /// through, also after a restart, and starts over from the first object after the last
+    /// one. No version at or after the one `keep_from` maps an object to is deleted, and
+    /// `keep_from` is only called once the versions to go through are read, so that it covers
+    /// the transactions executed meanwhile. Stops early once `stop` returns true. Returns how
+    /// many versions were deleted.
+    pub fn prune_object_versions(
+        &self,
+        depth: impl Fn(&Object) -> Option<u64>,
+        keep_from: impl FnOnce() -> SuiResult<HashMap<ObjectID, SequenceNumber>>,
+        max_objects: usize,
+        stop: impl Fn() -> bool,
+    ) -> SuiResult<usize> {
+        let start = self
+            .object_pruning_cursor
+            .get(&OBJECT_PRUNING_CURSOR_ADDR)?
+            .unwrap_or(ObjectID::ZERO);
+        let mut pruned = 0;
+        let mut to_delete = Vec::new();
+        // The versions of the current object, oldest first.
+        let mut versions = Vec::new();
+        let mut objects = 0;
+        // Where the next pass starts.
+        let mut cursor = start;
+        let mut entries = self
+            .objects
+            .iter()
+            .skip_to(&ObjectKey(start, SequenceNumber::MIN))?
+            .peekable();
+        let keep_from = keep_from()?;
+        while let Some((key, object)) = entries.next() {
+            if stop() {
+                cursor = key.0;
+                break;
+            }
+            versions.push(key);
+            let next = entries.peek().map(|(next, _)| next.0);
+            if next == Some(key.0) {
+                continue;
+            }
+            // `object` is the latest stored version, there is nothing to prune without others.
+            if versions.len() > 1 {
+                if let Some(object_depth) = depth(&object) {
+                    let kept = usize::try_from(object_depth).unwrap_or(usize::MAX);
+                    let mut excess = versions.len().saturating_sub(kept.saturating_add(1));
+                    if let Some(floor) = keep_from.get(&key.0) {
+                        excess = excess.min(versions.iter().take_while(|k| k.1 < *floor).count());
+                    }
+                    to_delete.extend(versions.drain(..excess));
+                }
+            }
+            versions.clear();
+            cursor = next.unwrap_or(ObjectID::ZERO);
+            objects += 1;
+            if objects >= max_objects {
+                break;
+            }
+            if to_delete.len() >= PRUNE_BATCH_SIZE {
+                pruned += to_delete.len();
+                self.delete_object_versions(to_delete.drain(..), cursor)?;
+            }
+        }
+        pruned += to_delete.len();
+        self.delete_object_versions(to_delete.into_iter(), cursor)?;
+        Ok(pruned)
+    }
+
+    /// Delete `keys` and save `cursor` as where the next pruning pass starts.
+    fn delete_object_versions(
+        &self,
+        keys: impl Iterator<Item = ObjectKey>,
+        cursor: ObjectID,
+    ) -> SuiResult {
+        self.objects
+            .batch()
+            .delete_batch(&self.objects, keys)?
+            .insert_batch(
+                &self.object_pruning_cursor,
+                iter::once((OBJECT_PRUNING_CURSOR_ADDR, cursor)),
+            )?
+            .write()?;
+        Ok(())
+    }
+
+    /// Read `object_id` as it was at `version`, with the reference that version was written
+    /// with. The object is None if that version deleted or wrapped it. Returns Ok(None) if the
+    /// object never had this version, and Err(ObjectVersionUnavailable) if it had it but its
+    /// contents are no longer stored.
+    pub fn get_past_object(
+        &self,
+        object_id: &ObjectID,
+        version: VersionNumber,
+    ) -> SuiResult<Option<(ObjectRef, Option<Object>)>> {
+        let object_ref = match self.get_parent_iterator(*object_id, Some(version))?.next() {
+            Some((object_ref, _)) => object_ref,
+            None => return Ok(None),
+        };
+        if !object_ref.2.is_alive() {
+            return Ok(Some((object_ref, None)));
+        }
+        match self.get_object_by_key(object_id, version)? {
+            Some(object) => Ok(Some((object_ref, Some(object)))),
+            None => Err(SuiError::ObjectVersionUnavailable {
+                object_id: *object_id,
+                version,
+            }),
+        }
+    }
+
     /// Read an object and return it, or Err(ObjectNotFound) if the object was not found.
     pub fn get_object(&self, object_id: &ObjectID) -> Result<Option<Object>, SuiError> {
+        match &self.object_cache {
+            Some(cache) => cache.get_or_read(object_id, || self.read_object(object_id)),
+            None => self.read_object(object_id),
+        }
+    }
+
+    fn read_object(&self, object_id: &ObjectID) -> Result<Option<Object>, SuiError> {
         let obj_entry = self
             .objects
             .iter()
@@ -559,15 +1021,17 @@ impl<S: Eq + Serialize + for<'de> Deserialize<'de>> SuiDataStore<S> {
 
         // Update the index
         if object.get_single_owner().is_some() {
-            self.owner_index.insert(
-                &(object.owner, object_ref.0),
-                &ObjectInfo::new(&object_ref, object),
-            )?;
+            let info = ObjectInfo::new(&object_ref, object);
+            self.owner_type_index
+                .insert(&owner_type_key(&info), &info)?;
+            self.owner_index
+                .insert(&(object.owner, object_ref.0), &info)?;
         }
 
         // Update the parent
         self.parent_sync
             .insert(&object_ref, &object.previous_transaction)?;
+        self.invalidate_cached_objects([&object_ref.0]);
 
         self.lock_service
             .initialize_locks(&[object_ref], false /* is_force_reset */)
@@ -599,6 +1063,13 @@ impl<S: Eq + Serialize + for<'de> Deserialize<'de>> SuiDataStore<S> {
                     .iter()
                     .map(|(oref, o)| ((o.owner, oref.0), ObjectInfo::new(oref, o))),
             )?
+            .insert_batch(
+                &self.owner_type_index,
+                ref_and_objects.iter().map(|(oref, o)| {
+                    let info = ObjectInfo::new(oref, o);
+                    (owner_type_key(&info), info)
+                }),
+            )?
             .insert_batch(
                 &self.parent_sync,
                 ref_and_objects
@@ -606,6 +1077,7 @@ impl<S: Eq + Serialize + for<'de> Deserialize<'de>> SuiDataStore<S> {
                     .map(|(oref, o)| (oref, o.previous_transaction)),
             )?
             .write()?;
+        self.invalidate_cached_objects(ref_and_objects.iter().map(|(oref, _)| &oref.0));
 
         let refs: Vec<_> = ref_and_objects.iter().map(|(oref, _)| *oref).collect();
         self.lock_service
@@ -639,6 +1111,24 @@ impl<S: Eq + Serialize + for<'de> Deserialize<'de>> SuiDataStore<S> {
         Ok(())
     }
 
+    /// Returns the owned objects locked to a transaction that wasn't executed yet, only those
+    /// owned by `owner` when set. Without an owner every lock is scanned.
+    pub async fn get_locked_objects(
+        &self,
+        owner: Option<SuiAddress>,
+    ) -> SuiResult<Vec<LockedObject>> {
+        let objects = match owner {
+            Some(owner) => Some(
+                self.get_owner_objects(Owner::AddressOwner(owner))?
+                    .into_iter()
+                    .map(|info| (info.object_id, info.version, info.digest))
+                    .collect(),
+            ),
+            None => None,
+        };
+        self.lock_service.locked_objects(objects).await
+    }
+
     /// This function should only be used by the gateway.
     /// It's called when we could not get a transaction to successfully execute,
     /// and have to roll back.
@@ -663,17 +1153,9 @@ impl<S: Eq + Serialize + for<'de> Deserialize<'de>> SuiDataStore<S> {
     ) -> SuiResult {
         // Extract the new state from the execution
         // TODO: events are already stored in the TxDigest -> TransactionEffects store. Is that enough?
-        let mut write_batch = self.certificates.batch();
-
-        // Store the certificate indexed by transaction digest
         let transaction_digest: &TransactionDigest = certificate.digest();
-        write_batch = write_batch.insert_batch(
-            &self.certificates,
-            std::iter::once((transaction_digest, certificate)),
-        )?;
-
         self.sequence_tx(
-            write_batch,
+            certificate,
             temporary_store,
             transaction_digest,
             proposed_seq,
@@ -695,9 +1177,8 @@ impl<S: Eq + Serialize + for<'de> Deserialize<'de>> SuiDataStore<S> {
         transaction_digest: TransactionDigest,
     ) -> Result<(), SuiError> {
         debug_assert_eq!(transaction_digest, TransactionDigest::genesis());
-        let write_batch = self.certificates.batch();
         self.batch_update_objects(
-            write_batch,
+            None,
             temporary_store,
             transaction_digest,
             UpdateType::Genesis,
@@ -731,16 +1212,8 @@ impl<S: Eq + Serialize + for<'de> Deserialize<'de>> SuiDataStore<S> {
             temporary_store.delete_object(&obj_ref.0, obj_ref.1, DeleteKind::Wrap);
         }
 
-        let mut write_batch = self.certificates.batch();
-
-        // Store the certificate indexed by transaction digest
-        write_batch = write_batch.insert_batch(
-            &self.certificates,
-            std::iter::once((transaction_digest, &certificate)),
-        )?;
-
         self.sequence_tx(
-            write_batch,
+            &certificate,
             temporary_store,
             transaction_digest,
             proposed_seq,
@@ -752,7 +1225,7 @@ impl<S: Eq + Serialize + for<'de> Deserialize<'de>> SuiDataStore<S> {
 
     async fn sequence_tx<BackingPackageStore>(
         &self,
-        write_batch: DBBatch,
+        certificate: &CertifiedTransaction,
         temporary_store: AuthorityTemporaryStore<BackingPackageStore>,
         transaction_digest: &TransactionDigest,
         proposed_seq: TxSequenceNumber,
@@ -762,7 +1235,7 @@ impl<S: Eq + Serialize + for<'de> Deserialize<'de>> SuiDataStore<S> {
         // Safe to unwrap since UpdateType::Transaction ensures we get a sequence number back.
         let assigned_seq = self
             .batch_update_objects(
-                write_batch,
+                Some(certificate),
                 temporary_store,
                 *transaction_digest,
                 UpdateType::Transaction(proposed_seq, *effects_digest),
@@ -801,7 +1274,7 @@ impl<S: Eq + Serialize + for<'de> Deserialize<'de>> SuiDataStore<S> {
     /// Helper function for updating the objects in the state
     async fn batch_update_objects<BackingPackageStore>(
         &self,
-        mut write_batch: DBBatch,
+        certificate: Option<&CertifiedTransaction>,
         temporary_store: AuthorityTemporaryStore<BackingPackageStore>,
         transaction_digest: TransactionDigest,
         update_type: UpdateType,
@@ -816,88 +1289,118 @@ impl<S: Eq + Serialize + for<'de> Deserialize<'de>> SuiDataStore<S> {
             .cloned()
             .collect();
 
-        // Make an iterator over all objects that are either deleted or have changed owner,
-        // along with their old owner.  This is used to update the owner index.
+        // Collect all objects that are either deleted or have changed owner, as they were
+        // before.  This is used to remove their old owner from the owner indexes.
         // For wrapped objects, although their owners technically didn't change, we will lose track
         // of them and there is no guarantee on their owner in the future. Hence we treat them
         // the same as deleted.
-        let old_object_owners = deleted
+        let old_objects: Vec<&Object> = deleted
             .iter()
             // We need to call get() on objects because some object that were just deleted may not
             // be in the objects list. This can happen if these deleted objects were wrapped in the past,
             // and hence will not show up in the input objects.
-            .filter_map(|(id, _)| objects.get(id).and_then(Object::get_owner_and_id))
+            .filter_map(|(id, _)| objects.get(id))
             .chain(
                 written
                     .iter()
                     .filter_map(|(id, (_, new_object))| match objects.get(id) {
                         Some(old_object) if old_object.owner != new_object.owner => {
-                            old_object.get_owner_and_id()
+                            Some(old_object)
                         }
                         _ => None,
                     }),
-            );
+            )
+            .collect();
 
-        // Delete the old owner index entries
-        write_batch = write_batch.delete_batch(&self.owner_index, old_object_owners)?;
+        let build = |mut write_batch: DBBatch| -> SuiResult<DBBatch> {
+            // Store the certificate indexed by transaction digest
+            if let Some(certificate) = certificate {
+                write_batch = write_batch.insert_batch(
+                    &self.certificates,
+                    std::iter::once((transaction_digest, certificate)),
+                )?;
+            }
 
-        // Index the certificate by the objects mutated
-        write_batch = write_batch.insert_batch(
-            &self.parent_sync,
-            written
-                .iter()
-                .map(|(_, (object_ref, _object_))| (object_ref, transaction_digest)),
-        )?;
+            // Delete the old owner index entries
+            write_batch = write_batch.delete_batch(
+                &self.owner_index,
+                old_objects
+                    .iter()
+                    .filter_map(|object| object.get_owner_and_id()),
+            )?;
+            write_batch = write_batch.delete_batch(
+                &self.owner_type_index,
+                old_objects.iter().copied().map(object_owner_type_key),
+            )?;
 
-        // Index the certificate by the objects deleted
-        write_batch = write_batch.insert_batch(
-            &self.parent_sync,
-            deleted.iter().map(|(object_id, (version, kind))| {
-                (
+            // Index the certificate by the objects mutated
+            write_batch = write_batch.insert_batch(
+                &self.parent_sync,
+                written
+                    .iter()
+                    .map(|(_, (object_ref, _object_))| (object_ref, transaction_digest)),
+            )?;
+
+            // Index the certificate by the objects deleted
+            write_batch = write_batch.insert_batch(
+                &self.parent_sync,
+                deleted.iter().map(|(object_id, (version, kind))| {
                     (
-                        *object_id,
-                        *version,
-                        if kind == &DeleteKind::Wrap {
-                            ObjectDigest::OBJECT_DIGEST_WRAPPED
-                        } else {
-                            ObjectDigest::OBJECT_DIGEST_DELETED
-                        },
-                    ),
-                    transaction_digest,
-                )
-            }),
-        )?;
+                        (
+                            *object_id,
+                            *version,
+                            if kind == &DeleteKind::Wrap {
+                                ObjectDigest::OBJECT_DIGEST_WRAPPED
+                            } else {
+                                ObjectDigest::OBJECT_DIGEST_DELETED
+                            },
+                        ),
+                        transaction_digest,
+                    )
+                }),
+            )?;
 
-        // Once a transaction is done processing and effects committed, we no longer
-        // need it in the transactions table. This also allows us to track pending
-        // transactions.
-        write_batch =
-            write_batch.delete_batch(&self.transactions, std::iter::once(transaction_digest))?;
+            // Once a transaction is done processing and effects committed, we no longer
+            // need it in the transactions table. This also allows us to track pending
+            // transactions.
+            write_batch = write_batch
+                .delete_batch(&self.transactions, std::iter::once(transaction_digest))?;
 
-        // Update the indexes of the objects written
-        write_batch = write_batch.insert_batch(
-            &self.owner_index,
-            written
+            // Update the indexes of the objects written
+            let written_infos: Vec<_> = written
                 .iter()
                 .filter_map(|(_id, (object_ref, new_object))| {
                     trace!(?object_ref, owner =? new_object.owner, "Updating owner_index");
                     new_object
                         .get_owner_and_id()
                         .map(|owner_id| (owner_id, ObjectInfo::new(object_ref, new_object)))
-                }),
-        )?;
+                })
+                .collect();
+            write_batch = write_batch.insert_batch(
+                &self.owner_type_index,
+                written_infos
+                    .iter()
+                    .map(|(_, info)| (owner_type_key(info), info)),
+            )?;
+            write_batch = write_batch.insert_batch(&self.owner_index, written_infos)?;
 
-        // Insert each output object into the stores
-        write_batch = write_batch.insert_batch(
-            &self.objects,
-            written
-                .iter()
-                .map(|(_, (obj_ref, new_object))| (ObjectKey::from(obj_ref), new_object)),
-        )?;
+            // Insert each output object into the stores
+            write_batch = write_batch.insert_batch(
+                &self.objects,
+                written
+                    .iter()
+                    .map(|(_, (obj_ref, new_object))| (ObjectKey::from(obj_ref), new_object)),
+            )?;
+            Ok(write_batch)
+        };
 
         // Atomic write of all data other than locks
-        write_batch.write()?;
+        match &self.group_committer {
+            Some(committer) => committer.commit(self.certificates.batch(), build).await?,
+            None => build(self.certificates.batch())?.write()?,
+        }
         trace!("Finished writing batch");
+        self.invalidate_cached_objects(written.keys().chain(deleted.keys()));
 
         // Need to have a critical section for now because we need to prevent execution of older
         // certs which may overwrite newer objects with older ones.  This can be removed once we have
@@ -965,7 +1468,7 @@ impl<S: Eq + Serialize + for<'de> Deserialize<'de>> SuiDataStore<S> {
     /// 1. Certificate and effects are deleted.
     /// 2. Latest parent_sync entries for each mutated object are deleted.
     /// 3. All new object states are deleted.
-    /// 4. owner_index table change is reverted.
+    /// 4. owner_index and owner_type_index table changes are reverted.
     pub fn revert_state_update(&self, tx_digest: &TransactionDigest) -> SuiResult {
         let effects = self.get_effects(tx_digest)?;
         let mut write_batch = self.certificates.batch();
@@ -982,12 +1485,15 @@ impl<S: Eq + Serialize + for<'de> Deserialize<'de>> SuiDataStore<S> {
             .chain(effects.wrapped.iter());
         write_batch = write_batch.delete_batch(&self.parent_sync, all_new_refs)?;
 
-        let all_new_object_keys = effects
+        let all_new_object_keys: Vec<_> = effects
             .mutated
             .iter()
             .chain(effects.created.iter())
             .chain(effects.unwrapped.iter())
-            .map(|((id, version, _), _)| ObjectKey(*id, *version));
+            .map(|((id, version, _), _)| ObjectKey(*id, *version))
+            .collect();
+        // Read before they are deleted, for their keys in the owner type index.
+        let new_objects = self.objects.multi_get(&all_new_object_keys)?;
         write_batch = write_batch.delete_batch(&self.objects, all_new_object_keys)?;
 
         // Reverting the change to the owner_index table is most complex.
@@ -1003,6 +1509,10 @@ impl<S: Eq + Serialize + for<'de> Deserialize<'de>> SuiDataStore<S> {
             .chain(effects.mutated.iter())
             .map(|((id, _, _), owner)| (*owner, *id));
         write_batch = write_batch.delete_batch(&self.owner_index, owners_to_delete)?;
+        write_batch = write_batch.delete_batch(
+            &self.owner_type_index,
+            new_objects.iter().flatten().map(object_owner_type_key),
+        )?;
         let mutated_objects = effects
             .mutated
             .iter()
@@ -1017,7 +1527,7 @@ impl<S: Eq + Serialize + for<'de> Deserialize<'de>> SuiDataStore<S> {
                         .expect("version revert should never fail"),
                 )
             });
-        let old_objects = self
+        let old_objects: Vec<_> = self
             .objects
             .multi_get(mutated_objects)?
             .into_iter()
@@ -1027,10 +1537,32 @@ impl<S: Eq + Serialize + for<'de> Deserialize<'de>> SuiDataStore<S> {
                     (obj.owner, obj.id()),
                     ObjectInfo::new(&obj.compute_object_reference(), &obj),
                 )
-            });
+            })
+            .collect();
+        write_batch = write_batch.insert_batch(
+            &self.owner_type_index,
+            old_objects
+                .iter()
+                .map(|(_, info)| (owner_type_key(info), info)),
+        )?;
         write_batch = write_batch.insert_batch(&self.owner_index, old_objects)?;
 
         write_batch.write()?;
+        self.invalidate_cached_objects(
+            effects
+                .mutated
+                .iter()
+                .chain(effects.created.iter())
+                .chain(effects.unwrapped.iter())
+                .map(|((id, _, _), _)| id)
+                .chain(
+                    effects
+                        .deleted
+                        .iter()
+                        .chain(effects.wrapped.iter())
+                        .map(|(id, _, _)| id),
+                ),
+        );
         Ok(())
     }
 
@@ -1048,12 +1580,21 @@ impl<S: Eq + Serialize + for<'de> Deserialize<'de>> SuiDataStore<S> {
     pub fn get_latest_parent_entry(
         &self,
         object_id: ObjectID,
+    ) -> Result<Option<(ObjectRef, TransactionDigest)>, SuiError> {
+        self.get_parent_entry_at_or_before(object_id, SequenceNumber::MAX)
+    }
+
+    /// The parent entry of the latest version of `object_id` that is `version` or before.
+    pub fn get_parent_entry_at_or_before(
+        &self,
+        object_id: ObjectID,
+        version: SequenceNumber,
     ) -> Result<Option<(ObjectRef, TransactionDigest)>, SuiError> {
         let mut iterator = self
             .parent_sync
             .iter()
-            // Make the max possible entry for this object ID.
-            .skip_prior_to(&(object_id, SequenceNumber::MAX, ObjectDigest::MAX))?;
+            // Make the max possible entry for this object ID at that version.
+            .skip_prior_to(&(object_id, version, ObjectDigest::MAX))?;
 
         Ok(iterator.next().and_then(|(obj_ref, tx_digest)| {
             if obj_ref.0 == object_id {
@@ -1175,6 +1716,26 @@ impl<S: Eq + Serialize + for<'de> Deserialize<'de>> SuiDataStore<S> {
             .collect())
     }
 
+    /// The sequence number of the oldest transaction whose certificate, effects and written
+    /// object versions are stored, or the next one when none are.
+    pub fn oldest_retained_sequence(&self) -> SuiResult<TxSequenceNumber> {
+        match self.executed_sequence.iter().next() {
+            Some((seq, _)) => Ok(seq),
+            None => self.next_sequence_number(),
+        }
+    }
+
+    /// The first sequence number batches can be streamed from: transactions before it are in
+    /// batches that are no longer retained.
+    pub fn batch_history_start(&self) -> SuiResult<TxSequenceNumber> {
+        Ok(self
+            .batches
+            .iter()
+            .next()
+            .map(|(_, batch)| batch.batch.next_sequence_number)
+            .unwrap_or(0))
+    }
+
     /// Retrieves batches including transactions within a range.
     ///
     /// This function returns all signed batches that enclose the requested transaction
@@ -1307,6 +1868,11 @@ impl<S: Eq + Serialize + for<'de> Deserialize<'de>> SuiDataStore<S> {
         Ok(())
     }
 
+    /// The info of `epoch`, if this authority reached it.
+    pub fn get_epoch_info(&self, epoch: EpochId) -> SuiResult<Option<EpochInfoLocals>> {
+        Ok(self.epochs.get(&epoch)?)
+    }
+
     pub fn get_last_epoch_info(&self) -> SuiResult<EpochInfoLocals> {
         // unwrap safe since we guarantee to insert an epoch entry at genesis.
         Ok(self.epochs.iter().skip_to_last().next().unwrap().1)
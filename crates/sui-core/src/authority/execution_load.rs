@@ -0,0 +1,78 @@
+// Copyright (c) 2022, Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+/// Time constant of the moving average of the throughput.
+const AVERAGE_THROUGHPUT_WINDOW: Duration = Duration::from_secs(60);
+
+/// How much work is waiting for execution and how fast it is done, as of the latest sample.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct ExecutionLoad {
+    /// Certificates waiting to be executed.
+    pub queue_depth: usize,
+    /// Transactions executed per second since the sample before.
+    pub throughput: f64,
+    /// Exponential moving average of `throughput` over about a minute, however often it is
+    /// sampled.
+    pub average_throughput: f64,
+}
+
+/// Turns periodic readings of the execution queue and of the number of transactions executed
+/// into an [`ExecutionLoad`].
+#[derive(Default)]
+pub(crate) struct ExecutionLoadTracker {
+    /// The number of transactions executed at the previous sample, and when that was.
+    previous: Option<(u64, Instant)>,
+    load: ExecutionLoad,
+}
+
+impl ExecutionLoadTracker {
+    pub fn sample(&mut self, queue_depth: usize, executed: u64, now: Instant) -> ExecutionLoad {
+        if let Some((previous_executed, previous_at)) = self.previous {
+            let elapsed = now.saturating_duration_since(previous_at).as_secs_f64();
+            if elapsed > 0.0 {
+                let throughput = executed.saturating_sub(previous_executed) as f64 / elapsed;
+                let weight = 1.0 - (-elapsed / AVERAGE_THROUGHPUT_WINDOW.as_secs_f64()).exp();
+                self.load.average_throughput +=
+                    weight * (throughput - self.load.average_throughput);
+                self.load.throughput = throughput;
+            }
+        }
+        self.previous = Some((executed, now));
+        self.load.queue_depth = queue_depth;
+        self.load
+    }
+
+    pub fn load(&self) -> ExecutionLoad {
+        self.load
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_execution_load() {
+        let start = Instant::now();
+        let mut tracker = ExecutionLoadTracker::default();
+        let load = tracker.sample(3, 100, start);
+        assert_eq!(load.queue_depth, 3);
+        assert_eq!(load.throughput, 0.0);
+
+        let load = tracker.sample(5, 150, start + Duration::from_secs(2));
+        assert_eq!(load.queue_depth, 5);
+        assert_eq!(load.throughput, 25.0);
+        assert!(load.average_throughput > 0.0 && load.average_throughput < 25.0);
+
+        // The average converges to a steady throughput.
+        let mut executed = 150;
+        for second in 3..600 {
+            executed += 25;
+            tracker.sample(0, executed, start + Duration::from_secs(second));
+        }
+        assert!((tracker.load().average_throughput - 25.0).abs() < 0.01);
+    }
+}
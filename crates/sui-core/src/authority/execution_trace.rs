@@ -0,0 +1,93 @@
+// Copyright (c) 2022, Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::future::Future;
+use std::time::Instant;
+use sui_types::base_types::TransactionDigest;
+use tracing::{info, info_span, Instrument, Span};
+
+/// Traces the phases of executing a sampled fraction of transactions: each phase runs in a
+/// `sampled_execution` span carrying the phase and the transaction digest, and logs how long it
+/// took when done.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ExecutionTraceSampler {
+    /// Out of `u64::MAX`, see `is_sampled`.
+    threshold: u64,
+}
+
+impl ExecutionTraceSampler {
+    /// Trace `rate` of the transactions, from 0 for none to 1 for all.
+    pub fn new(rate: f64) -> Self {
+        Self {
+            threshold: (rate.clamp(0.0, 1.0) * u64::MAX as f64) as u64,
+        }
+    }
+
+    /// Whether `digest` is traced. Decided from the digest alone, so that the phases run apart
+    /// from execution, like indexing, trace the same transactions.
+    pub fn is_sampled(&self, digest: &TransactionDigest) -> bool {
+        let mut prefix = [0; 8];
+        prefix.copy_from_slice(&digest.as_ref()[..8]);
+        self.threshold > 0 && u64::from_le_bytes(prefix) <= self.threshold
+    }
+
+    /// Run `phase` of executing `digest`, traced if sampled.
+    pub async fn trace<T>(
+        &self,
+        phase: &'static str,
+        digest: &TransactionDigest,
+        future: impl Future<Output = T>,
+    ) -> T {
+        if !self.is_sampled(digest) {
+            return future.await;
+        }
+        let span = phase_span(phase, digest);
+        let started = Instant::now();
+        let output = future.instrument(span.clone()).await;
+        log_elapsed(&span, phase, started);
+        output
+    }
+
+    /// Like [`ExecutionTraceSampler::trace`], for a phase that doesn't await.
+    pub fn trace_blocking<T>(
+        &self,
+        phase: &'static str,
+        digest: &TransactionDigest,
+        f: impl FnOnce() -> T,
+    ) -> T {
+        if !self.is_sampled(digest) {
+            return f();
+        }
+        let span = phase_span(phase, digest);
+        let started = Instant::now();
+        let output = span.in_scope(f);
+        log_elapsed(&span, phase, started);
+        output
+    }
+}
+
+fn phase_span(phase: &'static str, digest: &TransactionDigest) -> Span {
+    info_span!("sampled_execution", phase, tx_digest = ?digest)
+}
+
+fn log_elapsed(span: &Span, phase: &'static str, started: Instant) {
+    let elapsed_us = started.elapsed().as_micros() as u64;
+    span.in_scope(|| info!(elapsed_us, "Execution phase {phase} done"));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_execution_trace_sample_rate() {
+        let digests: Vec<_> = (0..1000).map(|_| TransactionDigest::random()).collect();
+        let sampled = |rate| {
+            let sampler = ExecutionTraceSampler::new(rate);
+            digests.iter().filter(|d| sampler.is_sampled(d)).count()
+        };
+        assert_eq!(sampled(0.0), 0);
+        assert_eq!(sampled(1.0), digests.len());
+        assert!((50..200).contains(&sampled(0.1)));
+    }
+}
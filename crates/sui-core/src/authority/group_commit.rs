@@ -0,0 +1,194 @@
+// Copyright (c) 2022, Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::metrics::{self, MetricsNamespace};
+use parking_lot::Mutex;
+use prometheus::{Histogram, Registry};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use sui_types::error::{SuiError, SuiResult};
+use tokio::sync::watch;
+use typed_store::rocks::DBBatch;
+
+/// Coalesces the writes of the transactions committed within a short window into a single
+/// RocksDB write, which saves write overhead under bursts of transactions.
+///
+/// The first transaction to commit opens a group, which is written `window` later. The
+/// transactions committing meanwhile add their writes to the batch of the group, and all of
+/// them return once it is written. The batch is written atomically, so a transaction is either
+/// written in full or not at all, and none returns before its writes are. A transaction whose
+/// writes can't be built fails alone, without joining the group.
+///
+/// The window is waited for with the millisecond resolution of the tokio timer, so windows
+/// shorter than a millisecond aren't supported.
+pub struct GroupCommitter {
+    window: Duration,
+    open: Arc<Mutex<Option<Group>>>,
+    group_size: Histogram,
+}
+
+struct Group {
+    /// Always set outside of `GroupCommitter::commit`. An error if adding the writes of a
+    /// transaction failed although they were built once already, which fails the whole group.
+    batch: Option<SuiResult<DBBatch>>,
+    size: usize,
+    written: watch::Sender<Option<SuiResult>>,
+    written_receiver: watch::Receiver<Option<SuiResult>>,
+}
+
+impl GroupCommitter {
+    pub fn new(window: Duration, registry: &Registry) -> Self {
+        let authority = MetricsNamespace::new(registry, metrics::AUTHORITY);
+        Self {
+            window,
+            open: Arc::new(Mutex::new(None)),
+            group_size: authority
+                .histogram_with_buckets(
+                    "group_commit_size",
+                    "Number of transactions written together by a group commit",
+                    vec![1., 2., 5., 10., 20., 50., 100., 200., 500.],
+                )
+                .unwrap(),
+        }
+    }
+
+    /// Write what `build` adds to a batch, together with the writes of the other transactions
+    /// committed within the window. `batch` is built on when this transaction opens a group,
+    /// and otherwise first built into on its own to check that the writes can be built, so
+    /// `build` must add the same writes each time. When it fails, only this transaction fails.
+    pub fn commit(
+        &self,
+        batch: DBBatch,
+        build: impl Fn(DBBatch) -> SuiResult<DBBatch>,
+    ) -> impl Future<Output = SuiResult> {
+        let written = self.join_group(batch, build);
+
+        // Only waiting is async, so that `build` doesn't have to be Send.
+        async move {
+            let mut written = written?;
+            while written.borrow().is_none() {
+                if written.changed().await.is_err() {
+                    break;
+                }
+            }
+            let result = written.borrow().clone();
+            result.unwrap_or_else(|| {
+                Err(SuiError::GenericStorageError(
+                    "Group commit failed before writing".to_string(),
+                ))
+            })
+        }
+    }
+
+    /// Add the writes `build` adds to `batch` to the open group, or to a new one, and return
+    /// the receiver of the result of writing it.
+    fn join_group(
+        &self,
+        batch: DBBatch,
+        build: impl Fn(DBBatch) -> SuiResult<DBBatch>,
+    ) -> SuiResult<watch::Receiver<Option<SuiResult>>> {
+        let mut open = self.open.lock();
+        match &mut *open {
+            Some(group) => {
+                // A failure would lose the writes added to the batch of the group before.
+                build(batch)?;
+                let batch = group.batch.take().expect("the batch of a group is set");
+                group.batch = Some(batch.and_then(build));
+                group.size += 1;
+                Ok(group.written_receiver.clone())
+            }
+            None => {
+                let batch = build(batch)?;
+                let (written, written_receiver) = watch::channel(None);
+                *open = Some(Group {
+                    batch: Some(Ok(batch)),
+                    size: 1,
+                    written,
+                    written_receiver: written_receiver.clone(),
+                });
+                // The group is written by a task of its own, so that it is written even if the
+                // transaction that opened it is cancelled.
+                self.spawn_write();
+                Ok(written_receiver)
+            }
+        }
+    }
+
+    fn spawn_write(&self) {
+        let open = self.open.clone();
+        let window = self.window;
+        let group_size = self.group_size.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(window).await;
+            // Transactions committing from now on open the next group.
+            let group = open
+                .lock()
+                .take()
+                .expect("only the task of a group closes it");
+            group_size.observe(group.size as f64);
+            let result = group
+                .batch
+                .expect("the batch of a group is set")
+                .and_then(|batch| Ok(batch.write()?));
+            let _ = group.written.send(Some(result));
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use typed_store::rocks::{open_cf, DBMap};
+    use typed_store::traits::Map;
+
+    fn open_table() -> (tempfile::TempDir, DBMap<u64, u64>) {
+        let dir = tempfile::tempdir().unwrap();
+        let db = open_cf(dir.path(), None, &["table"]).unwrap();
+        let table = DBMap::reopen(&db, Some("table")).unwrap();
+        (dir, table)
+    }
+
+    #[tokio::test]
+    async fn test_group_commit() {
+        let (_dir, table) = open_table();
+        let registry = Registry::new();
+        let committer = GroupCommitter::new(Duration::from_millis(50), &registry);
+
+        let insert = |key: u64| {
+            let table = &table;
+            move |batch: DBBatch| Ok(batch.insert_batch(table, [(key, key)])?)
+        };
+        let (first, second) = tokio::join!(
+            committer.commit(table.batch(), insert(1)),
+            committer.commit(table.batch(), insert(2)),
+        );
+        first.unwrap();
+        second.unwrap();
+        assert_eq!(table.get(&1).unwrap(), Some(1));
+        assert_eq!(table.get(&2).unwrap(), Some(2));
+        assert_eq!(committer.group_size.get_sample_count(), 1);
+        assert_eq!(committer.group_size.get_sample_sum(), 2.);
+
+        // A transaction whose writes can't be built fails alone, whether it opens the group or
+        // joins it.
+        let fail = |_: DBBatch| -> SuiResult<DBBatch> {
+            Err(SuiError::GenericStorageError("failed".to_string()))
+        };
+        let (first, second, third) = tokio::join!(
+            committer.commit(table.batch(), fail),
+            committer.commit(table.batch(), insert(3)),
+            committer.commit(table.batch(), fail),
+        );
+        assert!(first.is_err());
+        second.unwrap();
+        assert!(third.is_err());
+        assert_eq!(table.get(&3).unwrap(), Some(3));
+        assert_eq!(committer.group_size.get_sample_count(), 2);
+        assert_eq!(committer.group_size.get_sample_sum(), 3.);
+
+        // The next group is written again.
+        committer.commit(table.batch(), insert(4)).await.unwrap();
+        assert_eq!(table.get(&4).unwrap(), Some(4));
+    }
+}
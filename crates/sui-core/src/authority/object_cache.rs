@@ -0,0 +1,126 @@
+// Copyright (c) 2022, Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::metrics::{self, MetricsNamespace};
+use lru::LruCache;
+use parking_lot::Mutex;
+use prometheus::{IntCounter, Registry};
+use sui_types::{base_types::ObjectID, error::SuiResult, object::Object};
+
+/// An in-memory LRU cache of the latest version of recently read objects, in front of the
+/// objects table of the store.
+///
+/// Every write to an object invalidates its entry. A read that misses the cache only fills it
+/// when no write happened while it read the store, so a read racing with a write can't put a
+/// stale version back in.
+pub struct ObjectCache {
+    inner: Mutex<Inner>,
+    hits: IntCounter,
+    misses: IntCounter,
+}
+
+struct Inner {
+    objects: LruCache<ObjectID, Object>,
+    /// Incremented on every invalidation.
+    writes: u64,
+}
+
+impl ObjectCache {
+    pub fn new(entries: usize, registry: &Registry) -> Self {
+        let authority = MetricsNamespace::new(registry, metrics::AUTHORITY);
+        Self {
+            inner: Mutex::new(Inner {
+                objects: LruCache::new(entries),
+                writes: 0,
+            }),
+            hits: authority
+                .int_counter(
+                    "object_cache_hits",
+                    "Number of object reads served by the cache",
+                )
+                .unwrap(),
+            misses: authority
+                .int_counter(
+                    "object_cache_misses",
+                    "Number of object reads that went to the store",
+                )
+                .unwrap(),
+        }
+    }
+
+    /// The latest version of `object_id`, from the cache or else from `read`. Deleted and
+    /// missing objects are not cached.
+    pub fn get_or_read(
+        &self,
+        object_id: &ObjectID,
+        read: impl FnOnce() -> SuiResult<Option<Object>>,
+    ) -> SuiResult<Option<Object>> {
+        let writes = {
+            let mut inner = self.inner.lock();
+            if let Some(object) = inner.objects.get(object_id) {
+                self.hits.inc();
+                return Ok(Some(object.clone()));
+            }
+            inner.writes
+        };
+        self.misses.inc();
+
+        let object = read()?;
+        if let Some(object) = &object {
+            let mut inner = self.inner.lock();
+            if inner.writes == writes {
+                inner.objects.put(*object_id, object.clone());
+            }
+        }
+        Ok(object)
+    }
+
+    /// Forget the objects a write changed. Must be called once the write is committed to the
+    /// store.
+    pub fn invalidate<'a>(&self, object_ids: impl IntoIterator<Item = &'a ObjectID>) {
+        let mut inner = self.inner.lock();
+        inner.writes += 1;
+        for object_id in object_ids {
+            inner.objects.pop(object_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sui_types::base_types::SuiAddress;
+
+    #[test]
+    fn test_object_cache() {
+        let registry = Registry::new();
+        let cache = ObjectCache::new(1, &registry);
+        let object = Object::with_id_owner_for_testing(ObjectID::random(), SuiAddress::random());
+        let id = object.id();
+
+        let read = || Ok(Some(object.clone()));
+        assert_eq!(cache.get_or_read(&id, read).unwrap(), Some(object.clone()));
+        // Served from the cache now.
+        let unreachable = || -> SuiResult<Option<Object>> { panic!("should be cached") };
+        assert_eq!(
+            cache.get_or_read(&id, unreachable).unwrap(),
+            Some(object.clone())
+        );
+        assert_eq!((cache.hits.get(), cache.misses.get()), (1, 1));
+
+        cache.invalidate([&id]);
+        assert_eq!(cache.get_or_read(&id, || Ok(None)).unwrap(), None);
+        // Missing objects aren't cached.
+        assert_eq!(cache.get_or_read(&id, read).unwrap(), Some(object.clone()));
+        assert_eq!(cache.misses.get(), 3);
+
+        // A read racing with a write doesn't fill the cache.
+        cache.invalidate([&id]);
+        let racing_read = || {
+            cache.invalidate([&id]);
+            Ok(Some(object.clone()))
+        };
+        cache.get_or_read(&id, racing_read).unwrap();
+        assert_eq!(cache.get_or_read(&id, || Ok(None)).unwrap(), None);
+    }
+}
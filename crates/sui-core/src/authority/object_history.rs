@@ -0,0 +1,92 @@
+// Copyright (c) 2022, Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use sui_config::ObjectHistoryConfig;
+use sui_types::{event_filter::MoveEventTypeFilter, object::Object};
+
+/// How many past versions of an object are kept besides its latest one, by object type, see
+/// [`ObjectHistoryConfig`].
+pub struct ObjectHistoryPolicy {
+    default_depth: Option<u64>,
+    type_depths: Vec<(MoveEventTypeFilter, Option<u64>)>,
+    /// Objects each pruning pass goes through.
+    objects_per_pass: usize,
+}
+
+impl ObjectHistoryPolicy {
+    pub fn new(config: &ObjectHistoryConfig) -> Result<Self> {
+        let type_depths = config
+            .type_depths
+            .iter()
+            .map(|(pattern, depth)| Ok((pattern.parse()?, *depth)))
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            default_depth: config.default_depth,
+            type_depths,
+            objects_per_pass: config.objects_per_pass.max(1),
+        })
+    }
+
+    /// The past versions of `object` to keep, or None to keep them all.
+    pub fn depth(&self, object: &Object) -> Option<u64> {
+        match object.data.type_() {
+            Some(type_) => self
+                .type_depths
+                .iter()
+                .filter(|(pattern, _)| pattern.matches(type_))
+                .max_by_key(|(pattern, _)| specificity(pattern))
+                .map_or(self.default_depth, |(_, depth)| *depth),
+            None => self.default_depth,
+        }
+    }
+
+    /// Objects each pruning pass goes through, at least one.
+    pub fn objects_per_pass(&self) -> usize {
+        self.objects_per_pass
+    }
+}
+
+fn specificity(pattern: &MoveEventTypeFilter) -> u8 {
+    match pattern {
+        MoveEventTypeFilter::Package(_) => 0,
+        MoveEventTypeFilter::Module(_) => 1,
+        MoveEventTypeFilter::Type(_) => 2,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sui_types::base_types::{ObjectID, SuiAddress};
+
+    fn policy(type_depths: &[(&str, Option<u64>)]) -> ObjectHistoryPolicy {
+        let config = ObjectHistoryConfig {
+            default_depth: Some(10),
+            type_depths: type_depths
+                .iter()
+                .map(|(pattern, depth)| (pattern.to_string(), *depth))
+                .collect(),
+            prune_interval_secs: 60,
+            objects_per_pass: 100,
+        };
+        ObjectHistoryPolicy::new(&config).unwrap()
+    }
+
+    #[test]
+    fn test_object_history_depth() {
+        let gas = Object::with_id_owner_for_testing(ObjectID::random(), SuiAddress::random());
+
+        assert_eq!(policy(&[]).depth(&gas), Some(10));
+        assert_eq!(policy(&[("0x3", Some(1))]).depth(&gas), Some(10));
+        assert_eq!(policy(&[("0x2", None)]).depth(&gas), None);
+
+        // The most specific pattern wins.
+        let policy = policy(&[
+            ("0x2", None),
+            ("0x2::coin", Some(5)),
+            ("0x2::coin::Coin<0x2::sui::SUI>", Some(0)),
+        ]);
+        assert_eq!(policy.depth(&gas), Some(0));
+    }
+}
@@ -31,20 +31,23 @@
 
 use arc_swap::ArcSwap;
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, BTreeSet, HashMap},
     ops::Deref,
+    path::PathBuf,
     sync::Arc,
     time::Duration,
 };
+use sui_config::{NodeSyncConfig, PeerSelectionConfig, TrustedCheckpoint};
 use sui_storage::{follower_store::FollowerStore, node_sync_store::NodeSyncStore};
 use sui_types::{base_types::AuthorityName, error::SuiResult};
 use tokio::sync::Mutex;
 use tokio::task::JoinHandle;
-use tracing::error;
+use tracing::{error, info};
 
 use crate::{
     authority::AuthorityState, authority_aggregator::AuthorityAggregator,
-    authority_client::AuthorityAPI, gateway_state::GatewayMetrics,
+    authority_client::AuthorityAPI, bandwidth_throttle::BandwidthThrottle,
+    gateway_state::GatewayMetrics,
 };
 use tokio::time::Instant;
 
@@ -111,6 +114,14 @@ pub struct ActiveAuthority<A> {
     // Network health
     pub health: Arc<Mutex<HashMap<AuthorityName, AuthorityHealth>>>,
     pub gateway_metrics: GatewayMetrics,
+    // How gossip and node sync pick the peers they follow
+    pub peer_selection: PeerSelectionConfig,
+    // Shared by the clients of all followed peers
+    pub follower_bandwidth: Arc<BandwidthThrottle>,
+    // Where the keys to sign checkpoints with in later epochs are read from
+    pub checkpoint_keys_dir: Option<PathBuf>,
+    // Where node sync starts verifying checkpoints from
+    pub trusted_checkpoint: Option<TrustedCheckpoint>,
 }
 
 impl<A> ActiveAuthority<A> {
@@ -121,6 +132,10 @@ impl<A> ActiveAuthority<A> {
         gateway_metrics: GatewayMetrics,
     ) -> SuiResult<Self> {
         let committee = authority.clone_committee();
+        let follower_bandwidth = Arc::new(BandwidthThrottle::new(
+            None,
+            authority.metrics.gossip_bytes_received.clone(),
+        ));
 
         Ok(ActiveAuthority {
             health: Arc::new(Mutex::new(
@@ -137,9 +152,44 @@ impl<A> ActiveAuthority<A> {
                 gateway_metrics.clone(),
             ))),
             gateway_metrics,
+            peer_selection: PeerSelectionConfig::default(),
+            follower_bandwidth,
+            checkpoint_keys_dir: None,
+            trusted_checkpoint: None,
         })
     }
 
+    pub fn with_peer_selection(mut self, peer_selection: PeerSelectionConfig) -> Self {
+        self.peer_selection = peer_selection;
+        self
+    }
+
+    /// Limit what gossip and node sync download from the peers they follow, together, to
+    /// `bytes_per_sec` on average.
+    pub fn with_follower_bandwidth_limit(mut self, bytes_per_sec: u64) -> Self {
+        self.follower_bandwidth = Arc::new(BandwidthThrottle::new(
+            Some(bytes_per_sec),
+            self.state.metrics.gossip_bytes_received.clone(),
+        ));
+        self
+    }
+
+    /// Sign as the key pair in the `<epoch>.key` file of `dir` from each new epoch on, if there
+    /// is one, see [`checkpoint_key_for_epoch`].
+    ///
+    /// [`checkpoint_key_for_epoch`]: crate::epoch::reconfiguration::checkpoint_key_for_epoch
+    pub fn with_checkpoint_keys_dir(mut self, dir: PathBuf) -> Self {
+        self.checkpoint_keys_dir = Some(dir);
+        self
+    }
+
+    /// Have node sync verifying against checkpoints start from `checkpoint`, see
+    /// [`NodeConfig::trusted_checkpoint`](sui_config::NodeConfig::trusted_checkpoint).
+    pub fn with_trusted_checkpoint(mut self, checkpoint: TrustedCheckpoint) -> Self {
+        self.trusted_checkpoint = Some(checkpoint);
+        self
+    }
+
     pub fn new_with_ephemeral_follower_store(
         authority: Arc<AuthorityState>,
         authority_clients: BTreeMap<AuthorityName, A>,
@@ -201,6 +251,48 @@ impl<A> ActiveAuthority<A> {
     }
 }
 
+impl<A> ActiveAuthority<A>
+where
+    A: Clone,
+{
+    /// Change the set of authorities this authority talks to. Clients of the authorities that
+    /// stay are kept, and `connect` creates the clients of the new ones. Running processes pick
+    /// up the change when they next choose a peer, and followers of removed authorities end
+    /// with their current follow period.
+    pub fn reconcile_authority_clients<F>(
+        &self,
+        names: &BTreeSet<AuthorityName>,
+        mut connect: F,
+    ) -> SuiResult
+    where
+        F: FnMut(&AuthorityName) -> SuiResult<A>,
+    {
+        let net = self.net.load();
+        let mut current = net.clone_inner_clients();
+        let mut authority_clients = BTreeMap::new();
+        for name in names {
+            let client = match current.remove(name) {
+                Some(client) => client,
+                None => {
+                    info!(peer = ?name, "Adding authority client");
+                    connect(name)?
+                }
+            };
+            authority_clients.insert(*name, client);
+        }
+        for name in current.keys() {
+            info!(peer = ?name, "Removing authority client");
+        }
+
+        self.net.store(Arc::new(AuthorityAggregator::new(
+            net.committee.clone(),
+            authority_clients,
+            self.gateway_metrics.clone(),
+        )));
+        Ok(())
+    }
+}
+
 impl<A> Clone for ActiveAuthority<A> {
     fn clone(&self) -> Self {
         ActiveAuthority {
@@ -209,6 +301,8 @@ impl<A> Clone for ActiveAuthority<A> {
             net: ArcSwap::from(self.net.load().clone()),
             health: self.health.clone(),
             gateway_metrics: self.gateway_metrics.clone(),
+            peer_selection: self.peer_selection.clone(),
+            follower_bandwidth: self.follower_bandwidth.clone(),
         }
     }
 }
@@ -254,6 +348,7 @@ where
     pub async fn spawn_node_sync_process(
         self: Arc<Self>,
         node_sync_store: Arc<NodeSyncStore>,
+        config: NodeSyncConfig,
     ) -> JoinHandle<()> {
         let committee = self.state.committee.load().deref().clone();
         // nodes follow all validators to ensure they can eventually determine
@@ -262,7 +357,7 @@ where
         let target_num_tasks = committee.num_members();
 
         tokio::task::spawn(async move {
-            node_sync_process(&self, target_num_tasks, node_sync_store).await;
+            node_sync_process(&self, target_num_tasks, node_sync_store, config).await;
         })
     }
 
@@ -139,7 +139,7 @@ pub async fn checkpoint_process<A>(
                 // TODO: The sync process doesn't really work today because we don't yet have a
                 // mechanism to ensure that all past transactions will be executed.
                 if let Err(err) = sync_to_checkpoint(
-                    active_authority.state.name,
+                    active_authority.state.name(),
                     net.clone(),
                     state_checkpoints.clone(),
                     checkpoint.clone(),
@@ -158,7 +158,7 @@ pub async fn checkpoint_process<A>(
             // sync_to_checkpoint only syncs to the checkpoint before the latest checkpoint.
             // The latest checkpoint requires special handling (refer to the comments there).
             let result = update_latest_checkpoint(
-                active_authority.state.name,
+                active_authority.state.name(),
                 &net,
                 &state_checkpoints,
                 &checkpoint,
@@ -636,7 +636,7 @@ pub async fn diff_proposals<A>(
     // Exit when we have a checkpoint proposal.
 
     let mut available_authorities: BTreeSet<_> = proposals.iter().map(|(auth, _)| *auth).collect();
-    available_authorities.remove(&active_authority.state.name); // remove ourselves
+    available_authorities.remove(&active_authority.state.name()); // remove ourselves
     let mut fragments_num = 0;
 
     loop {
@@ -110,7 +110,7 @@ where
         // Sync and Execute with local authority state
         net.sync_certificate_to_authority_with_timeout_inner(
             sui_types::messages::ConfirmationTransaction::new(c.clone()),
-            active_authority.state.name,
+            active_authority.state.name(),
             &local_handler,
             tokio::time::Duration::from_secs(10),
             10,
@@ -0,0 +1,668 @@
+// Copyright (c) 2022, Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Checks the transactions node sync downloads against certified checkpoints, so that their
+//! effects aren't taken on the word of the peers that served them.
+//!
+//! Checkpoints are requested from the peer serving the transaction, but only those whose
+//! certificate verifies against the committee of their epoch are used, and only once their
+//! contents match the digest the certificate signs. Checkpoints of an epoch this node doesn't
+//! know the committee of yet are fetched again later. A transaction is verified when such a
+//! checkpoint lists it with the same effects digest, so it is only committed after it has been
+//! checkpointed. While catching up, up to `checkpoint-download-concurrency` checkpoints are
+//! downloaded at once, but they are still verified and added in order.
+//!
+//! With a trusted checkpoint, verification starts there rather than at the first checkpoint.
+//! The trusted checkpoint is taken as verified once its digest is the configured one, and the
+//! checkpoints below it need only be those it follows, by the previous digest each summary
+//! carries, instead of being certified by the committee of their epoch. A certified checkpoint
+//! at the trusted sequence number with another digest stops verification, and hence node
+//! sync, for good.
+//!
+//! A checkpoint whose certificate doesn't verify is never used. It is fetched again from the
+//! other peers node sync can follow, and once `checkpoint-peer-retries` of them served one that
+//! doesn't verify either, without any serving a valid one, node sync halts for good too,
+//! failing the readiness check. Either means the validators or the local committee can't be
+//! trusted, which needs an operator. Until that many peers were tried, e.g. because the others
+//! didn't answer, the checkpoint is fetched again later.
+//!
+//! The latest checkpoint whose transactions, and those of every checkpoint before it, were all
+//! executed is saved in the node sync store, and checkpoints are fetched from the one after it
+//! when the node restarts. It was verified then, so a restart past the trusted checkpoint
+//! carries on following digests from it.
+
+use crate::{
+    authority::{AuthorityState, AuthorityStore},
+    authority_active::ActiveAuthority,
+    authority_client::AuthorityAPI,
+    safe_client::SafeClient,
+};
+use futures::StreamExt;
+use parking_lot::Mutex;
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use sui_config::TrustedCheckpoint;
+use sui_storage::node_sync_store::NodeSyncStore;
+use sui_types::{
+    base_types::{AuthorityName, ExecutionDigests, TransactionDigest, TransactionEffectsDigest},
+    error::{SuiError, SuiResult},
+    messages::TransactionEffects,
+    messages_checkpoint::{
+        AuthenticatedCheckpoint, AuthorityCheckpointInfo, CertifiedCheckpointSummary,
+        CheckpointContents, CheckpointDigest, CheckpointRequest, CheckpointResponse,
+        CheckpointSequenceNumber,
+    },
+    sui_serde::{Base64, Encoding},
+};
+use tracing::{debug, error, info, warn};
+
+/// How often to ask for new checkpoints while waiting for a transaction to be checkpointed.
+const CHECKPOINT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long to wait for a transaction to be checkpointed before giving up on it. The follower
+/// retries the transaction later.
+const CHECKPOINT_WAIT_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug, PartialEq, Eq)]
+enum Verdict {
+    Verified,
+    Mismatch(TransactionEffectsDigest),
+    NotCheckpointed,
+}
+
+#[derive(Default)]
+struct VerifiedContents {
+    /// Effects digests of the transactions in the verified checkpoints that aren't known to be
+    /// executed locally yet, with the checkpoint listing them.
+    effects: HashMap<TransactionDigest, (TransactionEffectsDigest, CheckpointSequenceNumber)>,
+    /// Number of transactions in `effects` by checkpoint, for the checkpoints that have some.
+    unexecuted: BTreeMap<CheckpointSequenceNumber, usize>,
+    /// The next checkpoint to fetch.
+    next_checkpoint: CheckpointSequenceNumber,
+    /// The digests of the checkpoints fetched after the last one saved as synced.
+    digests: BTreeMap<CheckpointSequenceNumber, CheckpointDigest>,
+    /// The last checkpoint saved as synced.
+    saved: Option<CheckpointSequenceNumber>,
+    /// The digest of the last checkpoint fetched, once the trusted checkpoint was.
+    last_trusted_digest: Option<CheckpointDigest>,
+    /// Set when the trusted checkpoint turned out to be another one, or no peer served a
+    /// checkpoint that verifies.
+    refused: Option<SuiError>,
+}
+
+impl VerifiedContents {
+    fn add(
+        &mut self,
+        seq: CheckpointSequenceNumber,
+        digest: CheckpointDigest,
+        contents: CheckpointContents,
+        store: &AuthorityStore,
+    ) -> SuiResult {
+        self.digests.insert(seq, digest);
+        for digests in contents.transactions {
+            if !store.effects_exists(&digests.transaction)? {
+                self.effects
+                    .insert(digests.transaction, (digests.effects, seq));
+                *self.unexecuted.entry(seq).or_default() += 1;
+            }
+        }
+        Ok(())
+    }
+
+    fn check(&self, digests: &ExecutionDigests) -> Verdict {
+        match self.effects.get(&digests.transaction) {
+            Some((effects, _)) if *effects == digests.effects => Verdict::Verified,
+            Some((effects, _)) => Verdict::Mismatch(*effects),
+            None => Verdict::NotCheckpointed,
+        }
+    }
+
+    fn forget(&mut self, digest: &TransactionDigest) {
+        if let Some((_, seq)) = self.effects.remove(digest) {
+            if let Some(count) = self.unexecuted.get_mut(&seq) {
+                *count -= 1;
+                if *count == 0 {
+                    self.unexecuted.remove(&seq);
+                }
+            }
+        }
+    }
+
+    /// The latest checkpoint fetched whose transactions, and those of every checkpoint before
+    /// it, were all executed.
+    fn synced(&self) -> Option<CheckpointSequenceNumber> {
+        match self.unexecuted.keys().next() {
+            Some(first) => first.checked_sub(1),
+            None => self.next_checkpoint.checked_sub(1),
+        }
+    }
+
+    /// The synced checkpoint and its digest, if it is a later one than the last saved, which it
+    /// then becomes.
+    fn newly_synced(&mut self) -> Option<(CheckpointSequenceNumber, CheckpointDigest)> {
+        let synced = self.synced()?;
+        if matches!(self.saved, Some(saved) if saved >= synced) {
+            return None;
+        }
+        let digest = *self.digests.get(&synced)?;
+        self.digests = self.digests.split_off(&(synced + 1));
+        self.saved = Some(synced);
+        Some((synced, digest))
+    }
+}
+
+pub(crate) struct CheckpointVerifier<A> {
+    /// Whose clients checkpoints are fetched from when the certificate of the one the peer
+    /// serving a transaction served doesn't verify. Looked up each time, since the set of peers
+    /// may change.
+    active_authority: Arc<ActiveAuthority<A>>,
+    trusted: Option<TrustedCheckpoint>,
+    /// Number of other peers that must serve a checkpoint that doesn't verify before node sync
+    /// halts.
+    peer_retries: usize,
+    /// Number of checkpoints downloaded at once, see `checkpoint-download-concurrency`.
+    download_concurrency: usize,
+    node_sync_store: Arc<NodeSyncStore>,
+    /// Only locked briefly, never while fetching.
+    contents: Mutex<VerifiedContents>,
+    /// Held while fetching, so that concurrent transactions don't download the same
+    /// checkpoints.
+    fetching: tokio::sync::Mutex<()>,
+}
+
+impl<A> CheckpointVerifier<A>
+where
+    A: AuthorityAPI + Send + Sync + 'static + Clone,
+{
+    pub fn new(
+        active_authority: Arc<ActiveAuthority<A>>,
+        trusted: Option<TrustedCheckpoint>,
+        peer_retries: usize,
+        download_concurrency: usize,
+        node_sync_store: Arc<NodeSyncStore>,
+    ) -> Self {
+        let mut contents = VerifiedContents::default();
+        match node_sync_store.synced_checkpoint() {
+            Ok(Some((seq, digest))) => {
+                debug!(seq, "resuming checkpoint verification");
+                contents.next_checkpoint = seq + 1;
+                contents.saved = Some(seq);
+                if matches!(&trusted, Some(trusted) if seq >= trusted.sequence) {
+                    contents.last_trusted_digest = Some(digest);
+                }
+            }
+            Ok(None) => (),
+            Err(e) => warn!("Couldn't read the synced checkpoint, fetching from the first: {e}"),
+        }
+        Self {
+            active_authority,
+            trusted,
+            peer_retries,
+            download_concurrency,
+            node_sync_store,
+            contents: Mutex::new(contents),
+            fetching: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    fn state(&self) -> &AuthorityState {
+        &self.active_authority.state
+    }
+
+    fn store(&self) -> &AuthorityStore {
+        &self.state().database
+    }
+
+    /// Stop verifying, and hence node sync, for good.
+    fn refuse(&self, error: &SuiError) {
+        error!("Refusing to sync: {error}");
+        self.contents.lock().refused = Some(error.clone());
+        self.state().sync_progress.note_halted(error.clone());
+    }
+
+    /// Whether `digests` are verified by the checkpoints fetched so far, None until one of
+    /// them lists the transaction.
+    fn verdict(&self, digests: &ExecutionDigests) -> SuiResult<Option<SuiResult>> {
+        let digest = digests.transaction;
+        {
+            let contents = self.contents.lock();
+            if let Some(error) = &contents.refused {
+                return Ok(Some(Err(error.clone())));
+            }
+            match contents.check(digests) {
+                Verdict::Verified => return Ok(Some(Ok(()))),
+                Verdict::Mismatch(certified) => {
+                    return Ok(Some(Err(SuiError::CheckpointVerificationFailed {
+                        digest,
+                        error: format!(
+                            "the effects are {:?}, a certified checkpoint has {:?}",
+                            digests.effects, certified
+                        ),
+                    })))
+                }
+                Verdict::NotCheckpointed => (),
+            }
+        }
+        // Also executed by a concurrent download of the same transaction, which was verified
+        // and forgotten in the meantime.
+        if self.store().effects_exists(&digest)? {
+            return Ok(Some(Ok(())));
+        }
+        Ok(None)
+    }
+
+    /// Check that `effects`, downloaded from `peer` for the transaction announced with
+    /// `digests`, are the effects a certified checkpoint commits to. Waits for the transaction
+    /// to be checkpointed.
+    pub async fn verify(
+        &self,
+        peer: AuthorityName,
+        client: &SafeClient<A>,
+        digests: &ExecutionDigests,
+        effects: &TransactionEffects,
+    ) -> SuiResult {
+        let digest = digests.transaction;
+        if effects.digest() != digests.effects {
+            return Err(SuiError::CheckpointVerificationFailed {
+                digest,
+                error: "the downloaded effects aren't the announced ones".to_string(),
+            });
+        }
+
+        let deadline = Instant::now() + CHECKPOINT_WAIT_TIMEOUT;
+        loop {
+            if let Some(verdict) = self.verdict(digests)? {
+                return verdict;
+            }
+            let fetched = {
+                let _fetching = self.fetching.lock().await;
+                // The checkpoints fetched while waiting may list it.
+                if let Some(verdict) = self.verdict(digests)? {
+                    return verdict;
+                }
+                self.fetch_new_checkpoints(peer, client).await?
+            };
+
+            if Instant::now() >= deadline {
+                return Err(SuiError::GenericAuthorityError {
+                    error: format!("{digest:?} wasn't checkpointed in time to be verified"),
+                });
+            }
+            if !fetched {
+                tokio::time::sleep(CHECKPOINT_POLL_INTERVAL).await;
+            }
+        }
+    }
+
+    /// Drop what is known about a transaction once it is executed locally. Returns the latest
+    /// checkpoint whose transactions were all executed since then, if any, and saves it when it
+    /// is a later one.
+    pub fn forget_transaction(
+        &self,
+        digest: &TransactionDigest,
+    ) -> Option<CheckpointSequenceNumber> {
+        let mut contents = self.contents.lock();
+        contents.forget(digest);
+        if let Some((seq, digest)) = contents.newly_synced() {
+            if let Err(e) = self.node_sync_store.record_synced_checkpoint(seq, digest) {
+                warn!(seq, "Couldn't save the synced checkpoint: {e}");
+            }
+        }
+        contents.synced()
+    }
+
+    /// Fetch and verify the checkpoints certified since the last one fetched. Returns whether
+    /// there were any.
+    async fn fetch_new_checkpoints(
+        &self,
+        peer: AuthorityName,
+        client: &SafeClient<A>,
+    ) -> SuiResult<bool> {
+        let latest = match client
+            .handle_checkpoint(CheckpointRequest::latest(false))
+            .await?
+            .info
+        {
+            AuthorityCheckpointInfo::Proposal {
+                previous: AuthenticatedCheckpoint::Certified(previous),
+                ..
+            } => previous.summary.sequence_number,
+            _ => return Ok(false),
+        };
+
+        let mut fetched = false;
+        if let Some(trusted) = &self.trusted {
+            if self.contents.lock().last_trusted_digest.is_none() {
+                if latest < trusted.sequence {
+                    return Ok(false);
+                }
+                self.fetch_trusted_checkpoints(client, trusted).await?;
+                fetched = true;
+            }
+        }
+        let (next_checkpoint, mut last_trusted_digest) = {
+            let contents = self.contents.lock();
+            (contents.next_checkpoint, contents.last_trusted_digest)
+        };
+        // Downloaded concurrently but added in order, so that at most `download_concurrency`
+        // checkpoints are in flight or waiting for the ones before them at once.
+        let metrics = &self.state().metrics;
+        let mut downloads = futures::stream::iter(next_checkpoint..=latest)
+            .map(|seq| async move {
+                metrics.node_sync_checkpoint_downloads_in_flight.inc();
+                let _in_flight = scopeguard::guard((), |_| {
+                    metrics.node_sync_checkpoint_downloads_in_flight.dec()
+                });
+                self.fetch_verified_checkpoint(peer, client, seq).await
+            })
+            .buffered(self.download_concurrency.max(1));
+        let mut seq = next_checkpoint;
+        while let Some(downloaded) = downloads.next().await {
+            let (certificate, checkpoint_contents) = match downloaded {
+                Ok(fetched) => fetched,
+                Err(error @ SuiError::NoValidCheckpointCertificate { .. }) => {
+                    self.refuse(&error);
+                    return Err(error);
+                }
+                Err(error) => return Err(error),
+            };
+            metrics.node_sync_checkpoints_downloaded.inc();
+            metrics
+                .node_sync_checkpoint_transactions_downloaded
+                .inc_by(checkpoint_contents.transactions.len() as u64);
+            let digest = certificate.summary.digest();
+            if let Some(previous) = last_trusted_digest {
+                ensure_follows(&certificate, previous)?;
+                last_trusted_digest = Some(digest);
+            }
+
+            debug!(seq, "verified checkpoint for node sync");
+            self.state()
+                .index_checkpoint_contents(seq, &checkpoint_contents)?;
+            let mut contents = self.contents.lock();
+            contents.add(seq, digest, checkpoint_contents, self.store())?;
+            contents.next_checkpoint = seq + 1;
+            contents.last_trusted_digest = last_trusted_digest;
+            seq += 1;
+            fetched = true;
+        }
+        Ok(fetched)
+    }
+
+    /// Fetch checkpoint `seq` from `peer`, and from the other peers node sync can follow while
+    /// the certificate of the one fetched doesn't verify against the committee of its epoch.
+    /// Fails with `NoValidCheckpointCertificate` once `peer_retries` other peers served one that
+    /// doesn't verify either. Other errors, which say nothing about the checkpoint, are
+    /// returned as is, e.g. when fewer peers could be tried.
+    async fn fetch_verified_checkpoint(
+        &self,
+        peer: AuthorityName,
+        client: &SafeClient<A>,
+        seq: CheckpointSequenceNumber,
+    ) -> SuiResult<(CertifiedCheckpointSummary, CheckpointContents)> {
+        let fetched = fetch_checkpoint(client, seq).await?;
+        let mut error = match self.check_checkpoint(&peer, fetched)? {
+            Ok(verified) => return Ok(verified),
+            Err(error) => error,
+        };
+
+        let others: Vec<_> = self
+            .active_authority
+            .net
+            .load()
+            .authority_clients
+            .iter()
+            .filter(|(name, _)| **name != peer && **name != self.state().name())
+            .map(|(name, client)| (*name, client.clone()))
+            .collect();
+        // The other peers that served a checkpoint that doesn't verify.
+        let mut invalid = 0;
+        let mut others = others.into_iter();
+        while invalid < self.peer_retries {
+            let (name, client) = match others.next() {
+                Some(other) => other,
+                None => {
+                    return Err(SuiError::GenericAuthorityError {
+                        error: format!(
+                            "checkpoint {seq} doesn't verify, and only {invalid} other peers \
+                            served it: {error}"
+                        ),
+                    })
+                }
+            };
+            let fetched = match fetch_checkpoint(&client, seq).await {
+                Ok(fetched) => fetched,
+                Err(e) => {
+                    debug!(seq, peer = ?name, "Couldn't fetch checkpoint: {e}");
+                    continue;
+                }
+            };
+            match self.check_checkpoint(&name, fetched) {
+                Ok(Ok(verified)) => {
+                    info!(seq, peer = ?name, "Fetched a valid checkpoint from another peer");
+                    return Ok(verified);
+                }
+                Ok(Err(e)) => {
+                    invalid += 1;
+                    error = e;
+                }
+                Err(e) => debug!(seq, peer = ?name, "Couldn't verify checkpoint: {e}"),
+            }
+        }
+        Err(SuiError::NoValidCheckpointCertificate {
+            sequence: seq,
+            attempts: invalid + 1,
+            error: error.to_string(),
+        })
+    }
+
+    /// Verify the certificate and contents of a checkpoint fetched from `peer`, logging it if
+    /// they don't, which honest validators never serve. Fails without a verdict when the
+    /// committee of its epoch isn't known yet.
+    fn check_checkpoint(
+        &self,
+        peer: &AuthorityName,
+        (certificate, contents): (CertifiedCheckpointSummary, CheckpointContents),
+    ) -> SuiResult<SuiResult<(CertifiedCheckpointSummary, CheckpointContents)>> {
+        let epoch = certificate.summary.epoch;
+        let committee = self.state().committee_for_epoch(epoch)?.ok_or_else(|| {
+            SuiError::GenericAuthorityError {
+                error: format!("the committee of epoch {epoch} isn't known yet"),
+            }
+        })?;
+        if let Err(error) = certificate.verify_with_transactions(&committee, &contents) {
+            error!(
+                seq = certificate.summary.sequence_number,
+                digest = %Base64::encode(certificate.summary.digest()),
+                ?peer,
+                "Checkpoint failed verification, not using it: {error}"
+            );
+            return Ok(Err(error));
+        }
+        Ok(Ok((certificate, contents)))
+    }
+
+    /// Fetch the trusted checkpoint and, following their digests down from it, the
+    /// checkpoints below. Their contents are only added once they all check out.
+    async fn fetch_trusted_checkpoints(
+        &self,
+        client: &SafeClient<A>,
+        trusted: &TrustedCheckpoint,
+    ) -> SuiResult {
+        let (anchor, anchor_contents) = fetch_checkpoint(client, trusted.sequence).await?;
+        ensure_contents(&anchor, &anchor_contents)?;
+        let found = anchor.summary.digest();
+        if found != trusted.digest {
+            let error = SuiError::TrustedCheckpointMismatch {
+                sequence: trusted.sequence,
+                expected: Base64::encode(trusted.digest),
+                found: Base64::encode(found),
+            };
+            // Any peer can lie about a checkpoint, but not about a certified one.
+            let committee = self.state().committee_for_epoch(anchor.summary.epoch)?;
+            if matches!(committee, Some(committee) if anchor.verify(&committee).is_ok()) {
+                self.refuse(&error);
+            }
+            return Err(error);
+        }
+
+        let mut below = Vec::new();
+        let mut next = anchor.clone();
+        while let Some(seq) = next.summary.sequence_number.checked_sub(1) {
+            let (certificate, checkpoint_contents) = fetch_checkpoint(client, seq).await?;
+            ensure_contents(&certificate, &checkpoint_contents)?;
+            let digest = certificate.summary.digest();
+            ensure_follows(&next, digest)?;
+            below.push((seq, digest, checkpoint_contents));
+            next = certificate;
+        }
+
+        debug!(
+            seq = trusted.sequence,
+            below = below.len(),
+            "verified trusted checkpoint for node sync"
+        );
+        for (seq, _, checkpoint_contents) in &below {
+            self.state()
+                .index_checkpoint_contents(*seq, checkpoint_contents)?;
+        }
+        self.state()
+            .index_checkpoint_contents(trusted.sequence, &anchor_contents)?;
+        let mut contents = self.contents.lock();
+        for (seq, digest, checkpoint_contents) in below {
+            contents.add(seq, digest, checkpoint_contents, self.store())?;
+        }
+        contents.add(trusted.sequence, found, anchor_contents, self.store())?;
+        contents.next_checkpoint = trusted.sequence + 1;
+        contents.last_trusted_digest = Some(found);
+        Ok(())
+    }
+}
+
+async fn fetch_checkpoint<A>(
+    client: &SafeClient<A>,
+    seq: CheckpointSequenceNumber,
+) -> SuiResult<(CertifiedCheckpointSummary, CheckpointContents)>
+where
+    A: AuthorityAPI + Send + Sync + 'static + Clone,
+{
+    match client
+        .handle_checkpoint(CheckpointRequest::past(seq, true))
+        .await?
+    {
+        CheckpointResponse {
+            info: AuthorityCheckpointInfo::Past(AuthenticatedCheckpoint::Certified(c)),
+            detail: Some(detail),
+        } if c.summary.sequence_number == seq => Ok((c, detail)),
+        _ => Err(SuiError::GenericAuthorityError {
+            error: format!("peer did not return certified checkpoint {seq}"),
+        }),
+    }
+}
+
+/// Check that `contents` are those of `checkpoint`, without verifying its certificate.
+fn ensure_contents(
+    checkpoint: &CertifiedCheckpointSummary,
+    contents: &CheckpointContents,
+) -> SuiResult {
+    if contents.digest() != checkpoint.summary.content_digest {
+        return Err(SuiError::GenericAuthorityError {
+            error: format!(
+                "contents of checkpoint {} don't match its digest",
+                checkpoint.summary.sequence_number
+            ),
+        });
+    }
+    Ok(())
+}
+
+/// Check that `checkpoint` follows the checkpoint with digest `previous`.
+fn ensure_follows(
+    checkpoint: &CertifiedCheckpointSummary,
+    previous: CheckpointDigest,
+) -> SuiResult {
+    if checkpoint.summary.previous_digest != Some(previous) {
+        return Err(SuiError::GenericAuthorityError {
+            error: format!(
+                "checkpoint {} doesn't follow the checkpoint before it",
+                checkpoint.summary.sequence_number
+            ),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verified_contents() {
+        let mut contents = VerifiedContents::default();
+        let tx = ExecutionDigests::random();
+
+        assert_eq!(contents.check(&tx), Verdict::NotCheckpointed);
+
+        contents.effects.insert(tx.transaction, (tx.effects, 0));
+        assert_eq!(contents.check(&tx), Verdict::Verified);
+
+        let forged = ExecutionDigests::new(tx.transaction, TransactionEffectsDigest::random());
+        assert_eq!(contents.check(&forged), Verdict::Mismatch(tx.effects));
+
+        // Other transactions aren't affected.
+        assert_eq!(
+            contents.check(&ExecutionDigests::random()),
+            Verdict::NotCheckpointed
+        );
+    }
+
+    #[test]
+    fn test_synced_checkpoint() {
+        let mut contents = VerifiedContents::default();
+        assert_eq!(contents.synced(), None);
+
+        // Checkpoints 0 to 2 were fetched, with a transaction left to execute in 1 and 2.
+        let (first, second) = (ExecutionDigests::random(), ExecutionDigests::random());
+        contents
+            .effects
+            .insert(first.transaction, (first.effects, 1));
+        contents
+            .effects
+            .insert(second.transaction, (second.effects, 2));
+        contents.unexecuted.insert(1, 1);
+        contents.unexecuted.insert(2, 1);
+        contents.next_checkpoint = 3;
+        assert_eq!(contents.synced(), Some(0));
+
+        // Executing the later transaction first doesn't sync checkpoint 1.
+        contents.forget(&second.transaction);
+        assert_eq!(contents.synced(), Some(0));
+        contents.forget(&first.transaction);
+        assert_eq!(contents.synced(), Some(2));
+        assert!(contents.unexecuted.is_empty());
+    }
+
+    #[test]
+    fn test_newly_synced_checkpoint() {
+        let mut contents = VerifiedContents::default();
+        assert_eq!(contents.newly_synced(), None);
+
+        // Checkpoints 0 and 1 were fetched, with a transaction left to execute in 1.
+        let tx = ExecutionDigests::random();
+        contents.digests.insert(0, [0; 32]);
+        contents.digests.insert(1, [1; 32]);
+        contents.effects.insert(tx.transaction, (tx.effects, 1));
+        contents.unexecuted.insert(1, 1);
+        contents.next_checkpoint = 2;
+        assert_eq!(contents.newly_synced(), Some((0, [0; 32])));
+        assert_eq!(contents.newly_synced(), None);
+
+        contents.forget(&tx.transaction);
+        assert_eq!(contents.newly_synced(), Some((1, [1; 32])));
+        assert_eq!(contents.newly_synced(), None);
+        assert!(contents.digests.is_empty());
+    }
+}
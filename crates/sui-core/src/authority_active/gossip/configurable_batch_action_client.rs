@@ -158,8 +158,8 @@ impl AuthorityAPI for ConfigurableBatchActionClient {
     ) -> Result<BatchInfoResponseItemStream, SuiError> {
         let mut last_batch = AuthorityBatch::initial();
         let actions = &self.action_sequence_internal;
-        let secret = self.state.secret.clone();
-        let name = self.state.name;
+        let secret = self.state.secret();
+        let name = self.state.name();
         let mut items: Vec<Result<BatchInfoResponseItem, SuiError>> = Vec::new();
         let mut seq = 0;
         let zero_batch = SignedBatch::new(AuthorityBatch::initial(), &*secret, name);
@@ -12,6 +12,7 @@ use futures::{
     stream::{FuturesOrdered, FuturesUnordered},
     StreamExt,
 };
+use rand::{seq::IteratorRandom, Rng};
 use std::future::Future;
 use std::ops::Deref;
 use std::{
@@ -19,8 +20,9 @@ use std::{
     sync::Arc,
     time::Duration,
 };
+use sui_config::{NodeSyncConfig, PeerSelectionConfig};
 use sui_storage::{follower_store::FollowerStore, node_sync_store::NodeSyncStore};
-use sui_types::committee::StakeUnit;
+use sui_types::committee::{Committee, StakeUnit};
 use sui_types::{
     base_types::{AuthorityName, ExecutionDigests},
     batch::{TxSequenceNumber, UpdateItem},
@@ -29,6 +31,9 @@ use sui_types::{
         BatchInfoRequest, BatchInfoResponseItem, ConfirmationTransaction, TransactionInfoRequest,
         TransactionInfoResponse,
     },
+    messages_checkpoint::{
+        AuthenticatedCheckpoint, AuthorityCheckpointInfo, CheckpointRequest, CheckpointResponse,
+    },
 };
 use tracing::{debug, error, info, trace};
 
@@ -38,9 +43,12 @@ mod configurable_batch_action_client;
 #[cfg(test)]
 pub(crate) mod tests;
 
+mod checkpoint_verifier;
 mod node_sync;
 use node_sync::NodeSyncDigestHandler;
 
+pub mod sync_progress;
+
 struct Follower<A> {
     peer_name: AuthorityName,
     client: SafeClient<A>,
@@ -52,6 +60,9 @@ struct Follower<A> {
 
 const REQUEST_FOLLOW_NUM_DIGESTS: u64 = 100_000;
 const REFRESH_FOLLOWER_PERIOD_SECS: u64 = 60;
+/// How often a follower asks its peer for its latest checkpoint, see
+/// [`SyncProgress::peer_checkpoint`](sync_progress::SyncProgress::peer_checkpoint).
+const PEER_CHECKPOINT_POLL_INTERVAL: Duration = Duration::from_secs(10);
 
 use super::ActiveAuthority;
 
@@ -63,19 +74,18 @@ where
 }
 
 pub async fn node_sync_process<A>(
-    active_authority: &ActiveAuthority<A>,
+    active_authority: &Arc<ActiveAuthority<A>>,
     degree: usize,
     node_sync_store: Arc<NodeSyncStore>,
+    config: NodeSyncConfig,
 ) where
     A: AuthorityAPI + Send + Sync + 'static + Clone,
 {
     // TODO: special case follower for node sync.
-    let state = active_authority.state.clone();
-    let aggregator = active_authority.net.load().clone();
     follower_process(
         active_authority,
         degree,
-        NodeSyncDigestHandler::new(state, aggregator, node_sync_store),
+        NodeSyncDigestHandler::new(active_authority.clone(), node_sync_store, config),
     )
     .await;
 }
@@ -92,20 +102,23 @@ async fn follower_process<A, Handler: DigestHandler<A> + Clone>(
     let mut committee = local_active.state.committee.load().deref().clone();
 
     // Number of tasks at most "degree" and no more than committee - 1
-    let mut target_num_tasks = usize::min(committee.num_members() - 1, degree);
-
-    // If we do not expect to connect to anyone
-    if target_num_tasks == 0 {
+    if usize::min(committee.num_members() - 1, degree) == 0 {
+        // We do not expect to connect to anyone
         info!("Turning off gossip mechanism");
         return;
     }
     info!("Turning on gossip mechanism");
+    let mut target_num_tasks = num_gossip_tasks(&local_active, &committee, degree);
 
     // Keep track of names of active peers
     let mut peer_names = HashSet::new();
     let mut gossip_tasks = FuturesUnordered::new();
 
     loop {
+        if active_authority.state.is_shutting_down() {
+            info!("Turning off gossip mechanism, shutting down");
+            return;
+        }
         if active_authority.state.committee.load().epoch != committee.epoch {
             // If epoch has changed, we need to make a new copy of the active authority,
             // and update all local variables.
@@ -115,11 +128,17 @@ async fn follower_process<A, Handler: DigestHandler<A> + Clone>(
             // validators, and let them end naturally.
             local_active = Arc::new(active_authority.clone());
             committee = local_active.state.committee.load().deref().clone();
-            target_num_tasks = usize::min(committee.num_members() - 1, degree);
+            target_num_tasks = num_gossip_tasks(&local_active, &committee, degree);
             peer_names = peer_names
                 .into_iter()
                 .filter(|name| committee.authority_exists(name))
                 .collect();
+        } else if !Arc::ptr_eq(&*active_authority.net.load(), &*local_active.net.load()) {
+            // The set of peers changed. Tasks of removed peers end naturally as well, and their
+            // names are only dropped from the peer names then, so that they aren't followed
+            // twice if they are added back in the meantime.
+            local_active = Arc::new(active_authority.clone());
+            target_num_tasks = num_gossip_tasks(&local_active, &committee, degree);
         }
         let mut k = 0;
         while gossip_tasks.len() < target_num_tasks {
@@ -132,10 +151,13 @@ async fn follower_process<A, Handler: DigestHandler<A> + Clone>(
                 "Waiting for {:?}",
                 next_connect - tokio::time::Instant::now()
             );
-            tokio::time::sleep_until(next_connect).await;
+            tokio::select! {
+                _ = tokio::time::sleep_until(next_connect) => {}
+                _ = active_authority.state.shutdown_requested() => break,
+            }
 
             let name_result =
-                select_gossip_peer(local_active.state.name, peer_names.clone(), &local_active)
+                select_gossip_peer(local_active.state.name(), peer_names.clone(), &local_active)
                     .await;
             if name_result.is_err() {
                 continue;
@@ -143,6 +165,7 @@ async fn follower_process<A, Handler: DigestHandler<A> + Clone>(
             let name = name_result.unwrap();
 
             peer_names.insert(name);
+            record_active_peers(&local_active.state, &committee, &peer_names);
             let local_active_ref_copy = local_active.clone();
             let handler_clone = handler.clone();
             gossip_tasks.push(async move {
@@ -164,7 +187,7 @@ async fn follower_process<A, Handler: DigestHandler<A> + Clone>(
                 .iter()
                 .map(|name| committee.weight(name))
                 .sum::<StakeUnit>()
-                + committee.weight(&local_active.state.name);
+                + committee.weight(&local_active.state.name());
             if total_stake_used >= committee.quorum_threshold() {
                 break;
             }
@@ -172,13 +195,39 @@ async fn follower_process<A, Handler: DigestHandler<A> + Clone>(
 
         // If we have no peers no need to wait for one
         if gossip_tasks.is_empty() {
+            if target_num_tasks == 0 {
+                // All peers were removed, wait for some to be added.
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
             continue;
         }
 
-        wait_for_one_gossip_task_to_finish(&local_active, &mut peer_names, &mut gossip_tasks).await;
+        // The followers are dropped on shutdown, they store their progress as they go.
+        let finished =
+            wait_for_one_gossip_task_to_finish(&local_active, &mut peer_names, &mut gossip_tasks);
+        tokio::select! {
+            _ = finished => {}
+            _ = active_authority.state.shutdown_requested() => {}
+        }
     }
 }
 
+/// Number of tasks at most "degree", and no more than the peers we have a client for.
+fn num_gossip_tasks<A>(
+    active_authority: &ActiveAuthority<A>,
+    committee: &Committee,
+    degree: usize,
+) -> usize {
+    let num_peers = active_authority
+        .net
+        .load()
+        .authority_clients
+        .keys()
+        .filter(|name| **name != active_authority.state.name() && committee.authority_exists(name))
+        .count();
+    usize::min(num_peers, degree)
+}
+
 async fn wait_for_one_gossip_task_to_finish<A>(
     active_authority: &ActiveAuthority<A>,
     peer_names: &mut HashSet<AuthorityName>,
@@ -203,6 +252,21 @@ async fn wait_for_one_gossip_task_to_finish<A>(
         debug!(peer = ?finished_name, "End gossip from peer");
     }
     peer_names.remove(&finished_name);
+    let committee = active_authority.state.committee.load();
+    record_active_peers(&active_authority.state, &committee, peer_names);
+}
+
+fn record_active_peers(
+    state: &AuthorityState,
+    committee: &Committee,
+    peer_names: &HashSet<AuthorityName>,
+) {
+    let stake: StakeUnit = peer_names.iter().map(|name| committee.weight(name)).sum();
+    state
+        .metrics
+        .gossip_active_peers
+        .set(peer_names.len() as i64);
+    state.metrics.gossip_active_stake.set(stake as i64);
 }
 
 pub struct LocalConfirmationTransactionHandler {
@@ -216,7 +280,7 @@ impl ConfirmationTransactionHandler for LocalConfirmationTransactionHandler {
     }
 
     fn destination_name(&self) -> String {
-        format!("{:?}", self.state.name)
+        format!("{:?}", self.state.name())
     }
 }
 
@@ -232,15 +296,34 @@ where
     // where n is the total number of committee members.
     let mut tries_remaining = active_authority.state.committee.load().num_members();
     while tries_remaining > 0 {
-        let name = *active_authority.state.committee.load().sample();
+        let committee = active_authority.state.committee.load().clone();
+        let (name, sampling) = sample_peer(&committee, &active_authority.peer_selection);
         if peer_names.contains(&name)
             || name == my_name
+            || !active_authority
+                .net
+                .load()
+                .authority_clients
+                .contains_key(&name)
             || !active_authority.can_contact(name).await
         {
             tries_remaining -= 1;
             tokio::time::sleep(Duration::from_millis(10)).await;
             continue;
         }
+        active_authority
+            .state
+            .metrics
+            .gossip_peer_selections
+            .with_label_values(&[sampling.as_str()])
+            .inc();
+        info!(
+            peer = ?name,
+            stake = committee.weight(&name),
+            total_stake = committee.total_votes,
+            sampling = sampling.as_str(),
+            "Selected peer to follow"
+        );
         return Ok(name);
     }
     Err(SuiError::GenericAuthorityError {
@@ -248,10 +331,47 @@ where
     })
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Sampling {
+    ByStake,
+    Uniform,
+}
+
+impl Sampling {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Sampling::ByStake => "by_stake",
+            Sampling::Uniform => "uniform",
+        }
+    }
+}
+
+/// Pick a committee member, by stake or uniformly as configured. Weighting by stake favours the
+/// validators most likely to be up to date, and the uniform picks made anyway at
+/// `exploration_rate` keep the ones with little stake followed now and then.
+fn sample_peer(
+    committee: &Committee,
+    selection: &PeerSelectionConfig,
+) -> (AuthorityName, Sampling) {
+    let mut rng = rand::thread_rng();
+    if selection.weight_by_stake && rng.gen::<f64>() >= selection.exploration_rate {
+        (*committee.sample(), Sampling::ByStake)
+    } else {
+        // unwrap safe unless committee is empty
+        let name = *committee.names().choose(&mut rng).unwrap();
+        (name, Sampling::Uniform)
+    }
+}
+
 #[async_trait]
 trait DigestHandler<A> {
     /// handle_digest
     async fn handle_digest(&self, follower: &Follower<A>, digest: ExecutionDigests) -> SuiResult;
+
+    /// Number of digests to request from a peer at once.
+    fn request_length(&self) -> u64 {
+        REQUEST_FOLLOW_NUM_DIGESTS
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -337,10 +457,16 @@ where
         };
 
         debug!(peer = ?peer_name, ?start_seq, "Restarting follower at sequence");
+        active_authority
+            .state
+            .sync_progress
+            .note_processed(peer_name, start_seq);
 
         Self {
             peer_name,
-            client: active_authority.net.load().authority_clients[&peer_name].clone(),
+            client: active_authority.net.load().authority_clients[&peer_name]
+                .clone()
+                .with_bandwidth_throttle(active_authority.follower_bandwidth.clone()),
             state: active_authority.state.clone(),
             follower_store: active_authority.follower_store.clone(),
             max_seq: Some(start_seq),
@@ -358,6 +484,29 @@ where
         (peer_name, result)
     }
 
+    /// Record the latest checkpoint the peer reports. Failing to get it leaves the previous
+    /// report in place, it doesn't stop following the peer.
+    async fn poll_peer_checkpoint(&self) {
+        match self
+            .client
+            .handle_checkpoint(CheckpointRequest::latest(false))
+            .await
+        {
+            Ok(CheckpointResponse {
+                info: AuthorityCheckpointInfo::Proposal { previous, .. },
+                ..
+            }) => {
+                if !matches!(previous, AuthenticatedCheckpoint::None) {
+                    self.state
+                        .sync_progress
+                        .note_peer_checkpoint(self.peer_name, previous.summary().sequence_number);
+                }
+            }
+            Ok(_) => (),
+            Err(e) => debug!(peer = ?self.peer_name, "Couldn't get the latest checkpoint: {e}"),
+        }
+    }
+
     async fn follow_peer_for_duration<'a, Handler: DigestHandler<A>>(
         &self,
         duration: Duration,
@@ -371,11 +520,12 @@ where
 
         let req = BatchInfoRequest {
             start: self.max_seq,
-            length: REQUEST_FOLLOW_NUM_DIGESTS,
+            length: handler.request_length(),
         };
 
         let mut last_seq_in_cur_batch: TxSequenceNumber = 0;
         let mut streamx = Box::pin(self.client.handle_batch_stream(req).await?);
+        let mut checkpoint_poll = tokio::time::interval(PEER_CHECKPOINT_POLL_INTERVAL);
 
         loop {
             tokio::select! {
@@ -384,12 +534,15 @@ where
                     break;
                 },
 
+                _ = checkpoint_poll.tick() => self.poll_peer_checkpoint().await,
+
                 items = &mut streamx.next() => {
                     match items {
                         Some(Ok(BatchInfoResponseItem(UpdateItem::Batch(signed_batch)) )) => {
                             let next_seq = signed_batch.batch.next_sequence_number;
                             debug!(?peer, batch_next_seq = ?next_seq, "Received signed batch");
                             batch_seq_to_record.push_back((next_seq, last_seq_in_cur_batch));
+                            self.state.sync_progress.note_peer_tip(peer, next_seq);
                             if let Some(max_seq) = self.max_seq {
                                 if next_seq < max_seq {
                                     info!("Gossip sequence number unexpected: found {:?} but previously received {:?}", next_seq, max_seq);
@@ -424,7 +577,7 @@ where
                             tokio::time::sleep(Duration::from_secs(REFRESH_FOLLOWER_PERIOD_SECS / 12)).await;
                             let req = BatchInfoRequest {
                                 start: self.max_seq,
-                                length: REQUEST_FOLLOW_NUM_DIGESTS,
+                                length: handler.request_length(),
                             };
                             streamx = Box::pin(self.client.handle_batch_stream(req).await?);
                         },
@@ -434,6 +587,7 @@ where
                 result = &mut results.next() , if !results.is_empty() => {
                     let (seq, digests) = result.unwrap()?;
                     trace!(?peer, ?seq, ?digests, "digest handler finished");
+                    self.state.sync_progress.note_processed(peer, seq + 1);
 
                     while let Some((batch_seq, last_seq_in_batch)) = batch_seq_to_record.front() {
                         if seq < *last_seq_in_batch {
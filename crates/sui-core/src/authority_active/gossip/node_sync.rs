@@ -1,23 +1,28 @@
 // Copyright (c) 2022, Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use super::{DigestHandler, Follower};
+use super::{
+    checkpoint_verifier::CheckpointVerifier, DigestHandler, Follower, REQUEST_FOLLOW_NUM_DIGESTS,
+};
 use crate::{
-    authority::AuthorityState, authority_aggregator::AuthorityAggregator,
-    authority_client::AuthorityAPI,
+    authority::AuthorityState, authority_active::ActiveAuthority, authority_client::AuthorityAPI,
+    safe_client::SafeClient,
 };
 use async_trait::async_trait;
 
-use std::collections::{hash_map, HashMap};
+use std::collections::{hash_map, BTreeMap, BTreeSet, HashMap, HashSet};
+use sui_config::{CatchUpBurstConfig, NodeSyncConfig};
 use sui_storage::node_sync_store::NodeSyncStore;
 use sui_types::{
     base_types::{AuthorityName, ExecutionDigests, TransactionDigest, TransactionEffectsDigest},
+    batch::TxSequenceNumber,
     committee::{Committee, StakeUnit},
     error::{SuiError, SuiResult},
     messages::{CertifiedTransaction, SignedTransactionEffects},
 };
 
 use std::ops::Deref;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 
 use tokio::sync::{broadcast, mpsc, oneshot, OwnedSemaphorePermit, Semaphore};
@@ -30,6 +35,66 @@ const NODE_SYNC_QUEUE_LEN: usize = 500;
 // Process up to 20 digests concurrently.
 const MAX_NODE_SYNC_CONCURRENCY: usize = 20;
 
+/// Raises the download concurrency and the number of digests requested at once while the node
+/// is far behind the peers it follows, see `NodeSyncConfig::catch_up_burst`.
+struct CatchUpBurst {
+    config: CatchUpBurstConfig,
+    active: AtomicBool,
+    /// Download permits beyond the steady-state concurrency, only handed out while bursting.
+    /// Downloads holding them when the burst ends run to completion.
+    extra_permits: Arc<Semaphore>,
+}
+
+impl CatchUpBurst {
+    fn new(config: CatchUpBurstConfig) -> Self {
+        let extra_permits = config.concurrency.saturating_sub(MAX_NODE_SYNC_CONCURRENCY);
+        Self {
+            config,
+            active: AtomicBool::new(false),
+            extra_permits: Arc::new(Semaphore::new(extra_permits)),
+        }
+    }
+
+    fn is_active(&self) -> bool {
+        self.active.load(Ordering::Relaxed)
+    }
+
+    /// Start or stop bursting depending on how far behind the node is. Returns whether it
+    /// changed.
+    fn update(&self, lag: Option<TxSequenceNumber>) -> bool {
+        let behind = matches!(lag, Some(lag) if lag > self.config.lag_threshold);
+        self.active.swap(behind, Ordering::Relaxed) != behind
+    }
+
+    fn request_length(&self) -> u64 {
+        if self.is_active() {
+            self.config.batch_size
+        } else {
+            REQUEST_FOLLOW_NUM_DIGESTS
+        }
+    }
+}
+
+/// Peers that announced different effects for the same transaction, with enough stake behind
+/// two of them that each side includes an honest validator. Honest validators always agree on
+/// the effects of a transaction, so there is no telling which side to follow.
+#[derive(Clone, Debug)]
+struct EffectsConflict {
+    /// The peers that announced each of the effects digests.
+    effects: BTreeMap<TransactionEffectsDigest, BTreeSet<AuthorityName>>,
+}
+
+/// What an announcement of the effects of a transaction means for syncing it.
+#[derive(Debug)]
+enum EffectsVote {
+    /// The transaction is synced once a quorum agrees on its effects.
+    Counted,
+    /// The announcement revealed a conflict, and the transaction won't be synced.
+    Conflict(EffectsConflict),
+    /// The transaction was found conflicting before.
+    Blocked,
+}
+
 /// EffectsStakeMap tracks which effects digests have been attested by a quorum of validators and
 /// are thus final.
 struct EffectsStakeMap {
@@ -40,6 +105,11 @@ struct EffectsStakeMap {
     /// Keep track of stake votes per validator - needed to double check the total stored in
     /// effects_stake_map, which can otherwise be corrupted by byzantine double-voting.
     effects_vote_map: HashMap<TransactionEffectsDigest, HashMap<AuthorityName, StakeUnit>>,
+    /// The effects digests announced for each transaction that wasn't executed yet, and by whom.
+    tx_effects_map:
+        HashMap<TransactionDigest, BTreeMap<TransactionEffectsDigest, BTreeSet<AuthorityName>>>,
+    /// Transactions that won't be synced because peers announced conflicting effects for them.
+    blocked_txes: HashSet<TransactionDigest>,
 }
 
 impl EffectsStakeMap {
@@ -47,9 +117,70 @@ impl EffectsStakeMap {
         Self {
             effects_stake_map: HashMap::new(),
             effects_vote_map: HashMap::new(),
+            tx_effects_map: HashMap::new(),
+            blocked_txes: HashSet::new(),
         }
     }
 
+    /// Note which effects a validator announced for a transaction. A few peers announcing
+    /// other effects than the rest don't keep it from being synced with the effects a quorum
+    /// agrees on, but two effects with the validity threshold of stake behind each do.
+    pub fn note_tx_effects(
+        &mut self,
+        source: &AuthorityName,
+        digests: &ExecutionDigests,
+        committee: &Committee,
+    ) -> EffectsVote {
+        if self.blocked_txes.contains(&digests.transaction) {
+            return EffectsVote::Blocked;
+        }
+        let effects = self.tx_effects_map.entry(digests.transaction).or_default();
+        effects.entry(digests.effects).or_default().insert(*source);
+
+        let validity_threshold = committee.validity_threshold();
+        let valid = effects
+            .values()
+            .filter(|peers| {
+                peers
+                    .iter()
+                    .map(|peer| committee.weight(peer))
+                    .sum::<StakeUnit>()
+                    >= validity_threshold
+            })
+            .count();
+        if valid < 2 {
+            return EffectsVote::Counted;
+        }
+
+        // Only the digest of the transaction is kept once it is reported.
+        let effects = self
+            .tx_effects_map
+            .remove(&digests.transaction)
+            .unwrap_or_default();
+        for effects_digest in effects.keys() {
+            self.forget_effects(effects_digest);
+        }
+        self.blocked_txes.insert(digests.transaction);
+        EffectsVote::Conflict(EffectsConflict { effects })
+    }
+
+    /// Forget the announcements of a transaction synced with `digests.effects`, and return the
+    /// peers that announced other effects for it.
+    pub fn forget_transaction(&mut self, digests: &ExecutionDigests) -> BTreeSet<AuthorityName> {
+        let mut outvoted = BTreeSet::new();
+        for (effects_digest, peers) in self
+            .tx_effects_map
+            .remove(&digests.transaction)
+            .unwrap_or_default()
+        {
+            if effects_digest != digests.effects {
+                self.forget_effects(&effects_digest);
+                outvoted.extend(peers);
+            }
+        }
+        outvoted
+    }
+
     /// Note that a given effects digest has been attested by a validator, and return true if the
     /// stake that has attested that effects digest has exceeded the quorum threshold.
     pub fn note_effects_digest(
@@ -146,9 +277,12 @@ where
     }
 }
 
-struct DigestsMessage {
+struct DigestsMessage<A> {
     digests: ExecutionDigests,
     peer: AuthorityName,
+    /// The client of the follower the digests were received from, used to download them. The
+    /// set of peers may change while the node is running, so this isn't looked up later.
+    client: SafeClient<A>,
     tx: oneshot::Sender<SuiResult>,
 }
 
@@ -160,7 +294,16 @@ struct NodeSyncState<A> {
     effects_stake: Mutex<EffectsStakeMap>,
     state: Arc<AuthorityState>,
     node_sync_store: Arc<NodeSyncStore>,
-    aggregator: Arc<AuthorityAggregator<A>>,
+    config: NodeSyncConfig,
+
+    // Peers whose announcements are ignored, see `NodeSyncConfig::quarantine_equivocating_peers`.
+    quarantined: Mutex<HashSet<AuthorityName>>,
+
+    // Set when `NodeSyncConfig::verify_against_checkpoints` is.
+    checkpoint_verifier: Option<CheckpointVerifier<A>>,
+
+    // Set when `NodeSyncConfig::catch_up_burst` is.
+    burst: Option<Arc<CatchUpBurst>>,
 
     // Used to single-shot multiple concurrent downloads.
     pending_downloads: Waiter<TransactionDigest, SuiResult>,
@@ -173,22 +316,28 @@ impl<A> NodeSyncState<A>
 where
     A: AuthorityAPI + Send + Sync + 'static + Clone,
 {
-    fn start(self, mut receiver: mpsc::Receiver<DigestsMessage>) -> JoinHandle<()> {
+    fn start(self, mut receiver: mpsc::Receiver<DigestsMessage<A>>) -> JoinHandle<()> {
         let state = Arc::new(self);
         tokio::spawn(async move {
             // this pattern for limiting concurrency is from
             // https://github.com/tokio-rs/tokio/discussions/2648
             let limit = Arc::new(Semaphore::new(MAX_NODE_SYNC_CONCURRENCY));
 
-            while let Some(DigestsMessage { digests, peer, tx }) = receiver.recv().await {
+            while let Some(DigestsMessage {
+                digests,
+                peer,
+                client,
+                tx,
+            }) = receiver.recv().await
+            {
+                state.update_burst();
                 let state = state.clone();
                 let limit = limit.clone();
                 tokio::spawn(async move {
-                    // hold semaphore permit until task completes. unwrap ok because we never close
-                    // the semaphore in this context.
-                    let permit = limit.acquire_owned().await.unwrap();
+                    // hold semaphore permit until task completes.
+                    let permit = state.acquire_download_permit(limit).await;
 
-                    let res = state.process_digest(peer, digests, permit).await;
+                    let res = state.process_digest(peer, client, digests, permit).await;
                     if let Err(error) = &res {
                         error!(?digests, ?peer, "process_digest failed: {}", error);
                     }
@@ -210,19 +359,82 @@ where
         })
     }
 
+    fn update_burst(&self) {
+        let burst = match &self.burst {
+            Some(burst) => burst,
+            None => return,
+        };
+        let lag = self.state.sync_progress.lag();
+        if !burst.update(lag) {
+            return;
+        }
+        if burst.is_active() {
+            info!(?lag, "Node sync is far behind, catching up in burst mode");
+        } else {
+            info!(?lag, "Node sync caught up, leaving burst mode");
+        }
+        self.state
+            .metrics
+            .node_sync_burst_active
+            .set(burst.is_active() as i64);
+    }
+
+    async fn acquire_download_permit(&self, limit: Arc<Semaphore>) -> OwnedSemaphorePermit {
+        // unwraps ok because we never close the semaphores in this context.
+        match &self.burst {
+            Some(burst) if burst.is_active() => {
+                tokio::select! {
+                    permit = limit.acquire_owned() => permit.unwrap(),
+                    permit = burst.extra_permits.clone().acquire_owned() => permit.unwrap(),
+                }
+            }
+            _ => limit.acquire_owned().await.unwrap(),
+        }
+    }
+
     async fn process_digest(
         &self,
         peer: AuthorityName,
+        client: SafeClient<A>,
         digests: ExecutionDigests,
         permit: OwnedSemaphorePermit,
     ) -> SuiResult {
         trace!(?digests, ?peer, "process_digest");
 
+        if self.quarantined.lock().unwrap().contains(&peer) {
+            return Err(SuiError::ByzantineAuthoritySuspicion { authority: peer });
+        }
+
         // check if we the tx is already locally final
         if self.state.database.effects_exists(&digests.transaction)? {
             return Ok(());
         }
 
+        let vote =
+            self.effects_stake
+                .lock()
+                .unwrap()
+                .note_tx_effects(&peer, &digests, &self.committee);
+        match vote {
+            EffectsVote::Counted => (),
+            EffectsVote::Conflict(conflict) => {
+                self.state.metrics.node_sync_effects_conflicts.inc();
+                error!(
+                    digest = ?digests.transaction,
+                    effects = ?conflict.effects,
+                    "Peers announced conflicting effects, the transaction will not be executed"
+                );
+                return Err(SuiError::ConflictingTransactionEffects {
+                    digest: digests.transaction,
+                });
+            }
+            EffectsVote::Blocked => {
+                return Err(SuiError::ConflictingTransactionEffects {
+                    digest: digests.transaction,
+                })
+            }
+        }
+
         // TODO: We could kick off the cert download now, as an optimization. For simplicity
         // we wait until we have the final effects digest and download them both at once, after the
         // is_final check. We can't download the effects yet because a SignedEffects is signed
@@ -265,12 +477,31 @@ where
 
         // Download the cert and effects now that we have established finality and we know that the
         // effects digest is correct.
-        let (cert, effects) = self.download_cert_and_effects(&peer, &digests).await?;
+        let (cert, effects) = self
+            .download_cert_and_effects(&peer, client.clone(), &digests)
+            .await?;
 
         // we're done downloading at this point, so we no longer need to prevent other tasks from
         // starting.
         std::mem::drop(permit);
 
+        if let Some(verifier) = &self.checkpoint_verifier {
+            let result = verifier
+                .verify(peer, &client, &digests, &effects.effects)
+                .await;
+            if let Err(error @ SuiError::CheckpointVerificationFailed { .. }) = &result {
+                self.state
+                    .metrics
+                    .node_sync_checkpoint_verification_failures
+                    .inc();
+                error!(?digests, ?peer, "Checkpoint verification failed: {error}");
+                // Don't execute what was downloaded when the transaction is retried.
+                self.node_sync_store
+                    .delete_cert_and_effects(&digests.transaction)?;
+            }
+            result?;
+        }
+
         for parent in effects.effects.dependencies.iter() {
             let (_, mut rx) = self.pending_txes.wait(parent).await;
 
@@ -301,10 +532,19 @@ where
         // Garbage collect data for this tx.
         self.node_sync_store
             .delete_cert_and_effects(&digests.transaction)?;
-        self.effects_stake
-            .lock()
-            .unwrap()
-            .forget_effects(&digests.effects);
+        let outvoted = {
+            let mut effects_stake = self.effects_stake.lock().unwrap();
+            effects_stake.forget_effects(&digests.effects);
+            effects_stake.forget_transaction(&digests)
+        };
+        if !outvoted.is_empty() {
+            self.handle_outvoted(&digests.transaction, &outvoted);
+        }
+        if let Some(verifier) = &self.checkpoint_verifier {
+            if let Some(synced) = verifier.forget_transaction(&digests.transaction) {
+                self.state.sync_progress.note_synced_checkpoint(synced);
+            }
+        }
 
         // Notify waiting child transactions.
         trace!(digest = ?digests.transaction, "notifying parent");
@@ -312,10 +552,39 @@ where
         Ok(())
     }
 
+    /// Report the peers that announced other effects for a transaction than the quorum it was
+    /// synced with, and quarantine them if configured to.
+    fn handle_outvoted(&self, digest: &TransactionDigest, outvoted: &BTreeSet<AuthorityName>) {
+        warn!(
+            ?digest,
+            ?outvoted,
+            "Peers announced other effects than a quorum of validators"
+        );
+        if !self.config.quarantine_equivocating_peers {
+            return;
+        }
+
+        let mut quarantined = self.quarantined.lock().unwrap();
+        for peer in outvoted {
+            if quarantined.insert(*peer) {
+                error!(
+                    ?peer,
+                    ?digest,
+                    "Quarantining peer for announcing conflicting effects"
+                );
+            }
+        }
+        self.state
+            .metrics
+            .node_sync_quarantined_peers
+            .set(quarantined.len() as i64);
+    }
+
     // Download the certificate and effects specified in digests.
     async fn download_cert_and_effects(
         &self,
         peer: &AuthorityName,
+        client: SafeClient<A>,
         digests: &ExecutionDigests,
     ) -> SuiResult<(CertifiedTransaction, SignedTransactionEffects)> {
         let digest = digests.transaction;
@@ -326,13 +595,12 @@ where
         let (tx, mut rx) = self.pending_downloads.wait(&digest).await;
         // Only start the download if there are no other concurrent downloads.
         if let Some(tx) = tx {
-            let aggregator = self.aggregator.clone();
             let digests = *digests;
             let peer = *peer;
             let node_sync_store = self.node_sync_store.clone();
             tokio::task::spawn(async move {
                 if let Err(error) =
-                    tx.send(Self::download_impl(peer, aggregator, &digests, node_sync_store).await)
+                    tx.send(Self::download_impl(peer, client, &digests, node_sync_store).await)
                 {
                     error!(?digest, ?peer, ?error, "Could not broadcast cert response");
                 }
@@ -357,7 +625,7 @@ where
 
     async fn download_impl(
         peer: AuthorityName,
-        aggregator: Arc<AuthorityAggregator<A>>,
+        client: SafeClient<A>,
         digests: &ExecutionDigests,
         node_sync_store: Arc<NodeSyncStore>,
     ) -> SuiResult {
@@ -367,7 +635,6 @@ where
         // though we are fetching the cert/effects from the same validator that sent us the tx
         // digest, and even though we know the cert is final, any given validator may be byzantine
         // and refuse to give us the cert and effects.
-        let client = aggregator.clone_client(&peer);
         let resp = client
             .handle_transaction_and_effects_info_request(digests)
             .await
@@ -392,29 +659,47 @@ where
 }
 
 #[derive(Clone)]
-pub struct NodeSyncDigestHandler {
+pub struct NodeSyncDigestHandler<A> {
     _sync_join_handle: Arc<JoinHandle<()>>,
-    sender: mpsc::Sender<DigestsMessage>,
+    sender: mpsc::Sender<DigestsMessage<A>>,
+    burst: Option<Arc<CatchUpBurst>>,
 }
 
-impl NodeSyncDigestHandler {
-    pub fn new<A>(
-        state: Arc<AuthorityState>,
-        aggregator: Arc<AuthorityAggregator<A>>,
+impl<A> NodeSyncDigestHandler<A>
+where
+    A: AuthorityAPI + Send + Sync + 'static + Clone,
+{
+    pub fn new(
+        active_authority: Arc<ActiveAuthority<A>>,
         node_sync_store: Arc<NodeSyncStore>,
-    ) -> Self
-    where
-        A: AuthorityAPI + Send + Sync + 'static + Clone,
-    {
+        config: NodeSyncConfig,
+    ) -> Self {
         let (sender, receiver) = mpsc::channel(NODE_SYNC_QUEUE_LEN);
 
+        let state = active_authority.state.clone();
         let committee = state.committee.load().deref().clone();
+        let checkpoint_verifier = config.verify_against_checkpoints.then(|| {
+            CheckpointVerifier::new(
+                active_authority.clone(),
+                active_authority.trusted_checkpoint.clone(),
+                config.checkpoint_peer_retries,
+                config.checkpoint_download_concurrency,
+                node_sync_store.clone(),
+            )
+        });
+        let burst = config
+            .catch_up_burst
+            .clone()
+            .map(|config| Arc::new(CatchUpBurst::new(config)));
         let sync_state = NodeSyncState {
             committee,
             effects_stake: Mutex::new(EffectsStakeMap::new()),
             state,
-            aggregator,
             node_sync_store,
+            config,
+            quarantined: Mutex::new(HashSet::new()),
+            checkpoint_verifier,
+            burst: burst.clone(),
             pending_downloads: Waiter::new(),
             pending_txes: Waiter::new(),
         };
@@ -424,12 +709,13 @@ impl NodeSyncDigestHandler {
         Self {
             _sync_join_handle,
             sender,
+            burst,
         }
     }
 }
 
 #[async_trait]
-impl<A> DigestHandler<A> for NodeSyncDigestHandler
+impl<A> DigestHandler<A> for NodeSyncDigestHandler<A>
 where
     A: AuthorityAPI + Send + Sync + 'static + Clone,
 {
@@ -439,6 +725,7 @@ where
             .send(DigestsMessage {
                 digests,
                 peer: follower.peer_name,
+                client: follower.client.clone(),
                 tx,
             })
             .await
@@ -449,6 +736,12 @@ where
             error: e.to_string(),
         })?
     }
+
+    fn request_length(&self) -> u64 {
+        self.burst
+            .as_ref()
+            .map_or(REQUEST_FOLLOW_NUM_DIGESTS, |burst| burst.request_length())
+    }
 }
 
 #[cfg(test)]
@@ -456,11 +749,14 @@ mod tests {
     // Note: this code is tested end-to-end in full_node_tests.rs
 
     use sui_types::{
-        base_types::{AuthorityName, TransactionEffectsDigest},
+        base_types::{AuthorityName, ExecutionDigests, TransactionEffectsDigest},
+        committee::Committee,
         crypto::get_key_pair,
     };
 
-    use super::EffectsStakeMap;
+    use super::{CatchUpBurst, EffectsStakeMap, EffectsVote, REQUEST_FOLLOW_NUM_DIGESTS};
+    use std::collections::BTreeSet;
+    use sui_config::CatchUpBurstConfig;
 
     fn random_authority_name() -> AuthorityName {
         let key = get_key_pair();
@@ -491,4 +787,94 @@ mod tests {
         // double vote doesn't result in false if we already exceeded threshold.
         assert!(map.note_effects_digest(&byzantine, 1, threshold, &digests));
     }
+
+    #[test]
+    fn test_conflicting_tx_effects() {
+        let mut map = EffectsStakeMap::new();
+
+        let validator1 = random_authority_name();
+        let validator2 = random_authority_name();
+        let validator3 = random_authority_name();
+        let byzantine = random_authority_name();
+        let committee = Committee::new(
+            0,
+            [validator1, validator2, validator3, byzantine]
+                .into_iter()
+                .map(|name| (name, 1))
+                .collect(),
+        )
+        .unwrap();
+
+        // A single peer announcing other effects doesn't block the transaction.
+        let digests = ExecutionDigests::random();
+        let bad_digests =
+            ExecutionDigests::new(digests.transaction, TransactionEffectsDigest::random());
+        for (peer, digests) in [
+            (validator1, digests),
+            (byzantine, bad_digests),
+            (validator2, digests),
+        ] {
+            let vote = map.note_tx_effects(&peer, &digests, &committee);
+            assert!(matches!(vote, EffectsVote::Counted));
+        }
+
+        // It is outvoted once the transaction is synced.
+        assert_eq!(
+            map.forget_transaction(&digests),
+            BTreeSet::from([byzantine])
+        );
+        assert!(map.forget_transaction(&digests).is_empty());
+
+        // Two effects with the validity threshold behind each block it.
+        let digests = ExecutionDigests::random();
+        let bad_digests =
+            ExecutionDigests::new(digests.transaction, TransactionEffectsDigest::random());
+        for (peer, digests) in [
+            (validator1, digests),
+            (validator2, digests),
+            (byzantine, bad_digests),
+        ] {
+            let vote = map.note_tx_effects(&peer, &digests, &committee);
+            assert!(matches!(vote, EffectsVote::Counted));
+        }
+        match map.note_tx_effects(&validator3, &bad_digests, &committee) {
+            EffectsVote::Conflict(conflict) => {
+                assert_eq!(conflict.effects[&digests.effects].len(), 2);
+                assert!(conflict.effects[&bad_digests.effects].contains(&byzantine));
+            }
+            vote => panic!("Unexpected {vote:?}"),
+        }
+
+        // The conflict is only reported once, and the announcements aren't kept.
+        let vote = map.note_tx_effects(&validator1, &digests, &committee);
+        assert!(matches!(vote, EffectsVote::Blocked));
+        assert!(!map.tx_effects_map.contains_key(&digests.transaction));
+
+        // Other transactions aren't affected.
+        let vote = map.note_tx_effects(&validator1, &ExecutionDigests::random(), &committee);
+        assert!(matches!(vote, EffectsVote::Counted));
+    }
+
+    #[test]
+    fn test_catch_up_burst() {
+        let burst = CatchUpBurst::new(CatchUpBurstConfig {
+            lag_threshold: 1_000,
+            concurrency: 50,
+            batch_size: 500_000,
+        });
+        assert_eq!(burst.extra_permits.available_permits(), 30);
+
+        // Unknown lag doesn't start a burst.
+        assert!(!burst.update(None));
+        assert!(!burst.update(Some(1_000)));
+        assert_eq!(burst.request_length(), REQUEST_FOLLOW_NUM_DIGESTS);
+
+        assert!(burst.update(Some(1_001)));
+        assert!(burst.is_active());
+        assert_eq!(burst.request_length(), 500_000);
+        assert!(!burst.update(Some(5_000)));
+
+        assert!(burst.update(Some(10)));
+        assert!(!burst.is_active());
+    }
 }
@@ -0,0 +1,272 @@
+// Copyright (c) 2022, Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Estimates how long the node needs to catch up with the validators it follows.
+//!
+//! For every followed peer we track the latest sequence number it told us about (its tip, as
+//! far as we know) and how far we have processed its stream. The sync rate is a moving average
+//! over the recently processed sequence numbers of each peer.
+//!
+//! Node sync verifying against checkpoints also records the latest checkpoint whose
+//! transactions were all executed, for reads that must see at least a given checkpoint.
+//!
+//! Followers also record the latest checkpoint each peer reports, so that lagging or offline
+//! validators can be told apart from the node's own view.
+//!
+//! Node sync records why it halted when it can't go on safely, for readiness checks.
+
+use parking_lot::Mutex;
+use std::{
+    collections::{HashMap, VecDeque},
+    time::{Duration, Instant},
+};
+use sui_types::{
+    base_types::AuthorityName, batch::TxSequenceNumber, error::SuiError,
+    messages_checkpoint::CheckpointSequenceNumber,
+};
+
+/// Processed sequence numbers older than this are not used to compute the sync rate.
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+const MAX_SAMPLES: usize = 600;
+
+#[derive(Default)]
+struct PeerProgress {
+    /// Highest `next_sequence_number` of the batches received from the peer.
+    tip: Option<TxSequenceNumber>,
+    /// When the peer's sequence numbers below the sample were processed, oldest first.
+    samples: VecDeque<(Instant, TxSequenceNumber)>,
+}
+
+impl PeerProgress {
+    fn processed(&self) -> Option<TxSequenceNumber> {
+        self.samples.back().map(|(_, seq)| *seq)
+    }
+
+    /// Sequence numbers processed per second over the window, if anything was processed.
+    fn rate(&self) -> Option<f64> {
+        let (first_time, first_seq) = self.samples.front()?;
+        let (last_time, last_seq) = self.samples.back()?;
+        let elapsed = last_time.duration_since(*first_time).as_secs_f64();
+        if last_seq > first_seq && elapsed > 0.0 {
+            Some((last_seq - first_seq) as f64 / elapsed)
+        } else {
+            None
+        }
+    }
+
+    fn eta(&self) -> Option<Duration> {
+        let remaining = self.tip?.saturating_sub(self.processed()?);
+        if remaining == 0 {
+            return Some(Duration::ZERO);
+        }
+        let rate = self.rate()?;
+        Some(Duration::from_secs_f64(remaining as f64 / rate))
+    }
+}
+
+#[derive(Default)]
+pub struct SyncProgress {
+    peers: Mutex<HashMap<AuthorityName, PeerProgress>>,
+    synced_checkpoint: Mutex<Option<CheckpointSequenceNumber>>,
+    /// The latest checkpoint each peer reported, and when.
+    peer_checkpoints: Mutex<HashMap<AuthorityName, (CheckpointSequenceNumber, Instant)>>,
+    /// Why node sync stopped for good, if it did.
+    halted: Mutex<Option<SuiError>>,
+}
+
+impl SyncProgress {
+    /// Record that `peer` has a batch ending before `next_sequence_number`.
+    pub fn note_peer_tip(&self, peer: AuthorityName, next_sequence_number: TxSequenceNumber) {
+        let mut peers = self.peers.lock();
+        let progress = peers.entry(peer).or_default();
+        progress.tip = progress.tip.max(Some(next_sequence_number));
+    }
+
+    /// Record that all of `peer`'s transactions before `next_sequence_number` were processed.
+    pub fn note_processed(&self, peer: AuthorityName, next_sequence_number: TxSequenceNumber) {
+        self.note_processed_at(peer, next_sequence_number, Instant::now())
+    }
+
+    fn note_processed_at(
+        &self,
+        peer: AuthorityName,
+        next_sequence_number: TxSequenceNumber,
+        now: Instant,
+    ) {
+        let mut peers = self.peers.lock();
+        let samples = &mut peers.entry(peer).or_default().samples;
+        // Followers restart from the last recorded batch, which may be behind what was already
+        // processed.
+        if matches!(samples.back(), Some((_, seq)) if *seq > next_sequence_number) {
+            return;
+        }
+        samples.push_back((now, next_sequence_number));
+        while let Some((time, _)) = samples.front() {
+            if samples.len() <= MAX_SAMPLES && now.duration_since(*time) <= RATE_WINDOW {
+                break;
+            }
+            samples.pop_front();
+        }
+    }
+
+    /// Most sequence numbers a followed peer is known to have beyond those processed from it.
+    /// `None` until both are known for some peer.
+    pub fn lag(&self) -> Option<TxSequenceNumber> {
+        self.peers
+            .lock()
+            .values()
+            .filter_map(|progress| Some(progress.tip?.saturating_sub(progress.processed()?)))
+            .max()
+    }
+
+    /// Record that the transactions of every checkpoint up to `checkpoint` were executed.
+    pub fn note_synced_checkpoint(&self, checkpoint: CheckpointSequenceNumber) {
+        let mut synced = self.synced_checkpoint.lock();
+        *synced = (*synced).max(Some(checkpoint));
+    }
+
+    /// The latest checkpoint whose transactions, and those of the checkpoints before it, were
+    /// all executed. `None` until node sync verified one.
+    pub fn synced_checkpoint(&self) -> Option<CheckpointSequenceNumber> {
+        *self.synced_checkpoint.lock()
+    }
+
+    /// Record that node sync stopped for good because of `error`. Only the first error is kept.
+    pub fn note_halted(&self, error: SuiError) {
+        self.halted.lock().get_or_insert(error);
+    }
+
+    /// Why node sync stopped for good. `None` while it runs.
+    pub fn halted(&self) -> Option<SuiError> {
+        self.halted.lock().clone()
+    }
+
+    /// Record that `peer` reported `checkpoint` as its latest.
+    pub fn note_peer_checkpoint(&self, peer: AuthorityName, checkpoint: CheckpointSequenceNumber) {
+        self.peer_checkpoints
+            .lock()
+            .insert(peer, (checkpoint, Instant::now()));
+    }
+
+    /// The latest checkpoint `peer` reported, and how long ago. `None` until it reported one.
+    pub fn peer_checkpoint(
+        &self,
+        peer: &AuthorityName,
+    ) -> Option<(CheckpointSequenceNumber, Duration)> {
+        let (checkpoint, reported) = self.peer_checkpoints.lock().get(peer).copied()?;
+        Some((checkpoint, reported.elapsed()))
+    }
+
+    /// Estimated time until the node processed the tip of every peer it follows. `None` when
+    /// there isn't enough data yet, or a lagging peer's stream isn't making progress.
+    pub fn eta(&self) -> Option<Duration> {
+        let peers = self.peers.lock();
+        if peers.is_empty() {
+            return None;
+        }
+        peers
+            .values()
+            .map(PeerProgress::eta)
+            .try_fold(Duration::ZERO, |max, eta| Some(max.max(eta?)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sui_types::crypto::get_key_pair;
+
+    #[test]
+    fn test_sync_eta() {
+        let progress = SyncProgress::default();
+        let peer = *get_key_pair().1.public_key_bytes();
+        let start = Instant::now();
+
+        assert_eq!(progress.eta(), None);
+
+        progress.note_peer_tip(peer, 1_000);
+        progress.note_processed_at(peer, 100, start);
+        // No rate yet.
+        assert_eq!(progress.eta(), None);
+
+        // 100 sequence numbers per second, 800 to go.
+        progress.note_processed_at(peer, 200, start + Duration::from_secs(1));
+        assert_eq!(progress.eta(), Some(Duration::from_secs(8)));
+
+        // An older tip doesn't move it back.
+        progress.note_peer_tip(peer, 500);
+        assert_eq!(progress.eta(), Some(Duration::from_secs(8)));
+
+        // Restarting the follower from an earlier batch doesn't either.
+        progress.note_processed_at(peer, 150, start + Duration::from_secs(1));
+        assert_eq!(progress.eta(), Some(Duration::from_secs(8)));
+
+        progress.note_processed_at(peer, 1_000, start + Duration::from_secs(2));
+        assert_eq!(progress.eta(), Some(Duration::ZERO));
+
+        // A second peer we know nothing about makes the estimate unknown.
+        let other = *get_key_pair().1.public_key_bytes();
+        progress.note_peer_tip(other, 10);
+        assert_eq!(progress.eta(), None);
+    }
+
+    #[test]
+    fn test_sync_lag() {
+        let progress = SyncProgress::default();
+        let (peer, other) = (
+            *get_key_pair().1.public_key_bytes(),
+            *get_key_pair().1.public_key_bytes(),
+        );
+        assert_eq!(progress.lag(), None);
+
+        progress.note_peer_tip(peer, 1_000);
+        assert_eq!(progress.lag(), None);
+        progress.note_processed(peer, 100);
+        assert_eq!(progress.lag(), Some(900));
+
+        // The peer furthest ahead counts.
+        progress.note_peer_tip(other, 50);
+        progress.note_processed(other, 0);
+        assert_eq!(progress.lag(), Some(900));
+        progress.note_processed(peer, 1_000);
+        assert_eq!(progress.lag(), Some(50));
+    }
+
+    #[test]
+    fn test_synced_checkpoint() {
+        let progress = SyncProgress::default();
+        assert_eq!(progress.synced_checkpoint(), None);
+        progress.note_synced_checkpoint(3);
+        // It never goes back.
+        progress.note_synced_checkpoint(1);
+        assert_eq!(progress.synced_checkpoint(), Some(3));
+    }
+
+    #[test]
+    fn test_peer_checkpoints() {
+        let progress = SyncProgress::default();
+        let (peer, other) = (
+            *get_key_pair().1.public_key_bytes(),
+            *get_key_pair().1.public_key_bytes(),
+        );
+        progress.note_peer_checkpoint(peer, 5);
+        progress.note_peer_checkpoint(peer, 7);
+        assert_eq!(progress.peer_checkpoint(&peer).map(|(seq, _)| seq), Some(7));
+        assert_eq!(progress.peer_checkpoint(&other), None);
+    }
+
+    #[test]
+    fn test_sync_halted() {
+        let progress = SyncProgress::default();
+        assert_eq!(progress.halted(), None);
+        let error = |sequence| SuiError::NoValidCheckpointCertificate {
+            sequence,
+            attempts: 1,
+            error: "invalid signature".to_string(),
+        };
+        progress.note_halted(error(4));
+        // The first reason is kept.
+        progress.note_halted(error(5));
+        assert_eq!(progress.halted(), Some(error(4)));
+    }
+}
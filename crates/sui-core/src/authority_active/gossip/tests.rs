@@ -7,8 +7,9 @@ use crate::authority_active::gossip::configurable_batch_action_client::{
 };
 use crate::authority_active::MAX_RETRY_DELAY_MS;
 use crate::gateway_state::GatewayMetrics;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::time::Duration;
+use sui_types::crypto::get_key_pair;
 use tokio::task::JoinHandle;
 
 #[tokio::test(flavor = "current_thread", start_paused = true)]
@@ -153,6 +154,88 @@ pub async fn test_gossip_after_revert() {
     assert_eq!(all_batch_item_counts.iter().filter(|c| *c == &2).count(), 1);
 }
 
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+pub async fn test_reconcile_authority_clients() {
+    let action_sequence = vec![BatchAction::EmitUpdateItem()];
+    let (clients, states, _) = init_configurable_authorities(action_sequence).await;
+    let active_authority = ActiveAuthority::new_with_ephemeral_follower_store(
+        states[0].clone(),
+        clients.clone(),
+        GatewayMetrics::new_for_tests(),
+    )
+    .unwrap();
+    let peers = |active_authority: &ActiveAuthority<_>| -> BTreeSet<_> {
+        active_authority
+            .net
+            .load()
+            .authority_clients
+            .keys()
+            .copied()
+            .collect()
+    };
+
+    let all: BTreeSet<_> = clients.keys().copied().collect();
+    let removed = *all.iter().next().unwrap();
+    let mut kept = all.clone();
+    kept.remove(&removed);
+
+    // Remaining peers keep their clients.
+    active_authority
+        .reconcile_authority_clients(&kept, |_| panic!("no peer was added"))
+        .unwrap();
+    assert_eq!(peers(&active_authority), kept);
+
+    // Nothing changes when connecting to a new peer fails.
+    let result = active_authority.reconcile_authority_clients(&all, |_| {
+        Err(SuiError::GenericAuthorityError {
+            error: "unreachable".to_string(),
+        })
+    });
+    assert!(result.is_err());
+    assert_eq!(peers(&active_authority), kept);
+
+    active_authority
+        .reconcile_authority_clients(&all, |name| {
+            assert_eq!(*name, removed);
+            Ok(clients[name].clone())
+        })
+        .unwrap();
+    assert_eq!(peers(&active_authority), all);
+}
+
+#[test]
+fn test_sample_peer() {
+    let small = *get_key_pair().1.public_key_bytes();
+    let large = *get_key_pair().1.public_key_bytes();
+    let committee = Committee::new(0, BTreeMap::from([(small, 1), (large, 1_000)])).unwrap();
+    let small_picks = |selection: &PeerSelectionConfig| {
+        (0..1_000)
+            .filter(|_| sample_peer(&committee, selection).0 == small)
+            .count()
+    };
+
+    let by_stake = PeerSelectionConfig {
+        weight_by_stake: true,
+        exploration_rate: 0.0,
+    };
+    assert_eq!(sample_peer(&committee, &by_stake).1, Sampling::ByStake);
+    assert!(small_picks(&by_stake) < 50);
+
+    // Exploring picks the small validator about half the time it doesn't weight by stake.
+    let exploring = PeerSelectionConfig {
+        weight_by_stake: true,
+        exploration_rate: 0.5,
+    };
+    assert!((100..400).contains(&small_picks(&exploring)));
+
+    let uniform = PeerSelectionConfig {
+        weight_by_stake: false,
+        exploration_rate: 0.0,
+    };
+    assert_eq!(sample_peer(&committee, &uniform).1, Sampling::Uniform);
+    assert!((350..650).contains(&small_picks(&uniform)));
+}
+
 async fn start_gossip_process(
     states: Vec<Arc<AuthorityState>>,
     clients: BTreeMap<AuthorityName, ConfigurableBatchActionClient>,
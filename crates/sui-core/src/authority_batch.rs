@@ -83,8 +83,9 @@ impl crate::authority::AuthorityState {
             Some((_, last_batch)) => last_batch.batch,
             None => {
                 // Make a batch at zero
+                let identity = self.identity();
                 let zero_batch =
-                    SignedBatch::new(AuthorityBatch::initial(), &*self.secret, self.name);
+                    SignedBatch::new(AuthorityBatch::initial(), &*identity.secret, identity.name);
                 self.db().batches.insert(&0, &zero_batch)?;
                 zero_batch.batch
             }
@@ -100,11 +101,12 @@ impl crate::authority::AuthorityState {
 
         if !transactions.is_empty() {
             // Make a new batch, to put the old transactions not in a batch in.
+            let identity = self.identity();
             let last_signed_batch = SignedBatch::new(
                 // Unwrap safe due to check not empty
                 AuthorityBatch::make_next(&last_batch, &transactions)?,
-                &*self.secret,
-                self.name,
+                &*identity.secret,
+                identity.name,
             );
             self.db().batches.insert(
                 &last_signed_batch.batch.next_sequence_number,
@@ -116,6 +118,8 @@ impl crate::authority::AuthorityState {
         Ok(last_batch)
     }
 
+    /// Batch the executed transactions until the authority shuts down, see
+    /// [`AuthorityState::begin_shutdown`].
     pub async fn run_batch_service(
         &self,
         min_batch_size: u64,
@@ -176,6 +180,11 @@ impl crate::authority::AuthorityState {
                         }
                     }
                 }
+                _ = self.shutdown_requested() => {
+                    // Batch the transactions received so far before stopping.
+                    make_batch = true;
+                    exit = true;
+                }
             }
 
             // Logic to make a batch
@@ -186,11 +195,12 @@ impl crate::authority::AuthorityState {
                 }
 
                 // Make and store a new batch.
+                let identity = self.identity();
                 let new_batch = SignedBatch::new(
                     // Unwrap safe since we tested above it is not empty
                     AuthorityBatch::make_next(&prev_batch, &current_batch).unwrap(),
-                    &*self.secret,
-                    self.name,
+                    &*identity.secret,
+                    identity.name,
                 );
                 self.db()
                     .batches
@@ -227,6 +237,9 @@ impl crate::authority::AuthorityState {
         Ok(())
     }
 
+    /// Stream the retained batches and transactions from `request.start`, followed by live
+    /// updates until `request.length` items were sent. Subscribers joining late or reconnecting
+    /// resume by passing the sequence number after the last transaction they received.
     pub async fn handle_batch_streaming(
         &self,
         request: BatchInfoRequest,
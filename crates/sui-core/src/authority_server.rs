@@ -21,7 +21,7 @@ use sui_network::{
     tonic,
 };
 
-use sui_types::{error::*, messages::*};
+use sui_types::{error::*, fp_ensure, messages::*, protocol_config::ProtocolConfig};
 use tokio::{
     sync::mpsc::{channel, Sender},
     task::JoinHandle,
@@ -136,6 +136,7 @@ impl AuthorityServer {
                 state: self.state,
                 consensus_adapter: self.consensus_adapter,
                 _checkpoint_consensus_handle: None,
+                max_transaction_size: ProtocolConfig::current().max_tx_size_bytes as usize,
             }))
             .bind(&address)
             .await
@@ -155,6 +156,8 @@ pub struct ValidatorService {
     state: Arc<AuthorityState>,
     consensus_adapter: ConsensusAdapter,
     _checkpoint_consensus_handle: Option<JoinHandle<()>>,
+    /// Largest transaction data accepted, in bytes, see `max-transaction-size-bytes`.
+    max_transaction_size: usize,
 }
 
 impl ValidatorService {
@@ -228,14 +231,26 @@ impl ValidatorService {
             None
         };
 
+        let protocol_max = ProtocolConfig::current().max_tx_size_bytes as usize;
         Ok(Self {
             state,
             consensus_adapter,
             _checkpoint_consensus_handle: checkpoint_consensus_handle,
+            max_transaction_size: config
+                .max_transaction_size_bytes
+                .map_or(protocol_max, |size| size.min(protocol_max)),
         })
     }
 }
 
+/// Fails if `data` serializes to more than `limit` bytes. Checked before the signatures, so
+/// that oversized transactions are turned away before any expensive work.
+fn check_transaction_size(data: &TransactionData, limit: usize) -> SuiResult {
+    let size = bcs::serialized_size(data).expect("Serialization should not fail");
+    fp_ensure!(size <= limit, SuiError::TransactionTooLarge { size, limit });
+    Ok(())
+}
+
 #[async_trait]
 impl Validator for ValidatorService {
     async fn transaction(
@@ -244,6 +259,8 @@ impl Validator for ValidatorService {
     ) -> Result<tonic::Response<TransactionInfoResponse>, tonic::Status> {
         let mut transaction = request.into_inner();
 
+        check_transaction_size(&transaction.data, self.max_transaction_size)
+            .map_err(|e| tonic::Status::invalid_argument(e.to_string()))?;
         transaction
             .verify()
             .map_err(|e| tonic::Status::invalid_argument(e.to_string()))?;
@@ -275,6 +292,8 @@ impl Validator for ValidatorService {
     ) -> Result<tonic::Response<TransactionInfoResponse>, tonic::Status> {
         let mut transaction = request.into_inner();
 
+        check_transaction_size(&transaction.data, self.max_transaction_size)
+            .map_err(|e| tonic::Status::invalid_argument(e.to_string()))?;
         transaction
             .verify(&self.state.committee.load())
             .map_err(|e| tonic::Status::invalid_argument(e.to_string()))?;
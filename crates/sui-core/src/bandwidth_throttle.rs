@@ -0,0 +1,103 @@
+// Copyright (c) 2022, Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Caps the bandwidth taken by following other authorities.
+//!
+//! Clients wrapped with a [`BandwidthThrottle`] count the size of every response received,
+//! and when a limit is set, wait after a response until the bytes received are back within
+//! it. Waiting delays reading the next batch stream item or making the next request, so the
+//! peer is slowed down by flow control rather than disconnected. Sizes are those of the BCS
+//! encoded responses, which is close to what goes over the wire.
+
+use parking_lot::Mutex;
+use prometheus::IntCounter;
+use serde::Serialize;
+use std::time::Duration;
+use tokio::time::Instant;
+
+pub struct BandwidthThrottle {
+    bucket: Option<Mutex<TokenBucket>>,
+    bytes_received: IntCounter,
+}
+
+impl BandwidthThrottle {
+    /// Limits the responses received to `bytes_per_sec` on average, or only counts them into
+    /// `bytes_received` when it is `None`.
+    pub fn new(bytes_per_sec: Option<u64>, bytes_received: IntCounter) -> Self {
+        Self {
+            bucket: bytes_per_sec.map(|rate| Mutex::new(TokenBucket::new(rate, Instant::now()))),
+            bytes_received,
+        }
+    }
+
+    /// Account for `response`, waiting until it fits within the limit.
+    pub async fn consume<T: Serialize>(&self, response: &T) {
+        let bytes = bcs::serialized_size(response).unwrap_or(0) as u64;
+        self.bytes_received.inc_by(bytes);
+        let wait = match &self.bucket {
+            Some(bucket) => bucket.lock().take(bytes, Instant::now()),
+            None => return,
+        };
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// Holds up to a second worth of bytes, so that an idle follower can burst that much.
+struct TokenBucket {
+    bytes_per_sec: f64,
+    available: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    fn new(bytes_per_sec: u64, now: Instant) -> Self {
+        let bytes_per_sec = bytes_per_sec.max(1) as f64;
+        Self {
+            bytes_per_sec,
+            available: bytes_per_sec,
+            updated: now,
+        }
+    }
+
+    /// Take `bytes` out of the bucket, and return how long to wait until they are paid for.
+    /// Bytes taken beyond what is available are owed, so concurrent consumers wait in turn.
+    fn take(&mut self, bytes: u64, now: Instant) -> Duration {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.updated = now;
+        self.available =
+            (self.available + elapsed * self.bytes_per_sec).min(self.bytes_per_sec) - bytes as f64;
+        if self.available >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.available / self.bytes_per_sec)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_bucket() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(1_000, start);
+
+        // A second worth of bytes is available right away.
+        assert_eq!(bucket.take(600, start), Duration::ZERO);
+        assert_eq!(bucket.take(400, start), Duration::ZERO);
+        // Then bytes are owed, and whoever takes more waits for all of them.
+        assert_eq!(bucket.take(500, start), Duration::from_millis(500));
+        assert_eq!(bucket.take(500, start), Duration::from_secs(1));
+
+        // Owed bytes are paid back over time.
+        let later = start + Duration::from_secs(1);
+        assert_eq!(bucket.take(0, later), Duration::ZERO);
+        // An idle bucket doesn't fill up beyond a second worth of bytes.
+        let idle = later + Duration::from_secs(10);
+        assert_eq!(bucket.take(1_000, idle), Duration::ZERO);
+        assert_eq!(bucket.take(100, idle), Duration::from_millis(100));
+    }
+}
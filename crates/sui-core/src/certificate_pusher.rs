@@ -0,0 +1,168 @@
+// Copyright (c) 2022, Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Pushes the certificates executed by this authority to an external HTTP endpoint, in
+//! execution order and with at-least-once delivery.
+//!
+//! The authority's own executed sequence is the durable source of truth: the pusher reads ahead
+//! from it into a bounded in-memory buffer and only advances its persisted cursor once the
+//! endpoint acknowledged a batch with a success status. While the endpoint is down nothing is
+//! dropped, new certificates simply wait in the store until the buffer has room again. After a
+//! restart delivery resumes from the cursor, so a batch may be delivered more than once.
+
+use crate::authority::AuthorityState;
+use serde::Serialize;
+use std::{collections::VecDeque, path::Path, sync::Arc, time::Duration};
+use sui_config::CertificatePushConfig;
+use sui_storage::default_db_options;
+use sui_types::{
+    base_types::{SuiAddress, TransactionDigest},
+    batch::TxSequenceNumber,
+    error::{SuiError, SuiResult},
+    messages::CertifiedTransaction,
+};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, warn};
+use typed_store::{reopen, rocks::DBMap, Map};
+
+#[cfg(test)]
+#[path = "unit_tests/certificate_pusher_tests.rs"]
+mod certificate_pusher_tests;
+
+const CURSOR: u8 = 0;
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// What is sent for every executed certificate.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CertificateSummary {
+    pub sequence_number: TxSequenceNumber,
+    pub transaction_digest: TransactionDigest,
+    pub sender: SuiAddress,
+    pub certificate: CertifiedTransaction,
+}
+
+pub struct CertificatePusher {
+    state: Arc<AuthorityState>,
+    endpoint: String,
+    buffer_size: usize,
+    max_batch_size: usize,
+    client: reqwest::Client,
+    /// The sequence number of the next transaction to deliver.
+    cursor: DBMap<u8, TxSequenceNumber>,
+}
+
+impl CertificatePusher {
+    pub fn open<P: AsRef<Path>>(
+        path: P,
+        state: Arc<AuthorityState>,
+        config: &CertificatePushConfig,
+    ) -> SuiResult<Self> {
+        let (options, _) = default_db_options(None, None);
+
+        let db = {
+            let path = &path;
+            let db_options = Some(options.clone());
+            let opt_cfs: &[(&str, &rocksdb::Options)] = &[("cursor", &options)];
+            typed_store::rocks::open_cf_opts(path, db_options, opt_cfs)
+        }
+        .map_err(SuiError::StorageError)?;
+
+        let cursor = reopen!(&db, "cursor";<u8, TxSequenceNumber>);
+
+        Ok(Self {
+            state,
+            endpoint: config.endpoint.clone(),
+            buffer_size: config.buffer_size.max(1),
+            max_batch_size: config.max_batch_size.max(1),
+            client: reqwest::Client::new(),
+            cursor,
+        })
+    }
+
+    /// Deliver certificates until a storage error occurs or the authority shuts down. Delivery
+    /// errors are retried forever with exponential backoff.
+    pub async fn run(self) -> SuiResult {
+        let mut subscriber = self.state.subscribe_batch();
+
+        // Start from the tip the first time the pusher is enabled, rather than replaying all
+        // history into the endpoint.
+        let mut next_to_buffer = match self.cursor.get(&CURSOR)? {
+            Some(seq) => seq,
+            None => {
+                let seq = self.state.database.next_sequence_number()?;
+                self.cursor.insert(&CURSOR, &seq)?;
+                seq
+            }
+        };
+        let mut buffer: VecDeque<CertificateSummary> = VecDeque::new();
+        let mut backoff = INITIAL_BACKOFF;
+
+        loop {
+            if buffer.len() < self.buffer_size {
+                let end = next_to_buffer + (self.buffer_size - buffer.len()) as u64;
+                for (seq, digest) in self
+                    .state
+                    .database
+                    .transactions_in_seq_range(next_to_buffer, end)?
+                {
+                    next_to_buffer = seq + 1;
+                    match self.state.database.read_certificate(&digest)? {
+                        Some(certificate) => buffer.push_back(CertificateSummary {
+                            sequence_number: seq,
+                            transaction_digest: digest,
+                            sender: certificate.sender_address(),
+                            certificate,
+                        }),
+                        None => warn!(?digest, seq, "Executed certificate missing from store"),
+                    }
+                }
+            }
+
+            if buffer.is_empty() {
+                // Wait for new transactions. Lagging behind the batch stream is fine since the
+                // store is read again on every iteration.
+                tokio::select! {
+                    result = subscriber.recv() => {
+                        if let Err(RecvError::Closed) = result {
+                            return Ok(());
+                        }
+                    }
+                    _ = tokio::time::sleep(POLL_INTERVAL) => {}
+                    _ = self.state.shutdown_requested() => return Ok(()),
+                }
+                continue;
+            }
+
+            let batch_len = buffer.len().min(self.max_batch_size);
+            let batch: Vec<_> = buffer.iter().take(batch_len).collect();
+            let last_seq = batch[batch_len - 1].sequence_number;
+
+            let result = self
+                .client
+                .post(&self.endpoint)
+                .json(&batch)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status());
+            match result {
+                Ok(_) => {
+                    debug!(last_seq, count = batch_len, "Pushed certificates");
+                    buffer.drain(..batch_len);
+                    self.cursor.insert(&CURSOR, &(last_seq + 1))?;
+                    backoff = INITIAL_BACKOFF;
+                }
+                Err(e) => {
+                    warn!(
+                        endpoint = self.endpoint.as_str(),
+                        "Couldn't push certificates, retrying in {backoff:?}: {e}"
+                    );
+                    tokio::time::sleep(backoff).await;
+                    backoff = std::cmp::min(backoff * 2, MAX_BACKOFF);
+                }
+            }
+        }
+    }
+}
@@ -12,11 +12,11 @@ use narwhal_executor::ExecutionIndices;
 use rocksdb::Options;
 use serde::{Deserialize, Serialize};
 use std::{collections::HashSet, path::Path, sync::Arc};
-use sui_storage::default_db_options;
+use sui_storage::{create_db_checkpoint, default_db_options, flush_db};
 use sui_types::{
     base_types::{AuthorityName, ExecutionDigests},
     batch::TxSequenceNumber,
-    committee::{Committee, EpochId},
+    committee::{Committee, EpochId, StakeUnit},
     error::{SuiError, SuiResult},
     fp_ensure,
     // messages::CertifiedTransaction,
@@ -78,6 +78,24 @@ pub enum FragmentInternalError {
     Retry(Box<CheckpointFragment>),
 }
 
+/// The authorities whose signatures are stored for a checkpoint, together with their stake.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CheckpointSigners {
+    pub epoch: EpochId,
+    /// True if the stored checkpoint is certified by a quorum, false if it only carries the
+    /// signature of this authority.
+    pub certified: bool,
+    pub signers: Vec<(AuthorityName, StakeUnit)>,
+    /// The stake a certificate needs in the committee of `epoch`.
+    pub quorum_threshold: StakeUnit,
+}
+
+impl CheckpointSigners {
+    pub fn signed_stake(&self) -> StakeUnit {
+        self.signers.iter().map(|(_, stake)| stake).sum()
+    }
+}
+
 pub struct CheckpointStore {
     // Fixed size, static, identity of the authority
     /// The name of this authority.
@@ -364,6 +382,56 @@ impl CheckpointStore {
         })
     }
 
+    /// Returns the signers of the stored checkpoint `seq` weighted by their stake in
+    /// `committee`, or None if this authority has no signed or certified checkpoint at `seq`.
+    pub fn get_checkpoint_signers(
+        &self,
+        seq: CheckpointSequenceNumber,
+        committee_for_epoch: impl FnOnce(EpochId) -> SuiResult<Option<Arc<Committee>>>,
+    ) -> Result<Option<CheckpointSigners>, SuiError> {
+        let (epoch, certified, names) = match self.checkpoints.get(&seq)? {
+            None | Some(AuthenticatedCheckpoint::None) => return Ok(None),
+            Some(AuthenticatedCheckpoint::Signed(signed)) => (
+                signed.summary.epoch,
+                false,
+                vec![signed.auth_signature.authority],
+            ),
+            Some(AuthenticatedCheckpoint::Certified(certified)) => (
+                certified.summary.epoch,
+                true,
+                certified
+                    .auth_signature
+                    .signatures
+                    .iter()
+                    .map(|(name, _)| *name)
+                    .collect(),
+            ),
+        };
+        // Stakes change between epochs, so they are those of the checkpoint's.
+        let committee =
+            committee_for_epoch(epoch)?.ok_or_else(|| SuiError::GenericAuthorityError {
+                error: format!("the committee of epoch {epoch} isn't known"),
+            })?;
+
+        Ok(Some(CheckpointSigners {
+            epoch,
+            certified,
+            signers: names
+                .into_iter()
+                .map(|name| (name, committee.weight(&name)))
+                .collect(),
+            quorum_threshold: committee.quorum_threshold(),
+        }))
+    }
+
+    /// Sign checkpoints as `name` with `secret` from now on, at the start of an epoch whose
+    /// committee knows this authority by `name`. Checkpoints signed before keep the signature
+    /// of their epoch's key, which its committee verifies.
+    pub fn rotate_signing_key(&mut self, name: AuthorityName, secret: StableSyncAuthoritySigner) {
+        self.name = name;
+        self.secret = secret;
+    }
+
     pub fn sign_new_checkpoint(
         &mut self,
         summary: CheckpointSummary,
@@ -834,6 +902,16 @@ impl CheckpointStore {
         self.set_proposal(epoch)
     }
 
+    /// Write a consistent copy of the checkpoint store to `path`, see [`create_db_checkpoint`].
+    pub fn create_db_checkpoint(&self, path: &Path) -> SuiResult {
+        create_db_checkpoint(&self.checkpoints.rocksdb, path)
+    }
+
+    /// Write the memtables to disk, see [`flush_db`].
+    pub fn flush(&self) -> SuiResult {
+        flush_db(&self.checkpoints.rocksdb)
+    }
+
     /// Get the latest stored checkpoint if there is one
     pub fn latest_stored_checkpoint(
         &mut self,
@@ -846,6 +924,30 @@ impl CheckpointStore {
             .map(|(_, ckp)| ckp))
     }
 
+    /// The sequence number of the oldest stored checkpoint if there is one
+    pub fn oldest_stored_checkpoint(&self) -> Option<CheckpointSequenceNumber> {
+        self.checkpoints.iter().next().map(|(seq, _)| seq)
+    }
+
+    /// The lowest local sequence number of the transactions of checkpoint `seq`, if the
+    /// checkpoint is stored and any of its transactions was executed.
+    pub fn first_executed_transaction(
+        &self,
+        seq: CheckpointSequenceNumber,
+    ) -> SuiResult<Option<TxSequenceNumber>> {
+        Ok(self
+            .checkpoint_contents
+            .iter()
+            .skip_to(&(seq, 0))?
+            .next()
+            .map(|((checkpoint, tx_seq), _)| (checkpoint, tx_seq))
+            // Transactions not executed are keyed from TxSequenceNumber::MAX / 2 on.
+            .filter(|(checkpoint, tx_seq)| {
+                *checkpoint == seq && *tx_seq < TxSequenceNumber::MAX / 2
+            })
+            .map(|(_, tx_seq)| tx_seq))
+    }
+
     // Helper write functions
 
     /// Set the next checkpoint proposal.
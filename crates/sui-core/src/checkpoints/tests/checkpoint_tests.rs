@@ -490,6 +490,8 @@ fn latest_proposal() {
 #[test]
 fn set_get_checkpoint() {
     let (committee, _keys, mut stores) = random_ckpoint_store();
+    let committee_for_epoch =
+        |_: EpochId| -> SuiResult<Option<Arc<Committee>>> { Ok(Some(Arc::new(committee.clone()))) };
     let (_, mut cps1) = stores.pop().unwrap();
     let (_, mut cps2) = stores.pop().unwrap();
     let (_, mut cps3) = stores.pop().unwrap();
@@ -535,6 +537,10 @@ fn set_get_checkpoint() {
         AuthorityCheckpointInfo::Past(AuthenticatedCheckpoint::None)
     ));
     assert!(response.detail.is_none());
+    assert!(cps1
+        .get_checkpoint_signers(0, committee_for_epoch)
+        .unwrap()
+        .is_none());
 
     // ---
 
@@ -583,6 +589,17 @@ fn set_get_checkpoint() {
             .unwrap();
     }
 
+    // Only our own signature is known before the certificate arrives
+    let signers = cps1
+        .get_checkpoint_signers(0, committee_for_epoch)
+        .unwrap()
+        .unwrap();
+    assert!(!signers.certified);
+    assert_eq!(
+        signers.signers,
+        vec![(cps1.name, committee.weight(&cps1.name))]
+    );
+
     // Make a certificate
     let mut signed_checkpoint: Vec<SignedCheckpointSummary> = Vec::new();
     for x in [&mut cps1, &mut cps2, &mut cps3] {
@@ -614,6 +631,26 @@ fn set_get_checkpoint() {
         AuthorityCheckpointInfo::Past(AuthenticatedCheckpoint::Certified(..))
     ));
 
+    // The certificate carries the signers that make up the quorum
+    let signers = cps1
+        .get_checkpoint_signers(0, committee_for_epoch)
+        .unwrap()
+        .unwrap();
+    assert!(signers.certified);
+    assert_eq!(signers.epoch, committee.epoch);
+    assert_eq!(signers.quorum_threshold, committee.quorum_threshold());
+    let mut expected: Vec<_> = checkpoint_cert
+        .auth_signature
+        .signatures
+        .iter()
+        .map(|(name, _)| (*name, committee.weight(name)))
+        .collect();
+    let mut actual = signers.signers.clone();
+    expected.sort();
+    actual.sort();
+    assert_eq!(actual, expected);
+    assert!(signers.signed_stake() >= committee.quorum_threshold());
+
     // --- TEST 3 ---
 
     // Setting just cert to a node that does not have the checkpoint fails
@@ -1393,7 +1430,7 @@ impl TestSetup {
     pub fn get_authority(&self, name: &AuthorityName) -> &TestAuthority {
         self.authorities
             .iter()
-            .find(|t| &t.authority.name == name)
+            .find(|t| &t.authority.name() == name)
             .unwrap()
     }
 }
@@ -1550,7 +1587,7 @@ pub async fn checkpoint_tests_setup(
             .iter()
             .map(|a| {
                 (
-                    a.authority.name,
+                    a.authority.name(),
                     LocalAuthorityClient::new_from_authority(a.authority.clone()),
                 )
             })
@@ -1707,7 +1744,7 @@ async fn checkpoint_messaging_flow() {
             .lock()
             .process_checkpoint_certificate(
                 &checkpoint_cert,
-                if failed_authorities.contains(&auth.authority.name) {
+                if failed_authorities.contains(&auth.authority.name()) {
                     &contents
                 } else {
                     &None
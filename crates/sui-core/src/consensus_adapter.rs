@@ -12,6 +12,7 @@ use narwhal_types::TransactionProto;
 use narwhal_types::TransactionsClient;
 use std::collections::VecDeque;
 use std::sync::Arc;
+use std::time::Instant;
 use std::{
     collections::{hash_map::DefaultHasher, HashMap},
     hash::{Hash, Hasher},
@@ -65,6 +66,16 @@ type ConsensusOutput = (
     /* transaction */ SerializedConsensusTransaction,
 );
 
+/// How the latest certificates an authority submitted to consensus fared.
+#[derive(Clone, Debug, Default)]
+pub struct ConsensusStatus {
+    /// When a submitted certificate was last sequenced.
+    pub last_sequenced: Option<Instant>,
+    /// When a submission last failed, because consensus was unreachable or didn't sequence
+    /// the certificate in time, and why.
+    pub last_failure: Option<(Instant, SuiError)>,
+}
+
 /// Submit Sui certificates to the consensus.
 pub struct ConsensusAdapter {
     /// The authority's state.
@@ -132,11 +143,17 @@ impl ConsensusAdapter {
 
         // Check if this authority submits the transaction to consensus.
         if Self::should_submit(certificate) {
-            self.consensus_client
+            let result = self
+                .consensus_client
                 .clone()
                 .submit_transaction(TransactionProto { transaction: bytes })
                 .await
-                .map_err(|e| SuiError::ConsensusConnectionBroken(format!("{:?}", e)))?;
+                .map(|_| ())
+                .map_err(|e| SuiError::ConsensusConnectionBroken(format!("{:?}", e)));
+            if result.is_err() {
+                self.state.record_consensus_submission(&result);
+            }
+            result?;
         }
 
         // Wait for the consensus to sequence the certificate and assign locks to shared objects.
@@ -154,7 +171,10 @@ impl ConsensusAdapter {
                     .expect("Cleanup channel with consensus listener dropped");
                 Err(SuiError::FailedToHearBackFromConsensus(e.to_string()))
             }
-        }?;
+        };
+        self.state
+            .record_consensus_submission(&info.as_ref().map(|_| ()).map_err(Clone::clone));
+        let info = info?;
 
         if info.is_empty() {
             // Consensus successfully assigned shared-locks to this certificate.
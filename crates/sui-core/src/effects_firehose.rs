@@ -0,0 +1,122 @@
+// Copyright (c) 2022, Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Streams the effects of every transaction a full node executed, in sequence order, for
+//! indexers that need all of them and can't afford to page through JSON-RPC.
+//!
+//! The store is read a page at a time and only when the client asks for more, so a slow
+//! client is served at its own pace instead of being buffered for. A stream that caught up
+//! waits for the batch service to announce new transactions.
+
+use crate::authority::AuthorityState;
+use crate::authority_batch::BroadcastReceiver;
+use async_trait::async_trait;
+use futures::stream::{self, BoxStream};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+use sui_network::{api::EffectsFirehose, tonic};
+use sui_types::batch::TxSequenceNumber;
+use sui_types::error::SuiResult;
+use sui_types::messages::{EffectsFirehoseItem, EffectsFirehoseRequest};
+use tokio::sync::broadcast::error::RecvError;
+use typed_store::Map;
+
+#[cfg(test)]
+#[path = "unit_tests/effects_firehose_tests.rs"]
+mod effects_firehose_tests;
+
+/// Transactions read from the store at a time.
+const PAGE_SIZE: usize = 100;
+
+/// How long a stream that caught up waits for new transactions before reading the store
+/// again anyway, since no batch service announces them on nodes serving a secondary store.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+pub struct EffectsFirehoseService {
+    state: Arc<AuthorityState>,
+}
+
+impl EffectsFirehoseService {
+    pub fn new(state: Arc<AuthorityState>) -> Self {
+        Self { state }
+    }
+}
+
+struct Cursor {
+    state: Arc<AuthorityState>,
+    next: TxSequenceNumber,
+    page: VecDeque<EffectsFirehoseItem>,
+    subscriber: BroadcastReceiver,
+    exit: bool,
+}
+
+impl Cursor {
+    fn read_page(&self) -> SuiResult<VecDeque<EffectsFirehoseItem>> {
+        let store = self.state.db();
+        store
+            .executed_sequence
+            .iter()
+            .skip_to(&self.next)?
+            .take(PAGE_SIZE)
+            .map(|(sequence, digests)| {
+                Ok(EffectsFirehoseItem {
+                    sequence,
+                    digest: digests.transaction,
+                    effects: store.get_effects(&digests.transaction)?,
+                })
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl EffectsFirehose for EffectsFirehoseService {
+    type EffectsStream = BoxStream<'static, Result<EffectsFirehoseItem, tonic::Status>>;
+
+    async fn effects(
+        &self,
+        request: tonic::Request<EffectsFirehoseRequest>,
+    ) -> Result<tonic::Response<Self::EffectsStream>, tonic::Status> {
+        let cursor = Cursor {
+            state: self.state.clone(),
+            next: request.into_inner().start,
+            page: VecDeque::new(),
+            // Subscribe before reading the store, so that no announcement is missed.
+            subscriber: self.state.subscribe_batch(),
+            exit: false,
+        };
+
+        let stream = stream::unfold(cursor, |mut cursor| async move {
+            if cursor.exit {
+                return None;
+            }
+            loop {
+                if let Some(item) = cursor.page.pop_front() {
+                    cursor.next = item.sequence + 1;
+                    return Some((Ok(item), cursor));
+                }
+                match cursor.read_page() {
+                    Ok(page) if !page.is_empty() => cursor.page = page,
+                    Ok(_) => {
+                        tokio::select! {
+                            // Lagging behind the announcements is fine, the store is read again.
+                            result = cursor.subscriber.recv() => {
+                                if let Err(RecvError::Closed) = result {
+                                    return None;
+                                }
+                            }
+                            _ = tokio::time::sleep(POLL_INTERVAL) => {}
+                        }
+                    }
+                    Err(e) => {
+                        cursor.exit = true;
+                        return Some((Err(tonic::Status::internal(e.to_string())), cursor));
+                    }
+                }
+            }
+        });
+
+        Ok(tonic::Response::new(Box::pin(stream)))
+    }
+}
@@ -4,14 +4,22 @@
 use crate::authority_active::ActiveAuthority;
 use crate::authority_aggregator::AuthorityAggregator;
 use crate::authority_client::AuthorityAPI;
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::time::Duration;
-use sui_types::committee::Committee;
-use sui_types::crypto::PublicKeyBytes;
-use sui_types::error::SuiResult;
+use sui_types::base_types::AuthorityName;
+use sui_types::committee::{Committee, EpochId};
+use sui_types::crypto::{KeyPair, PublicKeyBytes};
+use sui_types::error::{SuiError, SuiResult};
+use sui_types::fp_ensure;
 use sui_types::messages::{ConfirmationTransaction, SignedTransaction};
 use sui_types::messages_checkpoint::CheckpointSequenceNumber;
+use sui_types::sui_system_state::SuiSystemState;
+use tracing::{error, info};
 use typed_store::Map;
 
 // TODO: Make last checkpoint number of each epoch more flexible.
@@ -55,7 +63,7 @@ where
             self.state.halted.load(Ordering::SeqCst),
             "finish_epoch_change called when validator is not halted",
         );
-        if let Some(checkpoints) = &self.state.checkpoints {
+        let transition_checkpoint = if let Some(checkpoints) = &self.state.checkpoints {
             let mut checkpoints = checkpoints.lock();
             let next_cp = checkpoints.get_locals().next_checkpoint;
             assert!(
@@ -75,9 +83,10 @@ where
             self.state.database.remove_all_pending_certificates()?;
 
             // drop checkpoints lock
+            next_cp
         } else {
             unreachable!();
-        }
+        };
 
         let sui_system_state = self.state.get_sui_system_state_object().await?;
         let next_epoch = sui_system_state.epoch + 1;
@@ -94,9 +103,21 @@ where
             .collect();
         let new_committee = Committee::new(next_epoch, votes)?;
         self.state.insert_new_epoch_info(&new_committee)?;
+        if let Some(dir) = &self.checkpoint_keys_dir {
+            match checkpoint_key_for_epoch(dir, &new_committee) {
+                Ok(Some(key_pair)) => {
+                    let name = *key_pair.public_key_bytes();
+                    self.state.rotate_key(key_pair);
+                    info!(epoch = next_epoch, ?name, "Rotated the authority key");
+                }
+                Ok(None) => (),
+                // Keep signing with the current key rather than stop the epoch change.
+                Err(e) => error!("Couldn't rotate the authority key: {e}"),
+            }
+        }
         let new_net = Arc::new(AuthorityAggregator::new(
             new_committee,
-            self.net.load().clone_inner_clients(),
+            rekey_rotated_clients(self.net.load().clone_inner_clients(), &sui_system_state),
             self.gateway_metrics.clone(),
         ));
         self.net.store(new_net.clone());
@@ -109,12 +130,13 @@ where
         // all active processes, maybe batch service.
         // We should also reduce the amount of committee passed around.
 
+        let identity = self.state.identity();
         let advance_epoch_tx = SignedTransaction::new_change_epoch(
             next_epoch,
             0, // TODO: fill in storage_charge
             0, // TODO: fill in computation_charge
-            self.state.name,
-            &*self.state.secret,
+            identity.name,
+            &*identity.secret,
         );
         // Add the signed transaction to the store.
         self.state
@@ -140,9 +162,29 @@ where
 
         // Resume the validator to start accepting transactions for the new epoch.
         self.state.unhalt_validator()?;
+        self.report_epoch_change(sui_system_state.epoch, transition_checkpoint);
         Ok(())
     }
 
+    /// Tell integrators the node crossed into a new epoch after `checkpoint`, the last one of
+    /// `old_epoch`: logged as an `epoch_change` event and counted by the `epoch_*` metrics.
+    fn report_epoch_change(&self, old_epoch: EpochId, checkpoint: CheckpointSequenceNumber) {
+        let committee = self.state.committee.load();
+        info!(
+            event = "epoch_change",
+            old_epoch,
+            new_epoch = committee.epoch,
+            committee_size = committee.num_members(),
+            checkpoint,
+            "Changed epoch"
+        );
+        let metrics = &self.state.metrics;
+        metrics.epoch.set(committee.epoch as i64);
+        metrics.committee_size.set(committee.num_members() as i64);
+        metrics.epoch_change_checkpoint.set(checkpoint as i64);
+        metrics.epoch_changes.inc();
+    }
+
     pub fn is_last_checkpoint_epoch(checkpoint: CheckpointSequenceNumber) -> bool {
         checkpoint > 0 && checkpoint % CHECKPOINT_COUNT_PER_EPOCH == 0
     }
@@ -151,3 +193,99 @@ where
         (checkpoint + 1) % CHECKPOINT_COUNT_PER_EPOCH == 0
     }
 }
+
+/// `clients` with the client of every validator that registered a new public key for the next
+/// epoch moved to that key, so that it is found by its name in the next committee. Validators
+/// are matched by their Sui address.
+fn rekey_rotated_clients<A>(
+    mut clients: BTreeMap<AuthorityName, A>,
+    sui_system_state: &SuiSystemState,
+) -> BTreeMap<AuthorityName, A> {
+    let validators = &sui_system_state.validators;
+    for next in &validators.next_epoch_validators {
+        let current = validators
+            .active_validators
+            .iter()
+            .map(|validator| &validator.metadata)
+            .find(|current| current.sui_address == next.sui_address);
+        let (old_name, new_name) = match current {
+            Some(current) if current.pubkey_bytes != next.pubkey_bytes => (
+                PublicKeyBytes::try_from(current.pubkey_bytes.as_ref()),
+                PublicKeyBytes::try_from(next.pubkey_bytes.as_ref()),
+            ),
+            _ => continue,
+        };
+        if let (Ok(old_name), Ok(new_name)) = (old_name, new_name) {
+            if let Some(client) = clients.remove(&old_name) {
+                info!(?old_name, ?new_name, "Authority rotated its key");
+                clients.insert(new_name, client);
+            }
+        }
+    }
+    clients
+}
+
+/// The key pair in the base64 `<epoch>.key` file of `dir` for the epoch of `committee`, or
+/// None when there is no such file. The validator must have registered its public key for
+/// that epoch in the system state, so that it is in `committee` and peers accept what it
+/// signs.
+pub fn checkpoint_key_for_epoch(dir: &Path, committee: &Committee) -> SuiResult<Option<KeyPair>> {
+    read_key_file(&dir.join(format!("{}.key", committee.epoch)), committee)
+}
+
+/// The key pair of the latest `<epoch>.key` file of `dir` up to the epoch of `committee`, or
+/// None when there is none. That is the key the validator rotated to at its last epoch change,
+/// which it signs with again after a restart.
+pub fn latest_key_for_epoch(dir: &Path, committee: &Committee) -> SuiResult<Option<KeyPair>> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => {
+            return Err(SuiError::GenericAuthorityError {
+                error: format!("Couldn't read {}: {e}", dir.display()),
+            })
+        }
+    };
+    let latest = entries
+        .filter_map(|entry| {
+            let file_name = entry.ok()?.file_name();
+            file_name
+                .to_str()?
+                .strip_suffix(".key")?
+                .parse::<EpochId>()
+                .ok()
+        })
+        .filter(|epoch| *epoch <= committee.epoch)
+        .max();
+    match latest {
+        Some(epoch) => read_key_file(&dir.join(format!("{epoch}.key")), committee),
+        None => Ok(None),
+    }
+}
+
+fn read_key_file(path: &Path, committee: &Committee) -> SuiResult<Option<KeyPair>> {
+    let encoded = match std::fs::read_to_string(path) {
+        Ok(encoded) => encoded,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => {
+            return Err(SuiError::GenericAuthorityError {
+                error: format!("Couldn't read {}: {e}", path.display()),
+            })
+        }
+    };
+    let key_pair =
+        KeyPair::from_str(encoded.trim()).map_err(|e| SuiError::GenericAuthorityError {
+            error: format!("Invalid key pair in {}: {e}", path.display()),
+        })?;
+    fp_ensure!(
+        committee.weight(key_pair.public_key_bytes()) > 0,
+        SuiError::GenericAuthorityError {
+            error: format!(
+                "The key pair in {} is not in the committee of epoch {}",
+                path.display(),
+                committee.epoch
+            ),
+        }
+    );
+    Ok(Some(key_pair))
+}
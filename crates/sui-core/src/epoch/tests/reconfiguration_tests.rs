@@ -12,18 +12,23 @@ use std::{
 
 use sui_types::{
     base_types::{ObjectID, SuiAddress},
-    crypto::{get_key_pair, AuthoritySignature, Signature},
+    committee::Committee,
+    crypto::{get_key_pair, AuthoritySignature, KeyPair, Signature},
     error::SuiError,
     gas::SuiGasStatus,
     messages::{ConfirmationTransaction, SignatureAggregator, Transaction, TransactionData},
     object::Object,
+    sui_system_state::SuiSystemState,
     SUI_SYSTEM_STATE_OBJECT_ID,
 };
 
 use crate::{
-    authority::AuthorityTemporaryStore, authority_active::ActiveAuthority,
+    authority::AuthorityTemporaryStore,
+    authority_active::ActiveAuthority,
     authority_aggregator::authority_aggregator_tests::init_local_authorities,
-    checkpoints::CheckpointLocals, epoch::reconfiguration::CHECKPOINT_COUNT_PER_EPOCH,
+    authority_client::LocalAuthorityClient,
+    checkpoints::CheckpointLocals,
+    epoch::reconfiguration::{checkpoint_key_for_epoch, CHECKPOINT_COUNT_PER_EPOCH},
     execution_engine,
 };
 use crate::{gateway_state::GatewayMetrics, transaction_input_checker::InputObjects};
@@ -111,8 +116,8 @@ async fn test_start_epoch_change() {
     for state in &states {
         cert = sigs
             .append(
-                state.name,
-                AuthoritySignature::new(&transaction.data, &*state.secret),
+                state.name(),
+                AuthoritySignature::new(&transaction.data, &*state.secret()),
             )
             .unwrap();
     }
@@ -154,7 +159,7 @@ async fn test_start_epoch_change() {
         SuiGasStatus::new_with_budget(1000, 1, 1),
         state.committee.load().epoch,
     );
-    let signed_effects = effects.to_sign_effects(0, &state.name, &*state.secret);
+    let signed_effects = effects.to_sign_effects(0, &state.name(), &*state.secret());
     assert_eq!(
         state
             .commit_certificate(temporary_store, &certificate, &signed_effects)
@@ -186,43 +191,7 @@ async fn test_finish_epoch_change() {
             .unwrap()
         })
         .collect();
-    let results: Vec<_> = states
-        .iter()
-        .zip(actives.iter())
-        .map(|(state, active)| {
-            async {
-                // Set the checkpoint number to be near the end of epoch.
-                let mut locals = CheckpointLocals {
-                    next_checkpoint: CHECKPOINT_COUNT_PER_EPOCH - 1,
-                    proposal_next_transaction: None,
-                    next_transaction_sequence: 0,
-                    no_more_fragments: true,
-                    current_proposal: None,
-                };
-                state
-                    .checkpoints
-                    .as_ref()
-                    .unwrap()
-                    .lock()
-                    .set_locals_for_testing(locals.clone())
-                    .unwrap();
-
-                active.start_epoch_change().await.unwrap();
-
-                locals.next_checkpoint += 1;
-                state
-                    .checkpoints
-                    .as_ref()
-                    .unwrap()
-                    .lock()
-                    .set_locals_for_testing(locals.clone())
-                    .unwrap();
-
-                active.finish_epoch_change().await.unwrap()
-            }
-        })
-        .collect();
-    futures::future::join_all(results).await;
+    change_epoch(&actives).await;
 
     // Verify that epoch changed in every authority state.
     for active in actives {
@@ -240,6 +209,13 @@ async fn test_finish_epoch_change() {
         );
         // Verify that validator is no longer halted.
         assert!(!active.state.halted.load(Ordering::SeqCst));
+        let metrics = &active.state.metrics;
+        assert_eq!(metrics.epoch.get(), 1);
+        assert_eq!(metrics.epoch_changes.get(), 1);
+        assert_eq!(
+            metrics.epoch_change_checkpoint.get(),
+            CHECKPOINT_COUNT_PER_EPOCH as i64
+        );
         let system_state = active.state.get_sui_system_state_object().await.unwrap();
         assert_eq!(system_state.epoch, 1);
         let (_, tx_digest) = active
@@ -258,3 +234,171 @@ async fn test_finish_epoch_change() {
         assert!(response.signed_effects.is_some());
     }
 }
+
+#[tokio::test]
+async fn test_finish_epoch_change_with_rotated_key() {
+    let genesis_objects = vec![];
+    let (net, states) = init_local_authorities(vec![
+        genesis_objects.clone(),
+        genesis_objects.clone(),
+        genesis_objects.clone(),
+        genesis_objects.clone(),
+    ])
+    .await;
+    let state = states[0].clone();
+    let old_name = state.name();
+    let (_, key_pair) = get_key_pair();
+    let new_name = *key_pair.public_key_bytes();
+
+    // Register the new key of the first authority for the next epoch.
+    for state in &states {
+        let mut object = state
+            .db()
+            .get_object(&SUI_SYSTEM_STATE_OBJECT_ID)
+            .unwrap()
+            .unwrap();
+        let move_object = object.data.try_as_move_mut().unwrap();
+        let mut system_state: SuiSystemState = bcs::from_bytes(move_object.contents()).unwrap();
+        for validator in &mut system_state.validators.next_epoch_validators {
+            if validator.pubkey_bytes == old_name.as_ref() {
+                validator.pubkey_bytes = new_name.as_ref().to_vec();
+            }
+        }
+        move_object.update_contents_without_version_change(bcs::to_bytes(&system_state).unwrap());
+        state
+            .db()
+            .insert_object_direct(object.compute_object_reference(), &object)
+            .await
+            .unwrap();
+    }
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("1.key"),
+        serde_json::to_value(&key_pair).unwrap().as_str().unwrap(),
+    )
+    .unwrap();
+
+    let actives: Vec<_> = states
+        .iter()
+        .map(|state| {
+            let active = ActiveAuthority::new_with_ephemeral_follower_store(
+                state.clone(),
+                net.clone_inner_clients(),
+                GatewayMetrics::new_for_tests(),
+            )
+            .unwrap();
+            if state.name() == old_name {
+                active.with_checkpoint_keys_dir(dir.path().to_path_buf())
+            } else {
+                active
+            }
+        })
+        .collect();
+    change_epoch(&actives).await;
+
+    // The first authority signs everything with its new key.
+    assert_eq!(state.name(), new_name);
+    assert_eq!(state.checkpoints.as_ref().unwrap().lock().name, new_name);
+    let (_, tx_digest) = state
+        .get_latest_parent_entry(SUI_SYSTEM_STATE_OBJECT_ID)
+        .await
+        .unwrap()
+        .unwrap();
+    let signed_effects = state
+        .handle_transaction_info_request(tx_digest.into())
+        .await
+        .unwrap()
+        .signed_effects
+        .unwrap();
+    assert_eq!(signed_effects.auth_signature.authority, new_name);
+    signed_effects
+        .auth_signature
+        .signature
+        .verify(&signed_effects.effects, new_name)
+        .unwrap();
+
+    // Every authority reaches it by its new name.
+    for active in actives {
+        let net = active.net.load();
+        assert_eq!(net.committee.epoch, 1);
+        assert!(net.committee.authority_exists(&new_name));
+        assert!(!net.committee.authority_exists(&old_name));
+        assert!(net.authority_clients.contains_key(&new_name));
+        assert!(!net.authority_clients.contains_key(&old_name));
+        assert!(!active.state.halted.load(Ordering::SeqCst));
+    }
+}
+
+/// Take `actives` through the last two checkpoints of the first epoch into the next.
+async fn change_epoch(actives: &[ActiveAuthority<LocalAuthorityClient>]) {
+    let results: Vec<_> = actives
+        .iter()
+        .map(|active| {
+            async {
+                // Set the checkpoint number to be near the end of epoch.
+                let mut locals = CheckpointLocals {
+                    next_checkpoint: CHECKPOINT_COUNT_PER_EPOCH - 1,
+                    proposal_next_transaction: None,
+                    next_transaction_sequence: 0,
+                    no_more_fragments: true,
+                    current_proposal: None,
+                };
+                active
+                    .state
+                    .checkpoints
+                    .as_ref()
+                    .unwrap()
+                    .lock()
+                    .set_locals_for_testing(locals.clone())
+                    .unwrap();
+
+                active.start_epoch_change().await.unwrap();
+
+                locals.next_checkpoint += 1;
+                active
+                    .state
+                    .checkpoints
+                    .as_ref()
+                    .unwrap()
+                    .lock()
+                    .set_locals_for_testing(locals.clone())
+                    .unwrap();
+
+                active.finish_epoch_change().await.unwrap()
+            }
+        })
+        .collect();
+    futures::future::join_all(results).await;
+}
+
+#[test]
+fn test_checkpoint_key_for_epoch() {
+    let (_, key_pair) = get_key_pair();
+    let (_, other_key_pair) = get_key_pair();
+    let committee =
+        Committee::new(1, [(*key_pair.public_key_bytes(), 1)].into_iter().collect()).unwrap();
+    let dir = tempfile::tempdir().unwrap();
+
+    assert!(checkpoint_key_for_epoch(dir.path(), &committee)
+        .unwrap()
+        .is_none());
+
+    let encoded = |key_pair: &KeyPair| serde_json::to_value(key_pair).unwrap();
+    std::fs::write(
+        dir.path().join("1.key"),
+        encoded(&key_pair).as_str().unwrap(),
+    )
+    .unwrap();
+    let rotated = checkpoint_key_for_epoch(dir.path(), &committee)
+        .unwrap()
+        .unwrap();
+    assert_eq!(rotated.public_key_bytes(), key_pair.public_key_bytes());
+
+    // Peers wouldn't accept a key outside of the committee.
+    std::fs::write(
+        dir.path().join("1.key"),
+        encoded(&other_key_pair).as_str().unwrap(),
+    )
+    .unwrap();
+    assert!(checkpoint_key_for_epoch(dir.path(), &committee).is_err());
+}
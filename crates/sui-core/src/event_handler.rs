@@ -5,8 +5,10 @@ use std::collections::BTreeMap;
 use std::sync::Arc;
 
 use move_bytecode_utils::module_cache::SyncModuleCache;
+use prometheus::{Histogram, Registry};
 use serde_json::Value;
 use sui_json_rpc_api::rpc_types::{SuiMoveStruct, SuiMoveValue};
+use tokio::sync::Mutex;
 use tokio_stream::Stream;
 use tracing::{debug, error, trace};
 
@@ -19,6 +21,7 @@ use sui_types::{
 };
 
 use crate::authority::{AuthorityStore, ResolverWrapper};
+use crate::metrics::{self, MetricsNamespace};
 use crate::streamer::Streamer;
 use sui_types::event_filter::EventFilter;
 
@@ -28,48 +31,111 @@ mod event_handler_tests;
 
 pub const EVENT_DISPATCH_BUFFER_SIZE: usize = 1000;
 
+/// Events waiting to be written to the event store together.
+#[derive(Default)]
+struct PendingEvents {
+    envelopes: Vec<EventEnvelope>,
+    checkpoint_num: u64,
+}
+
 pub struct EventHandler {
     module_cache: SyncModuleCache<ResolverWrapper<AuthorityStore>>,
     event_streamer: Streamer<EventEnvelope, EventFilter>,
     pub(crate) event_store: Arc<EventStoreType>,
+    pending: Mutex<PendingEvents>,
+    insert_batch_size: Histogram,
 }
 
 impl EventHandler {
-    pub fn new(validator_store: Arc<AuthorityStore>, event_store: Arc<EventStoreType>) -> Self {
+    pub fn new(
+        validator_store: Arc<AuthorityStore>,
+        event_store: Arc<EventStoreType>,
+        registry: &Registry,
+    ) -> Self {
         let streamer = Streamer::spawn(EVENT_DISPATCH_BUFFER_SIZE);
         Self {
             module_cache: SyncModuleCache::new(ResolverWrapper(validator_store)),
             event_streamer: streamer,
             event_store,
+            pending: Mutex::new(PendingEvents::default()),
+            insert_batch_size: MetricsNamespace::new(registry, metrics::EVENT_STORE)
+                .histogram_with_buckets(
+                    "insert_batch_size",
+                    "Number of events written to the event store at once",
+                    vec![
+                        1., 2., 5., 10., 20., 50., 100., 200., 500., 1000., 2000., 5000.,
+                    ],
+                )
+                .unwrap(),
         }
     }
 
+    /// Queue the events of a transaction for the event store, and write them out together with
+    /// the queued events of previous transactions once there are `insert_batch_size` of them.
+    /// Events are only dispatched to subscribers after they have been written. Use
+    /// `flush_events` to write out a partial batch.
+    ///
+    /// If writing fails, the events stay queued and the write is retried on the next call,
+    /// which may be for the same transaction again.
     pub async fn process_events(
         &self,
         effects: &TransactionEffects,
         timestamp_ms: u64,
         seq_num: u64,
         checkpoint_num: u64,
+        insert_batch_size: usize,
     ) -> SuiResult {
-        let res: Result<Vec<_>, _> = effects
-            .events
-            .iter()
-            .map(|e| self.create_envelope(e, effects.transaction_digest, seq_num, timestamp_ms))
-            .collect();
-        let envelopes = res?;
+        let mut pending = self.pending.lock().await;
+
+        // All events of a batch are written with the same checkpoint.
+        if pending.checkpoint_num != checkpoint_num {
+            self.flush_pending(&mut pending).await?;
+            pending.checkpoint_num = checkpoint_num;
+        }
+
+        // Transactions are processed in order, so the events of a retried transaction are
+        // already queued.
+        let already_queued =
+            matches!(pending.envelopes.last(), Some(envelope) if envelope.seq_num >= seq_num);
+        if !already_queued {
+            for event in &effects.events {
+                let envelope =
+                    self.create_envelope(event, effects.transaction_digest, seq_num, timestamp_ms)?;
+                pending.envelopes.push(envelope);
+            }
+        }
+
+        if pending.envelopes.len() >= insert_batch_size {
+            self.flush_pending(&mut pending).await?;
+        }
+        Ok(())
+    }
+
+    /// Write out all queued events.
+    pub async fn flush_events(&self) -> SuiResult {
+        let mut pending = self.pending.lock().await;
+        self.flush_pending(&mut pending).await
+    }
+
+    async fn flush_pending(&self, pending: &mut PendingEvents) -> SuiResult {
+        if pending.envelopes.is_empty() {
+            return Ok(());
+        }
 
         // Ingest all envelopes together at once (for efficiency) into Event Store
         self.event_store
-            .add_events(&envelopes, checkpoint_num)
+            .add_events(&pending.envelopes, pending.checkpoint_num)
             .await?;
+        self.insert_batch_size
+            .observe(pending.envelopes.len() as f64);
         trace!(
-            num_events = envelopes.len(),
-            digest =? effects.transaction_digest,
-            checkpoint_num, "Finished writing events to event store"
+            num_events = pending.envelopes.len(),
+            checkpoint_num = pending.checkpoint_num,
+            "Finished writing events to event store"
         );
 
         // serially dispatch event processing to honor events' orders.
-        for envelope in envelopes {
+        for envelope in pending.envelopes.drain(..) {
             if let Err(e) = self.event_streamer.send(envelope).await {
                 error!(error =? e, "Failed to send EventEnvelope to dispatch");
             }
@@ -13,14 +13,15 @@ use async_trait::async_trait;
 use futures::future;
 use move_bytecode_utils::module_cache::SyncModuleCache;
 use move_core_types::identifier::Identifier;
-use prometheus::{
-    register_histogram_with_registry, register_int_counter_with_registry, Histogram, IntCounter,
-};
+use move_core_types::language_storage::StructTag;
+use prometheus::{Histogram, IntCounter};
 use tracing::{debug, error, Instrument};
 
 use sui_adapter::adapter::resolve_and_type_check;
+use sui_storage::mutex_table::MutexTable;
 use sui_types::gas_coin::GasCoin;
 use sui_types::object::{ObjectFormatOptions, Owner};
+use sui_types::protocol_config::ProtocolConfig;
 use sui_types::{
     base_types::*,
     coin,
@@ -35,14 +36,18 @@ use sui_types::{
 use crate::authority::ResolverWrapper;
 use crate::transaction_input_checker;
 use crate::{
-    authority::GatewayStore, authority_aggregator::AuthorityAggregator,
-    authority_client::AuthorityAPI, query_helpers::QueryHelpers,
+    authority::GatewayStore,
+    authority_aggregator::AuthorityAggregator,
+    authority_client::AuthorityAPI,
+    metrics::{self, MetricsNamespace},
+    query_helpers::QueryHelpers,
 };
 use sui_json::{resolve_move_function_args, SuiJsonCallArg, SuiJsonValue};
 use sui_json_rpc_api::rpc_types::{
     GetObjectDataResponse, GetRawObjectDataResponse, MergeCoinResponse, MoveCallParams,
-    PublishResponse, RPCTransactionRequestParams, SplitCoinResponse, SuiMoveObject, SuiObject,
-    SuiObjectInfo, SuiTransactionEffects, SuiTypeTag, TransactionEffectsResponse,
+    PublishResponse, RPCTransactionRequestParams, SplitCoinResponse, SuiCoinBalance, SuiCoinPage,
+    SuiMoveObject, SuiObject, SuiObjectInfo, SuiProtocolConfig, SuiTransactionBlockOptions,
+    SuiTransactionBlockResponse, SuiTransactionEffects, SuiTypeTag, TransactionEffectsResponse,
     TransactionResponse, TransferObjectParams,
 };
 
@@ -61,6 +66,9 @@ pub type GatewayTxSeqNumber = u64;
 /// Number of times to retry failed TX
 const MAX_NUM_TX_RETRIES: usize = 5;
 
+/// Number of locks serializing the execution of transactions with the same digest.
+const TX_MUTEX_TABLE_SIZE: usize = 1 << 10;
+
 /// Prometheus metrics which can be displayed in Grafana, queried and alerted on
 #[derive(Clone)]
 pub struct GatewayMetrics {
@@ -71,6 +79,7 @@ pub struct GatewayMetrics {
     num_tx_splitcoin: IntCounter,
     num_tx_mergecoin: IntCounter,
     total_tx_retries: IntCounter,
+    total_tx_duplicates: IntCounter,
     shared_obj_tx: IntCounter,
     pub total_tx_certificates: IntCounter,
     pub num_signatures: Histogram,
@@ -86,91 +95,83 @@ const POSITIVE_INT_BUCKETS: &[f64] = &[
 
 impl GatewayMetrics {
     pub fn new(registry: &prometheus::Registry) -> Self {
+        let gateway = MetricsNamespace::new(registry, metrics::GATEWAY);
         Self {
-            total_tx_processed: register_int_counter_with_registry!(
-                "total_tx_processed",
-                "Total number of transaction certificates processed in Gateway",
-                registry,
-            )
-            .unwrap(),
-            total_tx_errored: register_int_counter_with_registry!(
-                "total_tx_errored",
-                "Total number of transactions which errored out",
-                registry,
-            )
-            .unwrap(),
+            total_tx_processed: gateway
+                .int_counter(
+                    "total_tx_processed",
+                    "Total number of transaction certificates processed in Gateway",
+                )
+                .unwrap(),
+            total_tx_errored: gateway
+                .int_counter(
+                    "total_tx_errored",
+                    "Total number of transactions which errored out",
+                )
+                .unwrap(),
             // total_effects == total transactions finished
-            num_tx_publish: register_int_counter_with_registry!(
-                "num_tx_publish",
-                "Number of publish transactions",
-                registry,
-            )
-            .unwrap(),
-            num_tx_movecall: register_int_counter_with_registry!(
-                "num_tx_movecall",
-                "Number of MOVE call transactions",
-                registry,
-            )
-            .unwrap(),
-            num_tx_splitcoin: register_int_counter_with_registry!(
-                "num_tx_splitcoin",
-                "Number of split coin transactions",
-                registry,
-            )
-            .unwrap(),
-            num_tx_mergecoin: register_int_counter_with_registry!(
-                "num_tx_mergecoin",
-                "Number of merge coin transactions",
-                registry,
-            )
-            .unwrap(),
-            total_tx_certificates: register_int_counter_with_registry!(
-                "total_tx_certificates",
-                "Total number of certificates made from validators",
-                registry,
-            )
-            .unwrap(),
-            total_tx_retries: register_int_counter_with_registry!(
-                "total_tx_retries",
-                "Total number of retries for transactions",
-                registry,
-            )
-            .unwrap(),
-            shared_obj_tx: register_int_counter_with_registry!(
-                "gateway_shared_obj_tx",
-                "Number of transactions involving shared objects",
-                registry,
-            )
-            .unwrap(),
+            num_tx_publish: gateway
+                .int_counter("num_tx_publish", "Number of publish transactions")
+                .unwrap(),
+            num_tx_movecall: gateway
+                .int_counter("num_tx_movecall", "Number of MOVE call transactions")
+                .unwrap(),
+            num_tx_splitcoin: gateway
+                .int_counter("num_tx_splitcoin", "Number of split coin transactions")
+                .unwrap(),
+            num_tx_mergecoin: gateway
+                .int_counter("num_tx_mergecoin", "Number of merge coin transactions")
+                .unwrap(),
+            total_tx_certificates: gateway
+                .int_counter(
+                    "total_tx_certificates",
+                    "Total number of certificates made from validators",
+                )
+                .unwrap(),
+            total_tx_retries: gateway
+                .int_counter(
+                    "total_tx_retries",
+                    "Total number of retries for transactions",
+                )
+                .unwrap(),
+            total_tx_duplicates: gateway
+                .int_counter(
+                    "total_tx_duplicates",
+                    "Total number of duplicate transactions answered with earlier effects",
+                )
+                .unwrap(),
+            shared_obj_tx: gateway
+                .int_counter(
+                    "shared_obj_tx",
+                    "Number of transactions involving shared objects",
+                )
+                .unwrap(),
             // It's really important to use the right histogram buckets for accurate histogram collection.
             // Otherwise values get clipped
-            num_signatures: register_histogram_with_registry!(
-                "num_signatures_per_tx",
-                "Number of signatures collected per transaction",
-                POSITIVE_INT_BUCKETS.to_vec(),
-                registry,
-            )
-            .unwrap(),
-            num_good_stake: register_histogram_with_registry!(
-                "num_good_stake_per_tx",
-                "Amount of good stake collected per transaction",
-                POSITIVE_INT_BUCKETS.to_vec(),
-                registry,
-            )
-            .unwrap(),
-            num_bad_stake: register_histogram_with_registry!(
-                "num_bad_stake_per_tx",
-                "Amount of bad stake collected per transaction",
-                POSITIVE_INT_BUCKETS.to_vec(),
-                registry,
-            )
-            .unwrap(),
-            transaction_latency: register_histogram_with_registry!(
-                "transaction_latency",
-                "Latency of execute_transaction_impl",
-                registry,
-            )
-            .unwrap(),
+            num_signatures: gateway
+                .histogram_with_buckets(
+                    "num_signatures_per_tx",
+                    "Number of signatures collected per transaction",
+                    POSITIVE_INT_BUCKETS.to_vec(),
+                )
+                .unwrap(),
+            num_good_stake: gateway
+                .histogram_with_buckets(
+                    "num_good_stake_per_tx",
+                    "Amount of good stake collected per transaction",
+                    POSITIVE_INT_BUCKETS.to_vec(),
+                )
+                .unwrap(),
+            num_bad_stake: gateway
+                .histogram_with_buckets(
+                    "num_bad_stake_per_tx",
+                    "Amount of bad stake collected per transaction",
+                    POSITIVE_INT_BUCKETS.to_vec(),
+                )
+                .unwrap(),
+            transaction_latency: gateway
+                .histogram("transaction_latency", "Latency of execute_transaction_impl")
+                .unwrap(),
         }
     }
 
@@ -191,6 +192,11 @@ pub struct GatewayState<A> {
     next_tx_seq_number: AtomicU64,
     metrics: GatewayMetrics,
     module_cache: SyncModuleCache<ResolverWrapper<GatewayStore>>,
+    /// Submissions of the same transaction are executed one at a time, so that duplicates
+    /// find the effects of the first one.
+    tx_mutex_table: MutexTable<TransactionDigest>,
+    /// Tell clients passing a stale version of an input object which version is current.
+    report_current_object_versions: bool,
 }
 
 impl<A> GatewayState<A> {
@@ -221,9 +227,45 @@ impl<A> GatewayState<A> {
             next_tx_seq_number,
             metrics,
             module_cache: SyncModuleCache::new(ResolverWrapper(store)),
+            tx_mutex_table: MutexTable::new(TX_MUTEX_TABLE_SIZE),
+            report_current_object_versions: false,
         })
     }
 
+    /// Include the current version of an input object in the error returned when a
+    /// transaction gives another, so that clients can retry with it. Off by default, as it
+    /// reveals the version of objects to anyone knowing their ID.
+    pub fn with_current_object_versions(mut self, report: bool) -> Self {
+        self.report_current_object_versions = report;
+        self
+    }
+
+    /// Turn the errors for input objects at the wrong version into version conflicts. A
+    /// single input error is returned on its own rather than in a list.
+    fn version_conflict_error(&self, error: SuiError) -> SuiError {
+        let conflict = |error| match error {
+            SuiError::UnexpectedSequenceNumber {
+                object_id,
+                expected_sequence,
+                given_sequence,
+            } => SuiError::ObjectVersionConflict {
+                object_id,
+                given_version: given_sequence,
+                current_version: self
+                    .report_current_object_versions
+                    .then(|| expected_sequence),
+            },
+            error => error,
+        };
+        match error {
+            SuiError::LockErrors { mut errors } if errors.len() == 1 => conflict(errors.remove(0)),
+            SuiError::LockErrors { errors } => SuiError::LockErrors {
+                errors: errors.into_iter().map(conflict).collect(),
+            },
+            error => conflict(error),
+        }
+    }
+
     // Given a list of inputs from a transaction, fetch the objects
     // from the db.
     async fn read_objects_from_store(
@@ -240,6 +282,21 @@ impl<A> GatewayState<A> {
         &self.authorities
     }
 
+    /// The certificate and effects of the transaction `digest`, if it was executed through
+    /// this gateway.
+    fn executed_transaction(
+        &self,
+        digest: &TransactionDigest,
+    ) -> SuiResult<Option<(CertifiedTransaction, TransactionEffects)>> {
+        if !self.store.effects_exists(digest)? {
+            return Ok(None);
+        }
+        match self.store.get_certified_transaction(digest)? {
+            Some(certificate) => Ok(Some((certificate, self.store.get_effects(digest)?))),
+            None => Ok(None),
+        }
+    }
+
     #[cfg(test)]
     pub fn store(&self) -> &Arc<GatewayStore> {
         &self.store
@@ -348,6 +405,13 @@ pub trait GatewayAPI {
     async fn get_object(&self, object_id: ObjectID)
         -> Result<GetObjectDataResponse, anyhow::Error>;
 
+    /// Get the object data as it was at `version`, from the versions this gateway stored
+    async fn get_object_at_version(
+        &self,
+        object_id: ObjectID,
+        version: SequenceNumber,
+    ) -> Result<GetObjectDataResponse, anyhow::Error>;
+
     /// Get the object data
     async fn get_raw_object(
         &self,
@@ -368,6 +432,9 @@ pub trait GatewayAPI {
     /// Get the total number of transactions ever happened in history.
     fn get_total_transaction_number(&self) -> Result<u64, anyhow::Error>;
 
+    /// Get the protocol parameters in effect for the current epoch of the committee.
+    async fn get_protocol_config(&self) -> Result<SuiProtocolConfig, anyhow::Error>;
+
     /// Return the list of transactions with sequence number in range [`start`, end).
     /// `start` is included, `end` is excluded.
     fn get_transactions_in_range(
@@ -387,6 +454,49 @@ pub trait GatewayAPI {
         &self,
         digest: TransactionDigest,
     ) -> Result<TransactionEffectsResponse, anyhow::Error>;
+
+    /// return the parts of a transaction asked for in `options`, see
+    /// [`QueryHelpers::get_transaction_block`]
+    async fn get_transaction_block(
+        &self,
+        digest: TransactionDigest,
+        options: SuiTransactionBlockOptions,
+    ) -> Result<SuiTransactionBlockResponse, anyhow::Error>;
+
+    /// return the transactions a transaction depends on, see
+    /// [`QueryHelpers::get_transaction_dependencies`]
+    async fn get_transaction_dependencies(
+        &self,
+        digest: TransactionDigest,
+        depth: u64,
+    ) -> Result<Vec<(TransactionDigest, Vec<TransactionDigest>)>, anyhow::Error>;
+
+    /// return SUI coins of an address covering a gas budget, see
+    /// [`QueryHelpers::select_gas_coins`]
+    async fn select_gas_coins(
+        &self,
+        address: SuiAddress,
+        budget: u64,
+    ) -> Result<Vec<ObjectRef>, anyhow::Error>;
+
+    /// return the balance of an address per coin type, of one coin type only when set, see
+    /// [`QueryHelpers::get_balances`]
+    async fn get_balances(
+        &self,
+        address: SuiAddress,
+        coin_type: Option<StructTag>,
+    ) -> Result<Vec<SuiCoinBalance>, anyhow::Error>;
+
+    /// return a page of at most `limit` coins of an address, see [`QueryHelpers::get_coins`]
+    async fn get_coins(
+        &self,
+        address: SuiAddress,
+        coin_type: StructTag,
+        largest_first: bool,
+        exclude_locked: bool,
+        cursor: Option<ObjectID>,
+        limit: usize,
+    ) -> Result<SuiCoinPage, anyhow::Error>;
 }
 
 impl<A> GatewayState<A>
@@ -554,7 +664,8 @@ where
             &transaction,
             &self.metrics.shared_obj_tx,
         )
-        .await?;
+        .await
+        .map_err(|e| self.version_conflict_error(e))?;
 
         let owned_objects = input_objects.filter_owned_objects();
         self.set_transaction_lock(&owned_objects, transaction.clone())
@@ -614,6 +725,47 @@ where
         Ok(objects)
     }
 
+    /// The response to a transaction of kind `tx_kind`, once executed with `effects`.
+    async fn create_transaction_response(
+        &self,
+        tx_kind: TransactionKind,
+        certificate: CertifiedTransaction,
+        effects: TransactionEffects,
+    ) -> Result<TransactionResponse, anyhow::Error> {
+        // Create custom response base on the request type
+        if let TransactionKind::Single(tx_kind) = tx_kind {
+            match tx_kind {
+                SingleTransactionKind::Publish(_) => {
+                    self.metrics.num_tx_publish.inc();
+                    return self.create_publish_response(certificate, effects).await;
+                }
+                // Work out if the transaction is split coin or merge coin transaction
+                SingleTransactionKind::Call(move_call) => {
+                    self.metrics.num_tx_movecall.inc();
+                    if move_call.package == self.get_framework_object_ref().await?
+                        && move_call.module.as_ref() == coin::COIN_MODULE_NAME
+                    {
+                        if move_call.function.as_ref() == coin::COIN_SPLIT_VEC_FUNC_NAME {
+                            self.metrics.num_tx_splitcoin.inc();
+                            return self.create_split_coin_response(certificate, effects).await;
+                        } else if move_call.function.as_ref() == coin::COIN_JOIN_FUNC_NAME {
+                            self.metrics.num_tx_mergecoin.inc();
+                            return self.create_merge_coin_response(certificate, effects).await;
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(TransactionResponse::EffectResponse(
+            TransactionEffectsResponse {
+                certificate: certificate.try_into()?,
+                effects: SuiTransactionEffects::try_from(effects, &self.module_cache)?,
+                timestamp_ms: None,
+            },
+        ))
+    }
+
     async fn create_publish_response(
         &self,
         certificate: CertifiedTransaction,
@@ -970,6 +1122,17 @@ where
 
         debug!(digest = ?tx_digest, "Received execute_transaction request");
 
+        // A duplicate submitted while the transaction executes waits for it here, and then
+        // gets the same effects, as do those submitted afterwards.
+        let _tx_guard = self.tx_mutex_table.acquire_lock(tx_digest).await;
+        if let Some((certificate, effects)) = self.executed_transaction(tx_digest)? {
+            debug!(digest = ?tx_digest, "Transaction already executed");
+            self.metrics.total_tx_duplicates.inc();
+            return self
+                .create_transaction_response(tx_kind, certificate, effects)
+                .await;
+        }
+
         let span = tracing::debug_span!(
             "gateway_execute_transaction",
             ?tx_digest,
@@ -1017,38 +1180,8 @@ where
         let effects = effects.effects;
 
         debug!(digest = ?tx_digest, "Transaction succeeded");
-        // Create custom response base on the request type
-        if let TransactionKind::Single(tx_kind) = tx_kind {
-            match tx_kind {
-                SingleTransactionKind::Publish(_) => {
-                    self.metrics.num_tx_publish.inc();
-                    return self.create_publish_response(certificate, effects).await;
-                }
-                // Work out if the transaction is split coin or merge coin transaction
-                SingleTransactionKind::Call(move_call) => {
-                    self.metrics.num_tx_movecall.inc();
-                    if move_call.package == self.get_framework_object_ref().await?
-                        && move_call.module.as_ref() == coin::COIN_MODULE_NAME
-                    {
-                        if move_call.function.as_ref() == coin::COIN_SPLIT_VEC_FUNC_NAME {
-                            self.metrics.num_tx_splitcoin.inc();
-                            return self.create_split_coin_response(certificate, effects).await;
-                        } else if move_call.function.as_ref() == coin::COIN_JOIN_FUNC_NAME {
-                            self.metrics.num_tx_mergecoin.inc();
-                            return self.create_merge_coin_response(certificate, effects).await;
-                        }
-                    }
-                }
-                _ => {}
-            }
-        }
-        return Ok(TransactionResponse::EffectResponse(
-            TransactionEffectsResponse {
-                certificate: certificate.try_into()?,
-                effects: SuiTransactionEffects::try_from(effects, &self.module_cache)?,
-                timestamp_ms: None,
-            },
-        ));
+        self.create_transaction_response(tx_kind, certificate, effects)
+            .await
     }
 
     async fn public_transfer_object(
@@ -1275,6 +1408,18 @@ where
         Ok(result.try_into()?)
     }
 
+    async fn get_object_at_version(
+        &self,
+        object_id: ObjectID,
+        version: SequenceNumber,
+    ) -> Result<GetObjectDataResponse, anyhow::Error> {
+        Ok(match self.store.get_past_object(&object_id, version)? {
+            None => GetObjectDataResponse::NotExists(object_id),
+            Some((object_ref, None)) => GetObjectDataResponse::Deleted(object_ref.into()),
+            Some((_, Some(object))) => GetObjectDataResponse::Exists(self.to_sui_object(object)?),
+        })
+    }
+
     async fn get_raw_object(
         &self,
         object_id: ObjectID,
@@ -1313,6 +1458,13 @@ where
         QueryHelpers::get_total_transaction_number(&self.store)
     }
 
+    async fn get_protocol_config(&self) -> Result<SuiProtocolConfig, anyhow::Error> {
+        Ok(SuiProtocolConfig {
+            epoch: self.authorities.committee.epoch,
+            config: ProtocolConfig::current(),
+        })
+    }
+
     fn get_transactions_in_range(
         &self,
         start: GatewayTxSeqNumber,
@@ -1340,4 +1492,57 @@ where
             timestamp_ms: None,
         })
     }
+
+    async fn get_transaction_block(
+        &self,
+        digest: TransactionDigest,
+        options: SuiTransactionBlockOptions,
+    ) -> Result<SuiTransactionBlockResponse, anyhow::Error> {
+        QueryHelpers::get_transaction_block(&self.store, &self.module_cache, digest, options)
+    }
+
+    async fn get_transaction_dependencies(
+        &self,
+        digest: TransactionDigest,
+        depth: u64,
+    ) -> Result<Vec<(TransactionDigest, Vec<TransactionDigest>)>, anyhow::Error> {
+        QueryHelpers::get_transaction_dependencies(&self.store, digest, depth)
+    }
+
+    async fn select_gas_coins(
+        &self,
+        address: SuiAddress,
+        budget: u64,
+    ) -> Result<Vec<ObjectRef>, anyhow::Error> {
+        QueryHelpers::select_gas_coins(&self.store, address, budget).await
+    }
+
+    async fn get_balances(
+        &self,
+        address: SuiAddress,
+        coin_type: Option<StructTag>,
+    ) -> Result<Vec<SuiCoinBalance>, anyhow::Error> {
+        QueryHelpers::get_balances(&self.store, address, coin_type.as_ref()).await
+    }
+
+    async fn get_coins(
+        &self,
+        address: SuiAddress,
+        coin_type: StructTag,
+        largest_first: bool,
+        exclude_locked: bool,
+        cursor: Option<ObjectID>,
+        limit: usize,
+    ) -> Result<SuiCoinPage, anyhow::Error> {
+        QueryHelpers::get_coins(
+            &self.store,
+            address,
+            &coin_type,
+            largest_first,
+            exclude_locked,
+            cursor,
+            limit,
+        )
+        .await
+    }
 }
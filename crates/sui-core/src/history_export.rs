@@ -0,0 +1,110 @@
+// Copyright (c) 2022, Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Streams the full transaction history of an address or object, for exports that don't fit
+//! in paginated JSON-RPC responses.
+//!
+//! The history is read from the indexes a page at a time while the stream is consumed, so memory
+//! use doesn't grow with the length of the history. Records are ordered by the authority's
+//! execution sequence number, and an export can be resumed from the sequence number following
+//! the last record it received.
+
+use crate::authority::AuthorityState;
+use futures::{stream, Stream};
+use serde::Serialize;
+use std::{collections::VecDeque, sync::Arc};
+use sui_types::{
+    base_types::{ObjectID, SuiAddress, TransactionDigest},
+    batch::TxSequenceNumber,
+    error::{SuiError, SuiResult},
+    messages::{CertifiedTransaction, TransactionEffects},
+};
+
+/// Number of index entries read at a time.
+const PAGE_SIZE: usize = 100;
+
+/// Whose history to export.
+#[derive(Clone, Copy, Debug)]
+pub enum HistorySubject {
+    /// Transactions sent by the address, or that transferred objects to it.
+    Address(SuiAddress),
+    /// Transactions that took the object as input or mutated it, which covers every version
+    /// of the object.
+    Object(ObjectID),
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryRecord {
+    pub sequence_number: TxSequenceNumber,
+    pub transaction_digest: TransactionDigest,
+    pub certificate: CertifiedTransaction,
+    pub effects: TransactionEffects,
+}
+
+impl HistoryRecord {
+    /// The record as one line of newline-delimited JSON.
+    pub fn to_ndjson_line(&self) -> SuiResult<String> {
+        let mut line =
+            serde_json::to_string(self).map_err(|e| SuiError::GenericAuthorityError {
+                error: format!("Couldn't serialize history record: {e}"),
+            })?;
+        line.push('\n');
+        Ok(line)
+    }
+}
+
+/// The transactions of `subject` executed at sequence number `since` or later. Fails right
+/// away when the authority doesn't keep the indexes the history is read from.
+pub fn history(
+    state: Arc<AuthorityState>,
+    subject: HistorySubject,
+    since: TxSequenceNumber,
+) -> SuiResult<impl Stream<Item = SuiResult<HistoryRecord>>> {
+    let indexes = state.get_indexes()?;
+
+    // The next sequence number to read from the indexes, `None` once they are exhausted.
+    let start: (Option<TxSequenceNumber>, VecDeque<_>) = (Some(since), VecDeque::new());
+    Ok(stream::try_unfold(start, move |(mut next, mut page)| {
+        let state = state.clone();
+        let indexes = indexes.clone();
+        async move {
+            if page.is_empty() {
+                if let Some(since) = next {
+                    page = match subject {
+                        HistorySubject::Address(addr) => {
+                            indexes.get_address_history_page(addr, since, PAGE_SIZE)?
+                        }
+                        HistorySubject::Object(object) => {
+                            indexes.get_object_history_page(object, since, PAGE_SIZE)?
+                        }
+                    }
+                    .into();
+                    next = match page.back() {
+                        Some((seq, _)) if page.len() == PAGE_SIZE => seq.checked_add(1),
+                        _ => None,
+                    };
+                }
+            }
+
+            let (sequence_number, transaction_digest) = match page.pop_front() {
+                Some(entry) => entry,
+                None => return Ok(None),
+            };
+            let certificate = state
+                .database
+                .read_certificate(&transaction_digest)?
+                .ok_or(SuiError::CertificateNotfound {
+                    certificate_digest: transaction_digest,
+                })?;
+            let effects = state.database.get_effects(&transaction_digest)?;
+            let record = HistoryRecord {
+                sequence_number,
+                transaction_digest,
+                certificate,
+                effects,
+            };
+            Ok(Some((record, (next, page))))
+        }
+    }))
+}
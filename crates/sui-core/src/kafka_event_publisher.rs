@@ -0,0 +1,316 @@
+// Copyright (c) 2022, Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Publishes the events emitted by the transactions this authority executed to a Kafka topic,
+//! in execution order.
+//!
+//! Like the certificate pusher, the publisher reads the events from the authority's own store
+//! into a bounded buffer and keeps a persisted cursor of the next transaction to publish, so it
+//! works whether or not the SQL event store is enabled. Messages are keyed by transaction digest
+//! or object ID, and their payload is the JSON event envelope the RPC API serves.
+//!
+//! With at-least-once delivery the cursor only moves past a transaction once the brokers
+//! acknowledged all its events. While they are unavailable the buffer is published again with
+//! exponential backoff, and new events wait in the store. With at-most-once delivery the cursor
+//! moves before the events are sent, and events the brokers don't acknowledge are dropped.
+
+use crate::authority::AuthorityState;
+use futures::future::join_all;
+use rdkafka::{
+    config::ClientConfig,
+    producer::{FutureProducer, FutureRecord},
+    util::Timeout,
+};
+use std::{collections::VecDeque, path::Path, sync::Arc, time::Duration};
+use sui_config::{KafkaDelivery, KafkaEventConfig, KafkaEventKey};
+use sui_json_rpc_api::rpc_types::{SuiEvent, SuiEventEnvelope};
+use sui_storage::default_db_options;
+use sui_types::{
+    base_types::TransactionDigest,
+    batch::TxSequenceNumber,
+    error::{SuiError, SuiResult},
+    event::Event,
+};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, warn};
+use typed_store::{reopen, rocks::DBMap, Map};
+
+const CURSOR: u8 = 0;
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// How long the brokers have to acknowledge a message before it counts as failed.
+const MESSAGE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The messages for the events of one transaction.
+struct TransactionMessages {
+    seq: TxSequenceNumber,
+    /// Key and JSON payload of every event.
+    messages: Vec<(String, String)>,
+}
+
+pub struct KafkaEventPublisher {
+    state: Arc<AuthorityState>,
+    producer: FutureProducer,
+    topic: String,
+    key: KafkaEventKey,
+    delivery: KafkaDelivery,
+    buffer_size: usize,
+    /// The sequence number of the next transaction to publish the events of.
+    cursor: DBMap<u8, TxSequenceNumber>,
+}
+
+impl KafkaEventPublisher {
+    pub fn open<P: AsRef<Path>>(
+        path: P,
+        state: Arc<AuthorityState>,
+        config: &KafkaEventConfig,
+    ) -> SuiResult<Self> {
+        let (options, _) = default_db_options(None, None);
+
+        let db = {
+            let path = &path;
+            let db_options = Some(options.clone());
+            let opt_cfs: &[(&str, &rocksdb::Options)] = &[("cursor", &options)];
+            typed_store::rocks::open_cf_opts(path, db_options, opt_cfs)
+        }
+        .map_err(SuiError::StorageError)?;
+
+        let cursor = reopen!(&db, "cursor";<u8, TxSequenceNumber>);
+
+        let mut producer_config = ClientConfig::new();
+        producer_config
+            .set("bootstrap.servers", &config.brokers)
+            .set(
+                "message.timeout.ms",
+                MESSAGE_TIMEOUT.as_millis().to_string(),
+            );
+        match config.delivery {
+            // Idempotence keeps the retries librdkafka does on its own from reordering or
+            // duplicating messages.
+            KafkaDelivery::AtLeastOnce => producer_config
+                .set("acks", "all")
+                .set("enable.idempotence", "true"),
+            KafkaDelivery::AtMostOnce => producer_config.set("acks", "1").set("retries", "0"),
+        };
+        let producer = producer_config
+            .create()
+            .map_err(|e| SuiError::GenericAuthorityError {
+                error: format!("Couldn't create Kafka producer for {}: {e}", config.brokers),
+            })?;
+
+        Ok(Self {
+            state,
+            producer,
+            topic: config.topic.clone(),
+            key: config.key,
+            delivery: config.delivery,
+            buffer_size: config.buffer_size.max(1),
+            cursor,
+        })
+    }
+
+    /// Publish events until a storage error occurs or the authority shuts down.
+    pub async fn run(self) -> SuiResult {
+        let mut subscriber = self.state.subscribe_batch();
+
+        // Start from the tip the first time the publisher is enabled, rather than replaying all
+        // history into the topic.
+        let mut cursor = match self.cursor.get(&CURSOR)? {
+            Some(seq) => seq,
+            None => {
+                let seq = self.state.database.next_sequence_number()?;
+                self.cursor.insert(&CURSOR, &seq)?;
+                seq
+            }
+        };
+        let mut next_to_buffer = cursor;
+        let mut buffer: VecDeque<TransactionMessages> = VecDeque::new();
+        let mut buffered_events = 0;
+        let mut backoff = INITIAL_BACKOFF;
+
+        loop {
+            if buffered_events < self.buffer_size {
+                let end = next_to_buffer + (self.buffer_size - buffered_events) as u64;
+                for (seq, digest) in self
+                    .state
+                    .database
+                    .transactions_in_seq_range(next_to_buffer, end)?
+                {
+                    next_to_buffer = seq + 1;
+                    let messages = self.messages(&digest).await?;
+                    if !messages.is_empty() {
+                        buffered_events += messages.len();
+                        buffer.push_back(TransactionMessages { seq, messages });
+                    }
+                    if buffered_events >= self.buffer_size {
+                        break;
+                    }
+                }
+            }
+
+            if buffer.is_empty() {
+                // Move past the transactions that had no events.
+                if cursor != next_to_buffer {
+                    cursor = next_to_buffer;
+                    self.cursor.insert(&CURSOR, &cursor)?;
+                }
+                // Wait for new transactions. Lagging behind the batch stream is fine since the
+                // store is read again on every iteration.
+                tokio::select! {
+                    result = subscriber.recv() => {
+                        if let Err(RecvError::Closed) = result {
+                            return Ok(());
+                        }
+                    }
+                    _ = tokio::time::sleep(POLL_INTERVAL) => {}
+                    _ = self.state.shutdown_requested() => return Ok(()),
+                }
+                continue;
+            }
+
+            if self.delivery == KafkaDelivery::AtMostOnce {
+                cursor = next_to_buffer;
+                self.cursor.insert(&CURSOR, &cursor)?;
+            }
+
+            let failed = self.publish(&buffer).await;
+            match failed {
+                None => {
+                    debug!(count = buffered_events, "Published events");
+                    buffer.clear();
+                    buffered_events = 0;
+                    cursor = next_to_buffer;
+                    self.cursor.insert(&CURSOR, &cursor)?;
+                    backoff = INITIAL_BACKOFF;
+                }
+                Some((index, e)) if self.delivery == KafkaDelivery::AtMostOnce => {
+                    warn!(
+                        topic = self.topic.as_str(),
+                        first_failed_seq = buffer[index].seq,
+                        "Couldn't publish events, dropping them: {e}"
+                    );
+                    buffer.clear();
+                    buffered_events = 0;
+                }
+                Some((index, e)) => {
+                    // Transactions before the first failed one were fully acknowledged.
+                    for published in buffer.drain(..index) {
+                        buffered_events -= published.messages.len();
+                    }
+                    cursor = buffer[0].seq;
+                    self.cursor.insert(&CURSOR, &cursor)?;
+                    warn!(
+                        topic = self.topic.as_str(),
+                        seq = cursor,
+                        "Couldn't publish events, retrying in {backoff:?}: {e}"
+                    );
+                    tokio::time::sleep(backoff).await;
+                    backoff = std::cmp::min(backoff * 2, MAX_BACKOFF);
+                }
+            }
+        }
+    }
+
+    /// Send every buffered message, and return the position in `buffer` of the first
+    /// transaction that had one not acknowledged, with the error.
+    async fn publish(&self, buffer: &VecDeque<TransactionMessages>) -> Option<(usize, String)> {
+        let sends = buffer.iter().enumerate().flat_map(|(index, transaction)| {
+            transaction
+                .messages
+                .iter()
+                .map(move |(key, payload)| async move {
+                    let record = FutureRecord::to(&self.topic).key(key).payload(payload);
+                    self.producer
+                        .send(record, Timeout::After(MESSAGE_TIMEOUT))
+                        .await
+                        .map_err(|(e, _)| (index, e.to_string()))
+                })
+        });
+        // Sent in order, so the results are too.
+        join_all(sends)
+            .await
+            .into_iter()
+            .find_map(|result| result.err())
+    }
+
+    async fn messages(&self, digest: &TransactionDigest) -> SuiResult<Vec<(String, String)>> {
+        let effects = self.state.database.get_effects(digest)?;
+        if effects.events.is_empty() {
+            return Ok(Vec::new());
+        }
+        let timestamp = match self.state.get_timestamp_ms(digest).await {
+            Ok(Some(timestamp)) => timestamp,
+            // Only full nodes index timestamps.
+            _ => AuthorityState::unixtime_now_ms(),
+        };
+
+        let mut messages = Vec::with_capacity(effects.events.len());
+        for event in effects.events {
+            let key = message_key(&event, digest, self.key);
+            let envelope = SuiEventEnvelope {
+                timestamp,
+                tx_digest: Some(*digest),
+                event: SuiEvent::try_from(event, &self.state.module_cache).map_err(|e| {
+                    SuiError::ObjectSerializationError {
+                        error: e.to_string(),
+                    }
+                })?,
+            };
+            let payload = serde_json::to_string(&envelope).map_err(|e| {
+                SuiError::ObjectSerializationError {
+                    error: e.to_string(),
+                }
+            })?;
+            messages.push((key, payload));
+        }
+        Ok(messages)
+    }
+}
+
+fn message_key(event: &Event, digest: &TransactionDigest, key: KafkaEventKey) -> String {
+    match (key, event.object_id()) {
+        (KafkaEventKey::ObjectId, Some(object_id)) => object_id.to_string(),
+        // Base64, as in the JSON payload.
+        _ => format!("{digest:?}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use move_core_types::identifier::Identifier;
+    use sui_types::{
+        base_types::{ObjectID, SuiAddress},
+        object::Owner,
+    };
+
+    #[test]
+    fn test_message_key() {
+        let digest = TransactionDigest::random();
+        let object_id = ObjectID::random();
+        let new_object = Event::NewObject {
+            package_id: ObjectID::random(),
+            transaction_module: Identifier::new("m").unwrap(),
+            sender: SuiAddress::random_for_testing_only(),
+            recipient: Owner::AddressOwner(SuiAddress::default()),
+            object_id,
+        };
+        let epoch_change = Event::EpochChange(1);
+
+        assert_eq!(
+            message_key(&new_object, &digest, KafkaEventKey::ObjectId),
+            object_id.to_string()
+        );
+        assert_eq!(
+            message_key(&new_object, &digest, KafkaEventKey::TransactionDigest),
+            format!("{digest:?}")
+        );
+        // No object to key by.
+        assert_eq!(
+            message_key(&epoch_change, &digest, KafkaEventKey::ObjectId),
+            format!("{digest:?}")
+        );
+    }
+}
@@ -7,12 +7,20 @@ pub mod authority_aggregator;
 pub mod authority_batch;
 pub mod authority_client;
 pub mod authority_server;
+pub mod bandwidth_throttle;
+pub mod certificate_pusher;
 pub mod checkpoints;
 pub mod consensus_adapter;
+pub mod effects_firehose;
 pub mod epoch;
 pub mod event_handler;
 pub mod execution_engine;
 pub mod gateway_state;
+pub mod history_export;
+#[cfg(feature = "kafka")]
+pub mod kafka_event_publisher;
+pub mod metrics;
+pub mod node_sync_state;
 pub mod safe_client;
 pub mod streamer;
 pub mod transaction_input_checker;
@@ -0,0 +1,143 @@
+// Copyright (c) 2022, Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! All the components of a node report to a single prometheus registry. To keep metric names
+//! unique and easy to attribute, every subsystem registers its metrics through a
+//! [`MetricsNamespace`], which prefixes their names with the subsystem, e.g.
+//! `authority_total_transaction_orders`.
+
+use prometheus::{
+    register_gauge_with_registry, register_histogram_vec_with_registry,
+    register_histogram_with_registry, register_int_counter_vec_with_registry,
+    register_int_counter_with_registry, register_int_gauge_vec_with_registry,
+    register_int_gauge_with_registry, Gauge, Histogram, HistogramOpts, HistogramVec, IntCounter,
+    IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry,
+};
+
+pub const AUTHORITY: &str = "authority";
+pub const CHECKPOINT: &str = "checkpoint";
+pub const EVENT_STORE: &str = "event_store";
+pub const GATEWAY: &str = "gateway";
+pub const GOSSIP: &str = "gossip";
+pub const NODE: &str = "node";
+pub const NODE_SYNC: &str = "node_sync";
+pub const RPC: &str = "rpc";
+
+/// Registers metrics on `registry` with the names prefixed by `subsystem`.
+#[derive(Clone, Copy)]
+pub struct MetricsNamespace<'a> {
+    registry: &'a Registry,
+    subsystem: &'static str,
+}
+
+impl<'a> MetricsNamespace<'a> {
+    pub fn new(registry: &'a Registry, subsystem: &'static str) -> Self {
+        Self {
+            registry,
+            subsystem,
+        }
+    }
+
+    /// The full name a metric called `name` is exported under.
+    pub fn metric_name(&self, name: &str) -> String {
+        format!("{}_{name}", self.subsystem)
+    }
+
+    pub fn int_counter(&self, name: &str, help: &str) -> prometheus::Result<IntCounter> {
+        register_int_counter_with_registry!(self.opts(name, help), self.registry)
+    }
+
+    pub fn int_gauge(&self, name: &str, help: &str) -> prometheus::Result<IntGauge> {
+        register_int_gauge_with_registry!(self.opts(name, help), self.registry)
+    }
+
+    pub fn gauge(&self, name: &str, help: &str) -> prometheus::Result<Gauge> {
+        register_gauge_with_registry!(self.opts(name, help), self.registry)
+    }
+
+    pub fn int_counter_vec(
+        &self,
+        name: &str,
+        help: &str,
+        labels: &[&str],
+    ) -> prometheus::Result<IntCounterVec> {
+        register_int_counter_vec_with_registry!(self.opts(name, help), labels, self.registry)
+    }
+
+    pub fn int_gauge_vec(
+        &self,
+        name: &str,
+        help: &str,
+        labels: &[&str],
+    ) -> prometheus::Result<IntGaugeVec> {
+        register_int_gauge_vec_with_registry!(self.opts(name, help), labels, self.registry)
+    }
+
+    /// A histogram with the default prometheus buckets.
+    pub fn histogram(&self, name: &str, help: &str) -> prometheus::Result<Histogram> {
+        register_histogram_with_registry!(self.histogram_opts(name, help), self.registry)
+    }
+
+    pub fn histogram_with_buckets(
+        &self,
+        name: &str,
+        help: &str,
+        buckets: Vec<f64>,
+    ) -> prometheus::Result<Histogram> {
+        register_histogram_with_registry!(
+            self.histogram_opts(name, help).buckets(buckets),
+            self.registry
+        )
+    }
+
+    pub fn histogram_vec(
+        &self,
+        name: &str,
+        help: &str,
+        labels: &[&str],
+    ) -> prometheus::Result<HistogramVec> {
+        register_histogram_vec_with_registry!(
+            self.histogram_opts(name, help),
+            labels,
+            self.registry
+        )
+    }
+
+    fn opts(&self, name: &str, help: &str) -> Opts {
+        Opts::new(name, help).namespace(self.subsystem)
+    }
+
+    fn histogram_opts(&self, name: &str, help: &str) -> HistogramOpts {
+        HistogramOpts::new(name, help).namespace(self.subsystem)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metrics_are_prefixed_with_subsystem() {
+        let registry = Registry::new();
+        let authority = MetricsNamespace::new(&registry, AUTHORITY);
+        let gossip = MetricsNamespace::new(&registry, GOSSIP);
+
+        authority.int_counter("queued_count", "authority").unwrap();
+        // The same name in another subsystem doesn't clash.
+        gossip.int_counter("queued_count", "gossip").unwrap();
+        // The same name in the same subsystem does.
+        assert!(authority.int_gauge("queued_count", "again").is_err());
+
+        let mut names: Vec<_> = registry
+            .gather()
+            .iter()
+            .map(|family| family.get_name().to_string())
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["authority_queued_count", "gossip_queued_count"]);
+        assert_eq!(
+            authority.metric_name("queued_count"),
+            "authority_queued_count"
+        );
+    }
+}
@@ -0,0 +1,164 @@
+// Copyright (c) 2022, Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Carries where node sync left off over to another host, so that a node moved along with a
+//! copy of its `store` doesn't sync again from the start.
+//!
+//! The state is the follower cursor of every followed validator, and the certificates and
+//! effects that were downloaded but not executed yet. Both are exported and imported while the
+//! node is stopped. A cursor is only safe to use with a store that executed everything the
+//! cursor moved past, so the state records the number of transactions the store had executed
+//! when it was exported, and is rejected by a store that executed fewer. A store that executed
+//! more is fine: the transactions in between are downloaded again and found already executed.
+
+use crate::authority::AuthorityStore;
+use serde::{Deserialize, Serialize};
+use sui_storage::{follower_store::FollowerStore, node_sync_store::NodeSyncStore};
+use sui_types::{
+    base_types::AuthorityName,
+    batch::TxSequenceNumber,
+    error::{SuiError, SuiResult},
+    messages::{CertifiedTransaction, SignedTransactionEffects},
+};
+
+/// Incremented whenever the encoding of [`NodeSyncState`] changes.
+const FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NodeSyncState {
+    format_version: u32,
+    /// `next_sequence_number` of the store when the state was exported.
+    executed_height: TxSequenceNumber,
+    /// The next sequence number to request from each followed validator.
+    follower_cursors: Vec<(AuthorityName, TxSequenceNumber)>,
+    /// Downloaded certificates and effects that weren't executed yet.
+    pending: Vec<(CertifiedTransaction, SignedTransactionEffects)>,
+}
+
+impl NodeSyncState {
+    pub fn export(
+        store: &AuthorityStore,
+        follower_store: &FollowerStore,
+        node_sync_store: &NodeSyncStore,
+    ) -> SuiResult<Self> {
+        Ok(Self {
+            format_version: FORMAT_VERSION,
+            executed_height: store.next_sequence_number()?,
+            follower_cursors: follower_store.next_sequences(),
+            pending: node_sync_store.all_cert_and_effects(),
+        })
+    }
+
+    /// Write the state into the follower and node sync stores of a node whose `store` is a
+    /// copy of the one the state was exported next to. Cursors already recorded for the same
+    /// validators are replaced.
+    pub fn import(
+        &self,
+        store: &AuthorityStore,
+        follower_store: &FollowerStore,
+        node_sync_store: &NodeSyncStore,
+    ) -> SuiResult {
+        let executed_height = store.next_sequence_number()?;
+        if executed_height < self.executed_height {
+            return Err(SuiError::GenericAuthorityError {
+                error: format!(
+                    "The store executed {executed_height} transactions, but the sync state was \
+                     exported after {}; copy a more recent store",
+                    self.executed_height
+                ),
+            });
+        }
+
+        for (peer, seq) in &self.follower_cursors {
+            follower_store.record_next_sequence(peer, *seq)?;
+        }
+        for (cert, effects) in &self.pending {
+            node_sync_store
+                .store_cert_and_effects(cert.digest(), &(cert.clone(), effects.clone()))?;
+        }
+        Ok(())
+    }
+
+    pub fn to_bytes(&self) -> SuiResult<Vec<u8>> {
+        bcs::to_bytes(self).map_err(|e| SuiError::GenericAuthorityError {
+            error: format!("Couldn't serialize node sync state: {e}"),
+        })
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> SuiResult<Self> {
+        let state: Self = bcs::from_bytes(bytes).map_err(|e| SuiError::GenericAuthorityError {
+            error: format!("Couldn't read node sync state: {e}"),
+        })?;
+        if state.format_version != FORMAT_VERSION {
+            return Err(SuiError::GenericAuthorityError {
+                error: format!(
+                    "Node sync state has format version {}, expected {FORMAT_VERSION}",
+                    state.format_version
+                ),
+            });
+        }
+        Ok(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sui_types::base_types::ExecutionDigests;
+    use sui_types::crypto::get_key_pair;
+
+    struct Stores {
+        _dir: tempfile::TempDir,
+        store: AuthorityStore,
+        follower_store: FollowerStore,
+        node_sync_store: NodeSyncStore,
+    }
+
+    fn open_stores(executed: TxSequenceNumber) -> Stores {
+        let dir = tempfile::tempdir().unwrap();
+        let store = AuthorityStore::open(dir.path().join("store"), None);
+        for seq in 0..executed {
+            store.side_sequence(seq, &ExecutionDigests::random());
+        }
+        Stores {
+            follower_store: FollowerStore::open(dir.path().join("follower_db")).unwrap(),
+            node_sync_store: NodeSyncStore::open(dir.path().join("node_sync_db")).unwrap(),
+            store,
+            _dir: dir,
+        }
+    }
+
+    #[test]
+    fn test_export_import_node_sync_state() {
+        let peer = *get_key_pair().1.public_key_bytes();
+        let source = open_stores(5);
+        source
+            .follower_store
+            .record_next_sequence(&peer, 42)
+            .unwrap();
+
+        let exported = NodeSyncState::export(
+            &source.store,
+            &source.follower_store,
+            &source.node_sync_store,
+        )
+        .unwrap();
+        let state = NodeSyncState::from_bytes(&exported.to_bytes().unwrap()).unwrap();
+
+        // A store copied before the export would skip transactions.
+        let stale = open_stores(4);
+        assert!(state
+            .import(&stale.store, &stale.follower_store, &stale.node_sync_store)
+            .is_err());
+        assert_eq!(stale.follower_store.get_next_sequence(&peer).unwrap(), None);
+
+        let copy = open_stores(6);
+        state
+            .import(&copy.store, &copy.follower_store, &copy.node_sync_store)
+            .unwrap();
+        assert_eq!(
+            copy.follower_store.get_next_sequence(&peer).unwrap(),
+            Some(42)
+        );
+    }
+}
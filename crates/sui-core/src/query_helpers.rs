@@ -3,13 +3,27 @@
 
 use crate::authority::SuiDataStore;
 use anyhow::anyhow;
+use move_bytecode_utils::module_cache::GetModule;
+use move_core_types::language_storage::StructTag;
 use serde::{Deserialize, Serialize};
-use sui_types::messages::{CertifiedTransaction, TransactionEffects};
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use sui_json_rpc_api::rpc_types::{
+    SuiBalanceChange, SuiCoin, SuiCoinBalance, SuiCoinPage, SuiEvent, SuiObjectChange,
+    SuiObjectTypeFilter, SuiTransactionBlockOptions, SuiTransactionBlockResponse,
+    SuiTransactionEffects,
+};
+use sui_types::coin::Coin;
+use sui_types::gas_coin::GasCoin;
+use sui_types::messages::{CertifiedTransaction, InputObjectKind, TransactionEffects};
+use sui_types::object::{Object, Owner};
 use sui_types::{base_types::*, batch::TxSequenceNumber, error::SuiError, fp_ensure};
 use tracing::debug;
 
 const MAX_TX_RANGE_SIZE: u64 = 4096;
 
+/// Farthest transitive dependencies are expanded to.
+pub const MAX_DEPENDENCY_DEPTH: u64 = 16;
+
 pub struct QueryHelpers<S> {
     _s: std::marker::PhantomData<S>,
 }
@@ -83,4 +97,480 @@ impl<S: Eq + Serialize + for<'de> Deserialize<'de>> QueryHelpers<S> {
             None => Err(anyhow!(SuiError::TransactionNotFound { digest })),
         }
     }
+
+    /// The parts of transaction `digest` asked for in `options`. The timestamp is left unset,
+    /// only the index store knows it.
+    pub fn get_transaction_block(
+        database: &SuiDataStore<S>,
+        resolver: &impl GetModule,
+        digest: TransactionDigest,
+        options: SuiTransactionBlockOptions,
+    ) -> Result<SuiTransactionBlockResponse, anyhow::Error> {
+        let (cert, effects) = Self::get_transaction(database, digest)?;
+        let object_changes = if options.show_object_changes {
+            Some(Self::get_object_changes(database, &cert, &effects)?)
+        } else {
+            None
+        };
+        let balance_changes = if options.show_balance_changes {
+            Some(Self::get_balance_changes(database, &cert, &effects)?)
+        } else {
+            None
+        };
+        let events = if options.show_events {
+            let events = effects.events.iter().cloned();
+            Some(
+                events
+                    .map(|event| SuiEvent::try_from(event, resolver))
+                    .collect::<Result<_, _>>()?,
+            )
+        } else {
+            None
+        };
+        let effects = if options.show_effects {
+            Some(SuiTransactionEffects::try_from(effects, resolver)?)
+        } else {
+            None
+        };
+        let transaction = if options.show_input {
+            Some(cert.try_into()?)
+        } else {
+            None
+        };
+        Ok(SuiTransactionBlockResponse {
+            digest,
+            transaction,
+            effects,
+            events,
+            object_changes,
+            balance_changes,
+            timestamp_ms: None,
+        })
+    }
+
+    /// The objects a transaction wrote, deleted or wrapped, with the types of those it wrote
+    /// and the versions its inputs had before it mutated them.
+    pub fn get_object_changes(
+        database: &SuiDataStore<S>,
+        cert: &CertifiedTransaction,
+        effects: &TransactionEffects,
+    ) -> Result<Vec<SuiObjectChange>, anyhow::Error> {
+        let input_versions = input_versions(cert, effects)?;
+        let mut changes = Vec::new();
+        for (reference, owner) in &effects.created {
+            let object = database.get_object_by_key(&reference.0, reference.1)?;
+            changes.push(match object {
+                Some(object) if object.is_package() => SuiObjectChange::Published {
+                    reference: (*reference).into(),
+                },
+                object => SuiObjectChange::Created {
+                    owner: *owner,
+                    reference: (*reference).into(),
+                    object_type: object.as_ref().and_then(object_type),
+                },
+            });
+        }
+        for (reference, owner) in &effects.mutated {
+            let object = database.get_object_by_key(&reference.0, reference.1)?;
+            changes.push(SuiObjectChange::Mutated {
+                owner: *owner,
+                reference: (*reference).into(),
+                object_type: object.as_ref().and_then(object_type),
+                previous_version: input_versions.get(&reference.0).copied(),
+            });
+        }
+        for (reference, owner) in &effects.unwrapped {
+            let object = database.get_object_by_key(&reference.0, reference.1)?;
+            changes.push(SuiObjectChange::Unwrapped {
+                owner: *owner,
+                reference: (*reference).into(),
+                object_type: object.as_ref().and_then(object_type),
+            });
+        }
+        changes.extend(
+            effects
+                .deleted
+                .iter()
+                .map(|reference| SuiObjectChange::Deleted {
+                    reference: (*reference).into(),
+                }),
+        );
+        changes.extend(
+            effects
+                .wrapped
+                .iter()
+                .map(|reference| SuiObjectChange::Wrapped {
+                    reference: (*reference).into(),
+                }),
+        );
+        Ok(changes)
+    }
+
+    /// How much a transaction changed the coin balances of each owner, by coin type: the coins
+    /// it wrote less the input coins it mutated, deleted or wrapped. Owners whose balance
+    /// didn't change are left out. Fails if this node pruned one of the coin versions.
+    pub fn get_balance_changes(
+        database: &SuiDataStore<S>,
+        cert: &CertifiedTransaction,
+        effects: &TransactionEffects,
+    ) -> Result<Vec<SuiBalanceChange>, anyhow::Error> {
+        let input_versions = input_versions(cert, effects)?;
+        let read = |id: &ObjectID, version: SequenceNumber| -> Result<Object, anyhow::Error> {
+            database
+                .get_object_by_key(id, version)?
+                .ok_or_else(|| anyhow!("Version {version} of object {id} was pruned"))
+        };
+        let mut changes = BTreeMap::<(Owner, String), i128>::new();
+        let spent = effects
+            .mutated
+            .iter()
+            .map(|(reference, _)| reference)
+            .chain(&effects.deleted)
+            .chain(&effects.wrapped);
+        for (id, _, _) in spent {
+            if let Some(version) = input_versions.get(id) {
+                let object = read(id, *version)?;
+                if let Some((coin_type, value)) = coin_balance(&object)? {
+                    *changes.entry((object.owner, coin_type)).or_default() -= value as i128;
+                }
+            }
+        }
+        let written = effects
+            .created
+            .iter()
+            .chain(&effects.mutated)
+            .chain(&effects.unwrapped);
+        for ((id, version, _), owner) in written {
+            if let Some((coin_type, value)) = coin_balance(&read(id, *version)?)? {
+                *changes.entry((*owner, coin_type)).or_default() += value as i128;
+            }
+        }
+        Ok(changes
+            .into_iter()
+            .filter(|(_, amount)| *amount != 0)
+            .map(|((owner, coin_type), amount)| SuiBalanceChange {
+                owner,
+                coin_type,
+                amount,
+            })
+            .collect())
+    }
+
+    /// The dependencies recorded in the effects of `digest`, and with `depth` above 1 those of
+    /// its dependencies too, up to `depth` transactions away. Every transaction expanded is
+    /// listed once with its direct dependencies, `digest` first and the others breadth first.
+    /// Dependencies whose effects aren't stored are listed but not expanded.
+    pub fn get_transaction_dependencies(
+        database: &SuiDataStore<S>,
+        digest: TransactionDigest,
+        depth: u64,
+    ) -> Result<Vec<(TransactionDigest, Vec<TransactionDigest>)>, anyhow::Error> {
+        fp_ensure!(
+            depth <= MAX_DEPENDENCY_DEPTH,
+            SuiError::TooManyItemsError(MAX_DEPENDENCY_DEPTH).into()
+        );
+        let mut graph = Vec::new();
+        let mut seen = HashSet::from([digest]);
+        let mut queue = VecDeque::from([(digest, 1)]);
+        while let Some((digest, distance)) = queue.pop_front() {
+            let dependencies = match database.get_effects(&digest) {
+                Ok(effects) => effects.dependencies,
+                Err(SuiError::TransactionNotFound { .. }) if !graph.is_empty() => continue,
+                Err(e) => return Err(e.into()),
+            };
+            fp_ensure!(
+                graph.len() < MAX_TX_RANGE_SIZE as usize,
+                SuiError::TooManyItemsError(MAX_TX_RANGE_SIZE).into()
+            );
+            if distance < depth {
+                for dependency in &dependencies {
+                    if seen.insert(*dependency) {
+                        queue.push_back((*dependency, distance + 1));
+                    }
+                }
+            }
+            graph.push((digest, dependencies));
+        }
+        Ok(graph)
+    }
+
+    /// SUI coins of `address` whose balances add up to at least `budget`, as few as possible,
+    /// largest first. The last coin is the smallest one covering what the others don't. Coins
+    /// locked to a transaction that wasn't executed yet are skipped, since using them would
+    /// conflict with it.
+    pub async fn select_gas_coins(
+        database: &SuiDataStore<S>,
+        address: SuiAddress,
+        budget: u64,
+    ) -> Result<Vec<ObjectRef>, anyhow::Error> {
+        let coin_type = SuiObjectTypeFilter::Exact(GasCoin::type_().to_string());
+        let locked = Self::locked_objects(database, address).await?;
+        let coins = Self::owned_objects(database, address, &coin_type)?;
+        gas_coins_covering(address, coins, &locked, budget)
+    }
+
+    /// A page of at most `limit` coins of `coin_type` owned by `address`, largest first and then
+    /// by object ID with `largest_first`, otherwise by object ID only, starting from the coin
+    /// `cursor`. Coins locked to a transaction that wasn't executed yet are left out with
+    /// `exclude_locked`, and marked as locked otherwise.
+    pub async fn get_coins(
+        database: &SuiDataStore<S>,
+        address: SuiAddress,
+        coin_type: &StructTag,
+        largest_first: bool,
+        exclude_locked: bool,
+        cursor: Option<ObjectID>,
+        limit: usize,
+    ) -> Result<SuiCoinPage, anyhow::Error> {
+        let owned_type = SuiObjectTypeFilter::Exact(Coin::type_(coin_type.clone()).to_string());
+        let locked = Self::locked_objects(database, address).await?;
+        let coins = Self::owned_objects(database, address, &owned_type)?;
+        coin_page(
+            address,
+            coins,
+            &locked,
+            largest_first,
+            exclude_locked,
+            cursor,
+            limit,
+        )
+    }
+
+    /// The balance of `address` per coin type, summed over the coins it owns as of the latest
+    /// transaction indexed, of `coin_type` only when set. A coin type without coins has a zero
+    /// balance. Coins locked to a transaction that wasn't executed yet still count, since they
+    /// are spent only once it is, but their sum is also reported as the locked balance.
+    pub async fn get_balances(
+        database: &SuiDataStore<S>,
+        address: SuiAddress,
+        coin_type: Option<&StructTag>,
+    ) -> Result<Vec<SuiCoinBalance>, anyhow::Error> {
+        let locked = Self::locked_objects(database, address).await?;
+        let coins = Self::owned_objects(database, address, &coins_type_filter(coin_type))?;
+        coin_balances(coins, &locked, coin_type)
+    }
+
+    async fn locked_objects(
+        database: &SuiDataStore<S>,
+        address: SuiAddress,
+    ) -> Result<HashSet<ObjectRef>, anyhow::Error> {
+        Ok(database
+            .get_locked_objects(Some(address))
+            .await?
+            .into_iter()
+            .map(|locked| locked.object_ref)
+            .collect())
+    }
+
+    /// The objects of `address` whose type matches `type_filter`, read after the owner index.
+    fn owned_objects(
+        database: &SuiDataStore<S>,
+        address: SuiAddress,
+        type_filter: &SuiObjectTypeFilter,
+    ) -> Result<Vec<(ObjectRef, Object)>, anyhow::Error> {
+        let refs: Vec<ObjectRef> = database
+            .get_owner_objects_by_type(Owner::AddressOwner(address), type_filter)?
+            .into_iter()
+            .map(ObjectRef::from)
+            .collect();
+        let ids: Vec<_> = refs.iter().map(|(id, _, _)| *id).collect();
+        Ok(refs
+            .into_iter()
+            .zip(database.get_objects(&ids)?)
+            // Skip objects deleted or written since the owner index was read.
+            .filter_map(|(object_ref, object)| {
+                object
+                    .filter(|object| object.compute_object_reference() == object_ref)
+                    .map(|object| (object_ref, object))
+            })
+            .collect())
+    }
+}
+
+/// The versions the objects a transaction took as input had before it, shared objects
+/// included. Packages are left out.
+fn input_versions(
+    cert: &CertifiedTransaction,
+    effects: &TransactionEffects,
+) -> Result<BTreeMap<ObjectID, SequenceNumber>, SuiError> {
+    let mut versions: BTreeMap<_, _> = cert
+        .data
+        .input_objects()?
+        .into_iter()
+        .filter_map(|input| match input {
+            InputObjectKind::ImmOrOwnedMoveObject((id, version, _)) => Some((id, version)),
+            InputObjectKind::MovePackage(_) | InputObjectKind::SharedMoveObject(_) => None,
+        })
+        .collect();
+    versions.extend(
+        effects
+            .shared_objects
+            .iter()
+            .map(|(id, version, _)| (*id, *version)),
+    );
+    Ok(versions)
+}
+
+fn object_type(object: &Object) -> Option<String> {
+    object.type_().map(|type_| type_.to_string())
+}
+
+/// The coin type and value of `object`, if it is a coin.
+fn coin_balance(object: &Object) -> Result<Option<(String, u64)>, anyhow::Error> {
+    let move_object = match object.data.try_as_move() {
+        Some(move_object) => move_object,
+        None => return Ok(None),
+    };
+    match Coin::coin_type(&move_object.type_) {
+        Some(coin_type) => Ok(Some((
+            coin_type.to_string(),
+            bcs::from_bytes::<Coin>(move_object.contents())?.value(),
+        ))),
+        None => Ok(None),
+    }
+}
+
+/// The type filter of the coins of `coin_type`, or of coins of any type when unset.
+pub(crate) fn coins_type_filter(coin_type: Option<&StructTag>) -> SuiObjectTypeFilter {
+    match coin_type {
+        Some(coin_type) => SuiObjectTypeFilter::Exact(Coin::type_(coin_type.clone()).to_string()),
+        // Coins of any type, e.g. `0x2::coin::Coin<`.
+        None => SuiObjectTypeFilter::Prefix(format!(
+            "{}<",
+            StructTag {
+                type_params: vec![],
+                ..GasCoin::type_()
+            }
+        )),
+    }
+}
+
+/// The SUI `coins` of `address` that cover `budget` and aren't `locked`, see
+/// [`QueryHelpers::select_gas_coins`].
+pub(crate) fn gas_coins_covering(
+    address: SuiAddress,
+    coins: Vec<(ObjectRef, Object)>,
+    locked: &HashSet<ObjectRef>,
+    budget: u64,
+) -> Result<Vec<ObjectRef>, anyhow::Error> {
+    let mut balances = Vec::with_capacity(coins.len());
+    for (coin, object) in coins {
+        if !locked.contains(&coin) {
+            balances.push((coin, GasCoin::try_from(&object)?.value()));
+        }
+    }
+    select_coins(balances, budget).ok_or_else(|| {
+        anyhow!(SuiError::InsufficientGas {
+            error: format!("The unlocked SUI coins of {address} don't cover {budget}"),
+        })
+    })
+}
+
+/// A page of the `coins` of `address`, see [`QueryHelpers::get_coins`].
+pub(crate) fn coin_page(
+    address: SuiAddress,
+    coins: Vec<(ObjectRef, Object)>,
+    locked: &HashSet<ObjectRef>,
+    largest_first: bool,
+    exclude_locked: bool,
+    cursor: Option<ObjectID>,
+    limit: usize,
+) -> Result<SuiCoinPage, anyhow::Error> {
+    let mut data = Vec::with_capacity(coins.len());
+    for (coin, object) in coins {
+        if exclude_locked && locked.contains(&coin) {
+            continue;
+        }
+        let move_object = match object.data.try_as_move() {
+            Some(move_object) => move_object,
+            None => continue,
+        };
+        let (coin_object_id, version, digest) = coin;
+        data.push(SuiCoin {
+            coin_object_id,
+            version,
+            digest,
+            balance: bcs::from_bytes::<Coin>(move_object.contents())?.value(),
+            locked: locked.contains(&coin),
+        });
+    }
+    if largest_first {
+        data.sort_by(|a, b| (b.balance, a.coin_object_id).cmp(&(a.balance, b.coin_object_id)));
+    } else {
+        data.sort_by_key(|coin| coin.coin_object_id);
+    }
+
+    if let Some(cursor) = cursor {
+        let start = data
+            .iter()
+            .position(|coin| coin.coin_object_id == cursor)
+            .ok_or_else(|| {
+                anyhow!(
+                    "Coin {cursor} isn't one of the coins of {address} anymore, \
+                    list them again from the first page"
+                )
+            })?;
+        data.drain(..start);
+    }
+    let next_cursor = data.get(limit).map(|coin| coin.coin_object_id);
+    data.truncate(limit);
+    Ok(SuiCoinPage { data, next_cursor })
+}
+
+/// The balances of `coins` per coin type, see [`QueryHelpers::get_balances`].
+pub(crate) fn coin_balances(
+    coins: Vec<(ObjectRef, Object)>,
+    locked: &HashSet<ObjectRef>,
+    coin_type: Option<&StructTag>,
+) -> Result<Vec<SuiCoinBalance>, anyhow::Error> {
+    let mut balances = BTreeMap::new();
+    if let Some(coin_type) = coin_type {
+        balances.insert(coin_type.to_string(), (0, 0, 0));
+    }
+    for (coin, object) in coins {
+        let move_object = match object.data.try_as_move() {
+            Some(move_object) => move_object,
+            None => continue,
+        };
+        let coin_type = match Coin::coin_type(&move_object.type_) {
+            Some(coin_type) => coin_type.to_string(),
+            None => continue,
+        };
+        let value = bcs::from_bytes::<Coin>(move_object.contents())?.value();
+        let (count, total, locked_total) = balances.entry(coin_type).or_insert((0, 0, 0));
+        *count += 1;
+        *total += value;
+        if locked.contains(&coin) {
+            *locked_total += value;
+        }
+    }
+    Ok(balances
+        .into_iter()
+        .map(
+            |(coin_type, (coin_object_count, total_balance, locked_balance))| SuiCoinBalance {
+                coin_type,
+                coin_object_count,
+                total_balance,
+                locked_balance,
+            },
+        )
+        .collect())
+}
+
+/// As few of `coins` as cover `budget`, see [`QueryHelpers::select_gas_coins`].
+fn select_coins(mut coins: Vec<(ObjectRef, u64)>, budget: u64) -> Option<Vec<ObjectRef>> {
+    coins.sort_by(|(_, a), (_, b)| b.cmp(a));
+    let mut selected = Vec::new();
+    let mut remaining = budget;
+    for (i, (coin, balance)) in coins.iter().enumerate() {
+        if *balance >= remaining {
+            // Coins are sorted by decreasing balance, so this is the smallest enough.
+            let (last, _) = coins[i..].iter().rev().find(|(_, b)| *b >= remaining)?;
+            selected.push(*last);
+            return Some(selected);
+        }
+        selected.push(*coin);
+        remaining -= balance;
+    }
+    None
 }
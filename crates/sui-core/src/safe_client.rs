@@ -3,7 +3,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::authority_client::{AuthorityAPI, BatchInfoResponseItemStream};
+use crate::bandwidth_throttle::BandwidthThrottle;
 use futures::StreamExt;
+use std::sync::Arc;
 use sui_types::batch::{AuthorityBatch, SignedBatch, TxSequenceNumber, UpdateItem};
 use sui_types::crypto::PublicKeyBytes;
 use sui_types::messages_checkpoint::{CheckpointRequest, CheckpointResponse};
@@ -19,6 +21,8 @@ pub struct SafeClient<C> {
     authority_client: C,
     committee: Committee,
     address: PublicKeyBytes,
+    /// Applied to the responses of the requests followers make.
+    bandwidth: Option<Arc<BandwidthThrottle>>,
 }
 
 impl<C> SafeClient<C> {
@@ -27,6 +31,20 @@ impl<C> SafeClient<C> {
             authority_client,
             committee,
             address,
+            bandwidth: None,
+        }
+    }
+
+    /// Make the transaction info, checkpoint and batch stream requests of this client count
+    /// against `throttle`.
+    pub fn with_bandwidth_throttle(mut self, throttle: Arc<BandwidthThrottle>) -> Self {
+        self.bandwidth = Some(throttle);
+        self
+    }
+
+    async fn consume_bandwidth<T: serde::Serialize>(&self, response: &T) {
+        if let Some(throttle) = &self.bandwidth {
+            throttle.consume(response).await;
         }
     }
 
@@ -368,6 +386,7 @@ where
             .authority_client
             .handle_transaction_info_request(request)
             .await?;
+        self.consume_bandwidth(&transaction_info).await;
 
         if let Err(err) = self.check_transaction_response(digest, None, &transaction_info) {
             self.report_client_error(err.clone());
@@ -388,6 +407,7 @@ where
             .authority_client
             .handle_transaction_info_request(digest.into())
             .await?;
+        self.consume_bandwidth(&transaction_info).await;
 
         if let Err(err) =
             self.check_transaction_response(digest, Some(effects_digest), &transaction_info)
@@ -404,7 +424,9 @@ where
     ) -> Result<CheckpointResponse, SuiError> {
         // SECURITY TODO: Implement all checks!
 
-        self.authority_client.handle_checkpoint(request).await
+        let response = self.authority_client.handle_checkpoint(request).await?;
+        self.consume_bandwidth(&response).await;
+        Ok(response)
     }
 
     /// Handle Batch information requests for this authority.
@@ -472,6 +494,20 @@ where
                 futures::future::ready(result)
             },
         ));
-        Ok(Box::pin(stream))
+        match self.bandwidth.clone() {
+            // Waiting before handing out an item delays reading the next one.
+            Some(throttle) => Ok(stream
+                .then(move |item| {
+                    let throttle = throttle.clone();
+                    async move {
+                        if let Ok(item) = &item {
+                            throttle.consume(item).await;
+                        }
+                        item
+                    }
+                })
+                .boxed()),
+            None => Ok(Box::pin(stream)),
+        }
     }
 }
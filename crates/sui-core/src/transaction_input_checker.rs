@@ -11,7 +11,10 @@ use sui_types::{
     error::{SuiError, SuiResult},
     fp_ensure,
     gas::{self, SuiGasStatus},
-    messages::{InputObjectKind, SingleTransactionKind, TransactionData, TransactionEnvelope},
+    messages::{
+        CertifiedTransaction, InputObjectKind, SingleTransactionKind, TransactionData,
+        TransactionEffects, TransactionEnvelope,
+    },
     object::{Object, Owner},
 };
 use tracing::{debug, instrument};
@@ -133,6 +136,54 @@ where
     Ok((gas_status, input_objects))
 }
 
+/// The objects `certificate` read when it was executed with `effects`, at the versions it read,
+/// and the gas status it started executing with, to execute it again. None if one of those
+/// versions is no longer stored.
+pub async fn load_executed_inputs<S>(
+    store: &SuiDataStore<S>,
+    certificate: &CertifiedTransaction,
+    effects: &TransactionEffects,
+) -> SuiResult<Option<(SuiGasStatus<'static>, InputObjects)>>
+where
+    S: Eq + Serialize + for<'de> Deserialize<'de>,
+{
+    let data = &certificate.data;
+    let mut gas_status = if data.kind.is_system_tx() {
+        SuiGasStatus::new_unmetered()
+    } else {
+        gas::start_gas_metering(data.gas_budget, data.gas_price, STORAGE_GAS_PRICE)?
+    };
+    if certificate.contains_shared_object() {
+        gas_status.charge_consensus()?;
+    }
+
+    let mut objects = Vec::new();
+    for kind in data.input_objects()? {
+        let object = match kind {
+            // Packages are immutable, their only version is the one read.
+            InputObjectKind::MovePackage(id) => store.get_object(&id)?,
+            InputObjectKind::ImmOrOwnedMoveObject((id, version, _)) => {
+                store.get_object_by_key(&id, version)?
+            }
+            InputObjectKind::SharedMoveObject(id) => {
+                match effects
+                    .shared_objects
+                    .iter()
+                    .find(|(shared, _, _)| *shared == id)
+                {
+                    Some((_, version, _)) => store.get_object_by_key(&id, *version)?,
+                    None => None,
+                }
+            }
+        };
+        match object {
+            Some(object) => objects.push((kind, object)),
+            None => return Ok(None),
+        }
+    }
+    Ok(Some((gas_status, InputObjects::new(objects))))
+}
+
 /// Checking gas budget by fetching the gas object only from the store,
 /// and check whether the balance and budget satisfies the miminum requirement.
 /// Returns the gas object (to be able to reuse it latter) and a gas status
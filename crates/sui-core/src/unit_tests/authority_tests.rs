@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::*;
+use crate::query_helpers::MAX_DEPENDENCY_DEPTH;
 use bcs;
 use move_binary_format::{
     file_format::{self, AddressIdentifierIndex, IdentifierIndex, ModuleHandle},
@@ -18,6 +19,7 @@ use sui_types::{
     base_types::dbg_addr,
     crypto::KeyPair,
     crypto::{get_key_pair, Signature},
+    gas_coin::GasCoin,
     messages::Transaction,
     object::{Owner, OBJECT_START_VERSION},
     sui_system_state::SuiSystemState,
@@ -26,6 +28,10 @@ use sui_types::{
 
 use std::fs;
 use std::{convert::TryInto, env};
+use sui_json_rpc_api::rpc_types::{
+    SuiBalanceChange, SuiObjectChange, SuiObjectTypeFilter, SuiStakeDistribution,
+};
+use sui_storage::ColumnFamilyGroup;
 
 pub enum TestCallArg {
     Object(ObjectID),
@@ -862,6 +868,42 @@ async fn test_handle_confirmation_transaction_receiver_equal_sender() {
         .is_some());
 }
 
+#[tokio::test]
+async fn test_handle_confirmation_transaction_prefetching_inputs() {
+    let (sender, sender_key) = get_key_pair();
+    let object_id = ObjectID::random();
+    let gas_object_id = ObjectID::random();
+    let authority_state = init_state_with_ids(vec![(sender, object_id), (sender, gas_object_id)])
+        .await
+        .with_input_prefetch_concurrency(4);
+    let object = authority_state
+        .get_object(&object_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let gas_object = authority_state
+        .get_object(&gas_object_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let certificate = init_certified_transfer_transaction(
+        sender,
+        &sender_key,
+        dbg_addr(2),
+        object.compute_object_reference(),
+        gas_object.compute_object_reference(),
+        &authority_state,
+    );
+
+    let info = authority_state
+        .handle_confirmation_transaction(ConfirmationTransaction::new(certificate))
+        .await
+        .unwrap();
+    info.signed_effects.unwrap().effects.status.unwrap();
+    // The object and the gas object.
+    assert_eq!(authority_state.metrics.prefetched_input_objects.get(), 2);
+}
+
 #[tokio::test]
 async fn test_handle_confirmation_transaction_ok() {
     let (sender, sender_key) = get_key_pair();
@@ -915,6 +957,18 @@ async fn test_handle_confirmation_transaction_ok() {
     assert_eq!(new_account.owner, recipient);
     assert_eq!(next_sequence_number, new_account.version());
     assert_eq!(None, info.signed_transaction);
+    let (latest_cert, latest_effects) = authority_state
+        .get_latest_modifying_transaction(object_id)
+        .await
+        .unwrap();
+    assert_eq!(
+        latest_cert.digest(),
+        certified_transfer_transaction.digest()
+    );
+    assert_eq!(
+        latest_effects.transaction_digest,
+        *certified_transfer_transaction.digest()
+    );
     let opt_cert = {
         let refx = authority_state
             .parent(&(object_id, new_account.version(), new_account.digest()))
@@ -1227,6 +1281,431 @@ async fn test_get_latest_parent_entry() {
     assert_eq!(effects.transaction_digest, tx);
 }
 
+#[tokio::test]
+async fn test_get_past_object_read() {
+    let (sender, sender_key) = get_key_pair();
+    let gas_object_id = ObjectID::random();
+    let authority_state = init_state_with_ids(vec![(sender, gas_object_id)]).await;
+
+    let effects = create_move_object(&authority_state, &gas_object_id, &sender, &sender_key)
+        .await
+        .unwrap();
+    let created_ref = effects.created[0].0;
+    let object_id = created_ref.0;
+    let created = authority_state
+        .get_object(&object_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let effects = call_framework_code(
+        &authority_state,
+        &gas_object_id,
+        &sender,
+        &sender_key,
+        "object_basics",
+        "delete",
+        vec![],
+        vec![TestCallArg::Object(object_id)],
+    )
+    .await
+    .unwrap();
+    assert_eq!(effects.deleted[0].0, object_id);
+
+    // The version the object was created at is still readable after it was deleted.
+    match authority_state
+        .get_past_object_read(&object_id, created_ref.1)
+        .await
+        .unwrap()
+    {
+        ObjectRead::Exists(obj_ref, object, _) => {
+            assert_eq!(obj_ref, created_ref);
+            assert_eq!(object, created);
+        }
+        _ => panic!("Expected the created version of {object_id}"),
+    }
+
+    assert!(matches!(
+        authority_state
+            .get_past_object_read(&object_id, effects.deleted[0].1)
+            .await
+            .unwrap(),
+        ObjectRead::Deleted(obj_ref) if obj_ref == effects.deleted[0]
+    ));
+
+    // A version the object never had.
+    assert!(matches!(
+        authority_state
+            .get_past_object_read(&object_id, SequenceNumber::from(10))
+            .await
+            .unwrap(),
+        ObjectRead::NotExists(id) if id == object_id
+    ));
+}
+
+#[tokio::test]
+async fn test_get_transaction_dependencies() {
+    let (sender, sender_key) = get_key_pair();
+    let gas_object_id = ObjectID::random();
+    let authority_state = init_state_with_ids(vec![(sender, gas_object_id)]).await;
+
+    let created = create_move_object(&authority_state, &gas_object_id, &sender, &sender_key)
+        .await
+        .unwrap();
+    let deleted = call_framework_code(
+        &authority_state,
+        &gas_object_id,
+        &sender,
+        &sender_key,
+        "object_basics",
+        "delete",
+        vec![],
+        vec![TestCallArg::Object(created.created[0].0 .0)],
+    )
+    .await
+    .unwrap();
+    assert!(deleted.dependencies.contains(&created.transaction_digest));
+
+    let direct = authority_state
+        .get_transaction_dependencies(deleted.transaction_digest, 1)
+        .await
+        .unwrap();
+    assert_eq!(
+        direct,
+        vec![(deleted.transaction_digest, deleted.dependencies.clone())]
+    );
+
+    // The genesis transaction the gas object comes from isn't stored, so it isn't expanded.
+    let transitive = authority_state
+        .get_transaction_dependencies(deleted.transaction_digest, 3)
+        .await
+        .unwrap();
+    assert_eq!(
+        transitive,
+        vec![
+            (deleted.transaction_digest, deleted.dependencies),
+            (created.transaction_digest, created.dependencies),
+        ]
+    );
+
+    assert!(authority_state
+        .get_transaction_dependencies(deleted.transaction_digest, MAX_DEPENDENCY_DEPTH + 1)
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_select_gas_coins() {
+    let (sender, sender_key) = get_key_pair();
+    let coins: Vec<_> = [10, 20, 40, 70, 100_000, 100_000]
+        .into_iter()
+        .map(|balance| Object::with_id_owner_gas_for_testing(ObjectID::random(), sender, balance))
+        .collect();
+    let coin_ref = |i: usize| coins[i].compute_object_reference();
+    let authority_state = init_state_with_objects(coins.clone()).await;
+
+    // Lock the two largest coins to a transaction that isn't executed.
+    let transaction =
+        init_transfer_transaction(sender, &sender_key, dbg_addr(2), coin_ref(4), coin_ref(5));
+    authority_state
+        .handle_transaction(transaction)
+        .await
+        .unwrap();
+
+    let select = |budget| authority_state.select_gas_coins(sender, budget);
+    // The smallest coin covering the budget.
+    assert_eq!(select(25).await.unwrap(), vec![coin_ref(2)]);
+    assert_eq!(select(70).await.unwrap(), vec![coin_ref(3)]);
+    // The largest coin, and the smallest one covering the rest.
+    assert_eq!(select(100).await.unwrap(), vec![coin_ref(3), coin_ref(2)]);
+    assert_eq!(
+        select(140).await.unwrap(),
+        vec![coin_ref(3), coin_ref(2), coin_ref(1), coin_ref(0)]
+    );
+    // The locked coins would cover it.
+    assert!(select(141).await.is_err());
+}
+
+#[tokio::test]
+async fn test_get_balances() {
+    let (sender, sender_key) = get_key_pair();
+    let coins: Vec<_> = [10, 20, 40]
+        .into_iter()
+        .map(|balance| Object::with_id_owner_gas_for_testing(ObjectID::random(), sender, balance))
+        .collect();
+    let coin_ref = |i: usize| coins[i].compute_object_reference();
+    let authority_state = init_state_with_objects(coins.clone()).await;
+
+    // Lock two of the coins to a transaction that isn't executed.
+    let transaction =
+        init_transfer_transaction(sender, &sender_key, dbg_addr(2), coin_ref(0), coin_ref(1));
+    authority_state
+        .handle_transaction(transaction)
+        .await
+        .unwrap();
+
+    let sui = SuiCoinBalance {
+        coin_type: GAS::type_().to_string(),
+        coin_object_count: 3,
+        total_balance: 70,
+        locked_balance: 30,
+    };
+    assert_eq!(
+        authority_state.get_balances(sender, None).await.unwrap(),
+        vec![sui.clone()]
+    );
+    assert_eq!(
+        authority_state
+            .get_balances(sender, Some(GAS::type_()))
+            .await
+            .unwrap(),
+        vec![sui]
+    );
+
+    // Coin types the address owns no coins of have a zero balance.
+    let other = StructTag {
+        address: SUI_FRAMEWORK_ADDRESS,
+        module: ident_str!("other").to_owned(),
+        name: ident_str!("OTHER").to_owned(),
+        type_params: vec![],
+    };
+    assert_eq!(
+        authority_state
+            .get_balances(dbg_addr(2), Some(other.clone()))
+            .await
+            .unwrap(),
+        vec![SuiCoinBalance {
+            coin_type: other.to_string(),
+            coin_object_count: 0,
+            total_balance: 0,
+            locked_balance: 0,
+        }]
+    );
+    assert!(authority_state
+        .get_balances(dbg_addr(2), None)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_get_coins() {
+    let (sender, sender_key) = get_key_pair();
+    let coins: Vec<_> = [40, 10, 70, 20]
+        .into_iter()
+        .map(|balance| Object::with_id_owner_gas_for_testing(ObjectID::random(), sender, balance))
+        .collect();
+    let coin_ref = |i: usize| coins[i].compute_object_reference();
+    let authority_state = init_state_with_objects(coins.clone()).await;
+
+    // Lock the largest coin to a transaction that isn't executed.
+    let transaction =
+        init_transfer_transaction(sender, &sender_key, dbg_addr(2), coin_ref(2), coin_ref(3));
+    authority_state
+        .handle_transaction(transaction)
+        .await
+        .unwrap();
+
+    let get_coins = |largest_first, exclude_locked, cursor, limit| {
+        authority_state.get_coins(
+            sender,
+            GAS::type_(),
+            largest_first,
+            exclude_locked,
+            cursor,
+            limit,
+        )
+    };
+    let balances =
+        |page: &SuiCoinPage| -> Vec<_> { page.data.iter().map(|coin| coin.balance).collect() };
+
+    // Largest first, a page at a time.
+    let page = get_coins(true, false, None, 3).await.unwrap();
+    assert_eq!(balances(&page), vec![70, 40, 20]);
+    assert_eq!(page.data[0].to_object_ref(), coin_ref(2));
+    assert!(page.data[0].locked && page.data[2].locked && !page.data[1].locked);
+    assert_eq!(page.next_cursor, Some(coin_ref(1).0));
+    let page = get_coins(true, false, page.next_cursor, 3).await.unwrap();
+    assert_eq!(balances(&page), vec![10]);
+    assert_eq!(page.next_cursor, None);
+
+    // Without the locked coins.
+    let page = get_coins(true, true, None, 3).await.unwrap();
+    assert_eq!(balances(&page), vec![40, 10]);
+    assert_eq!(page.next_cursor, None);
+
+    // By object ID.
+    let mut ids: Vec<_> = coins.iter().map(|coin| coin.id()).collect();
+    ids.sort();
+    let page = get_coins(false, false, None, 4).await.unwrap();
+    let listed: Vec<_> = page.data.iter().map(|coin| coin.coin_object_id).collect();
+    assert_eq!(listed, ids);
+
+    // A cursor that isn't one of the coins.
+    assert!(get_coins(true, false, Some(ObjectID::random()), 3)
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_verify_effects() {
+    let (sender, sender_key) = get_key_pair();
+    let coins: Vec<_> = [1000, 100_000]
+        .into_iter()
+        .map(|balance| Object::with_id_owner_gas_for_testing(ObjectID::random(), sender, balance))
+        .collect();
+    let coin_ref = |i: usize| coins[i].compute_object_reference();
+    let authority_state = init_state_with_objects(coins.clone()).await;
+
+    let transaction =
+        init_transfer_transaction(sender, &sender_key, dbg_addr(2), coin_ref(0), coin_ref(1));
+    let digest = *transaction.digest();
+    let effects = send_and_confirm_transaction(&authority_state, transaction)
+        .await
+        .unwrap()
+        .signed_effects
+        .unwrap()
+        .effects;
+
+    let unknown = TransactionDigest::random();
+    let verifications = authority_state
+        .verify_effects(&[
+            ExecutionDigests::new(digest, effects.digest()),
+            ExecutionDigests::new(digest, TransactionEffectsDigest::random()),
+            ExecutionDigests::new(unknown, effects.digest()),
+        ])
+        .unwrap();
+    let valid: Vec<_> = verifications
+        .iter()
+        .map(|verification| (verification.transaction_digest, verification.valid))
+        .collect();
+    assert_eq!(
+        valid,
+        vec![(digest, true), (digest, false), (unknown, false)]
+    );
+    // This authority has no way to tell the checkpoints of transactions.
+    assert!(verifications
+        .iter()
+        .all(|verification| verification.checkpoint.is_none()));
+}
+
+#[tokio::test]
+async fn test_reexecute_transaction() {
+    let (sender, sender_key) = get_key_pair();
+    let coins: Vec<_> = [1000, 100_000]
+        .into_iter()
+        .map(|balance| Object::with_id_owner_gas_for_testing(ObjectID::random(), sender, balance))
+        .collect();
+    let authority_state = init_state_with_objects(coins.clone()).await;
+
+    let transaction = init_transfer_transaction(
+        sender,
+        &sender_key,
+        dbg_addr(2),
+        coins[0].compute_object_reference(),
+        coins[1].compute_object_reference(),
+    );
+    let digest = *transaction.digest();
+    send_and_confirm_transaction(&authority_state, transaction)
+        .await
+        .unwrap();
+
+    // The objects it read were modified since, their past versions are read instead.
+    let (stored, reexecuted) = authority_state
+        .reexecute_transaction(&digest)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored, reexecuted);
+    assert_eq!(authority_state.metrics.reexecuted_transactions.get(), 1);
+    assert_eq!(authority_state.metrics.reexecution_divergences.get(), 0);
+
+    assert!(authority_state
+        .reexecute_transaction(&TransactionDigest::random())
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_get_transaction_block() {
+    let (sender, sender_key) = get_key_pair();
+    let recipient = dbg_addr(2);
+    let coins: Vec<_> = [1000, 100_000]
+        .into_iter()
+        .map(|balance| Object::with_id_owner_gas_for_testing(ObjectID::random(), sender, balance))
+        .collect();
+    let coin_ref = |i: usize| coins[i].compute_object_reference();
+    let authority_state = init_state_with_objects(coins.clone()).await;
+
+    let transaction =
+        init_transfer_transaction(sender, &sender_key, recipient, coin_ref(0), coin_ref(1));
+    let digest = *transaction.digest();
+    let effects = send_and_confirm_transaction(&authority_state, transaction)
+        .await
+        .unwrap()
+        .signed_effects
+        .unwrap()
+        .effects;
+
+    // Only what is asked for is returned.
+    let response = authority_state
+        .get_transaction_block(digest, SuiTransactionBlockOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(response.digest, digest);
+    assert!(response.transaction.is_none() && response.effects.is_none());
+
+    let options = SuiTransactionBlockOptions {
+        show_input: true,
+        show_effects: true,
+        show_events: true,
+        show_object_changes: true,
+        show_balance_changes: true,
+    };
+    let response = authority_state
+        .get_transaction_block(digest, options)
+        .await
+        .unwrap();
+    assert!(response.transaction.is_some() && response.effects.is_some());
+    assert_eq!(response.events.unwrap().len(), effects.events.len());
+
+    let object_changes = response.object_changes.unwrap();
+    assert_eq!(object_changes.len(), 2);
+    for change in object_changes {
+        match change {
+            SuiObjectChange::Mutated {
+                object_type,
+                previous_version,
+                ..
+            } => {
+                assert_eq!(object_type, Some(GasCoin::type_().to_string()));
+                assert_eq!(previous_version, Some(coins[0].version()));
+            }
+            change => panic!("Unexpected object change {change:?}"),
+        }
+    }
+
+    let sui = GAS::type_().to_string();
+    let mut balance_changes = response.balance_changes.unwrap();
+    balance_changes.sort_by_key(|change| change.owner != Owner::AddressOwner(sender));
+    assert_eq!(
+        balance_changes,
+        vec![
+            SuiBalanceChange {
+                owner: Owner::AddressOwner(sender),
+                coin_type: sui.clone(),
+                amount: -1000 - effects.gas_used.net_gas_usage() as i128,
+            },
+            SuiBalanceChange {
+                owner: Owner::AddressOwner(recipient),
+                coin_type: sui,
+                amount: 1000,
+            },
+        ]
+    );
+}
+
 #[tokio::test]
 async fn test_account_state_ok() {
     let sender = dbg_addr(1);
@@ -1298,34 +1777,206 @@ async fn test_authority_persist() {
 }
 
 #[tokio::test]
-async fn test_idempotent_reversed_confirmation() {
-    // In this test we exercise the case where an authority first receive the certificate,
-    // and then receive the raw transaction latter. We should still ensure idempotent
-    // response and be able to get back the same result.
-    let recipient = dbg_addr(2);
-    let (sender, sender_key) = get_key_pair();
+async fn test_authority_persist_with_column_family_groups() {
+    let seed = [1u8; 32];
+    let dir = env::temp_dir();
+    let path = dir.join(format!("DB_{:?}", ObjectID::random()));
+    fs::create_dir(&path).unwrap();
 
-    let object = Object::with_owner_for_testing(sender);
-    let object_ref = object.compute_object_reference();
-    let gas_object = Object::with_owner_for_testing(sender);
-    let gas_object_ref = gas_object.compute_object_reference();
-    let authority_state = init_state_with_objects([object, gas_object]).await;
+    let groups = StoreColumnFamilyGroups {
+        execution: ColumnFamilyGroup::new(8 << 20, 4 << 20, 2),
+        history: ColumnFamilyGroup::new(4 << 20, 1 << 20, 2),
+        max_background_jobs: Some(2),
+    };
+    let (committee, _, authority_key) =
+        crate::authority_batch::batch_tests::init_state_parameters_from_rng(
+            &mut StdRng::from_seed(seed),
+        );
+    let store = Arc::new(AuthorityStore::open_with_column_family_groups(
+        &path,
+        None,
+        Some(&groups),
+    ));
+    let authority =
+        crate::authority_batch::batch_tests::init_state(committee, authority_key, store).await;
 
-    let certified_transfer_transaction = init_certified_transfer_transaction(
-        sender,
-        &sender_key,
-        recipient,
-        object_ref,
-        gas_object_ref,
-        &authority_state,
-    );
-    let result1 = authority_state
-        .handle_confirmation_transaction(ConfirmationTransaction::new(
-            certified_transfer_transaction.clone(),
-        ))
+    let recipient = dbg_addr(2);
+    let object_id = ObjectID::random();
+    authority
+        .insert_genesis_object(Object::with_id_owner_for_testing(object_id, recipient))
         .await;
-    assert!(result1.is_ok());
-    let result2 = authority_state
+    drop(authority);
+
+    // The column families are the same without groups.
+    let (committee, _, authority_key) =
+        crate::authority_batch::batch_tests::init_state_parameters_from_rng(
+            &mut StdRng::from_seed(seed),
+        );
+    let store = Arc::new(AuthorityStore::open(&path, None));
+    let authority =
+        crate::authority_batch::batch_tests::init_state(committee, authority_key, store).await;
+    let object = authority.get_object(&object_id).await.unwrap().unwrap();
+    assert_eq!(object.owner, recipient);
+}
+
+#[test]
+fn test_store_locked_by_another_open() {
+    let dir = env::temp_dir();
+    let path = dir.join(format!("DB_{:?}", ObjectID::random()));
+    fs::create_dir(&path).unwrap();
+
+    let store = AuthorityStore::try_open_with_column_family_groups(&path, None, None).unwrap();
+    let error = AuthorityStore::try_open_with_column_family_groups(&path, None, None)
+        .err()
+        .unwrap();
+    assert!(is_db_locked_error(&error), "{error}");
+
+    // The lock is released with the store.
+    drop(store);
+    AuthorityStore::try_open_with_column_family_groups(&path, None, None).unwrap();
+}
+
+#[tokio::test]
+async fn test_secondary_store_follows_primary() {
+    let seed = [1u8; 32];
+    let (committee, _, authority_key) =
+        crate::authority_batch::batch_tests::init_state_parameters_from_rng(
+            &mut StdRng::from_seed(seed),
+        );
+
+    let dir = env::temp_dir();
+    let path = dir.join(format!("DB_{:?}", ObjectID::random()));
+    let secondary_path = dir.join(format!("DB_{:?}", ObjectID::random()));
+    fs::create_dir(&path).unwrap();
+    fs::create_dir(&secondary_path).unwrap();
+
+    let store = Arc::new(AuthorityStore::open(&path, None));
+    let primary =
+        crate::authority_batch::batch_tests::init_state(committee, authority_key, store).await;
+
+    let (committee, _, authority_key) =
+        crate::authority_batch::batch_tests::init_state_parameters_from_rng(
+            &mut StdRng::from_seed(seed),
+        );
+    let store = Arc::new(AuthorityStore::open_secondary(&path, &secondary_path, None));
+    assert!(store.is_secondary());
+    let secondary =
+        crate::authority_batch::batch_tests::init_state(committee, authority_key, store).await;
+
+    // Writes of the primary show up once the secondary caught up.
+    let (sender, sender_key) = get_key_pair();
+    let object_id = ObjectID::random();
+    let gas_object_id = ObjectID::random();
+    primary
+        .insert_genesis_object(Object::with_id_owner_for_testing(object_id, sender))
+        .await;
+    primary
+        .insert_genesis_object(Object::with_id_owner_for_testing(gas_object_id, sender))
+        .await;
+    secondary.database.try_catch_up_with_primary().unwrap();
+    let object = secondary.get_object(&object_id).await.unwrap().unwrap();
+    let gas_object = secondary.get_object(&gas_object_id).await.unwrap().unwrap();
+    assert_eq!(object.owner, Owner::AddressOwner(sender));
+
+    // The secondary refuses to handle transactions.
+    let transfer_transaction = init_transfer_transaction(
+        sender,
+        &sender_key,
+        dbg_addr(2),
+        object.compute_object_reference(),
+        gas_object.compute_object_reference(),
+    );
+    assert!(matches!(
+        secondary.handle_transaction(transfer_transaction).await,
+        Err(SuiError::UnsupportedFeatureError { .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_storage_failure_stops_authority() {
+    let (sender, sender_key) = get_key_pair();
+    let object_id = ObjectID::random();
+    let gas_object_id = ObjectID::random();
+    let authority_state =
+        init_state_with_ids(vec![(sender, object_id), (sender, gas_object_id)]).await;
+    let object = authority_state
+        .get_object(&object_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let gas_object = authority_state
+        .get_object(&gas_object_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let transfer_transaction = init_transfer_transaction(
+        sender,
+        &sender_key,
+        dbg_addr(2),
+        object.compute_object_reference(),
+        gas_object.compute_object_reference(),
+    );
+    let certificate = init_certified_transaction(transfer_transaction.clone(), &authority_state);
+    assert!(authority_state.check_healthy().is_ok());
+
+    authority_state.record_storage_failure(&SuiError::GenericStorageError("disk full".to_string()));
+
+    assert!(matches!(
+        authority_state.check_healthy(),
+        Err(SuiError::AuthorityUnhealthy { .. })
+    ));
+    assert!(matches!(
+        authority_state
+            .handle_transaction(transfer_transaction)
+            .await,
+        Err(SuiError::AuthorityUnhealthy { .. })
+    ));
+    assert!(matches!(
+        authority_state
+            .handle_confirmation_transaction(ConfirmationTransaction::new(certificate))
+            .await,
+        Err(SuiError::AuthorityUnhealthy { .. })
+    ));
+    // Nothing was executed.
+    assert_eq!(
+        authority_state
+            .get_object(&object_id)
+            .await
+            .unwrap()
+            .unwrap(),
+        object
+    );
+}
+
+#[tokio::test]
+async fn test_idempotent_reversed_confirmation() {
+    // In this test we exercise the case where an authority first receive the certificate,
+    // and then receive the raw transaction latter. We should still ensure idempotent
+    // response and be able to get back the same result.
+    let recipient = dbg_addr(2);
+    let (sender, sender_key) = get_key_pair();
+
+    let object = Object::with_owner_for_testing(sender);
+    let object_ref = object.compute_object_reference();
+    let gas_object = Object::with_owner_for_testing(sender);
+    let gas_object_ref = gas_object.compute_object_reference();
+    let authority_state = init_state_with_objects([object, gas_object]).await;
+
+    let certified_transfer_transaction = init_certified_transfer_transaction(
+        sender,
+        &sender_key,
+        recipient,
+        object_ref,
+        gas_object_ref,
+        &authority_state,
+    );
+    let result1 = authority_state
+        .handle_confirmation_transaction(ConfirmationTransaction::new(
+            certified_transfer_transaction.clone(),
+        ))
+        .await;
+    assert!(result1.is_ok());
+    let result2 = authority_state
         .handle_transaction(certified_transfer_transaction.to_transaction())
         .await;
     assert!(result2.is_ok());
@@ -1358,8 +2009,8 @@ async fn test_change_epoch_transaction() {
         1,
         100,
         100,
-        authority_state.name,
-        &*authority_state.secret,
+        authority_state.name(),
+        &*authority_state.secret(),
     );
     // Make sure that the raw transaction will never be accepted by the validator.
     assert_eq!(
@@ -1388,6 +2039,54 @@ async fn test_change_epoch_transaction() {
     assert_eq!(sui_system_object.epoch, 1);
 }
 
+#[tokio::test]
+async fn test_get_total_supply() {
+    let authority_state = init_state().await;
+    let system_state = authority_state.get_sui_system_state_object().await.unwrap();
+    assert_eq!(
+        authority_state
+            .get_total_supply(&sui_types::gas_coin::GAS::type_())
+            .await
+            .unwrap(),
+        (system_state.treasury_cap.value, None)
+    );
+
+    // Other coins are found through the indexes, which this authority doesn't keep.
+    let coin_type = StructTag {
+        address: ObjectID::random().into(),
+        module: Identifier::new("my_coin").unwrap(),
+        name: Identifier::new("MY_COIN").unwrap(),
+        type_params: vec![],
+    };
+    assert!(matches!(
+        authority_state.get_total_supply(&coin_type).await,
+        Err(SuiError::UnsupportedFeatureError { .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_stake_distribution() {
+    let authority_state = init_state().await;
+    let system_state = authority_state.get_sui_system_state_object().await.unwrap();
+    let distribution = SuiStakeDistribution::from(system_state.clone());
+
+    assert_eq!(distribution.epoch, 0);
+    assert_eq!(distribution.storage_fund, system_state.storage_fund.value());
+    assert!(!distribution.validators.is_empty());
+    assert_eq!(
+        distribution.validators.len(),
+        system_state.validators.active_validators.len()
+    );
+    assert_eq!(
+        distribution
+            .validators
+            .iter()
+            .map(|validator| validator.stake)
+            .sum::<u64>(),
+        distribution.validator_stake
+    );
+}
+
 #[tokio::test]
 async fn test_transfer_sui_no_amount() {
     let (sender, sender_key) = get_key_pair();
@@ -1518,11 +2217,20 @@ async fn test_store_revert_state_update() {
         .handle_confirmation_transaction(ConfirmationTransaction { certificate })
         .await
         .unwrap();
+    let gas_type = SuiObjectTypeFilter::Exact(gas_object.data.type_().unwrap().to_string());
+    let owned_by_type = |owner: SuiAddress| {
+        authority_state
+            .get_owner_objects_by_type(Owner::AddressOwner(owner), &gas_type)
+            .unwrap()
+            .len()
+    };
+    assert_eq!((owned_by_type(sender), owned_by_type(recipient)), (0, 1));
 
     authority_state
         .database
         .revert_state_update(&tx_digest)
         .unwrap();
+    assert_eq!((owned_by_type(sender), owned_by_type(recipient)), (1, 0));
     assert_eq!(
         authority_state
             .database
@@ -1561,6 +2269,343 @@ async fn test_store_revert_state_update() {
     assert!(authority_state.database.get_effects(&tx_digest).is_err());
 }
 
+#[tokio::test]
+async fn test_committee_for_epoch() {
+    let state = init_state().await;
+    let genesis = state.clone_committee();
+    assert_eq!(state.committee_for_epoch(0).unwrap().unwrap().epoch, 0);
+    assert!(state.committee_for_epoch(1).unwrap().is_none());
+
+    let next = Committee::new(1, genesis.names().map(|name| (*name, 1)).collect()).unwrap();
+    state.insert_new_epoch_info(&next).unwrap();
+    assert_eq!(state.committee_for_epoch(1).unwrap().unwrap().epoch, 1);
+    // The committees of past epochs are still known.
+    assert_eq!(state.committee_for_epoch(0).unwrap().unwrap().epoch, 0);
+    assert!(state.committee_for_epoch(2).unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_post_processing_dead_letters() {
+    let (committee, _, authority_key, store) = init_state_parameters();
+    let dir = tempfile::tempdir().unwrap();
+    let state = AuthorityState::new(
+        committee,
+        *authority_key.public_key_bytes(),
+        Arc::pin(authority_key),
+        store,
+        Some(Arc::new(IndexStore::open(dir.path().join("indexes"), None))),
+        None,
+        None,
+        &sui_config::genesis::Genesis::get_default_genesis(),
+        &prometheus::Registry::new(),
+    )
+    .await;
+    let (sender, sender_key) = get_key_pair();
+    let object_id = ObjectID::random();
+    let gas_object_id = ObjectID::random();
+    for id in [object_id, gas_object_id] {
+        let object = Object::with_id_owner_for_testing(id, sender);
+        state.insert_genesis_object(object).await;
+    }
+    let object = state.get_object(&object_id).await.unwrap().unwrap();
+    let gas_object = state.get_object(&gas_object_id).await.unwrap().unwrap();
+    let transaction = init_transfer_transaction(
+        sender,
+        &sender_key,
+        dbg_addr(2),
+        object.compute_object_reference(),
+        gas_object.compute_object_reference(),
+    );
+    send_and_confirm_transaction(&state, transaction)
+        .await
+        .unwrap();
+    let next = state.database.next_sequence_number().unwrap();
+    let (seq, digest) = state
+        .database
+        .transactions_in_seq_range(0, next)
+        .unwrap()
+        .pop()
+        .unwrap();
+
+    let retry_policy = PostProcessingRetryPolicy {
+        max_attempts: 2,
+        initial_backoff: Duration::from_millis(1),
+        max_backoff: Duration::from_millis(1),
+    };
+    let dead_letters = DeadLetterStore::open(dir.path().join("dead_letters")).unwrap();
+    let indexes = state.indexes.clone().unwrap();
+
+    // A failure is retried.
+    state.index_failures.store(1, Ordering::Relaxed);
+    state
+        .process_one_tx_with_retry(seq, &digest, &retry_policy, 1, &dead_letters)
+        .await;
+    assert_eq!(state.metrics.post_processing_retries.get(), 1);
+    assert_eq!(dead_letters.count(), 0);
+    assert_eq!(
+        indexes.get_transactions_from_addr(sender).unwrap(),
+        vec![(seq, digest)]
+    );
+
+    // The transaction is kept once all attempts failed.
+    state.index_failures.store(2, Ordering::Relaxed);
+    state
+        .process_one_tx_with_retry(seq, &digest, &retry_policy, 1, &dead_letters)
+        .await;
+    assert_eq!(state.metrics.post_processing_retries.get(), 2);
+    assert_eq!(state.metrics.post_processing_dead_letters.get(), 1);
+    let letters: Vec<_> = dead_letters.iter().collect();
+    assert_eq!(letters.len(), 1);
+    let (letter_seq, (letter_digest, error)) = &letters[0];
+    assert_eq!((*letter_seq, *letter_digest), (seq, digest));
+    assert!(error.contains("Injected indexing failure"));
+
+    // Reprocessing keeps it while indexing still fails.
+    state.index_failures.store(1, Ordering::Relaxed);
+    assert_eq!(
+        state.reprocess_dead_letters(&dead_letters).await.unwrap(),
+        0
+    );
+    assert_eq!(dead_letters.count(), 1);
+
+    assert_eq!(
+        state.reprocess_dead_letters(&dead_letters).await.unwrap(),
+        1
+    );
+    assert_eq!(dead_letters.count(), 0);
+    assert_eq!(state.metrics.post_processing_dead_letters.get(), 0);
+}
+
+#[tokio::test]
+async fn test_owner_objects_at_checkpoint() {
+    let (committee, _, authority_key, store) = init_state_parameters();
+    let dir = tempfile::tempdir().unwrap();
+    let state = AuthorityState::new(
+        committee,
+        *authority_key.public_key_bytes(),
+        Arc::pin(authority_key),
+        store,
+        Some(Arc::new(IndexStore::open(dir.path().join("indexes"), None))),
+        None,
+        None,
+        &sui_config::genesis::Genesis::get_default_genesis(),
+        &prometheus::Registry::new(),
+    )
+    .await;
+    let (sender, sender_key) = get_key_pair();
+    let recipient = dbg_addr(2);
+    let object_id = ObjectID::random();
+    let gas_object_id = ObjectID::random();
+    for id in [object_id, gas_object_id] {
+        let object = Object::with_id_owner_for_testing(id, sender);
+        state.insert_genesis_object(object).await;
+    }
+    let object = state.get_object(&object_id).await.unwrap().unwrap();
+    let gas_object = state.get_object(&gas_object_id).await.unwrap().unwrap();
+    let transaction = init_transfer_transaction(
+        sender,
+        &sender_key,
+        recipient,
+        object.compute_object_reference(),
+        gas_object.compute_object_reference(),
+    );
+    let effects = send_and_confirm_transaction(&state, transaction)
+        .await
+        .unwrap()
+        .signed_effects
+        .unwrap()
+        .effects;
+    let next = state.database.next_sequence_number().unwrap();
+    let (seq, digest) = state
+        .database
+        .transactions_in_seq_range(0, next)
+        .unwrap()
+        .pop()
+        .unwrap();
+    state.process_one_tx(seq, &digest, 1).await.unwrap();
+
+    let owned_at = |owner: SuiAddress, checkpoint| {
+        state
+            .get_owner_objects_at_checkpoint(Owner::AddressOwner(owner), checkpoint)
+            .unwrap()
+            .into_iter()
+            .map(|((id, version, _), _)| (id, version))
+            .collect::<BTreeSet<_>>()
+    };
+    let genesis_owned = BTreeSet::from([
+        (object_id, object.version()),
+        (gas_object_id, gas_object.version()),
+    ]);
+
+    // The transfer isn't checkpointed yet, so the objects are read as of genesis.
+    assert_eq!(owned_at(sender, 1), genesis_owned);
+    assert!(owned_at(recipient, 1).is_empty());
+
+    state
+        .index_checkpoint_contents(
+            1,
+            &CheckpointContents::new(std::iter::once(ExecutionDigests::new(
+                digest,
+                effects.digest(),
+            ))),
+        )
+        .unwrap();
+    assert_eq!(owned_at(sender, 0), genesis_owned);
+    let transferred = state.get_object(&object_id).await.unwrap().unwrap();
+    let gas_object = state.get_object(&gas_object_id).await.unwrap().unwrap();
+    assert_eq!(
+        owned_at(sender, 1),
+        BTreeSet::from([(gas_object_id, gas_object.version())])
+    );
+    assert_eq!(
+        owned_at(recipient, 1),
+        BTreeSet::from([(object_id, transferred.version())])
+    );
+
+    // The version as of a checkpoint is found walking back from the latest one.
+    assert_eq!(
+        state
+            .get_parent_entry_at_checkpoint(object_id, 0)
+            .unwrap()
+            .map(|((_, version, _), _)| version),
+        Some(object.version())
+    );
+}
+
+#[tokio::test]
+async fn test_prune_object_versions_in_passes() {
+    let (_, _, _, store) = init_state_parameters();
+    let (owner, _) = get_key_pair();
+    let mut ids: Vec<_> = (0..3).map(|_| ObjectID::random()).collect();
+    ids.sort();
+    for id in &ids {
+        for version in 1..=4 {
+            let object = Object::with_id_owner_version_for_testing(
+                *id,
+                SequenceNumber::from(version),
+                owner,
+            );
+            store
+                .insert_object_direct(object.compute_object_reference(), &object)
+                .await
+                .unwrap();
+        }
+    }
+    let stored_versions = || {
+        ids.iter()
+            .map(|id| {
+                (1..=4)
+                    .filter(|version| {
+                        store
+                            .get_object_by_key(id, SequenceNumber::from(*version))
+                            .unwrap()
+                            .is_some()
+                    })
+                    .count()
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let no_floor = || -> SuiResult<_> { Ok(HashMap::new()) };
+
+    // A pass goes through two objects, keeping their latest version and the one before.
+    assert_eq!(
+        store
+            .prune_object_versions(|_| Some(1), no_floor, 2, || false)
+            .unwrap(),
+        4
+    );
+    assert_eq!(stored_versions(), vec![2, 2, 4]);
+    // The next one resumes from the third object.
+    assert_eq!(
+        store
+            .prune_object_versions(|_| Some(1), no_floor, 2, || false)
+            .unwrap(),
+        2
+    );
+    assert_eq!(stored_versions(), vec![2, 2, 2]);
+
+    // A pass stopped right away deletes nothing, and the next starts over from the first.
+    assert_eq!(
+        store
+            .prune_object_versions(|_| Some(0), no_floor, 3, || true)
+            .unwrap(),
+        0
+    );
+    assert_eq!(
+        store
+            .prune_object_versions(|_| Some(0), no_floor, 1, || false)
+            .unwrap(),
+        1
+    );
+    assert_eq!(stored_versions(), vec![1, 2, 2]);
+    // Objects given no depth keep all their versions.
+    assert_eq!(
+        store
+            .prune_object_versions(|_| None, no_floor, 3, || false)
+            .unwrap(),
+        0
+    );
+    assert_eq!(stored_versions(), vec![1, 2, 2]);
+
+    // Versions from the one an object is kept from on are not deleted either.
+    let floor = || Ok(HashMap::from([(ids[1], SequenceNumber::from(3))]));
+    assert_eq!(
+        store
+            .prune_object_versions(|_| Some(0), floor, 3, || false)
+            .unwrap(),
+        1
+    );
+    assert_eq!(stored_versions(), vec![1, 2, 1]);
+}
+
+#[tokio::test]
+async fn test_get_owner_objects_by_type() {
+    let (sender, _sender_key) = get_key_pair();
+    let gas_object1 = Object::with_id_owner_for_testing(ObjectID::random(), sender);
+    let gas_object2 = Object::with_id_owner_for_testing(ObjectID::random(), sender);
+    let gas_type = gas_object1.data.type_().unwrap().to_string();
+    let authority_state = init_state_with_objects(vec![gas_object1, gas_object2]).await;
+    let owner = Owner::AddressOwner(sender);
+    let by_type = |filter: SuiObjectTypeFilter| {
+        authority_state
+            .get_owner_objects_by_type(owner, &filter)
+            .unwrap()
+    };
+
+    let exact = by_type(SuiObjectTypeFilter::Exact(gas_type.clone()));
+    assert_eq!(exact.len(), 2);
+    assert!(exact.iter().all(|info| info.type_ == gas_type));
+
+    // Neither a type that is a prefix of the type of the objects, nor one they are a prefix of.
+    for type_ in ["0x2::coin::Coin", "0x2::devnet_nft::DevNetNFT"] {
+        assert!(by_type(SuiObjectTypeFilter::Exact(type_.to_string())).is_empty());
+    }
+    assert!(by_type(SuiObjectTypeFilter::Exact(format!("{gas_type}>"))).is_empty());
+
+    for prefix in ["0x2::coin::Coin", "0x2::coin::Coin<", gas_type.as_str()] {
+        assert_eq!(
+            by_type(SuiObjectTypeFilter::Prefix(prefix.to_string())),
+            exact
+        );
+    }
+    assert!(by_type(SuiObjectTypeFilter::Prefix("0x2::devnet_nft".to_string())).is_empty());
+
+    // The empty prefix matches every object, like the unfiltered query.
+    let mut all = by_type(SuiObjectTypeFilter::Prefix(String::new()));
+    all.sort_by_key(|info| info.object_id);
+    assert_eq!(all, authority_state.get_owner_objects(owner).unwrap());
+    // The objects of other owners aren't included.
+    let (other, _) = get_key_pair();
+    assert!(authority_state
+        .get_owner_objects_by_type(
+            Owner::AddressOwner(other),
+            &SuiObjectTypeFilter::Prefix(String::new())
+        )
+        .unwrap()
+        .is_empty());
+}
+
 // helpers
 
 #[cfg(test)]
@@ -1686,8 +2731,8 @@ fn init_certified_transaction(
     let vote = SignedTransaction::new(
         0,
         transaction.clone(),
-        authority_state.name,
-        &*authority_state.secret,
+        authority_state.name(),
+        &*authority_state.secret(),
     );
     let committee = authority_state.committee.load();
     let mut builder = SignatureAggregator::try_new(transaction, &committee).unwrap();
@@ -1797,6 +2842,16 @@ async fn shared_object() {
     };
 
     let authority = init_state_with_objects(vec![gas_object, shared_object]).await;
+    assert_eq!(
+        authority
+            .get_shared_object_version(shared_object_id)
+            .unwrap(),
+        (OBJECT_START_VERSION, false)
+    );
+    assert!(matches!(
+        authority.get_shared_object_version(gas_object_id),
+        Err(SuiError::NotSharedObject { .. })
+    ));
 
     // Make a sample transaction.
     let module = "object_basics";
@@ -1856,6 +2911,12 @@ async fn shared_object() {
         .unwrap()[0]
         .unwrap();
     assert_eq!(shared_object_version, OBJECT_START_VERSION);
+    assert_eq!(
+        authority
+            .get_shared_object_version(shared_object_id)
+            .unwrap(),
+        (OBJECT_START_VERSION, true)
+    );
 
     // Finally process the certificate and execute the contract. Ensure that the
     // shared object lock is cleaned up and that its sequence number increased.
@@ -1877,4 +2938,10 @@ async fn shared_object() {
         .unwrap()
         .version();
     assert_eq!(shared_object_version, SequenceNumber::from(2));
+    assert_eq!(
+        authority
+            .get_shared_object_version(shared_object_id)
+            .unwrap(),
+        (SequenceNumber::from(2), false)
+    );
 }
@@ -197,6 +197,46 @@ async fn test_batch_manager_happy_path() {
     _join.await.expect("No errors in task").expect("ok");
 }
 
+#[tokio::test]
+async fn test_batch_manager_shutdown() {
+    let dir = env::temp_dir();
+    let path = dir.join(format!("DB_{:?}", ObjectID::random()));
+    fs::create_dir(&path).unwrap();
+    let store = Arc::new(AuthorityStore::open(&path, None));
+
+    let seed = [1u8; 32];
+    let (committee, _, authority_key) =
+        init_state_parameters_from_rng(&mut StdRng::from_seed(seed));
+    let authority_state = Arc::new(init_state(committee, authority_key, store.clone()).await);
+
+    // Batches are never full nor old enough to be made before the shutdown.
+    let inner_state = authority_state.clone();
+    let join = tokio::task::spawn(async move {
+        inner_state
+            .run_batch_service(1000, Duration::from_secs(6000))
+            .await
+    });
+
+    let mut rx = authority_state.subscribe_batch();
+    {
+        let t0 = &authority_state.batch_notifier.ticket().expect("ok");
+        store.side_sequence(t0.seq(), &ExecutionDigests::random());
+    }
+    assert!(matches!(
+        rx.recv().await.unwrap(),
+        UpdateItem::Transaction((0, _))
+    ));
+
+    // The pending transaction is batched before the service stops.
+    authority_state.begin_shutdown();
+    assert!(matches!(
+        rx.recv().await.unwrap(),
+        UpdateItem::Batch(batch) if batch.batch.next_sequence_number == 1
+    ));
+    join.await.expect("No errors in task").expect("ok");
+    authority_state.flush_stores().unwrap();
+}
+
 #[tokio::test]
 async fn test_batch_manager_out_of_order() {
     // Create a random directory to store the DB
@@ -489,6 +529,79 @@ async fn test_batch_store_retrieval() {
     _join.await.expect("No errors in task").expect("ok");
 }
 
+#[tokio::test]
+async fn test_batch_history_unavailable() {
+    // Create a random directory to store the DB
+    let dir = env::temp_dir();
+    let path = dir.join(format!("DB_{:?}", ObjectID::random()));
+    fs::create_dir(&path).unwrap();
+
+    // Create an authority
+    let store = Arc::new(AuthorityStore::open(&path, None));
+
+    // Make a test key pair
+    let seed = [1u8; 32];
+    let (committee, _, authority_key) =
+        init_state_parameters_from_rng(&mut StdRng::from_seed(seed));
+    let authority_state = Arc::new(init_state(committee, authority_key, store.clone()).await);
+
+    let inner_state = authority_state.clone();
+    let _join = tokio::task::spawn(async move {
+        inner_state
+            .run_batch_service(10, Duration::from_secs(6000))
+            .await
+    });
+
+    let tx_zero = ExecutionDigests::random();
+    for _i in 0u64..30 {
+        let t0 = &authority_state.batch_notifier.ticket().expect("ok");
+        store
+            .executed_sequence
+            .insert(&t0.seq(), &tx_zero)
+            .expect("Failed to write.");
+    }
+    while store.batches.get(&30).unwrap().is_none() {
+        tokio::task::yield_now().await;
+    }
+
+    // Nothing is retained before the zero batch.
+    assert_eq!(store.batch_history_start().unwrap(), 0);
+
+    // Drop the oldest batches, as pruning would.
+    store.batches.remove(&0).unwrap();
+    store.batches.remove(&10).unwrap();
+    assert_eq!(store.batch_history_start().unwrap(), 20);
+
+    let request = |start| BatchInfoRequest {
+        start: Some(start),
+        length: 5,
+    };
+    assert!(matches!(
+        authority_state.handle_batch_info_request(request(15)).await,
+        Err(SuiError::BatchHistoryUnavailable {
+            requested: 15,
+            retained_from: 20,
+        })
+    ));
+    assert!(matches!(
+        authority_state.handle_batch_streaming(request(15)).await,
+        Err(SuiError::BatchHistoryUnavailable { .. })
+    ));
+
+    // Resuming from within the retained history replays it.
+    let (items, _) = authority_state
+        .handle_batch_info_request(request(20))
+        .await
+        .expect("Retrieval failed!");
+    assert!(items
+        .iter()
+        .any(|item| matches!(item, UpdateItem::Transaction((22, _)))));
+
+    // When we close the sending channel we also also end the service task
+    authority_state.batch_notifier.close();
+    _join.await.expect("No errors in task").expect("ok");
+}
+
 #[derive(Clone)]
 struct TrustworthyAuthorityClient(Arc<Mutex<AuthorityState>>);
 
@@ -572,8 +685,8 @@ impl AuthorityAPI for TrustworthyAuthorityClient {
         &self,
         request: BatchInfoRequest,
     ) -> Result<BatchInfoResponseItemStream, SuiError> {
-        let secret = self.0.lock().await.secret.clone();
-        let name = self.0.lock().await.name;
+        let secret = self.0.lock().await.secret();
+        let name = self.0.lock().await.name();
         let batch_size = 3;
 
         let mut items = Vec::new();
@@ -699,8 +812,8 @@ impl AuthorityAPI for ByzantineAuthorityClient {
         &self,
         request: BatchInfoRequest,
     ) -> Result<BatchInfoResponseItemStream, SuiError> {
-        let secret = self.0.lock().await.secret.clone();
-        let name = self.0.lock().await.name;
+        let secret = self.0.lock().await.secret();
+        let name = self.0.lock().await.name();
         let batch_size = 3;
 
         let mut items = Vec::new();
@@ -0,0 +1,139 @@
+// Copyright (c) 2022, Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::authority::authority_tests::{
+    init_state_with_ids, init_transfer_transaction, send_and_confirm_transaction,
+};
+use axum::{http::StatusCode, routing::post, Extension, Json, Router};
+use parking_lot::Mutex;
+use std::net::TcpListener;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use sui_types::base_types::{dbg_addr, ObjectID};
+use sui_types::crypto::get_key_pair;
+
+/// A local endpoint recording the sequence numbers of the batches it accepted.
+#[derive(Default)]
+struct Endpoint {
+    failing: AtomicBool,
+    failed_requests: AtomicUsize,
+    batches: Mutex<Vec<Vec<TxSequenceNumber>>>,
+}
+
+async fn push(
+    Extension(endpoint): Extension<Arc<Endpoint>>,
+    Json(batch): Json<Vec<serde_json::Value>>,
+) -> StatusCode {
+    if endpoint.failing.load(Ordering::SeqCst) {
+        endpoint.failed_requests.fetch_add(1, Ordering::SeqCst);
+        return StatusCode::INTERNAL_SERVER_ERROR;
+    }
+    let batch = batch
+        .iter()
+        .map(|summary| summary["sequenceNumber"].as_u64().unwrap())
+        .collect();
+    endpoint.batches.lock().push(batch);
+    StatusCode::OK
+}
+
+fn serve(endpoint: Arc<Endpoint>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let app = Router::new()
+        .route("/", post(push))
+        .layer(Extension(endpoint));
+    tokio::spawn(
+        axum::Server::from_tcp(listener)
+            .unwrap()
+            .serve(app.into_make_service()),
+    );
+    format!("http://{address}/")
+}
+
+async fn wait_until(condition: impl Fn() -> bool) {
+    tokio::time::timeout(Duration::from_secs(30), async {
+        while !condition() {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_certificate_pusher() {
+    let (sender, sender_key) = get_key_pair();
+    let object_ids = [ObjectID::random(), ObjectID::random(), ObjectID::random()];
+    let gas_object_id = ObjectID::random();
+    let state = Arc::new(
+        init_state_with_ids(vec![
+            (sender, object_ids[0]),
+            (sender, object_ids[1]),
+            (sender, object_ids[2]),
+            (sender, gas_object_id),
+        ])
+        .await,
+    );
+    let transfer = |object_id| {
+        let state = state.clone();
+        let sender_key = &sender_key;
+        async move {
+            let object = state.get_object(&object_id).await.unwrap().unwrap();
+            let gas_object = state.get_object(&gas_object_id).await.unwrap().unwrap();
+            let transaction = init_transfer_transaction(
+                sender,
+                sender_key,
+                dbg_addr(2),
+                object.compute_object_reference(),
+                gas_object.compute_object_reference(),
+            );
+            send_and_confirm_transaction(&state, transaction)
+                .await
+                .unwrap();
+        }
+    };
+
+    let endpoint = Arc::new(Endpoint::default());
+    endpoint.failing.store(true, Ordering::SeqCst);
+    let config = CertificatePushConfig {
+        endpoint: serve(endpoint.clone()),
+        buffer_size: 10,
+        max_batch_size: 1,
+    };
+    let dir = tempfile::tempdir().unwrap();
+
+    let pusher = CertificatePusher::open(dir.path(), state.clone(), &config).unwrap();
+    let cursor = pusher.cursor.clone();
+    let handle = tokio::spawn(pusher.run());
+    // The first run starts from the tip.
+    wait_until(|| cursor.get(&CURSOR).unwrap().is_some()).await;
+    let start = cursor.get(&CURSOR).unwrap().unwrap();
+
+    transfer(object_ids[0]).await;
+    transfer(object_ids[1]).await;
+
+    // The cursor stays put while the endpoint fails.
+    wait_until(|| endpoint.failed_requests.load(Ordering::SeqCst) >= 2).await;
+    assert_eq!(cursor.get(&CURSOR).unwrap(), Some(start));
+    assert!(endpoint.batches.lock().is_empty());
+
+    // Once it recovers the batches arrive in order, and the cursor follows them.
+    endpoint.failing.store(false, Ordering::SeqCst);
+    wait_until(|| cursor.get(&CURSOR).unwrap() == Some(start + 2)).await;
+    assert_eq!(*endpoint.batches.lock(), vec![vec![start], vec![start + 1]]);
+
+    handle.abort();
+    assert!(handle.await.unwrap_err().is_cancelled());
+    drop(cursor);
+
+    // Delivery resumes from the saved cursor, without replaying what was delivered.
+    transfer(object_ids[2]).await;
+    let pusher = CertificatePusher::open(dir.path(), state.clone(), &config).unwrap();
+    let cursor = pusher.cursor.clone();
+    let _handle = tokio::spawn(pusher.run());
+    wait_until(|| cursor.get(&CURSOR).unwrap() == Some(start + 3)).await;
+    assert_eq!(
+        *endpoint.batches.lock(),
+        vec![vec![start], vec![start + 1], vec![start + 2]]
+    );
+}
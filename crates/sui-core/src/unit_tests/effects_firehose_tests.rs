@@ -0,0 +1,72 @@
+// Copyright (c) 2022, Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::authority::authority_tests::{
+    init_state_with_ids, init_transfer_transaction, send_and_confirm_transaction,
+};
+use futures::StreamExt;
+use sui_types::base_types::{dbg_addr, ObjectID};
+use sui_types::crypto::get_key_pair;
+
+#[tokio::test]
+async fn test_effects_firehose() {
+    let (sender, sender_key) = get_key_pair();
+    let object_ids = [ObjectID::random(), ObjectID::random()];
+    let gas_object_id = ObjectID::random();
+    let state = Arc::new(
+        init_state_with_ids(vec![
+            (sender, object_ids[0]),
+            (sender, object_ids[1]),
+            (sender, gas_object_id),
+        ])
+        .await,
+    );
+    let start = state.db().next_sequence_number().unwrap();
+
+    let transfer = |object_id| {
+        let state = state.clone();
+        let sender_key = &sender_key;
+        async move {
+            let object = state.get_object(&object_id).await.unwrap().unwrap();
+            let gas_object = state.get_object(&gas_object_id).await.unwrap().unwrap();
+            let transaction = init_transfer_transaction(
+                sender,
+                sender_key,
+                dbg_addr(2),
+                object.compute_object_reference(),
+                gas_object.compute_object_reference(),
+            );
+            let info = send_and_confirm_transaction(&state, transaction)
+                .await
+                .unwrap();
+            info.signed_effects.unwrap().effects
+        }
+    };
+    let effects = transfer(object_ids[0]).await;
+
+    let service = EffectsFirehoseService::new(state.clone());
+    let request = |start| tonic::Request::new(EffectsFirehoseRequest { start });
+    let mut stream = service.effects(request(start)).await.unwrap().into_inner();
+    let item = stream.next().await.unwrap().unwrap();
+    assert_eq!(item.sequence, start);
+    assert_eq!(item.digest, effects.transaction_digest);
+    assert_eq!(item.effects, effects);
+
+    // Transactions executed once the stream caught up are streamed as well.
+    let next = tokio::spawn(async move { stream.next().await.unwrap().unwrap() });
+    let effects = transfer(object_ids[1]).await;
+    let item = next.await.unwrap();
+    assert_eq!(item.sequence, start + 1);
+    assert_eq!(item.effects, effects);
+
+    // Resuming after the first transaction.
+    let mut stream = service
+        .effects(request(start + 1))
+        .await
+        .unwrap()
+        .into_inner();
+    let item = stream.next().await.unwrap().unwrap();
+    assert_eq!(item.sequence, start + 1);
+    assert_eq!(item.digest, effects.transaction_digest);
+}
@@ -99,6 +99,125 @@ async fn test_public_transfer_object() {
     assert_eq!(gateway.get_total_transaction_number().unwrap(), 1);
 }
 
+#[tokio::test]
+async fn test_duplicate_transaction() {
+    let (addr1, key1) = get_key_pair();
+    let (addr2, _key2) = get_key_pair();
+
+    let coin_object = Object::with_owner_for_testing(addr1);
+    let gas_object = Object::with_owner_for_testing(addr1);
+
+    let genesis_objects =
+        authority_genesis_objects(4, vec![coin_object.clone(), gas_object.clone()]);
+    let gateway = create_gateway_state(genesis_objects).await;
+
+    let data = gateway
+        .public_transfer_object(
+            addr1,
+            coin_object.id(),
+            Some(gas_object.id()),
+            GAS_VALUE_FOR_TESTING / 10,
+            addr2,
+        )
+        .await
+        .unwrap();
+    let signature = key1.sign(&data.to_bytes());
+    let transaction = Transaction::new(data, signature);
+
+    // Submitted while the first one executes.
+    let (first, second) = futures::join!(
+        gateway.execute_transaction(transaction.clone()),
+        gateway.execute_transaction(transaction.clone())
+    );
+    let effects = first.unwrap().to_effect_response().unwrap().effects;
+    assert_eq!(
+        second.unwrap().to_effect_response().unwrap().effects,
+        effects
+    );
+
+    // Submitted again afterwards.
+    let retried = gateway.execute_transaction(transaction).await.unwrap();
+    assert_eq!(retried.to_effect_response().unwrap().effects, effects);
+    assert_eq!(gateway.get_total_transaction_number().unwrap(), 1);
+}
+
+#[tokio::test]
+async fn test_version_conflict() {
+    let (addr1, key1) = get_key_pair();
+
+    let coin_object = Object::with_owner_for_testing(addr1);
+    let gas_object = Object::with_owner_for_testing(addr1);
+
+    let genesis_objects =
+        authority_genesis_objects(4, vec![coin_object.clone(), gas_object.clone()]);
+    let gateway = create_gateway_state(genesis_objects).await;
+
+    public_transfer_object(
+        &gateway,
+        addr1,
+        &key1,
+        coin_object.id(),
+        gas_object.id(),
+        addr1,
+    )
+    .await
+    .unwrap();
+    let coin_version = gateway
+        .store()
+        .get_object(&coin_object.id())
+        .unwrap()
+        .unwrap()
+        .version();
+    let gas_ref = gateway
+        .store()
+        .get_object(&gas_object.id())
+        .unwrap()
+        .unwrap()
+        .compute_object_reference();
+
+    // Transfer the coin again from its genesis version.
+    let stale_transfer = || {
+        let data = TransactionData::new_transfer(
+            addr1,
+            coin_object.compute_object_reference(),
+            addr1,
+            gas_ref,
+            GAS_VALUE_FOR_TESTING / 10,
+        );
+        let signature = key1.sign(&data.to_bytes());
+        Transaction::new(data, signature)
+    };
+    let error = gateway
+        .execute_transaction(stale_transfer())
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error.downcast_ref::<SuiError>(),
+        Some(&SuiError::ObjectVersionConflict {
+            object_id: coin_object.id(),
+            given_version: coin_object.version(),
+            current_version: None,
+        })
+    );
+
+    let gateway = gateway.with_current_object_versions(true);
+    let error = gateway
+        .execute_transaction(stale_transfer())
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error.downcast_ref::<SuiError>(),
+        Some(&SuiError::ObjectVersionConflict {
+            object_id: coin_object.id(),
+            given_version: coin_object.version(),
+            current_version: Some(coin_version),
+        })
+    );
+    assert!(error
+        .to_string()
+        .ends_with(&format!("current is {}", coin_version.value())));
+}
+
 #[tokio::test]
 async fn test_move_call() {
     let (addr1, key1) = get_key_pair();
@@ -13,7 +13,7 @@ use std::sync::Arc;
 use sui_types::{
     base_types::{dbg_addr, dbg_object_id, ExecutionDigests},
     batch::UpdateItem,
-    object::ObjectFormatOptions,
+    object::{Object, ObjectFormatOptions},
 };
 
 use crate::safe_client::SafeClient;
@@ -303,7 +303,7 @@ async fn test_subscription_safe_client() {
             fault_config: LocalAuthorityClientFaultConfig::default(),
         },
         state.clone_committee(),
-        state.name,
+        state.name(),
     );
 
     let _join = server
@@ -479,3 +479,26 @@ async fn test_subscription_safe_client() {
 
     server.state.batch_notifier.close();
 }
+
+#[test]
+fn test_check_transaction_size() {
+    let sender = dbg_addr(1);
+    let gas = Object::with_id_owner_for_testing(dbg_object_id(1), sender);
+    let data = TransactionData::new_transfer_sui(
+        dbg_addr(2),
+        sender,
+        None,
+        gas.compute_object_reference(),
+        1000,
+    );
+    let size = bcs::serialized_size(&data).unwrap();
+
+    assert!(check_transaction_size(&data, size).is_ok());
+    assert_eq!(
+        check_transaction_size(&data, size - 1),
+        Err(SuiError::TransactionTooLarge {
+            size,
+            limit: size - 1
+        })
+    );
+}
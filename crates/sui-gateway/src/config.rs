@@ -69,12 +69,10 @@ impl GatewayType {
                 let committee = config.make_committee()?;
                 let authority_clients = config.make_authority_clients();
                 let metrics = GatewayMetrics::new(&prometheus::Registry::new());
-                Arc::new(GatewayState::new(
-                    path,
-                    committee,
-                    authority_clients,
-                    metrics,
-                )?)
+                Arc::new(
+                    GatewayState::new(path, committee, authority_clients, metrics)?
+                        .with_current_object_versions(config.report_current_object_versions),
+                )
             }
             GatewayType::RPC(url) => Arc::new(RpcGatewayClient::new(url.clone())?),
         })
@@ -89,6 +87,10 @@ pub struct GatewayConfig {
     pub recv_timeout: Duration,
     pub buffer_size: usize,
     pub db_folder_path: PathBuf,
+    /// Tell clients passing a stale version of an input object which version is current, so
+    /// that they can retry with it. Off by default, as it reveals object versions.
+    #[serde(default)]
+    pub report_current_object_versions: bool,
 }
 
 impl Config for GatewayConfig {}
@@ -126,6 +128,7 @@ impl Default for GatewayConfig {
             recv_timeout: Duration::from_micros(4000000),
             buffer_size: 650000,
             db_folder_path: Default::default(),
+            report_current_object_versions: false,
         }
     }
 }
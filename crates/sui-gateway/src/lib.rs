@@ -24,10 +24,13 @@ pub fn create_client(
     })?;
     let committee = config.make_committee()?;
     let authority_clients = config.make_authority_clients();
-    Ok(Arc::new(GatewayState::new(
-        config.db_folder_path,
-        committee,
-        authority_clients,
-        gateway_metrics,
-    )?))
+    Ok(Arc::new(
+        GatewayState::new(
+            config.db_folder_path,
+            committee,
+            authority_clients,
+            gateway_metrics,
+        )?
+        .with_current_object_versions(config.report_current_object_versions),
+    ))
 }
@@ -3,21 +3,23 @@
 
 use anyhow::Error;
 use async_trait::async_trait;
+use move_core_types::language_storage::StructTag;
 use tokio::runtime::Handle;
 
 use sui_core::gateway_state::{GatewayAPI, GatewayTxSeqNumber};
 use sui_json::SuiJsonValue;
 use sui_json_rpc_api::client::SuiRpcClient;
 use sui_json_rpc_api::rpc_types::{
-    GetObjectDataResponse, GetRawObjectDataResponse, RPCTransactionRequestParams, SuiObjectInfo,
-    SuiTypeTag, TransactionEffectsResponse, TransactionResponse,
+    GetObjectDataResponse, GetRawObjectDataResponse, RPCTransactionRequestParams, SuiCoinBalance,
+    SuiCoinPage, SuiObjectInfo, SuiProtocolConfig, SuiTransactionBlockOptions,
+    SuiTransactionBlockResponse, SuiTypeTag, TransactionEffectsResponse, TransactionResponse,
 };
 use sui_json_rpc_api::QuorumDriverApiClient;
 use sui_json_rpc_api::RpcBcsApiClient;
 use sui_json_rpc_api::RpcTransactionBuilderClient;
 use sui_json_rpc_api::TransactionBytes;
 use sui_json_rpc_api::WalletSyncApiClient;
-use sui_types::base_types::{ObjectID, SuiAddress, TransactionDigest};
+use sui_types::base_types::{ObjectID, ObjectRef, SequenceNumber, SuiAddress, TransactionDigest};
 use sui_types::messages::{Transaction, TransactionData};
 use sui_types::sui_serde::Base64;
 pub struct RpcGatewayClient {
@@ -182,7 +184,19 @@ impl GatewayAPI for RpcGatewayClient {
     }
 
     async fn get_object(&self, object_id: ObjectID) -> Result<GetObjectDataResponse, Error> {
-        Ok(self.client.read_api().get_object(object_id).await?)
+        Ok(self.client.read_api().get_object(object_id, None).await?)
+    }
+
+    async fn get_object_at_version(
+        &self,
+        object_id: ObjectID,
+        version: SequenceNumber,
+    ) -> Result<GetObjectDataResponse, Error> {
+        Ok(self
+            .client
+            .read_api()
+            .get_object_at_version(object_id, version)
+            .await?)
     }
 
     async fn get_raw_object(&self, object_id: ObjectID) -> Result<GetRawObjectDataResponse, Error> {
@@ -196,7 +210,7 @@ impl GatewayAPI for RpcGatewayClient {
         Ok(self
             .client
             .read_api()
-            .get_objects_owned_by_address(address)
+            .get_objects_owned_by_address(address, None)
             .await?)
     }
 
@@ -207,10 +221,14 @@ impl GatewayAPI for RpcGatewayClient {
         Ok(self
             .client
             .read_api()
-            .get_objects_owned_by_object(object_id)
+            .get_objects_owned_by_object(object_id, None)
             .await?)
     }
 
+    async fn get_protocol_config(&self) -> Result<SuiProtocolConfig, Error> {
+        Ok(self.client.read_api().get_protocol_config().await?)
+    }
+
     fn get_total_transaction_number(&self) -> Result<u64, Error> {
         let handle = Handle::current();
         let _ = handle.enter();
@@ -246,6 +264,85 @@ impl GatewayAPI for RpcGatewayClient {
         &self,
         digest: TransactionDigest,
     ) -> Result<TransactionEffectsResponse, Error> {
-        Ok(self.client.read_api().get_transaction(digest).await?)
+        Ok(self.client.read_api().get_transaction(digest, None).await?)
+    }
+
+    async fn get_transaction_block(
+        &self,
+        digest: TransactionDigest,
+        options: SuiTransactionBlockOptions,
+    ) -> Result<SuiTransactionBlockResponse, Error> {
+        Ok(self
+            .client
+            .read_api()
+            .get_transaction_block(digest, Some(options), None)
+            .await?)
+    }
+
+    async fn get_transaction_dependencies(
+        &self,
+        digest: TransactionDigest,
+        depth: u64,
+    ) -> Result<Vec<(TransactionDigest, Vec<TransactionDigest>)>, Error> {
+        Ok(self
+            .client
+            .read_api()
+            .get_transaction_dependencies(digest, Some(depth))
+            .await?)
+    }
+
+    async fn select_gas_coins(
+        &self,
+        address: SuiAddress,
+        budget: u64,
+    ) -> Result<Vec<ObjectRef>, Error> {
+        Ok(self
+            .client
+            .read_api()
+            .select_gas_coins(address, budget, None)
+            .await?
+            .into_iter()
+            .map(|coin| coin.to_object_ref())
+            .collect())
+    }
+
+    async fn get_balances(
+        &self,
+        address: SuiAddress,
+        coin_type: Option<StructTag>,
+    ) -> Result<Vec<SuiCoinBalance>, Error> {
+        let read_api = self.client.read_api();
+        Ok(match coin_type {
+            Some(coin_type) => vec![
+                read_api
+                    .get_balance(address, coin_type.to_string(), None)
+                    .await?,
+            ],
+            None => read_api.get_all_balances(address, None).await?,
+        })
+    }
+
+    async fn get_coins(
+        &self,
+        address: SuiAddress,
+        coin_type: StructTag,
+        largest_first: bool,
+        exclude_locked: bool,
+        cursor: Option<ObjectID>,
+        limit: usize,
+    ) -> Result<SuiCoinPage, Error> {
+        Ok(self
+            .client
+            .read_api()
+            .get_coins(
+                address,
+                coin_type.to_string(),
+                largest_first,
+                cursor,
+                Some(exclude_locked),
+                Some(limit as u64),
+                None,
+            )
+            .await?)
     }
 }
@@ -5,8 +5,11 @@ use crate::rpc_types::SuiEventEnvelope;
 use crate::rpc_types::SuiEventFilter;
 use crate::rpc_types::{
     GetObjectDataResponse, GetRawObjectDataResponse, RPCTransactionRequestParams,
-    SuiInputObjectKind, SuiObjectInfo, SuiObjectRef, SuiTypeTag, TransactionEffectsResponse,
-    TransactionResponse,
+    SuiCheckpointSignatures, SuiCoinBalance, SuiCoinPage, SuiCoinSupply, SuiEffectsVerification,
+    SuiInputObjectKind, SuiObjectInfo, SuiObjectRef, SuiObjectTypeCount, SuiObjectTypeFilter,
+    SuiProtocolConfig, SuiPublishedPackagesPage, SuiRetentionWatermarks, SuiSharedObjectVersion,
+    SuiStakeDistribution, SuiTransactionBlockOptions, SuiTransactionBlockResponse, SuiTypeTag,
+    SuiValidatorCheckpoint, TransactionEffectsResponse, TransactionResponse,
 };
 use jsonrpsee::core::RpcResult;
 use jsonrpsee_proc_macros::rpc;
@@ -18,9 +21,10 @@ use sui_open_rpc::Module;
 use sui_open_rpc_macros::open_rpc;
 use sui_types::sui_serde::Base64;
 use sui_types::{
-    base_types::{ObjectID, SuiAddress, TransactionDigest},
+    base_types::{ExecutionDigests, ObjectID, SequenceNumber, SuiAddress, TransactionDigest},
     crypto::SignableBytes,
     messages::TransactionData,
+    messages_checkpoint::CheckpointSequenceNumber,
 };
 
 pub mod client;
@@ -52,17 +56,32 @@ pub trait WalletSyncApi {
 #[open_rpc(namespace = "sui", tag = "Read API")]
 #[rpc(server, client, namespace = "sui")]
 pub trait RpcReadApi {
-    /// Return the list of objects owned by an address.
+    /// Return the list of objects owned by an address. Fails with a retryable error if this node
+    /// hasn't synced `min_checkpoint` yet.
     #[method(name = "getObjectsOwnedByAddress")]
     async fn get_objects_owned_by_address(
         &self,
         address: SuiAddress,
+        min_checkpoint: Option<CheckpointSequenceNumber>,
     ) -> RpcResult<Vec<SuiObjectInfo>>;
 
+    /// Return the list of objects owned by an address whose type matches the type filter.
+    /// Fails with a retryable error if this node hasn't synced `min_checkpoint` yet.
+    #[method(name = "getObjectsOwnedByAddressAndType")]
+    async fn get_objects_owned_by_address_and_type(
+        &self,
+        address: SuiAddress,
+        type_filter: SuiObjectTypeFilter,
+        min_checkpoint: Option<CheckpointSequenceNumber>,
+    ) -> RpcResult<Vec<SuiObjectInfo>>;
+
+    /// Return the list of objects owned by an object. Fails with a retryable error if this node
+    /// hasn't synced `min_checkpoint` yet.
     #[method(name = "getObjectsOwnedByObject")]
     async fn get_objects_owned_by_object(
         &self,
         object_id: ObjectID,
+        min_checkpoint: Option<CheckpointSequenceNumber>,
     ) -> RpcResult<Vec<SuiObjectInfo>>;
 
     #[method(name = "getTotalTransactionNumber")]
@@ -81,15 +100,129 @@ pub trait RpcReadApi {
         count: u64,
     ) -> RpcResult<Vec<(GatewayTxSeqNumber, TransactionDigest)>>;
 
+    /// Return a transaction with its effects. Fails with a retryable error if this node hasn't
+    /// synced `min_checkpoint` yet
     #[method(name = "getTransaction")]
     async fn get_transaction(
         &self,
         digest: TransactionDigest,
+        min_checkpoint: Option<CheckpointSequenceNumber>,
     ) -> RpcResult<TransactionEffectsResponse>;
 
-    /// Return the object information for a specified object
+    /// Return a transaction with the parts of what it did asked for in `options`: its input,
+    /// effects, events, the objects it changed and how it changed the coin balances of their
+    /// owners. Without `options` only the digest and timestamp are returned. Fails with a
+    /// retryable error if this node hasn't synced `min_checkpoint` yet
+    #[method(name = "getTransactionBlock")]
+    async fn get_transaction_block(
+        &self,
+        digest: TransactionDigest,
+        options: Option<SuiTransactionBlockOptions>,
+        min_checkpoint: Option<CheckpointSequenceNumber>,
+    ) -> RpcResult<SuiTransactionBlockResponse>;
+
+    /// Return the transactions a transaction depends on, as recorded in its effects. `depth`
+    /// defaults to 1, only those, and above that their dependencies are expanded too, up to
+    /// `depth` transactions away. Each transaction expanded is listed once with its direct
+    /// dependencies, the requested one first
+    #[method(name = "getTransactionDependencies")]
+    async fn get_transaction_dependencies(
+        &self,
+        digest: TransactionDigest,
+        depth: Option<u64>,
+    ) -> RpcResult<Vec<(TransactionDigest, Vec<TransactionDigest>)>>;
+
+    /// Return SUI coins of an address whose balances add up to at least `budget`, to pay the
+    /// gas of a transaction with. As few coins as possible are picked, largest first, and
+    /// coins locked to a pending transaction are skipped. Fails if the unlocked coins don't
+    /// cover the budget, and with a retryable error if this node hasn't synced `min_checkpoint`
+    /// yet
+    #[method(name = "selectGasCoins")]
+    async fn select_gas_coins(
+        &self,
+        address: SuiAddress,
+        budget: u64,
+        min_checkpoint: Option<CheckpointSequenceNumber>,
+    ) -> RpcResult<Vec<SuiObjectRef>>;
+
+    /// Return the balance of an address in coins of `coin_type`, e.g. `0x2::sui::SUI`, summed
+    /// over the coins it owns as of the latest transaction indexed, and how many there are.
+    /// Fails with a retryable error if this node hasn't synced `min_checkpoint` yet
+    #[method(name = "getBalance")]
+    async fn get_balance(
+        &self,
+        address: SuiAddress,
+        coin_type: String,
+        min_checkpoint: Option<CheckpointSequenceNumber>,
+    ) -> RpcResult<SuiCoinBalance>;
+
+    /// Return a page of the coins of `coin_type` owned by an address, with their balances, to
+    /// build a payment with. Coins are listed largest first with `largest_first`, otherwise by
+    /// object ID, starting from `cursor`, the `nextCursor` of the previous page. Pages have
+    /// `limit` coins, or the default page size of the node. Coins locked to a pending
+    /// transaction are left out with `exclude_locked`. Fails with a retryable error if this
+    /// node hasn't synced `min_checkpoint` yet
+    #[method(name = "getCoins")]
+    async fn get_coins(
+        &self,
+        address: SuiAddress,
+        coin_type: String,
+        largest_first: bool,
+        cursor: Option<ObjectID>,
+        exclude_locked: Option<bool>,
+        limit: Option<u64>,
+        min_checkpoint: Option<CheckpointSequenceNumber>,
+    ) -> RpcResult<SuiCoinPage>;
+
+    /// Return the balance of an address in each coin type it owns coins of, see `getBalance`
+    #[method(name = "getAllBalances")]
+    async fn get_all_balances(
+        &self,
+        address: SuiAddress,
+        min_checkpoint: Option<CheckpointSequenceNumber>,
+    ) -> RpcResult<Vec<SuiCoinBalance>>;
+
+    /// Return, for each claimed pair of transaction and effects digests, whether this node
+    /// executed the transaction with those effects, and the checkpoint it is in if known. At
+    /// most `rpc-max-verification-batch` pairs are checked in a call
+    #[method(name = "verifyEffectsBatch")]
+    async fn verify_effects_batch(
+        &self,
+        claimed: Vec<ExecutionDigests>,
+    ) -> RpcResult<Vec<SuiEffectsVerification>>;
+
+    /// Return the object information for a specified object. Fails with a retryable error if this
+    /// node hasn't synced `min_checkpoint` yet
     #[method(name = "getObject")]
-    async fn get_object(&self, object_id: ObjectID) -> RpcResult<GetObjectDataResponse>;
+    async fn get_object(
+        &self,
+        object_id: ObjectID,
+        min_checkpoint: Option<CheckpointSequenceNumber>,
+    ) -> RpcResult<GetObjectDataResponse>;
+
+    /// Return the object information for a specified object as it was at `version`. Fails if
+    /// that version is no longer stored
+    #[method(name = "getObjectAtVersion")]
+    async fn get_object_at_version(
+        &self,
+        object_id: ObjectID,
+        version: SequenceNumber,
+    ) -> RpcResult<GetObjectDataResponse>;
+
+    /// Return the transaction that produced the current version of an object, or that deleted
+    /// it, along with its effects. Fails with a retryable error if this node hasn't synced
+    /// `min_checkpoint` yet
+    #[method(name = "getLatestModifyingTransaction")]
+    async fn get_latest_modifying_transaction(
+        &self,
+        object_id: ObjectID,
+        min_checkpoint: Option<CheckpointSequenceNumber>,
+    ) -> RpcResult<TransactionEffectsResponse>;
+
+    /// Return the protocol parameters in effect, with the protocol version and the current
+    /// epoch, to validate transactions against before submitting them
+    #[method(name = "getProtocolConfig")]
+    async fn get_protocol_config(&self) -> RpcResult<SuiProtocolConfig>;
 }
 
 #[open_rpc(namespace = "sui", tag = "Full Node API")]
@@ -121,11 +254,85 @@ pub trait RpcFullNodeReadApi {
         addr: SuiAddress,
     ) -> RpcResult<Vec<(GatewayTxSeqNumber, TransactionDigest)>>;
 
+    /// Return the number of transactions sent by `address`, which only grows. Counted from the
+    /// indexes, so transactions executed in the last moments may not be counted yet.
+    #[method(name = "getTransactionCount")]
+    async fn get_transaction_count(&self, address: SuiAddress) -> RpcResult<u64>;
+
     #[method(name = "getTransactionsToAddress")]
     async fn get_transactions_to_addr(
         &self,
         addr: SuiAddress,
     ) -> RpcResult<Vec<(GatewayTxSeqNumber, TransactionDigest)>>;
+
+    /// Return the validators that signed checkpoint `sequence_number` and their stake.
+    #[method(name = "getCheckpointSignatures")]
+    async fn get_checkpoint_signatures(
+        &self,
+        sequence_number: CheckpointSequenceNumber,
+    ) -> RpcResult<SuiCheckpointSignatures>;
+
+    /// Return the current version of a shared object. Fails if the object is not shared.
+    #[method(name = "getSharedObjectVersion")]
+    async fn get_shared_object_version(
+        &self,
+        object_id: ObjectID,
+    ) -> RpcResult<SuiSharedObjectVersion>;
+
+    /// Return the current versions of several shared objects, in the order requested. Fails
+    /// if any of the objects is not shared.
+    #[method(name = "getSharedObjectVersions")]
+    async fn get_shared_object_versions(
+        &self,
+        object_ids: Vec<ObjectID>,
+    ) -> RpcResult<Vec<SuiSharedObjectVersion>>;
+
+    /// Return the total supply of a coin type, e.g. `0x2::sui::SUI`, read from its TreasuryCap.
+    /// Only the caps created by the transactions this node indexed are known, and the supply
+    /// of coins whose cap is wrapped in another object can't be read.
+    #[method(name = "getTotalSupply")]
+    async fn get_total_supply(&self, coin_type: String) -> RpcResult<SuiCoinSupply>;
+
+    /// Return the balance of the storage fund and the stake of each active validator this
+    /// epoch, read from the system state object.
+    #[method(name = "getStakeDistribution")]
+    async fn get_stake_distribution(&self) -> RpcResult<SuiStakeDistribution>;
+
+    /// Return up to `limit` object types with their number of live objects, most common
+    /// first, skipping the `offset` most common ones. Generic types are counted per
+    /// instantiation, e.g. `0x2::coin::Coin<0x2::sui::SUI>`, and packages as `Package`.
+    /// Without `limit`, as many as the default page size of the node.
+    #[method(name = "getObjectTypeStats")]
+    async fn get_object_type_stats(
+        &self,
+        offset: u64,
+        limit: Option<u64>,
+    ) -> RpcResult<Vec<SuiObjectTypeCount>>;
+
+    /// Return the packages published in checkpoint `since_checkpoint` or later, in the order
+    /// this node executed them. Read further pages by passing the `nextCursor` of a page as
+    /// `cursor`, which overrides `since_checkpoint`. Pages have `limit` packages, or the
+    /// default page size of the node. Nodes without a checkpoint store can only list packages
+    /// from checkpoint 0.
+    #[method(name = "getPublishedPackages")]
+    async fn get_published_packages(
+        &self,
+        since_checkpoint: CheckpointSequenceNumber,
+        cursor: Option<GatewayTxSeqNumber>,
+        limit: Option<u64>,
+    ) -> RpcResult<SuiPublishedPackagesPage>;
+
+    /// Return the oldest data this node retains of objects, batches, checkpoints and events.
+    /// Queries for older data find nothing because it was pruned, not because it doesn't
+    /// exist.
+    #[method(name = "getRetentionWatermarks")]
+    async fn get_retention_watermarks(&self) -> RpcResult<SuiRetentionWatermarks>;
+
+    /// Return the latest checkpoint each validator of the committee reported to this node, and
+    /// how many milliseconds ago, to spot lagging or offline validators. Validators the node
+    /// doesn't follow, or that never answered, have neither.
+    #[method(name = "getValidatorCheckpoints")]
+    async fn get_validator_checkpoints(&self) -> RpcResult<Vec<SuiValidatorCheckpoint>>;
 }
 
 #[open_rpc(namespace = "sui", tag = "Transaction Builder API")]
@@ -214,6 +421,14 @@ pub trait RpcBcsApi {
     /// Return the raw BCS serialised move object bytes for a specified object
     #[method(name = "getRawObject")]
     async fn get_raw_object(&self, object_id: ObjectID) -> RpcResult<GetRawObjectDataResponse>;
+
+    /// Return the raw BCS serialised move object bytes for each of the specified objects, in
+    /// the order they were requested. The status of each object says whether it exists.
+    #[method(name = "getRawObjects")]
+    async fn get_raw_objects(
+        &self,
+        object_ids: Vec<ObjectID>,
+    ) -> RpcResult<Vec<GetRawObjectDataResponse>>;
 }
 
 #[serde_as]
@@ -264,7 +479,7 @@ pub trait EventReadApi {
         &self,
         package: ObjectID,
         module: String,
-        count: u64,
+        count: Option<u64>,
         start_time: u64,
         end_time: u64,
     ) -> RpcResult<Vec<SuiEventEnvelope>>;
@@ -273,7 +488,19 @@ pub trait EventReadApi {
     async fn get_events_by_event_type(
         &self,
         event_type: String,
-        count: u64,
+        count: Option<u64>,
+        start_time: u64,
+        end_time: u64,
+    ) -> RpcResult<Vec<SuiEventEnvelope>>;
+
+    /// Return the Move events of a type, or of all the types a module or a package declares,
+    /// within a time range. `move_event_type` is a type like `0x2::devnet_nft::MintNFTEvent`,
+    /// a module like `0x2::devnet_nft` or a package like `0x2`.
+    #[method(name = "getEventsByMoveEventType")]
+    async fn get_events_by_move_event_type(
+        &self,
+        move_event_type: String,
+        count: Option<u64>,
         start_time: u64,
         end_time: u64,
     ) -> RpcResult<Vec<SuiEventEnvelope>>;
@@ -282,7 +509,7 @@ pub trait EventReadApi {
     async fn get_events_by_sender(
         &self,
         sender: SuiAddress,
-        count: u64,
+        count: Option<u64>,
         start_time: u64,
         end_time: u64,
     ) -> RpcResult<Vec<SuiEventEnvelope>>;
@@ -291,7 +518,7 @@ pub trait EventReadApi {
     async fn get_events_by_object(
         &self,
         object: ObjectID,
-        count: u64,
+        count: Option<u64>,
         start_time: u64,
         end_time: u64,
     ) -> RpcResult<Vec<SuiEventEnvelope>>;
@@ -300,7 +527,7 @@ pub trait EventReadApi {
     async fn get_events_by_owner(
         &self,
         owner: SuiAddress,
-        count: u64,
+        count: Option<u64>,
         start_time: u64,
         end_time: u64,
     ) -> RpcResult<Vec<SuiEventEnvelope>>;
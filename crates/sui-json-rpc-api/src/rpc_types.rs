@@ -15,7 +15,7 @@ use itertools::Itertools;
 use move_bytecode_utils::module_cache::GetModule;
 use move_core_types::identifier::Identifier;
 use move_core_types::language_storage::{StructTag, TypeTag};
-use move_core_types::parser::{parse_struct_tag, parse_type_tag};
+use move_core_types::parser::parse_type_tag;
 use move_core_types::value::{MoveStruct, MoveStructLayout, MoveValue};
 use schemars::JsonSchema;
 use serde::ser::Error;
@@ -26,9 +26,10 @@ use serde_with::serde_as;
 
 use sui_json::SuiJsonValue;
 use sui_types::base_types::{
-    ObjectDigest, ObjectID, ObjectInfo, ObjectRef, SequenceNumber, SuiAddress, TransactionDigest,
+    AuthorityName, ObjectDigest, ObjectID, ObjectInfo, ObjectRef, SequenceNumber, SuiAddress,
+    TransactionDigest,
 };
-use sui_types::committee::EpochId;
+use sui_types::committee::{EpochId, StakeUnit};
 use sui_types::crypto::{AuthorityStrongQuorumSignInfo, Signature};
 use sui_types::error::SuiError;
 use sui_types::event::EventType;
@@ -43,19 +44,96 @@ use sui_types::messages::{
 use sui_types::messages_checkpoint::CheckpointSequenceNumber;
 use sui_types::move_package::disassemble_modules;
 use sui_types::object::{Data, MoveObject, Object, ObjectFormatOptions, ObjectRead, Owner};
+use sui_types::protocol_config::ProtocolConfig;
 use sui_types::sui_serde::{Base64, Encoding};
+use sui_types::sui_system_state::SuiSystemState;
 
 #[cfg(test)]
 #[path = "unit_tests/gateway_types_tests.rs"]
 mod gateway_types_tests;
 
-#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[derive(Serialize, Deserialize, Debug, JsonSchema, Clone)]
 pub struct TransactionEffectsResponse {
     pub certificate: SuiCertifiedTransaction,
     pub effects: SuiTransactionEffects,
     pub timestamp_ms: Option<u64>,
 }
 
+/// The parts of a transaction `getTransactionBlock` returns, none unless asked for.
+#[derive(Serialize, Deserialize, Debug, JsonSchema, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename = "TransactionBlockOptions", rename_all = "camelCase", default)]
+pub struct SuiTransactionBlockOptions {
+    pub show_input: bool,
+    pub show_effects: bool,
+    pub show_events: bool,
+    pub show_object_changes: bool,
+    pub show_balance_changes: bool,
+}
+
+/// A transaction and what it did, with the parts not asked for in its
+/// [`SuiTransactionBlockOptions`] unset.
+#[derive(Serialize, Deserialize, Debug, JsonSchema, Clone)]
+#[serde(rename = "TransactionBlockResponse", rename_all = "camelCase")]
+pub struct SuiTransactionBlockResponse {
+    pub digest: TransactionDigest,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transaction: Option<SuiCertifiedTransaction>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effects: Option<SuiTransactionEffects>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub events: Option<Vec<SuiEvent>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub object_changes: Option<Vec<SuiObjectChange>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub balance_changes: Option<Vec<SuiBalanceChange>>,
+    /// Unset until the transaction is indexed, and on the gateway.
+    pub timestamp_ms: Option<u64>,
+}
+
+/// An object a transaction wrote, deleted or wrapped. Types are unset for packages, and for
+/// versions this node pruned.
+#[derive(Serialize, Deserialize, Debug, JsonSchema, Clone, PartialEq, Eq)]
+#[serde(rename = "ObjectChange", rename_all = "camelCase", tag = "type")]
+pub enum SuiObjectChange {
+    #[serde(rename_all = "camelCase")]
+    Published { reference: SuiObjectRef },
+    #[serde(rename_all = "camelCase")]
+    Created {
+        owner: Owner,
+        reference: SuiObjectRef,
+        object_type: Option<String>,
+    },
+    /// `previousVersion` is unset for objects mutated without being an input of the
+    /// transaction, like the children of its inputs.
+    #[serde(rename_all = "camelCase")]
+    Mutated {
+        owner: Owner,
+        reference: SuiObjectRef,
+        object_type: Option<String>,
+        previous_version: Option<SequenceNumber>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Unwrapped {
+        owner: Owner,
+        reference: SuiObjectRef,
+        object_type: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Deleted { reference: SuiObjectRef },
+    #[serde(rename_all = "camelCase")]
+    Wrapped { reference: SuiObjectRef },
+}
+
+/// How much a transaction changed the total balance of a coin type an owner has.
+#[derive(Serialize, Deserialize, Debug, JsonSchema, Clone, PartialEq, Eq)]
+#[serde(rename = "BalanceChange", rename_all = "camelCase")]
+pub struct SuiBalanceChange {
+    pub owner: Owner,
+    /// E.g. `0x2::sui::SUI`.
+    pub coin_type: String,
+    pub amount: i128,
+}
+
 #[derive(Serialize, Deserialize, Debug, JsonSchema)]
 pub enum TransactionResponse {
     EffectResponse(TransactionEffectsResponse),
@@ -514,7 +592,7 @@ impl Display for PublishResponse {
 pub type GetObjectDataResponse = SuiObjectRead<SuiParsedMoveObject>;
 pub type GetRawObjectDataResponse = SuiObjectRead<SuiRawMoveObject>;
 
-#[derive(Serialize, Deserialize, JsonSchema)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
 #[serde(tag = "status", content = "details", rename = "ObjectRead")]
 pub enum SuiObjectRead<T: SuiMoveObject> {
     Exists(SuiObject<T>),
@@ -1137,6 +1215,13 @@ impl Display for SuiTransactionEffects {
                 )?;
             }
         }
+        writeln!(
+            writer,
+            "Gas Used: computation {} , storage {} , storage rebate {}",
+            self.gas_used.computation_cost,
+            self.gas_used.storage_cost,
+            self.gas_used.storage_rebate
+        )?;
         write!(f, "{}", writer)
     }
 }
@@ -1187,8 +1272,11 @@ fn to_owned_ref(owned_refs: Vec<(ObjectRef, Owner)>) -> Vec<OwnedObjectRef> {
 #[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(rename = "GasCostSummary", rename_all = "camelCase")]
 pub struct SuiGasCostSummary {
+    /// Charged for executing the transaction.
     pub computation_cost: u64,
+    /// Charged for the bytes of the objects the transaction wrote.
     pub storage_cost: u64,
+    /// Refunded for the storage of the objects the transaction deleted or overwrote.
     pub storage_rebate: u64,
 }
 
@@ -1408,6 +1496,227 @@ impl From<ObjectInfo> for SuiObjectInfo {
     }
 }
 
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[serde(rename = "ObjectTypeFilter")]
+pub enum SuiObjectTypeFilter {
+    /// Match objects of exactly this type e.g. `0x2::coin::Coin<0x2::sui::SUI>`
+    Exact(String),
+    /// Match objects whose type starts with this string e.g. `0x2::coin::Coin`
+    Prefix(String),
+}
+
+impl SuiObjectTypeFilter {
+    pub fn matches(&self, type_: &str) -> bool {
+        match self {
+            SuiObjectTypeFilter::Exact(expected) => type_ == expected,
+            SuiObjectTypeFilter::Prefix(prefix) => type_.starts_with(prefix.as_str()),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[serde(rename = "CheckpointSignatures", rename_all = "camelCase")]
+pub struct SuiCheckpointSignatures {
+    pub sequence_number: CheckpointSequenceNumber,
+    /// Epoch of the checkpoint, if this node stores it.
+    pub epoch: Option<EpochId>,
+    /// True if the checkpoint is certified by a quorum, false if only the signature of the
+    /// serving validator is stored.
+    pub certified: bool,
+    /// False on nodes that do not store per-validator checkpoint signatures, in which case
+    /// `signers` is empty.
+    pub signer_detail_available: bool,
+    pub signers: Vec<SuiCheckpointSigner>,
+    /// Total stake of `signers`.
+    pub signed_stake: StakeUnit,
+    /// Stake required for a checkpoint certificate in the committee of its epoch, or of the
+    /// current epoch when `signer_detail_available` is false.
+    pub quorum_threshold: StakeUnit,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[serde(rename = "CheckpointSigner", rename_all = "camelCase")]
+pub struct SuiCheckpointSigner {
+    pub public_key: AuthorityName,
+    pub stake: StakeUnit,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, PartialEq, Eq)]
+#[serde(rename = "CoinBalance", rename_all = "camelCase")]
+pub struct SuiCoinBalance {
+    /// E.g. `0x2::sui::SUI`.
+    pub coin_type: String,
+    pub coin_object_count: u64,
+    pub total_balance: u64,
+    /// The part of `totalBalance` in coins locked to a transaction that wasn't executed yet.
+    pub locked_balance: u64,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, PartialEq, Eq)]
+#[serde(rename = "Coin", rename_all = "camelCase")]
+pub struct SuiCoin {
+    pub coin_object_id: ObjectID,
+    pub version: SequenceNumber,
+    pub digest: ObjectDigest,
+    pub balance: u64,
+    /// Whether the coin is locked to a transaction that wasn't executed yet.
+    pub locked: bool,
+}
+
+impl SuiCoin {
+    pub fn to_object_ref(&self) -> ObjectRef {
+        (self.coin_object_id, self.version, self.digest)
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[serde(rename = "CoinPage", rename_all = "camelCase")]
+pub struct SuiCoinPage {
+    pub data: Vec<SuiCoin>,
+    /// The first coin of the next page, unset on the last page.
+    pub next_cursor: Option<ObjectID>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, PartialEq, Eq)]
+#[serde(rename = "EffectsVerification", rename_all = "camelCase")]
+pub struct SuiEffectsVerification {
+    pub transaction_digest: TransactionDigest,
+    /// Whether this node executed the transaction with the claimed effects.
+    pub valid: bool,
+    /// The checkpoint the transaction is in. Unset when invalid, or not known to be in one yet.
+    pub checkpoint: Option<CheckpointSequenceNumber>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[serde(rename = "CoinSupply", rename_all = "camelCase")]
+pub struct SuiCoinSupply {
+    pub coin_type: String,
+    pub total_supply: u64,
+    /// The TreasuryCap the supply was read from. Unset for SUI, whose supply is kept by the
+    /// system state object.
+    pub treasury_cap: Option<ObjectID>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[serde(rename = "ObjectTypeCount", rename_all = "camelCase")]
+pub struct SuiObjectTypeCount {
+    pub object_type: String,
+    /// Live objects of the type, counted as this node indexes transactions.
+    pub count: u64,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[serde(rename = "ProtocolConfig", rename_all = "camelCase")]
+pub struct SuiProtocolConfig {
+    /// The epoch the parameters are in effect for.
+    pub epoch: EpochId,
+    #[serde(flatten)]
+    pub config: ProtocolConfig,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[serde(rename = "PublishedPackage", rename_all = "camelCase")]
+pub struct SuiPublishedPackage {
+    pub package_id: ObjectID,
+    pub publisher: SuiAddress,
+    pub transaction_digest: TransactionDigest,
+    /// Sequence number of the publishing transaction on this node.
+    pub sequence_number: u64,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[serde(rename = "PublishedPackagesPage", rename_all = "camelCase")]
+pub struct SuiPublishedPackagesPage {
+    pub data: Vec<SuiPublishedPackage>,
+    /// The cursor to read the next page from, unset on the last page.
+    pub next_cursor: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[serde(rename = "RetentionWatermarks", rename_all = "camelCase")]
+pub struct SuiRetentionWatermarks {
+    /// Sequence number of the oldest transaction whose certificate, effects and written object
+    /// versions this node retains.
+    pub objects: u64,
+    /// The first transaction sequence number batches can be streamed from.
+    pub batches: u64,
+    /// The oldest checkpoint retained, unset if this node has none.
+    pub checkpoints: Option<CheckpointSequenceNumber>,
+    /// Timestamp in milliseconds of the oldest event retained, unset if this node has none.
+    pub events: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[serde(rename = "ValidatorCheckpoint", rename_all = "camelCase")]
+pub struct SuiValidatorCheckpoint {
+    pub public_key: AuthorityName,
+    /// The latest checkpoint the validator reported, unset if it never reported one.
+    pub sequence_number: Option<CheckpointSequenceNumber>,
+    /// Milliseconds since the validator reported `sequence_number`.
+    pub staleness_ms: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[serde(rename = "SharedObjectVersion", rename_all = "camelCase")]
+pub struct SuiSharedObjectVersion {
+    pub object_id: ObjectID,
+    /// Version of the object after the last transaction this node executed on it.
+    pub version: SequenceNumber,
+    /// True if consensus sequenced transactions on the object that are not executed yet, so
+    /// the version is about to change. Only validators see those, full nodes report false.
+    pub contended: bool,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[serde(rename = "StakeDistribution", rename_all = "camelCase")]
+pub struct SuiStakeDistribution {
+    pub epoch: EpochId,
+    /// Balance of the storage fund, which pays for the storage of the objects already written.
+    pub storage_fund: u64,
+    /// Stake of the active validators themselves, without their delegations.
+    pub validator_stake: u64,
+    pub delegation_stake: u64,
+    pub validators: Vec<SuiValidatorStake>,
+}
+
+/// Stake of an active validator this epoch. Validators don't charge a commission yet, so
+/// there is no commission rate.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[serde(rename = "ValidatorStake", rename_all = "camelCase")]
+pub struct SuiValidatorStake {
+    pub sui_address: SuiAddress,
+    pub name: String,
+    pub stake: u64,
+    pub delegation: u64,
+    pub delegator_count: u64,
+    /// Stake of the validator from the next epoch on, once pending stake and withdrawals apply.
+    pub next_epoch_stake: u64,
+}
+
+impl From<SuiSystemState> for SuiStakeDistribution {
+    fn from(state: SuiSystemState) -> Self {
+        Self {
+            epoch: state.epoch,
+            storage_fund: state.storage_fund.value(),
+            validator_stake: state.validators.validator_stake,
+            delegation_stake: state.validators.delegation_stake,
+            validators: state
+                .validators
+                .active_validators
+                .into_iter()
+                .map(|validator| SuiValidatorStake {
+                    sui_address: ObjectID::from(validator.metadata.sui_address).into(),
+                    name: String::from_utf8_lossy(&validator.metadata.name).into_owned(),
+                    stake: validator.stake.value(),
+                    delegation: validator.delegation,
+                    delegator_count: validator.delegator_count,
+                    next_epoch_stake: validator.metadata.next_epoch_stake,
+                })
+                .collect(),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ObjectExistsResponse {
@@ -1470,7 +1779,8 @@ pub struct MoveCallParams {
 pub enum SuiEventFilter {
     Package(ObjectID),
     Module(String),
-    /// Move StructTag string value of the event type e.g. `0x2::devnet_nft::MintNFTEvent`
+    /// Move StructTag string value of the event type e.g. `0x2::devnet_nft::MintNFTEvent`, or a
+    /// module like `0x2::devnet_nft` or a package like `0x2` for all the event types they declare
     MoveEventType(String),
     MoveEventField {
         path: String,
@@ -1493,10 +1803,7 @@ impl TryInto<EventFilter> for SuiEventFilter {
         Ok(match self {
             Package(id) => EventFilter::Package(id),
             Module(module) => EventFilter::Module(Identifier::new(module)?),
-            MoveEventType(event_type) => {
-                // parse_struct_tag converts StructTag string e.g. `0x2::devnet_nft::MintNFTEvent` to StructTag object
-                EventFilter::MoveEventType(parse_struct_tag(&event_type)?)
-            }
+            MoveEventType(event_type) => EventFilter::MoveEventType(event_type.parse()?),
             MoveEventField { path, value } => EventFilter::MoveEventField { path, value },
             SenderAddress(address) => EventFilter::SenderAddress(address),
             ObjectId(id) => EventFilter::ObjectId(id),
@@ -0,0 +1,254 @@
+// Copyright (c) 2022, Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Only lets JSON-RPC requests through with a credential the node accepts, for nodes serving
+//! known clients only.
+//!
+//! A request is authenticated by an API key in [`API_KEY_HEADER`], or by a bearer token in the
+//! `Authorization` header that is either an API key or a JWT. JWTs must be signed with RS256
+//! by one of the keys of a JWKS, which whoever issues the tokens publishes, and not be expired.
+//! Requests that aren't authenticated are answered with 401.
+//!
+//! Like load signaling, authentication needs the request headers, so it is only done by the
+//! servers reading requests with hyper, see [`crate::hyper_server`].
+
+use base64ct::{Base64UrlUnpadded, Encoding};
+use hyper::header::{self, HeaderMap};
+use parking_lot::RwLock;
+use ring::constant_time::verify_slices_are_equal;
+use ring::signature::{RsaPublicKeyComponents, RSA_PKCS1_2048_8192_SHA256};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// The credentials requests are checked against, shared between the servers checking them and
+/// whatever refreshes the JWT keys.
+#[derive(Clone)]
+pub struct RequestAuth {
+    api_keys: Arc<Vec<String>>,
+    jwt: Option<JwtValidator>,
+}
+
+impl RequestAuth {
+    /// Accept the `api_keys`, and JWTs the keys of `jwt` verify if set.
+    pub fn new(api_keys: Vec<String>, jwt: Option<JwtValidator>) -> Self {
+        Self {
+            api_keys: Arc::new(api_keys),
+            jwt,
+        }
+    }
+
+    pub fn is_authenticated(&self, headers: &HeaderMap) -> bool {
+        if let Some(key) = headers.get(API_KEY_HEADER) {
+            return self.is_api_key(key.as_bytes());
+        }
+        let token = match headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+        {
+            Some(token) => token.trim(),
+            None => return false,
+        };
+        self.is_api_key(token.as_bytes())
+            || matches!(&self.jwt, Some(jwt) if jwt.verify(token, unix_secs()))
+    }
+
+    fn is_api_key(&self, key: &[u8]) -> bool {
+        self.api_keys
+            .iter()
+            .any(|api_key| verify_slices_are_equal(api_key.as_bytes(), key).is_ok())
+    }
+}
+
+/// Verifies RS256 JWTs with the keys of a JWKS, from [`JwtValidator::set_jwks`].
+#[derive(Clone)]
+pub struct JwtValidator {
+    /// By key id.
+    keys: Arc<RwLock<HashMap<String, RsaPublicKeyComponents<Vec<u8>>>>>,
+    issuer: Option<String>,
+    audience: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Jwks {
+    keys: Vec<Jwk>,
+}
+
+#[derive(Deserialize)]
+struct Jwk {
+    kty: String,
+    #[serde(default)]
+    kid: String,
+    #[serde(default)]
+    n: String,
+    #[serde(default)]
+    e: String,
+}
+
+#[derive(Deserialize)]
+struct JwtHeader {
+    alg: String,
+    #[serde(default)]
+    kid: String,
+}
+
+impl JwtValidator {
+    /// Tokens must carry `issuer` as `iss` and `audience` in `aud`, when set.
+    pub fn new(issuer: Option<String>, audience: Option<String>) -> Self {
+        Self {
+            keys: Arc::new(RwLock::new(HashMap::new())),
+            issuer,
+            audience,
+        }
+    }
+
+    /// Verify tokens with the RSA keys of the JWKS `jwks` from now on. Returns how many keys
+    /// it has, other kinds of keys are ignored.
+    pub fn set_jwks(&self, jwks: &[u8]) -> anyhow::Result<usize> {
+        let jwks: Jwks = serde_json::from_slice(jwks)?;
+        let mut keys = HashMap::new();
+        for key in jwks.keys.into_iter().filter(|key| key.kty == "RSA") {
+            let n = Base64UrlUnpadded::decode_vec(&key.n)
+                .map_err(|e| anyhow::anyhow!("Invalid modulus of key {}: {e}", key.kid))?;
+            let e = Base64UrlUnpadded::decode_vec(&key.e)
+                .map_err(|e| anyhow::anyhow!("Invalid exponent of key {}: {e}", key.kid))?;
+            keys.insert(key.kid, RsaPublicKeyComponents { n, e });
+        }
+        let count = keys.len();
+        *self.keys.write() = keys;
+        Ok(count)
+    }
+
+    fn verify(&self, token: &str, now_secs: u64) -> bool {
+        let mut parts = token.split('.');
+        let (encoded_header, claims, signature) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(header), Some(claims), Some(signature)) if parts.next().is_none() => {
+                (header, claims, signature)
+            }
+            _ => return false,
+        };
+        let header: JwtHeader = match decode_json(encoded_header) {
+            Some(header) => header,
+            None => return false,
+        };
+        // Only RS256, so that a token can't pick a weaker algorithm, or none.
+        if header.alg != "RS256" {
+            return false;
+        }
+        let signature = match Base64UrlUnpadded::decode_vec(signature) {
+            Ok(signature) => signature,
+            Err(_) => return false,
+        };
+        let signed = format!("{encoded_header}.{claims}");
+        let verified = match self.keys.read().get(&header.kid) {
+            Some(key) => key
+                .verify(&RSA_PKCS1_2048_8192_SHA256, signed.as_bytes(), &signature)
+                .is_ok(),
+            None => false,
+        };
+        verified
+            && matches!(
+                decode_json::<Value>(claims),
+                Some(claims) if self.claims_are_valid(&claims, now_secs)
+            )
+    }
+
+    fn claims_are_valid(&self, claims: &Value, now_secs: u64) -> bool {
+        let expired = !matches!(claims["exp"].as_u64(), Some(exp) if exp > now_secs);
+        let not_yet_valid = matches!(claims["nbf"].as_u64(), Some(nbf) if nbf > now_secs);
+        let issuer_matches = match &self.issuer {
+            Some(issuer) => claims["iss"].as_str() == Some(issuer.as_str()),
+            None => true,
+        };
+        let audience_matches = match &self.audience {
+            // A single audience may be a string rather than an array.
+            Some(audience) => match &claims["aud"] {
+                Value::String(aud) => aud == audience,
+                Value::Array(auds) => auds.iter().any(|aud| aud.as_str() == Some(audience)),
+                _ => false,
+            },
+            None => true,
+        };
+        !expired && !not_yet_valid && issuer_matches && audience_matches
+    }
+}
+
+fn decode_json<T: for<'de> Deserialize<'de>>(part: &str) -> Option<T> {
+    let bytes = Base64UrlUnpadded::decode_vec(part).ok()?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+fn unix_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::header::HeaderValue;
+    use serde_json::json;
+
+    fn headers(name: &str, value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::HeaderName::from_bytes(name.as_bytes()).unwrap(),
+            HeaderValue::from_str(value).unwrap(),
+        );
+        headers
+    }
+
+    #[test]
+    fn test_api_keys() {
+        let auth = RequestAuth::new(vec!["secret".to_string()], None);
+        assert!(auth.is_authenticated(&headers(API_KEY_HEADER, "secret")));
+        assert!(auth.is_authenticated(&headers("authorization", "Bearer secret")));
+        assert!(!auth.is_authenticated(&headers(API_KEY_HEADER, "guess")));
+        assert!(!auth.is_authenticated(&headers("authorization", "Basic secret")));
+        assert!(!auth.is_authenticated(&HeaderMap::new()));
+    }
+
+    #[test]
+    fn test_jwt_claims() {
+        let jwt = JwtValidator::new(Some("issuer".to_string()), Some("node".to_string()));
+        let valid = json!({ "iss": "issuer", "aud": ["other", "node"], "exp": 100 });
+        assert!(jwt.claims_are_valid(&valid, 99));
+        assert!(!jwt.claims_are_valid(&valid, 100));
+        let claims = json!({ "iss": "issuer", "aud": "node", "exp": 100, "nbf": 50 });
+        assert!(jwt.claims_are_valid(&claims, 60));
+        assert!(!jwt.claims_are_valid(&claims, 40));
+        let claims = json!({ "iss": "elsewhere", "aud": "node", "exp": 100 });
+        assert!(!jwt.claims_are_valid(&claims, 60));
+        let claims = json!({ "iss": "issuer", "aud": "node" });
+        assert!(!jwt.claims_are_valid(&claims, 60));
+    }
+
+    #[test]
+    fn test_jwt_rejects_unsigned_tokens() {
+        let jwt = JwtValidator::new(None, None);
+        let jwks = json!({ "keys": [
+            { "kty": "RSA", "kid": "key", "n": "AQAB", "e": "AQAB" },
+            { "kty": "EC", "kid": "ec", "crv": "P-256" },
+        ]});
+        assert_eq!(jwt.set_jwks(jwks.to_string().as_bytes()).unwrap(), 1);
+        let encode = |value: Value| Base64UrlUnpadded::encode_string(value.to_string().as_bytes());
+        let claims = encode(json!({ "exp": u64::MAX }));
+        let token = format!(
+            "{}.{claims}.",
+            encode(json!({ "alg": "none", "kid": "key" }))
+        );
+        assert!(!jwt.verify(&token, 0));
+        let token = format!(
+            "{}.{claims}.AAAA",
+            encode(json!({ "alg": "RS256", "kid": "key" }))
+        );
+        assert!(!jwt.verify(&token, 0));
+    }
+}
@@ -0,0 +1,94 @@
+// Copyright (c) 2022, Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tells JSON-RPC clients how loaded the node is, so that well-behaved ones slow down before
+//! the node has to turn them away.
+//!
+//! Every HTTP response carries the current [`LoadLevel`] in [`LOAD_LEVEL_HEADER`], and above
+//! normal load how long to back off for in [`SUGGESTED_BACKOFF_HEADER`]. The level is decided
+//! by the node and published through a [`LoadSignal`], so signaling it costs an atomic load per
+//! response.
+//!
+//! jsonrpsee's HTTP server can't add response headers, so a TCP server with a load signal reads
+//! requests with hyper, see [`crate::hyper_server`].
+
+use hyper::header::{HeaderMap, HeaderValue};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+pub const LOAD_LEVEL_HEADER: &str = "sui-load-level";
+pub const SUGGESTED_BACKOFF_HEADER: &str = "sui-suggested-backoff-ms";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LoadLevel {
+    Normal,
+    Elevated,
+    Overloaded,
+}
+
+impl LoadLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LoadLevel::Normal => "normal",
+            LoadLevel::Elevated => "elevated",
+            LoadLevel::Overloaded => "overloaded",
+        }
+    }
+
+    fn from_u8(level: u8) -> Self {
+        match level {
+            0 => LoadLevel::Normal,
+            1 => LoadLevel::Elevated,
+            _ => LoadLevel::Overloaded,
+        }
+    }
+}
+
+/// The current load level of the node, shared between whatever measures it and the servers
+/// signaling it.
+#[derive(Clone)]
+pub struct LoadSignal {
+    level: Arc<AtomicU8>,
+    elevated_backoff: Duration,
+    overloaded_backoff: Duration,
+}
+
+impl LoadSignal {
+    /// Clients are asked to back off for `elevated_backoff` or `overloaded_backoff` between
+    /// requests at those levels.
+    pub fn new(elevated_backoff: Duration, overloaded_backoff: Duration) -> Self {
+        Self {
+            level: Arc::new(AtomicU8::new(LoadLevel::Normal as u8)),
+            elevated_backoff,
+            overloaded_backoff,
+        }
+    }
+
+    pub fn set(&self, level: LoadLevel) {
+        self.level.store(level as u8, Ordering::Relaxed);
+    }
+
+    pub fn level(&self) -> LoadLevel {
+        LoadLevel::from_u8(self.level.load(Ordering::Relaxed))
+    }
+
+    pub fn suggested_backoff(&self) -> Option<Duration> {
+        match self.level() {
+            LoadLevel::Normal => None,
+            LoadLevel::Elevated => Some(self.elevated_backoff),
+            LoadLevel::Overloaded => Some(self.overloaded_backoff),
+        }
+    }
+
+    pub(crate) fn add_headers(&self, headers: &mut HeaderMap) {
+        headers.insert(
+            LOAD_LEVEL_HEADER,
+            HeaderValue::from_static(self.level().as_str()),
+        );
+        if let Some(backoff) = self.suggested_backoff() {
+            let millis = backoff.as_millis() as u64;
+            headers.insert(SUGGESTED_BACKOFF_HEADER, HeaderValue::from(millis));
+        }
+    }
+}
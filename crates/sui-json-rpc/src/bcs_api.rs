@@ -5,6 +5,7 @@ use std::sync::Arc;
 
 use anyhow::anyhow;
 use async_trait::async_trait;
+use futures::future::try_join_all;
 use jsonrpsee::core::RpcResult;
 use jsonrpsee::RpcModule;
 
@@ -17,6 +18,9 @@ use crate::SuiRpcModule;
 use sui_json_rpc_api::rpc_types::GetRawObjectDataResponse;
 use sui_json_rpc_api::RpcBcsApiServer;
 
+/// Maximum number of objects `getRawObjects` returns at once.
+const MAX_RAW_OBJECTS_PER_REQUEST: usize = 1000;
+
 pub struct BcsApiImpl {
     client: ClientStateAdaptor,
 }
@@ -61,6 +65,25 @@ impl RpcBcsApiServer for BcsApiImpl {
     async fn get_raw_object(&self, object_id: ObjectID) -> RpcResult<GetRawObjectDataResponse> {
         Ok(self.client.get_raw_object(object_id).await?)
     }
+
+    async fn get_raw_objects(
+        &self,
+        object_ids: Vec<ObjectID>,
+    ) -> RpcResult<Vec<GetRawObjectDataResponse>> {
+        if object_ids.len() > MAX_RAW_OBJECTS_PER_REQUEST {
+            return Err(anyhow!(
+                "At most {MAX_RAW_OBJECTS_PER_REQUEST} objects can be requested at once, got {}",
+                object_ids.len()
+            )
+            .into());
+        }
+        Ok(try_join_all(
+            object_ids
+                .into_iter()
+                .map(|object_id| self.client.get_raw_object(object_id)),
+        )
+        .await?)
+    }
 }
 
 impl SuiRpcModule for BcsApiImpl {
@@ -0,0 +1,56 @@
+// Copyright (c) 2022, Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// How long calls may take before they are given up on, see `rpc-default-timeout-ms` and
+/// `rpc-method-timeouts-ms`.
+#[derive(Clone, Debug, Default)]
+pub struct CallTimeouts {
+    default: Option<Duration>,
+    /// By method name, e.g. `sui_getObjectTypeStats`.
+    methods: BTreeMap<String, Duration>,
+}
+
+impl CallTimeouts {
+    /// Calls to the methods listed in `methods` time out after theirs, others after `default`,
+    /// or never when it is unset.
+    pub fn new(default: Option<Duration>, methods: BTreeMap<String, Duration>) -> Self {
+        Self { default, methods }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.default.is_none() && self.methods.is_empty()
+    }
+
+    /// The time calls to `method` may take, None if unlimited.
+    pub fn get(&self, method: &str) -> Option<Duration> {
+        self.methods.get(method).copied().or(self.default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_call_timeouts() {
+        let methods = BTreeMap::from([(
+            "sui_getObjectTypeStats".to_string(),
+            Duration::from_secs(30),
+        )]);
+        let timeouts = CallTimeouts::new(Some(Duration::from_secs(2)), methods.clone());
+        assert_eq!(
+            timeouts.get("sui_getObjectTypeStats"),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(timeouts.get("sui_getObject"), Some(Duration::from_secs(2)));
+
+        // Without a default, only the methods listed time out.
+        let timeouts = CallTimeouts::new(None, methods);
+        assert!(!timeouts.is_empty());
+        assert_eq!(timeouts.get("sui_getObject"), None);
+        assert!(CallTimeouts::default().is_empty());
+    }
+}
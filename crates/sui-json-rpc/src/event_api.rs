@@ -1,13 +1,18 @@
 // Copyright (c) 2022, Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
+use crate::page_size::PageSizes;
 use crate::SuiRpcModule;
+use anyhow::anyhow;
 use async_trait::async_trait;
 use futures::{StreamExt, TryStream};
 use jsonrpsee::core::RpcResult;
 use jsonrpsee_core::error::SubscriptionClosed;
 use jsonrpsee_core::server::rpc_module::RpcModule;
 use jsonrpsee_core::server::rpc_module::{PendingSubscription, SubscriptionSink};
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::ModuleId;
 use serde::Serialize;
+use serde_json::Value;
 use std::fmt::Display;
 use std::sync::Arc;
 use sui_core::authority::AuthorityState;
@@ -16,7 +21,10 @@ use sui_json_rpc_api::rpc_types::{SuiEvent, SuiEventEnvelope, SuiEventFilter};
 use sui_json_rpc_api::EventReadApiServer;
 use sui_json_rpc_api::EventStreamingApiServer;
 use sui_open_rpc::Module;
+use sui_storage::event_store::StoredEvent;
 use sui_types::base_types::{ObjectID, SuiAddress, TransactionDigest};
+use sui_types::event::EventType;
+use sui_types::event_filter::MoveEventTypeFilter;
 use tracing::warn;
 
 pub struct EventStreamingApiImpl {
@@ -97,72 +105,165 @@ impl SuiRpcModule for EventStreamingApiImpl {
 pub struct EventReadApiImpl {
     state: Arc<AuthorityState>,
     event_handler: Arc<EventHandler>,
+    /// Most events returned by a query, whatever `count` is requested, and how many without
+    /// one.
+    page_sizes: PageSizes,
 }
 
 impl EventReadApiImpl {
-    pub fn new(state: Arc<AuthorityState>, event_handler: Arc<EventHandler>) -> Self {
+    pub fn new(
+        state: Arc<AuthorityState>,
+        event_handler: Arc<EventHandler>,
+        page_sizes: PageSizes,
+    ) -> Self {
         Self {
             state,
             event_handler,
+            page_sizes,
+        }
+    }
+
+    /// Events ingested before the event store kept whole events can't be returned, and are
+    /// left out.
+    fn to_envelopes(&self, events: Vec<StoredEvent>) -> RpcResult<Vec<SuiEventEnvelope>> {
+        let mut envelopes = Vec::with_capacity(events.len());
+        for stored in events {
+            if let Some(event) = stored.event() {
+                envelopes.push(SuiEventEnvelope {
+                    timestamp: stored.timestamp(),
+                    tx_digest: stored.tx_digest(),
+                    event: SuiEvent::try_from(event.clone(), &self.state.module_cache)?,
+                });
+            }
         }
+        Ok(envelopes)
     }
 }
 
-#[allow(unused)]
 #[async_trait]
 impl EventReadApiServer for EventReadApiImpl {
     async fn get_events_by_transaction(
         &self,
         digest: TransactionDigest,
     ) -> RpcResult<Vec<SuiEventEnvelope>> {
-        Ok(vec![])
+        let events = self
+            .state
+            .get_events_for_transaction(digest)
+            .await
+            .map_err(|e| anyhow!("{e}"))?;
+        self.to_envelopes(events)
     }
 
     async fn get_events_by_module(
         &self,
         package: ObjectID,
         module: String,
-        count: u64,
+        count: Option<u64>,
         start_time: u64,
         end_time: u64,
     ) -> RpcResult<Vec<SuiEventEnvelope>> {
-        Ok(vec![])
+        let module = ModuleId::new(*package, Identifier::new(module)?);
+        let events = self
+            .state
+            .get_events_by_module(
+                start_time,
+                end_time,
+                module,
+                self.page_sizes.limit("sui_getEventsByModule", count),
+            )
+            .await
+            .map_err(|e| anyhow!("{e}"))?;
+        self.to_envelopes(events)
     }
 
     async fn get_events_by_event_type(
         &self,
         event_type: String,
-        count: u64,
+        count: Option<u64>,
         start_time: u64,
         end_time: u64,
     ) -> RpcResult<Vec<SuiEventEnvelope>> {
-        Ok(vec![])
+        let event_type: EventType = serde_json::from_value(Value::String(event_type))
+            .map_err(|e| anyhow!("Unknown event type: {e}"))?;
+        let events = self
+            .state
+            .get_events_by_type(
+                start_time,
+                end_time,
+                event_type,
+                self.page_sizes.limit("sui_getEventsByEventType", count),
+            )
+            .await
+            .map_err(|e| anyhow!("{e}"))?;
+        self.to_envelopes(events)
+    }
+
+    async fn get_events_by_move_event_type(
+        &self,
+        move_event_type: String,
+        count: Option<u64>,
+        start_time: u64,
+        end_time: u64,
+    ) -> RpcResult<Vec<SuiEventEnvelope>> {
+        let filter: MoveEventTypeFilter = move_event_type.parse()?;
+        let events = self
+            .state
+            .get_events_by_move_event_type(
+                start_time,
+                end_time,
+                filter,
+                self.page_sizes.limit("sui_getEventsByMoveEventType", count),
+            )
+            .await
+            .map_err(|e| anyhow!("{e}"))?;
+        self.to_envelopes(events)
     }
 
     async fn get_events_by_sender(
         &self,
         sender: SuiAddress,
-        count: u64,
+        count: Option<u64>,
         start_time: u64,
         end_time: u64,
     ) -> RpcResult<Vec<SuiEventEnvelope>> {
-        Ok(vec![])
+        let events = self
+            .state
+            .get_events_by_sender(
+                start_time,
+                end_time,
+                sender,
+                self.page_sizes.limit("sui_getEventsBySender", count),
+            )
+            .await
+            .map_err(|e| anyhow!("{e}"))?;
+        self.to_envelopes(events)
     }
 
     async fn get_events_by_object(
         &self,
         object: ObjectID,
-        count: u64,
+        count: Option<u64>,
         start_time: u64,
         end_time: u64,
     ) -> RpcResult<Vec<SuiEventEnvelope>> {
-        Ok(vec![])
+        let events = self
+            .state
+            .get_events_by_object(
+                start_time,
+                end_time,
+                object,
+                self.page_sizes.limit("sui_getEventsByObject", count),
+            )
+            .await
+            .map_err(|e| anyhow!("{e}"))?;
+        self.to_envelopes(events)
     }
 
+    #[allow(unused)]
     async fn get_events_by_owner(
         &self,
         owner: SuiAddress,
-        count: u64,
+        count: Option<u64>,
         start_time: u64,
         end_time: u64,
     ) -> RpcResult<Vec<SuiEventEnvelope>> {
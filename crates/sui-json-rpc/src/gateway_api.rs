@@ -6,13 +6,17 @@ use async_trait::async_trait;
 use ed25519_dalek::ed25519::signature::Signature;
 use jsonrpsee::core::RpcResult;
 use jsonrpsee_core::server::rpc_module::RpcModule;
+use move_core_types::parser::parse_struct_tag;
 use tracing::debug;
 
+use crate::page_size::PageSizes;
 use crate::SuiRpcModule;
 use sui_core::gateway_state::{GatewayClient, GatewayTxSeqNumber};
 use sui_json::SuiJsonValue;
 use sui_json_rpc_api::rpc_types::{
-    GetObjectDataResponse, SuiObjectInfo, TransactionEffectsResponse, TransactionResponse,
+    GetObjectDataResponse, SuiCoinBalance, SuiCoinPage, SuiEffectsVerification, SuiObjectInfo,
+    SuiObjectRef, SuiObjectTypeFilter, SuiProtocolConfig, SuiTransactionBlockOptions,
+    SuiTransactionBlockResponse, TransactionEffectsResponse, TransactionResponse,
 };
 use sui_json_rpc_api::rpc_types::{RPCTransactionRequestParams, SuiTypeTag};
 use sui_json_rpc_api::{
@@ -22,12 +26,16 @@ use sui_json_rpc_api::{
 use sui_open_rpc::Module;
 use sui_types::sui_serde::Base64;
 use sui_types::{
-    base_types::{ObjectID, SuiAddress, TransactionDigest},
+    base_types::{ExecutionDigests, ObjectID, SequenceNumber, SuiAddress, TransactionDigest},
     crypto,
     crypto::SignableBytes,
     messages::{Transaction, TransactionData},
+    messages_checkpoint::CheckpointSequenceNumber,
 };
 
+/// Most coins `getCoins` returns at once.
+const MAX_COINS_PER_PAGE: usize = 1000;
+
 pub struct RpcGatewayImpl {
     client: GatewayClient,
 }
@@ -116,28 +124,87 @@ impl SuiRpcModule for GatewayWalletSyncApiImpl {
     }
 }
 
+/// The gateway doesn't follow checkpoints, so it can't serve reads that must see one.
+fn ensure_no_min_checkpoint(min_checkpoint: Option<CheckpointSequenceNumber>) -> RpcResult<()> {
+    match min_checkpoint {
+        Some(checkpoint) => Err(anyhow!(
+            "The gateway doesn't track checkpoints, can't read at checkpoint {checkpoint} or later"
+        )
+        .into()),
+        None => Ok(()),
+    }
+}
+
 #[async_trait]
 impl RpcReadApiServer for GatewayReadApiImpl {
     async fn get_objects_owned_by_address(
         &self,
         address: SuiAddress,
+        min_checkpoint: Option<CheckpointSequenceNumber>,
     ) -> RpcResult<Vec<SuiObjectInfo>> {
+        ensure_no_min_checkpoint(min_checkpoint)?;
         debug!("get_objects_own_by_address : {}", address);
         Ok(self.client.get_objects_owned_by_address(address).await?)
     }
 
+    async fn get_objects_owned_by_address_and_type(
+        &self,
+        address: SuiAddress,
+        type_filter: SuiObjectTypeFilter,
+        min_checkpoint: Option<CheckpointSequenceNumber>,
+    ) -> RpcResult<Vec<SuiObjectInfo>> {
+        ensure_no_min_checkpoint(min_checkpoint)?;
+        debug!("get_objects_owned_by_address_and_type : {}", address);
+        // The gateway keeps its own local store, so there is no index to push the filter into.
+        Ok(self
+            .client
+            .get_objects_owned_by_address(address)
+            .await?
+            .into_iter()
+            .filter(|info| type_filter.matches(&info.type_))
+            .collect())
+    }
+
     async fn get_objects_owned_by_object(
         &self,
         object_id: ObjectID,
+        min_checkpoint: Option<CheckpointSequenceNumber>,
     ) -> RpcResult<Vec<SuiObjectInfo>> {
+        ensure_no_min_checkpoint(min_checkpoint)?;
         debug!("get_objects_own_by_object : {}", object_id);
         Ok(self.client.get_objects_owned_by_object(object_id).await?)
     }
 
-    async fn get_object(&self, object_id: ObjectID) -> RpcResult<GetObjectDataResponse> {
+    async fn verify_effects_batch(
+        &self,
+        _claimed: Vec<ExecutionDigests>,
+    ) -> RpcResult<Vec<SuiEffectsVerification>> {
+        Err(
+            anyhow!("The gateway doesn't track checkpoints, verify effects with a full node")
+                .into(),
+        )
+    }
+
+    async fn get_object(
+        &self,
+        object_id: ObjectID,
+        min_checkpoint: Option<CheckpointSequenceNumber>,
+    ) -> RpcResult<GetObjectDataResponse> {
+        ensure_no_min_checkpoint(min_checkpoint)?;
         Ok(self.client.get_object(object_id).await?)
     }
 
+    async fn get_object_at_version(
+        &self,
+        object_id: ObjectID,
+        version: SequenceNumber,
+    ) -> RpcResult<GetObjectDataResponse> {
+        Ok(self
+            .client
+            .get_object_at_version(object_id, version)
+            .await?)
+    }
+
     async fn get_recent_transactions(
         &self,
         count: u64,
@@ -148,7 +215,117 @@ impl RpcReadApiServer for GatewayReadApiImpl {
     async fn get_transaction(
         &self,
         digest: TransactionDigest,
+        min_checkpoint: Option<CheckpointSequenceNumber>,
+    ) -> RpcResult<TransactionEffectsResponse> {
+        ensure_no_min_checkpoint(min_checkpoint)?;
+        Ok(self.client.get_transaction(digest).await?)
+    }
+
+    async fn get_transaction_block(
+        &self,
+        digest: TransactionDigest,
+        options: Option<SuiTransactionBlockOptions>,
+        min_checkpoint: Option<CheckpointSequenceNumber>,
+    ) -> RpcResult<SuiTransactionBlockResponse> {
+        ensure_no_min_checkpoint(min_checkpoint)?;
+        Ok(self
+            .client
+            .get_transaction_block(digest, options.unwrap_or_default())
+            .await?)
+    }
+
+    async fn get_transaction_dependencies(
+        &self,
+        digest: TransactionDigest,
+        depth: Option<u64>,
+    ) -> RpcResult<Vec<(TransactionDigest, Vec<TransactionDigest>)>> {
+        Ok(self
+            .client
+            .get_transaction_dependencies(digest, depth.unwrap_or(1))
+            .await?)
+    }
+
+    async fn select_gas_coins(
+        &self,
+        address: SuiAddress,
+        budget: u64,
+        min_checkpoint: Option<CheckpointSequenceNumber>,
+    ) -> RpcResult<Vec<SuiObjectRef>> {
+        ensure_no_min_checkpoint(min_checkpoint)?;
+        Ok(self
+            .client
+            .select_gas_coins(address, budget)
+            .await?
+            .into_iter()
+            .map(SuiObjectRef::from)
+            .collect())
+    }
+
+    async fn get_balance(
+        &self,
+        address: SuiAddress,
+        coin_type: String,
+        min_checkpoint: Option<CheckpointSequenceNumber>,
+    ) -> RpcResult<SuiCoinBalance> {
+        ensure_no_min_checkpoint(min_checkpoint)?;
+        let tag = parse_struct_tag(&coin_type)?;
+        let balance = self
+            .client
+            .get_balances(address, Some(tag))
+            .await?
+            .pop()
+            .ok_or_else(|| anyhow!("No balance of {coin_type} for {address}"))?;
+        Ok(balance)
+    }
+
+    async fn get_coins(
+        &self,
+        address: SuiAddress,
+        coin_type: String,
+        largest_first: bool,
+        cursor: Option<ObjectID>,
+        exclude_locked: Option<bool>,
+        limit: Option<u64>,
+        min_checkpoint: Option<CheckpointSequenceNumber>,
+    ) -> RpcResult<SuiCoinPage> {
+        ensure_no_min_checkpoint(min_checkpoint)?;
+        let tag = parse_struct_tag(&coin_type)?;
+        Ok(self
+            .client
+            .get_coins(
+                address,
+                tag,
+                largest_first,
+                exclude_locked.unwrap_or_default(),
+                cursor,
+                PageSizes::new(MAX_COINS_PER_PAGE).limit("sui_getCoins", limit),
+            )
+            .await?)
+    }
+
+    async fn get_all_balances(
+        &self,
+        address: SuiAddress,
+        min_checkpoint: Option<CheckpointSequenceNumber>,
+    ) -> RpcResult<Vec<SuiCoinBalance>> {
+        ensure_no_min_checkpoint(min_checkpoint)?;
+        Ok(self.client.get_balances(address, None).await?)
+    }
+
+    async fn get_latest_modifying_transaction(
+        &self,
+        object_id: ObjectID,
+        min_checkpoint: Option<CheckpointSequenceNumber>,
     ) -> RpcResult<TransactionEffectsResponse> {
+        ensure_no_min_checkpoint(min_checkpoint)?;
+        // The gateway doesn't keep the transactions that deleted objects, so deleted objects
+        // are reported as such.
+        let digest = self
+            .client
+            .get_object(object_id)
+            .await?
+            .object()?
+            .previous_transaction;
         Ok(self.client.get_transaction(digest).await?)
     }
 
@@ -156,6 +333,10 @@ impl RpcReadApiServer for GatewayReadApiImpl {
         Ok(self.client.get_total_transaction_number()?)
     }
 
+    async fn get_protocol_config(&self) -> RpcResult<SuiProtocolConfig> {
+        Ok(self.client.get_protocol_config().await?)
+    }
+
     async fn get_transactions_in_range(
         &self,
         start: GatewayTxSeqNumber,
@@ -0,0 +1,544 @@
+// Copyright (c) 2022, Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Serves JSON-RPC over HTTP with hyper, for what jsonrpsee's HTTP server can't do.
+//!
+//! jsonrpsee's HTTP server can neither add response headers, see [`crate::backpressure`], read
+//! request headers, see [`crate::auth`], time out calls, see [`crate::call_timeout`], nor pass
+//! their parameters to the middleware for logging slow calls, and it only binds TCP sockets,
+//! see [`crate::unix_socket`]. So requests are read with hyper and the calls they carry are
+//! handed to the [`RpcModule`] directly, the calls of a batch concurrently like jsonrpsee does.
+//! They are reported to the same metrics middleware.
+//!
+//! On TCP, origins are checked against the same `ACCESS_CONTROL_ALLOW_ORIGIN` list as
+//! jsonrpsee's, and browsers are allowed to read the load headers and to send credentials.
+
+use crate::auth::{RequestAuth, API_KEY_HEADER};
+use crate::backpressure::LoadSignal;
+use crate::call_timeout::CallTimeouts;
+use crate::ApiMetrics;
+use futures::future;
+use hyper::body::HttpBody;
+use hyper::header::{self, HeaderValue};
+use hyper::rt::Executor;
+use hyper::server::conn::AddrStream;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, StatusCode};
+use jsonrpsee_core::middleware::Middleware;
+use jsonrpsee_core::server::rpc_module::RpcModule;
+use serde_json::Value;
+use std::convert::Infallible;
+use std::future::Future;
+use std::net::{SocketAddr, TcpListener};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{oneshot, watch};
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+/// Same limit as jsonrpsee's HTTP server.
+const MAX_REQUEST_BODY_SIZE: usize = 10 * 1024 * 1024;
+
+const PARSE_ERROR: i32 = -32700;
+const INVALID_REQUEST: i32 = -32600;
+/// What jsonrpsee answers failed calls with.
+const CALL_EXECUTION_FAILED: i32 = -32000;
+
+/// Stops the server and closes its connections when dropped.
+pub struct TcpServerHandle {
+    local_addr: SocketAddr,
+    task: JoinHandle<()>,
+    shutdown: Option<oneshot::Sender<()>>,
+    _connections: watch::Sender<()>,
+}
+
+impl TcpServerHandle {
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    pub(crate) async fn stopped(&mut self) {
+        let _ = (&mut self.task).await;
+    }
+
+    /// Stop accepting connections and wait up to `timeout` for those open to finish the
+    /// requests they are serving. Returns whether they did, the rest are closed on drop.
+    pub(crate) async fn drain(&mut self, timeout: Duration) -> bool {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+        tokio::time::timeout(timeout, &mut self.task).await.is_ok()
+    }
+}
+
+impl Drop for TcpServerHandle {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Serve `module` on `listener`, which must be non-blocking, signaling `load` in every response
+/// and only serving the requests `auth` authenticates, when set. Requests from browsers are only
+/// served to `allowed_origins`, unless it is empty.
+pub(crate) fn serve_tcp(
+    module: RpcModule<()>,
+    metrics: ApiMetrics,
+    timeouts: CallTimeouts,
+    listener: TcpListener,
+    load: Option<LoadSignal>,
+    auth: Option<RequestAuth>,
+    allowed_origins: Vec<String>,
+) -> anyhow::Result<TcpServerHandle> {
+    let local_addr = listener.local_addr()?;
+    let module = Arc::new(module);
+    let timeouts = Arc::new(timeouts);
+    let allowed_origins = Arc::new(allowed_origins);
+    let make_service = make_service_fn(move |_: &AddrStream| {
+        let module = module.clone();
+        let metrics = metrics.clone();
+        let timeouts = timeouts.clone();
+        let load = load.clone();
+        let auth = auth.clone();
+        let allowed_origins = allowed_origins.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                handle_tcp_request(
+                    module.clone(),
+                    metrics.clone(),
+                    timeouts.clone(),
+                    load.clone(),
+                    auth.clone(),
+                    allowed_origins.clone(),
+                    request,
+                )
+            }))
+        }
+    });
+    let (shutdown, stopping) = oneshot::channel();
+    let (executor, connections) = ConnectionExecutor::new();
+    let server = hyper::Server::from_tcp(listener)?
+        .executor(executor)
+        .serve(make_service)
+        .with_graceful_shutdown(async move {
+            let _ = stopping.await;
+        });
+    let task = tokio::spawn(async move {
+        if let Err(e) = server.await {
+            warn!("JSON-RPC server stopped: {e}");
+        }
+    });
+
+    Ok(TcpServerHandle {
+        local_addr,
+        task,
+        shutdown: Some(shutdown),
+        _connections: connections,
+    })
+}
+
+async fn handle_tcp_request(
+    module: Arc<RpcModule<()>>,
+    metrics: ApiMetrics,
+    timeouts: Arc<CallTimeouts>,
+    load: Option<LoadSignal>,
+    auth: Option<RequestAuth>,
+    allowed_origins: Arc<Vec<String>>,
+    request: Request<Body>,
+) -> Result<Response<Body>, Infallible> {
+    let origin = match request.headers().get(header::ORIGIN) {
+        Some(origin) if !allowed_origins.is_empty() => {
+            let is_allowed = |origin: &str| {
+                allowed_origins
+                    .iter()
+                    .any(|allowed| allowed == origin || allowed == "*")
+            };
+            if !origin.to_str().map(is_allowed).unwrap_or(false) {
+                return Ok(status_response(StatusCode::FORBIDDEN));
+            }
+            Some(origin.clone())
+        }
+        _ => None,
+    };
+
+    // Browsers don't send credentials in preflight requests.
+    let mut response = if origin.is_some() && request.method() == Method::OPTIONS {
+        preflight_response()
+    } else if matches!(&auth, Some(auth) if !auth.is_authenticated(request.headers())) {
+        status_response(StatusCode::UNAUTHORIZED)
+    } else {
+        handle_request(module, metrics, timeouts, request).await?
+    };
+    let headers = response.headers_mut();
+    if let Some(origin) = origin {
+        headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, origin);
+        headers.insert(
+            header::ACCESS_CONTROL_EXPOSE_HEADERS,
+            HeaderValue::from_static("sui-load-level, sui-suggested-backoff-ms"),
+        );
+    }
+    if let Some(load) = load {
+        load.add_headers(headers);
+    }
+    Ok(response)
+}
+
+fn preflight_response() -> Response<Body> {
+    Response::builder()
+        .header(header::ACCESS_CONTROL_ALLOW_METHODS, "POST")
+        .header(
+            header::ACCESS_CONTROL_ALLOW_HEADERS,
+            format!("content-type, authorization, {API_KEY_HEADER}"),
+        )
+        .body(Body::empty())
+        .expect("Valid response")
+}
+
+/// A request reported to the middleware, whose response is reported when dropped, so that
+/// requests given up on because their connection closed count as done as well.
+struct InFlight<'a> {
+    metrics: &'a ApiMetrics,
+    started_at: Instant,
+}
+
+impl<'a> InFlight<'a> {
+    fn new(metrics: &'a ApiMetrics) -> Self {
+        Self {
+            metrics,
+            started_at: metrics.on_request(),
+        }
+    }
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.metrics.on_response(self.started_at);
+    }
+}
+
+/// Runs the connections of a server until they close, or until the sender of `closed` is
+/// dropped with the server handle. hyper runs every connection in a task of its own, so
+/// stopping the server alone would leave them open.
+#[derive(Clone)]
+pub(crate) struct ConnectionExecutor {
+    closed: watch::Receiver<()>,
+}
+
+impl ConnectionExecutor {
+    pub(crate) fn new() -> (Self, watch::Sender<()>) {
+        let (sender, closed) = watch::channel(());
+        (Self { closed }, sender)
+    }
+}
+
+impl<F> Executor<F> for ConnectionExecutor
+where
+    F: Future + Send + 'static,
+{
+    fn execute(&self, connection: F) {
+        let mut closed = self.closed.clone();
+        tokio::spawn(async move {
+            tokio::select! {
+                _ = connection => {}
+                // Only fails once the sender was dropped, nothing is ever sent.
+                _ = closed.changed() => {}
+            }
+        });
+    }
+}
+
+pub(crate) async fn handle_request(
+    module: Arc<RpcModule<()>>,
+    metrics: ApiMetrics,
+    timeouts: Arc<CallTimeouts>,
+    request: Request<Body>,
+) -> Result<Response<Body>, Infallible> {
+    if request.method() != Method::POST {
+        return Ok(status_response(StatusCode::METHOD_NOT_ALLOWED));
+    }
+    let body = match read_body(request.into_body()).await {
+        Ok(body) => body,
+        Err(status) => return Ok(status_response(status)),
+    };
+
+    let request = InFlight::new(&metrics);
+    let started_at = request.started_at;
+    let response = match serde_json::from_slice(&body) {
+        // An empty batch is answered with a single error rather than an empty array.
+        Ok(Value::Array(calls)) if calls.is_empty() => {
+            error_response(INVALID_REQUEST, "Invalid request")
+        }
+        Ok(Value::Array(calls)) => {
+            let responses = future::join_all(
+                calls
+                    .into_iter()
+                    .map(|call| handle_call(&module, &metrics, &timeouts, call, started_at)),
+            )
+            .await;
+            format!("[{}]", responses.join(","))
+        }
+        Ok(call) => handle_call(&module, &metrics, &timeouts, call, started_at).await,
+        Err(_) => error_response(PARSE_ERROR, "Parse error"),
+    };
+    drop(request);
+
+    Ok(Response::builder()
+        .header(header::CONTENT_TYPE, "application/json; charset=utf-8")
+        .body(Body::from(response))
+        .expect("Valid response"))
+}
+
+async fn handle_call(
+    module: &RpcModule<()>,
+    metrics: &ApiMetrics,
+    timeouts: &CallTimeouts,
+    call: Value,
+    started_at: Instant,
+) -> String {
+    let name = call
+        .get("method")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string();
+    metrics.on_call(&name);
+    let request = call.to_string();
+    let result = match timeouts.get(&name) {
+        Some(timeout) => tokio::time::timeout(timeout, module.raw_json_request(&request))
+            .await
+            .map_err(|_| timeout),
+        None => Ok(module.raw_json_request(&request).await),
+    };
+    let (response, success) = match result {
+        Ok(Ok((response, _))) => {
+            let success = matches!(
+                serde_json::from_str::<Value>(&response),
+                Ok(value) if value.get("error").is_none()
+            );
+            (response, success)
+        }
+        Ok(Err(_)) => (error_response(INVALID_REQUEST, "Invalid request"), false),
+        Err(timeout) => {
+            metrics.on_timeout(&name);
+            let message = format!("{name} timed out after {}ms", timeout.as_millis());
+            let response = serde_json::json!({
+                "jsonrpc": "2.0",
+                "error": { "code": CALL_EXECUTION_FAILED, "message": message },
+                "id": call.get("id").cloned().unwrap_or(Value::Null),
+            });
+            (response.to_string(), false)
+        }
+    };
+    metrics.on_result(&name, success, started_at);
+    metrics.on_call_result(&name, call.get("params"), success, started_at);
+    response
+}
+
+async fn read_body(mut body: Body) -> Result<Vec<u8>, StatusCode> {
+    let mut bytes = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(|_| StatusCode::BAD_REQUEST)?;
+        if bytes.len() + chunk.len() > MAX_REQUEST_BODY_SIZE {
+            return Err(StatusCode::PAYLOAD_TOO_LARGE);
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(bytes)
+}
+
+/// An error response to a call whose id couldn't be read.
+fn error_response(code: i32, message: &str) -> String {
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "error": { "code": code, "message": message },
+        "id": null,
+    })
+    .to_string()
+}
+
+pub(crate) fn status_response(status: StatusCode) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .body(Body::empty())
+        .expect("Valid response")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backpressure::{LoadLevel, LOAD_LEVEL_HEADER, SUGGESTED_BACKOFF_HEADER};
+    use crate::JsonRpcMetrics;
+
+    fn serve_module(
+        module: RpcModule<()>,
+        metrics: JsonRpcMetrics,
+        load: Option<LoadSignal>,
+        auth: Option<RequestAuth>,
+        allowed_origins: Vec<String>,
+    ) -> TcpServerHandle {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.set_nonblocking(true).unwrap();
+        serve_tcp(
+            module,
+            ApiMetrics::JsonRpcMetrics(metrics),
+            CallTimeouts::default(),
+            listener,
+            load,
+            auth,
+            allowed_origins,
+        )
+        .unwrap()
+    }
+
+    fn hello_module() -> RpcModule<()> {
+        let mut module = RpcModule::new(());
+        module
+            .register_method("say_hello", |_, _| Ok("hello"))
+            .unwrap();
+        module
+    }
+
+    async fn post(handle: &TcpServerHandle, body: Value) -> Value {
+        let request = Request::post(format!("http://{}", handle.local_addr()))
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let response = hyper::Client::new().request(request).await.unwrap();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_load_headers() {
+        let metrics = JsonRpcMetrics::new(&prometheus::Registry::new());
+        let load = LoadSignal::new(Duration::from_millis(100), Duration::from_secs(1));
+        let handle = serve_module(
+            hello_module(),
+            metrics,
+            Some(load.clone()),
+            None,
+            vec!["https://wallet.example".to_string()],
+        );
+
+        let client = hyper::Client::new();
+        let call = |origin: &str| {
+            Request::post(format!("http://{}", handle.local_addr()))
+                .header(header::CONTENT_TYPE, "application/json")
+                .header(header::ORIGIN, origin)
+                .body(Body::from(
+                    r#"{ "jsonrpc": "2.0", "method": "say_hello", "id": 1 }"#,
+                ))
+                .unwrap()
+        };
+
+        let response = client
+            .request(call("https://wallet.example"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[LOAD_LEVEL_HEADER], "normal");
+        assert!(response.headers().get(SUGGESTED_BACKOFF_HEADER).is_none());
+        assert_eq!(
+            response.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+            "https://wallet.example"
+        );
+
+        load.set(LoadLevel::Overloaded);
+        let response = client
+            .request(call("https://wallet.example"))
+            .await
+            .unwrap();
+        assert_eq!(response.headers()[LOAD_LEVEL_HEADER], "overloaded");
+        assert_eq!(response.headers()[SUGGESTED_BACKOFF_HEADER], "1000");
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["result"], "hello");
+
+        let response = client
+            .request(call("https://elsewhere.example"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_auth() {
+        let metrics = JsonRpcMetrics::new(&prometheus::Registry::new());
+        let auth = RequestAuth::new(vec!["secret".to_string()], None);
+        let handle = serve_module(hello_module(), metrics, None, Some(auth), vec![]);
+
+        let client = hyper::Client::new();
+        let call = |api_key: Option<&str>| {
+            let mut request = Request::post(format!("http://{}", handle.local_addr()))
+                .header(header::CONTENT_TYPE, "application/json");
+            if let Some(api_key) = api_key {
+                request = request.header(API_KEY_HEADER, api_key);
+            }
+            request
+                .body(Body::from(
+                    r#"{ "jsonrpc": "2.0", "method": "say_hello", "id": 1 }"#,
+                ))
+                .unwrap()
+        };
+
+        let response = client.request(call(None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = client.request(call(Some("guess"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = client.request(call(Some("secret"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().get(LOAD_LEVEL_HEADER).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_batches() {
+        let mut module = hello_module();
+        module
+            .register_async_method("sleep", |params, _| async move {
+                let millis: u64 = params.one()?;
+                tokio::time::sleep(Duration::from_millis(millis)).await;
+                Ok("slept")
+            })
+            .unwrap();
+        let metrics = JsonRpcMetrics::new(&prometheus::Registry::new());
+        let requests_by_route = metrics.requests_by_route.clone();
+        metrics.add_routes(module.method_names());
+        let handle = serve_module(module, metrics, None, None, vec![]);
+
+        // An empty batch is an invalid request.
+        let response = post(&handle, serde_json::json!([])).await;
+        assert_eq!(response["error"]["code"], INVALID_REQUEST);
+
+        // The calls of a batch run concurrently, and are answered in order.
+        let started = Instant::now();
+        let response = post(
+            &handle,
+            serde_json::json!([
+                { "jsonrpc": "2.0", "method": "sleep", "params": [500], "id": 1 },
+                { "jsonrpc": "2.0", "method": "sleep", "params": [500], "id": 2 },
+                { "jsonrpc": "2.0", "method": "say_hello", "id": 3 },
+            ]),
+        )
+        .await;
+        assert!(started.elapsed() < Duration::from_millis(1000));
+        assert_eq!(response[0]["id"], 1);
+        assert_eq!(response[1]["id"], 2);
+        assert_eq!(response[2]["result"], "hello");
+
+        // Methods that aren't registered are counted under a single route.
+        for method in ["nope", "nope_again"] {
+            let response = post(
+                &handle,
+                serde_json::json!({ "jsonrpc": "2.0", "method": method, "id": 1 }),
+            )
+            .await;
+            assert!(response.get("error").is_some());
+        }
+        assert_eq!(requests_by_route.with_label_values(&["sleep"]).get(), 2);
+        assert_eq!(
+            requests_by_route
+                .with_label_values(&[crate::UNKNOWN_ROUTE])
+                .get(),
+            2
+        );
+        assert_eq!(requests_by_route.with_label_values(&["nope"]).get(), 0);
+    }
+}
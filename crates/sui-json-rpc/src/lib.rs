@@ -2,24 +2,42 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use jsonrpsee::http_server::{AccessControlBuilder, HttpServerBuilder, HttpServerHandle};
-use jsonrpsee::ws_server::{WsServerBuilder, WsServerHandle};
+use jsonrpsee::ws_server::WsServerBuilder;
 use jsonrpsee_core::middleware::Middleware;
 use jsonrpsee_core::server::rpc_module::RpcModule;
 
-use prometheus::{
-    register_histogram_vec_with_registry, register_int_counter_vec_with_registry, HistogramVec,
-    IntCounterVec,
-};
+use auth::RequestAuth;
+use backpressure::LoadSignal;
+use call_timeout::CallTimeouts;
+use hyper_server::TcpServerHandle;
+use parking_lot::RwLock;
+use prometheus::{HistogramVec, IntCounter, IntCounterVec, IntGauge};
+use serde_json::Value;
+use socket2::{Domain, Protocol, Socket, TcpKeepalive, Type};
+use std::collections::HashSet;
 use std::env;
-use std::net::SocketAddr;
-use std::time::Instant;
+use std::net::{Ipv4Addr, SocketAddr, TcpListener};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use sui_core::metrics::{self, MetricsNamespace};
 use sui_open_rpc::{Module, Project};
-use tracing::info;
+use tracing::{info, warn};
+use unix_socket::UnixServerHandle;
+use websocket::WebsocketServerHandle;
 
+pub mod auth;
+pub mod backpressure;
 pub mod bcs_api;
+pub mod call_timeout;
 pub mod event_api;
 pub mod gateway_api;
+pub mod hyper_server;
+pub mod page_size;
 pub mod read_api;
+pub mod response_cache;
+pub mod unix_socket;
+pub mod websocket;
 
 pub enum ServerBuilder<M = ()> {
     HttpBuilder(HttpServerBuilder<M>),
@@ -28,7 +46,9 @@ pub enum ServerBuilder<M = ()> {
 
 pub enum ServerHandle {
     HttpHandler(HttpServerHandle),
-    WsHandle(WsServerHandle),
+    WsHandle(WebsocketServerHandle),
+    UnixHandle(UnixServerHandle),
+    TcpHandle(TcpServerHandle),
 }
 
 #[derive(Clone)]
@@ -45,18 +65,90 @@ impl ServerHandle {
         }
     }
 
-    pub fn into_ws_server_handle(self) -> Option<WsServerHandle> {
+    pub fn into_ws_server_handle(self) -> Option<WebsocketServerHandle> {
         match self {
             ServerHandle::WsHandle(handle) => Some(handle),
             _ => None,
         }
     }
+
+    pub fn into_unix_server_handle(self) -> Option<UnixServerHandle> {
+        match self {
+            ServerHandle::UnixHandle(handle) => Some(handle),
+            _ => None,
+        }
+    }
+
+    /// Resolves once the server stopped on its own, e.g. because it failed to accept
+    /// connections. It must not be awaited again after that, nor stopped.
+    pub async fn stopped(&mut self) {
+        match self {
+            ServerHandle::HttpHandler(handle) => handle.await,
+            ServerHandle::WsHandle(handle) => handle.stopped().await,
+            ServerHandle::UnixHandle(handle) => handle.stopped().await,
+            ServerHandle::TcpHandle(handle) => handle.stopped().await,
+        }
+    }
+
+    /// Stop accepting connections and wait up to `drain_timeout` for the requests being served
+    /// to complete, then close the server. Returns how many of the requests counted by
+    /// `requests_in_flight`, as returned by [`JsonRpcServerBuilder::requests_in_flight`], were
+    /// still being served when it closed. For jsonrpsee's HTTP server, whose connections outlive
+    /// it, those are only cut off when the process exits. Websocket subscriptions never
+    /// complete, so websocket servers are closed right away.
+    pub async fn stop(self, drain_timeout: Duration, requests_in_flight: &IntGauge) -> u64 {
+        let cut_off = || requests_in_flight.get().max(0) as u64;
+        match self {
+            ServerHandle::HttpHandler(handle) => match handle.stop() {
+                Ok(mut stopped) => {
+                    if tokio::time::timeout(drain_timeout, &mut stopped)
+                        .await
+                        .is_ok()
+                    {
+                        return 0;
+                    }
+                    stopped.abort();
+                    cut_off()
+                }
+                // Already stopped.
+                Err(_) => 0,
+            },
+            ServerHandle::WsHandle(handle) => {
+                handle.stop().await;
+                0
+            }
+            // Counted before dropping the handle closes the connections.
+            ServerHandle::UnixHandle(mut handle) => {
+                if handle.drain(drain_timeout).await {
+                    0
+                } else {
+                    cut_off()
+                }
+            }
+            ServerHandle::TcpHandle(mut handle) => {
+                if handle.drain(drain_timeout).await {
+                    0
+                } else {
+                    cut_off()
+                }
+            }
+        }
+    }
 }
 
 pub struct JsonRpcServerBuilder {
     module: RpcModule<()>,
     server_builder: ServerBuilder<ApiMetrics>,
+    metrics: ApiMetrics,
     rpc_doc: Project,
+    ws_ping_interval: Option<Duration>,
+    ws_idle_timeout: Option<Duration>,
+    tcp_keepalive: Option<TcpKeepalive>,
+    reuse_port: bool,
+    load_signal: Option<LoadSignal>,
+    auth: Option<RequestAuth>,
+    call_timeouts: CallTimeouts,
+    allowed_origins: Vec<String>,
 }
 
 pub fn sui_rpc_doc() -> Project {
@@ -72,10 +164,26 @@ pub fn sui_rpc_doc() -> Project {
 }
 
 impl JsonRpcServerBuilder {
+    /// Calls slower than `slow_query_threshold`, if set, are logged at WARN.
     pub fn new(
         use_websocket: bool,
         prometheus_registry: &prometheus::Registry,
+        slow_query_threshold: Option<Duration>,
     ) -> anyhow::Result<Self> {
+        let metrics = if use_websocket {
+            ApiMetrics::WebsocketMetrics(WebsocketMetrics::new(prometheus_registry))
+        } else {
+            ApiMetrics::JsonRpcMetrics(
+                JsonRpcMetrics::new(prometheus_registry)
+                    .with_slow_query_threshold(slow_query_threshold),
+            )
+        };
+        Self::with_metrics(metrics)
+    }
+
+    /// A builder reporting to `metrics`, which may be those of another server, see
+    /// [`JsonRpcServerBuilder::metrics`]. Websocket servers are built for websocket metrics.
+    pub fn with_metrics(metrics: ApiMetrics) -> anyhow::Result<Self> {
         let (ac_builder, allow_list) = match env::var("ACCESS_CONTROL_ALLOW_ORIGIN") {
             Ok(value) => {
                 let owned_list: Vec<String> = value
@@ -91,12 +199,12 @@ impl JsonRpcServerBuilder {
             _ => (AccessControlBuilder::default(), vec![]),
         };
 
+        let use_websocket = matches!(metrics, ApiMetrics::WebsocketMetrics(_));
         let server_builder = if use_websocket {
-            let mut builder = WsServerBuilder::default()
-                .set_middleware(ApiMetrics::WebsocketMetrics(WebsocketMetrics {}));
+            let mut builder = WsServerBuilder::default().set_middleware(metrics.clone());
             if !allow_list.is_empty() {
                 info!("Setting ACCESS_CONTROL_ALLOW_ORIGIN to : {:?}", allow_list);
-                builder = builder.set_allowed_origins(allow_list)?;
+                builder = builder.set_allowed_origins(allow_list.clone())?;
             }
             ServerBuilder::WsBuilder(builder)
         } else {
@@ -105,9 +213,7 @@ impl JsonRpcServerBuilder {
             ServerBuilder::HttpBuilder(
                 HttpServerBuilder::default()
                     .set_access_control(acl)
-                    .set_middleware(ApiMetrics::JsonRpcMetrics(JsonRpcMetrics::new(
-                        prometheus_registry,
-                    ))),
+                    .set_middleware(metrics.clone()),
             )
         };
 
@@ -116,10 +222,95 @@ impl JsonRpcServerBuilder {
         Ok(Self {
             module,
             server_builder,
+            metrics,
             rpc_doc: sui_rpc_doc(),
+            ws_ping_interval: None,
+            ws_idle_timeout: None,
+            tcp_keepalive: None,
+            reuse_port: false,
+            load_signal: None,
+            auth: None,
+            call_timeouts: CallTimeouts::default(),
+            allowed_origins: allow_list,
         })
     }
 
+    /// Send a ping to every websocket connection at this interval, so that proxies in front of
+    /// the server don't close idle subscriptions. Has no effect on HTTP servers.
+    pub fn set_ws_ping_interval(&mut self, interval: Duration) {
+        self.ws_ping_interval = Some(interval);
+    }
+
+    /// Close the websocket connections the client sent nothing on for `timeout`, not even a
+    /// pong, see [`websocket`]. It should be longer than the ping interval, so that the clients
+    /// answering the pings stay connected. Has no effect on HTTP servers.
+    pub fn set_ws_idle_timeout(&mut self, timeout: Duration) {
+        self.ws_idle_timeout = Some(timeout);
+    }
+
+    /// Enable TCP keepalive on the connections accepted by an HTTP server, so that the OS
+    /// closes those whose peer went away. Probes start after `idle` without traffic and are
+    /// sent every `interval`, and the connection is dropped after `count` unanswered ones. The
+    /// OS defaults are used for those left unset. Has no effect on websocket servers.
+    pub fn set_tcp_keepalive(
+        &mut self,
+        idle: Duration,
+        interval: Option<Duration>,
+        count: Option<u32>,
+    ) {
+        let mut keepalive = TcpKeepalive::new().with_time(idle);
+        if let Some(interval) = interval {
+            keepalive = keepalive.with_interval(interval);
+        }
+        if let Some(count) = count {
+            keepalive = keepalive.with_retries(count);
+        }
+        self.tcp_keepalive = Some(keepalive);
+    }
+
+    /// Bind the listening socket of an HTTP server with SO_REUSEPORT, so that several servers
+    /// can listen on the same address and the kernel spreads the connections between them.
+    /// Has no effect on websocket servers.
+    pub fn set_reuse_port(&mut self) {
+        self.reuse_port = true;
+    }
+
+    /// Signal the load published through `signal` to clients in the headers of every
+    /// response, see [`backpressure`]. Like authentication, call timeouts and logging slow
+    /// calls, it makes an HTTP server read requests with hyper, see [`hyper_server`]. Has no
+    /// effect on websocket servers.
+    pub fn set_load_signal(&mut self, signal: LoadSignal) {
+        self.load_signal = Some(signal);
+    }
+
+    /// Only serve requests `auth` authenticates on TCP, answering the others with 401, see
+    /// [`auth`]. Has no effect on websocket servers and Unix sockets, whose permissions decide
+    /// who can use them.
+    pub fn set_auth(&mut self, auth: RequestAuth) {
+        self.auth = Some(auth);
+    }
+
+    /// Answer calls that take longer than `timeouts` allows with an error naming the method,
+    /// counted by `timeouts_by_route`. Has no effect on websocket servers.
+    pub fn set_call_timeouts(&mut self, timeouts: CallTimeouts) {
+        self.call_timeouts = timeouts;
+    }
+
+    /// The metrics the server reports to, for other servers to report to as well so that they
+    /// add up.
+    pub fn metrics(&self) -> ApiMetrics {
+        self.metrics.clone()
+    }
+
+    /// Requests an HTTP server built by `self` started and didn't respond to yet, for knowing
+    /// what stopping it cuts off. None for websocket servers.
+    pub fn requests_in_flight(&self) -> Option<IntGauge> {
+        match &self.metrics {
+            ApiMetrics::JsonRpcMetrics(metrics) => Some(metrics.requests_in_flight.clone()),
+            ApiMetrics::WebsocketMetrics(_) => None,
+        }
+    }
+
     pub fn register_module<T: SuiRpcModule>(&mut self, module: T) -> Result<(), anyhow::Error> {
         self.rpc_doc.add_module(T::rpc_doc_module());
         self.module.merge(module.rpc()).map_err(Into::into)
@@ -132,18 +323,93 @@ impl JsonRpcServerBuilder {
         self.module
             .register_method("rpc.discover", move |_, _| Ok(self.rpc_doc.clone()))?;
         let methods_names = self.module.method_names().collect::<Vec<_>>();
+        self.metrics.add_routes(methods_names.iter().copied());
+        // jsonrpsee's HTTP server can neither add headers, time out calls nor pass their
+        // parameters to the middleware, see [`hyper_server`].
+        let logs_slow_queries = matches!(
+            &self.metrics,
+            ApiMetrics::JsonRpcMetrics(metrics) if metrics.slow_query_threshold.is_some()
+        );
+        let needs_hyper = self.load_signal.is_some()
+            || self.auth.is_some()
+            || !self.call_timeouts.is_empty()
+            || logs_slow_queries;
         let (handle, addr, server_name) = match self.server_builder {
+            ServerBuilder::HttpBuilder(_) if needs_hyper => {
+                let listener = if self.tcp_keepalive.is_some() || self.reuse_port {
+                    bind(listen_address, self.tcp_keepalive.as_ref(), self.reuse_port)?
+                } else {
+                    let listener = TcpListener::bind(listen_address)?;
+                    listener.set_nonblocking(true)?;
+                    listener
+                };
+                let handle = hyper_server::serve_tcp(
+                    self.module,
+                    self.metrics,
+                    self.call_timeouts,
+                    listener,
+                    self.load_signal,
+                    self.auth,
+                    self.allowed_origins,
+                )?;
+                let addr = handle.local_addr();
+                (ServerHandle::TcpHandle(handle), addr, "JSON-RPC")
+            }
             ServerBuilder::HttpBuilder(http_builder) => {
-                let server = http_builder.build(listen_address).await?;
+                let server = if self.tcp_keepalive.is_some() || self.reuse_port {
+                    http_builder.build_from_tcp(bind(
+                        listen_address,
+                        self.tcp_keepalive.as_ref(),
+                        self.reuse_port,
+                    )?)?
+                } else {
+                    http_builder.build(listen_address).await?
+                };
                 let addr = server.local_addr()?;
                 let handle = server.start(self.module)?;
                 (ServerHandle::HttpHandler(handle), addr, "JSON-RPC")
             }
-            ServerBuilder::WsBuilder(ws_builder) => {
-                let server = ws_builder.build(listen_address).await?;
-                let addr = server.local_addr()?;
-                let handle = server.start(self.module)?;
-                (ServerHandle::WsHandle(handle), addr, "Websocket")
+            ServerBuilder::WsBuilder(mut ws_builder) => {
+                if let Some(interval) = self.ws_ping_interval {
+                    ws_builder = ws_builder.ping_interval(interval);
+                }
+                let (server, addr, proxy) = match self.ws_idle_timeout {
+                    Some(idle_timeout) => {
+                        let listener = tokio::net::TcpListener::bind(listen_address).await?;
+                        let addr = listener.local_addr()?;
+                        // The server only accepts the connections of the proxy.
+                        let server = ws_builder
+                            .build(SocketAddr::from((Ipv4Addr::LOCALHOST, 0)))
+                            .await?;
+                        let upstream = server.local_addr()?;
+                        let server = server.start(self.module)?;
+                        let dropped = match &self.metrics {
+                            ApiMetrics::WebsocketMetrics(metrics) => {
+                                metrics.dropped_on_timeout.clone()
+                            }
+                            ApiMetrics::JsonRpcMetrics(_) => {
+                                unreachable!("Websocket servers have websocket metrics")
+                            }
+                        };
+                        let proxy = websocket::close_idle_connections(
+                            listener,
+                            upstream,
+                            idle_timeout,
+                            dropped,
+                        );
+                        (server, addr, Some(proxy))
+                    }
+                    None => {
+                        let server = ws_builder.build(listen_address).await?;
+                        let addr = server.local_addr()?;
+                        (server.start(self.module)?, addr, None)
+                    }
+                };
+                (
+                    ServerHandle::WsHandle(WebsocketServerHandle::new(server, proxy)),
+                    addr,
+                    "Websocket",
+                )
             }
         };
         info!(local_addr =? addr, "Sui {server_name} server listening on {addr}");
@@ -151,6 +417,57 @@ impl JsonRpcServerBuilder {
 
         Ok(handle)
     }
+
+    /// Serve HTTP JSON-RPC on a Unix domain socket at `path`, whose file is created with the
+    /// permissions `mode`. Access control by origin doesn't apply to the socket, only who can
+    /// open it does. Websockets are not supported.
+    pub async fn start_unix(mut self, path: &Path, mode: u32) -> anyhow::Result<ServerHandle> {
+        anyhow::ensure!(
+            matches!(self.server_builder, ServerBuilder::HttpBuilder(_)),
+            "Websocket servers can't listen on a Unix socket"
+        );
+        self.module
+            .register_method("rpc.discover", move |_, _| Ok(self.rpc_doc.clone()))?;
+        let methods_names = self.module.method_names().collect::<Vec<_>>();
+        self.metrics.add_routes(methods_names.iter().copied());
+        let handle = unix_socket::serve(
+            self.module,
+            self.metrics,
+            self.call_timeouts,
+            self.load_signal,
+            path,
+            mode,
+        )?;
+        info!(path = ?path, "Sui JSON-RPC server listening on {}", path.display());
+        info!("Available JSON-RPC methods : {:?}", methods_names);
+
+        Ok(ServerHandle::UnixHandle(handle))
+    }
+}
+
+/// A listening socket whose accepted connections inherit `keepalive` if set, bound with
+/// SO_REUSEPORT if `reuse_port`.
+fn bind(
+    address: SocketAddr,
+    keepalive: Option<&TcpKeepalive>,
+    reuse_port: bool,
+) -> anyhow::Result<TcpListener> {
+    let socket = Socket::new(
+        Domain::for_address(address),
+        Type::STREAM,
+        Some(Protocol::TCP),
+    )?;
+    if let Some(keepalive) = keepalive {
+        socket.set_tcp_keepalive(keepalive)?;
+    }
+    if reuse_port {
+        socket.set_reuse_port(true)?;
+    }
+    socket.set_reuse_address(true)?;
+    socket.set_nonblocking(true)?;
+    socket.bind(&address.into())?;
+    socket.listen(1024)?;
+    Ok(socket.into())
 }
 
 #[derive(Clone)]
@@ -161,64 +478,238 @@ pub struct JsonRpcMetrics {
     req_latency_by_route: HistogramVec,
     /// Failed requests by route
     errors_by_route: IntCounterVec,
+    /// Calls given up on for taking longer than their timeout, by route
+    timeouts_by_route: IntCounterVec,
+    /// Requests being served, a batch counts once
+    requests_in_flight: IntGauge,
+    /// Requests taking longer than this are logged
+    slow_query_threshold: Option<Duration>,
+    /// The methods the servers reporting to these metrics registered, the others are counted
+    /// under `UNKNOWN_ROUTE` so that clients can't add routes
+    routes: Arc<RwLock<HashSet<String>>>,
 }
 
+/// The route calls to methods that aren't registered are counted under.
+pub const UNKNOWN_ROUTE: &str = "unknown";
+
 impl JsonRpcMetrics {
     pub fn new(registry: &prometheus::Registry) -> Self {
+        let rpc = MetricsNamespace::new(registry, metrics::RPC);
         Self {
-            requests_by_route: register_int_counter_vec_with_registry!(
-                "rpc_requests_by_route",
-                "Number of requests by route",
-                &["route"],
-                registry,
-            )
-            .unwrap(),
-            req_latency_by_route: register_histogram_vec_with_registry!(
-                "req_latency_by_route",
-                "Latency of a request by route",
-                &["route"],
-                registry,
-            )
-            .unwrap(),
-            errors_by_route: register_int_counter_vec_with_registry!(
-                "errors_by_route",
-                "Number of errors by route",
-                &["route"],
-                registry,
-            )
-            .unwrap(),
+            requests_by_route: rpc
+                .int_counter_vec(
+                    "requests_by_route",
+                    "Number of requests by route",
+                    &["route"],
+                )
+                .unwrap(),
+            req_latency_by_route: rpc
+                .histogram_vec(
+                    "req_latency_by_route",
+                    "Latency of a request by route",
+                    &["route"],
+                )
+                .unwrap(),
+            errors_by_route: rpc
+                .int_counter_vec("errors_by_route", "Number of errors by route", &["route"])
+                .unwrap(),
+            timeouts_by_route: rpc
+                .int_counter_vec(
+                    "timeouts_by_route",
+                    "Number of calls that timed out by route",
+                    &["route"],
+                )
+                .unwrap(),
+            requests_in_flight: rpc
+                .int_gauge("requests_in_flight", "Number of requests being served")
+                .unwrap(),
+            slow_query_threshold: None,
+            routes: Arc::default(),
+        }
+    }
+
+    /// Count the calls to `methods` under their own route.
+    pub fn add_routes<'a>(&self, methods: impl IntoIterator<Item = &'a str>) {
+        self.routes
+            .write()
+            .extend(methods.into_iter().map(str::to_string));
+    }
+
+    /// The route a call to `method` is counted under.
+    fn route<'a>(&self, method: &'a str) -> &'a str {
+        if self.routes.read().contains(method) {
+            method
+        } else {
+            UNKNOWN_ROUTE
+        }
+    }
+
+    pub fn with_slow_query_threshold(mut self, threshold: Option<Duration>) -> Self {
+        self.slow_query_threshold = threshold;
+        self
+    }
+
+    /// What is logged about the call to `method` with `params` that started at `started_at`,
+    /// if it took longer than the slow query threshold.
+    fn slow_call<'a>(
+        &self,
+        method: &'a str,
+        params: Option<&Value>,
+        success: bool,
+        started_at: Instant,
+    ) -> Option<SlowCall<'a>> {
+        let duration = started_at.elapsed();
+        if !matches!(self.slow_query_threshold, Some(threshold) if duration > threshold) {
+            return None;
         }
+        let mut params = params.map(Value::to_string).unwrap_or_default();
+        if params.len() > SLOW_QUERY_MAX_PARAMS_LEN {
+            let mut end = SLOW_QUERY_MAX_PARAMS_LEN;
+            while !params.is_char_boundary(end) {
+                end -= 1;
+            }
+            let len = params.len();
+            params.truncate(end);
+            params.push_str(&format!("... ({len} bytes)"));
+        }
+        Some(SlowCall {
+            method,
+            params,
+            success,
+            duration_ms: duration.as_millis() as u64,
+        })
     }
 }
 
-// TODO: add metrics middleware for ws server
+/// Bytes of the parameters of a slow call that are logged, the rest is cut off.
+const SLOW_QUERY_MAX_PARAMS_LEN: usize = 1024;
+
+/// A call slower than the slow query threshold, as logged.
+#[derive(Debug, PartialEq)]
+struct SlowCall<'a> {
+    method: &'a str,
+    /// The JSON encoding of the parameters, truncated to `SLOW_QUERY_MAX_PARAMS_LEN` bytes.
+    params: String,
+    success: bool,
+    duration_ms: u64,
+}
+
 #[derive(Clone)]
-pub struct WebsocketMetrics {}
+pub struct WebsocketMetrics {
+    /// Number of open websocket connections
+    active_connections: IntGauge,
+    /// Connections closed for being idle longer than the idle timeout
+    dropped_on_timeout: IntCounter,
+}
+
+impl WebsocketMetrics {
+    pub fn new(registry: &prometheus::Registry) -> Self {
+        let rpc = MetricsNamespace::new(registry, metrics::RPC);
+        Self {
+            active_connections: rpc
+                .int_gauge(
+                    "ws_active_connections",
+                    "Number of open websocket connections",
+                )
+                .unwrap(),
+            dropped_on_timeout: rpc
+                .int_counter(
+                    "ws_dropped_on_timeout",
+                    "Websocket connections closed because the client sent nothing, not even \
+                    a pong, within the idle timeout",
+                )
+                .unwrap(),
+        }
+    }
+}
+
+impl ApiMetrics {
+    /// Count the calls to `methods` under their own route, see [`JsonRpcMetrics::add_routes`].
+    fn add_routes<'a>(&self, methods: impl IntoIterator<Item = &'a str>) {
+        if let ApiMetrics::JsonRpcMetrics(metrics) = self {
+            metrics.add_routes(methods);
+        }
+    }
+
+    /// Count a call to `name` that timed out, before its result is reported as an error.
+    fn on_timeout(&self, name: &str) {
+        if let ApiMetrics::JsonRpcMetrics(metrics) = self {
+            metrics
+                .timeouts_by_route
+                .with_label_values(&[metrics.route(name)])
+                .inc();
+        }
+    }
+
+    /// Log the call to `name` with `params` at WARN if it took longer than the slow query
+    /// threshold. Only the hyper servers see the parameters, so only they log slow calls.
+    fn on_call_result(
+        &self,
+        name: &str,
+        params: Option<&Value>,
+        success: bool,
+        started_at: Instant,
+    ) {
+        let slow_call = match self {
+            ApiMetrics::JsonRpcMetrics(metrics) => {
+                metrics.slow_call(name, params, success, started_at)
+            }
+            ApiMetrics::WebsocketMetrics(_) => None,
+        };
+        if let Some(call) = slow_call {
+            warn!(
+                method = call.method,
+                params = call.params.as_str(),
+                success = call.success,
+                duration_ms = call.duration_ms,
+                "Slow JSON-RPC call"
+            );
+        }
+    }
+}
 
 impl Middleware for ApiMetrics {
     type Instant = Instant;
 
+    fn on_connect(&self) {
+        if let ApiMetrics::WebsocketMetrics(metrics) = self {
+            metrics.active_connections.inc();
+        }
+    }
+
+    fn on_disconnect(&self) {
+        if let ApiMetrics::WebsocketMetrics(metrics) = self {
+            metrics.active_connections.dec();
+        }
+    }
+
     fn on_request(&self) -> Instant {
+        if let ApiMetrics::JsonRpcMetrics(metrics) = self {
+            metrics.requests_in_flight.inc();
+        }
         Instant::now()
     }
 
     fn on_result(&self, name: &str, success: bool, started_at: Instant) {
-        if let ApiMetrics::JsonRpcMetrics(JsonRpcMetrics {
-            requests_by_route,
-            req_latency_by_route,
-            errors_by_route,
-        }) = self
-        {
-            requests_by_route.with_label_values(&[name]).inc();
-            let req_latency_secs = (Instant::now() - started_at).as_secs_f64();
-            req_latency_by_route
-                .with_label_values(&[name])
-                .observe(req_latency_secs);
+        if let ApiMetrics::JsonRpcMetrics(metrics) = self {
+            let route = metrics.route(name);
+            metrics.requests_by_route.with_label_values(&[route]).inc();
+            let req_latency = Instant::now() - started_at;
+            metrics
+                .req_latency_by_route
+                .with_label_values(&[route])
+                .observe(req_latency.as_secs_f64());
             if !success {
-                errors_by_route.with_label_values(&[name]).inc();
+                metrics.errors_by_route.with_label_values(&[route]).inc();
             }
         }
     }
+
+    fn on_response(&self, _started_at: Instant) {
+        if let ApiMetrics::JsonRpcMetrics(metrics) = self {
+            metrics.requests_in_flight.dec();
+        }
+    }
 }
 
 pub trait SuiRpcModule
@@ -228,3 +719,72 @@ where
     fn rpc(self) -> RpcModule<Self>;
     fn rpc_doc_module() -> Module;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use socket2::SockRef;
+    use std::net::TcpStream;
+
+    #[test]
+    fn test_accepted_connections_inherit_keepalive() {
+        let keepalive = TcpKeepalive::new()
+            .with_time(Duration::from_secs(30))
+            .with_interval(Duration::from_secs(5))
+            .with_retries(3);
+        let listener = bind("127.0.0.1:0".parse().unwrap(), Some(&keepalive), false).unwrap();
+        listener.set_nonblocking(false).unwrap();
+
+        let _client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (accepted, _) = listener.accept().unwrap();
+        let socket = SockRef::from(&accepted);
+        assert!(socket.keepalive().unwrap());
+        assert_eq!(socket.keepalive_time().unwrap(), Duration::from_secs(30));
+        assert_eq!(socket.keepalive_interval().unwrap(), Duration::from_secs(5));
+        assert_eq!(socket.keepalive_retries().unwrap(), 3);
+    }
+
+    #[test]
+    fn test_slow_call_params_are_truncated() {
+        let metrics = JsonRpcMetrics::new(&prometheus::Registry::new())
+            .with_slow_query_threshold(Some(Duration::from_millis(10)));
+        let started_at = Instant::now() - Duration::from_secs(1);
+        let params = serde_json::json!(["0x".repeat(1000)]);
+
+        let call = metrics
+            .slow_call("sui_getObject", Some(&params), true, started_at)
+            .unwrap();
+        assert_eq!(call.method, "sui_getObject");
+        assert!(call.success);
+        assert!(call.duration_ms >= 1000);
+        assert_eq!(
+            call.params,
+            format!(
+                "{}... (2004 bytes)",
+                &params.to_string()[..SLOW_QUERY_MAX_PARAMS_LEN]
+            )
+        );
+
+        let params = serde_json::json!([1, "two"]);
+        let call = metrics
+            .slow_call("sui_getObject", Some(&params), false, started_at)
+            .unwrap();
+        assert_eq!(call.params, r#"[1,"two"]"#);
+        assert!(!call.success);
+
+        // Fast calls aren't logged.
+        assert!(metrics
+            .slow_call("sui_getObject", None, true, Instant::now())
+            .is_none());
+    }
+
+    #[test]
+    fn test_servers_share_address_with_reuse_port() {
+        let first = bind("127.0.0.1:0".parse().unwrap(), None, true).unwrap();
+        let address = first.local_addr().unwrap();
+        let second = bind(address, None, true).unwrap();
+        assert_eq!(second.local_addr().unwrap(), address);
+        // Without SO_REUSEPORT the address is taken.
+        assert!(bind(address, None, false).is_err());
+    }
+}
@@ -0,0 +1,75 @@
+// Copyright (c) 2022, Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::BTreeMap;
+
+/// How many items the list endpoints return, see `max-page-size`, `default-page-size` and
+/// `method-page-sizes`.
+#[derive(Clone, Debug)]
+pub struct PageSizes {
+    max: usize,
+    default: usize,
+    /// By method name, e.g. `sui_getEventsBySender`.
+    methods: BTreeMap<String, usize>,
+}
+
+impl PageSizes {
+    /// Pages of `max` items unless the client asks for fewer.
+    pub fn new(max: usize) -> Self {
+        Self {
+            max,
+            default: max,
+            methods: BTreeMap::new(),
+        }
+    }
+
+    /// Return `default` items, or those of `methods` for the methods listed, when the client
+    /// doesn't say how many. Still at most the max either way.
+    pub fn with_defaults(
+        mut self,
+        default: Option<usize>,
+        methods: BTreeMap<String, usize>,
+    ) -> Self {
+        self.default = default.unwrap_or(self.max);
+        self.methods = methods;
+        self
+    }
+
+    pub fn max(&self) -> usize {
+        self.max
+    }
+
+    /// Number of items `method` returns for a client asking for `requested`, or for nothing in
+    /// particular.
+    pub fn limit(&self, method: &str, requested: Option<u64>) -> usize {
+        let limit = match requested {
+            Some(requested) => usize::try_from(requested).unwrap_or(usize::MAX),
+            None => self.methods.get(method).copied().unwrap_or(self.default),
+        };
+        limit.min(self.max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_sizes() {
+        let methods = BTreeMap::from([
+            ("sui_getCoins".to_string(), 50),
+            ("sui_getEventsBySender".to_string(), 5000),
+        ]);
+        let sizes = PageSizes::new(1000).with_defaults(Some(100), methods);
+
+        // What the client asks for, up to the max.
+        assert_eq!(sizes.limit("sui_getCoins", Some(10)), 10);
+        assert_eq!(sizes.limit("sui_getCoins", Some(u64::MAX)), 1000);
+        // The default of the method, or the global one.
+        assert_eq!(sizes.limit("sui_getCoins", None), 50);
+        assert_eq!(sizes.limit("sui_getEventsBySender", None), 1000);
+        assert_eq!(sizes.limit("sui_getEventsByObject", None), 100);
+
+        assert_eq!(PageSizes::new(1000).limit("sui_getCoins", None), 1000);
+    }
+}
@@ -1,42 +1,214 @@
 // Copyright (c) 2022, Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::page_size::PageSizes;
+use crate::response_cache::{CacheKey, CachedResponse, ResponseCache};
 use crate::SuiRpcModule;
 use anyhow::anyhow;
 use async_trait::async_trait;
 use jsonrpsee::core::RpcResult;
 use jsonrpsee_core::server::rpc_module::RpcModule;
+use move_core_types::language_storage::StructTag;
+use move_core_types::parser::parse_struct_tag;
 use std::sync::Arc;
 use sui_core::authority::AuthorityState;
 use sui_core::gateway_state::GatewayTxSeqNumber;
 use sui_json_rpc_api::rpc_types::{
-    GetObjectDataResponse, SuiObjectInfo, SuiTransactionEffects, TransactionEffectsResponse,
+    GetObjectDataResponse, SuiCheckpointSignatures, SuiCheckpointSigner, SuiCoinBalance,
+    SuiCoinPage, SuiCoinSupply, SuiEffectsVerification, SuiObjectInfo, SuiObjectRef,
+    SuiObjectTypeCount, SuiObjectTypeFilter, SuiProtocolConfig, SuiPublishedPackage,
+    SuiPublishedPackagesPage, SuiRetentionWatermarks, SuiSharedObjectVersion, SuiStakeDistribution,
+    SuiTransactionBlockOptions, SuiTransactionBlockResponse, SuiTransactionEffects,
+    SuiValidatorCheckpoint, TransactionEffectsResponse,
 };
 use sui_json_rpc_api::RpcFullNodeReadApiServer;
 use sui_json_rpc_api::RpcReadApiServer;
 use sui_open_rpc::Module;
-use sui_types::base_types::{ObjectID, SuiAddress, TransactionDigest};
-use sui_types::object::Owner;
+use sui_types::base_types::{
+    ExecutionDigests, ObjectID, ObjectInfo, SequenceNumber, SuiAddress, TransactionDigest,
+};
+use sui_types::error::SuiError;
+use sui_types::messages_checkpoint::CheckpointSequenceNumber;
+use sui_types::object::{ObjectRead, Owner};
+use sui_types::protocol_config::ProtocolConfig;
+
+/// Maximum number of objects `getSharedObjectVersions` returns at once.
+const MAX_SHARED_OBJECTS_PER_REQUEST: usize = 1000;
 
 // An implementation of the read portion of the Gateway JSON-RPC interface intended for use in
 // Fullnodes.
 pub struct ReadApi {
     pub state: Arc<AuthorityState>,
+    /// The max of which is also the most transactions returned by a single range or recent
+    /// transactions query.
+    pub page_sizes: PageSizes,
+    /// Largest object returned as JSON, see [`ReadApi::check_object_size`].
+    pub max_object_size: usize,
+    /// Most pairs of digests a single `verifyEffectsBatch` call checks.
+    pub max_verification_batch: usize,
+    /// Shared by the instances of the server, see `json-rpc-server-instances`.
+    pub response_cache: Option<Arc<ResponseCache>>,
+    /// The checkpoint reads are answered as of, see `pin-reads-at-checkpoint`.
+    pub pinned_checkpoint: Option<CheckpointSequenceNumber>,
 }
 
 pub struct FullNodeApi {
     pub state: Arc<AuthorityState>,
+    pub page_sizes: PageSizes,
 }
 
 impl FullNodeApi {
-    pub fn new(state: Arc<AuthorityState>) -> Self {
-        Self { state }
+    pub fn new(state: Arc<AuthorityState>, page_sizes: PageSizes) -> Self {
+        Self { state, page_sizes }
     }
 }
 
 impl ReadApi {
-    pub fn new(state: Arc<AuthorityState>) -> Self {
-        Self { state }
+    pub fn new(
+        state: Arc<AuthorityState>,
+        page_sizes: PageSizes,
+        max_object_size: usize,
+        max_verification_batch: usize,
+    ) -> Self {
+        Self {
+            state,
+            page_sizes,
+            max_object_size,
+            max_verification_batch,
+            response_cache: None,
+            pinned_checkpoint: None,
+        }
+    }
+
+    /// Answer repeated requests whose response can't change from `cache`.
+    pub fn with_response_cache(mut self, cache: Arc<ResponseCache>) -> Self {
+        self.response_cache = Some(cache);
+        self
+    }
+
+    /// Answer reads as of `checkpoint`, from the past versions of objects. The node must know
+    /// the checkpoints of transactions, see [`AuthorityState::get_transaction_checkpoint`].
+    pub fn with_pinned_checkpoint(mut self, checkpoint: CheckpointSequenceNumber) -> Self {
+        self.pinned_checkpoint = Some(checkpoint);
+        self
+    }
+
+    fn cached(&self, key: &CacheKey) -> Option<CachedResponse> {
+        self.response_cache.as_ref()?.get(key)
+    }
+
+    fn cache(&self, key: CacheKey, response: CachedResponse) {
+        if let Some(cache) = &self.response_cache {
+            cache.put(key, response);
+        }
+    }
+
+    /// Fails until the node synced `min_checkpoint`, if set, and the pinned checkpoint. With
+    /// reads pinned, fails for good for a `min_checkpoint` after the pinned one.
+    fn ensure_synced(
+        &self,
+        min_checkpoint: Option<CheckpointSequenceNumber>,
+    ) -> Result<(), SuiError> {
+        if let Some(pinned) = self.pinned_checkpoint {
+            if matches!(min_checkpoint, Some(checkpoint) if checkpoint > pinned) {
+                return Err(SuiError::GenericAuthorityError {
+                    error: format!("Reads are pinned at checkpoint {pinned}"),
+                });
+            }
+            return self.state.ensure_checkpoint_synced(pinned);
+        }
+        match min_checkpoint {
+            Some(checkpoint) => self.state.ensure_checkpoint_synced(checkpoint),
+            None => Ok(()),
+        }
+    }
+
+    /// Fails with reads pinned, for `method` which can only answer for the latest state.
+    fn ensure_not_pinned(&self, method: &str) -> Result<(), SuiError> {
+        match self.pinned_checkpoint {
+            Some(pinned) => Err(SuiError::UnsupportedFeatureError {
+                error: format!("{method} isn't served with reads pinned at checkpoint {pinned}"),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// The objects of `owner`, of a type matching `type_filter` when set, as of the pinned
+    /// checkpoint with reads pinned.
+    fn owner_objects(
+        &self,
+        owner: Owner,
+        type_filter: Option<&SuiObjectTypeFilter>,
+    ) -> Result<Vec<ObjectInfo>, SuiError> {
+        match self.pinned_checkpoint {
+            Some(pinned) => {
+                let objects = match type_filter {
+                    Some(type_filter) => self.state.get_owner_objects_by_type_at_checkpoint(
+                        owner,
+                        type_filter,
+                        pinned,
+                    )?,
+                    None => self.state.get_owner_objects_at_checkpoint(owner, pinned)?,
+                };
+                Ok(objects
+                    .iter()
+                    .map(|(object_ref, object)| ObjectInfo::new(object_ref, object))
+                    .collect())
+            }
+            None => match type_filter {
+                Some(type_filter) => self.state.get_owner_objects_by_type(owner, type_filter),
+                None => self.state.get_owner_objects(owner),
+            },
+        }
+    }
+
+    /// The balances of `address`, as of the pinned checkpoint with reads pinned.
+    async fn balances(
+        &self,
+        address: SuiAddress,
+        coin_type: Option<StructTag>,
+    ) -> Result<Vec<SuiCoinBalance>, anyhow::Error> {
+        match self.pinned_checkpoint {
+            Some(pinned) => self
+                .state
+                .get_balances_at_checkpoint(address, coin_type, pinned),
+            None => self.state.get_balances(address, coin_type).await,
+        }
+    }
+
+    /// With reads pinned, whether transaction `digest` is in the pinned checkpoint or before.
+    fn is_visible(&self, digest: &TransactionDigest) -> Result<bool, SuiError> {
+        match self.pinned_checkpoint {
+            Some(pinned) => Ok(matches!(
+                self.state.get_transaction_checkpoint(digest)?,
+                Some(checkpoint) if checkpoint <= pinned
+            )),
+            None => Ok(true),
+        }
+    }
+
+    /// Fails with TransactionNotFound for a transaction of a checkpoint after the pinned one.
+    fn ensure_visible(&self, digest: &TransactionDigest) -> Result<(), SuiError> {
+        if !self.is_visible(digest)? {
+            return Err(SuiError::TransactionNotFound { digest: *digest });
+        }
+        Ok(())
+    }
+
+    /// Fails for an object over `max_object_size`, before it is turned into JSON. Sizes are the
+    /// approximation gas metering uses, which doesn't take serializing the object.
+    fn check_object_size(&self, read: ObjectRead) -> Result<ObjectRead, SuiError> {
+        if let ObjectRead::Exists(_, object, _) = &read {
+            let size = object.object_size_for_gas_metering();
+            if size > self.max_object_size {
+                return Err(SuiError::ObjectTooLargeForJson {
+                    object_id: object.id(),
+                    size,
+                    max_size: self.max_object_size,
+                });
+            }
+        }
+        Ok(read)
     }
 }
 
@@ -45,10 +217,26 @@ impl RpcReadApiServer for ReadApi {
     async fn get_objects_owned_by_address(
         &self,
         address: SuiAddress,
+        min_checkpoint: Option<CheckpointSequenceNumber>,
     ) -> RpcResult<Vec<SuiObjectInfo>> {
+        self.ensure_synced(min_checkpoint)?;
         Ok(self
-            .state
-            .get_owner_objects(Owner::AddressOwner(address))
+            .owner_objects(Owner::AddressOwner(address), None)
+            .map_err(|e| anyhow!("{e}"))?
+            .into_iter()
+            .map(SuiObjectInfo::from)
+            .collect())
+    }
+
+    async fn get_objects_owned_by_address_and_type(
+        &self,
+        address: SuiAddress,
+        type_filter: SuiObjectTypeFilter,
+        min_checkpoint: Option<CheckpointSequenceNumber>,
+    ) -> RpcResult<Vec<SuiObjectInfo>> {
+        self.ensure_synced(min_checkpoint)?;
+        Ok(self
+            .owner_objects(Owner::AddressOwner(address), Some(&type_filter))
             .map_err(|e| anyhow!("{e}"))?
             .into_iter()
             .map(SuiObjectInfo::from)
@@ -58,26 +246,112 @@ impl RpcReadApiServer for ReadApi {
     async fn get_objects_owned_by_object(
         &self,
         object_id: ObjectID,
+        min_checkpoint: Option<CheckpointSequenceNumber>,
     ) -> RpcResult<Vec<SuiObjectInfo>> {
+        self.ensure_synced(min_checkpoint)?;
         Ok(self
-            .state
-            .get_owner_objects(Owner::ObjectOwner(object_id.into()))
+            .owner_objects(Owner::ObjectOwner(object_id.into()), None)
             .map_err(|e| anyhow!("{e}"))?
             .into_iter()
             .map(SuiObjectInfo::from)
             .collect())
     }
 
-    async fn get_object(&self, object_id: ObjectID) -> RpcResult<GetObjectDataResponse> {
-        Ok(self
+    async fn verify_effects_batch(
+        &self,
+        claimed: Vec<ExecutionDigests>,
+    ) -> RpcResult<Vec<SuiEffectsVerification>> {
+        self.ensure_synced(None)?;
+        if claimed.len() > self.max_verification_batch {
+            return Err(anyhow!(
+                "Can't verify {} effects in a call, at most {}",
+                claimed.len(),
+                self.max_verification_batch
+            )
+            .into());
+        }
+        let mut verifications = self.state.verify_effects(&claimed)?;
+        if let Some(pinned) = self.pinned_checkpoint {
+            // Transactions after the pinned checkpoint read as not executed.
+            for verification in &mut verifications {
+                if !matches!(verification.checkpoint, Some(checkpoint) if checkpoint <= pinned) {
+                    verification.valid = false;
+                    verification.checkpoint = None;
+                }
+            }
+        }
+        Ok(verifications)
+    }
+
+    async fn get_object(
+        &self,
+        object_id: ObjectID,
+        min_checkpoint: Option<CheckpointSequenceNumber>,
+    ) -> RpcResult<GetObjectDataResponse> {
+        self.ensure_synced(min_checkpoint)?;
+        let key = CacheKey::Object(object_id);
+        if let Some(CachedResponse::Object(response)) = self.cached(&key) {
+            return Ok(response);
+        }
+        let read = match self.pinned_checkpoint {
+            Some(pinned) => {
+                self.state
+                    .get_object_read_at_checkpoint(&object_id, pinned)
+                    .await
+            }
+            None => self.state.get_object_read(&object_id).await,
+        };
+        let response: GetObjectDataResponse = read
+            .and_then(|read| self.check_object_size(read))
+            .map_err(|e| anyhow!("{e}"))?
+            .try_into()?;
+        // Only immutable objects stay as they are.
+        let immutable = matches!(
+            &response,
+            GetObjectDataResponse::Exists(object) if object.owner == Owner::Immutable
+        );
+        if immutable {
+            self.cache(key, CachedResponse::Object(response.clone()));
+        }
+        Ok(response)
+    }
+
+    async fn get_object_at_version(
+        &self,
+        object_id: ObjectID,
+        version: SequenceNumber,
+    ) -> RpcResult<GetObjectDataResponse> {
+        self.ensure_synced(None)?;
+        if let Some(pinned) = self.pinned_checkpoint {
+            // Versions are written in order, those up to the one as of the pinned checkpoint
+            // by its transactions or earlier ones.
+            let latest = self
+                .state
+                .get_parent_entry_at_checkpoint(object_id, pinned)?;
+            if !matches!(latest, Some((object_ref, _)) if object_ref.1 >= version) {
+                return Ok(GetObjectDataResponse::NotExists(object_id));
+            }
+        }
+        let key = CacheKey::ObjectAtVersion(object_id, version);
+        if let Some(CachedResponse::Object(response)) = self.cached(&key) {
+            return Ok(response);
+        }
+        let response: GetObjectDataResponse = self
             .state
-            .get_object_read(&object_id)
+            .get_past_object_read(&object_id, version)
             .await
+            .and_then(|read| self.check_object_size(read))
             .map_err(|e| anyhow!("{e}"))?
-            .try_into()?)
+            .try_into()?;
+        // A version which doesn't exist yet may be created later.
+        if let GetObjectDataResponse::Exists(_) = &response {
+            self.cache(key, CachedResponse::Object(response.clone()));
+        }
+        Ok(response)
     }
 
     async fn get_total_transaction_number(&self) -> RpcResult<u64> {
+        self.ensure_not_pinned("getTotalTransactionNumber")?;
         Ok(self.state.get_total_transaction_number()?)
     }
 
@@ -86,27 +360,197 @@ impl RpcReadApiServer for ReadApi {
         start: GatewayTxSeqNumber,
         end: GatewayTxSeqNumber,
     ) -> RpcResult<Vec<(GatewayTxSeqNumber, TransactionDigest)>> {
-        Ok(self.state.get_transactions_in_range(start, end)?)
+        // Ranges over the page size are cut short rather than rejected.
+        let end = end.min(start.saturating_add(self.page_sizes.max() as u64));
+        self.ensure_synced(None)?;
+        let mut transactions = self.state.get_transactions_in_range(start, end)?;
+        if self.pinned_checkpoint.is_some() {
+            // Sequence numbers are in local execution order, so transactions of later
+            // checkpoints may be anywhere in the range.
+            let mut visible = Vec::with_capacity(transactions.len());
+            for (seq, digest) in transactions {
+                if self.is_visible(&digest)? {
+                    visible.push((seq, digest));
+                }
+            }
+            transactions = visible;
+        }
+        Ok(transactions)
     }
 
     async fn get_recent_transactions(
         &self,
         count: u64,
     ) -> RpcResult<Vec<(GatewayTxSeqNumber, TransactionDigest)>> {
+        self.ensure_not_pinned("getRecentTransactions")?;
+        let count = count.min(self.page_sizes.max() as u64);
         Ok(self.state.get_recent_transactions(count)?)
     }
 
     async fn get_transaction(
         &self,
         digest: TransactionDigest,
+        min_checkpoint: Option<CheckpointSequenceNumber>,
     ) -> RpcResult<TransactionEffectsResponse> {
+        self.ensure_synced(min_checkpoint)?;
+        self.ensure_visible(&digest)?;
+        let key = CacheKey::Transaction(digest);
+        if let Some(CachedResponse::Transaction(response)) = self.cached(&key) {
+            return Ok(response);
+        }
         let (cert, effects) = self.state.get_transaction(digest).await?;
+        let response = TransactionEffectsResponse {
+            certificate: cert.try_into()?,
+            effects: SuiTransactionEffects::try_from(effects, &self.state.module_cache)?,
+            timestamp_ms: self.state.get_timestamp_ms(&digest).await?,
+        };
+        // The timestamp is only known once the transaction is indexed.
+        if response.timestamp_ms.is_some() {
+            self.cache(key, CachedResponse::Transaction(response.clone()));
+        }
+        Ok(response)
+    }
+
+    async fn get_transaction_block(
+        &self,
+        digest: TransactionDigest,
+        options: Option<SuiTransactionBlockOptions>,
+        min_checkpoint: Option<CheckpointSequenceNumber>,
+    ) -> RpcResult<SuiTransactionBlockResponse> {
+        self.ensure_synced(min_checkpoint)?;
+        self.ensure_visible(&digest)?;
+        Ok(self
+            .state
+            .get_transaction_block(digest, options.unwrap_or_default())
+            .await?)
+    }
+
+    async fn get_transaction_dependencies(
+        &self,
+        digest: TransactionDigest,
+        depth: Option<u64>,
+    ) -> RpcResult<Vec<(TransactionDigest, Vec<TransactionDigest>)>> {
+        // The dependencies of a transaction are all in its checkpoint or earlier ones.
+        self.ensure_synced(None)?;
+        self.ensure_visible(&digest)?;
+        Ok(self
+            .state
+            .get_transaction_dependencies(digest, depth.unwrap_or(1))
+            .await?)
+    }
+
+    async fn select_gas_coins(
+        &self,
+        address: SuiAddress,
+        budget: u64,
+        min_checkpoint: Option<CheckpointSequenceNumber>,
+    ) -> RpcResult<Vec<SuiObjectRef>> {
+        self.ensure_synced(min_checkpoint)?;
+        let coins = match self.pinned_checkpoint {
+            Some(pinned) => self
+                .state
+                .select_gas_coins_at_checkpoint(address, budget, pinned)?,
+            None => self.state.select_gas_coins(address, budget).await?,
+        };
+        Ok(coins.into_iter().map(SuiObjectRef::from).collect())
+    }
+
+    async fn get_balance(
+        &self,
+        address: SuiAddress,
+        coin_type: String,
+        min_checkpoint: Option<CheckpointSequenceNumber>,
+    ) -> RpcResult<SuiCoinBalance> {
+        self.ensure_synced(min_checkpoint)?;
+        let tag = parse_struct_tag(&coin_type)?;
+        let balance = self
+            .balances(address, Some(tag))
+            .await?
+            .pop()
+            .ok_or_else(|| anyhow!("No balance of {coin_type} for {address}"))?;
+        Ok(balance)
+    }
+
+    async fn get_coins(
+        &self,
+        address: SuiAddress,
+        coin_type: String,
+        largest_first: bool,
+        cursor: Option<ObjectID>,
+        exclude_locked: Option<bool>,
+        limit: Option<u64>,
+        min_checkpoint: Option<CheckpointSequenceNumber>,
+    ) -> RpcResult<SuiCoinPage> {
+        self.ensure_synced(min_checkpoint)?;
+        let tag = parse_struct_tag(&coin_type)?;
+        let limit = self.page_sizes.limit("sui_getCoins", limit);
+        Ok(match self.pinned_checkpoint {
+            Some(pinned) => self.state.get_coins_at_checkpoint(
+                address,
+                tag,
+                largest_first,
+                cursor,
+                limit,
+                pinned,
+            )?,
+            None => {
+                self.state
+                    .get_coins(
+                        address,
+                        tag,
+                        largest_first,
+                        exclude_locked.unwrap_or_default(),
+                        cursor,
+                        limit,
+                    )
+                    .await?
+            }
+        })
+    }
+
+    async fn get_all_balances(
+        &self,
+        address: SuiAddress,
+        min_checkpoint: Option<CheckpointSequenceNumber>,
+    ) -> RpcResult<Vec<SuiCoinBalance>> {
+        self.ensure_synced(min_checkpoint)?;
+        Ok(self.balances(address, None).await?)
+    }
+
+    async fn get_latest_modifying_transaction(
+        &self,
+        object_id: ObjectID,
+        min_checkpoint: Option<CheckpointSequenceNumber>,
+    ) -> RpcResult<TransactionEffectsResponse> {
+        self.ensure_synced(min_checkpoint)?;
+        let (cert, effects) = match self.pinned_checkpoint {
+            Some(pinned) => {
+                let (_, digest) = self
+                    .state
+                    .get_parent_entry_at_checkpoint(object_id, pinned)?
+                    .ok_or(SuiError::ObjectNotFound { object_id })?;
+                self.state.get_transaction(digest).await?
+            }
+            None => {
+                self.state
+                    .get_latest_modifying_transaction(object_id)
+                    .await?
+            }
+        };
+        let digest = *cert.digest();
         Ok(TransactionEffectsResponse {
             certificate: cert.try_into()?,
             effects: SuiTransactionEffects::try_from(effects, &self.state.module_cache)?,
             timestamp_ms: self.state.get_timestamp_ms(&digest).await?,
         })
     }
+
+    async fn get_protocol_config(&self) -> RpcResult<SuiProtocolConfig> {
+        Ok(SuiProtocolConfig {
+            epoch: self.state.committee.load().epoch,
+            config: ProtocolConfig::current(),
+        })
+    }
 }
 
 impl SuiRpcModule for ReadApi {
@@ -157,12 +601,161 @@ impl RpcFullNodeReadApiServer for FullNodeApi {
         Ok(self.state.get_transactions_from_addr(addr).await?)
     }
 
+    async fn get_transaction_count(&self, address: SuiAddress) -> RpcResult<u64> {
+        Ok(self.state.get_transaction_count(address).await?)
+    }
+
     async fn get_transactions_to_addr(
         &self,
         addr: SuiAddress,
     ) -> RpcResult<Vec<(GatewayTxSeqNumber, TransactionDigest)>> {
         Ok(self.state.get_transactions_to_addr(addr).await?)
     }
+
+    async fn get_checkpoint_signatures(
+        &self,
+        sequence_number: CheckpointSequenceNumber,
+    ) -> RpcResult<SuiCheckpointSignatures> {
+        match self.state.get_checkpoint_signers(sequence_number) {
+            Ok(Some(signers)) => Ok(SuiCheckpointSignatures {
+                sequence_number,
+                epoch: Some(signers.epoch),
+                certified: signers.certified,
+                signer_detail_available: true,
+                signed_stake: signers.signed_stake(),
+                quorum_threshold: signers.quorum_threshold,
+                signers: signers
+                    .signers
+                    .into_iter()
+                    .map(|(public_key, stake)| SuiCheckpointSigner { public_key, stake })
+                    .collect(),
+            }),
+            Ok(None) => Err(anyhow!("Checkpoint {sequence_number} not found").into()),
+            // Full nodes do not keep a checkpoint store, so there is no signer detail to return.
+            Err(SuiError::UnsupportedFeatureError { .. }) => Ok(SuiCheckpointSignatures {
+                sequence_number,
+                epoch: None,
+                certified: false,
+                signer_detail_available: false,
+                signers: vec![],
+                signed_stake: 0,
+                quorum_threshold: self.state.committee.load().quorum_threshold(),
+            }),
+            Err(e) => Err(anyhow!("{e}").into()),
+        }
+    }
+
+    async fn get_shared_object_version(
+        &self,
+        object_id: ObjectID,
+    ) -> RpcResult<SuiSharedObjectVersion> {
+        let (version, contended) = self.state.get_shared_object_version(object_id)?;
+        Ok(SuiSharedObjectVersion {
+            object_id,
+            version,
+            contended,
+        })
+    }
+
+    async fn get_shared_object_versions(
+        &self,
+        object_ids: Vec<ObjectID>,
+    ) -> RpcResult<Vec<SuiSharedObjectVersion>> {
+        if object_ids.len() > MAX_SHARED_OBJECTS_PER_REQUEST {
+            return Err(anyhow!(
+                "At most {MAX_SHARED_OBJECTS_PER_REQUEST} objects can be requested at once, got {}",
+                object_ids.len()
+            )
+            .into());
+        }
+        let mut versions = Vec::with_capacity(object_ids.len());
+        for object_id in object_ids {
+            versions.push(self.get_shared_object_version(object_id).await?);
+        }
+        Ok(versions)
+    }
+
+    async fn get_total_supply(&self, coin_type: String) -> RpcResult<SuiCoinSupply> {
+        let tag = parse_struct_tag(&coin_type)?;
+        let (total_supply, treasury_cap) = self.state.get_total_supply(&tag).await?;
+        Ok(SuiCoinSupply {
+            coin_type,
+            total_supply,
+            treasury_cap,
+        })
+    }
+
+    async fn get_stake_distribution(&self) -> RpcResult<SuiStakeDistribution> {
+        Ok(self.state.get_sui_system_state_object().await?.into())
+    }
+
+    async fn get_object_type_stats(
+        &self,
+        offset: u64,
+        limit: Option<u64>,
+    ) -> RpcResult<Vec<SuiObjectTypeCount>> {
+        let limit = self.page_sizes.limit("sui_getObjectTypeStats", limit);
+        Ok(self
+            .state
+            .get_object_type_stats(offset as usize, limit)?
+            .into_iter()
+            .map(|(object_type, count)| SuiObjectTypeCount { object_type, count })
+            .collect())
+    }
+
+    async fn get_published_packages(
+        &self,
+        since_checkpoint: CheckpointSequenceNumber,
+        cursor: Option<GatewayTxSeqNumber>,
+        limit: Option<u64>,
+    ) -> RpcResult<SuiPublishedPackagesPage> {
+        let limit = self.page_sizes.limit("sui_getPublishedPackages", limit);
+        // One more than a page, whose sequence number is the cursor of the next page.
+        let mut packages =
+            self.state
+                .get_published_packages(since_checkpoint, cursor, limit + 1)?;
+        let next_cursor = packages.get(limit).map(|(seq, ..)| *seq);
+        packages.truncate(limit);
+        Ok(SuiPublishedPackagesPage {
+            data: packages
+                .into_iter()
+                .map(
+                    |(sequence_number, package_id, publisher, transaction_digest)| {
+                        SuiPublishedPackage {
+                            package_id,
+                            publisher,
+                            transaction_digest,
+                            sequence_number,
+                        }
+                    },
+                )
+                .collect(),
+            next_cursor,
+        })
+    }
+
+    async fn get_retention_watermarks(&self) -> RpcResult<SuiRetentionWatermarks> {
+        let watermarks = self.state.retention_watermarks().await?;
+        Ok(SuiRetentionWatermarks {
+            objects: watermarks.objects,
+            batches: watermarks.batches,
+            checkpoints: watermarks.checkpoints,
+            events: watermarks.events,
+        })
+    }
+
+    async fn get_validator_checkpoints(&self) -> RpcResult<Vec<SuiValidatorCheckpoint>> {
+        Ok(self
+            .state
+            .validator_checkpoints()
+            .into_iter()
+            .map(|(public_key, report)| SuiValidatorCheckpoint {
+                public_key,
+                sequence_number: report.map(|(seq, _)| seq),
+                staleness_ms: report.map(|(_, staleness)| staleness.as_millis() as u64),
+            })
+            .collect())
+    }
 }
 
 impl SuiRpcModule for FullNodeApi {
@@ -0,0 +1,120 @@
+// Copyright (c) 2022, Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use lru::LruCache;
+use parking_lot::Mutex;
+use prometheus::{IntCounter, Registry};
+use std::time::{Duration, Instant};
+use sui_core::metrics::{self, MetricsNamespace};
+use sui_json_rpc_api::rpc_types::{GetObjectDataResponse, TransactionEffectsResponse};
+use sui_types::base_types::{ObjectID, SequenceNumber, TransactionDigest};
+
+/// The request a response answers, a read API method with its params.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub enum CacheKey {
+    Object(ObjectID),
+    ObjectAtVersion(ObjectID, SequenceNumber),
+    Transaction(TransactionDigest),
+}
+
+#[derive(Clone)]
+pub enum CachedResponse {
+    Object(GetObjectDataResponse),
+    Transaction(TransactionEffectsResponse),
+}
+
+/// An in-memory LRU cache of the responses of read API calls whose result can never change,
+/// such as immutable objects, past versions of objects and executed transactions, so that
+/// popular ones are neither read from the store nor converted again on every call.
+///
+/// Callers only put responses they know won't change. Entries are dropped when they are the
+/// least recently used of a full cache, or once they are older than the TTL if there is one.
+pub struct ResponseCache {
+    entries: Mutex<LruCache<CacheKey, (Instant, CachedResponse)>>,
+    ttl: Option<Duration>,
+    hits: IntCounter,
+    misses: IntCounter,
+}
+
+impl ResponseCache {
+    pub fn new(entries: usize, ttl: Option<Duration>, registry: &Registry) -> Self {
+        let rpc = MetricsNamespace::new(registry, metrics::RPC);
+        Self {
+            entries: Mutex::new(LruCache::new(entries)),
+            ttl,
+            hits: rpc
+                .int_counter(
+                    "response_cache_hits",
+                    "Number of read API calls answered from the response cache",
+                )
+                .unwrap(),
+            misses: rpc
+                .int_counter(
+                    "response_cache_misses",
+                    "Number of cacheable read API calls the response cache didn't answer",
+                )
+                .unwrap(),
+        }
+    }
+
+    pub fn get(&self, key: &CacheKey) -> Option<CachedResponse> {
+        let mut entries = self.entries.lock();
+        let expired = match entries.get(key) {
+            Some((cached_at, response)) => {
+                if !matches!(self.ttl, Some(ttl) if cached_at.elapsed() >= ttl) {
+                    self.hits.inc();
+                    return Some(response.clone());
+                }
+                true
+            }
+            None => false,
+        };
+        if expired {
+            entries.pop(key);
+        }
+        self.misses.inc();
+        None
+    }
+
+    pub fn put(&self, key: CacheKey, response: CachedResponse) {
+        self.entries.lock().put(key, (Instant::now(), response));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response() -> CachedResponse {
+        CachedResponse::Object(GetObjectDataResponse::NotExists(ObjectID::random()))
+    }
+
+    #[test]
+    fn test_response_cache() {
+        let registry = Registry::new();
+        let cache = ResponseCache::new(1, None, &registry);
+        let key = CacheKey::Object(ObjectID::random());
+        assert!(cache.get(&key).is_none());
+        cache.put(key.clone(), response());
+        assert!(cache.get(&key).is_some());
+        assert_eq!((cache.hits.get(), cache.misses.get()), (1, 1));
+
+        // The least recently used response goes once the cache is full.
+        let other = CacheKey::Transaction(TransactionDigest::random());
+        cache.put(other.clone(), response());
+        assert!(cache.get(&key).is_none());
+        assert!(cache.get(&other).is_some());
+    }
+
+    #[test]
+    fn test_response_cache_ttl() {
+        let registry = Registry::new();
+        let cache = ResponseCache::new(10, Some(Duration::from_millis(20)), &registry);
+        let key = CacheKey::ObjectAtVersion(ObjectID::random(), SequenceNumber::from(1));
+        cache.put(key.clone(), response());
+        assert!(cache.get(&key).is_some());
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(cache.get(&key).is_none());
+        assert_eq!(cache.entries.lock().len(), 0);
+    }
+}
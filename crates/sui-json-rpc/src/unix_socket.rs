@@ -0,0 +1,290 @@
+// Copyright (c) 2022, Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Serves JSON-RPC over HTTP on a Unix domain socket, for clients running on the same host.
+//!
+//! jsonrpsee's HTTP server only binds TCP sockets, so requests are read with hyper, see
+//! [`crate::hyper_server`]. Single calls and batches are served the same way as on TCP, and
+//! calls that take longer than their [`CallTimeouts`] are answered with an error naming the
+//! method. The load of the node is signaled in the same headers as on TCP, see
+//! [`crate::backpressure`].
+
+use crate::backpressure::LoadSignal;
+use crate::call_timeout::CallTimeouts;
+use crate::hyper_server::{handle_request, ConnectionExecutor};
+use crate::ApiMetrics;
+use hyper::server::accept;
+use hyper::service::{make_service_fn, service_fn};
+use jsonrpsee_core::server::rpc_module::RpcModule;
+use std::convert::Infallible;
+use std::fs;
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::{oneshot, watch};
+use tokio::task::JoinHandle;
+use tokio_stream::wrappers::UnixListenerStream;
+use tracing::warn;
+
+/// Stops the server, closing its connections, and removes its socket file when dropped.
+pub struct UnixServerHandle {
+    path: PathBuf,
+    task: JoinHandle<()>,
+    shutdown: Option<oneshot::Sender<()>>,
+    _connections: watch::Sender<()>,
+}
+
+impl UnixServerHandle {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub(crate) async fn stopped(&mut self) {
+        let _ = (&mut self.task).await;
+    }
+
+    /// Stop accepting connections and wait up to `timeout` for those open to finish the
+    /// requests they are serving. Returns whether they did, the rest are closed on drop.
+    pub(crate) async fn drain(&mut self, timeout: Duration) -> bool {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+        tokio::time::timeout(timeout, &mut self.task).await.is_ok()
+    }
+}
+
+impl Drop for UnixServerHandle {
+    fn drop(&mut self) {
+        self.task.abort();
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Bind a socket at `path` with permissions `mode` and serve `module` on it.
+pub(crate) fn serve(
+    module: RpcModule<()>,
+    metrics: ApiMetrics,
+    timeouts: CallTimeouts,
+    load: Option<LoadSignal>,
+    path: &Path,
+    mode: u32,
+) -> anyhow::Result<UnixServerHandle> {
+    // A socket left behind by a node that didn't shut down cleanly would make the bind fail.
+    // Anything else at the path is not ours to remove.
+    if let Ok(metadata) = fs::symlink_metadata(path) {
+        anyhow::ensure!(
+            metadata.file_type().is_socket(),
+            "{} exists and is not a socket",
+            path.display()
+        );
+        fs::remove_file(path)?;
+    }
+    let listener = UnixListener::bind(path)?;
+    fs::set_permissions(path, fs::Permissions::from_mode(mode))?;
+
+    let module = Arc::new(module);
+    let timeouts = Arc::new(timeouts);
+    let make_service = make_service_fn(move |_: &UnixStream| {
+        let module = module.clone();
+        let metrics = metrics.clone();
+        let timeouts = timeouts.clone();
+        let load = load.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                let response =
+                    handle_request(module.clone(), metrics.clone(), timeouts.clone(), request);
+                let load = load.clone();
+                async move {
+                    let mut response = response.await?;
+                    if let Some(load) = load {
+                        load.add_headers(response.headers_mut());
+                    }
+                    Ok::<_, Infallible>(response)
+                }
+            }))
+        }
+    });
+    let (shutdown, stopping) = oneshot::channel();
+    let (executor, connections) = ConnectionExecutor::new();
+    let server = hyper::Server::builder(accept::from_stream(UnixListenerStream::new(listener)))
+        .executor(executor)
+        .serve(make_service)
+        .with_graceful_shutdown(async move {
+            let _ = stopping.await;
+        });
+    let task = tokio::spawn(async move {
+        if let Err(e) = server.await {
+            warn!("JSON-RPC Unix socket server stopped: {e}");
+        }
+    });
+
+    Ok(UnixServerHandle {
+        path: path.to_path_buf(),
+        task,
+        shutdown: Some(shutdown),
+        _connections: connections,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::JsonRpcMetrics;
+    use hyper::{header, Body, Request, StatusCode};
+    use serde_json::Value;
+
+    #[tokio::test]
+    async fn test_serve_on_unix_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rpc.sock");
+        // A stale socket is replaced.
+        std::os::unix::net::UnixListener::bind(&path).unwrap();
+
+        let mut module = RpcModule::new(());
+        module
+            .register_method("say_hello", |_, _| Ok("hello"))
+            .unwrap();
+        let metrics = ApiMetrics::JsonRpcMetrics(JsonRpcMetrics::new(&prometheus::Registry::new()));
+        let handle = serve(module, metrics, CallTimeouts::default(), None, &path, 0o600).unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        let stream = UnixStream::connect(&path).await.unwrap();
+        let (mut sender, connection) = hyper::client::conn::handshake(stream).await.unwrap();
+        tokio::spawn(connection);
+        let request = Request::post("/")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(
+                serde_json::json!([
+                    { "jsonrpc": "2.0", "method": "say_hello", "id": 1 },
+                    { "jsonrpc": "2.0", "method": "nope", "id": 2 },
+                ])
+                .to_string(),
+            ))
+            .unwrap();
+        let response = sender.send_request(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let responses: Vec<Value> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(responses[0]["result"], "hello");
+        assert_eq!(responses[0]["id"], 1);
+        assert!(responses[1].get("error").is_some());
+
+        drop(handle);
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_drain() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rpc.sock");
+        let serve_sleep = || {
+            let mut module = RpcModule::new(());
+            module
+                .register_async_method("sleep", |params, _| async move {
+                    let millis: u64 = params.one()?;
+                    tokio::time::sleep(Duration::from_millis(millis)).await;
+                    Ok("slept")
+                })
+                .unwrap();
+            let metrics = JsonRpcMetrics::new(&prometheus::Registry::new());
+            let in_flight = metrics.requests_in_flight.clone();
+            let metrics = ApiMetrics::JsonRpcMetrics(metrics);
+            let handle =
+                serve(module, metrics, CallTimeouts::default(), None, &path, 0o600).unwrap();
+            (handle, in_flight)
+        };
+        let call = |millis: u64| {
+            let path = path.clone();
+            tokio::spawn(async move {
+                let stream = UnixStream::connect(&path).await.unwrap();
+                let (mut sender, connection) =
+                    hyper::client::conn::handshake(stream).await.unwrap();
+                tokio::spawn(connection);
+                let body = serde_json::json!(
+                    { "jsonrpc": "2.0", "method": "sleep", "params": [millis], "id": 1 }
+                );
+                let request = Request::post("/")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap();
+                sender.send_request(request).await
+            })
+        };
+
+        // A request that completes within the drain window is served.
+        let (mut handle, in_flight) = serve_sleep();
+        let request = call(200);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(in_flight.get(), 1);
+        assert!(handle.drain(Duration::from_secs(5)).await);
+        let response = request.await.unwrap().unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(in_flight.get(), 0);
+        drop(handle);
+
+        // One that doesn't is cut off when the handle is dropped.
+        let (mut handle, in_flight) = serve_sleep();
+        let request = call(60_000);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!handle.drain(Duration::from_millis(50)).await);
+        assert_eq!(in_flight.get(), 1);
+        drop(handle);
+        assert!(request.await.unwrap().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_call_timeout() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rpc.sock");
+        let mut module = RpcModule::new(());
+        module
+            .register_async_method("sleep", |params, _| async move {
+                let millis: u64 = params.one()?;
+                tokio::time::sleep(Duration::from_millis(millis)).await;
+                Ok("slept")
+            })
+            .unwrap();
+        let metrics = JsonRpcMetrics::new(&prometheus::Registry::new());
+        let timeouts_by_route = metrics.timeouts_by_route.clone();
+        let timeouts = CallTimeouts::new(
+            None,
+            [("sleep".to_string(), Duration::from_millis(100))].into(),
+        );
+        let _handle = serve(
+            module,
+            ApiMetrics::JsonRpcMetrics(metrics),
+            timeouts,
+            None,
+            &path,
+            0o600,
+        )
+        .unwrap();
+
+        let stream = UnixStream::connect(&path).await.unwrap();
+        let (mut sender, connection) = hyper::client::conn::handshake(stream).await.unwrap();
+        tokio::spawn(connection);
+        let request = Request::post("/")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(
+                serde_json::json!([
+                    { "jsonrpc": "2.0", "method": "sleep", "params": [10], "id": 1 },
+                    { "jsonrpc": "2.0", "method": "sleep", "params": [60_000], "id": 2 },
+                ])
+                .to_string(),
+            ))
+            .unwrap();
+        let response = sender.send_request(request).await.unwrap();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let responses: Vec<Value> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(responses[0]["result"], "slept");
+        assert_eq!(responses[1]["id"], 2);
+        assert_eq!(
+            responses[1]["error"]["message"],
+            "sleep timed out after 100ms"
+        );
+        assert_eq!(timeouts_by_route.with_label_values(&["sleep"]).get(), 1);
+    }
+}
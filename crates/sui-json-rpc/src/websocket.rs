@@ -0,0 +1,179 @@
+// Copyright (c) 2022, Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Closes the websocket connections of clients that went away without closing them.
+//!
+//! jsonrpsee's websocket server pings its connections but never sees the pongs, so a server
+//! with an idle timeout only accepts connections on loopback, from a proxy listening on its
+//! address. The proxy closes the connections nothing was received on for the timeout and
+//! counts them in `rpc_ws_dropped_on_timeout`. Pongs are traffic too, so that clients
+//! answering the pings stay connected however long they don't call anything.
+
+use jsonrpsee::ws_server::WsServerHandle;
+use prometheus::IntCounter;
+use std::io;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+/// How long accepting pauses after it failed, e.g. because the process ran out of file
+/// descriptors.
+const ACCEPT_ERROR_BACKOFF: Duration = Duration::from_millis(100);
+
+/// A running websocket server, with the proxy closing its idle connections if it has an idle
+/// timeout.
+pub struct WebsocketServerHandle {
+    server: WsServerHandle,
+    proxy: Option<JoinHandle<()>>,
+}
+
+impl WebsocketServerHandle {
+    pub(crate) fn new(server: WsServerHandle, proxy: Option<JoinHandle<()>>) -> Self {
+        Self { server, proxy }
+    }
+
+    /// Resolves once the server or its proxy stopped on its own. It must not be awaited again
+    /// after that.
+    pub async fn stopped(&mut self) {
+        match &mut self.proxy {
+            Some(proxy) => {
+                tokio::select! {
+                    _ = &mut self.server => (),
+                    _ = proxy => (),
+                }
+            }
+            None => (&mut self.server).await,
+        }
+    }
+
+    /// Close the server and its connections. Subscriptions never complete, so they aren't
+    /// waited for.
+    pub async fn stop(self) {
+        if let Some(proxy) = self.proxy {
+            proxy.abort();
+            let _ = proxy.await;
+        }
+        if let Ok(task) = self.server.stop() {
+            task.abort();
+            let _ = task.await;
+        }
+    }
+}
+
+/// Forward the connections accepted on `listener` to the server at `upstream`, closing those
+/// the client sent nothing on for `idle_timeout`, which are counted by `dropped`.
+pub(crate) fn close_idle_connections(
+    listener: TcpListener,
+    upstream: SocketAddr,
+    idle_timeout: Duration,
+    dropped: IntCounter,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            let (client, peer) = match listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    warn!("Websocket server failed to accept a connection: {e}");
+                    tokio::time::sleep(ACCEPT_ERROR_BACKOFF).await;
+                    continue;
+                }
+            };
+            let dropped = dropped.clone();
+            tokio::spawn(async move {
+                if let Err(e) = forward(client, upstream, idle_timeout, &dropped).await {
+                    debug!(?peer, "Websocket connection failed: {e}");
+                }
+            });
+        }
+    })
+}
+
+/// Copy the traffic between `client` and a new connection to `upstream` until either closes
+/// it, or until the client sent nothing for `idle_timeout`, which `dropped` counts. Both
+/// connections are closed when it returns.
+async fn forward(
+    client: TcpStream,
+    upstream: SocketAddr,
+    idle_timeout: Duration,
+    dropped: &IntCounter,
+) -> io::Result<()> {
+    let server = TcpStream::connect(upstream).await?;
+    client.set_nodelay(true)?;
+    server.set_nodelay(true)?;
+    let (mut client_read, mut client_write) = client.into_split();
+    let (mut server_read, mut server_write) = server.into_split();
+    let to_client = tokio::io::copy(&mut server_read, &mut client_write);
+    let to_server = async {
+        let mut buffer = vec![0; 8192];
+        loop {
+            match tokio::time::timeout(idle_timeout, client_read.read(&mut buffer)).await {
+                Err(_) => {
+                    debug!("Closing websocket connection idle for {idle_timeout:?}");
+                    dropped.inc();
+                    return Ok(());
+                }
+                Ok(Ok(0)) => return Ok(()),
+                Ok(Ok(read)) => server_write.write_all(&buffer[..read]).await?,
+                Ok(Err(e)) => return Err(e),
+            }
+        }
+    };
+    tokio::select! {
+        copied = to_client => copied.map(|_| ()),
+        forwarded = to_server => forwarded,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prometheus::Registry;
+    use sui_core::metrics::{self, MetricsNamespace};
+
+    #[tokio::test]
+    async fn test_idle_connections_are_closed() {
+        // An echo server stands in for the websocket server.
+        let upstream = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let upstream_addr = upstream.local_addr().unwrap();
+        tokio::spawn(async move {
+            loop {
+                let (mut connection, _) = upstream.accept().await.unwrap();
+                tokio::spawn(async move {
+                    let (mut read, mut write) = connection.split();
+                    let _ = tokio::io::copy(&mut read, &mut write).await;
+                });
+            }
+        });
+        let registry = Registry::new();
+        let dropped = MetricsNamespace::new(&registry, metrics::RPC)
+            .int_counter("ws_dropped_on_timeout", "test")
+            .unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let idle_timeout = Duration::from_millis(200);
+        let proxy = close_idle_connections(listener, upstream_addr, idle_timeout, dropped.clone());
+
+        let mut client = TcpStream::connect(addr).await.unwrap();
+        // Traffic within the timeout keeps the connection open.
+        for _ in 0..3 {
+            tokio::time::sleep(idle_timeout / 2).await;
+            client.write_all(b"pong").await.unwrap();
+            let mut echoed = [0; 4];
+            client.read_exact(&mut echoed).await.unwrap();
+            assert_eq!(&echoed, b"pong");
+        }
+        assert_eq!(dropped.get(), 0);
+
+        // Once idle for the timeout, it is closed.
+        let mut buffer = [0; 4];
+        let read = tokio::time::timeout(idle_timeout * 5, client.read(&mut buffer))
+            .await
+            .expect("the idle connection is closed");
+        assert!(matches!(read, Ok(0) | Err(_)));
+        assert_eq!(dropped.get(), 1);
+        proxy.abort();
+    }
+}
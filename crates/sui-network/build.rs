@@ -94,9 +94,25 @@ fn main() -> Result<()> {
         )
         .build();
 
+    let effects_firehose_service = Service::builder()
+        .name("EffectsFirehose")
+        .package("sui.firehose")
+        .comment("The effects of every transaction executed by a full node, in order")
+        .method(
+            Method::builder()
+                .name("effects")
+                .route_name("Effects")
+                .input_type("sui_types::messages::EffectsFirehoseRequest")
+                .output_type("sui_types::messages::EffectsFirehoseItem")
+                .server_streaming()
+                .codec_path(codec_path)
+                .build(),
+        )
+        .build();
+
     Builder::new()
         .out_dir(&out_dir)
-        .compile(&[validator_service]);
+        .compile(&[validator_service, effects_firehose_service]);
 
     println!("cargo:rerun-if-changed=build.rs");
     println!("cargo:rerun-if-env-changed=DUMP_GENERATED_GRPC");
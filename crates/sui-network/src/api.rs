@@ -5,7 +5,16 @@ mod validator {
     include!(concat!(env!("OUT_DIR"), "/sui.validator.Validator.rs"));
 }
 
+mod firehose {
+    include!(concat!(env!("OUT_DIR"), "/sui.firehose.EffectsFirehose.rs"));
+}
+
 pub use validator::{
     validator_client::ValidatorClient,
     validator_server::{Validator, ValidatorServer},
 };
+
+pub use firehose::{
+    effects_firehose_client::EffectsFirehoseClient,
+    effects_firehose_server::{EffectsFirehose, EffectsFirehoseServer},
+};
@@ -0,0 +1,639 @@
+// Copyright (c) 2022, Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Admin endpoints served next to `/metrics`.
+//!
+//! `/admin/logging` reports (GET) or replaces (POST, body is an `EnvFilter` directive such as
+//! `info,sui_core::authority=debug`) the tracing filter of the running process. This only has
+//! an effect when logging was set up with [`crate::logging::init`], as the `sui-node` binary
+//! does. Requests from non-loopback peers are rejected unless remote admin access is enabled.
+//!
+//! `/ready` is reachable from any peer and answers 200 once the node is up and healthy, and 503
+//! before that or after the node stopped accepting work, e.g. because a storage write failed.
+//!
+//! `/admin/sync-peers/reload` (POST) reads the node's `sync-peers-file` again and starts or stops
+//! following validators to match it.
+//!
+//! `/admin/export/address/<address>` and `/admin/export/object/<object id>` stream the whole
+//! transaction history of an address or object as newline-delimited JSON, one certificate and
+//! its effects per line, in execution order. `?since=<sequence number>` skips the transactions
+//! executed before it, so an export can be continued from the `sequenceNumber` after its last
+//! line. This requires the node to keep extended indexes. A storage error in the middle of an
+//! export aborts the response, so exports that don't end with a newline are incomplete.
+//!
+//! `/admin/post-processing/pause` and `/admin/post-processing/resume` (POST) stop and restart
+//! indexing and event emission, which frees the IO they take for reads. Execution carries on
+//! while paused, and the transactions executed meanwhile are processed on resuming. The pause
+//! survives restarts until post processing is resumed, and is reported by the
+//! `post_processing_paused` and `post_processing_lag` metrics.
+//!
+//! `/admin/dead-letters` lists the transactions post processing gave up on as JSON, with the
+//! error of their last attempt. `/admin/dead-letters/reprocess` (POST) makes one more attempt
+//! at each of them, and keeps those that fail again.
+//!
+//! `/admin/quarantine` lists the transactions the re-execution verifier quarantined as JSON,
+//! with the effects stored for them and those they had when executed again.
+//! `/admin/quarantine/reexecute` (POST, body is a base64 transaction digest) executes a
+//! transaction again, quarantining it if its effects still differ and releasing it otherwise.
+//!
+//! `/admin/locks` lists the owned objects locked to a transaction this node signed but didn't
+//! execute yet as JSON, with the transaction and for how long the lock was held, to find the
+//! locks of abandoned transactions that keep objects from being used. `?owner=<address>`
+//! only lists the objects of an address, otherwise every lock is scanned.
+//!
+//! `/admin/diagnostics` reports the health of the node's subsystems as JSON, see
+//! [`crate::diagnostics`]. It answers 503 when the overall status is critical.
+//!
+//! `/admin/execution-load` reports the number of certificates waiting to be executed and the
+//! transactions executed per second as JSON, as last sampled every second, see
+//! [`sui_core::authority::ExecutionLoad`]. They are also exported as the
+//! `execution_queue_depth`, `execution_throughput` and `execution_throughput_average` metrics.
+
+use crate::diagnostics::{Diagnostics, Status};
+use crate::logging;
+use crate::verify_reexecution;
+use axum::{
+    body::StreamBody,
+    extract::{ConnectInfo, Extension, Path, Query},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    routing::{get, post},
+    Json, Router,
+};
+use base64ct::{Base64, Encoding};
+use futures::StreamExt;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::{
+    net::SocketAddr,
+    str::FromStr,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use sui_core::{
+    authority::{AuthorityState, PostProcessingControl},
+    history_export::{self, HistorySubject},
+};
+use sui_storage::{dead_letter_store::DeadLetterStore, quarantine_store::QuarantineStore};
+use sui_types::{
+    base_types::{ObjectDigest, ObjectID, SequenceNumber, SuiAddress, TransactionDigest},
+    batch::TxSequenceNumber,
+    messages::TransactionEffects,
+};
+use tracing::info;
+use tracing_subscriber::{reload, EnvFilter, Registry};
+
+pub const LOGGING_ROUTE: &str = "/admin/logging";
+pub const READINESS_ROUTE: &str = "/ready";
+pub const SYNC_PEERS_RELOAD_ROUTE: &str = "/admin/sync-peers/reload";
+pub const ADDRESS_EXPORT_ROUTE: &str = "/admin/export/address/:address";
+pub const OBJECT_EXPORT_ROUTE: &str = "/admin/export/object/:object_id";
+pub const DIAGNOSTICS_ROUTE: &str = "/admin/diagnostics";
+pub const LOCKS_ROUTE: &str = "/admin/locks";
+pub const EXECUTION_LOAD_ROUTE: &str = "/admin/execution-load";
+pub const POST_PROCESSING_PAUSE_ROUTE: &str = "/admin/post-processing/pause";
+pub const POST_PROCESSING_RESUME_ROUTE: &str = "/admin/post-processing/resume";
+pub const DEAD_LETTERS_ROUTE: &str = "/admin/dead-letters";
+pub const DEAD_LETTERS_REPROCESS_ROUTE: &str = "/admin/dead-letters/reprocess";
+pub const QUARANTINE_ROUTE: &str = "/admin/quarantine";
+pub const QUARANTINE_REEXECUTE_ROUTE: &str = "/admin/quarantine/reexecute";
+
+/// What the admin routes of a node answer with, set by the first node of the process that
+/// installs it and unset when that node shuts down, see [`release`].
+struct Hook<T>(RwLock<Option<(usize, T)>>);
+
+impl<T> Hook<T> {
+    const fn new() -> Self {
+        Self(parking_lot::const_rwlock(None))
+    }
+
+    fn set(&self, node: &Arc<AuthorityState>, value: T) {
+        let mut hook = self.0.write();
+        if hook.is_none() {
+            *hook = Some((Arc::as_ptr(node) as usize, value));
+        }
+    }
+
+    fn release(&self, node: &Arc<AuthorityState>) {
+        let mut hook = self.0.write();
+        if matches!(&*hook, Some((owner, _)) if *owner == Arc::as_ptr(node) as usize) {
+            *hook = None;
+        }
+    }
+
+    fn with<R>(&self, f: impl FnOnce(&T) -> R) -> Option<R> {
+        self.0.read().as_ref().map(|(_, value)| f(value))
+    }
+}
+
+type ReadinessCheck = Box<dyn Fn() -> Result<(), String> + Send + Sync>;
+
+static READINESS_CHECK: Hook<ReadinessCheck> = Hook::new();
+
+static EXPORT_STATE: Hook<Arc<AuthorityState>> = Hook::new();
+
+static DIAGNOSTICS: Hook<Diagnostics> = Hook::new();
+
+static POST_PROCESSING_CONTROL: Hook<PostProcessingControl> = Hook::new();
+
+static DEAD_LETTERS: Hook<(Arc<AuthorityState>, Arc<DeadLetterStore>)> = Hook::new();
+
+static QUARANTINE: Hook<(Arc<AuthorityState>, Arc<QuarantineStore>)> = Hook::new();
+
+type SyncPeersReload = Box<dyn Fn() -> Result<usize, String> + Send + Sync>;
+
+static SYNC_PEERS_RELOAD: Hook<SyncPeersReload> = Hook::new();
+
+/// Install the check answering [`READINESS_ROUTE`]. Until this is called the node reports that
+/// it is not ready. When several nodes run in one process only the first one is reported.
+pub fn set_readiness_check(
+    node: &Arc<AuthorityState>,
+    check: impl Fn() -> Result<(), String> + Send + Sync + 'static,
+) {
+    READINESS_CHECK.set(node, Box::new(check));
+}
+
+/// Install what [`SYNC_PEERS_RELOAD_ROUTE`] runs, returning the number of peers now followed.
+/// When several nodes run in one process only the first one is reloaded.
+pub fn set_sync_peers_reload(
+    node: &Arc<AuthorityState>,
+    reload: impl Fn() -> Result<usize, String> + Send + Sync + 'static,
+) {
+    SYNC_PEERS_RELOAD.set(node, Box::new(reload));
+}
+
+/// Install the authority whose history the export routes serve, whose locks [`LOCKS_ROUTE`]
+/// lists, and whose load [`EXECUTION_LOAD_ROUTE`] reports. When several nodes run in one
+/// process only the first one is exported.
+pub fn set_export_state(state: Arc<AuthorityState>) {
+    EXPORT_STATE.set(&state, state.clone());
+}
+
+/// Install what [`DIAGNOSTICS_ROUTE`] reports on. When several nodes run in one process only
+/// the first one is reported.
+pub fn set_diagnostics(node: &Arc<AuthorityState>, diagnostics: Diagnostics) {
+    DIAGNOSTICS.set(node, diagnostics);
+}
+
+/// Install what [`POST_PROCESSING_PAUSE_ROUTE`] and [`POST_PROCESSING_RESUME_ROUTE`] pause and
+/// resume. When several nodes run in one process only the first one is controlled.
+pub fn set_post_processing_control(node: &Arc<AuthorityState>, control: PostProcessingControl) {
+    POST_PROCESSING_CONTROL.set(node, control);
+}
+
+/// Install the dead letters [`DEAD_LETTERS_ROUTE`] lists and [`DEAD_LETTERS_REPROCESS_ROUTE`]
+/// reprocesses. When several nodes run in one process only the first one is served.
+pub fn set_dead_letters(node: &Arc<AuthorityState>, dead_letters: Arc<DeadLetterStore>) {
+    DEAD_LETTERS.set(node, (node.clone(), dead_letters));
+}
+
+/// Install the quarantine [`QUARANTINE_ROUTE`] lists and [`QUARANTINE_REEXECUTE_ROUTE`]
+/// updates. When several nodes run in one process only the first one is served.
+pub fn set_quarantine(node: &Arc<AuthorityState>, quarantine: Arc<QuarantineStore>) {
+    QUARANTINE.set(node, (node.clone(), quarantine));
+}
+
+/// Unset what `node` installed, so that it can be dropped and its stores opened again, and the
+/// routes answer as if it never started.
+pub fn release(node: &Arc<AuthorityState>) {
+    READINESS_CHECK.release(node);
+    SYNC_PEERS_RELOAD.release(node);
+    EXPORT_STATE.release(node);
+    DIAGNOSTICS.release(node);
+    POST_PROCESSING_CONTROL.release(node);
+    DEAD_LETTERS.release(node);
+    QUARANTINE.release(node);
+}
+
+#[derive(Clone, Copy)]
+struct AdminAccess {
+    allow_remote: bool,
+}
+
+pub(crate) fn router(allow_remote: bool) -> Router {
+    Router::new()
+        .route(LOGGING_ROUTE, get(get_log_filter).post(set_log_filter))
+        .route(READINESS_ROUTE, get(readiness))
+        .route(SYNC_PEERS_RELOAD_ROUTE, post(reload_sync_peers))
+        .route(ADDRESS_EXPORT_ROUTE, get(export_address_history))
+        .route(OBJECT_EXPORT_ROUTE, get(export_object_history))
+        .route(DIAGNOSTICS_ROUTE, get(diagnostics))
+        .route(LOCKS_ROUTE, get(locked_objects))
+        .route(EXECUTION_LOAD_ROUTE, get(execution_load))
+        .route(POST_PROCESSING_PAUSE_ROUTE, post(pause_post_processing))
+        .route(POST_PROCESSING_RESUME_ROUTE, post(resume_post_processing))
+        .route(DEAD_LETTERS_ROUTE, get(dead_letters))
+        .route(DEAD_LETTERS_REPROCESS_ROUTE, post(reprocess_dead_letters))
+        .route(QUARANTINE_ROUTE, get(quarantined))
+        .route(QUARANTINE_REEXECUTE_ROUTE, post(reexecute_quarantined))
+        .layer(Extension(AdminAccess { allow_remote }))
+}
+
+fn check_access(access: AdminAccess, peer: SocketAddr) -> Result<(), (StatusCode, String)> {
+    if access.allow_remote || peer.ip().is_loopback() {
+        Ok(())
+    } else {
+        Err((
+            StatusCode::FORBIDDEN,
+            "admin endpoints are only reachable from localhost".to_string(),
+        ))
+    }
+}
+
+fn filter_handle() -> Result<&'static reload::Handle<EnvFilter, Registry>, (StatusCode, String)> {
+    logging::filter_handle().ok_or_else(|| {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            "logging was not initialized with a reloadable filter".to_string(),
+        )
+    })
+}
+
+async fn get_log_filter(
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    Extension(access): Extension<AdminAccess>,
+) -> (StatusCode, String) {
+    let result = check_access(access, peer)
+        .and_then(|_| filter_handle())
+        .and_then(|handle| {
+            handle
+                .with_current(|filter| filter.to_string())
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+        });
+    match result {
+        Ok(filter) => (StatusCode::OK, filter),
+        Err(error) => error,
+    }
+}
+
+async fn set_log_filter(
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    Extension(access): Extension<AdminAccess>,
+    directive: String,
+) -> (StatusCode, String) {
+    let result = check_access(access, peer)
+        .and_then(|_| filter_handle())
+        .and_then(|handle| {
+            let filter = EnvFilter::try_new(directive.trim())
+                .map_err(|e| (StatusCode::BAD_REQUEST, format!("invalid filter: {e}")))?;
+            handle
+                .reload(filter)
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+        });
+    match result {
+        Ok(()) => {
+            info!(%peer, filter = directive.trim(), "Log filter updated");
+            (StatusCode::OK, directive.trim().to_string())
+        }
+        Err(error) => error,
+    }
+}
+
+async fn readiness() -> (StatusCode, String) {
+    match READINESS_CHECK.with(|check| check()) {
+        Some(Ok(())) => (StatusCode::OK, "ready".to_string()),
+        Some(Err(error)) => (StatusCode::SERVICE_UNAVAILABLE, error),
+        None => (StatusCode::SERVICE_UNAVAILABLE, "starting".to_string()),
+    }
+}
+
+async fn reload_sync_peers(
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    Extension(access): Extension<AdminAccess>,
+) -> (StatusCode, String) {
+    if let Err(error) = check_access(access, peer) {
+        return error;
+    }
+    match SYNC_PEERS_RELOAD.with(|reload| reload()) {
+        Some(Ok(num_peers)) => {
+            info!(%peer, num_peers, "Sync peers reloaded");
+            (StatusCode::OK, format!("following {num_peers} peers"))
+        }
+        Some(Err(error)) => (StatusCode::INTERNAL_SERVER_ERROR, error),
+        None => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            "this node doesn't follow other validators".to_string(),
+        ),
+    }
+}
+
+async fn pause_post_processing(
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    Extension(access): Extension<AdminAccess>,
+) -> (StatusCode, String) {
+    set_post_processing_paused(access, peer, true)
+}
+
+async fn resume_post_processing(
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    Extension(access): Extension<AdminAccess>,
+) -> (StatusCode, String) {
+    set_post_processing_paused(access, peer, false)
+}
+
+fn set_post_processing_paused(
+    access: AdminAccess,
+    peer: SocketAddr,
+    paused: bool,
+) -> (StatusCode, String) {
+    if let Err(error) = check_access(access, peer) {
+        return error;
+    }
+    let result = match POST_PROCESSING_CONTROL.with(|control| control.set_paused(paused)) {
+        Some(result) => result,
+        None => {
+            return (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "this node doesn't run post processing".to_string(),
+            )
+        }
+    };
+    match result {
+        Ok(()) => {
+            let state = if paused { "paused" } else { "resumed" };
+            info!(%peer, "Post processing {state}");
+            (StatusCode::OK, format!("post processing {state}"))
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+#[derive(Serialize)]
+struct DeadLetterReport {
+    seq: TxSequenceNumber,
+    tx_digest: TransactionDigest,
+    error: String,
+}
+
+fn dead_letter_store() -> Result<(Arc<AuthorityState>, Arc<DeadLetterStore>), (StatusCode, String)>
+{
+    DEAD_LETTERS.with(|hook| hook.clone()).ok_or_else(|| {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            "this node doesn't run post processing".to_string(),
+        )
+    })
+}
+
+async fn dead_letters(
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    Extension(access): Extension<AdminAccess>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    check_access(access, peer)?;
+    let (_, dead_letters) = dead_letter_store()?;
+    let report: Vec<_> = dead_letters
+        .iter()
+        .map(|(seq, (tx_digest, error))| DeadLetterReport {
+            seq,
+            tx_digest,
+            error,
+        })
+        .collect();
+    Ok(Json(report))
+}
+
+async fn reprocess_dead_letters(
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    Extension(access): Extension<AdminAccess>,
+) -> (StatusCode, String) {
+    let result = check_access(access, peer).and_then(|_| dead_letter_store());
+    let (state, dead_letters) = match result {
+        Ok(hook) => hook,
+        Err(error) => return error,
+    };
+    match state.reprocess_dead_letters(&dead_letters).await {
+        Ok(reprocessed) => {
+            let remaining = dead_letters.count();
+            info!(%peer, reprocessed, remaining, "Dead letters reprocessed");
+            (
+                StatusCode::OK,
+                format!("reprocessed {reprocessed} transactions, {remaining} left"),
+            )
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+#[derive(Serialize)]
+struct QuarantinedReport {
+    tx_digest: TransactionDigest,
+    stored: TransactionEffects,
+    reexecuted: TransactionEffects,
+}
+
+fn quarantine_store() -> Result<(Arc<AuthorityState>, Arc<QuarantineStore>), (StatusCode, String)> {
+    QUARANTINE.with(|hook| hook.clone()).ok_or_else(|| {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            "this node doesn't run the re-execution verifier".to_string(),
+        )
+    })
+}
+
+async fn quarantined(
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    Extension(access): Extension<AdminAccess>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    check_access(access, peer)?;
+    let (_, quarantine) = quarantine_store()?;
+    let report: Vec<_> = quarantine
+        .iter()
+        .map(|(tx_digest, (stored, reexecuted))| QuarantinedReport {
+            tx_digest,
+            stored,
+            reexecuted,
+        })
+        .collect();
+    Ok(Json(report))
+}
+
+async fn reexecute_quarantined(
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    Extension(access): Extension<AdminAccess>,
+    digest: String,
+) -> (StatusCode, String) {
+    let result = check_access(access, peer).and_then(|_| quarantine_store());
+    let (state, quarantine) = match result {
+        Ok(hook) => hook,
+        Err(error) => return error,
+    };
+    let digest = match Base64::decode_vec(digest.trim())
+        .ok()
+        .and_then(|bytes| TransactionDigest::try_from(bytes.as_slice()).ok())
+    {
+        Some(digest) => digest,
+        None => {
+            return (
+                StatusCode::BAD_REQUEST,
+                "invalid transaction digest".to_string(),
+            )
+        }
+    };
+    match verify_reexecution(&state, &quarantine, &digest).await {
+        Ok(Some(diverges)) => {
+            info!(%peer, ?digest, diverges, "Transaction executed again");
+            let outcome = if diverges {
+                "effects still differ, transaction quarantined"
+            } else {
+                "effects match, transaction released from quarantine"
+            };
+            (StatusCode::OK, outcome.to_string())
+        }
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            "transaction wasn't executed, or an object version it read was pruned".to_string(),
+        ),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+async fn diagnostics(
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    Extension(access): Extension<AdminAccess>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    check_access(access, peer)?;
+    let report = DIAGNOSTICS
+        .with(|diagnostics| diagnostics.report())
+        .ok_or_else(|| (StatusCode::SERVICE_UNAVAILABLE, "starting".to_string()))?;
+    let status = match report.status {
+        Status::Critical => StatusCode::SERVICE_UNAVAILABLE,
+        Status::Healthy | Status::Degraded => StatusCode::OK,
+    };
+    Ok((status, Json(report)))
+}
+
+async fn execution_load(
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    Extension(access): Extension<AdminAccess>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    check_access(access, peer)?;
+    let state = EXPORT_STATE
+        .with(|state| state.clone())
+        .ok_or_else(|| (StatusCode::SERVICE_UNAVAILABLE, "starting".to_string()))?;
+    Ok(Json(state.execution_load()))
+}
+
+#[derive(Deserialize)]
+struct LocksQuery {
+    owner: Option<String>,
+}
+
+#[derive(Serialize)]
+struct LockedObjectReport {
+    object_id: ObjectID,
+    version: SequenceNumber,
+    digest: ObjectDigest,
+    tx_digest: TransactionDigest,
+    /// None for the locks taken before lock times were recorded.
+    locked_for_ms: Option<u64>,
+}
+
+async fn locked_objects(
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    Extension(access): Extension<AdminAccess>,
+    Query(query): Query<LocksQuery>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    check_access(access, peer)?;
+    let owner = query
+        .owner
+        .map(|owner| SuiAddress::from_str(&owner))
+        .transpose()
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("invalid address: {e}")))?;
+    let state = EXPORT_STATE
+        .with(|state| state.clone())
+        .ok_or_else(|| (StatusCode::SERVICE_UNAVAILABLE, "starting".to_string()))?;
+    let locked = state
+        .get_locked_objects(owner)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let now_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or(0);
+    let report: Vec<_> = locked
+        .into_iter()
+        .map(|lock| {
+            let (object_id, version, digest) = lock.object_ref;
+            LockedObjectReport {
+                object_id,
+                version,
+                digest,
+                tx_digest: lock.tx_digest,
+                locked_for_ms: lock
+                    .locked_at_ms
+                    .map(|locked_at| now_ms.saturating_sub(locked_at)),
+            }
+        })
+        .collect();
+    Ok(Json(report))
+}
+
+#[derive(Deserialize)]
+struct ExportQuery {
+    #[serde(default)]
+    since: TxSequenceNumber,
+}
+
+async fn export_address_history(
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    Extension(access): Extension<AdminAccess>,
+    Path(address): Path<String>,
+    Query(query): Query<ExportQuery>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    check_access(access, peer)?;
+    let address = SuiAddress::from_str(&address)
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("invalid address: {e}")))?;
+    export_history(HistorySubject::Address(address), query.since)
+}
+
+async fn export_object_history(
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    Extension(access): Extension<AdminAccess>,
+    Path(object_id): Path<String>,
+    Query(query): Query<ExportQuery>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    check_access(access, peer)?;
+    let object_id = ObjectID::from_str(&object_id)
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("invalid object id: {e}")))?;
+    export_history(HistorySubject::Object(object_id), query.since)
+}
+
+fn export_history(
+    subject: HistorySubject,
+    since: TxSequenceNumber,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let state = EXPORT_STATE
+        .with(|state| state.clone())
+        .ok_or_else(|| (StatusCode::SERVICE_UNAVAILABLE, "starting".to_string()))?;
+    let records = history_export::history(state, subject, since)
+        .map_err(|e| (StatusCode::SERVICE_UNAVAILABLE, e.to_string()))?;
+    info!(?subject, since, "Exporting history");
+    let lines = records.map(|record| record.and_then(|record| record.to_ndjson_line()));
+    Ok((
+        [(header::CONTENT_TYPE, "application/x-ndjson")],
+        StreamBody::new(lines),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_admin_access_is_local_only_by_default() {
+        let local: SocketAddr = "127.0.0.1:9184".parse().unwrap();
+        let remote: SocketAddr = "10.0.0.1:9184".parse().unwrap();
+
+        let local_only = AdminAccess {
+            allow_remote: false,
+        };
+        assert!(check_access(local_only, local).is_ok());
+        assert_eq!(
+            check_access(local_only, remote).unwrap_err().0,
+            StatusCode::FORBIDDEN
+        );
+
+        let open = AdminAccess { allow_remote: true };
+        assert!(check_access(open, remote).is_ok());
+    }
+}
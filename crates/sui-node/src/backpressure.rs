@@ -0,0 +1,104 @@
+// Copyright (c) 2022, Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Measures the load of the node for the JSON-RPC server to signal to its clients, see
+//! [`sui_json_rpc::backpressure`].
+//!
+//! The load is sampled periodically rather than on every request, since reading the execution
+//! queue walks the pending certificates.
+
+use std::sync::Arc;
+use std::time::Duration;
+use sui_config::RpcBackpressureConfig;
+use sui_core::authority::AuthorityState;
+use sui_json_rpc::backpressure::{LoadLevel, LoadSignal};
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+pub fn load_signal(config: &RpcBackpressureConfig) -> LoadSignal {
+    LoadSignal::new(
+        Duration::from_millis(config.elevated_backoff_ms),
+        Duration::from_millis(config.overloaded_backoff_ms),
+    )
+}
+
+/// Publish the load of `state` to `signal` every `SAMPLE_INTERVAL`, until the state is dropped
+/// or the returned task aborted. The state is only held during a sample.
+pub fn spawn_load_sampler(
+    state: &Arc<AuthorityState>,
+    config: RpcBackpressureConfig,
+    signal: LoadSignal,
+) -> JoinHandle<()> {
+    let state = Arc::downgrade(state);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(SAMPLE_INTERVAL);
+        loop {
+            interval.tick().await;
+            let state = match state.upgrade() {
+                Some(state) => state,
+                None => return,
+            };
+            match (state.execution_queue_depth(), state.post_processing_lag()) {
+                (Ok(queue_depth), Ok(indexing_lag)) => {
+                    // Indexing lagging behind on purpose isn't load.
+                    let indexing_lag = if state.metrics.post_processing_paused.get() == 1 {
+                        None
+                    } else {
+                        indexing_lag
+                    };
+                    signal.set(load_level(&config, queue_depth, indexing_lag))
+                }
+                (Err(e), _) | (_, Err(e)) => warn!("Couldn't measure the load of the node: {e}"),
+            }
+        }
+    })
+}
+
+fn load_level(
+    config: &RpcBackpressureConfig,
+    queue_depth: usize,
+    indexing_lag: Option<u64>,
+) -> LoadLevel {
+    let queue = if queue_depth >= config.execution_queue_overloaded {
+        LoadLevel::Overloaded
+    } else if queue_depth >= config.execution_queue_elevated {
+        LoadLevel::Elevated
+    } else {
+        LoadLevel::Normal
+    };
+    let indexing = match indexing_lag {
+        Some(lag) if lag >= config.indexing_lag_overloaded => LoadLevel::Overloaded,
+        Some(lag) if lag >= config.indexing_lag_elevated => LoadLevel::Elevated,
+        _ => LoadLevel::Normal,
+    };
+    queue.max(indexing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_level() {
+        let config = RpcBackpressureConfig {
+            execution_queue_elevated: 1_000,
+            execution_queue_overloaded: 10_000,
+            indexing_lag_elevated: 1_000,
+            indexing_lag_overloaded: 10_000,
+            elevated_backoff_ms: 100,
+            overloaded_backoff_ms: 1_000,
+        };
+        assert_eq!(load_level(&config, 0, None), LoadLevel::Normal);
+        assert_eq!(load_level(&config, 999, Some(999)), LoadLevel::Normal);
+        assert_eq!(load_level(&config, 1_000, Some(0)), LoadLevel::Elevated);
+        assert_eq!(load_level(&config, 0, Some(1_000)), LoadLevel::Elevated);
+        // The worst measure wins.
+        assert_eq!(
+            load_level(&config, 1_000, Some(10_000)),
+            LoadLevel::Overloaded
+        );
+        assert_eq!(load_level(&config, 10_000, None), LoadLevel::Overloaded);
+    }
+}
@@ -0,0 +1,249 @@
+// Copyright (c) 2022, Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Caps the inbound connections of the gRPC server of a node, in total and per source IP, so
+//! that a misbehaving peer can't exhaust them by opening thousands.
+//!
+//! The server of `mysten_network` accepts connections itself, so a node with limits serves
+//! its gRPC services with tonic over [`incoming`] instead, which closes the connections beyond
+//! a cap as soon as they are accepted.
+
+use anyhow::{bail, Result};
+use futures::stream::{self, Stream};
+use multiaddr::{Multiaddr, Protocol};
+use parking_lot::Mutex;
+use prometheus::{IntCounter, IntGauge, Registry};
+use std::collections::HashMap;
+use std::io;
+use std::net::IpAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use sui_config::GrpcConnectionLimitsConfig;
+use sui_core::metrics::{self, MetricsNamespace};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::{TcpListener, TcpStream};
+use tonic::transport::server::{Connected, TcpConnectInfo};
+use tracing::warn;
+
+/// How long accepting pauses after it failed, e.g. because the process ran out of file
+/// descriptors.
+const ACCEPT_ERROR_BACKOFF: Duration = Duration::from_millis(100);
+
+pub(crate) struct ConnectionLimiter {
+    max_connections: usize,
+    max_connections_per_ip: usize,
+    /// Open connections by source IP.
+    open: Mutex<HashMap<IpAddr, usize>>,
+    connections: IntGauge,
+    rejected: IntCounter,
+}
+
+impl ConnectionLimiter {
+    pub fn new(config: &GrpcConnectionLimitsConfig, registry: &Registry) -> Arc<Self> {
+        let node = MetricsNamespace::new(registry, metrics::NODE);
+        Arc::new(Self {
+            max_connections: config.max_inbound_connections,
+            max_connections_per_ip: config
+                .max_inbound_connections_per_ip
+                .unwrap_or(config.max_inbound_connections),
+            open: Mutex::new(HashMap::new()),
+            connections: node
+                .int_gauge(
+                    "grpc_inbound_connections",
+                    "Inbound connections the gRPC server has open",
+                )
+                .unwrap(),
+            rejected: node
+                .int_counter(
+                    "grpc_rejected_connections",
+                    "Inbound gRPC connections closed because a connection limit was reached",
+                )
+                .unwrap(),
+        })
+    }
+
+    /// A permit for a connection from `ip`, which is released when dropped, or None if the
+    /// total or per IP cap is reached.
+    fn try_acquire(self: &Arc<Self>, ip: IpAddr) -> Option<ConnectionPermit> {
+        let mut open = self.open.lock();
+        let from_ip = open.get(&ip).copied().unwrap_or(0);
+        if self.connections.get() as usize >= self.max_connections
+            || from_ip >= self.max_connections_per_ip
+        {
+            self.rejected.inc();
+            return None;
+        }
+        open.insert(ip, from_ip + 1);
+        self.connections.inc();
+        Some(ConnectionPermit {
+            limiter: self.clone(),
+            ip,
+        })
+    }
+}
+
+struct ConnectionPermit {
+    limiter: Arc<ConnectionLimiter>,
+    ip: IpAddr,
+}
+
+impl Drop for ConnectionPermit {
+    fn drop(&mut self) {
+        let mut open = self.limiter.open.lock();
+        if let Some(from_ip) = open.get_mut(&self.ip) {
+            *from_ip -= 1;
+            if *from_ip == 0 {
+                open.remove(&self.ip);
+            }
+        }
+        self.limiter.connections.dec();
+    }
+}
+
+/// An accepted connection, counted against the limits until it is closed.
+pub(crate) struct LimitedTcpStream {
+    stream: TcpStream,
+    _permit: ConnectionPermit,
+}
+
+impl AsyncRead for LimitedTcpStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for LimitedTcpStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.stream).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_shutdown(cx)
+    }
+}
+
+impl Connected for LimitedTcpStream {
+    type ConnectInfo = TcpConnectInfo;
+
+    fn connect_info(&self) -> Self::ConnectInfo {
+        self.stream.connect_info()
+    }
+}
+
+/// Bind a TCP listener to `address`, an IP or DNS name followed by a TCP port like the
+/// network address of a node.
+pub(crate) async fn bind(address: &Multiaddr) -> Result<TcpListener> {
+    let mut protocols = address.iter();
+    let host = match (protocols.next(), protocols.next()) {
+        (Some(Protocol::Ip4(ip)), Some(Protocol::Tcp(port))) => format!("{ip}:{port}"),
+        (Some(Protocol::Ip6(ip)), Some(Protocol::Tcp(port))) => format!("[{ip}]:{port}"),
+        (
+            Some(Protocol::Dns(name) | Protocol::Dns4(name) | Protocol::Dns6(name)),
+            Some(Protocol::Tcp(port)),
+        ) => format!("{name}:{port}"),
+        _ => bail!("Can't limit the connections of {address}, not a TCP address"),
+    };
+    Ok(TcpListener::bind(host).await?)
+}
+
+/// The connections accepted by `listener` that `limiter` lets through. The others are closed
+/// right away.
+pub(crate) fn incoming(
+    listener: TcpListener,
+    limiter: Arc<ConnectionLimiter>,
+) -> impl Stream<Item = io::Result<LimitedTcpStream>> {
+    stream::unfold((listener, limiter), |(listener, limiter)| async move {
+        loop {
+            let (stream, peer) = match listener.accept().await {
+                Ok(accepted) => accepted,
+                // Failing the stream would stop the server.
+                Err(e) => {
+                    warn!("Couldn't accept a gRPC connection: {e}");
+                    tokio::time::sleep(ACCEPT_ERROR_BACKOFF).await;
+                    continue;
+                }
+            };
+            if let Some(permit) = limiter.try_acquire(peer.ip()) {
+                let _ = stream.set_nodelay(true);
+                let stream = LimitedTcpStream {
+                    stream,
+                    _permit: permit,
+                };
+                return Some((Ok(stream), (listener, limiter)));
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+    use tokio::io::AsyncReadExt;
+
+    fn limiter(max: usize, per_ip: Option<usize>) -> Arc<ConnectionLimiter> {
+        let config = GrpcConnectionLimitsConfig {
+            max_inbound_connections: max,
+            max_inbound_connections_per_ip: per_ip,
+        };
+        ConnectionLimiter::new(&config, &Registry::new())
+    }
+
+    #[test]
+    fn test_connection_limits() {
+        let limiter = limiter(3, Some(2));
+        let a: IpAddr = "10.0.0.1".parse().unwrap();
+        let b: IpAddr = "10.0.0.2".parse().unwrap();
+
+        let first = limiter.try_acquire(a).unwrap();
+        let _second = limiter.try_acquire(a).unwrap();
+        // Over the cap per IP.
+        assert!(limiter.try_acquire(a).is_none());
+        let _third = limiter.try_acquire(b).unwrap();
+        // Over the total cap.
+        assert!(limiter.try_acquire(b).is_none());
+        assert_eq!(limiter.connections.get(), 3);
+        assert_eq!(limiter.rejected.get(), 2);
+
+        // Closing a connection makes room for another.
+        drop(first);
+        assert_eq!(limiter.connections.get(), 2);
+        let _fourth = limiter.try_acquire(a).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_connections_beyond_limit_are_closed() {
+        let listener = bind(&"/ip4/127.0.0.1/tcp/0".parse().unwrap())
+            .await
+            .unwrap();
+        let address = listener.local_addr().unwrap();
+        let limiter = limiter(1, None);
+        let mut incoming = Box::pin(incoming(listener, limiter.clone()));
+
+        let _first = TcpStream::connect(address).await.unwrap();
+        let accepted = incoming.next().await.unwrap().unwrap();
+        let mut second = TcpStream::connect(address).await.unwrap();
+        // The stream doesn't yield the second connection, it closes it.
+        let next = tokio::time::timeout(Duration::from_millis(200), incoming.next()).await;
+        assert!(next.is_err());
+        assert_eq!(second.read(&mut [0; 1]).await.unwrap(), 0);
+        assert_eq!(limiter.rejected.get(), 1);
+
+        drop(accepted);
+        assert_eq!(limiter.connections.get(), 0);
+    }
+}
@@ -0,0 +1,275 @@
+// Copyright (c) 2022, Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A self-diagnostic report of the node, combining the health of its subsystems into one
+//! overall status. Served on [`crate::admin::DIAGNOSTICS_ROUTE`].
+//!
+//! Each check is healthy, degraded or critical, and the report is as bad as its worst check.
+//! Checks of subsystems the node doesn't run are left out: consensus is only checked on
+//! validators, and peers only when the node follows other validators.
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::{
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use sui_core::{authority::AuthorityState, consensus_adapter::ConsensusStatus};
+use sui_types::{error::SuiError, messages_checkpoint::CheckpointSequenceNumber};
+
+/// A stalled checkpoint sequence is degraded after this long, and critical after
+/// `CHECKPOINT_STALL_CRITICAL`.
+const CHECKPOINT_STALL_DEGRADED: Duration = Duration::from_secs(5 * 60);
+const CHECKPOINT_STALL_CRITICAL: Duration = Duration::from_secs(30 * 60);
+
+/// Certificates waiting to be executed.
+const EXECUTION_QUEUE_DEGRADED: usize = 1_000;
+const EXECUTION_QUEUE_CRITICAL: usize = 10_000;
+
+/// Share of the disk holding the database that is free.
+const DISK_FREE_DEGRADED: f64 = 0.10;
+const DISK_FREE_CRITICAL: f64 = 0.05;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Healthy,
+    Degraded,
+    Critical,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: Status,
+    pub detail: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DiagnosticReport {
+    pub status: Status,
+    pub checks: Vec<Check>,
+}
+
+pub struct Diagnostics {
+    state: Arc<AuthorityState>,
+    db_path: PathBuf,
+    is_validator: bool,
+    follows_peers: bool,
+    /// The next checkpoint when a report last saw it change, and when that was.
+    checkpoint_progress: Mutex<Option<(CheckpointSequenceNumber, Instant)>>,
+}
+
+impl Diagnostics {
+    pub fn new(
+        state: Arc<AuthorityState>,
+        db_path: PathBuf,
+        is_validator: bool,
+        follows_peers: bool,
+    ) -> Self {
+        let checkpoint_progress = state
+            .checkpoints()
+            .map(|checkpoints| (checkpoints.lock().next_checkpoint(), Instant::now()));
+        Self {
+            state,
+            db_path,
+            is_validator,
+            follows_peers,
+            checkpoint_progress: Mutex::new(checkpoint_progress),
+        }
+    }
+
+    pub fn report(&self) -> DiagnosticReport {
+        let mut checks = Vec::new();
+        if self.is_validator {
+            checks.push(consensus_check(&self.state.consensus_status()));
+        }
+        if let Some(check) = self.checkpoint_check() {
+            checks.push(check);
+        }
+        if self.follows_peers {
+            checks.push(peers_check(self.state.metrics.gossip_active_peers.get()));
+        }
+        checks.push(self.execution_queue_check());
+        checks.push(self.disk_check());
+
+        let status = checks
+            .iter()
+            .map(|check| check.status)
+            .max()
+            .unwrap_or(Status::Healthy);
+        DiagnosticReport { status, checks }
+    }
+
+    fn checkpoint_check(&self) -> Option<Check> {
+        let next = self.state.checkpoints()?.lock().next_checkpoint();
+        let mut progress = self.checkpoint_progress.lock();
+        let stalled = match &*progress {
+            Some((seen, since)) if *seen == next => since.elapsed(),
+            _ => {
+                *progress = Some((next, Instant::now()));
+                Duration::ZERO
+            }
+        };
+        let status = if stalled >= CHECKPOINT_STALL_CRITICAL {
+            Status::Critical
+        } else if stalled >= CHECKPOINT_STALL_DEGRADED {
+            Status::Degraded
+        } else {
+            Status::Healthy
+        };
+        Some(Check {
+            name: "checkpoints",
+            status,
+            detail: format!(
+                "next checkpoint is {next}, unchanged for {}s",
+                stalled.as_secs()
+            ),
+        })
+    }
+
+    fn execution_queue_check(&self) -> Check {
+        let (status, detail) = match self.state.execution_queue_depth() {
+            Ok(depth) => (
+                threshold_status(depth, EXECUTION_QUEUE_DEGRADED, EXECUTION_QUEUE_CRITICAL),
+                format!("{depth} certificates waiting to be executed"),
+            ),
+            Err(e) => (Status::Critical, format!("couldn't read the queue: {e}")),
+        };
+        Check {
+            name: "execution_queue",
+            status,
+            detail,
+        }
+    }
+
+    fn disk_check(&self) -> Check {
+        let (status, detail) = match nix::sys::statvfs::statvfs(&self.db_path) {
+            Ok(stats) => {
+                let block = stats.fragment_size() as u64;
+                let free = stats.blocks_available() as u64 * block;
+                let total = stats.blocks() as u64 * block;
+                let free_share = if total == 0 {
+                    0.0
+                } else {
+                    free as f64 / total as f64
+                };
+                let status = if free_share < DISK_FREE_CRITICAL {
+                    Status::Critical
+                } else if free_share < DISK_FREE_DEGRADED {
+                    Status::Degraded
+                } else {
+                    Status::Healthy
+                };
+                let detail = format!(
+                    "{} of {} MiB free ({:.1}%)",
+                    free >> 20,
+                    total >> 20,
+                    free_share * 100.0
+                );
+                (status, detail)
+            }
+            Err(e) => (
+                Status::Critical,
+                format!("couldn't stat {}: {e}", self.db_path.display()),
+            ),
+        };
+        Check {
+            name: "disk",
+            status,
+            detail,
+        }
+    }
+}
+
+/// Critical when the latest submission failed because consensus is unreachable, degraded when
+/// it wasn't sequenced in time.
+fn consensus_check(status: &ConsensusStatus) -> Check {
+    let failed_last = match (&status.last_failure, status.last_sequenced) {
+        (Some((failed_at, error)), Some(sequenced_at)) if *failed_at > sequenced_at => Some(error),
+        (Some((_, error)), None) => Some(error),
+        _ => None,
+    };
+    let (status, detail) = match failed_last {
+        Some(error @ SuiError::ConsensusConnectionBroken(_)) => (
+            Status::Critical,
+            format!("latest submission failed: {error}"),
+        ),
+        Some(error) => (
+            Status::Degraded,
+            format!("latest submission failed: {error}"),
+        ),
+        None => match status.last_sequenced {
+            Some(at) => (
+                Status::Healthy,
+                format!("last certificate sequenced {}s ago", at.elapsed().as_secs()),
+            ),
+            None => (Status::Healthy, "nothing submitted yet".to_string()),
+        },
+    };
+    Check {
+        name: "consensus",
+        status,
+        detail,
+    }
+}
+
+fn peers_check(active_peers: i64) -> Check {
+    Check {
+        name: "peers",
+        status: if active_peers > 0 {
+            Status::Healthy
+        } else {
+            Status::Critical
+        },
+        detail: format!("following {active_peers} peers"),
+    }
+}
+
+fn threshold_status(value: usize, degraded: usize, critical: usize) -> Status {
+    if value >= critical {
+        Status::Critical
+    } else if value >= degraded {
+        Status::Degraded
+    } else {
+        Status::Healthy
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_consensus_check() {
+        let mut status = ConsensusStatus::default();
+        assert_eq!(consensus_check(&status).status, Status::Healthy);
+
+        let now = Instant::now();
+        status.last_failure = Some((
+            now,
+            SuiError::FailedToHearBackFromConsensus("timeout".to_string()),
+        ));
+        assert_eq!(consensus_check(&status).status, Status::Degraded);
+
+        status.last_failure = Some((
+            now,
+            SuiError::ConsensusConnectionBroken("refused".to_string()),
+        ));
+        assert_eq!(consensus_check(&status).status, Status::Critical);
+
+        // A later success clears it.
+        status.last_sequenced = Some(now + Duration::from_secs(1));
+        assert_eq!(consensus_check(&status).status, Status::Healthy);
+    }
+
+    #[test]
+    fn test_thresholds() {
+        assert!(Status::Critical > Status::Degraded && Status::Degraded > Status::Healthy);
+        assert_eq!(threshold_status(999, 1_000, 10_000), Status::Healthy);
+        assert_eq!(threshold_status(1_000, 1_000, 10_000), Status::Degraded);
+        assert_eq!(threshold_status(10_000, 1_000, 10_000), Status::Critical);
+        assert_eq!(peers_check(0).status, Status::Critical);
+    }
+}
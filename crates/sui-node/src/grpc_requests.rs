@@ -0,0 +1,74 @@
+// Copyright (c) 2022, Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Counts the requests the gRPC server of a node is serving, so that a shutdown can report how
+//! many it cut off after `shutdown-drain-timeout-secs`, as it does for the JSON-RPC server.
+
+use prometheus::{IntGauge, Registry};
+use std::task::{Context, Poll};
+use sui_core::metrics::{self, MetricsNamespace};
+use tonic::codegen::{http, BoxFuture, Service};
+use tonic::transport::NamedService;
+
+pub(crate) fn requests_in_flight(registry: &Registry) -> IntGauge {
+    MetricsNamespace::new(registry, metrics::NODE)
+        .int_gauge(
+            "grpc_requests_in_flight",
+            "Requests the gRPC server started and didn't respond to yet",
+        )
+        .unwrap()
+}
+
+/// A gRPC service whose requests are counted until their response starts. Streamed responses,
+/// such as those of followers, aren't counted while they stream.
+#[derive(Clone)]
+pub(crate) struct CountRequests<S> {
+    inner: S,
+    requests_in_flight: IntGauge,
+}
+
+impl<S> CountRequests<S> {
+    pub fn new(inner: S, requests_in_flight: IntGauge) -> Self {
+        Self {
+            inner,
+            requests_in_flight,
+        }
+    }
+}
+
+/// Uncounts a request when dropped, also when its future is.
+struct InFlight(IntGauge);
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.0.dec();
+    }
+}
+
+impl<S, B> Service<http::Request<B>> for CountRequests<S>
+where
+    S: Service<http::Request<B>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<S::Response, S::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        self.requests_in_flight.inc();
+        let in_flight = InFlight(self.requests_in_flight.clone());
+        let response = self.inner.call(request);
+        Box::pin(async move {
+            let _in_flight = in_flight;
+            response.await
+        })
+    }
+}
+
+impl<S: NamedService> NamedService for CountRequests<S> {
+    const NAME: &'static str = S::NAME;
+}
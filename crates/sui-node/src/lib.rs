@@ -1,53 +1,548 @@
 // Copyright (c) 2022, Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use anyhow::Result;
-use futures::TryFutureExt;
-use jsonrpsee::http_server::HttpServerHandle;
-use jsonrpsee::ws_server::WsServerHandle;
+use anyhow::{anyhow, bail, Result};
+use futures::{future, TryFutureExt};
+use multiaddr::Multiaddr;
 use parking_lot::Mutex;
-use prometheus::Registry;
-use std::{collections::BTreeMap, sync::Arc, time::Duration};
+use prometheus::{IntGauge, Registry};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
-use tracing::info;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info, warn};
 
-use sui_config::NodeConfig;
+use connection_limits::ConnectionLimiter;
+use diagnostics::Diagnostics;
+use grpc_requests::CountRequests;
+use metrics::MetricsServer;
+use state_snapshot::StateSnapshotter;
+use sui_config::{
+    ColumnFamilyGroupConfig, Config, EventIndex, FollowMode, NodeConfig, PeerConnectMode,
+    RpcListenAddress, SyncPeers,
+};
 use sui_core::authority_server::ValidatorService;
 use sui_core::{
-    authority::{AuthorityState, AuthorityStore},
+    authority::{
+        is_db_locked_error, AuthorityState, AuthorityStore, GroupCommitter, ObjectCache,
+        ObjectHistoryPolicy, PostProcessingControl, PostProcessingRetryPolicy,
+        StableSyncAuthoritySigner, StoreColumnFamilyGroups,
+    },
     authority_active::ActiveAuthority,
     authority_client::NetworkAuthorityClient,
+    certificate_pusher::CertificatePusher,
     checkpoints::CheckpointStore,
+    effects_firehose::EffectsFirehoseService,
+    epoch::reconfiguration::latest_key_for_epoch,
+    kafka_event_publisher::KafkaEventPublisher,
+    node_sync_state::NodeSyncState,
 };
 use sui_json_rpc::bcs_api::BcsApiImpl;
-use sui_json_rpc::JsonRpcServerBuilder;
-use sui_network::api::ValidatorServer;
+use sui_json_rpc::call_timeout::CallTimeouts;
+use sui_json_rpc::{ApiMetrics, JsonRpcMetrics, JsonRpcServerBuilder, ServerHandle};
+use sui_network::api::{EffectsFirehoseServer, ValidatorServer};
+use sui_types::{
+    base_types::{AuthorityName, TransactionDigest},
+    error::{SuiError, SuiResult},
+};
+
 use sui_storage::{
-    event_store::{EventStoreType, SqlEventStore},
+    dead_letter_store::DeadLetterStore,
+    event_store::{EventStoreType, SecondaryIndex, SqlEventStore},
     follower_store::FollowerStore,
     node_sync_store::NodeSyncStore,
-    IndexStore,
+    post_processing_store::PostProcessingStore,
+    quarantine_store::QuarantineStore,
+    ColumnFamilyGroup, IndexStore,
 };
 
 use sui_json_rpc::event_api::EventReadApiImpl;
 use sui_json_rpc::event_api::EventStreamingApiImpl;
+use sui_json_rpc::page_size::PageSizes;
 use sui_json_rpc::read_api::FullNodeApi;
 use sui_json_rpc::read_api::ReadApi;
+use sui_json_rpc::response_cache::ResponseCache;
+use sui_json_rpc::websocket::WebsocketServerHandle;
 
+use supervisor::{Subsystem, Supervisor};
+
+pub mod admin;
+pub mod backpressure;
+mod connection_limits;
+pub mod diagnostics;
+mod grpc_requests;
+pub mod logging;
 pub mod metrics;
+mod otlp_metrics;
+mod rpc_auth;
+mod signing_self_test;
+mod state_snapshot;
+mod supervisor;
 
 pub struct SuiNode {
-    grpc_server: tokio::task::JoinHandle<Result<()>>,
-    _json_rpc_service: Option<jsonrpsee::http_server::HttpServerHandle>,
-    _ws_subscription_service: Option<jsonrpsee::ws_server::WsServerHandle>,
-    _batch_subsystem_handle: tokio::task::JoinHandle<Result<()>>,
-    _post_processing_subsystem_handle: Option<tokio::task::JoinHandle<Result<()>>>,
-    _gossip_handle: Option<tokio::task::JoinHandle<()>>,
+    /// None once it stopped.
+    grpc_server: Option<JoinHandle<Result<()>>>,
+    /// Makes the gRPC server stop accepting connections and end once its requests completed.
+    grpc_shutdown: Option<oneshot::Sender<()>>,
+    grpc_requests_in_flight: IntGauge,
+    rpc: RpcState,
+    /// The batch service, post processing, gossip or node sync, the certificate pusher, the
+    /// Kafka event publisher, the periodic maintenance tasks and the effects firehose server,
+    /// those running. Post processing, gossip and node sync are supervised, and fail once they
+    /// crashed too many times.
+    subsystems: Vec<Subsystem>,
+    secondary_catch_up_handle: Option<JoinHandle<()>>,
+    startup_sync: Option<StartupSync>,
+    shutdown_drain_timeout: Duration,
+    metrics_server: MetricsServer,
+    state: Arc<AuthorityState>,
+}
+
+/// The JSON-RPC servers of a full node, which with `rpc-require-synced` start once synced.
+enum RpcState {
+    /// Waiting to be synced, and resolving with the servers unless they failed to start.
+    Gated(JoinHandle<Option<RpcServers>>),
+    Running(RpcServers),
+    /// The servers failed to start, or stopped.
+    Stopped,
+}
+
+impl RpcState {
+    /// Resolves once a server stopped, which is then no longer waited for, or the gated servers
+    /// failed to start.
+    async fn failure(&mut self) -> anyhow::Error {
+        loop {
+            match self {
+                RpcState::Gated(handle) => match handle.await {
+                    Ok(Some(servers)) => *self = RpcState::Running(servers),
+                    Ok(None) => *self = RpcState::Stopped,
+                    Err(e) => {
+                        *self = RpcState::Stopped;
+                        return anyhow!("Starting the JSON-RPC server failed: {e}");
+                    }
+                },
+                RpcState::Running(servers) => {
+                    let server = servers.next_stopped().await;
+                    return anyhow!("The {server} server stopped");
+                }
+                RpcState::Stopped => return future::pending().await,
+            }
+        }
+    }
+}
+
+/// The JSON-RPC servers of a full node.
+pub struct RpcServers {
+    /// The instances serving `json-rpc-address`, with the requests they started and didn't
+    /// respond to yet.
+    json_rpc: Option<(Vec<ServerHandle>, IntGauge)>,
+    websocket: Option<WebsocketServerHandle>,
+    /// Measures the load the JSON-RPC servers signal with `rpc-backpressure`.
+    load_sampler: Option<JoinHandle<()>>,
+}
+
+impl RpcServers {
+    /// Resolves with the name of the first server that stopped on its own, which is removed.
+    async fn next_stopped(&mut self) -> &'static str {
+        let (websocket, json_rpc) = (&mut self.websocket, &mut self.json_rpc);
+        let websocket_stopped = async {
+            match websocket {
+                Some(handle) => handle.stopped().await,
+                None => future::pending().await,
+            }
+            *websocket = None;
+        };
+        let json_rpc_stopped = async {
+            match json_rpc {
+                Some((servers, _)) if !servers.is_empty() => {
+                    let (_, index, _) = future::select_all(
+                        servers.iter_mut().map(|server| Box::pin(server.stopped())),
+                    )
+                    .await;
+                    servers.remove(index);
+                }
+                _ => future::pending().await,
+            }
+        };
+        tokio::select! {
+            _ = websocket_stopped => "websocket",
+            _ = json_rpc_stopped => "JSON-RPC",
+        }
+    }
+
+    /// Stop the servers, giving the requests being served up to `drain_timeout` to complete.
+    /// Returns how many were still being served when the servers were closed.
+    pub async fn shutdown(self, drain_timeout: Duration) -> u64 {
+        if let Some(websocket) = self.websocket {
+            websocket.stop().await;
+        }
+        if let Some(sampler) = self.load_sampler {
+            sampler.abort();
+            let _ = sampler.await;
+        }
+        match self.json_rpc {
+            // The instances count their requests together, so each one reports those of all
+            // that are cut off. They are stopped at once for the counts to be the same.
+            Some((servers, requests_in_flight)) => future::join_all(
+                servers
+                    .into_iter()
+                    .map(|server| server.stop(drain_timeout, &requests_in_flight)),
+            )
+            .await
+            .into_iter()
+            .max()
+            .unwrap_or(0),
+            None => 0,
+        }
+    }
+}
+
+/// How long each subsystem has to stop on shutdown before it is aborted.
+const SUBSYSTEM_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
+const STARTUP_SYNC_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Fails the node if it didn't execute `target` transactions within `deadline` of starting.
+struct StartupSync {
+    target: u64,
+    started_at: Instant,
+    deadline: Duration,
+    /// Transactions executed when the node was started, used to report the sync rate.
+    initial: u64,
+}
+
+impl StartupSync {
+    async fn wait(&self, state: &AuthorityState) -> Result<()> {
+        let mut interval = tokio::time::interval(STARTUP_SYNC_POLL_INTERVAL);
+        loop {
+            interval.tick().await;
+            let executed = state.get_total_transaction_number()?;
+            let elapsed = self.started_at.elapsed();
+            if executed >= self.target {
+                info!(executed, ?elapsed, "Startup sync target reached");
+                return Ok(());
+            }
+            if elapsed >= self.deadline {
+                let rate = (executed - self.initial) as f64 / elapsed.as_secs_f64();
+                bail!(
+                    "Startup sync target of {} transactions not reached within {:?}: executed \
+                    {executed} ({} behind, {rate:.1} tx/s)",
+                    self.target,
+                    self.deadline,
+                    self.target - executed,
+                );
+            }
+        }
+    }
+}
+
+/// Awaits `task` unless it already completed, and then clears it so that it isn't again.
+async fn join_once<T>(task: &mut Option<JoinHandle<T>>) -> Result<T, tokio::task::JoinError> {
+    let result = match task {
+        Some(handle) => handle.await,
+        None => future::pending().await,
+    };
+    *task = None;
+    result
+}
+
+/// Waits until node sync is within `distance` transactions of every validator it follows.
+async fn wait_until_synced(state: &AuthorityState, distance: u64) {
+    let mut interval = tokio::time::interval(STARTUP_SYNC_POLL_INTERVAL);
+    loop {
+        interval.tick().await;
+        if let Some(lag) = state.sync_lag() {
+            if lag <= distance {
+                info!(lag, "Synced, starting the JSON-RPC server");
+                return;
+            }
+        }
+    }
+}
+
+/// How often the oldest_retained gauges are updated.
+const RETENTION_REPORT_INTERVAL: Duration = Duration::from_secs(60);
+
+fn spawn_retention_reporter(state: Arc<AuthorityState>) -> JoinHandle<Result<()>> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(RETENTION_REPORT_INTERVAL);
+        loop {
+            tokio::select! {
+                _ = interval.tick() => (),
+                _ = state.shutdown_requested() => return Ok(()),
+            }
+            if let Err(e) = state.retention_watermarks().await {
+                warn!("Couldn't read the retention watermarks: {e}");
+            }
+        }
+    })
+}
+
+/// How often the execution queue and throughput are sampled.
+const EXECUTION_LOAD_SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+fn spawn_execution_load_sampler(state: Arc<AuthorityState>) -> JoinHandle<Result<()>> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(EXECUTION_LOAD_SAMPLE_INTERVAL);
+        loop {
+            tokio::select! {
+                _ = interval.tick() => (),
+                _ = state.shutdown_requested() => return Ok(()),
+            }
+            if let Err(e) = state.sample_execution_load() {
+                warn!("Couldn't sample the execution load: {e}");
+            }
+        }
+    })
+}
+
+/// Recomputes the object counts kept with `indexes` every `interval`, starting right away when
+/// they were never computed.
+fn spawn_object_count_reconciler(
+    state: Arc<AuthorityState>,
+    indexes: &IndexStore,
+    interval: Duration,
+) -> JoinHandle<Result<()>> {
+    let mut counted = matches!(indexes.object_counts(), Ok(Some(_)));
+    tokio::spawn(async move {
+        loop {
+            if counted {
+                tokio::select! {
+                    _ = tokio::time::sleep(interval) => (),
+                    _ = state.shutdown_requested() => return Ok(()),
+                }
+            }
+            counted = true;
+            let reconciling_state = state.clone();
+            let started = Instant::now();
+            match tokio::task::spawn_blocking(move || reconciling_state.reconcile_object_counts())
+                .await
+            {
+                Ok(Ok(Some(counts))) => info!(
+                    live_objects = counts.live_objects,
+                    owner_addresses = counts.owner_addresses,
+                    "Reconciled object counts in {:?}",
+                    started.elapsed()
+                ),
+                Ok(Ok(None)) => return Ok(()),
+                Ok(Err(e)) => warn!("Couldn't reconcile object counts: {e}"),
+                Err(e) => warn!("Object count reconciliation failed: {e}"),
+            }
+        }
+    })
+}
+
+/// Every `interval`, deletes the past object versions `policy` doesn't keep.
+fn spawn_object_history_pruner(
+    state: Arc<AuthorityState>,
+    policy: ObjectHistoryPolicy,
+    interval: Duration,
+) -> JoinHandle<Result<()>> {
+    let policy = Arc::new(policy);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(interval);
+        loop {
+            tokio::select! {
+                _ = interval.tick() => (),
+                _ = state.shutdown_requested() => return Ok(()),
+            }
+            let (pruning_state, policy) = (state.clone(), policy.clone());
+            let started = Instant::now();
+            match tokio::task::spawn_blocking(move || pruning_state.prune_object_versions(&policy))
+                .await
+            {
+                Ok(Ok(Some(pruned))) => {
+                    info!(pruned, "Pruned object versions in {:?}", started.elapsed())
+                }
+                Ok(Ok(None)) => debug!("Skipped pruning object versions, post-processing lags"),
+                Ok(Err(e)) => warn!("Couldn't prune object versions: {e}"),
+                Err(e) => warn!("Object version pruning failed: {e}"),
+            }
+        }
+    })
+}
+
+/// Transactions read at once by the re-execution verifier, at most those a range query returns.
+const REEXECUTION_BATCH_SIZE: u64 = 4096;
+
+/// Executes `digest` again, and quarantines it if its effects differ from those stored or
+/// releases it from quarantine if they don't. Returns whether they differ, None if it couldn't
+/// be executed again, see [`AuthorityState::reexecute_transaction`].
+pub(crate) async fn verify_reexecution(
+    state: &AuthorityState,
+    quarantine: &QuarantineStore,
+    digest: &TransactionDigest,
+) -> SuiResult<Option<bool>> {
+    match state.reexecute_transaction(digest).await? {
+        Some((stored, reexecuted)) if stored != reexecuted => {
+            error!(
+                ?digest,
+                ?stored,
+                ?reexecuted,
+                "Transaction has other effects when executed again, quarantining it"
+            );
+            quarantine.insert(digest, stored, reexecuted)?;
+            Ok(Some(true))
+        }
+        Some(_) => {
+            quarantine.remove(digest)?;
+            Ok(Some(false))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Every `interval`, executes again a sample of the transactions committed since, and
+/// quarantines those whose effects differ from the ones stored. Where it left off is saved in
+/// `quarantine`, so that a restart carries on from there.
+fn spawn_reexecution_verifier(
     state: Arc<AuthorityState>,
+    quarantine: Arc<QuarantineStore>,
+    sample_rate: f64,
+    interval: Duration,
+) -> Result<JoinHandle<Result<()>>> {
+    // The first time, start from the tip rather than sampling all history.
+    let mut cursor = match quarantine.cursor()? {
+        Some(cursor) => cursor,
+        None => {
+            let cursor = state.get_total_transaction_number()?;
+            quarantine.set_cursor(cursor)?;
+            cursor
+        }
+    };
+    Ok(tokio::spawn(async move {
+        let mut interval = tokio::time::interval(interval);
+        loop {
+            tokio::select! {
+                _ = interval.tick() => (),
+                _ = state.shutdown_requested() => return Ok(()),
+            }
+            let end = match state.get_total_transaction_number() {
+                Ok(end) => end,
+                Err(e) => {
+                    warn!("Couldn't read the transactions to re-execute: {e}");
+                    continue;
+                }
+            };
+            while cursor < end {
+                let batch_end = end.min(cursor + REEXECUTION_BATCH_SIZE);
+                let transactions = match state.get_transactions_in_range(cursor, batch_end) {
+                    Ok(transactions) => transactions,
+                    Err(e) => {
+                        warn!("Couldn't read the transactions to re-execute: {e}");
+                        break;
+                    }
+                };
+                for (_, digest) in transactions {
+                    if state.is_shutting_down() {
+                        return Ok(());
+                    }
+                    if rand::random::<f64>() >= sample_rate {
+                        continue;
+                    }
+                    match verify_reexecution(&state, &quarantine, &digest).await {
+                        Ok(Some(_)) => (),
+                        Ok(None) => debug!(?digest, "Skipped re-executing transaction"),
+                        Err(e) => warn!(?digest, "Couldn't re-execute transaction: {e}"),
+                    }
+                }
+                cursor = batch_end;
+                if let Err(e) = quarantine.set_cursor(cursor) {
+                    warn!("Couldn't save where re-execution left off: {e}");
+                }
+            }
+        }
+    }))
+}
+
+/// Connects to the validators this node follows: those listed in `sync-peers-file`, or all
+/// genesis validators when it isn't set.
+struct SyncPeerConnector {
+    net_config: mysten_network::config::Config,
+    addresses: BTreeMap<AuthorityName, Multiaddr>,
+    sync_peers_file: Option<PathBuf>,
+}
+
+impl SyncPeerConnector {
+    fn peers(&self) -> Result<BTreeSet<AuthorityName>> {
+        let path = match &self.sync_peers_file {
+            Some(path) => path,
+            None => return Ok(self.addresses.keys().copied().collect()),
+        };
+        let mut peers = BTreeSet::new();
+        for name in SyncPeers::load(path)?.peers {
+            if self.addresses.contains_key(&name) {
+                peers.insert(name);
+            } else {
+                warn!(peer = ?name, "Ignoring sync peer that isn't a genesis validator");
+            }
+        }
+        Ok(peers)
+    }
+
+    fn connect(&self, name: &AuthorityName) -> SuiResult<NetworkAuthorityClient> {
+        let channel = self
+            .net_config
+            .connect_lazy(&self.addresses[name])
+            .map_err(|e| SuiError::GenericAuthorityError {
+                error: format!("Couldn't connect to {name:?}: {e}"),
+            })?;
+        Ok(NetworkAuthorityClient::new(channel))
+    }
+
+    /// Open the connections to the sync peers now, rather than on their first request. Returns
+    /// the clients of the peers that could be reached, the others are left to connect lazily.
+    async fn connect_eagerly(&self) -> Result<BTreeMap<AuthorityName, NetworkAuthorityClient>> {
+        let connections = future::join_all(self.peers()?.into_iter().map(|name| async move {
+            (name, self.net_config.connect(&self.addresses[&name]).await)
+        }))
+        .await;
+        let mut clients = BTreeMap::new();
+        for (name, connection) in connections {
+            match connection {
+                Ok(channel) => {
+                    clients.insert(name, NetworkAuthorityClient::new(channel));
+                }
+                Err(e) => warn!(peer = ?name, "Couldn't connect, will connect lazily: {e}"),
+            }
+        }
+        Ok(clients)
+    }
+
+    /// Re-read the sync peers and update the clients of `active_authority` to match.
+    fn reconcile(
+        &self,
+        active_authority: &ActiveAuthority<NetworkAuthorityClient>,
+    ) -> Result<usize> {
+        self.reconcile_with(active_authority, BTreeMap::new())
+    }
+
+    /// Like [`Self::reconcile`], using the clients of `connected` for the peers they're for.
+    fn reconcile_with(
+        &self,
+        active_authority: &ActiveAuthority<NetworkAuthorityClient>,
+        mut connected: BTreeMap<AuthorityName, NetworkAuthorityClient>,
+    ) -> Result<usize> {
+        let peers = self.peers()?;
+        active_authority.reconcile_authority_clients(&peers, |name| {
+            match connected.remove(name) {
+                Some(client) => Ok(client),
+                None => self.connect(name),
+            }
+        })?;
+        Ok(peers.len())
+    }
 }
 
 impl SuiNode {
     pub async fn start(config: &NodeConfig) -> Result<SuiNode> {
+        let started_at = Instant::now();
+
         //
         // Start metrics server
         //
@@ -55,30 +550,135 @@ impl SuiNode {
             "Starting Prometheus HTTP endpoint at {}",
             config.metrics_address
         );
-        let prometheus_registry = metrics::start_prometheus_server(config.metrics_address);
+        let metrics_server = metrics::start_prometheus_server(
+            config.metrics_address,
+            config.admin_allow_remote,
+            config.metrics_auth.as_ref(),
+            config.otlp_metrics.as_ref(),
+        );
+        let prometheus_registry = metrics_server.registry().clone();
 
         info!(node =? config.public_key(),
             "Initializing sui-node listening on {}", config.network_address
         );
 
+        // Checked before the JSON-RPC server is gated on syncing. Full nodes only learn the
+        // checkpoints of transactions from verifying them, and read the past versions of
+        // objects as of the pinned one.
+        if config.pin_reads_at_checkpoint.is_some() && config.consensus_config().is_none() {
+            if !config.node_sync_config.verify_against_checkpoints {
+                bail!(
+                    "Pinning reads at a checkpoint needs node sync to verify against checkpoints"
+                );
+            }
+            if config.object_history_config.is_some() {
+                bail!(
+                    "Reads can't be pinned at a checkpoint while object-history-config prunes them"
+                );
+            }
+        }
+
+        if (1..1000).contains(&config.group_commit_window_us) {
+            bail!("group-commit-window-us must be 0 or at least 1000, windows are timed in ms");
+        }
+
         let genesis = config.genesis()?;
+        if config.consensus_config().is_some() && !config.skip_signing_self_test {
+            signing_self_test::check_signing_key(
+                config.key_pair(),
+                &config.network_address,
+                genesis.validator_set(),
+            )?;
+        }
 
-        let secret = Arc::pin(config.key_pair().copy());
         let committee = genesis.committee()?;
-        let store = Arc::new(AuthorityStore::open(config.db_path().join("store"), None));
+        let (store, catch_up_interval) = match &config.follow_mode {
+            FollowMode::Standard => (open_store_when_unlocked(config).await?, None),
+            FollowMode::SecondaryDb {
+                primary_path,
+                catch_up_interval_ms,
+            } => {
+                if config.consensus_config().is_some() {
+                    bail!("Validators cannot run with a secondary store");
+                }
+                // TODO: also tail the index and event stores of the primary.
+                if config.enable_event_processing {
+                    bail!("Event processing is not supported with a secondary store");
+                }
+                info!("Following the store of {}", primary_path.display());
+                (
+                    AuthorityStore::open_secondary(
+                        primary_path.join("store"),
+                        config.db_path().join("store_secondary"),
+                        None,
+                    ),
+                    Some(Duration::from_millis(*catch_up_interval_ms)),
+                )
+            }
+        };
+        let store = match config.object_cache_entries {
+            0 => store,
+            entries => store.with_object_cache(ObjectCache::new(entries, &prometheus_registry)),
+        };
+        let store = match config.group_commit_window_us {
+            0 => store,
+            window => store.with_group_commit(GroupCommitter::new(
+                Duration::from_micros(window),
+                &prometheus_registry,
+            )),
+        };
+        let store = Arc::new(store);
+        let is_secondary = store.is_secondary();
+
+        // A validator that rotated its key at an epoch change keeps signing with it.
+        let key_pair = match &config.checkpoint_keys_dir {
+            Some(dir) if config.consensus_config().is_some() && !store.database_is_empty()? => {
+                latest_key_for_epoch(dir, &store.get_last_epoch_info()?.committee)?
+            }
+            _ => None,
+        }
+        .unwrap_or_else(|| config.key_pair().copy());
+        let name = *key_pair.public_key_bytes();
+        let secret: StableSyncAuthoritySigner = Arc::pin(key_pair);
+
+        let secondary_catch_up_handle = catch_up_interval.map(|interval| {
+            let store = store.clone();
+            tokio::task::spawn(async move {
+                let mut interval = tokio::time::interval(interval);
+                loop {
+                    interval.tick().await;
+                    if let Err(e) = store.try_catch_up_with_primary() {
+                        warn!("Failed to catch up with the primary store: {e}");
+                    }
+                }
+            })
+        });
         let checkpoint_store = if config.consensus_config().is_some() {
             Some(Arc::new(Mutex::new(CheckpointStore::open(
                 config.db_path().join("checkpoints"),
                 None,
                 committee.epoch,
-                config.public_key(),
+                name,
                 secret.clone(),
             )?)))
         } else {
             None
         };
 
-        let index_store = if config.consensus_config().is_some() {
+        let state_snapshotter = match (&config.state_snapshot_config, &checkpoint_store) {
+            (Some(snapshot_config), Some(checkpoint_store)) => Some(StateSnapshotter::new(
+                snapshot_config.clone(),
+                store.clone(),
+                checkpoint_store.clone(),
+            )?),
+            (Some(_), None) => {
+                warn!("Ignoring state-snapshot-config, only validators have checkpoints");
+                None
+            }
+            (None, _) => None,
+        };
+
+        let index_store = if config.consensus_config().is_some() || is_secondary {
             None
         } else {
             Some(Arc::new(IndexStore::open(
@@ -91,8 +691,27 @@ impl SuiNode {
 
         let event_store = if config.enable_event_processing {
             let path = config.db_path().join("events.db");
-            let db = SqlEventStore::new_from_file(&path).await?;
+            let indexes: Vec<_> = config
+                .event_store_config
+                .indexes
+                .iter()
+                .map(|index| match index {
+                    EventIndex::EventType => SecondaryIndex::EventType,
+                    EventIndex::Sender => SecondaryIndex::Sender,
+                    EventIndex::Package => SecondaryIndex::Package,
+                    EventIndex::ObjectId => SecondaryIndex::ObjectId,
+                    EventIndex::MoveEventType => SecondaryIndex::MoveEventType,
+                })
+                .collect();
+            let db = SqlEventStore::new_from_file_with_pools(
+                &path,
+                config.event_store_config.read_pool_size,
+                config.event_store_config.write_pool_size,
+            )
+            .await?
+            .with_indexes(&indexes);
             db.initialize().await?;
+            db.register_metrics(&prometheus_registry)?;
             Some(Arc::new(EventStoreType::SqlEventStore(db)))
         } else {
             None
@@ -101,7 +720,7 @@ impl SuiNode {
         let state = Arc::new(
             AuthorityState::new(
                 committee,
-                config.public_key(),
+                name,
                 secret,
                 store,
                 index_store.clone(),
@@ -110,78 +729,252 @@ impl SuiNode {
                 genesis,
                 &prometheus_registry,
             )
-            .await,
+            .await
+            .with_execution_trace_sample_rate(config.execution_trace_sample_rate)
+            .with_input_prefetch_concurrency(config.input_prefetch_concurrency),
         );
 
         // TODO: maybe have a config enum that takes care of this for us.
         let is_validator = config.consensus_config().is_some();
         let is_node = !is_validator;
 
-        let should_start_follower = is_node || config.enable_gossip;
+        let should_start_follower = !is_secondary && (is_node || config.enable_gossip);
+        let supervisor = Supervisor::new(
+            config.subsystem_restart_config.clone(),
+            &prometheus_registry,
+            state.subscribe_shutdown(),
+        );
 
         let gossip_handle = if should_start_follower {
+            let timeouts = &config.network_timeout_config;
             let mut net_config = mysten_network::config::Config::new();
-            net_config.connect_timeout = Some(Duration::from_secs(5));
-            net_config.request_timeout = Some(Duration::from_secs(5));
-            net_config.http2_keepalive_interval = Some(Duration::from_secs(5));
+            net_config.connect_timeout = Some(timeouts.connect_timeout());
+            net_config.request_timeout = Some(timeouts.request_timeout());
+            net_config.http2_keepalive_interval = Some(timeouts.keepalive_interval());
 
-            let mut authority_clients = BTreeMap::new();
-            for validator in genesis.validator_set() {
-                let channel = net_config
-                    .connect_lazy(validator.network_address())
-                    .unwrap();
-                let client = NetworkAuthorityClient::new(channel);
-                authority_clients.insert(validator.public_key(), client);
-            }
+            let connector = SyncPeerConnector {
+                net_config,
+                addresses: genesis
+                    .validator_set()
+                    .iter()
+                    .map(|validator| (validator.public_key(), validator.network_address().clone()))
+                    .collect(),
+                sync_peers_file: config.sync_peers_file.clone(),
+            };
 
             let gateway_metrics =
                 sui_core::gateway_state::GatewayMetrics::new(&prometheus_registry);
-            let active_authority = Arc::new(ActiveAuthority::new(
+            let mut active_authority = ActiveAuthority::new(
                 state.clone(),
                 follower_store,
-                authority_clients,
+                BTreeMap::new(),
                 gateway_metrics,
-            )?);
+            )?
+            .with_peer_selection(config.peer_selection.clone());
+            if let Some(bytes_per_sec) = config.gossip_max_bytes_per_sec {
+                info!("Limiting gossip to {bytes_per_sec} bytes per second");
+                active_authority = active_authority.with_follower_bandwidth_limit(bytes_per_sec);
+            }
+            if let Some(dir) = &config.checkpoint_keys_dir {
+                active_authority = active_authority.with_checkpoint_keys_dir(dir.clone());
+            }
+            if let Some(checkpoint) = &config.trusted_checkpoint {
+                if config.node_sync_config.verify_against_checkpoints {
+                    active_authority = active_authority.with_trusted_checkpoint(checkpoint.clone());
+                } else {
+                    warn!("Ignoring trusted-checkpoint, node sync doesn't verify checkpoints");
+                }
+            }
+            let active_authority = Arc::new(active_authority);
+            let num_peers = match config.peer_connect_mode {
+                PeerConnectMode::Lazy => connector.reconcile(&active_authority)?,
+                PeerConnectMode::Eager => {
+                    let connected = connector.connect_eagerly().await?;
+                    let num_connected = connected.len();
+                    let num_peers = connector.reconcile_with(&active_authority, connected)?;
+                    info!("Connected to {num_connected} of {num_peers} validators at startup");
+                    num_peers
+                }
+            };
+            info!("Following {num_peers} validators");
+
+            let reload_active_authority = active_authority.clone();
+            admin::set_sync_peers_reload(&state, move || {
+                connector
+                    .reconcile(&reload_active_authority)
+                    .map_err(|e| e.to_string())
+            });
 
             Some(if is_validator {
-                // TODO: get degree from config file.
-                let degree = 4;
-                active_authority.spawn_gossip_process(degree).await
+                let degree =
+                    config.effective_gossip_degree(state.committee.load().num_members())?;
+                info!("Gossiping with {degree} validators");
+                supervisor.supervise("gossip", move || {
+                    let active_authority = active_authority.clone();
+                    async move {
+                        active_authority.spawn_gossip_process(degree).await.await?;
+                        Ok(())
+                    }
+                })
             } else {
                 let pending_store =
                     Arc::new(NodeSyncStore::open(config.db_path().join("node_sync_db"))?);
-
-                active_authority
-                    .spawn_node_sync_process(pending_store)
-                    .await
+                let node_sync_config = config.node_sync_config.clone();
+                supervisor.supervise("node_sync", move || {
+                    let active_authority = active_authority.clone();
+                    let pending_store = pending_store.clone();
+                    let node_sync_config = node_sync_config.clone();
+                    async move {
+                        active_authority
+                            .spawn_node_sync_process(pending_store, node_sync_config)
+                            .await
+                            .await?;
+                        Ok(())
+                    }
+                })
             })
         } else {
             None
         };
 
-        let batch_subsystem_handle = {
+        let batch_subsystem_handle = if is_secondary {
+            None
+        } else {
             // Start batch system so that this node can be followed
             let batch_state = state.clone();
-            tokio::task::spawn(async move {
+            Some(tokio::task::spawn(async move {
                 batch_state
                     .run_batch_service(1000, Duration::from_secs(1))
                     .await
                     .map_err(Into::into)
-            })
+            }))
         };
 
         let post_processing_subsystem_handle =
             if index_store.is_some() || config.enable_event_processing {
                 let indexing_state = state.clone();
-                Some(tokio::task::spawn(async move {
-                    indexing_state
-                        .run_tx_post_processing_process()
-                        .await
-                        .map_err(Into::into)
+                let post_processing = &config.post_processing_config;
+                let retry_policy = PostProcessingRetryPolicy {
+                    max_attempts: post_processing.max_attempts,
+                    initial_backoff: Duration::from_millis(post_processing.initial_backoff_ms),
+                    max_backoff: Duration::from_millis(post_processing.max_backoff_ms),
+                };
+                let event_insert_batch_size = config.event_store_config.insert_batch_size.max(1);
+                let dead_letters = Arc::new(DeadLetterStore::open(
+                    config.db_path().join("dead_letter_db"),
+                )?);
+                let control = PostProcessingControl::new(Arc::new(PostProcessingStore::open(
+                    config.db_path().join("post_processing_db"),
+                )?))?;
+                if control.is_paused() {
+                    warn!("Post processing is paused, resume it through the admin endpoint");
+                }
+                admin::set_post_processing_control(&state, control.clone());
+                admin::set_dead_letters(&state, dead_letters.clone());
+                Some(supervisor.supervise("post_processing", move || {
+                    let indexing_state = indexing_state.clone();
+                    let retry_policy = retry_policy.clone();
+                    let dead_letters = dead_letters.clone();
+                    let control = control.clone();
+                    async move {
+                        indexing_state
+                            .run_tx_post_processing_process(
+                                retry_policy,
+                                event_insert_batch_size,
+                                dead_letters,
+                                control,
+                            )
+                            .await
+                            .map_err(Into::into)
+                    }
                 }))
             } else {
                 None
             };
+        let object_count_reconciler_handle = index_store.as_ref().map(|indexes| {
+            spawn_object_count_reconciler(
+                state.clone(),
+                indexes,
+                Duration::from_secs(
+                    config
+                        .post_processing_config
+                        .object_count_reconciliation_interval_secs,
+                ),
+            )
+        });
+        let retention_reporter_handle = spawn_retention_reporter(state.clone());
+        let execution_load_sampler_handle = spawn_execution_load_sampler(state.clone());
+        let object_history_pruner_handle = match &config.object_history_config {
+            // The primary prunes the versions a secondary store reads.
+            Some(_) if is_secondary => None,
+            Some(history_config) => {
+                let policy = ObjectHistoryPolicy::new(history_config)?;
+                Some(spawn_object_history_pruner(
+                    state.clone(),
+                    policy,
+                    Duration::from_secs(history_config.prune_interval_secs),
+                ))
+            }
+            None => None,
+        };
+        let state_snapshotter_handle =
+            state_snapshotter.map(|snapshotter| snapshotter.spawn(state.subscribe_shutdown()));
+
+        let reexecution_verifier_handle =
+            if let Some(verifier_config) = &config.reexecution_verifier {
+                let quarantine = Arc::new(QuarantineStore::open(
+                    config.db_path().join("quarantine_db"),
+                )?);
+                let quarantined = quarantine.count();
+                if quarantined > 0 {
+                    warn!(
+                        quarantined,
+                        "Transactions are quarantined for diverging re-execution"
+                    );
+                }
+                admin::set_quarantine(&state, quarantine.clone());
+                Some(spawn_reexecution_verifier(
+                    state.clone(),
+                    quarantine,
+                    verifier_config.sample_rate,
+                    Duration::from_secs(verifier_config.interval_secs),
+                )?)
+            } else {
+                None
+            };
+
+        let certificate_pusher_handle = match &config.certificate_push_config {
+            Some(push_config) => {
+                info!("Pushing executed certificates to {}", push_config.endpoint);
+                let pusher = CertificatePusher::open(
+                    config.db_path().join("certificate_push_db"),
+                    state.clone(),
+                    push_config,
+                )?;
+                Some(tokio::task::spawn(async move {
+                    pusher.run().await.map_err(Into::into)
+                }))
+            }
+            None => None,
+        };
+
+        let kafka_event_publisher_handle = match &config.kafka_event_config {
+            Some(kafka_config) => {
+                info!(
+                    "Publishing events to Kafka topic {} on {}",
+                    kafka_config.topic, kafka_config.brokers
+                );
+                let publisher = KafkaEventPublisher::open(
+                    config.db_path().join("kafka_event_db"),
+                    state.clone(),
+                    kafka_config,
+                )?;
+                Some(tokio::task::spawn(async move {
+                    publisher.run().await.map_err(Into::into)
+                }))
+            }
+            None => None,
+        };
 
         let validator_service = if config.consensus_config().is_some() {
             Some(ValidatorService::new(config, state.clone()).await?)
@@ -189,33 +982,167 @@ impl SuiNode {
             None
         };
 
-        let grpc_server = {
+        let grpc_requests_in_flight = grpc_requests::requests_in_flight(&prometheus_registry);
+        let validator_service = validator_service.map(|service| {
+            CountRequests::new(
+                ValidatorServer::new(service),
+                grpc_requests_in_flight.clone(),
+            )
+        });
+        let (grpc_server, grpc_shutdown) = if let Some(limits) = &config.grpc_connection_limits {
+            let limiter = ConnectionLimiter::new(limits, &prometheus_registry);
+            let listener = connection_limits::bind(config.network_address()).await?;
+            info!(
+                "Listening to traffic on {}, at most {} connections",
+                listener.local_addr()?,
+                limits.max_inbound_connections
+            );
+            let (shutdown, stopped) = oneshot::channel::<()>();
+            let server = tonic::transport::Server::builder()
+                .add_optional_service(validator_service)
+                .serve_with_incoming_shutdown(
+                    connection_limits::incoming(listener, limiter),
+                    async {
+                        let _ = stopped.await;
+                    },
+                );
+            (tokio::spawn(server.map_err(Into::into)), Some(shutdown))
+        } else {
             let mut server_builder = mysten_network::config::Config::new().server_builder();
 
             if let Some(validator_service) = validator_service {
-                server_builder =
-                    server_builder.add_service(ValidatorServer::new(validator_service));
+                server_builder = server_builder.add_service(validator_service);
             }
 
-            let server = server_builder.bind(config.network_address()).await?;
+            let mut server = server_builder.bind(config.network_address()).await?;
             let local_addr = server.local_addr();
             info!("Listening to traffic on {local_addr}");
-            tokio::spawn(server.serve().map_err(Into::into))
+            let shutdown = server.take_cancel_handle();
+            (tokio::spawn(server.serve().map_err(Into::into)), shutdown)
         };
 
-        let (json_rpc_service, ws_subscription_service) =
-            build_node_server(state.clone(), config, &prometheus_registry).await?;
+        let effects_firehose_server = match &config.effects_firehose_address {
+            Some(_) if is_validator => {
+                warn!("Ignoring effects-firehose-address, only full nodes serve the firehose");
+                None
+            }
+            Some(address) => {
+                let server = mysten_network::config::Config::new()
+                    .server_builder()
+                    .add_service(EffectsFirehoseServer::new(EffectsFirehoseService::new(
+                        state.clone(),
+                    )))
+                    .bind(address)
+                    .await?;
+                info!("Serving the effects firehose on {}", server.local_addr());
+                Some(tokio::spawn(server.serve().map_err(Into::into)))
+            }
+            None => None,
+        };
+
+        let gate_rpc = config.rpc_require_synced && is_node && should_start_follower;
+        if config.rpc_require_synced && !gate_rpc {
+            warn!("Ignoring rpc-require-synced, the node doesn't sync from validators");
+        }
+        let rpc = if gate_rpc {
+            let state = state.clone();
+            let config = config.clone();
+            let prometheus_registry = prometheus_registry.clone();
+            RpcState::Gated(tokio::spawn(async move {
+                info!(
+                    "Starting the JSON-RPC server once synced within {} transactions",
+                    config.rpc_sync_distance
+                );
+                wait_until_synced(&state, config.rpc_sync_distance).await;
+                match build_node_server(state, &config, &prometheus_registry).await {
+                    Ok(servers) => Some(servers),
+                    Err(e) => {
+                        error!("Couldn't start the JSON-RPC server: {e}");
+                        None
+                    }
+                }
+            }))
+        } else {
+            RpcState::Running(build_node_server(state.clone(), config, &prometheus_registry).await?)
+        };
+
+        let startup_sync = match (
+            config.startup_sync_target,
+            config.startup_sync_deadline_secs,
+        ) {
+            (Some(target), Some(deadline_secs)) => Some(StartupSync {
+                target,
+                started_at,
+                deadline: Duration::from_secs(deadline_secs),
+                initial: state.get_total_transaction_number()?,
+            }),
+            _ => None,
+        };
+
+        let gossip_name = if is_validator { "gossip" } else { "node_sync" };
+        let subsystems = [
+            ("batch", batch_subsystem_handle, true),
+            ("post_processing", post_processing_subsystem_handle, true),
+            (gossip_name, gossip_handle, true),
+            ("certificate_pusher", certificate_pusher_handle, true),
+            ("kafka_event_publisher", kafka_event_publisher_handle, true),
+            (
+                "object_count_reconciler",
+                object_count_reconciler_handle,
+                true,
+            ),
+            ("retention_reporter", Some(retention_reporter_handle), true),
+            (
+                "execution_load_sampler",
+                Some(execution_load_sampler_handle),
+                true,
+            ),
+            ("object_history_pruner", object_history_pruner_handle, true),
+            ("state_snapshotter", state_snapshotter_handle, true),
+            ("reexecution_verifier", reexecution_verifier_handle, true),
+            ("effects_firehose", effects_firehose_server, false),
+        ]
+        .into_iter()
+        .filter_map(|(name, handle, stops_on_shutdown)| {
+            handle.map(|handle| Subsystem {
+                name,
+                handle,
+                stops_on_shutdown,
+            })
+        })
+        .collect();
 
         let node = Self {
-            grpc_server,
-            _json_rpc_service: json_rpc_service,
-            _ws_subscription_service: ws_subscription_service,
-            _gossip_handle: gossip_handle,
-            _batch_subsystem_handle: batch_subsystem_handle,
-            _post_processing_subsystem_handle: post_processing_subsystem_handle,
+            grpc_server: Some(grpc_server),
+            grpc_shutdown,
+            grpc_requests_in_flight,
+            rpc,
+            subsystems,
+            secondary_catch_up_handle,
+            startup_sync,
+            shutdown_drain_timeout: Duration::from_secs(config.shutdown_drain_timeout_secs),
+            metrics_server,
             state,
         };
 
+        let readiness_state = node.state.clone();
+        admin::set_readiness_check(&node.state, move || {
+            readiness_state
+                .check_healthy()
+                .and_then(|()| readiness_state.check_syncing())
+                .map_err(|e| e.to_string())
+        });
+        admin::set_export_state(node.state.clone());
+        admin::set_diagnostics(
+            &node.state,
+            Diagnostics::new(
+                node.state.clone(),
+                config.db_path().to_path_buf(),
+                is_validator,
+                should_start_follower,
+            ),
+        );
+
         info!("SuiNode started!");
 
         Ok(node)
@@ -225,44 +1152,338 @@ impl SuiNode {
         self.state.clone()
     }
 
-    //TODO watch/wait on all the components
-    pub async fn wait(self) -> Result<()> {
-        self.grpc_server.await??;
+    /// Returns once the gRPC server stopped. Fails if a startup sync target is configured and
+    /// not reached in time, if a subsystem failed, if a supervised one crashed more often than
+    /// its restart policy allows, or if a JSON-RPC or websocket server stopped. The node can
+    /// still be shut down after it returned or was cancelled.
+    pub async fn wait(&mut self) -> Result<()> {
+        let subsystem_failure = supervisor::next_failure(&mut self.subsystems);
+        tokio::pin!(subsystem_failure);
+        let rpc_failure = self.rpc.failure();
+        tokio::pin!(rpc_failure);
+        if let Some(startup_sync) = &self.startup_sync {
+            tokio::select! {
+                result = join_once(&mut self.grpc_server) => return result?,
+                error = &mut subsystem_failure => return Err(error),
+                error = &mut rpc_failure => return Err(error),
+                result = startup_sync.wait(&self.state) => result?,
+            }
+        }
+        tokio::select! {
+            result = join_once(&mut self.grpc_server) => result??,
+            error = subsystem_failure => return Err(error),
+            error = rpc_failure => return Err(error),
+        }
 
         Ok(())
     }
+
+    /// Stop serving, giving the gRPC and JSON-RPC requests being served
+    /// `shutdown-drain-timeout-secs` to complete once no new connections are accepted. Returns
+    /// how many were still being served when the servers were closed.
+    ///
+    /// The subsystems are then stopped, each given `SUBSYSTEM_SHUTDOWN_TIMEOUT` to write out
+    /// what it was working on, and once they all ended the stores are flushed, so that the
+    /// node starts again without replaying their write-ahead logs. The stores are closed once
+    /// the returned future completes, so that they can be opened again in the same process.
+    pub async fn shutdown(mut self) -> u64 {
+        info!("Shutting down SuiNode");
+        if let Some(shutdown) = self.grpc_shutdown.take() {
+            let _ = shutdown.send(());
+        }
+        if let Some(handle) = &self.secondary_catch_up_handle {
+            handle.abort();
+        }
+        for subsystem in self.subsystems.iter().filter(|s| !s.stops_on_shutdown) {
+            subsystem.handle.abort();
+        }
+        let servers = match self.rpc {
+            RpcState::Gated(handle) => {
+                handle.abort();
+                // It may have started the servers meanwhile.
+                handle.await.ok().flatten()
+            }
+            RpcState::Running(servers) => Some(servers),
+            RpcState::Stopped => None,
+        };
+        let json_rpc_drained = async {
+            match servers {
+                Some(servers) => servers.shutdown(self.shutdown_drain_timeout).await,
+                None => 0,
+            }
+        };
+        let (grpc_cut_off, json_rpc_cut_off) = futures::join!(
+            drain_grpc(
+                self.grpc_server.take(),
+                self.shutdown_drain_timeout,
+                &self.grpc_requests_in_flight
+            ),
+            json_rpc_drained
+        );
+        for (server, cut_off) in [("gRPC", grpc_cut_off), ("JSON-RPC", json_rpc_cut_off)] {
+            if cut_off > 0 {
+                warn!(
+                    requests = cut_off,
+                    "Closed the {server} server with requests still in flight after {:?}",
+                    self.shutdown_drain_timeout
+                );
+            }
+        }
+
+        self.state.begin_shutdown();
+        supervisor::shut_down(self.subsystems, SUBSYSTEM_SHUTDOWN_TIMEOUT).await;
+        if let Some(handle) = self.secondary_catch_up_handle {
+            let _ = handle.await;
+        }
+        admin::release(&self.state);
+        self.metrics_server.stop().await;
+        match self.state.flush_stores() {
+            Ok(()) => info!("SuiNode shut down"),
+            Err(e) => warn!("Couldn't flush the stores: {e}"),
+        }
+        grpc_cut_off + json_rpc_cut_off
+    }
+}
+
+/// Wait up to `drain_timeout` for the gRPC server to end after it was told to stop, and abort
+/// it otherwise. Returns how many requests it was still serving when aborted.
+async fn drain_grpc(
+    server: Option<JoinHandle<Result<()>>>,
+    drain_timeout: Duration,
+    requests_in_flight: &IntGauge,
+) -> u64 {
+    let mut server = match server {
+        Some(server) => server,
+        None => return 0,
+    };
+    if tokio::time::timeout(drain_timeout, &mut server)
+        .await
+        .is_ok()
+    {
+        return 0;
+    }
+    let cut_off = requests_in_flight.get().max(0) as u64;
+    server.abort();
+    let _ = server.await;
+    cut_off
+}
+
+/// Write where node sync left off to `path`, for [`import_sync_state`] on the host the node
+/// moves to. The node must be stopped.
+pub fn export_sync_state(config: &NodeConfig, path: &Path) -> Result<()> {
+    let (store, follower_store, node_sync_store) = open_sync_stores(config)?;
+    let state = NodeSyncState::export(&store, &follower_store, &node_sync_store)?;
+    std::fs::write(path, state.to_bytes()?)?;
+    info!("Exported node sync state to {}", path.display());
+    Ok(())
+}
+
+/// Resume node sync from the state [`export_sync_state`] wrote on another host. The node's
+/// `store` must already be copied over, and the node must be stopped.
+pub fn import_sync_state(config: &NodeConfig, path: &Path) -> Result<()> {
+    let state = NodeSyncState::from_bytes(&std::fs::read(path)?)?;
+    let (store, follower_store, node_sync_store) = open_sync_stores(config)?;
+    state.import(&store, &follower_store, &node_sync_store)?;
+    info!("Imported node sync state from {}", path.display());
+    Ok(())
+}
+
+fn open_store(config: &NodeConfig) -> SuiResult<AuthorityStore> {
+    let group = |config: &ColumnFamilyGroupConfig| {
+        ColumnFamilyGroup::new(
+            config.block_cache_mb << 20,
+            config.write_buffer_mb << 20,
+            config.max_write_buffers,
+        )
+    };
+    let groups = config
+        .store_column_families
+        .as_ref()
+        .map(|cfs| StoreColumnFamilyGroups {
+            execution: group(&cfs.execution),
+            history: group(&cfs.history),
+            max_background_jobs: cfs.max_background_jobs,
+        });
+    AuthorityStore::try_open_with_column_family_groups(
+        config.db_path().join("store"),
+        None,
+        groups.as_ref(),
+    )
+}
+
+/// How often opening the store is retried while another process holds its lock.
+const DB_LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Open the store, retrying for `db-lock-timeout-secs` while another process holds its lock.
+async fn open_store_when_unlocked(config: &NodeConfig) -> Result<AuthorityStore> {
+    let timeout = Duration::from_secs(config.db_lock_timeout_secs.unwrap_or(0));
+    let started = Instant::now();
+    loop {
+        match open_store(config) {
+            Err(error) if is_db_locked_error(&error) => {
+                if started.elapsed() >= timeout {
+                    bail!(
+                        "The store at {} is still locked by another process after {:?}, \
+                        is a previous node still running? {error}",
+                        config.db_path().join("store").display(),
+                        timeout,
+                    );
+                }
+                debug!("The store is locked by another process, retrying");
+                tokio::time::sleep(DB_LOCK_RETRY_INTERVAL).await;
+            }
+            result => return Ok(result?),
+        }
+    }
+}
+
+fn open_sync_stores(config: &NodeConfig) -> Result<(AuthorityStore, FollowerStore, NodeSyncStore)> {
+    if !matches!(config.follow_mode, FollowMode::Standard) {
+        bail!("Nodes following a secondary store don't sync from validators");
+    }
+    Ok((
+        open_store(config)?,
+        FollowerStore::open(config.db_path().join("follower_db"))?,
+        NodeSyncStore::open(config.db_path().join("node_sync_db"))?,
+    ))
 }
 
 pub async fn build_node_server(
     state: Arc<AuthorityState>,
     config: &NodeConfig,
     prometheus_registry: &Registry,
-) -> Result<(Option<HttpServerHandle>, Option<WsServerHandle>)> {
+) -> Result<RpcServers> {
     // Validators do not expose these APIs
     if config.consensus_config().is_some() {
-        return Ok((None, None));
+        return Ok(RpcServers {
+            json_rpc: None,
+            websocket: None,
+            load_sampler: None,
+        });
     }
 
-    let mut server = JsonRpcServerBuilder::new(false, prometheus_registry)?;
+    let instances = match &config.json_rpc_address {
+        RpcListenAddress::Tcp(_) => config.json_rpc_server_instances.max(1),
+        RpcListenAddress::Unix(_) => {
+            if config.json_rpc_server_instances > 1 {
+                warn!("Serving the Unix socket with a single JSON-RPC server");
+            }
+            1
+        }
+    };
+    // Shared by all the instances, so that they add up.
+    let slow_query_threshold = config
+        .rpc_slow_query_threshold_ms
+        .map(Duration::from_millis);
+    let metrics = ApiMetrics::JsonRpcMetrics(
+        JsonRpcMetrics::new(prometheus_registry).with_slow_query_threshold(slow_query_threshold),
+    );
+    let mut load_sampler = None;
+    let load_signal = config.rpc_backpressure.as_ref().map(|backpressure_config| {
+        let signal = backpressure::load_signal(backpressure_config);
+        load_sampler = Some(backpressure::spawn_load_sampler(
+            &state,
+            backpressure_config.clone(),
+            signal.clone(),
+        ));
+        signal
+    });
+    if let (Some(_), RpcListenAddress::Unix(_)) = (&config.rpc_auth, &config.json_rpc_address) {
+        warn!("Ignoring rpc-auth, requests on a Unix socket are authorized by its permissions");
+    }
+    let auth = match &config.rpc_auth {
+        Some(auth_config) => Some(rpc_auth::request_auth(auth_config).await?),
+        None => None,
+    };
+    let response_cache = config.rpc_response_cache.as_ref().map(|cache_config| {
+        Arc::new(ResponseCache::new(
+            cache_config.entries,
+            cache_config.ttl_secs.map(Duration::from_secs),
+            prometheus_registry,
+        ))
+    });
 
-    server.register_module(ReadApi::new(state.clone()))?;
-    server.register_module(FullNodeApi::new(state.clone()))?;
-    server.register_module(BcsApiImpl::new(state.clone()))?;
+    let page_sizes = PageSizes::new(config.max_page_size)
+        .with_defaults(config.default_page_size, config.method_page_sizes.clone());
+    let call_timeouts = CallTimeouts::new(
+        config.rpc_default_timeout_ms.map(Duration::from_millis),
+        config
+            .rpc_method_timeouts_ms
+            .iter()
+            .map(|(method, timeout_ms)| (method.clone(), Duration::from_millis(*timeout_ms)))
+            .collect(),
+    );
 
-    if let Some(event_handler) = state.event_handler.clone() {
-        server.register_module(EventReadApiImpl::new(state.clone(), event_handler))?;
-    }
+    let mut rpc_server_handles = Vec::with_capacity(instances);
+    let mut requests_in_flight = None;
+    for _ in 0..instances {
+        let mut server = JsonRpcServerBuilder::with_metrics(metrics.clone())?;
+        if instances > 1 {
+            server.set_reuse_port();
+        }
+        if let Some(keepalive) = &config.json_rpc_tcp_keepalive {
+            server.set_tcp_keepalive(
+                Duration::from_secs(keepalive.idle_secs),
+                keepalive.interval_secs.map(Duration::from_secs),
+                keepalive.count,
+            );
+        }
+        if let Some(signal) = &load_signal {
+            server.set_load_signal(signal.clone());
+        }
+        if let Some(auth) = &auth {
+            server.set_auth(auth.clone());
+        }
+        server.set_call_timeouts(call_timeouts.clone());
+
+        let mut read_api = ReadApi::new(
+            state.clone(),
+            page_sizes.clone(),
+            config.rpc_max_object_size,
+            config.rpc_max_verification_batch,
+        );
+        if let Some(cache) = &response_cache {
+            read_api = read_api.with_response_cache(cache.clone());
+        }
+        if let Some(checkpoint) = config.pin_reads_at_checkpoint {
+            read_api = read_api.with_pinned_checkpoint(checkpoint);
+        }
+        server.register_module(read_api)?;
+        server.register_module(FullNodeApi::new(state.clone(), page_sizes.clone()))?;
+        server.register_module(BcsApiImpl::new(state.clone()))?;
 
-    let rpc_server_handle = server
-        .start(config.json_rpc_address)
-        .await?
-        .into_http_server_handle()
-        .expect("Expect a http server handle");
+        if let Some(event_handler) = state.event_handler.clone() {
+            server.register_module(EventReadApiImpl::new(
+                state.clone(),
+                event_handler,
+                page_sizes.clone(),
+            ))?;
+        }
+
+        requests_in_flight = server.requests_in_flight();
+        rpc_server_handles.push(match &config.json_rpc_address {
+            RpcListenAddress::Tcp(address) => server.start(*address).await?,
+            RpcListenAddress::Unix(path) => {
+                server.start_unix(path, config.json_rpc_socket_mode).await?
+            }
+        });
+    }
+    let requests_in_flight = requests_in_flight.expect("An HTTP server counts its requests");
 
     // TODO: we will change the conditions soon when we introduce txn subs
     let ws_server_handle = match (config.websocket_address, state.event_handler.clone()) {
+        (Some(_), Some(_)) if config.rpc_auth.is_some() => {
+            warn!("Not starting the websocket server, it can't authenticate requests");
+            None
+        }
         (Some(ws_addr), Some(event_handler)) => {
-            let mut server = JsonRpcServerBuilder::new(true, prometheus_registry)?;
+            let mut server = JsonRpcServerBuilder::new(true, prometheus_registry, None)?;
+            if let Some(interval) = config.websocket_ping_interval_ms {
+                server.set_ws_ping_interval(Duration::from_millis(interval));
+            }
+            if let Some(timeout) = config.websocket_idle_timeout_ms {
+                server.set_ws_idle_timeout(Duration::from_millis(timeout));
+            }
             server.register_module(EventStreamingApiImpl::new(state.clone(), event_handler))?;
             Some(
                 server
@@ -274,5 +1495,9 @@ pub async fn build_node_server(
         }
         _ => None,
     };
-    Ok((Some(rpc_server_handle), ws_server_handle))
+    Ok(RpcServers {
+        json_rpc: Some((rpc_server_handles, requests_in_flight)),
+        websocket: ws_server_handle,
+        load_sampler,
+    })
 }
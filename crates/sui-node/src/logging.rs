@@ -0,0 +1,97 @@
+// Copyright (c) 2022, Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! The tracing subscriber of the `sui-node` binary. It has the outputs of the telemetry
+//! subscriber the other binaries install from telemetry-subscribers, and a filter that
+//! [`crate::admin::LOGGING_ROUTE`] can replace at runtime, which that subscriber doesn't
+//! expose. Logs go to stdout, and as with the telemetry subscriber:
+//!
+//! - `RUST_LOG` sets the initial filter, `info` by default. It applies to every output, so
+//!   Tokio Console needs `tokio=trace,runtime=trace` in it.
+//! - `SUI_JSON_SPAN_LOGS` logs in the newline-delimited JSON of Bunyan, with span start and
+//!   end lines.
+//! - `SUI_TRACING_ENABLE` sends spans to a local Jaeger agent.
+//! - `SUI_TOKIO_CONSOLE` serves Tokio Console.
+
+use anyhow::{anyhow, Result};
+use once_cell::sync::OnceCell;
+use std::env;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_bunyan_formatter::{BunyanFormattingLayer, JsonStorageLayer};
+use tracing_subscriber::{
+    fmt,
+    layer::{Layered, SubscriberExt},
+    reload,
+    util::SubscriberInitExt,
+    EnvFilter, Layer, Registry,
+};
+
+const DEFAULT_LOG_FILTER: &str = "info";
+
+static LOG_FILTER_HANDLE: OnceCell<reload::Handle<EnvFilter, Registry>> = OnceCell::new();
+
+/// What the outputs are layered on.
+type Filtered = Layered<reload::Layer<EnvFilter, Registry>, Registry>;
+
+/// Flushes the logs and the spans not sent yet when dropped, so it must be kept until the
+/// process ends.
+pub struct LoggingGuard {
+    _log: WorkerGuard,
+    tracing: bool,
+}
+
+impl Drop for LoggingGuard {
+    fn drop(&mut self) {
+        if self.tracing {
+            opentelemetry::global::shutdown_tracer_provider();
+        }
+    }
+}
+
+/// Install the subscriber as the global default, see the module documentation.
+pub fn init(service_name: &str) -> Result<LoggingGuard> {
+    let env_filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(DEFAULT_LOG_FILTER));
+    let (filter, handle) = reload::Layer::new(env_filter);
+
+    let (writer, log_guard) = tracing_appender::non_blocking(std::io::stdout());
+    let log: Box<dyn Layer<Filtered> + Send + Sync> = if env::var_os("SUI_JSON_SPAN_LOGS").is_some()
+    {
+        Box::new(
+            JsonStorageLayer.and_then(BunyanFormattingLayer::new(service_name.to_string(), writer)),
+        )
+    } else {
+        Box::new(fmt::layer().with_writer(writer))
+    };
+    let tracing = match env::var_os("SUI_TRACING_ENABLE") {
+        Some(_) => {
+            let tracer = opentelemetry_jaeger::new_pipeline()
+                .with_service_name(service_name)
+                .install_batch(opentelemetry::runtime::Tokio)?;
+            Some(tracing_opentelemetry::layer().with_tracer(tracer))
+        }
+        None => None,
+    };
+    let traced = tracing.is_some();
+    let console = env::var_os("SUI_TOKIO_CONSOLE").map(|_| console_subscriber::spawn());
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(log)
+        .with(tracing)
+        .with(console)
+        .try_init()?;
+    LOG_FILTER_HANDLE
+        .set(handle)
+        .map_err(|_| anyhow!("Logging has already been initialized"))?;
+
+    Ok(LoggingGuard {
+        _log: log_guard,
+        tracing: traced,
+    })
+}
+
+/// The handle of the filter [`init`] installed, unless logging was set up otherwise.
+pub(crate) fn filter_handle() -> Option<&'static reload::Handle<EnvFilter, Registry>> {
+    LOG_FILTER_HANDLE.get()
+}
@@ -6,6 +6,7 @@ use clap::Parser;
 use multiaddr::Multiaddr;
 use std::path::PathBuf;
 use sui_config::{Config, NodeConfig};
+use tokio::signal::unix::{signal, SignalKind};
 
 #[derive(Parser)]
 #[clap(rename_all = "kebab-case")]
@@ -15,14 +16,26 @@ struct Args {
 
     #[clap(long, help = "Specify address to listen on")]
     listen_address: Option<Multiaddr>,
+
+    #[clap(
+        long,
+        conflicts_with = "import-sync-state",
+        help = "Write where node sync left off to this file and exit, for moving the node to \
+                another host"
+    )]
+    export_sync_state: Option<PathBuf>,
+
+    #[clap(
+        long,
+        help = "Resume node sync from a file written by --export-sync-state and exit. The \
+                store must already be copied from the old host"
+    )]
+    import_sync_state: Option<PathBuf>,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Initialize logging
-    let _guard = telemetry_subscribers::TelemetryConfig::new(env!("CARGO_BIN_NAME"))
-        .with_env()
-        .init();
+    let _guard = sui_node::logging::init(env!("CARGO_BIN_NAME"))?;
 
     let args = Args::parse();
 
@@ -32,8 +45,31 @@ async fn main() -> Result<()> {
         config.network_address = listen_address;
     }
 
-    let node = sui_node::SuiNode::start(&config).await?;
-    node.wait().await?;
+    if let Some(path) = args.export_sync_state {
+        return sui_node::export_sync_state(&config, &path);
+    }
+    if let Some(path) = args.import_sync_state {
+        return sui_node::import_sync_state(&config, &path);
+    }
+
+    let mut node = sui_node::SuiNode::start(&config).await?;
+    let result = tokio::select! {
+        result = node.wait() => result,
+        result = shutdown_signal() => result,
+    };
+    // Also when the node failed, so that the subsystems still running stop and the stores are
+    // flushed.
+    node.shutdown().await;
+
+    result
+}
 
+/// Resolves on SIGINT or SIGTERM, the signal orchestrators stop nodes with.
+async fn shutdown_signal() -> Result<()> {
+    let mut terminate = signal(SignalKind::terminate())?;
+    tokio::select! {
+        result = tokio::signal::ctrl_c() => result?,
+        _ = terminate.recv() => {}
+    }
     Ok(())
 }
@@ -1,27 +1,115 @@
 // Copyright (c) 2022, Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use axum::{extract::Extension, http::StatusCode, routing::get, Router};
+use crate::{admin, otlp_metrics, supervisor};
+use axum::{
+    body::Body,
+    extract::Extension,
+    http::{header, Request, StatusCode},
+    middleware::{self, Next},
+    response::Response,
+    routing::get,
+    Router,
+};
+use base64ct::{Base64, Encoding};
+use futures::future;
 use prometheus::{Registry, TextEncoder};
+use ring::constant_time::verify_slices_are_equal;
 use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use sui_config::{MetricsAuthConfig, OtlpMetricsConfig};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tracing::warn;
 
 const METRICS_ROUTE: &str = "/metrics";
 
-pub fn start_prometheus_server(addr: SocketAddr) -> Registry {
+/// How long history exports and the other admin requests being served have to complete when
+/// the server is stopped.
+const METRICS_SERVER_STOP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Serve `/metrics` and the admin routes on `addr`. Admin routes only answer loopback peers
+/// unless `admin_allow_remote` is set. When `auth` is set, requests to `/metrics` must carry
+/// the matching `Authorization` header, while the admin routes and `/ready` stay open to
+/// them. When `otlp` is set, the metrics are also pushed to an OpenTelemetry collector.
+/// Serves until the returned server is stopped or dropped.
+pub fn start_prometheus_server(
+    addr: SocketAddr,
+    admin_allow_remote: bool,
+    auth: Option<&MetricsAuthConfig>,
+    otlp: Option<&OtlpMetricsConfig>,
+) -> MetricsServer {
     let registry = Registry::new();
+    let (shutdown, stopped) = watch::channel(false);
+
+    let mut tasks = Vec::new();
+    if let Some(otlp) = otlp {
+        tasks.push(otlp_metrics::spawn_otlp_exporter(
+            registry.clone(),
+            otlp,
+            stopped.clone(),
+        ));
+    }
 
-    let app = Router::new()
+    let mut app = Router::new()
         .route(METRICS_ROUTE, get(metrics))
         .layer(Extension(registry.clone()));
+    if let Some(auth) = auth {
+        let expected = Arc::new(expected_authorization(auth));
+        app = app.route_layer(middleware::from_fn(
+            move |request: Request<Body>, next: Next<Body>| {
+                require_authorization(request, next, expected.clone())
+            },
+        ));
+    }
+    // Merged once the metrics route is wrapped, so that auth only applies to it.
+    let app = app.merge(admin::router(admin_allow_remote));
 
-    tokio::spawn(async move {
+    let mut stopped = stopped;
+    tasks.push(tokio::spawn(async move {
         axum::Server::bind(&addr)
-            .serve(app.into_make_service())
+            .serve(app.into_make_service_with_connect_info::<SocketAddr>())
+            .with_graceful_shutdown(supervisor::shutdown_signalled(&mut stopped))
             .await
             .unwrap();
-    });
+    }));
+
+    MetricsServer {
+        registry,
+        shutdown,
+        tasks,
+    }
+}
 
-    registry
+/// The server [`start_prometheus_server`] started, with the registry it serves.
+pub struct MetricsServer {
+    registry: Registry,
+    shutdown: watch::Sender<bool>,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl MetricsServer {
+    pub fn registry(&self) -> &Registry {
+        &self.registry
+    }
+
+    /// Stop serving and pushing metrics. The requests being served are given
+    /// `METRICS_SERVER_STOP_TIMEOUT` to complete, after which they are cut off.
+    pub async fn stop(self) {
+        self.shutdown.send_replace(true);
+        future::join_all(self.tasks.into_iter().map(|mut task| async move {
+            if tokio::time::timeout(METRICS_SERVER_STOP_TIMEOUT, &mut task)
+                .await
+                .is_err()
+            {
+                warn!("Metrics server didn't stop within {METRICS_SERVER_STOP_TIMEOUT:?}");
+                task.abort();
+                let _ = task.await;
+            }
+        }))
+        .await;
+    }
 }
 
 async fn metrics(Extension(registry): Extension<Registry>) -> (StatusCode, String) {
@@ -34,3 +122,96 @@ async fn metrics(Extension(registry): Extension<Registry>) -> (StatusCode, Strin
         ),
     }
 }
+
+fn expected_authorization(auth: &MetricsAuthConfig) -> String {
+    match auth {
+        MetricsAuthConfig::Basic { username, password } => format!(
+            "Basic {}",
+            Base64::encode_string(format!("{username}:{password}").as_bytes())
+        ),
+        MetricsAuthConfig::Bearer { token } => format!("Bearer {token}"),
+    }
+}
+
+async fn require_authorization(
+    request: Request<Body>,
+    next: Next<Body>,
+    expected: Arc<String>,
+) -> Result<Response, StatusCode> {
+    let authorized = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .map(|value| verify_slices_are_equal(value.as_bytes(), expected.as_bytes()).is_ok())
+        .unwrap_or(false);
+    if authorized {
+        Ok(next.run(request).await)
+    } else {
+        Err(StatusCode::UNAUTHORIZED)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expected_authorization() {
+        let basic = MetricsAuthConfig::Basic {
+            username: "Aladdin".to_string(),
+            password: "open sesame".to_string(),
+        };
+        // Example from RFC 7617
+        assert_eq!(
+            expected_authorization(&basic),
+            "Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ=="
+        );
+
+        let bearer = MetricsAuthConfig::Bearer {
+            token: "secret".to_string(),
+        };
+        assert_eq!(expected_authorization(&bearer), "Bearer secret");
+    }
+
+    #[tokio::test]
+    async fn test_authorization_only_applies_to_metrics() {
+        let addr: SocketAddr = format!("127.0.0.1:{}", sui_config::utils::get_available_port())
+            .parse()
+            .unwrap();
+        let auth = MetricsAuthConfig::Bearer {
+            token: "secret".to_string(),
+        };
+        let server = start_prometheus_server(addr, false, Some(&auth), None);
+        let client = reqwest::Client::new();
+        let get = |route: &str, token: Option<&str>| {
+            let mut request = client.get(format!("http://{addr}{route}"));
+            if let Some(token) = token {
+                request = request.bearer_auth(token);
+            }
+            async move {
+                // The server may not be listening yet.
+                loop {
+                    if let Ok(response) = request.try_clone().unwrap().send().await {
+                        return response.status();
+                    }
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                }
+            }
+        };
+
+        assert_eq!(get(METRICS_ROUTE, None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            get(METRICS_ROUTE, Some("wrong")).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(get(METRICS_ROUTE, Some("secret")).await, StatusCode::OK);
+        assert_ne!(
+            get(admin::READINESS_ROUTE, None).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_ne!(
+            get(admin::LOGGING_ROUTE, None).await,
+            StatusCode::UNAUTHORIZED
+        );
+        server.stop().await;
+    }
+}
@@ -0,0 +1,287 @@
+// Copyright (c) 2022, Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Pushes the metrics of a Prometheus registry to an OpenTelemetry collector, for pipelines
+//! that ingest OTLP rather than scrape.
+//!
+//! Metrics are sent with OTLP/HTTP in its JSON encoding, so no protobuf code is needed.
+//! Counters become cumulative monotonic sums, and gauges, histograms and summaries map to their
+//! OTLP equivalents. Prometheus histogram buckets are cumulative while OTLP ones aren't, so
+//! they are converted. Every data point is cumulative since the exporter started.
+
+use crate::supervisor;
+use prometheus::proto::{LabelPair, Metric, MetricFamily, MetricType};
+use prometheus::Registry;
+use serde_json::{json, Map, Value};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use sui_config::OtlpMetricsConfig;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+const SERVICE_NAME: &str = "sui-node";
+const SCOPE_NAME: &str = "sui-node/prometheus";
+
+/// OTLP `AggregationTemporality::Cumulative`.
+const CUMULATIVE: u8 = 2;
+
+/// Push the metrics of `registry` to `config.endpoint` every `config.push_interval_secs`.
+/// Pushes that fail are logged and dropped, the next one carries the same cumulative values.
+/// Stops once `shutdown` is set.
+pub(crate) fn spawn_otlp_exporter(
+    registry: Registry,
+    config: &OtlpMetricsConfig,
+    mut shutdown: watch::Receiver<bool>,
+) -> JoinHandle<()> {
+    let endpoint = config.endpoint.clone();
+    let push_interval = Duration::from_secs(config.push_interval_secs.max(1));
+    tokio::spawn(async move {
+        let client = reqwest::Client::new();
+        let start_nanos = unix_nanos();
+        let mut interval = tokio::time::interval(push_interval);
+        loop {
+            tokio::select! {
+                _ = interval.tick() => (),
+                _ = supervisor::shutdown_signalled(&mut shutdown) => return,
+            }
+            let body = encode(&registry.gather(), start_nanos, unix_nanos());
+            let result = client
+                .post(&endpoint)
+                .timeout(push_interval)
+                .json(&body)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status());
+            if let Err(e) = result {
+                warn!(endpoint = endpoint.as_str(), "Couldn't push metrics: {e}");
+            }
+        }
+    })
+}
+
+fn unix_nanos() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+/// The `ExportMetricsServiceRequest` for `families`. 64-bit integers are strings, as in the
+/// JSON mapping of protobuf.
+fn encode(families: &[MetricFamily], start_nanos: u64, now_nanos: u64) -> Value {
+    let metrics: Vec<Value> = families
+        .iter()
+        .map(|family| {
+            let point = |metric: &Metric, fields: Value| {
+                let time_nanos = match metric.get_timestamp_ms() {
+                    0 => now_nanos,
+                    ms => ms as u64 * 1_000_000,
+                };
+                let mut point = json!({
+                    "attributes": attributes(metric.get_label()),
+                    "startTimeUnixNano": start_nanos.to_string(),
+                    "timeUnixNano": time_nanos.to_string(),
+                });
+                if let (Some(point), Value::Object(fields)) = (point.as_object_mut(), fields) {
+                    point.extend(fields);
+                }
+                point
+            };
+            let metrics = family.get_metric().iter();
+            let (kind, data) = match family.get_field_type() {
+                MetricType::COUNTER => {
+                    let points: Vec<Value> = metrics
+                        .map(|m| point(m, json!({ "asDouble": m.get_counter().get_value() })))
+                        .collect();
+                    let sum = json!({
+                        "dataPoints": points,
+                        "aggregationTemporality": CUMULATIVE,
+                        "isMonotonic": true,
+                    });
+                    ("sum", sum)
+                }
+                MetricType::GAUGE => {
+                    let points: Vec<Value> = metrics
+                        .map(|m| point(m, json!({ "asDouble": m.get_gauge().get_value() })))
+                        .collect();
+                    ("gauge", json!({ "dataPoints": points }))
+                }
+                MetricType::UNTYPED => {
+                    let points: Vec<Value> = metrics
+                        .map(|m| point(m, json!({ "asDouble": m.get_untyped().get_value() })))
+                        .collect();
+                    ("gauge", json!({ "dataPoints": points }))
+                }
+                MetricType::HISTOGRAM => {
+                    let points: Vec<Value> = metrics
+                        .map(|m| {
+                            let histogram = m.get_histogram();
+                            let (bounds, counts) = histogram_buckets(
+                                histogram
+                                    .get_bucket()
+                                    .iter()
+                                    .map(|b| (b.get_upper_bound(), b.get_cumulative_count())),
+                                histogram.get_sample_count(),
+                            );
+                            let counts: Vec<String> =
+                                counts.iter().map(|count| count.to_string()).collect();
+                            let fields = json!({
+                                "count": histogram.get_sample_count().to_string(),
+                                "sum": histogram.get_sample_sum(),
+                                "explicitBounds": bounds,
+                                "bucketCounts": counts,
+                            });
+                            point(m, fields)
+                        })
+                        .collect();
+                    let histogram = json!({
+                        "dataPoints": points,
+                        "aggregationTemporality": CUMULATIVE,
+                    });
+                    ("histogram", histogram)
+                }
+                MetricType::SUMMARY => {
+                    let points: Vec<Value> = metrics
+                        .map(|m| {
+                            let summary = m.get_summary();
+                            let quantiles: Vec<Value> = summary
+                                .get_quantile()
+                                .iter()
+                                .map(|q| {
+                                    json!({ "quantile": q.get_quantile(), "value": q.get_value() })
+                                })
+                                .collect();
+                            let fields = json!({
+                                "count": summary.get_sample_count().to_string(),
+                                "sum": summary.get_sample_sum(),
+                                "quantileValues": quantiles,
+                            });
+                            point(m, fields)
+                        })
+                        .collect();
+                    ("summary", json!({ "dataPoints": points }))
+                }
+            };
+            let mut metric = Map::new();
+            metric.insert("name".to_string(), json!(family.get_name()));
+            metric.insert("description".to_string(), json!(family.get_help()));
+            metric.insert(kind.to_string(), data);
+            Value::Object(metric)
+        })
+        .collect();
+
+    json!({
+        "resourceMetrics": [{
+            "resource": {
+                "attributes": [{
+                    "key": "service.name",
+                    "value": { "stringValue": SERVICE_NAME },
+                }],
+            },
+            "scopeMetrics": [{
+                "scope": { "name": SCOPE_NAME },
+                "metrics": metrics,
+            }],
+        }]
+    })
+}
+
+fn attributes(labels: &[LabelPair]) -> Vec<Value> {
+    labels
+        .iter()
+        .map(|label| {
+            json!({
+                "key": label.get_name(),
+                "value": { "stringValue": label.get_value() },
+            })
+        })
+        .collect()
+}
+
+/// Turn the cumulative `(upper bound, count)` buckets of a Prometheus histogram into OTLP
+/// explicit bounds and per bucket counts, with a last bucket for what is above every bound.
+fn histogram_buckets(
+    buckets: impl Iterator<Item = (f64, u64)>,
+    sample_count: u64,
+) -> (Vec<f64>, Vec<u64>) {
+    let mut bounds = Vec::new();
+    let mut counts = Vec::new();
+    let mut below = 0;
+    for (upper_bound, cumulative_count) in buckets {
+        // Prometheus may list the implicit +Inf bucket.
+        if upper_bound.is_infinite() {
+            break;
+        }
+        bounds.push(upper_bound);
+        counts.push(cumulative_count.saturating_sub(below));
+        below = cumulative_count;
+    }
+    counts.push(sample_count.saturating_sub(below));
+    (bounds, counts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prometheus::{
+        register_histogram_with_registry, register_int_counter_vec_with_registry,
+        register_int_gauge_with_registry,
+    };
+
+    #[test]
+    fn test_histogram_buckets() {
+        let (bounds, counts) =
+            histogram_buckets(vec![(0.1, 2), (1.0, 5), (f64::INFINITY, 7)].into_iter(), 7);
+        assert_eq!(bounds, vec![0.1, 1.0]);
+        assert_eq!(counts, vec![2, 3, 2]);
+    }
+
+    #[test]
+    fn test_encode() {
+        let registry = Registry::new();
+        let counter = register_int_counter_vec_with_registry!(
+            "requests",
+            "Requests served",
+            &["method"],
+            registry
+        )
+        .unwrap();
+        counter.with_label_values(&["get"]).inc_by(3);
+        let gauge = register_int_gauge_with_registry!("peers", "Peers", registry).unwrap();
+        gauge.set(4);
+        let histogram =
+            register_histogram_with_registry!("latency", "Latency", vec![0.1, 1.0], registry)
+                .unwrap();
+        histogram.observe(0.5);
+        histogram.observe(5.0);
+
+        let body = encode(&registry.gather(), 1, 2);
+        let scope = &body["resourceMetrics"][0]["scopeMetrics"][0];
+        let metric = |name: &str| {
+            scope["metrics"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|metric| metric["name"] == name)
+                .unwrap()
+                .clone()
+        };
+
+        let requests = metric("requests");
+        assert_eq!(requests["sum"]["isMonotonic"], true);
+        let point = &requests["sum"]["dataPoints"][0];
+        assert_eq!(point["asDouble"], 3.0);
+        assert_eq!(point["attributes"][0]["key"], "method");
+        assert_eq!(point["attributes"][0]["value"]["stringValue"], "get");
+        assert_eq!(point["startTimeUnixNano"], "1");
+        assert_eq!(point["timeUnixNano"], "2");
+
+        assert_eq!(metric("peers")["gauge"]["dataPoints"][0]["asDouble"], 4.0);
+
+        let point = &metric("latency")["histogram"]["dataPoints"][0];
+        assert_eq!(point["count"], "2");
+        assert_eq!(point["sum"], 5.5);
+        assert_eq!(point["explicitBounds"], json!([0.1, 1.0]));
+        assert_eq!(point["bucketCounts"], json!(["0", "1", "1"]));
+    }
+}
@@ -0,0 +1,67 @@
+// Copyright (c) 2022, Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Sets up the authentication of JSON-RPC requests, see [`sui_json_rpc::auth`], and keeps the
+//! keys JWTs are verified with up to date with the JWKS they are published in.
+
+use anyhow::Result;
+use std::time::Duration;
+use sui_config::RpcAuthConfig;
+use sui_json_rpc::auth::{JwtValidator, RequestAuth};
+use tracing::{info, warn};
+
+const JWKS_FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Fails if the JWKS can't be fetched, rather than starting a node that rejects every JWT.
+pub(crate) async fn request_auth(config: &RpcAuthConfig) -> Result<RequestAuth> {
+    let jwt = match &config.jwks_url {
+        Some(url) => {
+            let jwt = JwtValidator::new(config.jwt_issuer.clone(), config.jwt_audience.clone());
+            let client = reqwest::Client::new();
+            let keys = fetch_jwks(&client, url, &jwt).await?;
+            info!(url = url.as_str(), "Verifying JWTs with {keys} keys");
+            spawn_jwks_refresher(
+                client,
+                url.clone(),
+                Duration::from_secs(config.jwks_refresh_interval_secs.max(1)),
+                jwt.clone(),
+            );
+            Some(jwt)
+        }
+        None => None,
+    };
+    Ok(RequestAuth::new(config.api_keys.clone(), jwt))
+}
+
+/// Fetch the JWKS at `url` every `interval`. Failures are logged and the keys fetched last
+/// are kept.
+fn spawn_jwks_refresher(
+    client: reqwest::Client,
+    url: String,
+    interval: Duration,
+    jwt: JwtValidator,
+) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(interval);
+        // The first tick is immediate, and the keys were just fetched.
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            if let Err(e) = fetch_jwks(&client, &url, &jwt).await {
+                warn!(url = url.as_str(), "Couldn't refresh the JWKS: {e}");
+            }
+        }
+    });
+}
+
+async fn fetch_jwks(client: &reqwest::Client, url: &str, jwt: &JwtValidator) -> Result<usize> {
+    let jwks = client
+        .get(url)
+        .timeout(JWKS_FETCH_TIMEOUT)
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+    jwt.set_jwks(&jwks)
+}
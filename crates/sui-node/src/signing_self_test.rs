@@ -0,0 +1,64 @@
+// Copyright (c) 2022, Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Checks at startup that a validator signs with the key genesis records for it, so that a
+//! wrong or corrupt key file fails the node at boot rather than once it signs something live.
+
+use anyhow::{anyhow, Result};
+use multiaddr::Multiaddr;
+use serde::{Deserialize, Serialize};
+use sui_config::ValidatorInfo;
+use sui_types::crypto::{AuthoritySignature, BcsSignable, KeyPair};
+
+#[derive(Serialize, Deserialize)]
+struct SigningSelfTest(String);
+
+impl BcsSignable for SigningSelfTest {}
+
+/// Sign a dummy message with `key_pair` and verify the signature with the public key of the
+/// validator at `network_address` among `validators`.
+pub(crate) fn check_signing_key(
+    key_pair: &KeyPair,
+    network_address: &Multiaddr,
+    validators: &[ValidatorInfo],
+) -> Result<()> {
+    let validator = validators
+        .iter()
+        .find(|validator| validator.network_address() == network_address)
+        .ok_or_else(|| {
+            anyhow!("Signing self-test failed: no genesis validator is at {network_address}")
+        })?;
+    let message = SigningSelfTest("signing self-test".to_string());
+    AuthoritySignature::new(&message, key_pair)
+        .verify(&message, validator.public_key())
+        .map_err(|e| {
+            anyhow!(
+                "Signing self-test failed: the key pair doesn't sign for {:?}, the public key \
+                genesis records for the validator at {network_address}: {e}",
+                validator.public_key()
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sui_types::crypto::get_key_pair;
+
+    #[test]
+    fn test_check_signing_key() {
+        let (_, key_pair) = get_key_pair();
+        let address: Multiaddr = "/ip4/127.0.0.1/tcp/8080/http".parse().unwrap();
+        let validators = vec![ValidatorInfo {
+            public_key: *key_pair.public_key_bytes(),
+            stake: 1,
+            network_address: address.clone(),
+        }];
+        check_signing_key(&key_pair, &address, &validators).unwrap();
+
+        let (_, other) = get_key_pair();
+        assert!(check_signing_key(&other, &address, &validators).is_err());
+        let elsewhere = "/ip4/127.0.0.1/tcp/8081/http".parse().unwrap();
+        assert!(check_signing_key(&key_pair, &elsewhere, &validators).is_err());
+    }
+}
@@ -0,0 +1,183 @@
+// Copyright (c) 2022, Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Periodic snapshots of the state of a validator, see [`StateSnapshotConfig`].
+//!
+//! The checkpoint store is copied while its lock is held, so that its copy ends at a checkpoint
+//! boundary, and the store right after without it, so that execution isn't held up. The store
+//! copy therefore holds every transaction of the snapshot checkpoints, and maybe some executed
+//! since.
+
+use crate::supervisor;
+use anyhow::Result;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use sui_config::StateSnapshotConfig;
+use sui_core::authority::AuthorityStore;
+use sui_core::checkpoints::CheckpointStore;
+use sui_types::batch::TxSequenceNumber;
+use sui_types::messages_checkpoint::CheckpointSequenceNumber;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+/// How often the snapshotter checks whether enough checkpoints were made since the last
+/// snapshot.
+const SNAPSHOT_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// Describes the state a snapshot holds, in its `manifest.json`.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct SnapshotManifest {
+    /// The last checkpoint of the checkpoint store copy.
+    pub checkpoint: CheckpointSequenceNumber,
+    /// The transactions of the checkpoints of the snapshot have local sequence numbers below it.
+    pub next_checkpoint_transaction: TxSequenceNumber,
+    /// The store copy holds at least this many executed transactions.
+    pub executed_transactions: TxSequenceNumber,
+    pub timestamp_ms: u64,
+}
+
+pub(crate) struct StateSnapshotter {
+    config: StateSnapshotConfig,
+    store: Arc<AuthorityStore>,
+    checkpoints: Arc<Mutex<CheckpointStore>>,
+    /// The checkpoint of the latest snapshot in `config.dir`.
+    last_snapshot: Mutex<Option<CheckpointSequenceNumber>>,
+}
+
+impl StateSnapshotter {
+    pub fn new(
+        config: StateSnapshotConfig,
+        store: Arc<AuthorityStore>,
+        checkpoints: Arc<Mutex<CheckpointStore>>,
+    ) -> Result<Self> {
+        fs::create_dir_all(&config.dir)?;
+        let last_snapshot = snapshots(&config.dir)?
+            .last()
+            .map(|(checkpoint, _)| *checkpoint);
+        Ok(Self {
+            config,
+            store,
+            checkpoints,
+            last_snapshot: Mutex::new(last_snapshot),
+        })
+    }
+
+    /// Take snapshots until `shutdown` is set.
+    pub fn spawn(self, mut shutdown: watch::Receiver<bool>) -> JoinHandle<Result<()>> {
+        let snapshotter = Arc::new(self);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(SNAPSHOT_POLL_INTERVAL);
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => (),
+                    _ = supervisor::shutdown_signalled(&mut shutdown) => return Ok(()),
+                }
+                let started = Instant::now();
+                let snapshotting = snapshotter.clone();
+                // Copying the stores flushes their memtables, which blocks.
+                match tokio::task::spawn_blocking(move || snapshotting.snapshot_if_due()).await {
+                    Ok(Ok(Some(manifest))) => info!(
+                        checkpoint = manifest.checkpoint,
+                        executed_transactions = manifest.executed_transactions,
+                        "Took a state snapshot in {:?}",
+                        started.elapsed()
+                    ),
+                    Ok(Ok(None)) => {}
+                    Ok(Err(e)) => warn!("Couldn't take a state snapshot: {e}"),
+                    Err(e) => warn!("State snapshot failed: {e}"),
+                }
+            }
+        })
+    }
+
+    /// Take a snapshot if `interval-checkpoints` checkpoints were made since the last one, and
+    /// delete the oldest beyond `max-snapshots`.
+    fn snapshot_if_due(&self) -> Result<Option<SnapshotManifest>> {
+        let mut last_snapshot = self.last_snapshot.lock();
+        let mut checkpoints = self.checkpoints.lock();
+        let checkpoint = match checkpoints.next_checkpoint().checked_sub(1) {
+            Some(checkpoint) => checkpoint,
+            None => return Ok(None),
+        };
+        if let Some(last) = *last_snapshot {
+            if checkpoint < last + self.config.interval_checkpoints {
+                return Ok(None);
+            }
+        }
+
+        let tmp = self.config.dir.join(format!("{checkpoint}.tmp"));
+        if tmp.exists() {
+            fs::remove_dir_all(&tmp)?;
+        }
+        fs::create_dir(&tmp)?;
+        let next_checkpoint_transaction = checkpoints.next_transaction_sequence_expected();
+        checkpoints.create_db_checkpoint(&tmp.join("checkpoints"))?;
+        drop(checkpoints);
+
+        let executed_transactions = self.store.next_sequence_number()?;
+        self.store.create_db_checkpoint(&tmp.join("store"))?;
+        let manifest = SnapshotManifest {
+            checkpoint,
+            next_checkpoint_transaction,
+            executed_transactions,
+            timestamp_ms: SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as u64,
+        };
+        fs::write(
+            tmp.join(MANIFEST_FILE),
+            serde_json::to_vec_pretty(&manifest)?,
+        )?;
+        fs::rename(&tmp, self.config.dir.join(checkpoint.to_string()))?;
+        *last_snapshot = Some(checkpoint);
+
+        let snapshots = snapshots(&self.config.dir)?;
+        let excess = snapshots.len().saturating_sub(self.config.max_snapshots);
+        for (_, path) in &snapshots[..excess] {
+            fs::remove_dir_all(path)?;
+        }
+        Ok(Some(manifest))
+    }
+}
+
+/// The complete snapshots in `dir`, oldest first.
+fn snapshots(dir: &Path) -> Result<Vec<(CheckpointSequenceNumber, PathBuf)>> {
+    let mut snapshots = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let checkpoint = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.parse().ok());
+        if let Some(checkpoint) = checkpoint {
+            snapshots.push((checkpoint, path));
+        }
+    }
+    snapshots.sort();
+    Ok(snapshots)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshots_skip_incomplete() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["12", "3", "14.tmp", "other"] {
+            fs::create_dir(dir.path().join(name)).unwrap();
+        }
+        let snapshots = snapshots(dir.path()).unwrap();
+        let checkpoints: Vec<_> = snapshots
+            .iter()
+            .map(|(checkpoint, _)| *checkpoint)
+            .collect();
+        assert_eq!(checkpoints, vec![3, 12]);
+    }
+}
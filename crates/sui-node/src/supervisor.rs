@@ -0,0 +1,253 @@
+// Copyright (c) 2022, Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Restarts the background subsystems of a node when they crash, so that a panic in gossip or
+//! post processing is either recovered from or fails the node, rather than leaving it serving
+//! stale data.
+
+use anyhow::{anyhow, bail, Result};
+use futures::future;
+use prometheus::{IntCounterVec, Registry};
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use sui_config::SubsystemRestartConfig;
+use sui_core::metrics::{self, MetricsNamespace};
+use tokio::sync::watch;
+use tokio::task::{JoinError, JoinHandle};
+use tracing::{error, info, warn};
+
+pub(crate) struct Supervisor {
+    policy: SubsystemRestartConfig,
+    restarts: IntCounterVec,
+    /// Set on shutdown, after which crashed subsystems aren't restarted anymore.
+    shutdown: watch::Receiver<bool>,
+}
+
+impl Supervisor {
+    pub fn new(
+        policy: SubsystemRestartConfig,
+        registry: &Registry,
+        shutdown: watch::Receiver<bool>,
+    ) -> Self {
+        let restarts = MetricsNamespace::new(registry, metrics::NODE)
+            .int_counter_vec(
+                "subsystem_restarts",
+                "Restarts of background subsystems after they crashed",
+                &["subsystem"],
+            )
+            .unwrap();
+        Self {
+            policy,
+            restarts,
+            shutdown,
+        }
+    }
+
+    /// Run the subsystem `start` creates until it exits cleanly. When it panics or fails, a
+    /// new one is started after a backoff, and once it crashed more than `max-restarts` times
+    /// the returned task fails with its last error. It also fails instead of restarting it once
+    /// shutting down, and aborting the returned task aborts the subsystem.
+    pub fn supervise<F, Fut>(&self, name: &'static str, start: F) -> JoinHandle<Result<()>>
+    where
+        F: Fn() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        let max_restarts = self.policy.max_restarts;
+        let mut backoff = Duration::from_millis(self.policy.initial_backoff_ms);
+        let max_backoff = Duration::from_millis(self.policy.max_backoff_ms);
+        let restarts = self.restarts.with_label_values(&[name]);
+        let mut shutdown = self.shutdown.clone();
+        tokio::spawn(async move {
+            let mut restarted = 0;
+            loop {
+                // Running it in its own task turns a panic into an error.
+                let error = match AbortOnDrop(tokio::spawn(start())).await {
+                    Ok(Ok(())) => return Ok(()),
+                    Ok(Err(e)) => e,
+                    Err(e) => anyhow!(e),
+                };
+                if restarted == max_restarts {
+                    error!(
+                        subsystem = name,
+                        "Subsystem crashed, failing the node: {error}"
+                    );
+                    bail!("Subsystem {name} crashed after {restarted} restarts: {error}");
+                }
+                restarted += 1;
+                restarts.inc();
+                error!(
+                    subsystem = name,
+                    "Subsystem crashed, restart {restarted}/{max_restarts} in {backoff:?}: {error}"
+                );
+                tokio::select! {
+                    _ = tokio::time::sleep(backoff) => (),
+                    _ = shutdown_signalled(&mut shutdown) => {
+                        bail!("Subsystem {name} crashed while shutting down: {error}")
+                    }
+                }
+                backoff = (backoff * 2).min(max_backoff);
+            }
+        })
+    }
+}
+
+/// A task that is aborted when dropped, unlike a `JoinHandle` which detaches it.
+struct AbortOnDrop<T>(JoinHandle<T>);
+
+impl<T> Future for AbortOnDrop<T> {
+    type Output = Result<T, JoinError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.0).poll(cx)
+    }
+}
+
+impl<T> Drop for AbortOnDrop<T> {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Resolves once `shutdown` is set, or its sender was dropped.
+pub(crate) async fn shutdown_signalled(shutdown: &mut watch::Receiver<bool>) {
+    while !*shutdown.borrow() {
+        if shutdown.changed().await.is_err() {
+            return;
+        }
+    }
+}
+
+/// A background task of a node.
+pub(crate) struct Subsystem {
+    pub name: &'static str,
+    pub handle: JoinHandle<Result<()>>,
+    /// Whether it returns on `AuthorityState::begin_shutdown`, rather than being aborted.
+    pub stops_on_shutdown: bool,
+}
+
+/// Resolves with the error of the first of `subsystems` that failed, and never if they all
+/// keep running or exit cleanly. Those that exited are removed, so that it can be called again
+/// and the others can still be shut down when it is dropped.
+pub(crate) async fn next_failure(subsystems: &mut Vec<Subsystem>) -> anyhow::Error {
+    while !subsystems.is_empty() {
+        let (result, index, _) =
+            future::select_all(subsystems.iter_mut().map(|subsystem| &mut subsystem.handle)).await;
+        let name = subsystems.remove(index).name;
+        match result {
+            Ok(Ok(())) => info!(subsystem = name, "Subsystem exited"),
+            Ok(Err(e)) => return e,
+            Err(e) => return e.into(),
+        }
+    }
+    future::pending().await
+}
+
+/// Wait up to `timeout` for each of `subsystems` that stops on shutdown to do so, once
+/// `AuthorityState::begin_shutdown` was called, and abort the others. Returns once they all
+/// ended, so that nothing they hold is in use anymore.
+pub(crate) async fn shut_down(subsystems: Vec<Subsystem>, timeout: Duration) {
+    future::join_all(subsystems.into_iter().map(|mut subsystem| async move {
+        if !subsystem.stops_on_shutdown {
+            subsystem.handle.abort();
+            let _ = subsystem.handle.await;
+            return;
+        }
+        let name = subsystem.name;
+        match tokio::time::timeout(timeout, &mut subsystem.handle).await {
+            Ok(Ok(Ok(()))) => info!(subsystem = name, "Subsystem stopped"),
+            Ok(Ok(Err(e))) => warn!(subsystem = name, "Subsystem failed while stopping: {e}"),
+            Ok(Err(e)) => warn!(subsystem = name, "Subsystem crashed while stopping: {e}"),
+            Err(_) => {
+                warn!(
+                    subsystem = name,
+                    "Subsystem didn't stop within {timeout:?}, aborting it"
+                );
+                subsystem.handle.abort();
+                let _ = subsystem.handle.await;
+            }
+        }
+    }))
+    .await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    fn supervisor(
+        max_restarts: u32,
+        registry: &Registry,
+        shutdown: watch::Receiver<bool>,
+    ) -> Supervisor {
+        let policy = SubsystemRestartConfig {
+            max_restarts,
+            initial_backoff_ms: 1,
+            max_backoff_ms: 2,
+        };
+        Supervisor::new(policy, registry, shutdown)
+    }
+
+    #[tokio::test]
+    async fn test_restart_after_panic() {
+        let registry = Registry::new();
+        let (_shutdown, receiver) = watch::channel(false);
+        let supervisor = supervisor(3, &registry, receiver);
+        let runs = Arc::new(AtomicU32::new(0));
+        let counted = runs.clone();
+        let handle = supervisor.supervise("test", move || {
+            let run = counted.fetch_add(1, Ordering::SeqCst);
+            async move {
+                if run < 2 {
+                    panic!("crashed");
+                }
+                Ok(())
+            }
+        });
+        handle.await.unwrap().unwrap();
+        assert_eq!(runs.load(Ordering::SeqCst), 3);
+        assert_eq!(supervisor.restarts.with_label_values(&["test"]).get(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_fail_after_max_restarts() {
+        let registry = Registry::new();
+        let (_shutdown, receiver) = watch::channel(false);
+        let supervisor = supervisor(2, &registry, receiver);
+        let runs = Arc::new(AtomicU32::new(0));
+        let counted = runs.clone();
+        let handle = supervisor.supervise("test", move || {
+            counted.fetch_add(1, Ordering::SeqCst);
+            async { bail!("failed") }
+        });
+        let mut subsystems = vec![Subsystem {
+            name: "test",
+            handle,
+            stops_on_shutdown: true,
+        }];
+        let error = next_failure(&mut subsystems).await;
+        assert!(error.to_string().contains("after 2 restarts"));
+        assert_eq!(runs.load(Ordering::SeqCst), 3);
+        assert!(subsystems.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_no_restart_while_shutting_down() {
+        let registry = Registry::new();
+        let (shutdown, receiver) = watch::channel(false);
+        let supervisor = supervisor(3, &registry, receiver);
+        shutdown.send_replace(true);
+        let runs = Arc::new(AtomicU32::new(0));
+        let counted = runs.clone();
+        let handle = supervisor.supervise("test", move || {
+            counted.fetch_add(1, Ordering::SeqCst);
+            async { bail!("failed") }
+        });
+        let error = handle.await.unwrap().unwrap_err();
+        assert!(error.to_string().contains("while shutting down"));
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,98 @@
+// Copyright (c) 2022, Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::path::Path;
+use sui_types::{
+    base_types::TransactionDigest,
+    batch::TxSequenceNumber,
+    error::{SuiError, SuiResult},
+};
+use typed_store::rocks::DBMap;
+use typed_store::{reopen, traits::Map};
+
+use crate::default_db_options;
+
+use tracing::debug;
+
+/// DeadLetterStore keeps the transactions that post processing (indexing and event emission)
+/// gave up on after exhausting its retries, along with the last error, so that they can be
+/// inspected and reprocessed later.
+pub struct DeadLetterStore {
+    dead_letters: DBMap<TxSequenceNumber, (TransactionDigest, String)>,
+}
+
+impl DeadLetterStore {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, SuiError> {
+        let (options, _) = default_db_options(None, None);
+
+        let db = {
+            let path = &path;
+            let db_options = Some(options.clone());
+            let opt_cfs: &[(&str, &rocksdb::Options)] = &[("dead_letters", &options)];
+            typed_store::rocks::open_cf_opts(path, db_options, opt_cfs)
+        }
+        .map_err(SuiError::StorageError)?;
+
+        let dead_letters =
+            reopen!(&db, "dead_letters";<TxSequenceNumber, (TransactionDigest, String)>);
+
+        Ok(Self { dead_letters })
+    }
+
+    pub fn insert(
+        &self,
+        seq: TxSequenceNumber,
+        digest: &TransactionDigest,
+        error: String,
+    ) -> SuiResult {
+        debug!(?seq, ?digest, "insert dead letter");
+        self.dead_letters
+            .insert(&seq, &(*digest, error))
+            .map_err(SuiError::StorageError)
+    }
+
+    pub fn remove(&self, seq: TxSequenceNumber) -> SuiResult {
+        self.dead_letters
+            .remove(&seq)
+            .map_err(SuiError::StorageError)
+    }
+
+    /// All dead letters, ordered by transaction sequence number.
+    pub fn iter(
+        &self,
+    ) -> impl Iterator<Item = (TxSequenceNumber, (TransactionDigest, String))> + '_ {
+        self.dead_letters.iter()
+    }
+
+    pub fn count(&self) -> usize {
+        self.dead_letters.iter().count()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::dead_letter_store::DeadLetterStore;
+    use sui_types::base_types::TransactionDigest;
+
+    #[test]
+    fn test_dead_letter_store() {
+        let working_dir = tempfile::tempdir().unwrap();
+
+        let store = DeadLetterStore::open(&working_dir).expect("cannot open db");
+        assert_eq!(store.count(), 0);
+
+        let d1 = TransactionDigest::random();
+        let d2 = TransactionDigest::random();
+        store.insert(7, &d1, "db busy".into()).expect("write error");
+        store.insert(3, &d2, "db busy".into()).expect("write error");
+        assert_eq!(store.count(), 2);
+
+        let letters: Vec<_> = store.iter().collect();
+        assert_eq!(letters[0], (3, (d2, "db busy".to_string())));
+        assert_eq!(letters[1], (7, (d1, "db busy".to_string())));
+
+        store.remove(3).expect("write error");
+        assert_eq!(store.count(), 1);
+        assert_eq!(store.iter().next().unwrap().0, 7);
+    }
+}
@@ -17,13 +17,14 @@ use futures::prelude::stream::BoxStream;
 use move_core_types::language_storage::ModuleId;
 use move_core_types::value::MoveValue;
 use serde_json::Value;
-use sui_types::base_types::{ObjectID, TransactionDigest};
+use sui_types::base_types::{ObjectID, SuiAddress, TransactionDigest};
 use sui_types::error::SuiError;
-use sui_types::event::{EventEnvelope, EventType};
+use sui_types::event::{Event, EventEnvelope, EventType};
+use sui_types::event_filter::MoveEventTypeFilter;
 use tokio_stream::StreamExt;
 
 pub mod sql;
-pub use sql::SqlEventStore;
+pub use sql::{SecondaryIndex, SqlEventStore};
 
 use flexstr::SharedStr;
 
@@ -57,6 +58,23 @@ pub struct StoredEvent {
     /// * `destination` - address, in hex bytes, used by TransferObject
     /// * `type` - used by TransferObject (TransferType - Coin, ToAddress, ToObject)
     fields: Vec<(SharedStr, EventValue)>, // Change this to something based on CBOR for binary values, or our own value types for efficiency
+    /// The event as emitted.  Not present for events ingested by versions which didn't store it
+    event: Option<Event>,
+}
+
+impl StoredEvent {
+    /// UTC timestamp in milliseconds
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    pub fn tx_digest(&self) -> Option<TransactionDigest> {
+        self.tx_digest
+    }
+
+    pub fn event(&self) -> Option<&Event> {
+        self.event.as_ref()
+    }
 }
 
 /// Enum for different types of values returnable from events in the EventStore
@@ -123,6 +141,10 @@ pub trait EventStore {
         end_checkpoint: u64,
     ) -> Result<StreamedResult, SuiError>;
 
+    /// The timestamp of the oldest event stored, None if there are no events.  Queries for
+    /// times before it can't return events, whether or not there were any.
+    async fn oldest_timestamp(&self) -> Result<Option<u64>, SuiError>;
+
     /// Queries all Move events belonging to a certain Module ID within a given time window.
     /// Will return at most limit of the most recent events within the window, sorted in descending time.
     async fn events_by_module_id(
@@ -132,6 +154,38 @@ pub trait EventStore {
         module: ModuleId,
         limit: usize,
     ) -> Result<Vec<StoredEvent>, SuiError>;
+
+    /// Queries all events emitted by transactions of a given sender within a given time window.
+    /// Will return at most limit of the most recent events within the window, sorted in descending time.
+    async fn events_by_sender(
+        &self,
+        start_time: u64,
+        end_time: u64,
+        sender: SuiAddress,
+        limit: usize,
+    ) -> Result<Vec<StoredEvent>, SuiError>;
+
+    /// Queries all events about a given object within a given time window, see
+    /// [`Event::object_id`].
+    /// Will return at most limit of the most recent events within the window, sorted in descending time.
+    async fn events_by_object(
+        &self,
+        start_time: u64,
+        end_time: u64,
+        object: ObjectID,
+        limit: usize,
+    ) -> Result<Vec<StoredEvent>, SuiError>;
+
+    /// Queries all Move events whose type matches `filter` within a given time window. Events
+    /// ingested by versions which didn't record their type are never returned.
+    /// Will return at most limit of the most recent events within the window, sorted in descending time.
+    async fn events_by_move_event_type(
+        &self,
+        start_time: u64,
+        end_time: u64,
+        filter: MoveEventTypeFilter,
+        limit: usize,
+    ) -> Result<Vec<StoredEvent>, SuiError>;
 }
 
 /// EventStoreType contains different implementations of EventStores, but implements the EventStore trait.
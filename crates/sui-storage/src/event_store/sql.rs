@@ -11,10 +11,16 @@ use std::path::Path;
 use std::sync::atomic::{AtomicU64, Ordering};
 use strum::{EnumMessage, IntoEnumIterator};
 
+use move_core_types::account_address::AccountAddress;
+use move_core_types::language_storage::StructTag;
+use prometheus::core::{Collector, Desc};
+use prometheus::proto::MetricFamily;
+use prometheus::{IntCounter, IntGaugeVec, Opts, Registry};
 use sqlx::{
-    sqlite::{SqliteConnectOptions, SqliteRow},
+    sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteRow},
     Executor, Row, SqlitePool,
 };
+use sui_config::node::{DEFAULT_EVENT_STORE_READ_POOL_SIZE, DEFAULT_EVENT_STORE_WRITE_POOL_SIZE};
 use sui_types::error::SuiError;
 use sui_types::event::Event;
 use tracing::{debug, info, warn};
@@ -22,6 +28,10 @@ use tracing::{debug, info, warn};
 /// Maximum number of events one can ask for right now
 const MAX_LIMIT: usize = 5000;
 
+/// Secondary indexes built unless chosen otherwise
+pub const DEFAULT_SECONDARY_INDEXES: &[SecondaryIndex] =
+    &[SecondaryIndex::EventType, SecondaryIndex::Package];
+
 /// Sqlite-based Event Store
 ///
 /// ## Data Model
@@ -29,11 +39,37 @@ const MAX_LIMIT: usize = 5000;
 /// - object_id is used for multiple purposes, including the Publish package ID
 /// - event_type is an integer in order to save space and corresponds to EventType discriminant
 /// - fields is JSON for now (for easy JSON filtering) and contains all fields not in main columns
+/// - move_event_type is the type of Move events, see [`move_event_type_key`], so that they can
+///   be queried by type, module or package with a range scan
+///
+/// ## Connection pools
+/// Reads and writes go through separate connection pools, so that queries do not have to wait
+/// behind event ingestion for a free connection.  For in-memory databases both pools are the
+/// same, since every pool would otherwise get its own private database.
+///
+/// ## Timestamps
+/// Event timestamps come from the clock of the node that processed the transaction, which keeps
+/// them nondecreasing in sequence order. Events ingested with an earlier timestamp than an
+/// event of a lower sequence number are stored anyway, logged and counted by the
+/// `event_store_non_monotonic_timestamps` metric, since time range queries may miss them.
+///
+/// ## Indexes
+/// Timestamps and transaction digests are always indexed.  Which of the [`SecondaryIndex`]es
+/// are built is chosen with [`SqlEventStore::with_indexes`], since each one slows down
+/// ingestion.  Queries filtering on a column which isn't indexed scan the time range instead.
 pub struct SqlEventStore {
-    pool: SqlitePool,
+    /// Pool used by add_events and initialization
+    write_pool: SqlitePool,
+    /// Pool used by all the queries
+    read_pool: SqlitePool,
     // Sequence number is used to prevent previously ingested events from being ingested again
     // It acts as a cache, as the seq_num field is also written to the DB.
     seq_num: AtomicU64,
+    /// Latest timestamp ingested, to flag the events whose timestamp goes back.
+    last_timestamp: AtomicU64,
+    non_monotonic_timestamps: IntCounter,
+    /// Secondary indexes built by initialize, the others are dropped
+    indexes: Vec<SecondaryIndex>,
 }
 
 // OK this is some strum macros magic so we can programmatically get the column number / position,
@@ -62,15 +98,63 @@ enum EventsTableColumns {
     ObjectId,
     /// fields TEXT
     Fields,
+    /// sender BLOB
+    Sender,
+    /// bcs BLOB
+    Bcs,
+    /// move_event_type TEXT
+    MoveEventType,
+    /// event_num INTEGER
+    EventNum,
+}
+
+const INDEXED_COLUMNS: &[&str] = &["timestamp", "tx_digest"];
+
+/// Identifies an event by its transaction and its position among the events of the
+/// transaction, so that inserting an event again is ignored. Rows written before `event_num`
+/// existed have it NULL, which never conflicts.
+const EVENT_ID_INDEX: &str =
+    "CREATE UNIQUE INDEX IF NOT EXISTS event_id_idx on events (seq_num, event_num)";
+
+/// Indexes of earlier versions which were replaced by a [`SecondaryIndex`]
+const REPLACED_INDEXES: &[&str] = &["package_id_idx", "module_name_idx"];
+
+/// Optional indexes of the events table, each speeding up the queries filtering on its columns
+#[derive(Clone, Copy, Debug, PartialEq, Eq, strum_macros::EnumIter)]
+pub enum SecondaryIndex {
+    /// Used by events_by_type
+    EventType,
+    /// Used by events_by_sender
+    Sender,
+    /// Package ID and module name, used by events_by_module_id
+    Package,
+    /// Used by events_by_object
+    ObjectId,
+    /// Used by events_by_move_event_type
+    MoveEventType,
 }
 
-const INDEXED_COLUMNS: &[&str] = &[
-    "timestamp",
-    "tx_digest",
-    "event_type",
-    "package_id",
-    "module_name",
-];
+impl SecondaryIndex {
+    fn name(&self) -> &'static str {
+        match self {
+            SecondaryIndex::EventType => "event_type_idx",
+            SecondaryIndex::Sender => "sender_idx",
+            SecondaryIndex::Package => "package_module_idx",
+            SecondaryIndex::ObjectId => "object_id_idx",
+            SecondaryIndex::MoveEventType => "move_event_type_idx",
+        }
+    }
+
+    fn columns(&self) -> &'static str {
+        match self {
+            SecondaryIndex::EventType => "event_type",
+            SecondaryIndex::Sender => "sender",
+            SecondaryIndex::Package => "package_id, module_name",
+            SecondaryIndex::ObjectId => "object_id",
+            SecondaryIndex::MoveEventType => "move_event_type",
+        }
+    }
+}
 
 impl SqlEventStore {
     /// Creates a new SQLite in-memory database, mostly for testing
@@ -80,27 +164,91 @@ impl SqlEventStore {
             .map_err(convert_sqlx_err)?;
         info!("Created new in-memory SQLite EventStore for testing");
         Ok(Self {
-            pool,
+            write_pool: pool.clone(),
+            read_pool: pool,
             seq_num: AtomicU64::new(0),
+            last_timestamp: AtomicU64::new(0),
+            non_monotonic_timestamps: non_monotonic_timestamps_counter(),
+            indexes: DEFAULT_SECONDARY_INDEXES.to_vec(),
         })
     }
 
-    /// Creates or opens a new SQLite database at a specific path
+    /// Creates or opens a new SQLite database at a specific path, using the default pool sizes
     pub async fn new_from_file(db_path: &Path) -> Result<Self, SuiError> {
+        Self::new_from_file_with_pools(
+            db_path,
+            DEFAULT_EVENT_STORE_READ_POOL_SIZE,
+            DEFAULT_EVENT_STORE_WRITE_POOL_SIZE,
+        )
+        .await
+    }
+
+    /// Creates or opens a new SQLite database at a specific path, with at most `read_pool_size`
+    /// connections serving queries and at most `write_pool_size` connections ingesting events.
+    pub async fn new_from_file_with_pools(
+        db_path: &Path,
+        read_pool_size: u32,
+        write_pool_size: u32,
+    ) -> Result<Self, SuiError> {
         // TODO: configure other SQLite options
+        // Both pools use the write-ahead log, so that queries read the last committed events
+        // while an ingestion is in progress instead of waiting for its lock.
         let options = SqliteConnectOptions::new()
             .filename(db_path)
-            .create_if_missing(true);
-        let pool = SqlitePool::connect_with(options)
+            .create_if_missing(true)
+            .journal_mode(SqliteJournalMode::Wal);
+        // The write pool is opened first so that the database file exists for the readers
+        let write_pool = SqlitePoolOptions::new()
+            .max_connections(write_pool_size)
+            .connect_with(options.clone())
             .await
             .map_err(convert_sqlx_err)?;
-        info!(?db_path, "Created/opened SQLite EventStore on disk");
+        let read_pool = SqlitePoolOptions::new()
+            .max_connections(read_pool_size)
+            .connect_with(options.read_only(true))
+            .await
+            .map_err(convert_sqlx_err)?;
+        info!(
+            ?db_path,
+            read_pool_size, write_pool_size, "Created/opened SQLite EventStore on disk"
+        );
         Ok(Self {
-            pool,
+            write_pool,
+            read_pool,
             seq_num: AtomicU64::new(0),
+            last_timestamp: AtomicU64::new(0),
+            non_monotonic_timestamps: non_monotonic_timestamps_counter(),
+            indexes: DEFAULT_SECONDARY_INDEXES.to_vec(),
         })
     }
 
+    /// Builds only the secondary `indexes`, rather than the default ones, once initialized.
+    /// Indexes built before which aren't chosen anymore are dropped.
+    pub fn with_indexes(mut self, indexes: &[SecondaryIndex]) -> Self {
+        self.indexes = indexes.to_vec();
+        self
+    }
+
+    /// Registers gauges reporting the size and the number of in-use connections of the read
+    /// and write pools, sampled whenever the registry is gathered, and the count of events
+    /// ingested with a timestamp earlier than the one of a previous event.
+    pub fn register_metrics(&self, registry: &Registry) -> Result<(), SuiError> {
+        let collector =
+            PoolUtilizationCollector::new(self.read_pool.clone(), self.write_pool.clone())
+                .map_err(convert_prometheus_err)?;
+        registry
+            .register(Box::new(collector))
+            .map_err(convert_prometheus_err)?;
+        registry
+            .register(Box::new(self.non_monotonic_timestamps.clone()))
+            .map_err(convert_prometheus_err)
+    }
+
+    /// Number of events ingested with a timestamp earlier than the one of a previous event.
+    pub fn non_monotonic_timestamps(&self) -> u64 {
+        self.non_monotonic_timestamps.get()
+    }
+
     /// Initializes the database, creating tables and indexes as needed
     /// It should be safe to call this every time after new_sqlite() as IF NOT EXISTS are used.
     pub async fn initialize(&self) -> Result<(), SuiError> {
@@ -113,17 +261,34 @@ impl SqlEventStore {
             "CREATE TABLE IF NOT EXISTS events({});",
             table_columns.join(", ")
         );
-        self.pool
+        self.write_pool
             .execute(create_sql.as_str())
             .await
             .map_err(convert_sqlx_err)?;
+        // Tables created by earlier versions lack the columns added since, at the end
+        let existing_columns: Vec<String> =
+            sqlx::query("SELECT name FROM pragma_table_info('events')")
+                .map(|row: SqliteRow| row.get(0))
+                .fetch_all(&self.write_pool)
+                .await
+                .map_err(convert_sqlx_err)?;
+        for column in &table_columns {
+            let name = column.split_whitespace().next().unwrap();
+            if !existing_columns.iter().any(|existing| existing == name) {
+                self.write_pool
+                    .execute(format!("ALTER TABLE events ADD COLUMN {}", column).as_str())
+                    .await
+                    .map_err(convert_sqlx_err)?;
+                info!(column = name, "Added column to events table");
+            }
+        }
         info!("SQLite events table is initialized");
 
         // Then, create indexes
         for column in INDEXED_COLUMNS {
             // NOTE: Cannot prepare CREATE INDEX statements.
             // Also, this may take a long time if we add fields to index, at startup.  TODO
-            self.pool
+            self.write_pool
                 .execute(
                     format!(
                         "CREATE INDEX IF NOT EXISTS {}_idx on events ({})",
@@ -135,6 +300,32 @@ impl SqlEventStore {
                 .map_err(convert_sqlx_err)?;
             info!(column, "Index is ready");
         }
+        self.write_pool
+            .execute(EVENT_ID_INDEX)
+            .await
+            .map_err(convert_sqlx_err)?;
+        for index in SecondaryIndex::iter() {
+            let sql = if self.indexes.contains(&index) {
+                format!(
+                    "CREATE INDEX IF NOT EXISTS {} on events ({})",
+                    index.name(),
+                    index.columns()
+                )
+            } else {
+                format!("DROP INDEX IF EXISTS {}", index.name())
+            };
+            self.write_pool
+                .execute(sql.as_str())
+                .await
+                .map_err(convert_sqlx_err)?;
+        }
+        for index in REPLACED_INDEXES {
+            self.write_pool
+                .execute(format!("DROP INDEX IF EXISTS {}", index).as_str())
+                .await
+                .map_err(convert_sqlx_err)?;
+        }
+        info!(indexes = ?self.indexes, "Secondary indexes are ready");
 
         // Setting last sequence number
         let last_seq_num = self.last_seq_num().await?;
@@ -143,6 +334,8 @@ impl SqlEventStore {
             last_seq_num,
             "Recovered last sequence number from event store"
         );
+        let last_timestamp = self.last_timestamp().await?;
+        self.last_timestamp.store(last_timestamp, Ordering::Relaxed);
 
         Ok(())
     }
@@ -151,21 +344,43 @@ impl SqlEventStore {
     #[allow(unused)]
     async fn total_event_count(&self) -> Result<usize, SuiError> {
         let result = sqlx::query("SELECT COUNT(*) FROM events")
-            .fetch_one(&self.pool)
+            .fetch_one(&self.read_pool)
             .await
             .map_err(convert_sqlx_err)?;
         let num_rows: i64 = result.get(0);
         Ok(num_rows as usize)
     }
 
+    async fn last_timestamp(&self) -> Result<u64, SuiError> {
+        let result = sqlx::query("SELECT MAX(timestamp) FROM events")
+            .fetch_one(&self.write_pool)
+            .await
+            .map_err(convert_sqlx_err)?;
+        // NULL when there are no events yet.
+        let timestamp: Option<i64> = result.get(0);
+        Ok(timestamp.unwrap_or(0) as u64)
+    }
+
     async fn last_seq_num(&self) -> Result<u64, SuiError> {
         let result = sqlx::query("SELECT MAX(seq_num) FROM events")
-            .fetch_one(&self.pool)
+            .fetch_one(&self.write_pool)
             .await
             .map_err(convert_sqlx_err)?;
         let num_rows: i64 = result.get(0);
         Ok(num_rows as u64)
     }
+
+    /// Returns how SQLite runs `query`, one line per step.  Only meant for testing which
+    /// indexes queries use.
+    #[allow(unused)]
+    async fn query_plan(&self, query: &str) -> Result<Vec<String>, SuiError> {
+        let steps = sqlx::query(&format!("EXPLAIN QUERY PLAN {}", query))
+            .map(|row: SqliteRow| row.get("detail"))
+            .fetch_all(&self.read_pool)
+            .await
+            .map_err(convert_sqlx_err)?;
+        Ok(steps)
+    }
 }
 
 fn try_extract_object_id(row: &SqliteRow, col: usize) -> Result<Option<ObjectID>, SuiError> {
@@ -204,6 +419,9 @@ fn sql_row_to_event(row: SqliteRow) -> StoredEvent {
     let module_name: Option<String> = row.get(EventsTableColumns::ModuleName as usize);
     let function: Option<String> = row.get(EventsTableColumns::Function as usize);
     let fields_text: &str = row.get(EventsTableColumns::Fields as usize);
+    let event_bcs: Option<Vec<u8>> = row.get(EventsTableColumns::Bcs as usize);
+    let event =
+        event_bcs.map(|bytes| bcs::from_bytes(&bytes).expect("Cannot deserialize stored event"));
     let fields: Vec<_> = if fields_text.is_empty() {
         Vec::new()
     } else {
@@ -232,6 +450,7 @@ fn sql_row_to_event(row: SqliteRow) -> StoredEvent {
         function_name: function.map(SharedStr::from),
         object_id,
         fields,
+        event,
     }
 }
 
@@ -256,9 +475,34 @@ fn event_to_json(event: &EventEnvelope) -> String {
     }
 }
 
+/// The move_event_type column of a Move event of type `type_`. Types of a module or package
+/// share the prefix [`move_event_type_prefix`] returns for them.
+fn move_event_type_key(type_: &StructTag) -> String {
+    let mut key = format!(
+        "{}{}",
+        move_event_type_prefix(&type_.address, Some(type_.module.as_str())),
+        type_.name
+    );
+    if !type_.type_params.is_empty() {
+        let params: Vec<_> = type_.type_params.iter().map(|t| t.to_string()).collect();
+        key.push_str(&format!("<{}>", params.join(", ")));
+    }
+    key
+}
+
+fn move_event_type_prefix(package: &AccountAddress, module: Option<&str>) -> String {
+    match module {
+        Some(module) => format!("{}::{}::", ObjectID::from(*package), module),
+        None => format!("{}::", ObjectID::from(*package)),
+    }
+}
+
+// Events already stored, e.g. by post processing a transaction again after a crash, are
+// ignored, see EVENT_ID_INDEX.
 const SQL_INSERT_TX: &str =
-    "INSERT INTO events (timestamp, seq_num, checkpoint, tx_digest, event_type, \
-    package_id, module_name, object_id, fields) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)";
+    "INSERT OR IGNORE INTO events (timestamp, seq_num, checkpoint, tx_digest, event_type, \
+    package_id, module_name, object_id, fields, sender, bcs, move_event_type, event_num) \
+    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)";
 
 const TS_QUERY: &str = "SELECT * FROM events WHERE timestamp >= ? AND timestamp < ? LIMIT ?";
 
@@ -270,6 +514,21 @@ const QUERY_BY_TYPE: &str = "SELECT * FROM events WHERE timestamp >= ? AND \
 const QUERY_BY_MODULE: &str = "SELECT * FROM events WHERE timestamp >= ? AND \
     timestamp < ? AND package_id = ? AND module_name = ? ORDER BY timestamp DESC LIMIT ?";
 
+const QUERY_BY_SENDER: &str = "SELECT * FROM events WHERE timestamp >= ? AND \
+    timestamp < ? AND sender = ? ORDER BY timestamp DESC LIMIT ?";
+
+const QUERY_BY_OBJECT: &str = "SELECT * FROM events WHERE timestamp >= ? AND \
+    timestamp < ? AND object_id = ? ORDER BY timestamp DESC LIMIT ?";
+
+const QUERY_BY_MOVE_EVENT_TYPE: &str = "SELECT * FROM events WHERE timestamp >= ? AND \
+    timestamp < ? AND move_event_type = ? ORDER BY timestamp DESC LIMIT ?";
+
+/// The keys starting with a prefix ending with `::` are those in [prefix, prefix with its last
+/// `:` replaced by `;`), the next character.
+const QUERY_BY_MOVE_EVENT_TYPE_PREFIX: &str = "SELECT * FROM events WHERE timestamp >= ? AND \
+    timestamp < ? AND move_event_type >= ? AND move_event_type < ? \
+    ORDER BY timestamp DESC LIMIT ?";
+
 const QUERY_BY_CHECKPOINT: &str =
     "SELECT * FROM events WHERE checkpoint >= ? AND checkpoint <= ? LIMIT ?";
 
@@ -288,9 +547,16 @@ impl EventStore for SqlEventStore {
         events: &[EventEnvelope],
         checkpoint_num: u64,
     ) -> Result<(), SuiError> {
-        // TODO: submit writes in one transaction/batch so it won't just fail in the middle
+        // All events are written in one transaction, so that a batch never fails in the middle.
+        let mut db_tx = self.write_pool.begin().await.map_err(convert_sqlx_err)?;
         let mut cur_seq = self.seq_num.load(Ordering::Acquire);
         let initial_seq = cur_seq;
+        let mut last_timestamp = self.last_timestamp.load(Ordering::Relaxed);
+        let mut non_monotonic = 0;
+        // The position of the event among those of its transaction, which are all in the batch.
+        let mut event_num = 0;
+        let mut previous_seq = None;
+        let mut duplicates = 0;
 
         // TODO: benchmark
         // TODO: use techniques in https://docs.rs/sqlx-core/0.5.13/sqlx_core/query_builder/struct.QueryBuilder.html#method.push_values
@@ -303,12 +569,36 @@ impl EventStore for SqlEventStore {
                 continue;
             }
             cur_seq = event.seq_num;
+            if previous_seq == Some(event.seq_num) {
+                event_num += 1;
+            } else {
+                event_num = 0;
+                previous_seq = Some(event.seq_num);
+            }
+            if event.timestamp < last_timestamp {
+                warn!(
+                    tx_digest =? event.tx_digest,
+                    seq_num = event.seq_num,
+                    timestamp = event.timestamp,
+                    last_timestamp,
+                    "Event timestamp is earlier than the one of a previous event"
+                );
+                non_monotonic += 1;
+            } else {
+                last_timestamp = event.timestamp;
+            }
 
             // If batching, turn off persistent to avoid caching as we may fill up the prepared statement cache
             let insert_tx_q = sqlx::query(SQL_INSERT_TX).persistent(true);
             let event_type = EventType::from(&event.event);
+            let event_bcs = bcs::to_bytes(&event.event)
+                .map_err(|e| SuiError::GenericStorageError(e.to_string()))?;
+            let move_event_type = match &event.event {
+                Event::MoveEvent { type_, .. } => Some(move_event_type_key(type_)),
+                _ => None,
+            };
             // TODO: use batched API?
-            insert_tx_q
+            let inserted = insert_tx_q
                 .bind(event.timestamp as i64)
                 .bind(event.seq_num as i64)
                 .bind(checkpoint_num as i64)
@@ -318,10 +608,23 @@ impl EventStore for SqlEventStore {
                 .bind(event.event.module_name())
                 .bind(event.event.object_id().map(|id| id.to_vec()))
                 .bind(event_to_json(event))
-                .execute(&self.pool)
+                .bind(event.event.sender().map(|sender| sender.to_vec()))
+                .bind(event_bcs)
+                .bind(move_event_type)
+                .bind(event_num as i64)
+                .execute(&mut db_tx)
                 .await
                 .map_err(convert_sqlx_err)?;
+            if inserted.rows_affected() == 0 {
+                duplicates += 1;
+            }
         }
+        db_tx.commit().await.map_err(convert_sqlx_err)?;
+        if duplicates > 0 {
+            debug!(duplicates, "Ignored events already in the event store");
+        }
+        self.last_timestamp.store(last_timestamp, Ordering::Relaxed);
+        self.non_monotonic_timestamps.inc_by(non_monotonic);
 
         // CAS is used to detect any concurrency glitches.  Note that we assume a single writer
         // append model, which is currently true.  In single writer the CAS should never fail.
@@ -343,7 +646,7 @@ impl EventStore for SqlEventStore {
             .persistent(true)
             .bind(digest.to_bytes())
             .map(sql_row_to_event)
-            .fetch_all(&self.pool)
+            .fetch_all(&self.read_pool)
             .await
             .map_err(convert_sqlx_err)?;
         Ok(rows)
@@ -364,7 +667,7 @@ impl EventStore for SqlEventStore {
             .bind(event_type as u16)
             .bind(limit as i64)
             .map(sql_row_to_event)
-            .fetch_all(&self.pool)
+            .fetch_all(&self.read_pool)
             .await
             .map_err(convert_sqlx_err)?;
         Ok(rows)
@@ -382,7 +685,7 @@ impl EventStore for SqlEventStore {
             .bind(end_time as i64)
             .bind(limit as i64)
             .map(sql_row_to_event)
-            .fetch_all(&self.pool)
+            .fetch_all(&self.read_pool)
             .await
             .map_err(convert_sqlx_err)?;
         Ok(rows)
@@ -397,11 +700,20 @@ impl EventStore for SqlEventStore {
             .bind(start_checkpoint as i64)
             .bind(end_checkpoint as i64)
             .map(sql_row_to_event)
-            .fetch(&self.pool)
+            .fetch(&self.read_pool)
             .map(|r| r.map_err(convert_sqlx_err));
         Ok(StreamedResult::new(Box::pin(stream)))
     }
 
+    async fn oldest_timestamp(&self) -> Result<Option<u64>, SuiError> {
+        let result = sqlx::query("SELECT MIN(timestamp) FROM events")
+            .fetch_one(&self.read_pool)
+            .await
+            .map_err(convert_sqlx_err)?;
+        let timestamp: Option<i64> = result.get(0);
+        Ok(timestamp.map(|timestamp| timestamp as u64))
+    }
+
     async fn events_by_module_id(
         &self,
         start_time: u64,
@@ -418,17 +730,167 @@ impl EventStore for SqlEventStore {
             .bind(module.name().to_string())
             .bind(limit as i64)
             .map(sql_row_to_event)
-            .fetch_all(&self.pool)
+            .fetch_all(&self.read_pool)
+            .await
+            .map_err(convert_sqlx_err)?;
+        Ok(rows)
+    }
+
+    async fn events_by_sender(
+        &self,
+        start_time: u64,
+        end_time: u64,
+        sender: SuiAddress,
+        limit: usize,
+    ) -> Result<Vec<StoredEvent>, SuiError> {
+        check_limit(limit)?;
+        let rows = sqlx::query(QUERY_BY_SENDER)
+            .persistent(true)
+            .bind(start_time as i64)
+            .bind(end_time as i64)
+            .bind(sender.to_vec())
+            .bind(limit as i64)
+            .map(sql_row_to_event)
+            .fetch_all(&self.read_pool)
+            .await
+            .map_err(convert_sqlx_err)?;
+        Ok(rows)
+    }
+
+    async fn events_by_object(
+        &self,
+        start_time: u64,
+        end_time: u64,
+        object: ObjectID,
+        limit: usize,
+    ) -> Result<Vec<StoredEvent>, SuiError> {
+        check_limit(limit)?;
+        let rows = sqlx::query(QUERY_BY_OBJECT)
+            .persistent(true)
+            .bind(start_time as i64)
+            .bind(end_time as i64)
+            .bind(object.to_vec())
+            .bind(limit as i64)
+            .map(sql_row_to_event)
+            .fetch_all(&self.read_pool)
+            .await
+            .map_err(convert_sqlx_err)?;
+        Ok(rows)
+    }
+
+    async fn events_by_move_event_type(
+        &self,
+        start_time: u64,
+        end_time: u64,
+        filter: MoveEventTypeFilter,
+        limit: usize,
+    ) -> Result<Vec<StoredEvent>, SuiError> {
+        check_limit(limit)?;
+        let prefix_query = |prefix: String| {
+            let end = format!("{};", &prefix[..prefix.len() - 1]);
+            sqlx::query(QUERY_BY_MOVE_EVENT_TYPE_PREFIX)
+                .persistent(true)
+                .bind(start_time as i64)
+                .bind(end_time as i64)
+                .bind(prefix)
+                .bind(end)
+        };
+        let query = match &filter {
+            MoveEventTypeFilter::Type(type_) => sqlx::query(QUERY_BY_MOVE_EVENT_TYPE)
+                .persistent(true)
+                .bind(start_time as i64)
+                .bind(end_time as i64)
+                .bind(move_event_type_key(type_)),
+            MoveEventTypeFilter::Module(module) => prefix_query(move_event_type_prefix(
+                module.address(),
+                Some(module.name().as_str()),
+            )),
+            MoveEventTypeFilter::Package(package) => prefix_query(move_event_type_prefix(
+                &AccountAddress::from(*package),
+                None,
+            )),
+        };
+        let rows = query
+            .bind(limit as i64)
+            .map(sql_row_to_event)
+            .fetch_all(&self.read_pool)
             .await
             .map_err(convert_sqlx_err)?;
         Ok(rows)
     }
 }
 
+fn non_monotonic_timestamps_counter() -> IntCounter {
+    IntCounter::new(
+        "event_store_non_monotonic_timestamps",
+        "Number of events ingested with a timestamp earlier than the one of a previous event",
+    )
+    .unwrap()
+}
+
 fn convert_sqlx_err(err: sqlx::Error) -> SuiError {
     SuiError::GenericStorageError(err.to_string())
 }
 
+fn convert_prometheus_err(err: prometheus::Error) -> SuiError {
+    SuiError::GenericStorageError(err.to_string())
+}
+
+/// Prometheus collector which samples the event store connection pools on every scrape
+struct PoolUtilizationCollector {
+    read_pool: SqlitePool,
+    write_pool: SqlitePool,
+    /// Number of connections currently checked out of each pool, pool is a label
+    connections_in_use: IntGaugeVec,
+    /// Number of connections currently open in each pool, pool is a label
+    connections_open: IntGaugeVec,
+}
+
+impl PoolUtilizationCollector {
+    fn new(read_pool: SqlitePool, write_pool: SqlitePool) -> Result<Self, prometheus::Error> {
+        Ok(Self {
+            read_pool,
+            write_pool,
+            connections_in_use: IntGaugeVec::new(
+                Opts::new(
+                    "event_store_pool_connections_in_use",
+                    "Number of event store connections currently in use",
+                ),
+                &["pool"],
+            )?,
+            connections_open: IntGaugeVec::new(
+                Opts::new(
+                    "event_store_pool_connections_open",
+                    "Number of event store connections currently open",
+                ),
+                &["pool"],
+            )?,
+        })
+    }
+}
+
+impl Collector for PoolUtilizationCollector {
+    fn desc(&self) -> Vec<&Desc> {
+        let mut descs = self.connections_in_use.desc();
+        descs.extend(self.connections_open.desc());
+        descs
+    }
+
+    fn collect(&self) -> Vec<MetricFamily> {
+        for (label, pool) in [("read", &self.read_pool), ("write", &self.write_pool)] {
+            let open = pool.size() as i64;
+            let idle = pool.num_idle() as i64;
+            self.connections_open.with_label_values(&[label]).set(open);
+            self.connections_in_use
+                .with_label_values(&[label])
+                .set(open - idle);
+        }
+        let mut families = self.connections_in_use.collect();
+        families.extend(self.connections_open.collect());
+        families
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -448,7 +910,7 @@ mod tests {
     use sui_types::{
         base_types::SuiAddress,
         event::{Event, EventEnvelope, TransferType},
-        SUI_FRAMEWORK_ADDRESS,
+        MOVE_STDLIB_ADDRESS, SUI_FRAMEWORK_ADDRESS,
     };
 
     #[derive(Debug, Serialize, Deserialize)]
@@ -595,6 +1057,7 @@ mod tests {
             orig.event.module_name().map(SharedStr::from)
         );
         assert_eq!(queried.object_id, orig.event.object_id());
+        assert_eq!(queried.event(), Some(&orig.event));
     }
 
     #[tokio::test]
@@ -741,6 +1204,55 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_eventstore_query_by_move_event_type() -> Result<(), SuiError> {
+        telemetry_subscribers::init_for_testing();
+
+        let db = SqlEventStore::new_memory_only_not_prod()
+            .await?
+            .with_indexes(&[SecondaryIndex::MoveEventType]);
+        db.initialize().await?;
+        let to_insert = test_events();
+        db.add_events(&to_insert, 1).await?;
+
+        let type_ = TestEvent::struct_tag();
+        let module = |name: &str| {
+            MoveEventTypeFilter::Module(ModuleId::new(
+                SUI_FRAMEWORK_ADDRESS,
+                Identifier::new(name).unwrap(),
+            ))
+        };
+        let matching = [
+            MoveEventTypeFilter::Type(type_.clone()),
+            module("SUI"),
+            MoveEventTypeFilter::Package(ObjectID::from(SUI_FRAMEWORK_ADDRESS)),
+        ];
+        for filter in matching {
+            let queried_events = db
+                .events_by_move_event_type(1_000_000, 1_005_001, filter, 10)
+                .await?;
+            assert_eq!(queried_events.len(), 1);
+            test_queried_event_vs_test_envelope(&queried_events[0], &to_insert[5]);
+        }
+
+        // Other type arguments, a module whose name is a prefix of it and another package
+        let mut other_type = type_;
+        other_type.type_params.pop();
+        let not_matching = [
+            MoveEventTypeFilter::Type(other_type),
+            module("SU"),
+            MoveEventTypeFilter::Package(ObjectID::from(MOVE_STDLIB_ADDRESS)),
+        ];
+        for filter in not_matching {
+            let queried_events = db
+                .events_by_move_event_type(1_000_000, 1_005_001, filter, 10)
+                .await?;
+            assert!(queried_events.is_empty());
+        }
+
+        Ok(())
+    }
+
     // Test creating and opening file-based database
     #[tokio::test]
     async fn test_eventstore_max_limit() -> Result<(), SuiError> {
@@ -757,6 +1269,33 @@ mod tests {
         Ok(())
     }
 
+    // Test that a file-based store with explicit pool sizes reads back through the read pool
+    #[tokio::test]
+    async fn test_eventstore_separate_pools() -> Result<(), SuiError> {
+        telemetry_subscribers::init_for_testing();
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let db =
+            SqlEventStore::new_from_file_with_pools(&dir.path().join("events.db"), 2, 1).await?;
+        db.initialize().await?;
+
+        let to_insert = test_events();
+        db.add_events(&to_insert, 1).await?;
+        assert_eq!(db.total_event_count().await?, 6);
+
+        let queried_events = db.event_iterator(1_000_000, 1_002_000, 20).await?;
+        assert_eq!(queried_events.len(), 2);
+
+        let registry = Registry::new();
+        db.register_metrics(&registry)?;
+        let families = registry.gather();
+        assert!(families
+            .iter()
+            .any(|f| f.get_name() == "event_store_pool_connections_in_use"));
+
+        Ok(())
+    }
+
     // Test Idempotency / Sequence Numbering
     #[tokio::test]
     async fn test_eventstore_seq_num() -> Result<(), SuiError> {
@@ -794,4 +1333,192 @@ mod tests {
 
         Ok(())
     }
+
+    // Test that writing the events of the last transaction again, as post processing does when
+    // it runs a transaction again after a crash, doesn't duplicate them
+    #[tokio::test]
+    async fn test_eventstore_duplicate_events_ignored() -> Result<(), SuiError> {
+        telemetry_subscribers::init_for_testing();
+
+        let db = SqlEventStore::new_memory_only_not_prod().await?;
+        db.initialize().await?;
+
+        // The third and fourth events are of the same transaction
+        let to_insert = test_events();
+        db.add_events(&to_insert[..4], 1).await?;
+        assert_eq!(db.total_event_count().await?, 4);
+
+        db.add_events(&to_insert[2..4], 1).await?;
+        assert_eq!(db.total_event_count().await?, 4);
+        let queried_events = db
+            .events_for_transaction(to_insert[2].tx_digest.unwrap())
+            .await?;
+        assert_eq!(queried_events.len(), 1);
+
+        db.add_events(&to_insert[..], 1).await?;
+        assert_eq!(db.total_event_count().await?, 6);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_eventstore_non_monotonic_timestamps() -> Result<(), SuiError> {
+        telemetry_subscribers::init_for_testing();
+
+        let dir = tempfile::TempDir::new().unwrap(); // NOTE this must be its own line so dir isn't dropped
+        let db_file = dir.path().join("events.db");
+        let db = SqlEventStore::new_from_file(&db_file).await?;
+        db.initialize().await?;
+
+        let mut to_insert = test_events();
+        db.add_events(&to_insert[..2], 1).await?;
+        assert_eq!(db.non_monotonic_timestamps(), 0);
+
+        // A later event whose timestamp goes back is stored, but flagged
+        to_insert[2].timestamp = 999_000;
+        db.add_events(&to_insert[2..3], 1).await?;
+        assert_eq!(db.total_event_count().await?, 3);
+        assert_eq!(db.non_monotonic_timestamps(), 1);
+
+        // The latest timestamp is recovered on reload, so going back past it is still flagged
+        drop(db);
+        let db = SqlEventStore::new_from_file(&db_file).await?;
+        db.initialize().await?;
+        to_insert[4].timestamp = 1_000_500;
+        db.add_events(&to_insert[4..], 1).await?;
+        assert_eq!(db.non_monotonic_timestamps(), 1);
+
+        Ok(())
+    }
+    #[tokio::test]
+    async fn test_eventstore_oldest_timestamp() -> Result<(), SuiError> {
+        telemetry_subscribers::init_for_testing();
+
+        let db = SqlEventStore::new_memory_only_not_prod().await?;
+        db.initialize().await?;
+        assert_eq!(db.oldest_timestamp().await?, None);
+
+        let to_insert = test_events();
+        db.add_events(&to_insert[2..], 1).await?;
+        assert_eq!(db.oldest_timestamp().await?, Some(1_002_000));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_eventstore_query_by_sender_and_object() -> Result<(), SuiError> {
+        telemetry_subscribers::init_for_testing();
+
+        let db = SqlEventStore::new_memory_only_not_prod()
+            .await?
+            .with_indexes(&[SecondaryIndex::Sender, SecondaryIndex::ObjectId]);
+        db.initialize().await?;
+        let to_insert = test_events();
+        db.add_events(&to_insert, 1).await?;
+
+        let sender = to_insert[2].event.sender().unwrap();
+        let queried_events = db
+            .events_by_sender(1_000_000, 1_005_001, sender, 10)
+            .await?;
+        assert_eq!(queried_events.len(), 1);
+        test_queried_event_vs_test_envelope(&queried_events[0], &to_insert[2]);
+
+        let object = to_insert[3].event.object_id().unwrap();
+        let queried_events = db
+            .events_by_object(1_000_000, 1_005_001, object, 10)
+            .await?;
+        assert_eq!(queried_events.len(), 1);
+        test_queried_event_vs_test_envelope(&queried_events[0], &to_insert[3]);
+
+        // Outside of the time window
+        let queried_events = db
+            .events_by_object(1_004_000, 1_005_001, object, 10)
+            .await?;
+        assert!(queried_events.is_empty());
+
+        Ok(())
+    }
+
+    fn uses_index(plan: &[String], index: &str) -> bool {
+        plan.iter()
+            .any(|step| step.contains(&format!("USING INDEX {}", index)))
+    }
+
+    // Test that queries use the secondary indexes chosen, and that the others are dropped
+    #[tokio::test]
+    async fn test_eventstore_secondary_indexes() -> Result<(), SuiError> {
+        telemetry_subscribers::init_for_testing();
+
+        let dir = tempfile::TempDir::new().unwrap(); // NOTE this must be its own line so dir isn't dropped
+        let db_file = dir.path().join("events.db");
+        let db = SqlEventStore::new_from_file(&db_file).await?;
+        db.initialize().await?;
+        assert!(uses_index(
+            &db.query_plan(QUERY_BY_TYPE).await?,
+            "event_type_idx"
+        ));
+        assert!(uses_index(
+            &db.query_plan(QUERY_BY_MODULE).await?,
+            "package_module_idx"
+        ));
+        assert!(!uses_index(
+            &db.query_plan(QUERY_BY_SENDER).await?,
+            "sender_idx"
+        ));
+        assert!(uses_index(&db.query_plan(TX_QUERY).await?, "tx_digest_idx"));
+
+        drop(db);
+        let db = SqlEventStore::new_from_file(&db_file)
+            .await?
+            .with_indexes(&[SecondaryIndex::Sender, SecondaryIndex::ObjectId]);
+        db.initialize().await?;
+        assert!(uses_index(
+            &db.query_plan(QUERY_BY_SENDER).await?,
+            "sender_idx"
+        ));
+        assert!(uses_index(
+            &db.query_plan(QUERY_BY_OBJECT).await?,
+            "object_id_idx"
+        ));
+        assert!(!uses_index(
+            &db.query_plan(QUERY_BY_TYPE).await?,
+            "event_type_idx"
+        ));
+        assert!(!uses_index(
+            &db.query_plan(QUERY_BY_MODULE).await?,
+            "package_module_idx"
+        ));
+
+        Ok(())
+    }
+
+    // Test that tables created before the sender and bcs columns existed get them
+    #[tokio::test]
+    async fn test_eventstore_adds_new_columns() -> Result<(), SuiError> {
+        telemetry_subscribers::init_for_testing();
+
+        let db = SqlEventStore::new_memory_only_not_prod()
+            .await?
+            .with_indexes(&[SecondaryIndex::Sender]);
+        db.write_pool
+            .execute(
+                "CREATE TABLE events(timestamp INTEGER NOT NULL, seq_num INTEGER, \
+                checkpoint INTEGER, tx_digest BLOB, event_type INTEGER, package_id BLOB, \
+                module_name TEXT, function TEXT, object_id BLOB, fields TEXT)",
+            )
+            .await
+            .map_err(convert_sqlx_err)?;
+        db.initialize().await?;
+
+        let to_insert = test_events();
+        db.add_events(&to_insert, 1).await?;
+        let sender = to_insert[4].event.sender().unwrap();
+        let queried_events = db
+            .events_by_sender(1_000_000, 1_005_001, sender, 10)
+            .await?;
+        assert_eq!(queried_events.len(), 1);
+        test_queried_event_vs_test_envelope(&queried_events[0], &to_insert[4]);
+
+        Ok(())
+    }
 }
@@ -47,6 +47,11 @@ impl FollowerStore {
             .insert(name, &seq)
             .map_err(SuiError::StorageError)
     }
+
+    /// The next sequence number recorded for every peer.
+    pub fn next_sequences(&self) -> Vec<(AuthorityName, TxSequenceNumber)> {
+        self.next_sequence.iter().collect()
+    }
 }
 
 #[cfg(test)]
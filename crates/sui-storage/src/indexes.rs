@@ -5,21 +5,37 @@
 //! The main user of this data is the explorer.
 
 use rocksdb::Options;
-use serde::{de::DeserializeOwned, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 use crate::default_db_options;
+use std::collections::BTreeMap;
 use std::path::Path;
 use sui_types::base_types::{ObjectID, SuiAddress, TransactionDigest};
 use sui_types::batch::TxSequenceNumber;
 use sui_types::error::SuiResult;
+use sui_types::messages_checkpoint::CheckpointSequenceNumber;
 
 use sui_types::base_types::ObjectRef;
 use sui_types::object::Owner;
 
 use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::StructTag;
 use typed_store::rocks::DBMap;
 use typed_store::{reopen, traits::Map};
 
+const OBJECT_COUNTS: u8 = 0;
+
+/// Live objects and addresses owning at least one of them, maintained by
+/// [`IndexStore::update_object_counts`] as transactions are indexed.
+///
+/// The counts are approximate: transactions that post processing skips or fails on, e.g. after
+/// a crash, aren't counted. [`IndexStore::reconcile_object_counts`] recomputes them exactly.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ObjectCounts {
+    pub live_objects: u64,
+    pub owner_addresses: u64,
+}
+
 pub struct IndexStore {
     /// Index from sui address to transactions initiated by that address.
     transactions_from_addr: DBMap<(SuiAddress, TxSequenceNumber), TransactionDigest>,
@@ -42,9 +58,43 @@ pub struct IndexStore {
     /// on a node according to the local machine time, so it varies across nodes.
     /// The timestamping happens when the node sees a txn certificate for the first time.
     timestamps: DBMap<TransactionDigest, u64>,
+
+    /// Index from coin type to the TreasuryCap of the coin, for the caps created by indexed
+    /// transactions.
+    treasury_caps: DBMap<StructTag, ObjectID>,
+
+    /// Index from the sequence number of a transaction and the packages it published to its
+    /// sender and digest.
+    published_packages: DBMap<(TxSequenceNumber, ObjectID), (SuiAddress, TransactionDigest)>,
+
+    /// The current [`ObjectCounts`], and the sequence number of the next transaction to count.
+    /// Transactions before it are already counted, or were executed before the counts were
+    /// last reconciled.
+    object_counts: DBMap<u8, (ObjectCounts, TxSequenceNumber)>,
+
+    /// The addresses counted in `ObjectCounts::owner_addresses`.
+    object_owners: DBMap<SuiAddress, ()>,
+
+    /// Number of live objects of each type, counted along with `object_counts`. Types are
+    /// written the way the owner index writes them, so generic types are counted per
+    /// instantiation under the canonical form of their type tag, and packages as `Package`.
+    object_types: DBMap<String, u64>,
+
+    /// The entries of `object_types` keyed by `u64::MAX - count`, so that iterating it lists
+    /// the most common types first.
+    object_types_by_count: DBMap<(u64, String), ()>,
+
+    /// Index from transaction digest to the checkpoint including it, for the checkpoints node
+    /// sync verified.
+    transaction_checkpoints: DBMap<TransactionDigest, CheckpointSequenceNumber>,
 }
 
 impl IndexStore {
+    /// See [`flush_db`](crate::flush_db).
+    pub fn flush(&self) -> SuiResult {
+        crate::flush_db(&self.timestamps.rocksdb)
+    }
+
     pub fn open<P: AsRef<Path>>(path: P, db_options: Option<Options>) -> Self {
         let (options, point_lookup) = default_db_options(db_options, Some(1_000_000));
 
@@ -58,6 +108,13 @@ impl IndexStore {
                 ("transactions_by_mutated_object_id", &options),
                 ("transactions_by_move_function", &options),
                 ("timestamps", &point_lookup),
+                ("treasury_caps", &point_lookup),
+                ("published_packages", &options),
+                ("object_counts", &point_lookup),
+                ("object_owners", &point_lookup),
+                ("object_types", &point_lookup),
+                ("object_types_by_count", &options),
+                ("transaction_checkpoints", &point_lookup),
             ];
             typed_store::rocks::open_cf_opts(path, db_options, opt_cfs)
         }
@@ -70,6 +127,13 @@ impl IndexStore {
             transactions_by_mutated_object_id,
             transactions_by_move_function,
             timestamps,
+            treasury_caps,
+            published_packages,
+            object_counts,
+            object_owners,
+            object_types,
+            object_types_by_count,
+            transaction_checkpoints,
         ) = reopen!(
             &db,
             "transactions_from_addr"; <(SuiAddress, TxSequenceNumber), TransactionDigest>,
@@ -77,7 +141,14 @@ impl IndexStore {
             "transactions_by_input_object_id"; <(ObjectID, TxSequenceNumber), TransactionDigest>,
             "transactions_by_mutated_object_id"; <(ObjectID, TxSequenceNumber), TransactionDigest>,
             "transactions_by_move_function"; <(ObjectID, String, String, TxSequenceNumber), TransactionDigest>,
-            "timestamps";<TransactionDigest, u64>
+            "timestamps";<TransactionDigest, u64>,
+            "treasury_caps";<StructTag, ObjectID>,
+            "published_packages";<(TxSequenceNumber, ObjectID), (SuiAddress, TransactionDigest)>,
+            "object_counts";<u8, (ObjectCounts, TxSequenceNumber)>,
+            "object_owners";<SuiAddress, ()>,
+            "object_types";<String, u64>,
+            "object_types_by_count";<(u64, String), ()>,
+            "transaction_checkpoints";<TransactionDigest, CheckpointSequenceNumber>
         );
 
         Self {
@@ -87,6 +158,13 @@ impl IndexStore {
             transactions_by_mutated_object_id,
             transactions_by_move_function,
             timestamps,
+            treasury_caps,
+            published_packages,
+            object_counts,
+            object_owners,
+            object_types,
+            object_types_by_count,
+            transaction_checkpoints,
         }
     }
 
@@ -96,6 +174,8 @@ impl IndexStore {
         active_inputs: impl Iterator<Item = ObjectID>,
         mutated_objects: impl Iterator<Item = &'a (ObjectRef, Owner)> + Clone,
         move_functions: impl Iterator<Item = (ObjectID, Identifier, Identifier)> + Clone,
+        treasury_caps: impl Iterator<Item = (StructTag, ObjectID)>,
+        published_packages: impl Iterator<Item = ObjectID>,
         sequence: TxSequenceNumber,
         digest: &TransactionDigest,
         timestamp_ms: u64,
@@ -142,11 +222,180 @@ impl IndexStore {
         let batch =
             batch.insert_batch(&self.timestamps, std::iter::once((*digest, timestamp_ms)))?;
 
+        let batch = batch.insert_batch(&self.treasury_caps, treasury_caps)?;
+
+        let batch = batch.insert_batch(
+            &self.published_packages,
+            published_packages.map(|package| ((sequence, package), (sender, *digest))),
+        )?;
+
         batch.write()?;
 
         Ok(())
     }
 
+    /// The object counts, or `None` until they were first reconciled.
+    pub fn object_counts(&self) -> SuiResult<Option<ObjectCounts>> {
+        Ok(self
+            .object_counts
+            .get(&OBJECT_COUNTS)?
+            .map(|(counts, _)| counts))
+    }
+
+    /// Up to `limit` object types with their number of live objects, most common first,
+    /// skipping the `offset` most common ones.
+    pub fn object_type_counts(&self, offset: usize, limit: usize) -> SuiResult<Vec<(String, u64)>> {
+        Ok(self
+            .object_types_by_count
+            .keys()
+            .skip(offset)
+            .take(limit)
+            .map(|(inverted_count, type_)| (type_, u64::MAX - inverted_count))
+            .collect())
+    }
+
+    /// Count the transaction `sequence`, which changed the number of live objects by
+    /// `object_delta` and the number of objects of some types by `type_deltas`, given whether
+    /// each address it could have changed the objects of owns any now. Counting a transaction
+    /// again, or one executed before the last reconciliation, has no effect. Returns the new
+    /// counts.
+    pub fn update_object_counts(
+        &self,
+        sequence: TxSequenceNumber,
+        object_delta: i64,
+        type_deltas: impl Iterator<Item = (String, i64)>,
+        owners: impl Iterator<Item = (SuiAddress, bool)>,
+    ) -> SuiResult<ObjectCounts> {
+        let (mut counts, next) = self.object_counts.get(&OBJECT_COUNTS)?.unwrap_or_default();
+        if sequence < next {
+            return Ok(counts);
+        }
+        counts.live_objects = (counts.live_objects as i64 + object_delta).max(0) as u64;
+
+        let mut added = Vec::new();
+        let mut removed = Vec::new();
+        for (address, owns_objects) in owners {
+            match (owns_objects, self.object_owners.contains_key(&address)?) {
+                (true, false) => added.push((address, ())),
+                (false, true) => removed.push(address),
+                _ => {}
+            }
+        }
+        counts.owner_addresses =
+            (counts.owner_addresses + added.len() as u64).saturating_sub(removed.len() as u64);
+
+        let mut deltas = BTreeMap::new();
+        for (type_, delta) in type_deltas {
+            *deltas.entry(type_).or_insert(0i64) += delta;
+        }
+        let mut type_counts = Vec::new();
+        let mut removed_types = Vec::new();
+        let mut stale_ranks = Vec::new();
+        let mut ranks = Vec::new();
+        for (type_, delta) in deltas {
+            if delta == 0 {
+                continue;
+            }
+            let count = self.object_types.get(&type_)?.unwrap_or(0);
+            let new_count = (count as i64 + delta).max(0) as u64;
+            if count > 0 {
+                stale_ranks.push((u64::MAX - count, type_.clone()));
+            }
+            if new_count > 0 {
+                ranks.push(((u64::MAX - new_count, type_.clone()), ()));
+                type_counts.push((type_, new_count));
+            } else {
+                removed_types.push(type_);
+            }
+        }
+
+        self.object_owners
+            .batch()
+            .insert_batch(&self.object_owners, added)?
+            .delete_batch(&self.object_owners, removed)?
+            .delete_batch(&self.object_types_by_count, stale_ranks)?
+            .insert_batch(&self.object_types_by_count, ranks)?
+            .delete_batch(&self.object_types, removed_types)?
+            .insert_batch(&self.object_types, type_counts)?
+            .insert_batch(
+                &self.object_counts,
+                std::iter::once((OBJECT_COUNTS, (counts, sequence + 1))),
+            )?
+            .write()?;
+        Ok(counts)
+    }
+
+    /// Recompute the object counts from `objects`, the owner and type of every live object
+    /// ordered the way the authority's owner index is, so that the objects of an address are
+    /// next to each other and addresses are in ascending order. Transactions before
+    /// `next_sequence` are taken to be reflected in `objects`, and aren't counted anymore.
+    /// Returns the new counts. Nothing is written if `objects` yields an error.
+    pub fn reconcile_object_counts(
+        &self,
+        objects: impl Iterator<Item = SuiResult<(Owner, String)>>,
+        next_sequence: TxSequenceNumber,
+    ) -> SuiResult<ObjectCounts> {
+        let mut counts = ObjectCounts::default();
+        let mut type_counts = BTreeMap::new();
+        let mut known = self.object_owners.keys().peekable();
+        let mut added = Vec::new();
+        let mut removed = Vec::new();
+        let mut last_address = None;
+        for object in objects {
+            let (owner, type_) = object?;
+            counts.live_objects += 1;
+            *type_counts.entry(type_).or_insert(0u64) += 1;
+            let address = match owner {
+                Owner::AddressOwner(address) => address,
+                _ => continue,
+            };
+            if last_address == Some(address) {
+                continue;
+            }
+            last_address = Some(address);
+            counts.owner_addresses += 1;
+            // Addresses known before this one don't own objects anymore.
+            while let Some(stale) = known.next_if(|known| *known < address) {
+                removed.push(stale);
+            }
+            if known.next_if_eq(&address).is_none() {
+                added.push((address, ()));
+            }
+        }
+        removed.extend(known);
+
+        let next = match self.object_counts.get(&OBJECT_COUNTS)? {
+            Some((_, next)) => next.max(next_sequence),
+            None => next_sequence,
+        };
+        let ranks: Vec<_> = type_counts
+            .iter()
+            .map(|(type_, count)| ((u64::MAX - count, type_.clone()), ()))
+            .collect();
+        self.object_owners
+            .batch()
+            .insert_batch(&self.object_owners, added)?
+            .delete_batch(&self.object_owners, removed)?
+            .delete_batch(&self.object_types, self.object_types.keys())?
+            .delete_batch(
+                &self.object_types_by_count,
+                self.object_types_by_count.keys(),
+            )?
+            .insert_batch(&self.object_types, type_counts)?
+            .insert_batch(&self.object_types_by_count, ranks)?
+            .insert_batch(
+                &self.object_counts,
+                std::iter::once((OBJECT_COUNTS, (counts, next))),
+            )?
+            .write()?;
+        Ok(counts)
+    }
+
+    /// The TreasuryCap of the coins of type `coin_type`, if an indexed transaction created it.
+    pub fn get_treasury_cap(&self, coin_type: &StructTag) -> SuiResult<Option<ObjectID>> {
+        Ok(self.treasury_caps.get(coin_type)?)
+    }
+
     /// Returns unix timestamp for a transaction if it exists
     pub fn get_timestamp_ms(
         &self,
@@ -156,6 +405,31 @@ impl IndexStore {
         Ok(ts)
     }
 
+    /// Record that the transactions `digests` are included in checkpoint `seq`.
+    pub fn index_checkpoint(
+        &self,
+        seq: CheckpointSequenceNumber,
+        digests: impl Iterator<Item = TransactionDigest>,
+    ) -> SuiResult {
+        self.transaction_checkpoints
+            .batch()
+            .insert_batch(
+                &self.transaction_checkpoints,
+                digests.map(|digest| (digest, seq)),
+            )?
+            .write()?;
+        Ok(())
+    }
+
+    /// The checkpoint including transaction `digest`, if recorded by
+    /// [`IndexStore::index_checkpoint`].
+    pub fn get_transaction_checkpoint(
+        &self,
+        digest: &TransactionDigest,
+    ) -> SuiResult<Option<CheckpointSequenceNumber>> {
+        Ok(self.transaction_checkpoints.get(digest)?)
+    }
+
     fn get_transactions_by_object<
         KeyT: Clone + Serialize + DeserializeOwned + std::cmp::PartialEq,
     >(
@@ -170,6 +444,81 @@ impl IndexStore {
             .collect())
     }
 
+    /// Up to `limit` transactions in either index for `key`, from sequence number `since` on,
+    /// in sequence number order.
+    fn get_history_page<KeyT: Clone + Serialize + DeserializeOwned + std::cmp::PartialEq>(
+        indexes: [&DBMap<(KeyT, TxSequenceNumber), TransactionDigest>; 2],
+        key: KeyT,
+        since: TxSequenceNumber,
+        limit: usize,
+    ) -> SuiResult<Vec<(TxSequenceNumber, TransactionDigest)>> {
+        let mut page = Vec::new();
+        for index in indexes {
+            page.extend(
+                index
+                    .iter()
+                    .skip_to(&(key.clone(), since))?
+                    .take_while(|((k, _), _)| *k == key)
+                    .take(limit)
+                    .map(|((_, seq), digest)| (seq, digest)),
+            );
+        }
+        page.sort();
+        page.dedup();
+        page.truncate(limit);
+        Ok(page)
+    }
+
+    /// Up to `limit` packages published by transactions from sequence number `since` on, with
+    /// the sequence number and digest of the transaction and its sender.
+    pub fn get_published_packages(
+        &self,
+        since: TxSequenceNumber,
+        limit: usize,
+    ) -> SuiResult<Vec<(TxSequenceNumber, ObjectID, SuiAddress, TransactionDigest)>> {
+        Ok(self
+            .published_packages
+            .iter()
+            .skip_to(&(since, ObjectID::ZERO))?
+            .take(limit)
+            .map(|((seq, package), (sender, digest))| (seq, package, sender, digest))
+            .collect())
+    }
+
+    /// Up to `limit` transactions sent by or to `addr`, from sequence number `since` on.
+    pub fn get_address_history_page(
+        &self,
+        addr: SuiAddress,
+        since: TxSequenceNumber,
+        limit: usize,
+    ) -> SuiResult<Vec<(TxSequenceNumber, TransactionDigest)>> {
+        Self::get_history_page(
+            [&self.transactions_from_addr, &self.transactions_to_addr],
+            addr,
+            since,
+            limit,
+        )
+    }
+
+    /// Up to `limit` transactions that took `object` as input or mutated it, from sequence
+    /// number `since` on.
+    pub fn get_object_history_page(
+        &self,
+        object: ObjectID,
+        since: TxSequenceNumber,
+        limit: usize,
+    ) -> SuiResult<Vec<(TxSequenceNumber, TransactionDigest)>> {
+        Self::get_history_page(
+            [
+                &self.transactions_by_input_object_id,
+                &self.transactions_by_mutated_object_id,
+            ],
+            object,
+            since,
+            limit,
+        )
+    }
+
     pub fn get_transactions_by_input_object(
         &self,
         input_object: ObjectID,
@@ -191,6 +540,16 @@ impl IndexStore {
         Self::get_transactions_by_object(&self.transactions_from_addr, addr)
     }
 
+    /// Number of transactions sent by `addr` that were indexed.
+    pub fn count_transactions_from_addr(&self, addr: SuiAddress) -> SuiResult<u64> {
+        Ok(self
+            .transactions_from_addr
+            .iter()
+            .skip_to(&(addr, TxSequenceNumber::MIN))?
+            .take_while(|((sender, _), _)| *sender == addr)
+            .count() as u64)
+    }
+
     pub fn get_transactions_by_move_function(
         &self,
         package: ObjectID,
@@ -222,3 +581,326 @@ impl IndexStore {
         Self::get_transactions_by_object(&self.transactions_to_addr, addr)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{IndexStore, ObjectCounts};
+    use move_core_types::{ident_str, language_storage::StructTag};
+    use sui_types::base_types::{ObjectID, SuiAddress, TransactionDigest};
+    use sui_types::object::Owner;
+
+    #[test]
+    fn test_history_pages() {
+        let working_dir = tempfile::tempdir().unwrap();
+        let store = IndexStore::open(&working_dir, None);
+
+        let sender = SuiAddress::random_for_testing_only();
+        let object = ObjectID::random();
+        let digests: Vec<_> = (0..5).map(|_| TransactionDigest::random()).collect();
+        for (seq, digest) in digests.iter().enumerate() {
+            // The object is only an input of every other transaction.
+            let inputs = if seq % 2 == 0 { vec![object] } else { vec![] };
+            store
+                .index_tx(
+                    sender,
+                    inputs.into_iter(),
+                    std::iter::empty(),
+                    std::iter::empty(),
+                    std::iter::empty(),
+                    std::iter::empty(),
+                    seq as u64,
+                    digest,
+                    0,
+                )
+                .unwrap();
+        }
+
+        let page = store.get_address_history_page(sender, 1, 3).unwrap();
+        assert_eq!(
+            page,
+            vec![(1, digests[1]), (2, digests[2]), (3, digests[3])]
+        );
+        let page = store.get_address_history_page(sender, 4, 3).unwrap();
+        assert_eq!(page, vec![(4, digests[4])]);
+
+        let page = store.get_object_history_page(object, 1, 10).unwrap();
+        assert_eq!(page, vec![(2, digests[2]), (4, digests[4])]);
+        assert!(store
+            .get_object_history_page(ObjectID::random(), 0, 10)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_treasury_caps() {
+        let working_dir = tempfile::tempdir().unwrap();
+        let store = IndexStore::open(&working_dir, None);
+        let coin_type = StructTag {
+            address: ObjectID::random().into(),
+            module: ident_str!("my_coin").to_owned(),
+            name: ident_str!("MY_COIN").to_owned(),
+            type_params: vec![],
+        };
+        let cap = ObjectID::random();
+
+        assert_eq!(store.get_treasury_cap(&coin_type).unwrap(), None);
+        store
+            .index_tx(
+                SuiAddress::random_for_testing_only(),
+                std::iter::empty(),
+                std::iter::empty(),
+                std::iter::empty(),
+                std::iter::once((coin_type.clone(), cap)),
+                std::iter::empty(),
+                0,
+                &TransactionDigest::random(),
+                0,
+            )
+            .unwrap();
+        assert_eq!(store.get_treasury_cap(&coin_type).unwrap(), Some(cap));
+    }
+
+    #[test]
+    fn test_count_transactions_from_addr() {
+        let working_dir = tempfile::tempdir().unwrap();
+        let store = IndexStore::open(&working_dir, None);
+        let sender = SuiAddress::random_for_testing_only();
+        let other = SuiAddress::random_for_testing_only();
+        for (seq, address) in [sender, other, sender].into_iter().enumerate() {
+            store
+                .index_tx(
+                    address,
+                    std::iter::empty(),
+                    std::iter::empty(),
+                    std::iter::empty(),
+                    std::iter::empty(),
+                    std::iter::empty(),
+                    seq as u64,
+                    &TransactionDigest::random(),
+                    0,
+                )
+                .unwrap();
+        }
+
+        assert_eq!(store.count_transactions_from_addr(sender).unwrap(), 2);
+        assert_eq!(store.count_transactions_from_addr(other).unwrap(), 1);
+        assert_eq!(
+            store
+                .count_transactions_from_addr(SuiAddress::random_for_testing_only())
+                .unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_transaction_checkpoints() {
+        let working_dir = tempfile::tempdir().unwrap();
+        let store = IndexStore::open(&working_dir, None);
+        let digests: Vec<_> = (0..3).map(|_| TransactionDigest::random()).collect();
+
+        store
+            .index_checkpoint(4, digests[..2].iter().copied())
+            .unwrap();
+        store
+            .index_checkpoint(5, digests[2..].iter().copied())
+            .unwrap();
+        assert_eq!(
+            store.get_transaction_checkpoint(&digests[1]).unwrap(),
+            Some(4)
+        );
+        assert_eq!(
+            store.get_transaction_checkpoint(&digests[2]).unwrap(),
+            Some(5)
+        );
+        assert_eq!(
+            store
+                .get_transaction_checkpoint(&TransactionDigest::random())
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_published_packages() {
+        let working_dir = tempfile::tempdir().unwrap();
+        let store = IndexStore::open(&working_dir, None);
+
+        let sender = SuiAddress::random_for_testing_only();
+        let packages: Vec<_> = (0..3).map(|_| ObjectID::random()).collect();
+        let digests: Vec<_> = (0..3).map(|_| TransactionDigest::random()).collect();
+        for (seq, (package, digest)) in packages.iter().zip(&digests).enumerate() {
+            store
+                .index_tx(
+                    sender,
+                    std::iter::empty(),
+                    std::iter::empty(),
+                    std::iter::empty(),
+                    std::iter::empty(),
+                    std::iter::once(*package),
+                    seq as u64,
+                    digest,
+                    0,
+                )
+                .unwrap();
+        }
+
+        let published = store.get_published_packages(1, 10).unwrap();
+        assert_eq!(
+            published,
+            vec![
+                (1, packages[1], sender, digests[1]),
+                (2, packages[2], sender, digests[2]),
+            ]
+        );
+        assert_eq!(store.get_published_packages(0, 1).unwrap().len(), 1);
+        assert!(store.get_published_packages(3, 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_object_counts() {
+        let working_dir = tempfile::tempdir().unwrap();
+        let store = IndexStore::open(&working_dir, None);
+        let mut addresses: Vec<_> = (0..3)
+            .map(|_| SuiAddress::random_for_testing_only())
+            .collect();
+        addresses.sort();
+        let (a, b, c) = (addresses[0], addresses[1], addresses[2]);
+
+        assert_eq!(store.object_counts().unwrap(), None);
+        let counts = store
+            .update_object_counts(0, 2, std::iter::empty(), [(a, true), (b, true)].into_iter())
+            .unwrap();
+        assert_eq!(
+            counts,
+            ObjectCounts {
+                live_objects: 2,
+                owner_addresses: 2
+            }
+        );
+        // Counting a transaction again has no effect.
+        let counts = store
+            .update_object_counts(0, 2, std::iter::empty(), [(a, true), (b, true)].into_iter())
+            .unwrap();
+        assert_eq!(counts.live_objects, 2);
+
+        // b gives its object to c, and a deletes its own.
+        let counts = store
+            .update_object_counts(
+                1,
+                -1,
+                std::iter::empty(),
+                [(a, false), (b, false), (c, true)].into_iter(),
+            )
+            .unwrap();
+        assert_eq!(
+            counts,
+            ObjectCounts {
+                live_objects: 1,
+                owner_addresses: 1
+            }
+        );
+        assert_eq!(store.object_counts().unwrap(), Some(counts));
+
+        // The counts missed a transaction creating an object for a and a shared object.
+        let owners = [
+            (Owner::AddressOwner(a), "T".to_string()),
+            (Owner::AddressOwner(c), "T".to_string()),
+            (Owner::Shared, "T".to_string()),
+        ];
+        let counts = store
+            .reconcile_object_counts(owners.into_iter().map(Ok), 3)
+            .unwrap();
+        assert_eq!(
+            counts,
+            ObjectCounts {
+                live_objects: 3,
+                owner_addresses: 2
+            }
+        );
+        // Transactions before the reconciliation aren't counted anymore.
+        let counts = store
+            .update_object_counts(2, 1, std::iter::empty(), [(b, true)].into_iter())
+            .unwrap();
+        assert_eq!(counts.live_objects, 3);
+
+        // c gives its object to a.
+        let counts = store
+            .update_object_counts(
+                3,
+                0,
+                std::iter::empty(),
+                [(c, false), (a, true)].into_iter(),
+            )
+            .unwrap();
+        assert_eq!(
+            counts,
+            ObjectCounts {
+                live_objects: 3,
+                owner_addresses: 1
+            }
+        );
+        let owners = [
+            (Owner::AddressOwner(a), "T".to_string()),
+            (Owner::AddressOwner(a), "T".to_string()),
+            (Owner::Shared, "T".to_string()),
+        ];
+        let counts = store
+            .reconcile_object_counts(owners.into_iter().map(Ok), 4)
+            .unwrap();
+        assert_eq!(counts.owner_addresses, 1);
+    }
+
+    #[test]
+    fn test_object_type_counts() {
+        let working_dir = tempfile::tempdir().unwrap();
+        let store = IndexStore::open(&working_dir, None);
+        let nft = || "0x2::devnet_nft::DevNetNFT".to_string();
+        let coin = || "0x2::coin::Coin<0x2::sui::SUI>".to_string();
+        let package = || "Package".to_string();
+
+        store
+            .update_object_counts(
+                0,
+                4,
+                [(nft(), 1), (nft(), 1), (coin(), 1), (package(), 1)].into_iter(),
+                std::iter::empty(),
+            )
+            .unwrap();
+        assert_eq!(
+            store.object_type_counts(0, 10).unwrap(),
+            vec![(nft(), 2), (coin(), 1), (package(), 1)]
+        );
+        // Counting a transaction again has no effect.
+        store
+            .update_object_counts(0, 1, [(coin(), 1)].into_iter(), std::iter::empty())
+            .unwrap();
+        assert_eq!(store.object_type_counts(0, 1).unwrap(), vec![(nft(), 2)]);
+
+        // Coins catch up with NFTs, and the package is gone. Ties are ordered by type.
+        store
+            .update_object_counts(
+                1,
+                1,
+                [(coin(), 1), (coin(), 1), (nft(), 1), (package(), -1)].into_iter(),
+                std::iter::empty(),
+            )
+            .unwrap();
+        assert_eq!(
+            store.object_type_counts(0, 10).unwrap(),
+            vec![(coin(), 3), (nft(), 3)]
+        );
+        store
+            .update_object_counts(2, 1, [(coin(), 1)].into_iter(), std::iter::empty())
+            .unwrap();
+        assert_eq!(store.object_type_counts(1, 10).unwrap(), vec![(nft(), 3)]);
+
+        let objects = [(Owner::Shared, nft()), (Owner::Immutable, package())];
+        store
+            .reconcile_object_counts(objects.into_iter().map(Ok), 3)
+            .unwrap();
+        assert_eq!(
+            store.object_type_counts(0, 10).unwrap(),
+            vec![(nft(), 1), (package(), 1)]
+        );
+    }
+}
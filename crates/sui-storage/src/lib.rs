@@ -7,13 +7,19 @@ pub use lock_service::LockService;
 pub mod indexes;
 pub use indexes::IndexStore;
 
+pub mod dead_letter_store;
 pub mod event_store;
 pub mod follower_store;
 pub mod mutex_table;
 pub mod node_sync_store;
+pub mod post_processing_store;
+pub mod quarantine_store;
 pub mod write_ahead_log;
 
-use rocksdb::Options;
+use rocksdb::checkpoint::Checkpoint;
+use rocksdb::{BlockBasedOptions, Cache, DBWithThreadMode, MultiThreaded, Options};
+use std::path::Path;
+use sui_types::error::SuiError;
 
 /// Given a provided `db_options`, add a few default options.
 /// Returns the default option and the point lookup option.
@@ -44,3 +50,72 @@ pub fn default_db_options(
 
     (options, point_lookup)
 }
+
+/// Writes a consistent copy of `db` to `path`, which must not exist yet, as a RocksDB
+/// checkpoint. The memtables are flushed and the table files are hard-linked rather than copied
+/// when `path` is on the same filesystem, so that it is fast and takes little space at first.
+pub fn create_db_checkpoint(
+    db: &DBWithThreadMode<MultiThreaded>,
+    path: &Path,
+) -> Result<(), SuiError> {
+    Checkpoint::new(db)
+        .and_then(|checkpoint| checkpoint.create_checkpoint(path))
+        .map_err(|e| SuiError::GenericStorageError(e.to_string()))
+}
+
+/// Writes the memtables of all column families of `db` to table files, so that opening it
+/// again needn't replay its write-ahead log.
+pub fn flush_db(db: &DBWithThreadMode<MultiThreaded>) -> Result<(), SuiError> {
+    let to_error = |e: rocksdb::Error| SuiError::GenericStorageError(e.to_string());
+    let cfs = DBWithThreadMode::<MultiThreaded>::list_cf(&Options::default(), db.path())
+        .map_err(to_error)?;
+    for name in cfs {
+        if let Some(cf) = db.cf_handle(&name) {
+            db.flush_cf(&cf).map_err(to_error)?;
+        }
+    }
+    Ok(())
+}
+
+/// Column families whose block cache and memtables are sized apart from the others. The block
+/// cache is shared by the column families of the group only, so that reading some of them
+/// doesn't evict the blocks of the others.
+pub struct ColumnFamilyGroup {
+    block_cache: Cache,
+    write_buffer_size: usize,
+    max_write_buffer_number: i32,
+}
+
+impl ColumnFamilyGroup {
+    /// `block_cache_size` and `write_buffer_size` are in bytes. A column family of the group can
+    /// have up to `max_write_buffer_number` memtables of `write_buffer_size` each.
+    pub fn new(
+        block_cache_size: usize,
+        write_buffer_size: usize,
+        max_write_buffer_number: i32,
+    ) -> Self {
+        Self {
+            block_cache: Cache::new_lru_cache(block_cache_size).expect("Cache is ok"),
+            write_buffer_size,
+            max_write_buffer_number,
+        }
+    }
+
+    /// The options of a column family of the group, based on the default `options` returned by
+    /// `default_db_options`. Column families read by key get bloom filters, like with the point
+    /// lookup options, but keep the block cache of the group.
+    pub fn column_family_options(&self, options: &Options, point_lookup: bool) -> Options {
+        let mut options = options.clone();
+        let mut table_options = BlockBasedOptions::default();
+        table_options.set_block_cache(&self.block_cache);
+        if point_lookup {
+            table_options.set_bloom_filter(10.0, false);
+            options.set_memtable_prefix_bloom_ratio(0.02);
+            options.set_memtable_whole_key_filtering(true);
+        }
+        options.set_block_based_table_factory(&table_options);
+        options.set_write_buffer_size(self.write_buffer_size);
+        options.set_max_write_buffer_number(self.max_write_buffer_number);
+        options
+    }
+}
@@ -20,6 +20,7 @@ use rocksdb::Options;
 use std::path::Path;
 use std::sync::Arc;
 use std::thread::JoinHandle;
+use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::sync::mpsc::{channel, Receiver, Sender};
 use tracing::{debug, error, info, trace, warn};
 use typed_store::rocks::{DBBatch, DBMap};
@@ -60,6 +61,16 @@ enum LockServiceCommands {
 
 type SuiLockResult = Result<Option<Option<TransactionDigest>>, SuiError>;
 
+/// An object locked to a transaction that hasn't been executed yet.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LockedObject {
+    pub object_ref: ObjectRef,
+    pub tx_digest: TransactionDigest,
+    /// When the lock was taken, in milliseconds since the Unix epoch. None for the locks taken
+    /// before lock times were recorded.
+    pub locked_at_ms: Option<u64>,
+}
+
 /// Queries to the LockService state
 #[derive(Debug)]
 enum LockServiceQueries {
@@ -71,6 +82,10 @@ enum LockServiceQueries {
         objects: Vec<ObjectRef>,
         resp: oneshot::Sender<SuiResult>,
     },
+    ListLocked {
+        objects: Option<Vec<ObjectRef>>,
+        resp: oneshot::Sender<SuiResult<Vec<LockedObject>>>,
+    },
 }
 
 /// Inner LockService implementation that does single threaded database accesses.  Cannot be
@@ -85,6 +100,10 @@ struct LockServiceImpl {
     /// forgotten.
     transaction_lock: DBMap<ObjectRef, Option<TransactionDigest>>,
 
+    /// When the locks set to a transaction in `transaction_lock` were taken, in milliseconds
+    /// since the Unix epoch, to tell the locks of transactions that were abandoned.
+    lock_times: DBMap<ObjectRef, u64>,
+
     /// The semantics of transaction_lock ensure that certificates are always processed
     /// in causal order - that is, certificates naturally form a partial order. tx_sequence
     /// records a total ordering among all processed certificates (which is naturally local
@@ -104,18 +123,21 @@ impl LockServiceImpl {
             let db_options = Some(options);
             let opt_cfs: &[(&str, &rocksdb::Options)] = &[
                 ("transaction_lock", &point_lookup),
+                ("lock_times", &point_lookup),
                 ("tx_sequence", &point_lookup),
             ];
             typed_store::rocks::open_cf_opts(path, db_options, opt_cfs)
         }
         .map_err(SuiError::StorageError)?;
 
-        let (transaction_lock, tx_sequence) = reopen!(&db, "transaction_lock";<ObjectRef, Option<TransactionDigest>>,
+        let (transaction_lock, lock_times, tx_sequence) = reopen!(&db, "transaction_lock";<ObjectRef, Option<TransactionDigest>>,
+                "lock_times";<ObjectRef, u64>,
                 "tx_sequence";<TransactionDigest, TxSequenceNumber>
         );
 
         Ok(Self {
             transaction_lock,
+            lock_times,
             tx_sequence,
         })
     }
@@ -134,6 +156,36 @@ impl LockServiceImpl {
             .map_err(SuiError::StorageError)
     }
 
+    /// Returns the locks of `objects`, or of all objects when None, that are set to a transaction.
+    fn locked_objects(&self, objects: Option<&[ObjectRef]>) -> SuiResult<Vec<LockedObject>> {
+        let locks: Vec<(ObjectRef, TransactionDigest)> = match objects {
+            Some(objects) => self
+                .transaction_lock
+                .multi_get(objects)?
+                .into_iter()
+                .zip(objects)
+                .filter_map(|(lock, object_ref)| Some((*object_ref, lock.flatten()?)))
+                .collect(),
+            None => self
+                .transaction_lock
+                .iter()
+                .filter_map(|(object_ref, lock)| Some((object_ref, lock?)))
+                .collect(),
+        };
+        let lock_times = self
+            .lock_times
+            .multi_get(locks.iter().map(|(object_ref, _)| object_ref))?;
+        Ok(locks
+            .into_iter()
+            .zip(lock_times)
+            .map(|((object_ref, tx_digest), locked_at_ms)| LockedObject {
+                object_ref,
+                tx_digest,
+                locked_at_ms,
+            })
+            .collect())
+    }
+
     /// Checks multiple object locks exist.
     /// Returns Err(TransactionLockDoesNotExist) if at least one object lock is not initialized.
     fn locks_exist(&self, objects: &[ObjectRef]) -> SuiResult {
@@ -276,9 +328,18 @@ impl LockServiceImpl {
 
         if !locks_to_write.is_empty() {
             trace!(?locks_to_write, "Writing locks");
+            let now_ms = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_millis() as u64)
+                .unwrap_or(0);
+            let lock_times: Vec<_> = locks_to_write
+                .iter()
+                .map(|(obj_ref, _)| (*obj_ref, now_ms))
+                .collect();
             self.transaction_lock
                 .batch()
                 .insert_batch(&self.transaction_lock, locks_to_write)?
+                .insert_batch(&self.lock_times, lock_times)?
                 .write()?;
         }
 
@@ -315,10 +376,12 @@ impl LockServiceImpl {
             }
         }
 
-        let write_batch = write_batch.insert_batch(
-            &self.transaction_lock,
-            objects.iter().map(|obj_ref| (obj_ref, None)),
-        )?;
+        let write_batch = write_batch
+            .insert_batch(
+                &self.transaction_lock,
+                objects.iter().map(|obj_ref| (obj_ref, None)),
+            )?
+            .delete_batch(&self.lock_times, objects)?;
 
         Ok(write_batch)
     }
@@ -334,6 +397,7 @@ impl LockServiceImpl {
     fn delete_locks(&self, objects: &[ObjectRef]) -> SuiResult {
         debug!(?objects, "delete_locks");
         self.transaction_lock.multi_remove(objects)?;
+        self.lock_times.multi_remove(objects)?;
         Ok(())
     }
 
@@ -401,6 +465,11 @@ impl LockServiceImpl {
                         warn!("Could not respond to sender, sender dropped!");
                     }
                 }
+                LockServiceQueries::ListLocked { objects, resp } => {
+                    if let Err(_e) = resp.send(self.locked_objects(objects.as_deref())) {
+                        warn!("Could not respond to sender, sender dropped!");
+                    }
+                }
             }
         }
         info!("LockService queries loop stopped, the sender on other end hung up/dropped");
@@ -622,6 +691,26 @@ impl LockService {
             .await
             .expect("Response from lockservice was cancelled, should not happen!")
     }
+
+    /// Returns the locks of `objects`, or of all objects when None, that are set to a
+    /// transaction. Listing all of them scans every lock.
+    pub async fn locked_objects(
+        &self,
+        objects: Option<Vec<ObjectRef>>,
+    ) -> SuiResult<Vec<LockedObject>> {
+        let (os_sender, os_receiver) = oneshot::channel::<SuiResult<Vec<LockedObject>>>();
+        self.inner
+            .query_sender()
+            .send(LockServiceQueries::ListLocked {
+                objects,
+                resp: os_sender,
+            })
+            .await
+            .expect("Could not send message to inner LockService");
+        os_receiver
+            .await
+            .expect("Response from lockservice was cancelled, should not happen!")
+    }
 }
 
 #[cfg(test)]
@@ -738,6 +827,34 @@ mod tests {
             .unwrap();
     }
 
+    #[test]
+    fn test_lockdb_locked_objects() {
+        let ls = init_lockservice_db();
+
+        let ref1: ObjectRef = (ObjectID::random(), 1.into(), ObjectDigest::random());
+        let ref2: ObjectRef = (ObjectID::random(), 1.into(), ObjectDigest::random());
+        let tx1 = TransactionDigest::random();
+
+        // Initialized locks aren't set to a transaction
+        ls.initialize_locks(&[ref1, ref2], false /* is_force_reset */)
+            .unwrap();
+        assert_eq!(ls.locked_objects(None), Ok(vec![]));
+
+        ls.acquire_locks(&[ref1], tx1).unwrap();
+        let locked = ls.locked_objects(None).unwrap();
+        assert_eq!(locked.len(), 1);
+        assert_eq!((locked[0].object_ref, locked[0].tx_digest), (ref1, tx1));
+        assert!(locked[0].locked_at_ms.is_some());
+        assert_eq!(ls.locked_objects(Some(&[ref1, ref2])), Ok(locked));
+        assert_eq!(ls.locked_objects(Some(&[ref2])), Ok(vec![]));
+
+        // Resetting a lock forgets when it was taken
+        ls.initialize_locks(&[ref1], true /* is_force_reset */)
+            .unwrap();
+        assert_eq!(ls.locked_objects(None), Ok(vec![]));
+        assert_eq!(ls.lock_times.get(&ref1).unwrap(), None);
+    }
+
     #[tokio::test]
     async fn test_lockservice_conc_acquire_init() {
         telemetry_subscribers::init_for_testing();
@@ -8,16 +8,22 @@ use sui_types::{
     base_types::TransactionDigest,
     error::{SuiError, SuiResult},
     messages::{CertifiedTransaction, SignedTransactionEffects},
+    messages_checkpoint::{CheckpointDigest, CheckpointSequenceNumber},
 };
 
 use typed_store::rocks::DBMap;
 use typed_store::{reopen, traits::Map};
 
+const SYNCED_CHECKPOINT: u8 = 0;
+
 /// NodeSyncStore store is used by nodes to store downloaded objects (certs, etc) that have
 /// not yet been applied to the node's SuiDataStore.
 pub struct NodeSyncStore {
     /// Certificates/Effects that have been fetched from remote validators, but not sequenced.
     certs_and_fx: DBMap<TransactionDigest, (CertifiedTransaction, SignedTransactionEffects)>,
+    /// The latest checkpoint whose transactions, and those of every checkpoint before it, were
+    /// all verified and executed, with its digest.
+    synced_checkpoint: DBMap<u8, (CheckpointSequenceNumber, CheckpointDigest)>,
 }
 
 impl NodeSyncStore {
@@ -27,14 +33,22 @@ impl NodeSyncStore {
         let db = {
             let path = &path;
             let db_options = Some(options.clone());
-            let opt_cfs: &[(&str, &rocksdb::Options)] = &[("certs_and_fx", &options)];
+            let opt_cfs: &[(&str, &rocksdb::Options)] =
+                &[("certs_and_fx", &options), ("synced_checkpoint", &options)];
             typed_store::rocks::open_cf_opts(path, db_options, opt_cfs)
         }
         .map_err(SuiError::StorageError)?;
 
-        let certs_and_fx = reopen!(&db, "certs_and_fx";<TransactionDigest, (CertifiedTransaction, SignedTransactionEffects)>);
+        let (certs_and_fx, synced_checkpoint) = reopen!(
+            &db,
+            "certs_and_fx";<TransactionDigest, (CertifiedTransaction, SignedTransactionEffects)>,
+            "synced_checkpoint";<u8, (CheckpointSequenceNumber, CheckpointDigest)>
+        );
 
-        Ok(Self { certs_and_fx })
+        Ok(Self {
+            certs_and_fx,
+            synced_checkpoint,
+        })
     }
 
     pub fn has_cert_and_effects(&self, tx: &TransactionDigest) -> SuiResult<bool> {
@@ -59,4 +73,25 @@ impl NodeSyncStore {
     pub fn delete_cert_and_effects(&self, tx: &TransactionDigest) -> SuiResult {
         Ok(self.certs_and_fx.remove(tx)?)
     }
+
+    /// Every certificate and its effects fetched but not yet executed.
+    pub fn all_cert_and_effects(&self) -> Vec<(CertifiedTransaction, SignedTransactionEffects)> {
+        self.certs_and_fx.values().collect()
+    }
+
+    pub fn synced_checkpoint(
+        &self,
+    ) -> SuiResult<Option<(CheckpointSequenceNumber, CheckpointDigest)>> {
+        Ok(self.synced_checkpoint.get(&SYNCED_CHECKPOINT)?)
+    }
+
+    pub fn record_synced_checkpoint(
+        &self,
+        seq: CheckpointSequenceNumber,
+        digest: CheckpointDigest,
+    ) -> SuiResult {
+        Ok(self
+            .synced_checkpoint
+            .insert(&SYNCED_CHECKPOINT, &(seq, digest))?)
+    }
 }
@@ -0,0 +1,103 @@
+// Copyright (c) 2022, Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::path::Path;
+use sui_types::{
+    batch::TxSequenceNumber,
+    error::{SuiError, SuiResult},
+};
+use typed_store::rocks::DBMap;
+use typed_store::{reopen, traits::Map};
+
+use crate::default_db_options;
+
+const KEY: u8 = 0;
+
+/// PostProcessingStore persists whether post processing (indexing and event emission) was
+/// paused by an operator, and the first transaction it has yet to process after a pause, so
+/// that a pause survives restarts and no transaction executed meanwhile is missed.
+pub struct PostProcessingStore {
+    paused: DBMap<u8, bool>,
+    resume_from: DBMap<u8, TxSequenceNumber>,
+}
+
+impl PostProcessingStore {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, SuiError> {
+        let (options, _) = default_db_options(None, None);
+
+        let db = {
+            let path = &path;
+            let db_options = Some(options.clone());
+            let opt_cfs: &[(&str, &rocksdb::Options)] =
+                &[("paused", &options), ("resume_from", &options)];
+            typed_store::rocks::open_cf_opts(path, db_options, opt_cfs)
+        }
+        .map_err(SuiError::StorageError)?;
+
+        let (paused, resume_from) = reopen!(&db,
+            "paused";<u8, bool>,
+            "resume_from";<u8, TxSequenceNumber>
+        );
+
+        Ok(Self {
+            paused,
+            resume_from,
+        })
+    }
+
+    pub fn is_paused(&self) -> SuiResult<bool> {
+        Ok(self
+            .paused
+            .get(&KEY)
+            .map_err(SuiError::StorageError)?
+            .unwrap_or(false))
+    }
+
+    pub fn set_paused(&self, paused: bool) -> SuiResult {
+        self.paused
+            .insert(&KEY, &paused)
+            .map_err(SuiError::StorageError)
+    }
+
+    /// The first transaction post processing has yet to catch up with since it was paused, or
+    /// `None` when it didn't fall behind.
+    pub fn resume_from(&self) -> SuiResult<Option<TxSequenceNumber>> {
+        self.resume_from.get(&KEY).map_err(SuiError::StorageError)
+    }
+
+    pub fn set_resume_from(&self, seq: Option<TxSequenceNumber>) -> SuiResult {
+        match seq {
+            Some(seq) => self.resume_from.insert(&KEY, &seq),
+            None => self.resume_from.remove(&KEY),
+        }
+        .map_err(SuiError::StorageError)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::post_processing_store::PostProcessingStore;
+
+    #[test]
+    fn test_post_processing_store() {
+        let working_dir = tempfile::tempdir().unwrap();
+
+        let store = PostProcessingStore::open(&working_dir).expect("cannot open db");
+        assert!(!store.is_paused().unwrap());
+        assert_eq!(store.resume_from().unwrap(), None);
+
+        store.set_paused(true).expect("write error");
+        store.set_resume_from(Some(42)).expect("write error");
+        drop(store);
+
+        // Both survive a restart.
+        let store = PostProcessingStore::open(&working_dir).expect("cannot open db");
+        assert!(store.is_paused().unwrap());
+        assert_eq!(store.resume_from().unwrap(), Some(42));
+
+        store.set_paused(false).expect("write error");
+        store.set_resume_from(None).expect("write error");
+        assert!(!store.is_paused().unwrap());
+        assert_eq!(store.resume_from().unwrap(), None);
+    }
+}
@@ -0,0 +1,162 @@
+// Copyright (c) 2022, Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::path::Path;
+use sui_types::{
+    base_types::TransactionDigest,
+    batch::TxSequenceNumber,
+    error::{SuiError, SuiResult},
+    messages::TransactionEffects,
+};
+use typed_store::rocks::DBMap;
+use typed_store::{reopen, traits::Map};
+
+use crate::default_db_options;
+
+use tracing::debug;
+
+/// The effects stored for a transaction, then those executing it again produced.
+pub type DivergentEffects = (TransactionEffects, TransactionEffects);
+
+const CURSOR: u8 = 0;
+
+/// QuarantineStore keeps the transactions that had other effects when executed again than
+/// those stored when they were committed, with both, so that the divergence can be analyzed.
+/// Any is a sign of nondeterministic execution or of a corrupted store.
+pub struct QuarantineStore {
+    quarantined: DBMap<TransactionDigest, DivergentEffects>,
+    /// The sequence number of the next transaction the re-execution verifier samples from.
+    cursor: DBMap<u8, TxSequenceNumber>,
+}
+
+impl QuarantineStore {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, SuiError> {
+        let (options, _) = default_db_options(None, None);
+
+        let db = {
+            let path = &path;
+            let db_options = Some(options.clone());
+            let opt_cfs: &[(&str, &rocksdb::Options)] =
+                &[("quarantined", &options), ("cursor", &options)];
+            typed_store::rocks::open_cf_opts(path, db_options, opt_cfs)
+        }
+        .map_err(SuiError::StorageError)?;
+
+        let (quarantined, cursor) = reopen!(
+            &db,
+            "quarantined";<TransactionDigest, DivergentEffects>,
+            "cursor";<u8, TxSequenceNumber>
+        );
+
+        Ok(Self {
+            quarantined,
+            cursor,
+        })
+    }
+
+    pub fn insert(
+        &self,
+        digest: &TransactionDigest,
+        stored: TransactionEffects,
+        reexecuted: TransactionEffects,
+    ) -> SuiResult {
+        debug!(?digest, "quarantine transaction");
+        self.quarantined
+            .insert(digest, &(stored, reexecuted))
+            .map_err(SuiError::StorageError)
+    }
+
+    pub fn remove(&self, digest: &TransactionDigest) -> SuiResult {
+        self.quarantined
+            .remove(digest)
+            .map_err(SuiError::StorageError)
+    }
+
+    pub fn get(&self, digest: &TransactionDigest) -> SuiResult<Option<DivergentEffects>> {
+        self.quarantined.get(digest).map_err(SuiError::StorageError)
+    }
+
+    /// All quarantined transactions, ordered by digest.
+    pub fn iter(&self) -> impl Iterator<Item = (TransactionDigest, DivergentEffects)> + '_ {
+        self.quarantined.iter()
+    }
+
+    pub fn count(&self) -> usize {
+        self.quarantined.iter().count()
+    }
+
+    /// Where the re-execution verifier left off, None if it never ran.
+    pub fn cursor(&self) -> SuiResult<Option<TxSequenceNumber>> {
+        self.cursor.get(&CURSOR).map_err(SuiError::StorageError)
+    }
+
+    pub fn set_cursor(&self, cursor: TxSequenceNumber) -> SuiResult {
+        self.cursor
+            .insert(&CURSOR, &cursor)
+            .map_err(SuiError::StorageError)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::quarantine_store::QuarantineStore;
+    use sui_types::base_types::{ObjectDigest, ObjectID, SequenceNumber, TransactionDigest};
+    use sui_types::gas::GasCostSummary;
+    use sui_types::messages::{ExecutionStatus, TransactionEffects};
+    use sui_types::object::Owner;
+
+    fn effects(digest: TransactionDigest, computation_cost: u64) -> TransactionEffects {
+        TransactionEffects {
+            status: ExecutionStatus::Success,
+            gas_used: GasCostSummary {
+                computation_cost,
+                storage_cost: 0,
+                storage_rebate: 0,
+            },
+            shared_objects: vec![],
+            transaction_digest: digest,
+            created: vec![],
+            mutated: vec![],
+            unwrapped: vec![],
+            deleted: vec![],
+            wrapped: vec![],
+            gas_object: (
+                (
+                    ObjectID::random(),
+                    SequenceNumber::new(),
+                    ObjectDigest::random(),
+                ),
+                Owner::Immutable,
+            ),
+            events: vec![],
+            dependencies: vec![],
+        }
+    }
+
+    #[test]
+    fn test_quarantine_store() {
+        let working_dir = tempfile::tempdir().unwrap();
+
+        let store = QuarantineStore::open(&working_dir).expect("cannot open db");
+        assert_eq!(store.count(), 0);
+
+        let digest = TransactionDigest::random();
+        let (stored, reexecuted) = (effects(digest, 10), effects(digest, 11));
+        store
+            .insert(&digest, stored.clone(), reexecuted.clone())
+            .expect("write error");
+        assert_eq!(store.count(), 1);
+        assert_eq!(store.get(&digest).unwrap(), Some((stored, reexecuted)));
+        assert_eq!(store.iter().next().unwrap().0, digest);
+        assert_eq!(store.get(&TransactionDigest::random()).unwrap(), None);
+
+        store.remove(&digest).expect("write error");
+        assert_eq!(store.count(), 0);
+
+        assert_eq!(store.cursor().unwrap(), None);
+        store.set_cursor(42).expect("write error");
+        drop(store);
+        let store = QuarantineStore::open(&working_dir).expect("cannot open db");
+        assert_eq!(store.cursor().unwrap(), Some(42));
+    }
+}
@@ -19,6 +19,7 @@ use schemars::JsonSchema;
 
 pub const COIN_MODULE_NAME: &IdentStr = ident_str!("coin");
 pub const COIN_STRUCT_NAME: &IdentStr = ident_str!("Coin");
+pub const TREASURY_CAP_STRUCT_NAME: &IdentStr = ident_str!("TreasuryCap");
 pub const COIN_JOIN_FUNC_NAME: &IdentStr = ident_str!("join");
 pub const COIN_SPLIT_VEC_FUNC_NAME: &IdentStr = ident_str!("split_vec");
 
@@ -46,6 +47,21 @@ impl Coin {
         }
     }
 
+    /// The coin type of a Coin of type `type_`, e.g. `0x2::sui::SUI` for
+    /// `0x2::coin::Coin<0x2::sui::SUI>`, or None if `type_` isn't a Coin.
+    pub fn coin_type(type_: &StructTag) -> Option<&StructTag> {
+        match &type_.type_params[..] {
+            [TypeTag::Struct(coin_type)]
+                if type_.address == SUI_FRAMEWORK_ADDRESS
+                    && type_.module.as_ident_str() == COIN_MODULE_NAME
+                    && type_.name.as_ident_str() == COIN_STRUCT_NAME =>
+            {
+                Some(coin_type)
+            }
+            _ => None,
+        }
+    }
+
     pub fn id(&self) -> &ObjectID {
         self.id.object_id()
     }
@@ -85,3 +101,29 @@ pub struct TreasuryCap {
     pub id: VersionedID,
     pub total_supply: Supply,
 }
+
+impl TreasuryCap {
+    pub fn type_(type_param: StructTag) -> StructTag {
+        StructTag {
+            address: SUI_FRAMEWORK_ADDRESS,
+            name: TREASURY_CAP_STRUCT_NAME.to_owned(),
+            module: COIN_MODULE_NAME.to_owned(),
+            type_params: vec![TypeTag::Struct(type_param)],
+        }
+    }
+
+    /// The type of the coins minted with a TreasuryCap of type `type_`, or None if `type_`
+    /// isn't a TreasuryCap.
+    pub fn coin_type(type_: &StructTag) -> Option<&StructTag> {
+        match &type_.type_params[..] {
+            [TypeTag::Struct(coin_type)]
+                if type_.address == SUI_FRAMEWORK_ADDRESS
+                    && type_.module.as_ident_str() == COIN_MODULE_NAME
+                    && type_.name.as_ident_str() == TREASURY_CAP_STRUCT_NAME =>
+            {
+                Some(coin_type)
+            }
+            _ => None,
+        }
+    }
+}
@@ -2,7 +2,9 @@
 // Copyright (c) 2022, Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{base_types::*, committee::EpochId, messages::ExecutionFailureStatus};
+use crate::{
+    base_types::*, batch::TxSequenceNumber, committee::EpochId, messages::ExecutionFailureStatus,
+};
 use move_binary_format::errors::{Location, PartialVMError, VMError};
 use move_core_types::vm_status::{AbortLocation, StatusCode};
 use narwhal_executor::{ExecutionStateError, SubscriberError};
@@ -64,6 +66,8 @@ pub enum SuiError {
     UnsupportedSharedObjectError,
     #[error("Object used as shared is not shared.")]
     NotSharedObjectError,
+    #[error("Object {object_id} is not a shared object")]
+    NotSharedObject { object_id: ObjectID },
     #[error("An object that's owned by another object cannot be deleted or wrapped. It must be transferred to an account address first before deletion")]
     DeleteObjectOwnedObject,
     #[error("The shared locks for this transaction have not yet been set.")]
@@ -96,6 +100,17 @@ pub enum SuiError {
         expected_sequence: SequenceNumber,
         given_sequence: SequenceNumber,
     },
+    #[error(
+        "Version conflict for object {object_id}: version {} was given{}",
+        .given_version.value(),
+        current_version_hint(.current_version)
+    )]
+    ObjectVersionConflict {
+        object_id: ObjectID,
+        given_version: SequenceNumber,
+        /// Only set by gateways configured to report it.
+        current_version: Option<SequenceNumber>,
+    },
     #[error("Conflicting transaction already received: {pending_transaction:?}")]
     ConflictingTransaction {
         pending_transaction: TransactionDigest,
@@ -193,6 +208,14 @@ pub enum SuiError {
     SubscriptionItemsDroppedError(u64),
     #[error("Subscription service closed.")]
     SubscriptionServiceClosed,
+    #[error(
+        "Batch history is only retained from sequence number {retained_from}, \
+        {requested} was requested."
+    )]
+    BatchHistoryUnavailable {
+        requested: TxSequenceNumber,
+        retained_from: TxSequenceNumber,
+    },
     #[error("Checkpointing error: {}", error)]
     CheckpointingError { error: String },
 
@@ -242,6 +265,9 @@ pub enum SuiError {
         current_module: String,
     },
 
+    #[error("Transaction data of {size} bytes exceeds the limit of {limit} bytes")]
+    TransactionTooLarge { size: usize, limit: usize },
+
     // Gas related errors
     #[error("Gas budget set higher than max: {error:?}.")]
     GasBudgetTooHigh { error: String },
@@ -263,6 +289,20 @@ pub enum SuiError {
     ObjectNotFound { object_id: ObjectID },
     #[error("Object deleted at reference {:?}.", object_ref)]
     ObjectDeleted { object_ref: ObjectRef },
+    #[error("Version {version:?} of object {object_id:?} is no longer stored.")]
+    ObjectVersionUnavailable {
+        object_id: ObjectID,
+        version: SequenceNumber,
+    },
+    #[error(
+        "Object {object_id:?} is {size} bytes, over the {max_size} bytes served as JSON. Fetch \
+         it as BCS with sui_getRawObject instead."
+    )]
+    ObjectTooLargeForJson {
+        object_id: ObjectID,
+        size: usize,
+        max_size: usize,
+    },
     #[error("Object ID did not have the expected type")]
     BadObjectType { error: String },
     #[error("Move Execution failed")]
@@ -279,6 +319,38 @@ pub enum SuiError {
         "We have received cryptographic level of evidence that authority {authority:?} is faulty in a Byzantine manner."
     )]
     ByzantineAuthoritySuspicion { authority: AuthorityName },
+    #[error("Peers announced conflicting effects for transaction {digest:?}, it is not executed")]
+    ConflictingTransactionEffects { digest: TransactionDigest },
+    #[error("Effects of transaction {digest:?} don't match the certified checkpoints: {error}")]
+    CheckpointVerificationFailed {
+        digest: TransactionDigest,
+        error: String,
+    },
+    #[error("Checkpoint {sequence} has digest {found}, not the trusted {expected}")]
+    TrustedCheckpointMismatch {
+        sequence: u64,
+        expected: String,
+        found: String,
+    },
+    #[error(
+        "None of the {attempts} peers tried served checkpoint {sequence} with a certificate \
+         that verifies, last: {error}"
+    )]
+    NoValidCheckpointCertificate {
+        sequence: u64,
+        attempts: usize,
+        error: String,
+    },
+    #[error("Node sync halted for good: {error}")]
+    NodeSyncHalted { error: String },
+    #[error(
+        "Checkpoint {checkpoint} is not yet available on this node, which synced up to \
+         {synced:?}. Retry later or on another node"
+    )]
+    CheckpointNotYetAvailable {
+        checkpoint: u64,
+        synced: Option<u64>,
+    },
     #[error(
         "Sync from authority failed. From {xsource:?} to {destination:?}, digest {tx_digest:?}: {error:?}",
     )]
@@ -292,6 +364,8 @@ pub enum SuiError {
     StorageError(#[from] TypedStoreError),
     #[error("Non-RocksDB Storage error: {0}")]
     GenericStorageError(String),
+    #[error("Authority stopped accepting work after a failed storage write: {error}")]
+    AuthorityUnhealthy { error: String },
     #[error("Batch error: cannot send transaction to batch.")]
     BatchErrorSender,
     #[error("Authority Error: {error:?}")]
@@ -376,6 +450,13 @@ pub enum SuiError {
 
 pub type SuiResult<T = ()> = Result<T, SuiError>;
 
+fn current_version_hint(current_version: &Option<SequenceNumber>) -> String {
+    match current_version {
+        Some(version) => format!(", current is {}", version.value()),
+        None => String::new(),
+    }
+}
+
 // TODO these are both horribly wrong, categorization needs to be considered
 impl std::convert::From<PartialVMError> for SuiError {
     fn from(error: PartialVMError) -> Self {
@@ -417,6 +498,16 @@ impl From<ExecutionErrorKind> for SuiError {
     }
 }
 
+impl SuiError {
+    /// True for errors raised by the underlying storage rather than by the request itself.
+    pub fn is_storage_error(&self) -> bool {
+        matches!(
+            self,
+            SuiError::StorageError(..) | SuiError::GenericStorageError(..)
+        )
+    }
+}
+
 impl std::convert::From<&str> for SuiError {
     fn from(error: &str) -> Self {
         SuiError::GenericAuthorityError {
@@ -432,6 +523,7 @@ impl ExecutionStateError for SuiError {
             Self::ObjectFetchFailed { .. }
                 | Self::ByzantineAuthoritySuspicion { .. }
                 | Self::StorageError(..)
+                | Self::AuthorityUnhealthy { .. }
                 | Self::GenericAuthorityError { .. }
         )
     }
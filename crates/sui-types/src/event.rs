@@ -29,7 +29,9 @@ use crate::{
 /// A universal Sui event type encapsulating different types of events
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct EventEnvelope {
-    /// UTC timestamp in milliseconds since epoch (1/1/1970)
+    /// UTC timestamp in milliseconds since epoch (1/1/1970), taken from the clock of the node
+    /// that processed the transaction. It is nondecreasing with `seq_num` on a node, but nodes
+    /// with different clocks give the same event different timestamps.
     pub timestamp: u64,
     /// Transaction digest of associated transaction, if any
     pub tx_digest: Option<TransactionDigest>,
@@ -1,8 +1,12 @@
 // Copyright (c) 2022, Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use std::str::FromStr;
+
+use anyhow::anyhow;
 use move_core_types::identifier::Identifier;
-use move_core_types::language_storage::StructTag;
+use move_core_types::language_storage::{ModuleId, StructTag};
+use move_core_types::parser::parse_struct_tag;
 use serde_json::Value;
 
 use crate::base_types::SuiAddress;
@@ -19,7 +23,7 @@ mod event_filter_tests;
 pub enum EventFilter {
     Package(ObjectID),
     Module(Identifier),
-    MoveEventType(StructTag),
+    MoveEventType(MoveEventTypeFilter),
     EventType(EventType),
     MoveEventField { path: String, value: Value },
     SenderAddress(SuiAddress),
@@ -32,8 +36,8 @@ pub enum EventFilter {
 impl EventFilter {
     fn try_matches(&self, item: &EventEnvelope) -> Result<bool, anyhow::Error> {
         Ok(match self {
-            EventFilter::MoveEventType(event_type) => match &item.event {
-                Event::MoveEvent { type_, .. } => type_ == event_type,
+            EventFilter::MoveEventType(filter) => match &item.event {
+                Event::MoveEvent { type_, .. } => filter.matches(type_),
                 _ => false,
             },
             EventFilter::MoveEventField { path, value } => match &item.move_struct_json_value {
@@ -68,6 +72,46 @@ impl EventFilter {
     }
 }
 
+/// Types of Move events, a full type or all those declared by a module or a package.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MoveEventTypeFilter {
+    /// This type, with these type arguments if it is generic.
+    Type(StructTag),
+    Module(ModuleId),
+    Package(ObjectID),
+}
+
+impl MoveEventTypeFilter {
+    pub fn matches(&self, type_: &StructTag) -> bool {
+        match self {
+            MoveEventTypeFilter::Type(filter_type) => type_ == filter_type,
+            MoveEventTypeFilter::Module(module) => {
+                type_.address == *module.address() && type_.module.as_ident_str() == module.name()
+            }
+            MoveEventTypeFilter::Package(package) => ObjectID::from(type_.address) == *package,
+        }
+    }
+}
+
+/// Parses a full type like `0x2::devnet_nft::MintNFTEvent`, a module like `0x2::devnet_nft` or
+/// a package like `0x2`.
+impl FromStr for MoveEventTypeFilter {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split("::").collect::<Vec<_>>()[..] {
+            [package] => Ok(Self::Package(ObjectID::from_hex_literal(package)?)),
+            [package, module] => Ok(Self::Module(ModuleId::new(
+                ObjectID::from_hex_literal(package)?.into(),
+                Identifier::new(module)?,
+            ))),
+            _ => parse_struct_tag(s)
+                .map(Self::Type)
+                .map_err(|e| anyhow!("Invalid Move event type {s}: {e}")),
+        }
+    }
+}
+
 impl Filter<EventEnvelope> for EventFilter {
     fn matches(&self, item: &EventEnvelope) -> bool {
         self.try_matches(item).unwrap_or_default()
@@ -117,6 +117,34 @@ pub static MAX_GAS_BUDGET: Lazy<u64> =
 pub static MIN_GAS_BUDGET: Lazy<u64> =
     Lazy::new(|| to_external(INIT_SUI_COST_TABLE.min_transaction_cost.0).get());
 
+/// The costs of the cost table in internal gas units, of which there are
+/// `gas_unit_scaling_factor` in a gas unit. Gas budgets and the gas used by a transaction are in
+/// gas units.
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct GasCostParameters {
+    pub gas_unit_scaling_factor: u64,
+    pub min_transaction_cost: u64,
+    pub package_publish_per_byte_cost: u64,
+    pub object_read_per_byte_cost: u64,
+    pub object_mutation_per_byte_cost: u64,
+    pub consensus_cost: u64,
+    pub storage_per_byte_cost: u64,
+}
+
+pub fn gas_cost_parameters() -> GasCostParameters {
+    let table = &*INIT_SUI_COST_TABLE;
+    GasCostParameters {
+        gas_unit_scaling_factor: INITIAL_COST_SCHEDULE.gas_constants.gas_unit_scaling_factor,
+        min_transaction_cost: table.min_transaction_cost.0.get(),
+        package_publish_per_byte_cost: table.package_publish_per_byte_cost.0.get(),
+        object_read_per_byte_cost: table.object_read_per_byte_cost.0.get(),
+        object_mutation_per_byte_cost: table.object_mutation_per_byte_cost.0.get(),
+        consensus_cost: table.consensus_cost.0.get(),
+        storage_per_byte_cost: table.storage_per_byte_cost.0.get(),
+    }
+}
+
 fn to_external(internal_units: InternalGasUnits<GasCarrier>) -> GasUnits<GasCarrier> {
     let consts = &INITIAL_COST_SCHEDULE.gas_constants;
     consts.to_external_units(internal_units)
@@ -27,6 +27,7 @@ pub mod messages;
 pub mod messages_checkpoint;
 pub mod move_package;
 pub mod object;
+pub mod protocol_config;
 pub mod signature_seed;
 pub mod storage;
 pub mod sui_serde;
@@ -788,6 +788,10 @@ pub struct AccountInfoRequest {
 /// database starting at the batch that includes `start`,
 /// and then listens to new transactions until a batch equal or
 /// is over the batch end marker.
+///
+/// A subscriber that was disconnected resumes by requesting again from the sequence number
+/// after the last transaction it received. Requesting a `start` older than the batch history
+/// the authority still retains fails with `SuiError::BatchHistoryUnavailable`.
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Serialize, Deserialize)]
 pub struct BatchInfoRequest {
     // The sequence number at which to start the sequence to return, or None for the latest.
@@ -799,6 +803,21 @@ pub struct BatchInfoRequest {
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub struct BatchInfoResponseItem(pub UpdateItem);
 
+/// A request for the effects of every executed transaction in sequence order, from `start`
+/// and then as transactions are executed. A subscriber that was disconnected resumes by
+/// requesting again from the sequence number after the last item it received.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Serialize, Deserialize)]
+pub struct EffectsFirehoseRequest {
+    pub start: TxSequenceNumber,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct EffectsFirehoseItem {
+    pub sequence: TxSequenceNumber,
+    pub digest: TransactionDigest,
+    pub effects: TransactionEffects,
+}
+
 impl From<SuiAddress> for AccountInfoRequest {
     fn from(account: SuiAddress) -> Self {
         AccountInfoRequest { account }
@@ -0,0 +1,42 @@
+// Copyright (c) 2022, Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::gas::{self, GasCostParameters, MAX_GAS_BUDGET, MIN_GAS_BUDGET};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Version of the protocol this build implements. Incremented whenever a change to the
+/// parameters in [`ProtocolConfig`], or to execution, makes nodes of different versions disagree.
+pub const PROTOCOL_VERSION: u64 = 2;
+
+/// Largest BCS-serialized transaction data validators accept, see
+/// [`ProtocolConfig::max_tx_size_bytes`].
+pub const MAX_TX_SIZE_BYTES: u64 = 128 * 1024;
+
+/// The parameters transactions are checked against, for clients to validate transactions
+/// before submitting them.
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ProtocolConfig {
+    pub protocol_version: u64,
+    /// Bounds of the gas budget of a transaction, in gas units.
+    pub min_gas_budget: u64,
+    pub max_gas_budget: u64,
+    /// Largest BCS-serialized transaction data accepted, in bytes. Larger transactions are
+    /// rejected before their signature is checked.
+    pub max_tx_size_bytes: u64,
+    pub gas_costs: GasCostParameters,
+}
+
+impl ProtocolConfig {
+    /// The parameters of [`PROTOCOL_VERSION`].
+    pub fn current() -> Self {
+        Self {
+            protocol_version: PROTOCOL_VERSION,
+            min_gas_budget: *MIN_GAS_BUDGET,
+            max_gas_budget: *MAX_GAS_BUDGET,
+            max_tx_size_bytes: MAX_TX_SIZE_BYTES,
+            gas_costs: gas::gas_cost_parameters(),
+        }
+    }
+}
@@ -5,12 +5,13 @@ use std::collections::BTreeMap;
 
 use move_core_types::ident_str;
 use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{ModuleId, StructTag};
 use serde_json::json;
 
 use crate::base_types::{SequenceNumber, SuiAddress, TransactionDigest};
 use crate::event::{Event, EventEnvelope};
 use crate::event::{EventType, TransferType};
-use crate::event_filter::{EventFilter, Filter};
+use crate::event_filter::{EventFilter, Filter, MoveEventTypeFilter};
 use crate::gas_coin::GasCoin;
 use crate::object::Owner;
 use crate::{ObjectID, MOVE_STDLIB_ADDRESS, SUI_FRAMEWORK_ADDRESS};
@@ -35,7 +36,14 @@ fn test_move_event_filter() {
     };
 
     let filters = vec![
-        EventFilter::MoveEventType(GasCoin::type_()),
+        EventFilter::MoveEventType(MoveEventTypeFilter::Type(GasCoin::type_())),
+        EventFilter::MoveEventType(MoveEventTypeFilter::Module(ModuleId::new(
+            SUI_FRAMEWORK_ADDRESS,
+            GasCoin::type_().module,
+        ))),
+        EventFilter::MoveEventType(MoveEventTypeFilter::Package(ObjectID::from(
+            SUI_FRAMEWORK_ADDRESS,
+        ))),
         EventFilter::EventType(EventType::MoveEvent),
         EventFilter::Module(Identifier::from(ident_str!("test_module"))),
         EventFilter::Package(ObjectID::from(SUI_FRAMEWORK_ADDRESS)),
@@ -66,6 +74,40 @@ fn test_move_event_filter() {
     assert!(EventFilter::MatchAny(filters.clone()).matches(&envelope));
 }
 
+#[test]
+fn test_parse_move_event_type_filter() {
+    let type_ = StructTag {
+        address: SUI_FRAMEWORK_ADDRESS,
+        module: Identifier::from(ident_str!("devnet_nft")),
+        name: Identifier::from(ident_str!("MintNFTEvent")),
+        type_params: vec![],
+    };
+    let module = ModuleId::new(SUI_FRAMEWORK_ADDRESS, type_.module.clone());
+    let filters = [
+        (
+            "0x2::devnet_nft::MintNFTEvent",
+            MoveEventTypeFilter::Type(type_.clone()),
+        ),
+        ("0x2::devnet_nft", MoveEventTypeFilter::Module(module)),
+        (
+            "0x2",
+            MoveEventTypeFilter::Package(ObjectID::from(SUI_FRAMEWORK_ADDRESS)),
+        ),
+    ];
+    for (filter, expected) in filters {
+        let filter: MoveEventTypeFilter = filter.parse().unwrap();
+        assert_eq!(filter, expected);
+        assert!(filter.matches(&type_));
+    }
+    assert!("0x2::not a module".parse::<MoveEventTypeFilter>().is_err());
+
+    // Another module of the package, or a module of the same name in another package, is not it.
+    let other_module: MoveEventTypeFilter = "0x2::coin".parse().unwrap();
+    assert!(!other_module.matches(&type_));
+    let other_package: MoveEventTypeFilter = "0x1::devnet_nft".parse().unwrap();
+    assert!(!other_package.matches(&type_));
+}
+
 #[test]
 fn test_transfer_filter() {
     let object_id = ObjectID::random();
@@ -55,13 +55,15 @@ async fn main() -> anyhow::Result<()> {
 
     let prom_binding = PROM_PORT_ADDR.parse().unwrap();
     info!("Starting Prometheus HTTP endpoint at {}", prom_binding);
-    let prometheus_registry = sui_node::metrics::start_prometheus_server(prom_binding);
+    let metrics_server =
+        sui_node::metrics::start_prometheus_server(prom_binding, false, None, None);
+    let prometheus_registry = metrics_server.registry().clone();
 
     let metrics = GatewayMetrics::new(&prometheus_registry);
     let client = create_client(&config_path, metrics)?;
 
     let address = SocketAddr::new(IpAddr::V4(options.host), options.port);
-    let mut server = JsonRpcServerBuilder::new(false, &prometheus_registry)?;
+    let mut server = JsonRpcServerBuilder::new(false, &prometheus_registry, None)?;
     server.register_module(RpcGatewayImpl::new(client.clone()))?;
     server.register_module(GatewayReadApiImpl::new(client.clone()))?;
     server.register_module(TransactionBuilderImpl::new(client.clone()))?;
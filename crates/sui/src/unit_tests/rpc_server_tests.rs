@@ -13,6 +13,7 @@ use sui_json_rpc_api::{
     QuorumDriverApiClient, RpcReadApiClient, RpcTransactionBuilderClient, TransactionBytes,
     WalletSyncApiClient,
 };
+use sui_types::protocol_config::ProtocolConfig;
 use sui_types::sui_serde::Base64;
 use sui_types::{
     base_types::{ObjectID, TransactionDigest},
@@ -28,18 +29,32 @@ async fn test_get_objects() -> Result<(), anyhow::Error> {
     let address = test_network.accounts.first().unwrap();
 
     http_client.sync_account_state(*address).await?;
-    let objects = http_client.get_objects_owned_by_address(*address).await?;
+    let objects = http_client
+        .get_objects_owned_by_address(*address, None)
+        .await?;
     assert_eq!(5, objects.len());
     Ok(())
 }
 
+#[tokio::test]
+async fn test_get_protocol_config() -> Result<(), anyhow::Error> {
+    let test_network = start_rpc_test_network(None).await?;
+    let config = test_network.http_client.get_protocol_config().await?;
+    assert_eq!(config.epoch, 0);
+    assert_eq!(config.config, ProtocolConfig::current());
+    assert!(config.config.min_gas_budget <= config.config.max_gas_budget);
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_public_transfer_object() -> Result<(), anyhow::Error> {
     let test_network = start_rpc_test_network(None).await?;
     let http_client = test_network.http_client;
     let address = test_network.accounts.first().unwrap();
     http_client.sync_account_state(*address).await?;
-    let objects = http_client.get_objects_owned_by_address(*address).await?;
+    let objects = http_client
+        .get_objects_owned_by_address(*address, None)
+        .await?;
 
     let tx_data: TransactionBytes = http_client
         .public_transfer_object(
@@ -75,7 +90,9 @@ async fn test_publish() -> Result<(), anyhow::Error> {
     let http_client = test_network.http_client;
     let address = test_network.accounts.first().unwrap();
     http_client.sync_account_state(*address).await?;
-    let objects = http_client.get_objects_owned_by_address(*address).await?;
+    let objects = http_client
+        .get_objects_owned_by_address(*address, None)
+        .await?;
     let gas = objects.first().unwrap();
 
     let compiled_modules = build_move_package_to_bytes(
@@ -113,7 +130,9 @@ async fn test_move_call() -> Result<(), anyhow::Error> {
     let http_client = test_network.http_client;
     let address = test_network.accounts.first().unwrap();
     http_client.sync_account_state(*address).await?;
-    let objects = http_client.get_objects_owned_by_address(*address).await?;
+    let objects = http_client
+        .get_objects_owned_by_address(*address, None)
+        .await?;
     let gas = objects.first().unwrap();
 
     let package_id = ObjectID::new(SUI_FRAMEWORK_ADDRESS.into_bytes());
@@ -161,10 +180,12 @@ async fn test_get_object_info() -> Result<(), anyhow::Error> {
     let http_client = test_network.http_client;
     let address = test_network.accounts.first().unwrap();
     http_client.sync_account_state(*address).await?;
-    let objects = http_client.get_objects_owned_by_address(*address).await?;
+    let objects = http_client
+        .get_objects_owned_by_address(*address, None)
+        .await?;
 
     for oref in objects {
-        let result: GetObjectDataResponse = http_client.get_object(oref.object_id).await?;
+        let result: GetObjectDataResponse = http_client.get_object(oref.object_id, None).await?;
         assert!(
             matches!(result, GetObjectDataResponse::Exists(object) if oref.object_id == object.id() && &object.owner.get_owner_address()? == address)
         );
@@ -180,7 +201,9 @@ async fn test_get_transaction() -> Result<(), anyhow::Error> {
 
     http_client.sync_account_state(*address).await?;
 
-    let objects = http_client.get_objects_owned_by_address(*address).await?;
+    let objects = http_client
+        .get_objects_owned_by_address(*address, None)
+        .await?;
     let gas_id = objects.last().unwrap().object_id;
 
     // Make some transactions
@@ -228,7 +251,8 @@ async fn test_get_transaction() -> Result<(), anyhow::Error> {
 
     // test get_transaction
     for (_, tx_digest) in tx {
-        let response: TransactionEffectsResponse = http_client.get_transaction(tx_digest).await?;
+        let response: TransactionEffectsResponse =
+            http_client.get_transaction(tx_digest, None).await?;
         assert!(tx_responses.iter().any(
             |effects| effects.effects.transaction_digest == response.effects.transaction_digest
         ))
@@ -490,6 +490,39 @@ async fn test_full_node_cold_sync() -> Result<(), anyhow::Error> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_full_node_restart_after_shutdown() -> Result<(), anyhow::Error> {
+    telemetry_subscribers::init_for_testing();
+
+    let (swarm, mut context, _) = setup_network_and_wallet().await?;
+
+    let config = swarm.config().generate_fullnode_config();
+    let node = SuiNode::start(&config).await?;
+
+    let (transfered_object, _, receiver, digest) = transfer_coin(&mut context).await?;
+    wait_for_tx(digest, node.state()).await;
+    node.shutdown().await;
+
+    // Fails if something still holds the stores of the first node.
+    let node = SuiNode::start(&config).await?;
+
+    let object = node
+        .state()
+        .get_object_read(&transfered_object)
+        .await?
+        .into_object()?;
+    assert_eq!(object.owner.get_owner_address().unwrap(), receiver);
+    let info = node
+        .state()
+        .handle_transaction_info_request(TransactionInfoRequest {
+            transaction_digest: digest,
+        })
+        .await?;
+    assert!(info.signed_effects.is_some());
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_full_node_sync_flood() -> Result<(), anyhow::Error> {
     telemetry_subscribers::init_for_testing();
@@ -606,7 +639,7 @@ async fn set_up_jsonrpc(port: u16, swarm: &Swarm) -> Result<(SuiNode, HttpClient
     let jsonrpc_addr: SocketAddr = jsonrpc_server_url.parse().unwrap();
 
     let mut config = swarm.config().generate_fullnode_config();
-    config.json_rpc_address = jsonrpc_addr;
+    config.json_rpc_address = jsonrpc_addr.into();
 
     let node = SuiNode::start(&config).await?;
 